@@ -0,0 +1,125 @@
+//! Marking the source line that a crash happens at, for `cargo fuzz annotate`.
+//!
+//! The marker is a comment on the line above, with the crash's hash and the
+//! artifact that reproduces it:
+//!
+//! ```text
+//! // FUZZ-CRASH: 1b6c2f0e9d7a4c31 reproduced by fuzz/artifacts/parse/crash-…
+//! ```
+//!
+//! so that `grep -r FUZZ-CRASH` lists the code with open fuzz findings.
+
+use crate::crash::Crash;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// The word that starts every annotation.
+pub const MARKER: &str = "FUZZ-CRASH:";
+
+/// How many unchanged lines the diff shows around the annotation.
+const CONTEXT: usize = 3;
+
+/// The file and line, counting from 1, that `crash` should be annotated at:
+/// the panic's location, or else the innermost frame, that's in a file under
+/// `root`. Relative paths are looked up in each of `dirs` in turn.
+pub fn crash_line(crash: &Crash, root: &Path, dirs: &[&Path]) -> Option<(PathBuf, usize)> {
+    crash
+        .location
+        .iter()
+        .chain(
+            crash
+                .frames
+                .iter()
+                .filter_map(|frame| frame.location.as_ref()),
+        )
+        .find_map(|location| source_line(location, root, dirs))
+}
+
+/// Turn a location like `/repo/src/lib.rs:10:5` into a path and a line, if
+/// the file is under `root`.
+fn source_line(location: &str, root: &Path, dirs: &[&Path]) -> Option<(PathBuf, usize)> {
+    let mut parts = location.rsplitn(3, ':');
+    let (path, line) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(_column), Some(line), Some(path)) if line.parse::<usize>().is_ok() => {
+            (path, line.parse().ok()?)
+        }
+        (Some(line), Some(path), None) => (path, line.parse().ok()?),
+        _ => return None,
+    };
+    let path = PathBuf::from(path);
+    let path = if path.is_absolute() {
+        path
+    } else {
+        dirs.iter()
+            .map(|dir| dir.join(&path))
+            .find(|path| path.is_file())?
+    };
+    if line == 0 || !path.starts_with(root) || !path.is_file() {
+        return None;
+    }
+    Some((path, line))
+}
+
+/// The annotation for the crash with `hash`, reproduced by `artifact`.
+pub fn comment(hash: &str, artifact: &str) -> String {
+    format!("// {} {} reproduced by {}", MARKER, hash, artifact)
+}
+
+/// Insert the annotation for the crash with `hash`, reproduced by
+/// `artifact`, above `line` of `source`, indented like it. Returns `None` if
+/// the line doesn't exist or the comments above it already annotate the
+/// crash. That's checked by the artifact too, since a crash without a
+/// backtrace is hashed by its location, which the annotation itself moves.
+pub fn insert(source: &str, line: usize, hash: &str, artifact: &str) -> Option<String> {
+    let lines: Vec<&str> = source.split_inclusive('\n').collect();
+    let target = *lines.get(line.checked_sub(1)?)?;
+    let by = format!(" reproduced by {}", artifact);
+    let annotated = lines[..line - 1]
+        .iter()
+        .rev()
+        .take_while(|l| l.trim_start().starts_with("//"))
+        .any(|l| l.contains(MARKER) && (l.contains(hash) || l.trim_end().ends_with(&by)));
+    if annotated {
+        return None;
+    }
+    let indent = &target[..target.len() - target.trim_start().len()];
+    let mut out = String::with_capacity(source.len());
+    for l in &lines[..line - 1] {
+        out.push_str(l);
+    }
+    let _ = writeln!(out, "{}{}", indent, comment(hash, artifact));
+    for l in &lines[line - 1..] {
+        out.push_str(l);
+    }
+    Some(out)
+}
+
+/// A unified diff of adding `comment` above `line` of `source`, which
+/// `insert` must have accepted, for the file that `path` names.
+pub fn diff(path: &str, source: &str, line: usize, comment: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let target = lines[line - 1];
+    let indent = &target[..target.len() - target.trim_start().len()];
+    let start = line.saturating_sub(CONTEXT).max(1);
+    let end = (line + CONTEXT - 1).min(lines.len());
+    let old_len = end - start + 1;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "--- a/{}", path);
+    let _ = writeln!(out, "+++ b/{}", path);
+    let _ = writeln!(
+        out,
+        "@@ -{},{} +{},{} @@",
+        start,
+        old_len,
+        start,
+        old_len + 1
+    );
+    for (i, l) in lines[start - 1..end].iter().enumerate() {
+        if start + i == line {
+            let _ = writeln!(out, "+{}{}", indent, comment);
+        }
+        let _ = writeln!(out, " {}", l);
+    }
+    out
+}
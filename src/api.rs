@@ -0,0 +1,234 @@
+//! The public API of the fuzzed crate, for `cargo fuzz coverage --uncovered`
+//! and `cargo fuzz suggest-split`.
+//!
+//! It's found by parsing the crate's sources from its library root, following
+//! `mod` declarations, and keeping the `pub` functions, inherent `pub`
+//! methods, and `pub` modules that are reachable through `pub` modules. Items
+//! that are only re-exported from private modules, and code in `#[cfg(test)]`
+//! modules, are left out.
+//!
+//! The calls in each function are found by name alone, which is enough to
+//! tell which of the crate's public functions call which others.
+
+use crate::signature;
+use anyhow::Result;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::{ImplItem, Item, Type, Visibility};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Function,
+    Module,
+}
+
+/// A public function or module, and where its code is.
+#[derive(Clone, Debug)]
+pub struct ApiItem {
+    /// The item's path, starting with the crate's name.
+    pub path: String,
+    pub kind: Kind,
+    /// The first and last line of each part of the item's code, by file. A
+    /// module's code includes that of the modules in it.
+    pub spans: Vec<(PathBuf, (u64, u64))>,
+    /// The number of lines of code in the item.
+    pub lines: u64,
+    /// The names of the functions and methods a function calls, sorted.
+    pub calls: Vec<String>,
+}
+
+/// The public functions and modules of the crate named `crate_name` whose
+/// library root is `lib`.
+pub fn public_items(lib: &Path, crate_name: &str) -> Result<Vec<ApiItem>> {
+    let items = signature::parse(lib)?;
+    let dir = lib.parent().unwrap();
+    let mut api = Vec::new();
+    walk(&items, lib, dir, crate_name, &mut api)?;
+    Ok(api)
+}
+
+/// Collect the public items among `items`, which are in `module` in `file`,
+/// whose submodules' files are in `dir`. Returns the spans of the submodules
+/// that are in files of their own.
+fn walk(
+    items: &[Item],
+    file: &Path,
+    dir: &Path,
+    module: &str,
+    api: &mut Vec<ApiItem>,
+) -> Result<Vec<(PathBuf, (u64, u64))>> {
+    let mut files = Vec::new();
+    for item in items {
+        match item {
+            Item::Fn(function) if is_pub(&function.vis) => {
+                let name = &function.sig.ident;
+                api.push(item_at(
+                    format!("{}::{}", module, name),
+                    Kind::Function,
+                    file,
+                    lines(function),
+                    calls(function.block.to_token_stream()),
+                ));
+            }
+            Item::Impl(imp) if imp.trait_.is_none() => {
+                let ty = match &*imp.self_ty {
+                    Type::Path(ty) => match ty.path.segments.last() {
+                        Some(segment) => segment.ident.to_string(),
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                for item in &imp.items {
+                    if let ImplItem::Method(method) = item {
+                        if is_pub(&method.vis) {
+                            api.push(item_at(
+                                format!("{}::{}::{}", module, ty, method.sig.ident),
+                                Kind::Function,
+                                file,
+                                lines(method),
+                                calls(method.block.to_token_stream()),
+                            ));
+                        }
+                    }
+                }
+            }
+            Item::Mod(declaration) if is_pub(&declaration.vis) && !is_test(&declaration.attrs) => {
+                let name = declaration.ident.to_string();
+                let path = format!("{}::{}", module, name);
+                let mut spans = match &declaration.content {
+                    Some((_, items)) => {
+                        let span = (file.to_path_buf(), lines(declaration));
+                        let mut spans = walk(items, file, &dir.join(&name), &path, api)?;
+                        files.extend(spans.clone());
+                        spans.push(span);
+                        spans
+                    }
+                    None => {
+                        let module_file = dir.join(format!("{}.rs", name));
+                        let module_file = if module_file.is_file() {
+                            module_file
+                        } else {
+                            dir.join(&name).join("mod.rs")
+                        };
+                        if !module_file.is_file() {
+                            continue;
+                        }
+                        let items = signature::parse(&module_file)?;
+                        let mut spans = walk(&items, &module_file, &dir.join(&name), &path, api)?;
+                        spans.push((module_file.clone(), (1, u64::MAX)));
+                        files.extend(spans.clone());
+                        spans
+                    }
+                };
+                spans.sort();
+                let lines = spans.iter().map(span_lines).sum();
+                api.push(ApiItem {
+                    path,
+                    kind: Kind::Module,
+                    spans,
+                    lines,
+                    calls: Vec::new(),
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(files)
+}
+
+fn item_at(
+    path: String,
+    kind: Kind,
+    file: &Path,
+    lines: (u64, u64),
+    calls: Vec<String>,
+) -> ApiItem {
+    ApiItem {
+        path,
+        kind,
+        spans: vec![(file.to_path_buf(), lines)],
+        lines: lines.1 - lines.0 + 1,
+        calls,
+    }
+}
+
+/// The names called in a function's body: every identifier followed by its
+/// arguments in parentheses, possibly after a turbofish.
+fn calls(body: TokenStream) -> Vec<String> {
+    let mut names = Vec::new();
+    collect_calls(body, &mut names);
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn collect_calls(tokens: TokenStream, names: &mut Vec<String>) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Ident(ident) => {
+                let mut next = i + 1;
+                // Skip a turbofish, like in `parse::<u8>(..)`.
+                if matches!(
+                    (tokens.get(next), tokens.get(next + 2)),
+                    (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                        if a.as_char() == ':' && b.as_char() == '<'
+                ) {
+                    let mut depth = 0;
+                    for (j, token) in tokens.iter().enumerate().skip(next + 2) {
+                        if let TokenTree::Punct(punct) = token {
+                            match punct.as_char() {
+                                '<' => depth += 1,
+                                '>' => depth -= 1,
+                                _ => {}
+                            }
+                        }
+                        if depth == 0 {
+                            next = j + 1;
+                            break;
+                        }
+                    }
+                }
+                if matches!(
+                    tokens.get(next),
+                    Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis
+                ) {
+                    names.push(ident.to_string());
+                }
+            }
+            TokenTree::Group(group) => collect_calls(group.stream(), names),
+            _ => {}
+        }
+    }
+}
+
+/// The first and last line of a syntax tree node.
+fn lines(node: &impl Spanned) -> (u64, u64) {
+    let span = node.span();
+    (span.start().line as u64, span.end().line as u64)
+}
+
+/// The number of lines in a span, counting the whole file for a span of one.
+fn span_lines((file, (start, end)): &(PathBuf, (u64, u64))) -> u64 {
+    if *end == u64::MAX {
+        return fs::read_to_string(file).map_or(0, |source| source.lines().count() as u64);
+    }
+    end - start + 1
+}
+
+fn is_pub(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// Whether an item is only compiled for tests.
+fn is_test(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path.is_ident("cfg")
+            && attr
+                .parse_args::<syn::Ident>()
+                .is_ok_and(|ident| ident == "test")
+    })
+}
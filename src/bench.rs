@@ -0,0 +1,152 @@
+//! The throughput of a fuzz target replaying its corpus, measured by `cargo
+//! fuzz bench --corpus` with an optimized build that has no instrumentation.
+//!
+//! The throughput last recorded with `cargo fuzz bench --corpus --update` is
+//! kept in `fuzz/bench/<target>.json`, so that `bench` can show whether a
+//! change made the fuzz target slower.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Throughput {
+    /// The number of inputs that were timed.
+    pub inputs: usize,
+    pub inputs_per_sec: f64,
+    pub bytes_per_sec: f64,
+    /// The median of the nanoseconds each input takes to run.
+    pub p50_ns: u64,
+    /// The 99th percentile of the nanoseconds each input takes to run.
+    pub p99_ns: u64,
+}
+
+impl Throughput {
+    /// The throughput of running each input once, given how many nanoseconds
+    /// each one takes and its size in bytes.
+    pub fn measure(timings: &[(u64, u64)]) -> Throughput {
+        let mut nanos: Vec<u64> = timings.iter().map(|(ns, _)| *ns).collect();
+        nanos.sort_unstable();
+        let secs = nanos.iter().sum::<u64>().max(1) as f64 / 1e9;
+        let bytes: u64 = timings.iter().map(|(_, bytes)| bytes).sum();
+        Throughput {
+            inputs: timings.len(),
+            inputs_per_sec: timings.len() as f64 / secs,
+            bytes_per_sec: bytes as f64 / secs,
+            p50_ns: percentile(&nanos, 50),
+            p99_ns: percentile(&nanos, 99),
+        }
+    }
+}
+
+/// The nearest-rank percentile of sorted values.
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Load the recorded throughput at `path`, if there is one.
+pub fn load(path: &Path) -> Result<Option<Throughput>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .with_context(|| format!("could not decode {}", path.display()))
+}
+
+/// Save a throughput to `path`, replacing any there was.
+pub fn save(path: &Path, throughput: &Throughput) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+    }
+    let data = serde_json::to_string_pretty(throughput).expect("throughput always serializes");
+    fs::write(path, data + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Render a table of the recorded and current throughput.
+pub fn render(recorded: Option<&Throughput>, current: &Throughput) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<16} {:>14} {:>14} {:>8}",
+        "", "recorded", "now", "change"
+    );
+    let rate = |rate: f64| format!("{:.0}", rate);
+    render_row(
+        &mut out,
+        "inputs/sec",
+        recorded.map(|t| t.inputs_per_sec),
+        current.inputs_per_sec,
+        rate,
+    );
+    render_row(
+        &mut out,
+        "bytes/sec",
+        recorded.map(|t| t.bytes_per_sec),
+        current.bytes_per_sec,
+        format_bytes,
+    );
+    render_row(
+        &mut out,
+        "p50 latency",
+        recorded.map(|t| t.p50_ns as f64),
+        current.p50_ns as f64,
+        format_nanos,
+    );
+    render_row(
+        &mut out,
+        "p99 latency",
+        recorded.map(|t| t.p99_ns as f64),
+        current.p99_ns as f64,
+        format_nanos,
+    );
+    out
+}
+
+fn render_row(
+    out: &mut String,
+    name: &str,
+    before: Option<f64>,
+    now: f64,
+    format: impl Fn(f64) -> String,
+) {
+    let change = match before {
+        Some(before) if before > 0.0 => format!("{:+.1}%", (now - before) / before * 100.0),
+        _ => "-".to_string(),
+    };
+    let _ = writeln!(
+        out,
+        "{:<16} {:>14} {:>14} {:>8}",
+        name,
+        before.map_or("-".to_string(), &format),
+        format(now),
+        change
+    );
+}
+
+pub fn format_bytes(bytes: f64) -> String {
+    match bytes {
+        b if b >= 1e9 => format!("{:.1} GB", b / 1e9),
+        b if b >= 1e6 => format!("{:.1} MB", b / 1e6),
+        b if b >= 1e3 => format!("{:.1} kB", b / 1e3),
+        b => format!("{:.0} B", b),
+    }
+}
+
+fn format_nanos(nanos: f64) -> String {
+    match nanos {
+        ns if ns >= 1e9 => format!("{:.2} s", ns / 1e9),
+        ns if ns >= 1e6 => format!("{:.2} ms", ns / 1e6),
+        ns if ns >= 1e3 => format!("{:.2} µs", ns / 1e3),
+        ns => format!("{:.0} ns", ns),
+    }
+}
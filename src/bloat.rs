@@ -0,0 +1,275 @@
+//! The size breakdown of a fuzz target's binary, for `cargo fuzz build
+//! --analyze-size`, in the spirit of `cargo bloat --crates`.
+//!
+//! The code is attributed to crates by the symbols' names, as `llvm-nm`
+//! reports them. Code that isn't Rust is attributed to what it's part of
+//! where that's clear from its name: libFuzzer, or a sanitizer's runtime.
+
+use crate::utils;
+use anyhow::{bail, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// How many crates get a row of their own; the rest are added up.
+const CRATE_ROWS: usize = 15;
+
+/// The sizes of a binary's parts, in bytes.
+#[derive(Debug)]
+pub struct Report {
+    pub file: u64,
+    pub code: u64,
+    pub debuginfo: u64,
+    /// The size of each crate's code, largest first.
+    pub crates: Vec<(String, u64)>,
+}
+
+impl Report {
+    /// Whether debug info is most of the binary.
+    pub fn mostly_debuginfo(&self) -> bool {
+        self.debuginfo > self.file / 2
+    }
+}
+
+/// Break down the size of the binary at `binary`.
+pub fn analyze(binary: &Path) -> Result<Report> {
+    let file = fs::metadata(binary)
+        .with_context(|| format!("failed to read the size of {}", binary.display()))?
+        .len();
+    let (mut code, debuginfo) = section_sizes(binary)?;
+
+    // The same symbols, in the same order, as their names in the binary and
+    // demangled, since it takes both to tell Rust from C++.
+    let mangled = symbols(binary, false)?;
+    let demangled = symbols(binary, true)?;
+    if mangled.len() != demangled.len() {
+        bail!(
+            "`llvm-nm` listed the symbols of {} differently",
+            binary.display()
+        );
+    }
+    let mut sizes: HashMap<String, u64> = HashMap::new();
+    let mut addresses = HashSet::new();
+    for ((address, size, mangled), (_, _, demangled)) in mangled.iter().zip(&demangled) {
+        // Aliases share their code.
+        if addresses.insert(address) {
+            *sizes.entry(owner(mangled, demangled)).or_default() += size;
+        }
+    }
+    if code == 0 {
+        code = sizes.values().sum();
+    }
+    let mut crates: Vec<_> = sizes.into_iter().collect();
+    crates.sort_by(|(a, a_size), (b, b_size)| b_size.cmp(a_size).then(a.cmp(b)));
+    Ok(Report {
+        file,
+        code,
+        debuginfo,
+        crates,
+    })
+}
+
+/// The sizes of the code and the debug info sections of `binary`.
+fn section_sizes(binary: &Path) -> Result<(u64, u64)> {
+    let output = Command::new(utils::llvm_tool("llvm-size")?)
+        .arg("-A")
+        .arg(binary)
+        .output()
+        .context("failed to run `llvm-size`")?;
+    if !output.status.success() {
+        bail!(
+            "`llvm-size` failed on {}: {}",
+            binary.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let (mut code, mut debuginfo) = (0, 0);
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut columns = line.split_whitespace();
+        let (name, size) = match (
+            columns.next(),
+            columns.next().and_then(|s| s.parse::<u64>().ok()),
+        ) {
+            (Some(name), Some(size)) => (name, size),
+            _ => continue,
+        };
+        if name == ".text" {
+            code += size;
+        } else if name.starts_with(".debug") || name.starts_with(".zdebug") {
+            debuginfo += size;
+        }
+    }
+    Ok((code, debuginfo))
+}
+
+/// The address, size and name of each function in `binary`.
+fn symbols(binary: &Path, demangle: bool) -> Result<Vec<(String, u64, String)>> {
+    let mut nm = Command::new(utils::llvm_tool("llvm-nm")?);
+    nm.args(["--print-size", "--defined-only", "--no-sort"]);
+    if demangle {
+        nm.arg("--demangle");
+    }
+    let output = nm.arg(binary).output().context("failed to run `llvm-nm`")?;
+    if !output.status.success() {
+        bail!(
+            "`llvm-nm` failed on {}: {}",
+            binary.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.splitn(4, ' ');
+            let address = columns.next()?;
+            let size = u64::from_str_radix(columns.next()?, 16).ok()?;
+            let kind = columns.next()?;
+            let name = columns.next()?;
+            matches!(kind, "t" | "T" | "w" | "W")
+                .then(|| (address.to_string(), size, name.to_string()))
+        })
+        .collect())
+}
+
+/// What a function is part of: its crate if it's Rust, and otherwise
+/// libFuzzer, a sanitizer's runtime, or some other C or C++ code.
+fn owner(mangled: &str, demangled: &str) -> String {
+    // Mach-O puts an underscore in front of every symbol.
+    let mangled = mangled
+        .strip_prefix('_')
+        .filter(|m| m.starts_with("_Z") || m.starts_with("_R"))
+        .unwrap_or(mangled);
+    if is_rust(mangled) {
+        if let Some(krate) = rust_crate(demangled) {
+            return krate.to_string();
+        }
+    }
+    const RUNTIMES: &[(&str, &str)] = &[
+        ("__sanitizer_cov", "[libFuzzer]"),
+        ("fuzzer::", "[libFuzzer]"),
+        ("__asan", "[AddressSanitizer runtime]"),
+        ("__hwasan", "[HWAddressSanitizer runtime]"),
+        ("__lsan", "[LeakSanitizer runtime]"),
+        ("__msan", "[MemorySanitizer runtime]"),
+        ("__tsan", "[ThreadSanitizer runtime]"),
+        ("__sanitizer", "[sanitizer_common]"),
+        ("__interception", "[sanitizer_common]"),
+        ("__interceptor", "[sanitizer_common]"),
+        ("__ubsan", "[sanitizer_common]"),
+    ];
+    for (marker, runtime) in RUNTIMES {
+        if demangled.contains(marker) {
+            return runtime.to_string();
+        }
+    }
+    if mangled.starts_with("_Z") {
+        "[C++]".to_string()
+    } else {
+        "[unknown]".to_string()
+    }
+}
+
+/// Whether a symbol's name is mangled the way Rust mangles: either the `v0`
+/// scheme, or the legacy one, which looks like C++'s but ends in a hash.
+fn is_rust(mangled: &str) -> bool {
+    // LLVM adds suffixes like `.llvm.1234` to the names it makes local.
+    let mangled = mangled.split('.').next().unwrap_or(mangled);
+    if mangled.starts_with("_R") {
+        return true;
+    }
+    let hash = mangled
+        .strip_prefix("_ZN")
+        .and_then(|rest| rest.strip_suffix('E'))
+        .and_then(|rest| rest.len().checked_sub(16).map(|at| rest.split_at(at)));
+    matches!(hash, Some((path, hash))
+        if path.ends_with("17h") && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// The crate of a demangled Rust path, like `alloc` for
+/// `<alloc::vec::Vec<u8> as core::ops::drop::Drop>::drop`, going by the
+/// trait for implementations on types from no crate, like `&u8`.
+fn rust_crate(demangled: &str) -> Option<&str> {
+    let path = demangled.trim_start_matches(|c| "<&*[(".contains(c));
+    let path = ["const ", "mut ", "dyn "]
+        .iter()
+        .fold(path, |path, prefix| {
+            path.strip_prefix(prefix).unwrap_or(path)
+        });
+    let end = path
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(path.len());
+    if end > 0 && (path[end..].starts_with("::") || path[end..].starts_with('[')) {
+        return Some(&path[..end]);
+    }
+    let (_, rest) = demangled.split_once(" as ")?;
+    rust_crate(rest)
+}
+
+/// Render the breakdown of a binary named `name`.
+pub fn render(name: &str, report: &Report) -> String {
+    let share = |size: u64, of: u64| size as f64 * 100.0 / of.max(1) as f64;
+    let mut out = String::new();
+    let _ = writeln!(out, "{}: {}", name, format_size(report.file));
+    let other = report.file.saturating_sub(report.code + report.debuginfo);
+    for (part, size) in [
+        ("code", report.code),
+        ("debug info", report.debuginfo),
+        ("other", other),
+    ] {
+        let _ = writeln!(
+            out,
+            "  {:<12} {:>10} {:>6.1}%",
+            part,
+            format_size(size),
+            share(size, report.file)
+        );
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Code by crate:");
+    let _ = writeln!(out, "  {:>10} {:>6}  crate", "size", "share");
+    let mut rest = (0, 0);
+    for (i, (krate, size)) in report.crates.iter().enumerate() {
+        if i < CRATE_ROWS {
+            let _ = writeln!(
+                out,
+                "  {:>10} {:>5.1}%  {}",
+                format_size(*size),
+                share(*size, report.code),
+                krate
+            );
+        } else {
+            rest = (rest.0 + 1, rest.1 + size);
+        }
+    }
+    if rest.0 > 0 {
+        let _ = writeln!(
+            out,
+            "  {:>10} {:>5.1}%  and {} more",
+            format_size(rest.1),
+            share(rest.1, report.code),
+            rest.0
+        );
+    }
+    out
+}
+
+/// A size in bytes, like `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{:.1} {}", size, unit)
+}
@@ -0,0 +1,258 @@
+//! Finding the comparisons that a corpus never gets past, for `cargo fuzz
+//! blockers`.
+//!
+//! The fuzz target is built with a recorder of the comparisons it makes,
+//! which sanitizer coverage reports to libFuzzer, and replays the corpus. A
+//! comparison whose operands were never equal is a blocker if it looks like
+//! one that the fuzzer can't guess its way past:
+//!
+//! * a comparison with a constant wider than a byte, or a `match` arm for
+//!   one, like a magic number at the start of a file format;
+//! * a comparison of two values that both vary from input to input, at least
+//!   four bytes wide, like a checksum that the input has to match.
+//!
+//! Single bytes are left out, since the fuzzer finds those by chance, and so
+//! are ordering comparisons that came out both ways.
+
+use crate::crash::strip_symbol_hash;
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// The sanitizer coverage hooks that the recorder wraps.
+pub const HOOKS: &[&str] = &[
+    "__sanitizer_cov_trace_cmp1",
+    "__sanitizer_cov_trace_cmp2",
+    "__sanitizer_cov_trace_cmp4",
+    "__sanitizer_cov_trace_cmp8",
+    "__sanitizer_cov_trace_const_cmp1",
+    "__sanitizer_cov_trace_const_cmp2",
+    "__sanitizer_cov_trace_const_cmp4",
+    "__sanitizer_cov_trace_const_cmp8",
+    "__sanitizer_cov_trace_switch",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A comparison with a constant, which is the first operand.
+    Const,
+    /// A comparison of two computed values.
+    Computed,
+    /// A case of a `match`, whose value is the first operand.
+    SwitchCase,
+}
+
+/// What the recorder saw at one comparison, or one case of a `match`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Site {
+    /// The offset of the comparison in the fuzz target's binary.
+    pub offset: u64,
+    pub kind: Kind,
+    /// The width of the operands in bytes.
+    pub width: u8,
+    /// The first operands seen, of which the first is the constant, if any.
+    pub first: (u64, u64),
+    /// Whether each operand ever differed from its first value.
+    pub varied: (bool, bool),
+    /// How many times the comparison ran, and how many of those its operands
+    /// were equal.
+    pub count: u64,
+    pub equal: u64,
+    /// The second operand that had the most bytes in common with the first,
+    /// and how many.
+    pub closest: (u64, u8),
+}
+
+impl Site {
+    /// Whether the comparison blocks the fuzzer, and why.
+    pub fn blocker(&self) -> Option<Blocker> {
+        if self.equal > 0 {
+            return None;
+        }
+        match self.kind {
+            Kind::Const | Kind::SwitchCase if self.first.0 > 0xff => {
+                Some(Blocker::Magic(self.first.0))
+            }
+            Kind::Computed if self.width >= 4 && self.varied.0 && self.varied.1 => {
+                Some(Blocker::Checksum)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Blocker {
+    /// An input has to hold this constant.
+    Magic(u64),
+    /// An input has to match a value computed from it.
+    Checksum,
+}
+
+/// Parse the log that the recorder writes, a line per site.
+pub fn parse_log(log: &str) -> Result<Vec<Site>> {
+    log.lines()
+        .map(|line| {
+            parse_site(line).ok_or_else(|| anyhow!("invalid comparison log line: {}", line))
+        })
+        .collect()
+}
+
+fn parse_site(line: &str) -> Option<Site> {
+    let fields: Vec<&str> = line.split(' ').collect();
+    if fields.len() != 14 {
+        return None;
+    }
+    let hex = |i: usize| u64::from_str_radix(fields[i], 16).ok();
+    let dec = |i: usize| fields[i].parse::<u64>().ok();
+    Some(Site {
+        offset: hex(0)?,
+        kind: match dec(2)? {
+            0 => Kind::Const,
+            1 => Kind::Computed,
+            2 => Kind::SwitchCase,
+            _ => return None,
+        },
+        width: dec(3)?.try_into().ok()?,
+        varied: (dec(4)? != 0, dec(5)? != 0),
+        first: (hex(7)?, hex(8)?),
+        count: dec(9)?,
+        equal: dec(10)?,
+        closest: (hex(13)?, dec(6)?.try_into().ok()?),
+    })
+}
+
+/// Parse what `llvm-symbolizer` prints for some addresses: for each, its
+/// inlined frames, innermost first, as functions and locations.
+pub fn parse_symbolizer(output: &str) -> Vec<Vec<(String, String)>> {
+    output
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let lines: Vec<&str> = block.lines().collect();
+            lines
+                .chunks(2)
+                .filter_map(|pair| match pair {
+                    [function, location] => Some((
+                        strip_symbol_hash(function.trim()).to_string(),
+                        location.trim().to_string(),
+                    )),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The innermost of a site's frames whose source is under `root`, as its
+/// function and its location relative to `root`.
+pub fn source_frame(frames: &[(String, String)], root: &Path) -> Option<(String, String)> {
+    frames.iter().find_map(|(function, location)| {
+        let relative = Path::new(location).strip_prefix(root).ok()?;
+        Some((function.clone(), relative.display().to_string()))
+    })
+}
+
+/// A blocker at one source location, merging the sites that code generation
+/// made of it.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub function: String,
+    pub location: String,
+    pub blocker: Blocker,
+    pub width: u8,
+    pub count: u64,
+    pub closest: (u64, u8),
+}
+
+/// Merge blockers at the same location, in the order of their locations.
+pub fn merge(found: Vec<Finding>) -> Vec<Finding> {
+    let mut merged = BTreeMap::<(String, Blocker), Finding>::new();
+    for finding in found {
+        match merged.get_mut(&(finding.location.clone(), finding.blocker)) {
+            Some(existing) => {
+                existing.count += finding.count;
+                if finding.closest.1 > existing.closest.1 {
+                    existing.closest = finding.closest;
+                }
+            }
+            None => {
+                merged.insert((finding.location.clone(), finding.blocker), finding);
+            }
+        }
+    }
+    let mut findings: Vec<Finding> = merged.into_values().collect();
+    findings.sort_by_key(|f| {
+        let mut parts = f.location.rsplitn(3, ':');
+        let column = parts.next().and_then(|c| c.parse::<u32>().ok());
+        let line = parts.next().and_then(|l| l.parse::<u32>().ok());
+        (parts.next().map(String::from), line, column, f.blocker)
+    });
+    findings
+}
+
+/// The bytes of `value` in an input, as a libFuzzer dictionary string, in
+/// little-endian order or else big-endian.
+fn dictionary_entry(value: u64, width: u8, little_endian: bool) -> String {
+    let mut bytes: Vec<u8> = (0..width).map(|i| (value >> (8 * i)) as u8).collect();
+    if !little_endian {
+        bytes.reverse();
+    }
+    let escaped: String = bytes.iter().map(|b| format!("\\x{:02x}", b)).collect();
+    format!("\"{}\"", escaped)
+}
+
+/// A report of the blockers in the replay of a target's corpus.
+pub fn render(target: &str, inputs: usize, findings: &[Finding]) -> String {
+    let mut out = String::new();
+    if findings.is_empty() {
+        let _ = writeln!(
+            out,
+            "Found no comparisons that the {} input(s) of `{}` never get past",
+            inputs, target
+        );
+        return out;
+    }
+    let _ = writeln!(
+        out,
+        "Found {} comparison(s) that the {} input(s) of `{}` never get past:",
+        findings.len(),
+        inputs,
+        target
+    );
+    for finding in findings {
+        let _ = writeln!(out, "\n{} in {}", finding.location, finding.function);
+        match finding.blocker {
+            Blocker::Magic(value) => {
+                let _ = writeln!(
+                    out,
+                    "  compares with the constant {:#x}, which {} comparison(s) never matched",
+                    value, finding.count
+                );
+                let _ = writeln!(
+                    out,
+                    "  closest: {:#x}, with {} of {} bytes",
+                    finding.closest.0, finding.closest.1, finding.width
+                );
+                let _ = writeln!(
+                    out,
+                    "  dictionary: {} (little-endian) or {} (big-endian)",
+                    dictionary_entry(value, finding.width, true),
+                    dictionary_entry(value, finding.width, false)
+                );
+            }
+            Blocker::Checksum => {
+                let _ = writeln!(
+                    out,
+                    "  compares two {}-byte values computed from the input, like a checksum, \
+                     which {} comparison(s) never matched",
+                    finding.width, finding.count
+                );
+                let _ = writeln!(out, "  consider skipping the check under `cfg(fuzzing)`");
+            }
+        }
+    }
+    out
+}
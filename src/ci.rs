@@ -0,0 +1,132 @@
+//! The summary of a `cargo fuzz ci` run, on stderr and as JUnit XML or JSON
+//! for CI systems to show.
+
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// How fuzzing one target went.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TargetResult {
+    pub target: String,
+    /// How long the target was fuzzed for, in seconds.
+    pub seconds: u64,
+    /// The crashes whose signatures weren't among the known ones.
+    pub new_crashes: Vec<NewCrash>,
+    /// How many artifacts were crashes that were already known.
+    pub known_crashes: usize,
+    /// Why fuzzing failed, if it failed without finding a crash.
+    pub error: Option<String>,
+    /// How many inputs the corpus had after minimizing it, if it was.
+    pub corpus_size: Option<usize>,
+}
+
+impl TargetResult {
+    pub fn passed(&self) -> bool {
+        self.new_crashes.is_empty() && self.error.is_none()
+    }
+}
+
+/// A crash that isn't a known one.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct NewCrash {
+    pub artifact: String,
+    /// The crash's hash, if the artifact reproduced it.
+    pub hash: Option<String>,
+    /// The kind of crash and its message, or why it's unknown.
+    pub description: String,
+}
+
+/// A line per target, for stderr.
+pub fn render_text(results: &[TargetResult]) -> String {
+    let mut out = String::new();
+    for result in results {
+        let status = if !result.new_crashes.is_empty() {
+            format!("{} new crash(es)", result.new_crashes.len())
+        } else if let Some(error) = &result.error {
+            format!("failed: {}", error)
+        } else {
+            "ok".to_string()
+        };
+        let _ = write!(
+            out,
+            "\t{}: {} in {}s",
+            result.target, status, result.seconds
+        );
+        if result.known_crashes > 0 {
+            let _ = write!(out, ", {} known crash(es)", result.known_crashes);
+        }
+        if let Some(size) = result.corpus_size {
+            let _ = write!(out, ", {} corpus input(s)", size);
+        }
+        out.push('\n');
+        for crash in &result.new_crashes {
+            let _ = writeln!(out, "\t  {}: {}", crash.artifact, crash.description);
+        }
+    }
+    out
+}
+
+/// A JUnit test suite with a test case per target, which fails with each new
+/// crash it found.
+pub fn render_junit(results: &[TargetResult]) -> String {
+    let failures = results.iter().filter(|r| !r.new_crashes.is_empty()).count();
+    let errors = results
+        .iter()
+        .filter(|r| r.new_crashes.is_empty() && r.error.is_some())
+        .count();
+    let time: u64 = results.iter().map(|r| r.seconds).sum();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        out,
+        "<testsuite name=\"cargo-fuzz\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{}\">",
+        results.len(),
+        failures,
+        errors,
+        time
+    );
+    for result in results {
+        let _ = write!(
+            out,
+            "  <testcase classname=\"fuzz\" name=\"{}\" time=\"{}\"",
+            escape(&result.target),
+            result.seconds
+        );
+        if result.passed() {
+            out.push_str("/>\n");
+            continue;
+        }
+        out.push_str(">\n");
+        for crash in &result.new_crashes {
+            let _ = writeln!(
+                out,
+                "    <failure message=\"{}\">{}</failure>",
+                escape(&crash.description),
+                escape(&crash.artifact)
+            );
+        }
+        if let (true, Some(error)) = (result.new_crashes.is_empty(), &result.error) {
+            let _ = writeln!(out, "    <error message=\"{}\"/>", escape(error));
+        }
+        out.push_str("  </testcase>\n");
+    }
+    out.push_str("</testsuite>\n");
+    out
+}
+
+/// The results as a JSON document.
+pub fn render_json(results: &[TargetResult]) -> String {
+    let summary = serde_json::json!({
+        "passed": results.iter().all(TargetResult::passed),
+        "targets": results,
+    });
+    serde_json::to_string_pretty(&summary).expect("CI summaries always serialize") + "\n"
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
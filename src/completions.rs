@@ -0,0 +1,200 @@
+//! Shell completions, for `cargo fuzz completions`.
+//!
+//! The scripts themselves are small: they pass the words on the command line,
+//! up to the one being completed, to the hidden `cargo fuzz __complete`. That
+//! walks them through the same `clap` definition that parses them, to tell
+//! whether the word is a subcommand, a flag, a flag's value or a positional
+//! argument, and prints what it can be. Fuzz targets, artifacts and corpus
+//! directories come from the project in the current directory, so they're
+//! always up to date. When nothing is printed, the shell completes a path.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use structopt::clap::{App, AppSettings, ArgSettings};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Shell::Bash => "bash",
+                Shell::Zsh => "zsh",
+                Shell::Fish => "fish",
+            }
+        )
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!("unknown shell: {}", s)),
+        }
+    }
+}
+
+/// The completion script for `shell`.
+pub fn script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash_completions_template!().to_string(),
+        Shell::Zsh => zsh_completions_template!().to_string(),
+        Shell::Fish => fish_completions_template!().to_string(),
+    }
+}
+
+/// What the word being completed is.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Expected {
+    /// One of these words: a subcommand, a flag, or one of a flag's values.
+    Words(Vec<String>),
+    /// The name of a fuzz target.
+    Target,
+    /// An artifact of the fuzz target named before it, or of any target.
+    Artifact(Option<String>),
+    /// A corpus directory of the fuzz target named before it, or of any
+    /// target.
+    Corpus(Option<String>),
+    /// Anything else, which the shell completes as a path.
+    Path,
+}
+
+/// What the last of `words`, a command line up to and including the word
+/// being completed, is, going by `app`.
+///
+/// `clap` 2 only exposes the arguments and subcommands of an `App` through
+/// its hidden `p` field.
+pub fn expected(app: &App, words: &[String]) -> Expected {
+    let (current, before) = match words.split_last() {
+        Some(split) => split,
+        None => return Expected::Path,
+    };
+    let mut before = before.iter().map(String::as_str).peekable();
+    // `cargo [+toolchain] fuzz`, or `cargo-fuzz`, which Cargo passes `fuzz`.
+    if before.peek() == Some(&"cargo") {
+        before.next();
+        while before.peek().is_some_and(|word| word.starts_with('+')) {
+            before.next();
+        }
+    } else if before
+        .peek()
+        .is_some_and(|word| Path::new(word).file_stem() == Some("cargo-fuzz".as_ref()))
+    {
+        before.next();
+    }
+    if before.peek() == Some(&"fuzz") {
+        before.next();
+    }
+
+    let mut app = app;
+    let mut positionals = Vec::new();
+    // The option whose value comes next.
+    let mut pending = None;
+    for word in before {
+        if word == "--" {
+            // The rest goes to the engine or the program run.
+            return Expected::Path;
+        }
+        if let Some(option) = pending.take() {
+            // Bash splits `--flag=value` into three words.
+            if word == "=" {
+                pending = Some(option);
+            }
+            continue;
+        }
+        if let Some(long) = word.strip_prefix("--") {
+            if !long.contains('=') {
+                pending = app.p.opts.iter().position(|opt| opt.s.long == Some(long));
+            }
+            continue;
+        }
+        if word.len() > 1 && word.starts_with('-') {
+            // `-j 4`, rather than `-j4`.
+            if word.len() == 2 {
+                let short = word.chars().nth(1);
+                pending = app.p.opts.iter().position(|opt| opt.s.short == short);
+            }
+            continue;
+        }
+        if positionals.is_empty() {
+            if let Some(subcommand) = app.p.subcommands.iter().find(|sub| sub.get_name() == word) {
+                app = subcommand;
+                continue;
+            }
+        }
+        positionals.push(word);
+    }
+
+    if let Some(option) = pending {
+        return match &app.p.opts[option].v.possible_vals {
+            Some(values) => Expected::Words(values.iter().map(|v| v.to_string()).collect()),
+            None => Expected::Path,
+        };
+    }
+    if current.starts_with('-') {
+        let flags = app.p.flags.iter().map(|flag| (&flag.b, &flag.s));
+        let opts = app.p.opts.iter().map(|opt| (&opt.b, &opt.s));
+        return Expected::Words(
+            flags
+                .chain(opts)
+                .filter(|(base, _)| !base.is_set(ArgSettings::Hidden))
+                .filter_map(|(_, switched)| switched.long.map(|long| format!("--{}", long)))
+                .collect(),
+        );
+    }
+    if positionals.is_empty() && !app.p.subcommands.is_empty() {
+        return Expected::Words(
+            app.p
+                .subcommands
+                .iter()
+                .filter(|sub| !sub.p.is_set(AppSettings::Hidden))
+                .map(|sub| sub.get_name().to_string())
+                .collect(),
+        );
+    }
+
+    // The positional argument the word is for: the next one, or the last one
+    // given if it takes several values.
+    let args: Vec<_> = app
+        .p
+        .positionals
+        .values()
+        .filter(|pos| !pos.b.is_set(ArgSettings::Last))
+        .collect();
+    let arg = match args.get(positionals.len()) {
+        Some(arg) => arg,
+        None => match args.last() {
+            Some(arg) if arg.b.is_set(ArgSettings::Multiple) => arg,
+            _ => return Expected::Path,
+        },
+    };
+    if let Some(values) = &arg.v.possible_vals {
+        return Expected::Words(values.iter().map(|v| v.to_string()).collect());
+    }
+    let target = args
+        .iter()
+        .zip(&positionals)
+        .find(|(arg, _)| arg.b.name == "target")
+        .map(|(_, target)| target.to_string());
+    match arg.b.name {
+        // `cargo fuzz add` names a new target.
+        "target" if app.get_name() == "add" => Expected::Path,
+        "target" | "targets" | "old" => Expected::Target,
+        "corpus" => Expected::Corpus(target),
+        "artifact" | "artifacts" | "test-case" | "input" | "inputs" => Expected::Artifact(target),
+        _ => Expected::Path,
+    }
+}
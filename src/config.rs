@@ -0,0 +1,243 @@
+//! The optional `fuzz/fuzz.toml` project configuration file.
+//!
+//! It holds defaults for options that would otherwise have to be passed on
+//! every invocation, both for all targets and for individual targets:
+//!
+//! ```toml
+//! toolchain = "nightly-2024-05-01"
+//! libfuzzer-archive = "../third_party/libfuzzer/libFuzzer.a"
+//! libfuzzer-sys = "https://github.com/me/libfuzzer#my-patches"
+//!
+//! [defaults]
+//! sanitizer = "address"
+//! features = "foo"
+//! jobs = 8
+//! timeout = 10
+//! rss-limit = 4096
+//! max-len = 4096
+//! args = ["-only_ascii=1"]
+//! corpus-remote = "s3://my-bucket/corpora"
+//!
+//! [targets.my_target]
+//! sanitizer = "none"
+//! runner = "qemu-aarch64 -L /usr/aarch64-linux-gnu"
+//! corpus = "corpus/my_target"
+//! seeds = ["seeds/my_target", "../tests/data"]
+//! pre-run = "./start-server.sh"
+//! post-run = "./stop-server.sh"
+//! on-crash = "scripts/upload.sh {artifact} {target}"
+//!
+//! [targets.my_target.env]
+//! SERVER_ADDR = "127.0.0.1:8080"
+//!
+//! [targets.my_cli]
+//! bin = "my-cli"
+//! ```
+//!
+//! Options given on the command line always take precedence over the
+//! configuration file, and per-target settings take precedence over the
+//! `[defaults]`.
+
+use crate::options::{BuildOptions, Engine, Sanitizer};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// The rustup toolchain that every fuzz build uses, for reproducible
+    /// builds.
+    pub toolchain: Option<String>,
+
+    /// A static libFuzzer archive to link in place of the one that
+    /// `libfuzzer-sys` builds, relative to the fuzz directory.
+    pub libfuzzer_archive: Option<PathBuf>,
+
+    /// Where to get `libfuzzer-sys` from instead of crates.io: a git URL with
+    /// an optional `#<rev>`, a path relative to the fuzz directory, or a
+    /// version.
+    pub libfuzzer_sys: Option<String>,
+
+    /// Settings for all fuzz targets.
+    #[serde(default)]
+    pub defaults: TargetConfig,
+
+    /// Settings for individual fuzz targets, overriding the defaults.
+    #[serde(default)]
+    pub targets: BTreeMap<String, TargetConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct TargetConfig {
+    pub sanitizer: Option<Sanitizer>,
+    pub engine: Option<Engine>,
+    pub dev: Option<bool>,
+    pub release: Option<bool>,
+    /// A Cargo profile to build with, instead of `dev` or `release`.
+    pub profile: Option<String>,
+    pub debug_assertions: Option<bool>,
+    pub features: Option<String>,
+    pub no_default_features: Option<bool>,
+    pub all_features: Option<bool>,
+    /// The only crates to build with coverage instrumentation, like
+    /// `--coverage-crates`.
+    pub coverage_crates: Option<Vec<String>>,
+    /// Command to run the fuzz target through, like an emulator for a
+    /// cross-compiled target.
+    pub runner: Option<String>,
+    /// Number of concurrent jobs for `cargo fuzz run`.
+    pub jobs: Option<u32>,
+    /// Seconds an input may run for before `cargo fuzz run` reports it as a
+    /// timeout.
+    pub timeout: Option<u64>,
+    /// Megabytes of memory the fuzzer may use before `cargo fuzz run`
+    /// reports an out-of-memory crash, or 0 for no limit.
+    pub rss_limit: Option<u64>,
+    /// Megabytes a single allocation may take before `cargo fuzz run`
+    /// reports an out-of-memory crash.
+    pub malloc_limit: Option<u64>,
+    /// The largest input, in bytes, that `cargo fuzz run` generates, which
+    /// it warns about existing corpus inputs over.
+    pub max_len: Option<u64>,
+    /// Extra libFuzzer arguments for `cargo fuzz run`, passed before any given
+    /// on the command line. Also written `engine-args`.
+    #[serde(alias = "engine-args")]
+    pub args: Option<Vec<String>>,
+    /// Whether `cargo fuzz run` treats the values the fuzz target compares
+    /// as coverage, like `--value-profile`.
+    pub value_profile: Option<bool>,
+    /// Whether `cargo fuzz run` schedules the inputs that recently found new
+    /// coverage more often, like `--entropic`.
+    pub entropic: Option<bool>,
+    /// Whether `cargo fuzz run` mutates inputs with the values the fuzz
+    /// target compares them with, like `--cmp-log`.
+    pub cmp_log: Option<bool>,
+    /// Remote storage for `cargo fuzz corpus push` and `pull`.
+    pub corpus_remote: Option<String>,
+    /// The working corpus, relative to the fuzz directory, which new inputs
+    /// are added to and `cargo fuzz cmin` minimizes.
+    pub corpus: Option<String>,
+    /// Seed corpora, relative to the fuzz directory, which fuzzing reads from
+    /// but never changes.
+    pub seeds: Option<Vec<String>>,
+    /// Environment variables to set for the fuzz target and its hooks. A
+    /// target's variables are added to those in the `[defaults]`.
+    pub env: Option<BTreeMap<String, String>>,
+    /// A shell command that `run`, `cmin` and `coverage` run in the fuzz
+    /// directory before the fuzz target.
+    pub pre_run: Option<String>,
+    /// A shell command that `run`, `cmin` and `coverage` run in the fuzz
+    /// directory after the fuzz target, even if it failed.
+    pub post_run: Option<String>,
+    /// A shell command that fuzzing runs in the fuzz directory for each new
+    /// artifact, with `{artifact}` and `{target}` replaced by the artifact's
+    /// path and the target's name.
+    pub on_crash: Option<String>,
+    /// A binary of the fuzzed crate that the fuzz target runs on its inputs,
+    /// which `cargo fuzz build` builds alongside it.
+    pub bin: Option<String>,
+    /// Globs of input file names that `cargo fuzz miri` skips, like those
+    /// too slow to interpret or that call code Miri doesn't support.
+    pub miri_skip: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Load the configuration file at `path`, or the default configuration if
+    /// there is no such file.
+    pub fn load(path: &Path) -> Result<Config> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("could not read the config file: {}", path.display()))?;
+        toml::from_str(&data)
+            .with_context(|| format!("could not decode the config file at {}", path.display()))
+    }
+
+    /// The effective settings for a fuzz target, or for all targets if `None`.
+    pub fn target(&self, target: Option<&str>) -> TargetConfig {
+        let defaults = self.defaults.clone();
+        match target.and_then(|t| self.targets.get(t)) {
+            Some(overrides) => overrides.clone().or(defaults),
+            None => defaults,
+        }
+    }
+}
+
+impl TargetConfig {
+    /// Fill in any settings missing from `self` with those from `other`.
+    fn or(self, other: TargetConfig) -> TargetConfig {
+        TargetConfig {
+            sanitizer: self.sanitizer.or(other.sanitizer),
+            engine: self.engine.or(other.engine),
+            dev: self.dev.or(other.dev),
+            release: self.release.or(other.release),
+            profile: self.profile.or(other.profile),
+            debug_assertions: self.debug_assertions.or(other.debug_assertions),
+            features: self.features.or(other.features),
+            no_default_features: self.no_default_features.or(other.no_default_features),
+            all_features: self.all_features.or(other.all_features),
+            coverage_crates: self.coverage_crates.or(other.coverage_crates),
+            runner: self.runner.or(other.runner),
+            jobs: self.jobs.or(other.jobs),
+            timeout: self.timeout.or(other.timeout),
+            rss_limit: self.rss_limit.or(other.rss_limit),
+            malloc_limit: self.malloc_limit.or(other.malloc_limit),
+            max_len: self.max_len.or(other.max_len),
+            args: self.args.or(other.args),
+            value_profile: self.value_profile.or(other.value_profile),
+            entropic: self.entropic.or(other.entropic),
+            cmp_log: self.cmp_log.or(other.cmp_log),
+            corpus_remote: self.corpus_remote.or(other.corpus_remote),
+            corpus: self.corpus.or(other.corpus),
+            seeds: self.seeds.or(other.seeds),
+            env: match (self.env, other.env) {
+                (Some(mut env), Some(defaults)) => {
+                    for (name, value) in defaults {
+                        env.entry(name).or_insert(value);
+                    }
+                    Some(env)
+                }
+                (env, defaults) => env.or(defaults),
+            },
+            pre_run: self.pre_run.or(other.pre_run),
+            post_run: self.post_run.or(other.post_run),
+            on_crash: self.on_crash.or(other.on_crash),
+            bin: self.bin.or(other.bin),
+            miri_skip: self.miri_skip.or(other.miri_skip),
+        }
+    }
+
+    /// Fill in build options that weren't given on the command line.
+    pub fn apply(&self, build: &mut BuildOptions) {
+        if build.sanitizer.is_none() {
+            build.sanitizer = self.sanitizer;
+        }
+        if build.engine.is_none() {
+            build.engine = self.engine;
+        }
+        if !build.dev && !build.release && build.profile.is_none() {
+            build.dev = self.dev.unwrap_or(false);
+            build.release = self.release.unwrap_or(false) && !build.dev;
+            if !build.dev && !build.release {
+                build.profile = self.profile.clone();
+            }
+        }
+        build.debug_assertions |= self.debug_assertions.unwrap_or(false);
+        if build.features.is_none() && !build.all_features && !build.no_default_features {
+            build.features = self.features.clone();
+            build.no_default_features = self.no_default_features.unwrap_or(false);
+            build.all_features = self.all_features.unwrap_or(false) && build.features.is_none();
+        }
+        if build.runner.is_none() {
+            build.runner = self.runner.clone();
+        }
+        if build.coverage_crates.is_none() {
+            build.coverage_crates = self.coverage_crates.as_ref().map(|c| c.join(","));
+        }
+    }
+}
@@ -0,0 +1,527 @@
+//! Comparing the coverage of a fuzz target with a baseline profile, for
+//! `cargo fuzz coverage --diff`, the coverage of one input with the rest of
+//! the corpus, for `cargo fuzz explain`, and the coverage of each input with
+//! the lines changed since a git revision, for `cargo fuzz run
+//! --focus-changed`, and finding the public API that no input reaches, for
+//! `cargo fuzz coverage --uncovered`, along with converting lcov reports for
+//! `cargo fuzz coverage --format cobertura` and `--upload coveralls`.
+//!
+//! Both profiles are read back through the current build of the target with
+//! `llvm-cov export`, so regions are matched by their location in the current
+//! sources. A baseline from another revision only carries over counts for the
+//! functions that haven't changed since, since `llvm-cov` drops the data of
+//! functions whose code has.
+
+use crate::api::{ApiItem, Kind};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A code region, from its first line and column to its last.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Region {
+    pub file: PathBuf,
+    pub start: (u64, u64),
+    pub end: (u64, u64),
+}
+
+/// Whether each code region was covered, according to a profile.
+pub type Regions = BTreeMap<Region, bool>;
+
+/// The changed lines of each file, according to a diff.
+pub type ChangedLines = BTreeMap<PathBuf, BTreeSet<u64>>;
+
+#[derive(Deserialize)]
+struct Export {
+    data: Vec<ExportData>,
+}
+
+#[derive(Deserialize)]
+struct ExportData {
+    functions: Vec<ExportFunction>,
+}
+
+#[derive(Deserialize)]
+struct ExportFunction {
+    filenames: Vec<PathBuf>,
+    /// `[line start, column start, line end, column end, execution count,
+    /// file id, expanded file id, kind]`, with newer versions of LLVM adding
+    /// more fields after those.
+    regions: Vec<Vec<u64>>,
+}
+
+/// The kind of region that counts executions of code, as opposed to
+/// expansions, skipped code, gaps, and branches.
+const CODE_REGION: u64 = 0;
+
+/// Read the code regions of files in `sources` out of the JSON printed by
+/// `llvm-cov export -format=text`.
+///
+/// A region is covered if any instantiation of its function executed it.
+pub fn parse_export(json: &str, sources: &Path) -> Result<Regions> {
+    let export: Export =
+        serde_json::from_str(json).context("could not decode the output of `llvm-cov export`")?;
+    let mut regions = Regions::new();
+    for function in export.data.iter().flat_map(|d| &d.functions) {
+        for region in &function.regions {
+            let (start_line, start_col, end_line, end_col, count, file_id, kind) = match region[..]
+            {
+                [start_line, start_col, end_line, end_col, count, file_id, _, kind, ..] => (
+                    start_line, start_col, end_line, end_col, count, file_id, kind,
+                ),
+                _ => continue,
+            };
+            if kind != CODE_REGION {
+                continue;
+            }
+            let file = match function.filenames.get(file_id as usize) {
+                Some(file) if file.starts_with(sources) => file.clone(),
+                _ => continue,
+            };
+            let region = Region {
+                file,
+                start: (start_line, start_col),
+                end: (end_line, end_col),
+            };
+            *regions.entry(region).or_insert(false) |= count > 0;
+        }
+    }
+    Ok(regions)
+}
+
+/// How the coverage of one file changed.
+#[derive(Debug, Default)]
+pub struct FileDiff {
+    pub regions: usize,
+    pub base: usize,
+    pub current: usize,
+    pub gained: Vec<Region>,
+    pub lost: Vec<Region>,
+}
+
+/// Compare the regions covered by a baseline profile with the ones covered by
+/// the current profile, file by file.
+pub fn diff(base: &Regions, current: &Regions) -> BTreeMap<PathBuf, FileDiff> {
+    let mut files = BTreeMap::<PathBuf, FileDiff>::new();
+    for (region, &covered) in current {
+        let was_covered = base.get(region).copied().unwrap_or(false);
+        let file = files.entry(region.file.clone()).or_default();
+        file.regions += 1;
+        file.base += was_covered as usize;
+        file.current += covered as usize;
+        match (was_covered, covered) {
+            (false, true) => file.gained.push(region.clone()),
+            (true, false) => file.lost.push(region.clone()),
+            _ => {}
+        }
+    }
+    files
+}
+
+/// Render a table of how many regions of each file were covered before and
+/// after, with paths relative to `root`.
+pub fn render_summary(files: &BTreeMap<PathBuf, FileDiff>, root: &Path) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<40} {:>15} {:>15} {:>7} {:>7}",
+        "file", "base", "current", "gained", "lost"
+    );
+    let mut total = [0; 5];
+    for (path, file) in files {
+        let counts = [
+            file.regions,
+            file.base,
+            file.current,
+            file.gained.len(),
+            file.lost.len(),
+        ];
+        let name = path.strip_prefix(root).unwrap_or(path).display();
+        render_row(&mut out, &name.to_string(), counts);
+        for (total, count) in total.iter_mut().zip(counts) {
+            *total += count;
+        }
+    }
+    render_row(&mut out, "total", total);
+    out
+}
+
+fn render_row(out: &mut String, name: &str, [regions, base, current, gained, lost]: [usize; 5]) {
+    let _ = writeln!(
+        out,
+        "{:<40} {:>15} {:>15} {:>7} {:>7}",
+        name,
+        format!("{}/{}", base, regions),
+        format!("{}/{}", current, regions),
+        format!("+{}", gained),
+        format!("-{}", lost),
+    );
+}
+
+/// Render the source lines where coverage changed, prefixed with `+` where
+/// a region gained coverage, `-` where one lost it, and `~` where both
+/// happened on the same line.
+pub fn render_annotated(files: &BTreeMap<PathBuf, FileDiff>, root: &Path) -> Result<String> {
+    let mut out = String::new();
+    for (path, file) in files {
+        if file.gained.is_empty() && file.lost.is_empty() {
+            continue;
+        }
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let lines: Vec<&str> = source.lines().collect();
+
+        // Each line where a changed region starts, and whether regions on it
+        // gained and lost coverage.
+        let mut changed = BTreeMap::<u64, (bool, bool)>::new();
+        for region in &file.gained {
+            changed.entry(region.start.0).or_default().0 = true;
+        }
+        for region in &file.lost {
+            changed.entry(region.start.0).or_default().1 = true;
+        }
+
+        let name = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        let _ = writeln!(out, "--- {} (base)\n+++ {} (current)", name, name);
+        for (line, (gained, lost)) in changed {
+            let marker = match (gained, lost) {
+                (true, true) => '~',
+                (true, false) => '+',
+                _ => '-',
+            };
+            let text = lines
+                .get(line.saturating_sub(1) as usize)
+                .copied()
+                .unwrap_or("");
+            let _ = writeln!(out, "{}{:>6} | {}", marker, line, text);
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// How many lines of a region `render_unique` prints at most.
+const MAX_REGION_LINES: usize = 5;
+
+/// The regions that `input` covers and `others` doesn't, by file.
+pub fn unique(input: &Regions, others: &Regions) -> BTreeMap<PathBuf, Vec<Region>> {
+    let mut files = BTreeMap::<PathBuf, Vec<Region>>::new();
+    for (region, &covered) in input {
+        if covered && !others.get(region).copied().unwrap_or(false) {
+            files
+                .entry(region.file.clone())
+                .or_default()
+                .push(region.clone());
+        }
+    }
+    files
+}
+
+/// Render each region with its location and source lines, file by file, with
+/// paths relative to `root`.
+pub fn render_unique(files: &BTreeMap<PathBuf, Vec<Region>>, root: &Path) -> Result<String> {
+    let mut out = String::new();
+    for (path, regions) in files {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let lines: Vec<&str> = source.lines().collect();
+
+        let _ = writeln!(out, "{}", path.strip_prefix(root).unwrap_or(path).display());
+        for region in regions {
+            let _ = writeln!(
+                out,
+                "  {}:{}-{}:{}",
+                region.start.0, region.start.1, region.end.0, region.end.1
+            );
+            let (first, last) = (region.start.0, region.end.0);
+            for line in (first..=last).take(MAX_REGION_LINES) {
+                let text = lines
+                    .get(line.saturating_sub(1) as usize)
+                    .copied()
+                    .unwrap_or("");
+                let _ = writeln!(out, "{:>8} | {}", line, text);
+            }
+            if last - first >= MAX_REGION_LINES as u64 {
+                let _ = writeln!(out, "{:>8} | ...", "");
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Read the lines that `git diff --unified=0 --no-prefix` adds or changes,
+/// with paths relative to `root`. Removing lines counts as changing the line
+/// before them, since that's where the code they held used to run.
+pub fn changed_lines(diff: &str, root: &Path) -> ChangedLines {
+    let mut changed = ChangedLines::new();
+    let mut file = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            // Git ends paths containing spaces with a tab.
+            let path = path.trim_end_matches('\t');
+            file = Some(root.join(path)).filter(|_| path != "/dev/null");
+            continue;
+        }
+        let (file, hunk) = match (&file, line.strip_prefix("@@ ")) {
+            (Some(file), Some(hunk)) => (file, hunk),
+            _ => continue,
+        };
+        let new = match hunk.split(' ').find_map(|range| range.strip_prefix('+')) {
+            Some(new) => new,
+            None => continue,
+        };
+        let (start, len) = match new.split_once(',') {
+            Some((start, len)) => (start.parse::<u64>(), len.parse::<u64>()),
+            None => (new.parse::<u64>(), Ok(1)),
+        };
+        let lines = changed.entry(file.clone()).or_default();
+        match (start, len) {
+            (Ok(start), Ok(0)) => {
+                lines.insert(start.max(1));
+            }
+            (Ok(start), Ok(len)) => lines.extend(start..start + len),
+            _ => {}
+        }
+    }
+    changed
+}
+
+/// Whether a region spans any of the `changed` lines.
+pub fn touches(region: &Region, changed: &ChangedLines) -> bool {
+    changed
+        .get(&region.file)
+        .is_some_and(|lines| lines.range(region.start.0..=region.end.0).next().is_some())
+}
+
+/// The public functions and modules of `api` that no covered region is in,
+/// largest first: code that was never linked into the fuzz target has no
+/// regions at all. Modules without public functions are left out, and so are
+/// the functions in a module that's uncovered as a whole, since the module
+/// stands for them.
+pub fn uncovered<'a>(api: &'a [ApiItem], regions: &Regions) -> Vec<&'a ApiItem> {
+    let is_covered = |item: &ApiItem| {
+        regions.iter().any(|(region, &covered)| {
+            covered
+                && item.spans.iter().any(|(file, (start, end))| {
+                    region.file == *file && region.start.0 >= *start && region.end.0 <= *end
+                })
+        })
+    };
+    let mut uncovered: Vec<&ApiItem> = api
+        .iter()
+        .filter(|item| item.kind == Kind::Function || functions_in(item, api) > 0)
+        .filter(|item| !is_covered(item))
+        .collect();
+    let modules: Vec<String> = uncovered
+        .iter()
+        .filter(|item| item.kind == Kind::Module)
+        .map(|module| format!("{}::", module.path))
+        .collect();
+    uncovered.retain(|item| {
+        !modules
+            .iter()
+            .any(|module| item.path.starts_with(module.as_str()))
+    });
+    uncovered.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.path.cmp(&b.path)));
+    uncovered
+}
+
+/// The number of public functions in a module of `api`.
+fn functions_in(module: &ApiItem, api: &[ApiItem]) -> usize {
+    let prefix = format!("{}::", module.path);
+    api.iter()
+        .filter(|item| item.kind == Kind::Function && item.path.starts_with(&prefix))
+        .count()
+}
+
+/// Render a table of the uncovered items of `api`, with the number of public
+/// functions in each module.
+pub fn render_uncovered(uncovered: &[&ApiItem], api: &[ApiItem]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "{:>7}  item", "lines");
+    for item in uncovered {
+        let _ = match item.kind {
+            Kind::Function => writeln!(out, "{:>7}  {}", item.lines, item.path),
+            Kind::Module => writeln!(
+                out,
+                "{:>7}  {} (module, {} public function(s))",
+                item.lines,
+                item.path,
+                functions_in(item, api)
+            ),
+        };
+    }
+    out
+}
+
+/// How many times each line of each file was executed, according to an lcov
+/// report.
+pub type LineCounts = BTreeMap<PathBuf, BTreeMap<u64, u64>>;
+
+/// Read the line counts out of the lcov report printed by `llvm-cov export
+/// -format=lcov`.
+pub fn parse_lcov(lcov: &str) -> LineCounts {
+    let mut files = LineCounts::new();
+    let mut file = None;
+    for line in lcov.lines() {
+        if let Some(path) = line.strip_prefix("SF:") {
+            file = Some(PathBuf::from(path));
+        } else if line == "end_of_record" {
+            file = None;
+        } else if let (Some(file), Some(da)) = (&file, line.strip_prefix("DA:")) {
+            let mut fields = da.split(',');
+            if let (Some(Ok(line)), Some(Ok(count))) = (
+                fields.next().map(str::parse),
+                fields.next().map(str::parse::<u64>),
+            ) {
+                *files
+                    .entry(file.clone())
+                    .or_default()
+                    .entry(line)
+                    .or_insert(0) += count;
+            }
+        }
+    }
+    files
+}
+
+/// The fraction of `lines` that were executed, or 1 if there are none.
+fn line_rate<'a>(lines: impl Iterator<Item = &'a u64>) -> (usize, usize, f64) {
+    let (mut covered, mut valid) = (0, 0);
+    for count in lines {
+        valid += 1;
+        covered += usize::from(*count > 0);
+    }
+    let rate = if valid == 0 {
+        1.0
+    } else {
+        covered as f64 / valid as f64
+    };
+    (covered, valid, rate)
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render line counts as a Cobertura XML report, with file names relative to
+/// `root` and a package for each directory.
+pub fn render_cobertura(files: &LineCounts, root: &Path, timestamp: u64) -> String {
+    let mut packages: BTreeMap<String, Vec<(String, _)>> = BTreeMap::new();
+    for (file, lines) in files {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        let name = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let package = match name.rsplit_once('/') {
+            Some((dir, _)) => dir.replace('/', "."),
+            None => String::new(),
+        };
+        packages.entry(package).or_default().push((name, lines));
+    }
+
+    let (covered, valid, rate) = line_rate(files.values().flat_map(|lines| lines.values()));
+    let mut out = String::new();
+    let _ = writeln!(out, "<?xml version=\"1.0\" ?>");
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">"
+    );
+    let _ = writeln!(
+        out,
+        "<coverage line-rate=\"{:.4}\" branch-rate=\"0\" lines-covered=\"{}\" \
+         lines-valid=\"{}\" branches-covered=\"0\" branches-valid=\"0\" complexity=\"0\" \
+         version=\"cargo-fuzz {}\" timestamp=\"{}\">",
+        rate,
+        covered,
+        valid,
+        env!("CARGO_PKG_VERSION"),
+        timestamp
+    );
+    let _ = writeln!(out, "  <sources>");
+    let _ = writeln!(
+        out,
+        "    <source>{}</source>",
+        xml_escape(&root.display().to_string())
+    );
+    let _ = writeln!(out, "  </sources>");
+    let _ = writeln!(out, "  <packages>");
+    for (package, classes) in &packages {
+        let (_, _, rate) = line_rate(classes.iter().flat_map(|(_, lines)| lines.values()));
+        let _ = writeln!(
+            out,
+            "    <package name=\"{}\" line-rate=\"{:.4}\" branch-rate=\"0\" complexity=\"0\">",
+            xml_escape(package),
+            rate
+        );
+        let _ = writeln!(out, "      <classes>");
+        for (name, lines) in classes {
+            let (_, _, rate) = line_rate(lines.values());
+            let _ = writeln!(
+                out,
+                "        <class name=\"{0}\" filename=\"{0}\" line-rate=\"{1:.4}\" \
+                 branch-rate=\"0\" complexity=\"0\">",
+                xml_escape(name),
+                rate
+            );
+            let _ = writeln!(out, "          <methods/>");
+            let _ = writeln!(out, "          <lines>");
+            for (line, count) in lines.iter() {
+                let _ = writeln!(
+                    out,
+                    "            <line number=\"{}\" hits=\"{}\"/>",
+                    line, count
+                );
+            }
+            let _ = writeln!(out, "          </lines>");
+            let _ = writeln!(out, "        </class>");
+        }
+        let _ = writeln!(out, "      </classes>");
+        let _ = writeln!(out, "    </package>");
+    }
+    let _ = writeln!(out, "  </packages>");
+    let _ = writeln!(out, "</coverage>");
+    out
+}
+
+/// The `source_files` of a Coveralls job: each file's name relative to
+/// `root`, the MD5 digest of its contents, and the count of each of its
+/// lines, or `null` for the lines that aren't code.
+pub fn coveralls_source_files(files: &LineCounts, root: &Path) -> Result<serde_json::Value> {
+    let mut source_files = Vec::new();
+    for (file, lines) in files {
+        let source =
+            fs::read(file).with_context(|| format!("failed to read {}", file.display()))?;
+        let line_count =
+            source.split(|&b| b == b'\n').count() - usize::from(source.ends_with(b"\n"));
+        let coverage: Vec<Option<u64>> = (1..=line_count as u64)
+            .map(|line| lines.get(&line).copied())
+            .collect();
+        let name = file
+            .strip_prefix(root)
+            .unwrap_or(file)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        source_files.push(serde_json::json!({
+            "name": name,
+            "source_digest": crate::utils::md5_hex(&source),
+            "coverage": coverage,
+        }));
+    }
+    Ok(serde_json::Value::Array(source_files))
+}
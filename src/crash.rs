@@ -0,0 +1,385 @@
+//! Parsing the output of a crashing fuzz target into a crash signature that we
+//! can use to tell unique bugs apart from duplicates.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::process::ExitStatus;
+
+/// How many of the innermost interesting stack frames make up a signature.
+const SIGNATURE_FRAMES: usize = 3;
+
+/// How many times one function has to appear in a trace for a crash to be
+/// taken for a stack overflow through recursion.
+const RECURSION_FRAMES: usize = 16;
+
+/// The kinds that a stack overflow shows up as when nothing reports it as
+/// one: a segfault on the guard page, as the sanitizer or libFuzzer sees it.
+const OVERFLOW_KINDS: &[&str] = &["SEGV", "deadly-signal", "stack-overflow"];
+
+/// Stack frames from these crates and runtimes are never the interesting part
+/// of a crash, so they are skipped when computing signatures.
+const IGNORED_FRAME_PREFIXES: &[&str] = &[
+    "std::",
+    "core::",
+    "alloc::",
+    "<std::",
+    "<core::",
+    "<alloc::",
+    "__rust",
+    "rust_panic",
+    "rust_begin_unwind",
+    "rust_fuzzer_test_input",
+    "libfuzzer_sys::",
+    "<libfuzzer_sys::",
+    "fuzzer::",
+    "LLVMFuzzerTestOneInput",
+    "__sanitizer",
+    "__asan",
+    "__msan",
+    "__tsan",
+    "__lsan",
+    "__interceptor",
+    "__libc_start",
+    "_start",
+    "main",
+    "abort",
+    "raise",
+    "panic_abort::",
+    "panic_unwind::",
+    "__scrt_common_main_seh",
+    "invoke_main",
+    "mainCRTStartup",
+    "BaseThreadInitThunk",
+    "RtlUserThreadStart",
+];
+
+/// The Windows exception codes that crashed processes exit with, with their
+/// names and the crash kinds they stand for.
+const EXCEPTIONS: &[(u32, &str, &str)] = &[
+    (0x8000_0003, "STATUS_BREAKPOINT", "breakpoint"),
+    (0xC000_0005, "STATUS_ACCESS_VIOLATION", "access-violation"),
+    (
+        0xC000_001D,
+        "STATUS_ILLEGAL_INSTRUCTION",
+        "illegal-instruction",
+    ),
+    (
+        0xC000_008C,
+        "STATUS_ARRAY_BOUNDS_EXCEEDED",
+        "array-bounds-exceeded",
+    ),
+    (
+        0xC000_0094,
+        "STATUS_INTEGER_DIVIDE_BY_ZERO",
+        "integer-divide-by-zero",
+    ),
+    (0xC000_0095, "STATUS_INTEGER_OVERFLOW", "integer-overflow"),
+    (0xC000_00FD, "STATUS_STACK_OVERFLOW", "stack-overflow"),
+    (0xC000_0374, "STATUS_HEAP_CORRUPTION", "heap-corruption"),
+    // What `std::process::abort` exits with, so also what a panic in a fuzz
+    // target ends in.
+    (0xC000_0409, "STATUS_STACK_BUFFER_OVERRUN", "fail-fast"),
+    (0xC000_0602, "STATUS_FAIL_FAST_EXCEPTION", "fail-fast"),
+];
+
+/// A crash observed while running a fuzz target on some input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Crash {
+    /// The kind of crash, e.g. `panic`, `heap-buffer-overflow`, or `timeout`.
+    pub kind: String,
+    /// The panic message or sanitizer description, if any.
+    pub message: Option<String>,
+    /// Source location of the panic, if any.
+    pub location: Option<String>,
+    /// Interesting stack frames, innermost first, with symbol hashes removed.
+    pub frames: Vec<Frame>,
+}
+
+/// A single symbolized stack frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub function: String,
+    pub location: Option<String>,
+}
+
+impl Crash {
+    /// Parse the stderr of a crashed fuzz target.
+    ///
+    /// Returns `None` if the output doesn't look like any crash we know about.
+    pub fn parse(output: &str) -> Option<Crash> {
+        let mut kind = None;
+        let mut message = None;
+        let mut location = None;
+        let mut frames = Vec::new();
+        // How many times each function appears in the trace, and whether
+        // Rust's handler for the guard page reported an overflow.
+        let mut depths = BTreeMap::<String, usize>::new();
+        let mut overflowed = false;
+
+        let mut lines = output.lines().peekable();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim();
+
+            if trimmed.starts_with("thread '") && trimmed.ends_with(" has overflowed its stack") {
+                overflowed = true;
+                continue;
+            }
+
+            if let Some(panic) = parse_panic(trimmed) {
+                // Newer Rust prints the message on the line after the location.
+                let (loc, msg) = match panic {
+                    (loc, Some(msg)) => (loc, Some(msg)),
+                    (loc, None) => (loc, lines.peek().map(|l| l.trim().to_string())),
+                };
+                if kind.is_none() || kind.as_deref() == Some("deadly-signal") {
+                    kind = Some("panic".to_string());
+                }
+                location = location.or(loc);
+                message = message.or(msg);
+                continue;
+            }
+
+            if let Some(k) = parse_sanitizer_error(trimmed) {
+                // The first error reported is the one that caused the crash,
+                // except that a panic aborting with a deadly signal is still
+                // a panic.
+                if kind.is_none() {
+                    kind = Some(k);
+                }
+                continue;
+            }
+
+            if let Some(frame) = parse_frame(trimmed) {
+                if is_ignored_frame(&frame.function) {
+                    continue;
+                }
+                *depths.entry(frame.function.clone()).or_default() += 1;
+                if !frames.contains(&frame) {
+                    frames.push(frame);
+                }
+            }
+        }
+
+        // A stack overflow is a segfault to the sanitizers, unless the frame
+        // that overflowed was an instrumented one, so tell it apart by the
+        // recursion that led up to it.
+        let recursion = depths
+            .into_iter()
+            .filter(|(_, depth)| *depth >= RECURSION_FRAMES)
+            .max_by_key(|(_, depth)| *depth);
+        let kind = match kind {
+            Some(k)
+                if OVERFLOW_KINDS.contains(&k.as_str()) && (overflowed || recursion.is_some()) =>
+            {
+                Some("stack-overflow".to_string())
+            }
+            None if overflowed => Some("stack-overflow".to_string()),
+            k => k,
+        }?;
+        if let (true, Some((function, depth))) = (kind == "stack-overflow", &recursion) {
+            message = message.or_else(|| {
+                Some(format!(
+                    "recursion through `{}`, {} frames deep in the trace",
+                    function, depth
+                ))
+            });
+        }
+        Some(Crash {
+            kind,
+            message,
+            location,
+            frames,
+        })
+    }
+
+    /// The deduplication signature of this crash.
+    ///
+    /// Panic messages often contain input-dependent values, so they only make
+    /// up the signature when there is no stack trace or location to go by.
+    pub fn signature(&self) -> String {
+        let mut sig = self.kind.clone();
+        if !self.frames.is_empty() {
+            for frame in self.frames.iter().take(SIGNATURE_FRAMES) {
+                sig.push('|');
+                sig.push_str(&frame.function);
+            }
+        } else if let Some(location) = &self.location {
+            sig.push('|');
+            sig.push_str(location);
+        } else if let Some(message) = &self.message {
+            sig.push('|');
+            sig.push_str(message);
+        }
+        sig
+    }
+
+    /// A short, stable hash of this crash's signature.
+    pub fn hash(&self) -> String {
+        format!("{:016x}", fnv1a(self.signature().as_bytes()))
+    }
+}
+
+impl fmt::Display for Crash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(location) = &self.location {
+            write!(f, "\n\tat {}", location)?;
+        }
+        for frame in self.frames.iter().take(SIGNATURE_FRAMES) {
+            write!(f, "\n\tin {}", frame.function)?;
+            if let Some(location) = &frame.location {
+                write!(f, " {}", location)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The name and crash kind of the Windows exception a process exited with, if
+/// it exited with one.
+pub fn exception(status: ExitStatus) -> Option<(&'static str, &'static str)> {
+    let code = status.code()? as u32;
+    EXCEPTIONS
+        .iter()
+        .find(|(c, _, _)| *c == code)
+        .map(|&(_, name, kind)| (name, kind))
+}
+
+/// Describe how a process exited, naming the Windows exception it exited
+/// with, if any, like `exit code: 0xc0000005 (STATUS_ACCESS_VIOLATION)`.
+pub fn describe_exit(status: ExitStatus) -> String {
+    match exception(status) {
+        Some((name, _)) => format!("{} ({})", status, name),
+        None => status.to_string(),
+    }
+}
+
+/// The 64-bit FNV-1a hash. Unlike `DefaultHasher`, this is stable across Rust
+/// releases, so crash and binary hashes can be stored and compared later.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        hash ^= u64::from(*b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Whether a line of a fuzz target's output starts the report of a crash
+/// that a sanitizer, libFuzzer, or Rust's stack overflow handler caught.
+pub fn starts_report(line: &str) -> bool {
+    let line = line.trim();
+    (line.contains("ERROR: ") && parse_sanitizer_error(line).is_some())
+        || (line.starts_with("thread '") && line.ends_with(" has overflowed its stack"))
+}
+
+/// Parse a `thread '...' panicked at ...` line into its location and message.
+///
+/// Older Rust prints `panicked at 'message', src/lib.rs:1:2` while newer Rust
+/// prints `panicked at src/lib.rs:1:2:` followed by the message on its own
+/// line, in which case the message is `None`. The newest Rust also puts the
+/// thread's ID after its name, as in `thread '<unnamed>' (1234) panicked at`.
+fn parse_panic(line: &str) -> Option<(Option<String>, Option<String>)> {
+    if !line.starts_with("thread '") {
+        return None;
+    }
+    let rest = &line[line.find(" panicked at ")? + " panicked at ".len()..];
+    if let Some(quoted) = rest.strip_prefix('\'') {
+        let end = quoted.rfind("', ")?;
+        let message = quoted[..end].to_string();
+        let location = quoted[end + "', ".len()..].to_string();
+        Some((Some(location), Some(message)))
+    } else {
+        Some((Some(rest.trim_end_matches(':').to_string()), None))
+    }
+}
+
+/// Parse sanitizer and libFuzzer error lines, returning the crash kind.
+fn parse_sanitizer_error(line: &str) -> Option<String> {
+    let line = line.trim_start_matches(|c: char| c == '=' || c.is_ascii_digit());
+    let rest = line
+        .trim_start()
+        .strip_prefix("ERROR: ")
+        .or_else(|| line.trim_start().strip_prefix("WARNING: "))?;
+    let (tool, description) = rest.split_at(rest.find(": ")?);
+    if !tool.ends_with("Sanitizer") && tool != "libFuzzer" {
+        return None;
+    }
+    let description = description[2..].trim();
+    if description.starts_with("detected memory leaks") {
+        return Some("memory-leak".to_string());
+    }
+    // Keep only the descriptive words, not addresses or sizes.
+    let kind = description
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .take_while(|w| !w.is_empty() && !w.starts_with("0x") && *w != "on" && *w != "after")
+        .collect::<Vec<_>>()
+        .join("-");
+    if kind.is_empty() {
+        None
+    } else {
+        Some(kind)
+    }
+}
+
+/// Parse a sanitizer-symbolized stack frame like
+/// `#3 0x55d0 in my_crate::parse::h0123456789abcdef /src/lib.rs:10:5`.
+///
+/// Frames without debug info end in the binary and offset, like
+/// `(/path/to/binary+0x18a7b5) (BuildId: 89...)`, or in the name of the
+/// codegen unit, like `my_crate.44b838da099f6030-cgu.0`. Neither is kept as
+/// the location.
+fn parse_frame(line: &str) -> Option<Frame> {
+    let rest = line.strip_prefix('#')?;
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+    let rest = &rest[rest.find(" in ")? + " in ".len()..];
+    let rest = match rest.rfind(" (BuildId: ") {
+        Some(build_id) => &rest[..build_id],
+        None => rest,
+    };
+    let (function, location) = match rest.rsplit_once(' ') {
+        Some((function, location))
+            if location.contains(':')
+                || location.starts_with('(')
+                || location.contains('/')
+                || location.contains("-cgu.") =>
+        {
+            let location = if location.starts_with('(') || location.contains("-cgu.") {
+                None
+            } else {
+                Some(location.to_string())
+            };
+            (function, location)
+        }
+        _ => (rest, None),
+    };
+    Some(Frame {
+        function: strip_symbol_hash(function.trim()).to_string(),
+        location,
+    })
+}
+
+/// Strip the trailing `::h0123456789abcdef` hash that rustc adds to symbols.
+pub fn strip_symbol_hash(function: &str) -> &str {
+    match function.rsplit_once("::h") {
+        Some((name, hash)) if hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+            name
+        }
+        _ => function,
+    }
+}
+
+/// Whether a frame's function is runtime or harness machinery. Entries ending
+/// in `::` or starting with `_` or `<` match as prefixes, all others exactly.
+fn is_ignored_frame(function: &str) -> bool {
+    IGNORED_FRAME_PREFIXES.iter().any(|prefix| {
+        if prefix.ends_with("::") || prefix.starts_with('_') || prefix.starts_with('<') {
+            function.starts_with(prefix)
+        } else {
+            function == *prefix
+        }
+    })
+}
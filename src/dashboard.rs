@@ -0,0 +1,172 @@
+//! The live dashboard of `cargo fuzz run --tui`.
+//!
+//! Rather than interleaving the output of every job on stderr, each worker's
+//! output goes to `fuzz/logs/<target>/worker-<n>.log`, and the dashboard keeps
+//! the latest status line of each worker to redraw a table of them in place.
+
+use crate::crash::Crash;
+use crate::libfuzzer;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Instant;
+
+/// Moves the cursor to the top left corner and clears the screen below it.
+pub const CLEAR: &str = "\x1b[H\x1b[J";
+
+pub struct Dashboard {
+    target: String,
+    logs: PathBuf,
+    start: Instant,
+    workers: Vec<Worker>,
+    initial_corpus: usize,
+    /// The number of inputs in the corpus directory now.
+    pub corpus: usize,
+    last_crash: Option<LastCrash>,
+}
+
+#[derive(Default)]
+struct Worker {
+    status: Option<libfuzzer::Status>,
+    crashes: u64,
+    /// How the worker exited, if it has.
+    exit: Option<String>,
+}
+
+struct LastCrash {
+    worker: usize,
+    artifact: PathBuf,
+    /// The kind and message of the crash, once its worker has exited and its
+    /// output has been parsed.
+    description: Option<String>,
+}
+
+impl Dashboard {
+    pub fn new(target: &str, jobs: usize, logs: &Path, corpus: usize) -> Dashboard {
+        Dashboard {
+            target: target.to_string(),
+            logs: logs.to_path_buf(),
+            start: Instant::now(),
+            workers: (0..jobs).map(|_| Worker::default()).collect(),
+            initial_corpus: corpus,
+            corpus,
+            last_crash: None,
+        }
+    }
+
+    /// Update a worker with a line of its output.
+    pub fn observe(&mut self, worker: usize, line: &str) {
+        if let Some(artifact) = libfuzzer::parse_written_artifact(line) {
+            let slow = artifact
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(libfuzzer::SLOW_UNIT_PREFIX));
+            if !slow {
+                self.workers[worker].crashes += 1;
+                self.last_crash = Some(LastCrash {
+                    worker,
+                    artifact,
+                    description: None,
+                });
+            }
+        } else if let Some(status) = libfuzzer::Status::parse(line) {
+            self.workers[worker].status = Some(status);
+        }
+    }
+
+    /// Record that a worker exited, and, if it crashed, the crash parsed from
+    /// its output. Workers that were stopped have no exit status worth
+    /// showing.
+    pub fn exited(&mut self, worker: usize, status: Option<ExitStatus>, crash: Option<Crash>) {
+        self.workers[worker].exit = Some(match status {
+            Some(status) if status.success() => "done".to_string(),
+            Some(status) => format!("exited with {}", status),
+            None => "stopped".to_string(),
+        });
+        if let (Some(last), Some(crash)) = (&mut self.last_crash, crash) {
+            if last.worker == worker {
+                last.description = Some(match crash.message {
+                    Some(message) => format!("{}: {}", crash.kind, message),
+                    None => crash.kind,
+                });
+            }
+        }
+    }
+
+    /// Render the dashboard as a table of the workers, their totals, and the
+    /// growth of the corpus and the last crash below it.
+    pub fn render(&self) -> String {
+        let elapsed = self.start.elapsed().as_secs();
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "Fuzzing `{}` with {} worker(s) for {:02}:{:02}:{:02}; logs in {}\n",
+            self.target,
+            self.workers.len(),
+            elapsed / 3600,
+            elapsed / 60 % 60,
+            elapsed % 60,
+            self.logs.display()
+        );
+        let _ = writeln!(
+            out,
+            "{:<8} {:>12} {:>8} {:>8} {:>8} {:>8} {:>8}  state",
+            "worker", "execs", "exec/s", "cov", "ft", "corpus", "crashes"
+        );
+        let number = |n: Option<u64>| n.map_or("-".to_string(), |n| n.to_string());
+        let (mut execs, mut execs_per_sec, mut crashes) = (0, 0, 0);
+        for (i, worker) in self.workers.iter().enumerate() {
+            let status = worker.status.as_ref();
+            execs += status.map_or(0, |s| s.execs);
+            execs_per_sec += status.and_then(|s| s.execs_per_sec).unwrap_or(0);
+            crashes += worker.crashes;
+            let state = match (&worker.exit, status) {
+                (Some(exit), _) => exit.as_str(),
+                (None, Some(_)) => "running",
+                (None, None) => "starting",
+            };
+            let _ = writeln!(
+                out,
+                "{:<8} {:>12} {:>8} {:>8} {:>8} {:>8} {:>8}  {}",
+                i,
+                number(status.map(|s| s.execs)),
+                number(status.and_then(|s| s.execs_per_sec)),
+                number(status.and_then(|s| s.coverage)),
+                number(status.and_then(|s| s.features)),
+                number(status.and_then(|s| s.corpus_entries)),
+                worker.crashes,
+                state
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{:<8} {:>12} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "total", execs, execs_per_sec, "", "", "", crashes
+        );
+
+        let _ = writeln!(
+            out,
+            "\ncorpus:     {} input(s), {:+} since the start",
+            self.corpus,
+            self.corpus as i64 - self.initial_corpus as i64
+        );
+        match &self.last_crash {
+            Some(crash) => {
+                let name = crash.artifact.file_name().unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "last crash: worker {}, {}",
+                    crash.worker,
+                    name.to_string_lossy()
+                );
+                if let Some(description) = &crash.description {
+                    let _ = writeln!(out, "            {}", description);
+                }
+            }
+            None => {
+                let _ = writeln!(out, "last crash: none");
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,209 @@
+//! The input decoder that `cargo fuzz fmt` builds for a fuzz target, and the
+//! benchmark that `cargo fuzz bench --corpus` builds the same way.
+//!
+//! The decoder is the fuzz target's own source, built as an ordinary binary
+//! against a stand-in for `libfuzzer-sys` whose `fuzz_target!` reads an input
+//! file, decodes it the way the real macro would, and prints its `Debug`
+//! formatting. It doesn't need a nightly toolchain, instrumentation, or
+//! libFuzzer, so it builds quickly for any target. The benchmark's stand-in
+//! runs the fuzz target on inputs instead, and times each run, and the one
+//! for `cargo fuzz miri` runs it once on an input under Miri.
+//!
+//! Their manifest copies the fuzz crate's dependencies and features, with
+//! relative paths made absolute, and swaps the stand-in in for
+//! `libfuzzer-sys`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::Path;
+use toml::value::Table;
+use toml::Value;
+
+/// The name of the stand-in package, which the fuzz target refers to as
+/// `libfuzzer_sys`.
+const LIBFUZZER_SYS: &str = "libfuzzer-sys";
+
+/// What a fuzz target built against a stand-in for `libfuzzer-sys` does
+/// instead of fuzzing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shim {
+    /// Print the `Debug` formatting of an input, for `cargo fuzz fmt`.
+    Decoder,
+    /// Time the fuzz target on inputs, for `cargo fuzz bench --corpus`.
+    Bench,
+    /// Run the fuzz target on an input under Miri, for `cargo fuzz miri`.
+    Miri,
+}
+
+impl Shim {
+    /// The name of the directory it's built in, under the target directory.
+    pub fn name(self) -> &'static str {
+        match self {
+            Shim::Decoder => "decoder",
+            Shim::Bench => "bench",
+            Shim::Miri => "miri",
+        }
+    }
+
+    /// The command that builds it.
+    pub fn command(self) -> &'static str {
+        match self {
+            Shim::Decoder => "cargo fuzz fmt",
+            Shim::Bench => "cargo fuzz bench",
+            Shim::Miri => "cargo fuzz miri",
+        }
+    }
+}
+
+/// The version of `arbitrary` that the fuzz crate's `libfuzzer-sys` resolves
+/// to, read from the output of `cargo metadata --format-version 1`, so that
+/// the stand-in re-exports the same `Arbitrary` trait that the fuzz target's
+/// types implement.
+pub fn arbitrary_version(metadata: &str) -> Result<String> {
+    let metadata: serde_json::Value = serde_json::from_str(metadata)
+        .context("could not decode the output of `cargo metadata`")?;
+    let packages = metadata["packages"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice);
+    let libfuzzer_sys = packages
+        .iter()
+        .find(|p| p["name"] == LIBFUZZER_SYS)
+        .ok_or_else(|| anyhow!("the fuzz crate doesn't depend on `libfuzzer-sys`"))?;
+    let nodes = metadata["resolve"]["nodes"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice);
+    let arbitrary = nodes
+        .iter()
+        .find(|node| node["id"] == libfuzzer_sys["id"])
+        .and_then(|node| node["deps"].as_array())
+        .and_then(|deps| deps.iter().find(|dep| dep["name"] == "arbitrary"))
+        .ok_or_else(|| {
+            anyhow!(
+                "`libfuzzer-sys` {} doesn't support `Arbitrary` inputs",
+                libfuzzer_sys["version"].as_str().unwrap_or("?")
+            )
+        })?;
+    packages
+        .iter()
+        .find(|p| p["id"] == arbitrary["pkg"])
+        .and_then(|p| p["version"].as_str())
+        .map(String::from)
+        .ok_or_else(|| anyhow!("could not find the version of `arbitrary` in `cargo metadata`"))
+}
+
+/// The manifest of the decoder or benchmark for the fuzz target `target`,
+/// whose source is at `source`, given the fuzz crate's manifest, the directory
+/// it's in, and the directory of the stand-in for `libfuzzer-sys`.
+pub fn manifest(
+    shim: Shim,
+    fuzz_manifest: &Value,
+    fuzz_dir: &Path,
+    shim_dir: &Path,
+    target: &str,
+    source: &Path,
+) -> Result<String> {
+    let mut package = Table::new();
+    package.insert("name".into(), format!("cargo-fuzz-{}", shim.name()).into());
+    package.insert("version".into(), "0.0.0".into());
+    package.insert("publish".into(), false.into());
+    if let Some(edition) = fuzz_manifest.get("package").and_then(|p| p.get("edition")) {
+        package.insert("edition".into(), edition.clone());
+    }
+
+    let mut manifest = Table::new();
+    manifest.insert("package".into(), package.into());
+    if let Some(dependencies) = fuzz_manifest.get("dependencies") {
+        let dependencies = decoder_dependencies(shim, dependencies, fuzz_dir, shim_dir)?;
+        manifest.insert("dependencies".into(), dependencies);
+    }
+    if let Some(Value::Table(targets)) = fuzz_manifest.get("target") {
+        let mut decoder_targets = Table::new();
+        for (cfg, table) in targets {
+            if let Some(dependencies) = table.get("dependencies") {
+                let mut table = Table::new();
+                table.insert(
+                    "dependencies".into(),
+                    decoder_dependencies(shim, dependencies, fuzz_dir, shim_dir)?,
+                );
+                decoder_targets.insert(cfg.clone(), table.into());
+            }
+        }
+        manifest.insert("target".into(), decoder_targets.into());
+    }
+    if let Some(features) = fuzz_manifest.get("features") {
+        manifest.insert("features".into(), features.clone());
+    }
+    if let Some(Value::Table(patches)) = fuzz_manifest.get("patch") {
+        let mut decoder_patches = Table::new();
+        for (source, dependencies) in patches {
+            let dependencies = decoder_dependencies(shim, dependencies, fuzz_dir, shim_dir)?;
+            decoder_patches.insert(source.clone(), dependencies);
+        }
+        manifest.insert("patch".into(), decoder_patches.into());
+    }
+
+    let mut bin = Table::new();
+    bin.insert("name".into(), target.into());
+    bin.insert("path".into(), source.display().to_string().into());
+    bin.insert("test".into(), false.into());
+    bin.insert("doc".into(), false.into());
+    manifest.insert("bin".into(), Value::Array(vec![bin.into()]));
+    // Keep this from being part of any workspace it happens to be in.
+    manifest.insert("workspace".into(), Table::new().into());
+
+    let manifest = toml::to_string(&Value::Table(manifest))
+        .with_context(|| format!("could not encode the {}'s manifest", shim.name()))?;
+    Ok(format!(
+        "# Generated by `{}` from {}.\n\n{}",
+        shim.command(),
+        fuzz_dir.join("Cargo.toml").display(),
+        manifest
+    ))
+}
+
+/// A table of dependencies with paths made absolute, and `libfuzzer-sys`
+/// replaced with the stand-in in `shim_dir`, keeping the features it's
+/// used with.
+fn decoder_dependencies(
+    shim: Shim,
+    dependencies: &Value,
+    fuzz_dir: &Path,
+    shim_dir: &Path,
+) -> Result<Value> {
+    let dependencies = match dependencies {
+        Value::Table(dependencies) => dependencies,
+        _ => bail!("expected a table of dependencies in the fuzz crate's manifest"),
+    };
+    let mut decoder = Table::new();
+    for (name, spec) in dependencies {
+        let mut spec = match spec {
+            Value::Table(spec) => spec.clone(),
+            version => {
+                let mut spec = Table::new();
+                spec.insert("version".into(), version.clone());
+                spec
+            }
+        };
+        if spec.get("workspace").and_then(Value::as_bool) == Some(true) {
+            bail!(
+                "the fuzz crate inherits the `{}` dependency from its workspace, which the \
+                 {} for `{}` can't",
+                name,
+                shim.name(),
+                shim.command()
+            );
+        }
+        let is_libfuzzer_sys =
+            spec.get("package").and_then(Value::as_str).unwrap_or(name) == LIBFUZZER_SYS;
+        if is_libfuzzer_sys {
+            for key in ["version", "git", "branch", "tag", "rev", "registry"] {
+                spec.remove(key);
+            }
+            spec.insert("path".into(), shim_dir.display().to_string().into());
+        } else if let Some(path) = spec.get("path").and_then(Value::as_str) {
+            let path = fuzz_dir.join(path).display().to_string();
+            spec.insert("path".into(), path.into());
+        }
+        decoder.insert(name.clone(), spec.into());
+    }
+    Ok(decoder.into())
+}
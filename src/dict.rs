@@ -0,0 +1,308 @@
+//! Generating libFuzzer dictionaries from Rust source code, and merging them.
+//!
+//! This is a small lexer, not a parser: it finds the string and byte string
+//! literals in a source file, plus the integer literals used as match patterns,
+//! since those are the magic values a fuzzer has the hardest time guessing.
+//! Format strings, i.e. string literals that are the first argument of a macro
+//! like `println!` or `panic!`, are skipped.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// libFuzzer ignores dictionary entries longer than this.
+const MAX_ENTRY_LEN: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(Vec<u8>),
+    Integer { value: u64, width: Option<usize> },
+    Punct(char),
+    Ident,
+}
+
+/// Collect the dictionary entries found in a Rust source file.
+pub fn scan(source: &str, entries: &mut BTreeSet<Vec<u8>>) {
+    let tokens = tokenize(source);
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Literal(bytes) => {
+                let is_format_string = i >= 2
+                    && tokens[i - 2] == Token::Punct('!')
+                    && matches!(tokens[i - 1], Token::Punct('(' | '[' | '{'));
+                if !is_format_string && !bytes.is_empty() && bytes.len() <= MAX_ENTRY_LEN {
+                    entries.insert(bytes.clone());
+                }
+            }
+            Token::Integer { value, width } => {
+                let is_pattern = matches!(
+                    tokens.get(i + 1..i + 3),
+                    Some([Token::Punct('='), Token::Punct('>')]) | Some([Token::Punct('|'), _])
+                );
+                // Single bytes are cheap for the fuzzer to find on its own.
+                if is_pattern && *value > 0xff {
+                    let width = width.unwrap_or(match *value {
+                        0..=0xffff => 2,
+                        0x1_0000..=0xffff_ffff => 4,
+                        _ => 8,
+                    });
+                    let bytes = value.to_le_bytes();
+                    entries.insert(bytes[..width].to_vec());
+                    entries.insert(bytes[..width].iter().rev().copied().collect());
+                }
+            }
+            Token::Punct(_) | Token::Ident => {}
+        }
+    }
+}
+
+/// Render dictionary entries in libFuzzer's dictionary format.
+pub fn render(entries: &BTreeSet<Vec<u8>>) -> String {
+    let mut dict = String::from("# Generated by `cargo fuzz dict`.\n");
+    for entry in entries {
+        dict.push('"');
+        for &b in entry {
+            match b {
+                b'"' | b'\\' => write!(dict, "\\x{:02x}", b).unwrap(),
+                0x20..=0x7e => dict.push(b as char),
+                _ => write!(dict, "\\x{:02x}", b).unwrap(),
+            }
+        }
+        dict.push_str("\"\n");
+    }
+    dict
+}
+
+/// Merge dictionaries, given as their names and contents, into one, leaving
+/// out the entries that an earlier dictionary already has. Engines only take
+/// a single dictionary.
+pub fn merge(dicts: &[(String, String)]) -> String {
+    let mut dict = String::from("# Merged by `cargo fuzz run`.\n");
+    let mut seen = BTreeSet::new();
+    for (name, contents) in dicts {
+        writeln!(dict, "\n# {}", name).unwrap();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            // Entries are `"value"` or `name="value"`, and only the value
+            // matters to the fuzzer.
+            let value = line.find('"').map_or(line, |quote| &line[quote..]);
+            if seen.insert(value.to_string()) {
+                dict.push_str(line);
+                dict.push('\n');
+            }
+        }
+    }
+    dict
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in &mut chars {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                skip_block_comment(&mut chars);
+            }
+            '"' => tokens.push(Token::Literal(string(&mut chars))),
+            '\'' => skip_char_or_lifetime(&mut chars),
+            'b' if chars.peek() == Some(&'"') => {
+                chars.next();
+                tokens.push(Token::Literal(string(&mut chars)));
+            }
+            'b' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                skip_char_or_lifetime(&mut chars);
+            }
+            'b' | 'r' if is_raw_string_start(c, &chars) => {
+                if c == 'b' {
+                    chars.next();
+                }
+                tokens.push(Token::Literal(raw_string(&mut chars)));
+            }
+            c if c.is_ascii_digit() => tokens.push(integer(c, &mut chars)),
+            c if c.is_alphabetic() || c == '_' => {
+                // This also covers raw identifiers, since `#` can't start a
+                // token we care about.
+                while chars
+                    .peek()
+                    .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                {
+                    chars.next();
+                }
+                tokens.push(Token::Ident);
+            }
+            c if c.is_whitespace() => {}
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    tokens
+}
+
+fn skip_block_comment(chars: &mut Peekable<Chars>) {
+    let mut depth = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'/') => {
+                chars.next();
+                depth -= 1;
+                if depth == 0 {
+                    return;
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                depth += 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Skip a character literal like `'a'` or `'\n'`, or a lifetime like `'a`,
+/// whose opening quote has already been consumed.
+fn skip_char_or_lifetime(chars: &mut Peekable<Chars>) {
+    match chars.next() {
+        Some('\\') => {
+            // Skip the escaped character, which might be a quote itself.
+            chars.next();
+            for c in chars {
+                if c == '\'' {
+                    break;
+                }
+            }
+        }
+        Some(_) if chars.peek() == Some(&'\'') => {
+            chars.next();
+        }
+        _ => {}
+    }
+}
+
+/// Whether `c`, followed by `chars`, starts a raw string like `r"..."`,
+/// `r#"..."#`, or `br"..."`.
+fn is_raw_string_start(c: char, chars: &Peekable<Chars>) -> bool {
+    let mut rest = chars.clone();
+    if c == 'b' && rest.next() != Some('r') {
+        return false;
+    }
+    loop {
+        match rest.next() {
+            Some('#') => {}
+            Some('"') => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Read a raw string literal, after its `r` or `br`.
+fn raw_string(chars: &mut Peekable<Chars>) -> Vec<u8> {
+    let mut hashes = 0;
+    while chars.next() == Some('#') {
+        hashes += 1;
+    }
+    let mut literal = String::new();
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            let mut closing = chars.clone();
+            if (0..hashes).all(|_| closing.next() == Some('#')) {
+                for _ in 0..hashes {
+                    chars.next();
+                }
+                break;
+            }
+        }
+        literal.push(c);
+    }
+    literal.into_bytes()
+}
+
+/// Read a string or byte string literal, after its opening quote, resolving
+/// escapes.
+fn string(chars: &mut Peekable<Chars>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => match chars.next() {
+                Some('n') => bytes.push(b'\n'),
+                Some('r') => bytes.push(b'\r'),
+                Some('t') => bytes.push(b'\t'),
+                Some('0') => bytes.push(0),
+                Some('x') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    bytes.push(u8::from_str_radix(&hex, 16).unwrap_or(0));
+                }
+                Some('u') => {
+                    let hex: String = chars
+                        .by_ref()
+                        .take_while(|c| *c != '}')
+                        .filter(|c| c.is_ascii_hexdigit())
+                        .collect();
+                    let c = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER);
+                    bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                // A line continuation skips the newline and any leading
+                // whitespace on the next line.
+                Some('\n') => {
+                    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                        chars.next();
+                    }
+                }
+                Some(c) => bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                None => break,
+            },
+            c => bytes.extend(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    bytes
+}
+
+/// Read an integer literal like `0x7f45_4c46u32`, starting with `first`.
+///
+/// Floats come out as a garbage integer followed by more tokens, but are only
+/// used if they're followed by `=>`, which floats can't be.
+fn integer(first: char, chars: &mut Peekable<Chars>) -> Token {
+    let mut literal = first.to_string();
+    while chars
+        .peek()
+        .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_')
+    {
+        literal.push(chars.next().unwrap());
+    }
+    let literal = literal.replace('_', "");
+    let (radix, digits) = match literal.get(..2) {
+        Some("0x") => (16, &literal[2..]),
+        Some("0o") => (8, &literal[2..]),
+        Some("0b") => (2, &literal[2..]),
+        _ => (10, &literal[..]),
+    };
+    let suffix_start = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let (digits, suffix) = digits.split_at(suffix_start);
+    let width = match suffix {
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" => Some(4),
+        "u64" | "i64" => Some(8),
+        _ => None,
+    };
+    match u64::from_str_radix(digits, radix) {
+        Ok(value) => Token::Integer { value, width },
+        Err(_) => Token::Ident,
+    }
+}
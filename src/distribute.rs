@@ -0,0 +1,354 @@
+//! The protocol between `cargo fuzz serve`, which coordinates fuzzing across
+//! machines, and the `cargo fuzz worker`s that connect to it.
+//!
+//! It's plain HTTP/1.1, one request per connection:
+//!
+//! * `GET /corpus/<target>` lists the hashes of the coordinator's corpus
+//!   inputs, one per line.
+//! * `GET /corpus/<target>/<hash>` downloads an input.
+//! * `PUT /corpus/<target>/<hash>` uploads an input, whose hash must match
+//!   its contents.
+//! * `PUT /artifacts/<target>/<name>` uploads a crash artifact.
+//!
+//! Inputs are named by the same hash of their contents that the corpus
+//! provenance uses, so each side only transfers the inputs the other lacks.
+
+use crate::crash;
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The largest input or artifact the coordinator accepts.
+const MAX_BODY: usize = 64 << 20;
+
+/// The hash that inputs are named by.
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:016x}", crash::fnv1a(data))
+}
+
+/// The inputs in a corpus directory, by the hash of their contents.
+pub fn hash_corpus(dir: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    let mut inputs = BTreeMap::new();
+    if !dir.is_dir() {
+        return Ok(inputs);
+    }
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry
+            .with_context(|| format!("failed to read directory entry inside {}", dir.display()))?
+            .path();
+        if path.is_file() {
+            let data =
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+            inputs.insert(content_hash(&data), path);
+        }
+    }
+    Ok(inputs)
+}
+
+/// Whether `name` is safe to use as a file name in a corpus or artifacts
+/// directory.
+fn is_file_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+}
+
+/// Where the coordinator keeps one fuzz target's corpus and artifacts.
+pub struct TargetDirs {
+    pub corpus: PathBuf,
+    pub artifacts: PathBuf,
+}
+
+/// The corpora and artifacts that the coordinator serves.
+pub struct Store {
+    targets: BTreeMap<String, TargetDirs>,
+    /// The inputs in each target's corpus, as of the last time a worker
+    /// listed them.
+    inputs: Mutex<BTreeMap<String, BTreeMap<String, PathBuf>>>,
+}
+
+impl Store {
+    pub fn new(targets: BTreeMap<String, TargetDirs>) -> Store {
+        Store {
+            targets,
+            inputs: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Answer one request from a worker.
+    pub fn handle(&self, stream: TcpStream) -> Result<()> {
+        let peer = stream.peer_addr().ok();
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+        // Checking that the coordinator is up connects without a request.
+        if reader.fill_buf()?.is_empty() {
+            return Ok(());
+        }
+        let (status, body) = match read_request(&mut reader) {
+            Ok((method, path, body)) => match self.respond(&method, &path, body, peer) {
+                Ok(response) => response,
+                Err(e) => (500, format!("{:#}\n", e).into_bytes()),
+            },
+            Err(e) => (400, format!("{:#}\n", e).into_bytes()),
+        };
+        write_message(
+            &mut stream,
+            &format!("HTTP/1.1 {} {}", status, reason(status)),
+            &[],
+            &body,
+        )
+    }
+
+    fn respond(
+        &self,
+        method: &str,
+        path: &str,
+        body: Vec<u8>,
+        peer: Option<SocketAddr>,
+    ) -> Result<(u16, Vec<u8>)> {
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let (kind, target, name) = match parts.as_slice() {
+            [kind, target] => (*kind, *target, None),
+            [kind, target, name] => (*kind, *target, Some(*name)),
+            _ => return Ok((404, b"not found\n".to_vec())),
+        };
+        let dirs = match self.targets.get(target) {
+            Some(dirs) => dirs,
+            None => {
+                return Ok((
+                    404,
+                    format!("no fuzz target named `{}`\n", target).into_bytes(),
+                ))
+            }
+        };
+        let from = peer.map_or("a worker".to_string(), |peer| peer.to_string());
+        match (method, kind, name) {
+            ("GET", "corpus", None) => {
+                let inputs = hash_corpus(&dirs.corpus)?;
+                let list: String = inputs.keys().map(|hash| format!("{}\n", hash)).collect();
+                self.inputs
+                    .lock()
+                    .unwrap()
+                    .insert(target.to_string(), inputs);
+                Ok((200, list.into_bytes()))
+            }
+            ("GET", "corpus", Some(hash)) => {
+                let path = self
+                    .inputs
+                    .lock()
+                    .unwrap()
+                    .get(target)
+                    .and_then(|inputs| inputs.get(hash).cloned());
+                match path.and_then(|path| fs::read(path).ok()) {
+                    Some(data) => Ok((200, data)),
+                    None => Ok((404, b"no such input\n".to_vec())),
+                }
+            }
+            ("PUT", "corpus", Some(hash)) => {
+                if content_hash(&body) != hash {
+                    return Ok((400, b"the input doesn't match its hash\n".to_vec()));
+                }
+                let mut inputs = self.inputs.lock().unwrap();
+                let inputs = match inputs.entry(target.to_string()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(hash_corpus(&dirs.corpus)?),
+                };
+                if inputs.contains_key(hash) {
+                    return Ok((200, Vec::new()));
+                }
+                fs::create_dir_all(&dirs.corpus).with_context(|| {
+                    format!("could not make a directory at {}", dirs.corpus.display())
+                })?;
+                let path = dirs.corpus.join(hash);
+                fs::write(&path, &body)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                inputs.insert(hash.to_string(), path);
+                Ok((201, Vec::new()))
+            }
+            ("PUT", "artifacts", Some(name)) if is_file_name(name) => {
+                let path = dirs.artifacts.join(name);
+                if path.exists() {
+                    return Ok((200, Vec::new()));
+                }
+                fs::create_dir_all(&dirs.artifacts).with_context(|| {
+                    format!("could not make a directory at {}", dirs.artifacts.display())
+                })?;
+                fs::write(&path, &body)
+                    .with_context(|| format!("failed to write {}", path.display()))?;
+                eprintln!("{}: received {} from {}", target, name, from);
+                Ok((201, Vec::new()))
+            }
+            _ => Ok((404, b"not found\n".to_vec())),
+        }
+    }
+}
+
+/// A connection to a coordinator, for a worker.
+pub struct Client {
+    addr: String,
+}
+
+impl Client {
+    /// A client of the coordinator at `addr`, like `fuzz-01:7878` or
+    /// `http://fuzz-01:7878`.
+    pub fn new(addr: &str) -> Client {
+        let addr = addr.strip_prefix("http://").unwrap_or(addr);
+        Client {
+            addr: addr.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The hashes of the inputs in the coordinator's corpus of `target`.
+    pub fn list(&self, target: &str) -> Result<Vec<String>> {
+        let body = self.request("GET", &format!("/corpus/{}", target), &[])?;
+        let list = String::from_utf8(body).context("the coordinator sent an invalid list")?;
+        Ok(list.lines().map(String::from).collect())
+    }
+
+    /// Download an input from the coordinator's corpus of `target`.
+    pub fn download(&self, target: &str, hash: &str) -> Result<Vec<u8>> {
+        let data = self.request("GET", &format!("/corpus/{}/{}", target, hash), &[])?;
+        if content_hash(&data) != hash {
+            bail!("the coordinator sent an input that doesn't match its hash");
+        }
+        Ok(data)
+    }
+
+    /// Upload an input to the coordinator's corpus of `target`.
+    pub fn upload(&self, target: &str, data: &[u8]) -> Result<()> {
+        let path = format!("/corpus/{}/{}", target, content_hash(data));
+        self.request("PUT", &path, data).map(drop)
+    }
+
+    /// Upload a crash artifact of `target` to the coordinator.
+    pub fn upload_artifact(&self, target: &str, name: &str, data: &[u8]) -> Result<()> {
+        self.request("PUT", &format!("/artifacts/{}/{}", target, name), data)
+            .map(drop)
+    }
+
+    fn request(&self, method: &str, path: &str, body: &[u8]) -> Result<Vec<u8>> {
+        let fail = || {
+            format!(
+                "failed to {} {} on the coordinator at {}",
+                method, path, self.addr
+            )
+        };
+        let mut stream = TcpStream::connect(&self.addr).with_context(fail)?;
+        write_message(
+            &mut stream,
+            &format!("{} {} HTTP/1.1", method, path),
+            &[("Host", &self.addr)],
+            body,
+        )
+        .with_context(fail)?;
+        let mut reader = BufReader::new(stream);
+        let (status, body) = read_response(&mut reader).with_context(fail)?;
+        if !(200..300).contains(&status) {
+            bail!(
+                "{}: {} {}",
+                fail(),
+                status,
+                String::from_utf8_lossy(&body).trim()
+            );
+        }
+        Ok(body)
+    }
+}
+
+pub fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+pub fn write_message(
+    stream: &mut TcpStream,
+    start: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> Result<()> {
+    let mut head = format!("{}\r\n", start);
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Read a message's start line and headers, returning the start line and the
+/// length of its body.
+fn read_head(reader: &mut impl BufRead) -> Result<(String, Option<usize>)> {
+    let mut start = String::new();
+    reader.read_line(&mut start)?;
+    let mut len = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("the connection closed in the middle of the headers");
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                let value = value.trim().parse().context("invalid Content-Length")?;
+                len = Some(value);
+            }
+        }
+    }
+    if len.is_some_and(|len| len > MAX_BODY) {
+        bail!("the body is larger than {} bytes", MAX_BODY);
+    }
+    Ok((start.trim_end().to_string(), len))
+}
+
+pub fn read_request(reader: &mut impl BufRead) -> Result<(String, String, Vec<u8>)> {
+    let (start, len) = read_head(reader)?;
+    let mut words = start.split(' ');
+    let (method, path) = match (words.next(), words.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => bail!("invalid request line: {}", start),
+    };
+    let mut body = vec![0; len.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    Ok((method, path, body))
+}
+
+fn read_response(reader: &mut impl BufRead) -> Result<(u16, Vec<u8>)> {
+    let (start, len) = read_head(reader)?;
+    let status = start
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| anyhow!("invalid status line: {}", start))?;
+    let mut body = Vec::new();
+    match len {
+        Some(len) => {
+            body.resize(len, 0);
+            reader.read_exact(&mut body)?;
+        }
+        None => {
+            reader.read_to_end(&mut body)?;
+        }
+    }
+    Ok((status, body))
+}
@@ -0,0 +1,289 @@
+//! The checks that `cargo fuzz doctor` runs on the environment, for the
+//! problems with toolchains, sanitizers and the system that commonly stop
+//! fuzzing from working.
+
+use crate::options::Sanitizer;
+use crate::utils;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// Fuzzing works, but some commands or features won't.
+    Warning,
+    /// Fuzzing won't work.
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about it, unless the check passed.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Check {
+        Check {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        name: &'static str,
+        status: Status,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Check {
+        Check {
+            name,
+            status,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check for fuzzing on `triple`.
+pub fn checks(triple: &str) -> Vec<Check> {
+    let mut checks = vec![
+        toolchain(),
+        c_compiler(),
+        llvm_tools(),
+        sanitizer_runtimes(triple),
+    ];
+    if cfg!(target_os = "linux") {
+        checks.extend(ptrace_scope());
+        checks.extend(core_dumps());
+    }
+    checks
+}
+
+/// Fuzz targets are built with `-Zsanitizer` and other unstable flags, which
+/// need a nightly compiler.
+fn toolchain() -> Check {
+    let name = "toolchain";
+    let release = match utils::rustc_release() {
+        Some(release) => release,
+        None => {
+            return Check::problem(
+                name,
+                Status::Error,
+                "could not run `rustc`",
+                "install Rust with rustup from https://rustup.rs",
+            )
+        }
+    };
+    if release.contains("nightly") || release.contains("dev") {
+        Check::ok(name, format!("rustc {}", release))
+    } else if env::var("RUSTC_BOOTSTRAP").is_ok_and(|v| v == "1") {
+        Check::ok(
+            name,
+            format!(
+                "rustc {}, with nightly features from RUSTC_BOOTSTRAP",
+                release
+            ),
+        )
+    } else {
+        Check::problem(
+            name,
+            Status::Error,
+            format!("rustc {} is not a nightly compiler", release),
+            "run `rustup toolchain install nightly` and use `cargo +nightly fuzz`, \
+             or `rustup override set nightly` in this project",
+        )
+    }
+}
+
+/// libfuzzer-sys compiles libFuzzer, which is C++, when it's built.
+fn c_compiler() -> Check {
+    let name = "C++ compiler";
+    let compiler = env::var("CXX").ok().or_else(|| {
+        ["c++", "clang++", "g++", "cl"]
+            .iter()
+            .find(|cxx| {
+                utils::find_on_path(&format!("{}{}", cxx, env::consts::EXE_SUFFIX)).is_some()
+            })
+            .map(|cxx| cxx.to_string())
+    });
+    match compiler {
+        Some(compiler) => Check::ok(name, compiler),
+        None => Check::problem(
+            name,
+            Status::Error,
+            "no C++ compiler found to build libFuzzer with",
+            "install clang or g++, or set $CXX to a C++ compiler",
+        ),
+    }
+}
+
+/// `cargo fuzz coverage` and the commands that replay coverage need
+/// `llvm-profdata` and `llvm-cov` matching rustc's LLVM.
+fn llvm_tools() -> Check {
+    let name = "llvm-tools";
+    match (
+        utils::llvm_tool("llvm-profdata"),
+        utils::llvm_tool("llvm-cov"),
+    ) {
+        (Ok(profdata), Ok(_)) => Check::ok(name, profdata.display().to_string()),
+        _ => Check::problem(
+            name,
+            Status::Warning,
+            "`llvm-profdata` or `llvm-cov` is missing, so `cargo fuzz coverage` won't work",
+            "run `rustup component add llvm-tools-preview`",
+        ),
+    }
+}
+
+/// The sanitizer runtimes are shipped with the standard library of each
+/// target that supports them, except on Windows, where the MSVC toolset has
+/// AddressSanitizer's.
+fn sanitizer_runtimes(triple: &str) -> Check {
+    let name = "sanitizer runtimes";
+    if triple.ends_with("-windows-msvc") {
+        return match utils::msvc_asan_runtime_dir(triple) {
+            Some(dir) => Check::ok(name, format!("address, in {}", dir.display())),
+            None => Check::problem(
+                name,
+                Status::Warning,
+                "could not find the MSVC AddressSanitizer runtime",
+                "run cargo fuzz from a Visual Studio developer prompt, which sets \
+                 $VCToolsInstallDir, or build with `--sanitizer none`",
+            ),
+        };
+    }
+    let lib = match utils::sysroot() {
+        Some(sysroot) => sysroot.join("lib").join("rustlib").join(triple).join("lib"),
+        None => {
+            return Check::problem(
+                name,
+                Status::Error,
+                "could not find rustc's sysroot",
+                "install Rust with rustup from https://rustup.rs",
+            )
+        }
+    };
+    let files: Vec<String> = match fs::read_dir(&lib) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect(),
+        Err(_) => {
+            return Check::problem(
+                name,
+                Status::Error,
+                format!("the standard library for {} isn't installed", triple),
+                format!("run `rustup target add {}`", triple),
+            )
+        }
+    };
+    let runtimes = [
+        (Sanitizer::Address, "asan"),
+        (Sanitizer::Leak, "lsan"),
+        (Sanitizer::Memory, "msan"),
+        (Sanitizer::Thread, "tsan"),
+    ];
+    let found: Vec<Sanitizer> = runtimes
+        .iter()
+        .filter(|(_, runtime)| {
+            let runtime = format!("_rt.{}.", runtime);
+            files.iter().any(|f| f.contains(&runtime))
+        })
+        .map(|(sanitizer, _)| *sanitizer)
+        .collect();
+    if found.contains(&Sanitizer::Address) {
+        let found: Vec<String> = found.iter().map(|s| s.to_string()).collect();
+        Check::ok(name, found.join(", "))
+    } else {
+        Check::problem(
+            name,
+            Status::Error,
+            format!(
+                "no AddressSanitizer runtime for {} in {}",
+                triple,
+                lib.display()
+            ),
+            format!(
+                "run `rustup component add rust-std --target {}` on a nightly toolchain, \
+                 or build with `--sanitizer none`",
+                triple
+            ),
+        )
+    }
+}
+
+/// LeakSanitizer stops the process's threads with ptrace to look for leaks,
+/// and debuggers attach with it, which Yama's `ptrace_scope` can forbid.
+fn ptrace_scope() -> Option<Check> {
+    let name = "ptrace_scope";
+    let scope = fs::read_to_string("/proc/sys/kernel/yama/ptrace_scope").ok()?;
+    let scope: u32 = scope.trim().parse().ok()?;
+    Some(if scope < 2 {
+        Check::ok(name, scope.to_string())
+    } else {
+        Check::problem(
+            name,
+            Status::Warning,
+            format!(
+                "{}, so LeakSanitizer can't check for leaks and debuggers can't attach",
+                scope
+            ),
+            "run `sudo sysctl kernel.yama.ptrace_scope=1`, or `--sanitizer none` or \
+             `-detect_leaks=0` to fuzz without leak checking",
+        )
+    })
+}
+
+/// Fuzz targets built without a sanitizer dump core when they crash, which
+/// can take a long time with a core pattern that pipes to a crash reporter.
+fn core_dumps() -> Option<Check> {
+    let name = "core dumps";
+    let pattern = fs::read_to_string("/proc/sys/kernel/core_pattern").ok()?;
+    let pattern = pattern.trim();
+    let limits = fs::read_to_string("/proc/self/limits").ok()?;
+    let limit = limits
+        .lines()
+        .find_map(|l| l.strip_prefix("Max core file size"))?
+        .split_whitespace()
+        .next()?;
+    Some(if limit == "0" {
+        Check::ok(name, "disabled")
+    } else if let Some(handler) = pattern.strip_prefix('|') {
+        let program = handler.split_whitespace().next().unwrap_or(handler);
+        Check::problem(
+            name,
+            Status::Warning,
+            format!(
+                "crashes are piped to {}, which can make each crash take seconds",
+                program
+            ),
+            "run `ulimit -c 0` in the shell you fuzz from",
+        )
+    } else {
+        Check::ok(name, format!("written to `{}`", pattern))
+    })
+}
+
+/// Render the checks as a list, with how to fix each problem.
+pub fn render(checks: &[Check]) -> String {
+    let mut out = String::new();
+    for check in checks {
+        let status = match check.status {
+            Status::Ok => "ok",
+            Status::Warning => "warning",
+            Status::Error => "error",
+        };
+        let _ = writeln!(out, "{:<8} {}: {}", status, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(out, "{:<8} fix: {}", "", fix);
+        }
+    }
+    out
+}
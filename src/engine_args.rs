@@ -0,0 +1,252 @@
+//! Checking the libFuzzer flags given after `--` or as `engine-args` in
+//! `fuzz.toml`, which are otherwise passed along as they are.
+//!
+//! libFuzzer only warns about a flag it doesn't know in its own output, and
+//! ignores it, and it takes anything that doesn't start with `-` as a corpus
+//! directory, so a typo like `-max_len =4096` or `--runs=100` silently does
+//! nothing. The flags here are those of the libFuzzer that libfuzzer-sys
+//! 0.3.5 vendors, and the few added since, with the release of libfuzzer-sys
+//! that first has them.
+
+/// What a flag's value has to be.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Value {
+    Int,
+    Unsigned,
+    String,
+    /// The flag is deprecated, and libFuzzer ignores it.
+    Deprecated,
+}
+
+/// libFuzzer's flags, and what their values have to be.
+const FLAGS: &[(&str, Value)] = &[
+    ("verbosity", Value::Int),
+    ("seed", Value::Unsigned),
+    ("runs", Value::Int),
+    ("max_len", Value::Int),
+    ("len_control", Value::Int),
+    ("seed_inputs", Value::String),
+    ("keep_seed", Value::Int),
+    ("cross_over", Value::Int),
+    ("cross_over_uniform_dist", Value::Int),
+    ("mutate_depth", Value::Int),
+    ("reduce_depth", Value::Int),
+    ("shuffle", Value::Int),
+    ("prefer_small", Value::Int),
+    ("timeout", Value::Int),
+    ("error_exitcode", Value::Int),
+    ("timeout_exitcode", Value::Int),
+    ("max_total_time", Value::Int),
+    ("help", Value::Int),
+    ("fork", Value::Int),
+    ("fork_corpus_groups", Value::Int),
+    ("ignore_timeouts", Value::Int),
+    ("ignore_ooms", Value::Int),
+    ("ignore_crashes", Value::Int),
+    ("merge", Value::Int),
+    ("set_cover_merge", Value::Int),
+    ("stop_file", Value::String),
+    ("merge_inner", Value::String),
+    ("merge_control_file", Value::String),
+    ("minimize_crash", Value::Int),
+    ("cleanse_crash", Value::Int),
+    ("minimize_crash_internal_step", Value::Int),
+    ("features_dir", Value::String),
+    ("mutation_graph_file", Value::String),
+    ("use_counters", Value::Int),
+    ("use_memmem", Value::Int),
+    ("use_value_profile", Value::Int),
+    ("use_cmp", Value::Int),
+    ("shrink", Value::Int),
+    ("reduce_inputs", Value::Int),
+    ("jobs", Value::Unsigned),
+    ("workers", Value::Unsigned),
+    ("reload", Value::Int),
+    ("report_slow_units", Value::Int),
+    ("only_ascii", Value::Int),
+    ("dict", Value::String),
+    ("artifact_prefix", Value::String),
+    ("exact_artifact_path", Value::String),
+    ("print_pcs", Value::Int),
+    ("print_funcs", Value::Int),
+    ("print_final_stats", Value::Int),
+    ("print_corpus_stats", Value::Int),
+    ("print_coverage", Value::Int),
+    ("print_full_coverage", Value::Int),
+    ("dump_coverage", Value::Int),
+    ("handle_segv", Value::Int),
+    ("handle_bus", Value::Int),
+    ("handle_abrt", Value::Int),
+    ("handle_ill", Value::Int),
+    ("handle_fpe", Value::Int),
+    ("handle_int", Value::Int),
+    ("handle_term", Value::Int),
+    ("handle_xfsz", Value::Int),
+    ("handle_usr1", Value::Int),
+    ("handle_usr2", Value::Int),
+    ("handle_winexcept", Value::Int),
+    ("close_fd_mask", Value::Int),
+    ("detect_leaks", Value::Int),
+    ("purge_allocator_interval", Value::Int),
+    ("trace_malloc", Value::Int),
+    ("rss_limit_mb", Value::Int),
+    ("malloc_limit_mb", Value::Int),
+    ("exit_on_src_pos", Value::String),
+    ("exit_on_item", Value::String),
+    ("ignore_remaining_args", Value::Int),
+    ("focus_function", Value::String),
+    ("entropic", Value::Int),
+    ("entropic_feature_frequency_threshold", Value::Int),
+    ("entropic_number_of_rarest_features", Value::Int),
+    ("entropic_scale_per_exec_time", Value::Int),
+    ("analyze_dict", Value::Int),
+    ("use_clang_coverage", Value::Deprecated),
+    ("data_flow_trace", Value::String),
+    ("collect_data_flow", Value::String),
+    ("create_missing_dirs", Value::Int),
+];
+
+/// The flags newer than libfuzzer-sys 0.3.5, and the first release of
+/// libfuzzer-sys whose libFuzzer has each.
+const SINCE: &[(&str, (u64, u64, u64))] = &[
+    ("set_cover_merge", (0, 4, 4)),
+    ("fork_corpus_groups", (0, 4, 7)),
+];
+
+/// Warnings about the flags in `args` that libFuzzer would ignore, or take
+/// as something else, given the version of libfuzzer-sys that the fuzz crate
+/// links, if it's known.
+pub fn check(args: &[String], version: Option<(u64, u64, u64)>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut args = args.iter().peekable();
+    while let Some(arg) = args.next() {
+        if let Some(param) = arg.strip_prefix("--") {
+            let known = lookup(flag_name(param)).is_some();
+            warnings.push(if known {
+                format!(
+                    "libFuzzer ignores `{}`, since it starts with `--`; did you mean `-{}`?",
+                    arg, param
+                )
+            } else {
+                format!("libFuzzer ignores `{}`, since it starts with `--`", arg)
+            });
+            continue;
+        }
+        let param = match arg.strip_prefix('-') {
+            // Anything else is a corpus directory or an input.
+            Some(param) if !param.is_empty() => param,
+            _ => continue,
+        };
+        let (name, value) = match param.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (param, None),
+        };
+        let (flag, kind) = match lookup(name.trim_end()) {
+            Some(found) => found,
+            None => {
+                warnings.push(match similar(name) {
+                    Some(similar) => format!(
+                        "libFuzzer ignores the unknown flag `{}`; did you mean `-{}`?",
+                        arg, similar
+                    ),
+                    None => format!("libFuzzer ignores the unknown flag `{}`", arg),
+                });
+                continue;
+            }
+        };
+        if name != flag || value.is_none() {
+            // `-max_len =4096` or `-max_len 4096`, which libFuzzer takes as an
+            // unknown flag and a corpus directory.
+            let next = args
+                .peek()
+                .filter(|next| value.is_none() && !next.starts_with('-'));
+            let value = match (value, next) {
+                (Some(value), _) => value.trim_start().to_string(),
+                (None, Some(next)) => next.trim_start_matches('=').trim_start().to_string(),
+                (None, None) => "<value>".to_string(),
+            };
+            let given = match next {
+                Some(next) => format!("{} {}", arg, next),
+                None => arg.clone(),
+            };
+            warnings.push(format!(
+                "libFuzzer ignores `{}`; write it as `-{}={}`",
+                given, flag, value
+            ));
+            if next.is_some() {
+                args.next();
+            }
+            continue;
+        }
+        let value = value.unwrap_or_default();
+        match kind {
+            Value::Deprecated => {
+                warnings.push(format!(
+                    "`-{}` is deprecated, and libFuzzer ignores it",
+                    name
+                ));
+            }
+            Value::Int if value.parse::<i64>().is_err() => {
+                warnings.push(format!("`{}` takes a number, not `{}`", arg, value));
+            }
+            Value::Unsigned if value.parse::<u64>().is_err() => {
+                warnings.push(format!(
+                    "`{}` takes a non-negative number, not `{}`",
+                    arg, value
+                ));
+            }
+            _ => {}
+        }
+        let since = SINCE.iter().find(|(name, _)| *name == flag);
+        if let (Some((_, since)), Some(version)) = (since, version) {
+            if version < *since {
+                warnings.push(format!(
+                    "`-{}` needs libfuzzer-sys {}.{}.{} or later, but the fuzz crate links {}.{}.{}, \
+                     whose libFuzzer ignores it",
+                    name, since.0, since.1, since.2, version.0, version.1, version.2
+                ));
+            }
+        }
+        // The rest are for the fuzz target.
+        if flag == "ignore_remaining_args" && value.parse::<i64>().is_ok_and(|v| v != 0) {
+            break;
+        }
+    }
+    warnings
+}
+
+/// The name of the flag in `param`, a flag without its leading `-`.
+fn flag_name(param: &str) -> &str {
+    param.split('=').next().unwrap_or(param).trim_end()
+}
+
+fn lookup(name: &str) -> Option<(&'static str, Value)> {
+    FLAGS.iter().copied().find(|(flag, _)| *flag == name)
+}
+
+/// The known flag that `name` is a typo of, if it's close enough to one.
+fn similar(name: &str) -> Option<&'static str> {
+    let name = name.trim().to_ascii_lowercase().replace('-', "_");
+    FLAGS
+        .iter()
+        .map(|(flag, _)| (distance(&name, flag), *flag))
+        .filter(|(distance, flag)| *distance <= 2.min(flag.len() / 3))
+        .min()
+        .map(|(_, flag)| flag)
+}
+
+/// The Levenshtein distance between `a` and `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
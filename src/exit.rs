@@ -0,0 +1,53 @@
+//! Exit codes that let scripts tell why `cargo fuzz` failed.
+//!
+//! `cargo fuzz` exits with 0 when it succeeds, which for `run` includes
+//! reaching a time limit or only finding known crashes, and with 1 for any
+//! error without an exit code of its own.
+
+use std::fmt;
+
+/// Failures that CI pipelines may want to branch on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Fuzzing found a crash that isn't a known one.
+    CrashFound = 2,
+    /// Building a fuzz target failed.
+    BuildFailed = 3,
+}
+
+/// An error that makes `cargo fuzz` exit with a particular exit code.
+#[derive(Debug)]
+pub struct Failure {
+    pub code: ExitCode,
+    message: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// An error that exits with `code`.
+pub fn fail(code: ExitCode, message: impl Into<String>) -> anyhow::Error {
+    Failure {
+        code,
+        message: message.into(),
+    }
+    .into()
+}
+
+/// The exit code for an error: that of the outermost `Failure` it wraps, or 1.
+pub fn code(error: &anyhow::Error) -> i32 {
+    error
+        .chain()
+        .find_map(|e| e.downcast_ref::<Failure>())
+        .map_or(1, |failure| failure.code as i32)
+}
+
+/// Whether an error is, or wraps, a failure with `code`.
+pub fn is(error: &anyhow::Error, code: ExitCode) -> bool {
+    self::code(error) == code as i32
+}
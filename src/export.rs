@@ -0,0 +1,160 @@
+//! Writing inputs as text, for `cargo fuzz corpus export`, and reading them
+//! back, for `cargo fuzz corpus import --format`.
+//!
+//! Text survives bug trackers, configuration files and chat, which mangle
+//! binary data. Every format round-trips any input, including an empty one:
+//! the line formats have exactly one line per input, so an empty input is an
+//! empty line.
+
+use crate::utils;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt as stdfmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// An input per line, in hex.
+    Hex,
+    /// An input per line, in base64.
+    Base64,
+    /// A JSON document with the fuzz target's name and each input's name and
+    /// base64 contents.
+    Base64Json,
+}
+
+impl stdfmt::Display for Format {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Format::Hex => "hex",
+                Format::Base64 => "base64",
+                Format::Base64Json => "base64-json",
+            }
+        )
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Format::Hex),
+            "base64" => Ok(Format::Base64),
+            "base64-json" => Ok(Format::Base64Json),
+            _ => Err(format!("unknown export format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    target: String,
+    inputs: Vec<Input>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Input {
+    name: String,
+    /// The input's contents, in base64.
+    data: String,
+}
+
+/// Write named inputs of `target` in `format`.
+pub fn render(format: Format, target: &str, inputs: &[(String, Vec<u8>)]) -> String {
+    match format {
+        Format::Hex | Format::Base64 => {
+            let encode = match format {
+                Format::Hex => encode_hex,
+                _ => utils::encode_base64,
+            };
+            inputs.iter().map(|(_, data)| encode(data) + "\n").collect()
+        }
+        Format::Base64Json => {
+            let document = Document {
+                target: target.to_string(),
+                inputs: inputs
+                    .iter()
+                    .map(|(name, data)| Input {
+                        name: name.clone(),
+                        data: utils::encode_base64(data),
+                    })
+                    .collect(),
+            };
+            serde_json::to_string_pretty(&document).expect("exports always serialize") + "\n"
+        }
+    }
+}
+
+/// Read the inputs back from what `render` wrote.
+pub fn parse(format: Format, text: &str) -> Result<Vec<Vec<u8>>> {
+    match format {
+        Format::Hex | Format::Base64 => {
+            if text.is_empty() {
+                return Ok(Vec::new());
+            }
+            let text = text.strip_suffix('\n').unwrap_or(text);
+            text.split('\n')
+                .enumerate()
+                .map(|(i, line)| {
+                    let line = line.strip_suffix('\r').unwrap_or(line);
+                    let data = match format {
+                        Format::Hex => decode_hex(line),
+                        _ => utils::decode_base64(line),
+                    };
+                    data.ok_or_else(|| anyhow!("line {} isn't valid {}", i + 1, format))
+                })
+                .collect()
+        }
+        Format::Base64Json => {
+            let document: Document =
+                serde_json::from_str(text).context("invalid base64-json export")?;
+            document
+                .inputs
+                .into_iter()
+                .map(|input| {
+                    utils::decode_base64(&input.data).ok_or_else(|| {
+                        anyhow!("the data of input `{}` isn't valid base64", input.name)
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+pub fn encode_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The start of an input as a Rust byte string, like `b"GET /\r\n"`, noting
+/// how many bytes are left out after `limit`.
+pub fn escape(data: &[u8], limit: usize) -> String {
+    let shown = &data[..data.len().min(limit)];
+    let mut out = String::from("b\"");
+    for b in shown {
+        match b {
+            b'\'' => out.push('\''),
+            _ => out.extend(std::ascii::escape_default(*b).map(char::from)),
+        }
+    }
+    out.push('"');
+    if data.len() > shown.len() {
+        out.push_str(&format!(" and {} more byte(s)", data.len() - shown.len()));
+    }
+    out
+}
+
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,408 @@
+//! Generating the fuzz target that `cargo fuzz add --ffi` writes for a C
+//! function that the fuzzed crate exports.
+//!
+//! The function is found by parsing the crate's sources from its library
+//! root, following `mod` declarations, for an `extern "C"` function that's
+//! `#[no_mangle]` or has an `#[export_name]`. The fuzz target declares it in
+//! an `extern "C"` block from its signature and calls it through its symbol,
+//! like C code would, rather than through its Rust path.
+//!
+//! Its arguments are made from the fuzzer's input: a pointer to bytes that's
+//! followed by an integer named like a length, such as `len` or `size`, is a
+//! buffer and its length, any other pointer to bytes is a NUL-terminated
+//! string, and numbers are built with `Arbitrary`. Every buffer and string is
+//! a heap allocation of exactly its length, so that AddressSanitizer reports
+//! any read or write past its end.
+
+use crate::signature;
+use anyhow::{bail, Result};
+use quote::ToTokens;
+use std::path::Path;
+use syn::{FnArg, Item, Lit, Meta, Pat, ReturnType, Type};
+
+/// The integer types that C functions take, and the Rust types they're
+/// declared with in the fuzz target.
+const INTEGERS: &[(&str, &str)] = &[
+    ("u8", "u8"),
+    ("u16", "u16"),
+    ("u32", "u32"),
+    ("u64", "u64"),
+    ("usize", "usize"),
+    ("i8", "i8"),
+    ("i16", "i16"),
+    ("i32", "i32"),
+    ("i64", "i64"),
+    ("isize", "isize"),
+    ("size_t", "usize"),
+    ("ssize_t", "isize"),
+    ("c_char", "std::os::raw::c_char"),
+    ("c_schar", "std::os::raw::c_schar"),
+    ("c_uchar", "std::os::raw::c_uchar"),
+    ("c_short", "std::os::raw::c_short"),
+    ("c_ushort", "std::os::raw::c_ushort"),
+    ("c_int", "std::os::raw::c_int"),
+    ("c_uint", "std::os::raw::c_uint"),
+    ("c_long", "std::os::raw::c_long"),
+    ("c_ulong", "std::os::raw::c_ulong"),
+    ("c_longlong", "std::os::raw::c_longlong"),
+    ("c_ulonglong", "std::os::raw::c_ulonglong"),
+];
+
+/// The other types that are passed by value, and are built with `Arbitrary`.
+const SCALARS: &[(&str, &str)] = &[
+    ("bool", "bool"),
+    ("f32", "f32"),
+    ("f64", "f64"),
+    ("c_float", "std::os::raw::c_float"),
+    ("c_double", "std::os::raw::c_double"),
+];
+
+/// The types that a pointer to bytes points to.
+const BYTES: &[&str] = &["u8", "i8", "c_char", "c_schar", "c_uchar", "c_void"];
+
+/// An `extern "C"` function that the fuzzed crate exports.
+pub struct Export {
+    /// The symbol it's exported as.
+    pub symbol: String,
+    sig: syn::Signature,
+}
+
+/// The parts of a fuzz target that calls an exported function.
+pub struct Harness {
+    /// The function's declaration in an `extern "C"` block.
+    pub declaration: String,
+    /// The body of the fuzz target's closure, which calls the function.
+    pub body: String,
+    /// How the function's arguments are made, for a comment in the target.
+    pub description: &'static str,
+}
+
+/// An argument of an exported function.
+enum Argument {
+    /// A pointer to bytes, and the length after it.
+    Buffer { mutable: bool },
+    /// A pointer to bytes that aren't followed by a length.
+    String { mutable: bool },
+    /// Any other value, of the given type.
+    Scalar(String),
+}
+
+/// Find the `extern "C"` function that the crate whose library root is `lib`
+/// exports as `symbol`.
+pub fn find(lib: &Path, symbol: &str) -> Result<Export> {
+    let mut exports = Vec::new();
+    collect(&signature::parse(lib)?, lib.parent().unwrap(), &mut exports)?;
+    let symbols: Vec<String> = exports.iter().map(|export| export.symbol.clone()).collect();
+    match exports.into_iter().find(|export| export.symbol == symbol) {
+        Some(export) => Ok(export),
+        None if symbols.is_empty() => bail!(
+            "the fuzzed crate exports no `extern \"C\"` functions; they have to be \
+             `#[no_mangle]` or have an `#[export_name]`"
+        ),
+        None => bail!(
+            "the fuzzed crate exports no `extern \"C\"` function as `{}`; it exports {}",
+            symbol,
+            symbols
+                .iter()
+                .map(|symbol| format!("`{}`", symbol))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Collect the exported functions among `items`, whose submodules' files
+/// are in `dir`.
+fn collect(items: &[Item], dir: &Path, exports: &mut Vec<Export>) -> Result<()> {
+    for item in items {
+        match item {
+            Item::Fn(function) if is_c_abi(&function.sig) => {
+                if let Some(symbol) = exported_name(&function.attrs, &function.sig) {
+                    exports.push(Export {
+                        symbol,
+                        sig: function.sig.clone(),
+                    });
+                }
+            }
+            Item::Mod(declaration) => {
+                let name = declaration.ident.to_string();
+                match &declaration.content {
+                    Some((_, items)) => collect(items, &dir.join(&name), exports)?,
+                    None => {
+                        let file = dir.join(format!("{}.rs", name));
+                        let file = if file.is_file() {
+                            file
+                        } else {
+                            dir.join(&name).join("mod.rs")
+                        };
+                        if file.is_file() {
+                            collect(&signature::parse(&file)?, &dir.join(&name), exports)?;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn is_c_abi(sig: &syn::Signature) -> bool {
+    match &sig.abi {
+        Some(abi) => abi
+            .name
+            .as_ref()
+            .is_none_or(|name| ["C", "C-unwind"].contains(&name.value().as_str())),
+        None => false,
+    }
+}
+
+/// The symbol a function is exported as, if it's `#[no_mangle]` or has an
+/// `#[export_name]`.
+fn exported_name(attrs: &[syn::Attribute], sig: &syn::Signature) -> Option<String> {
+    attrs.iter().find_map(|attr| match attr.parse_meta().ok()? {
+        Meta::Path(path) if path.is_ident("no_mangle") => Some(sig.ident.to_string()),
+        Meta::NameValue(meta) if meta.path.is_ident("export_name") => match meta.lit {
+            Lit::Str(name) => Some(name.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Generate the declaration of `export` and the fuzz target's closure that
+/// calls it.
+pub fn harness(export: &Export) -> Result<Harness> {
+    let sig = &export.sig;
+    let symbol = &export.symbol;
+    if sig.variadic.is_some() {
+        bail!(
+            "`{}` is variadic; add a fuzz target by hand to pick its arguments",
+            symbol
+        );
+    }
+
+    let mut params = Vec::new();
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let input = match input {
+            FnArg::Typed(input) => input,
+            FnArg::Receiver(_) => bail!("`{}` takes `self`, so it isn't a C function", symbol),
+        };
+        let name = match &*input.pat {
+            Pat::Ident(pat) => pat.ident.to_string(),
+            _ => format!("arg{}", i),
+        };
+        params.push((name, c_type(symbol, &input.ty)?, &*input.ty));
+    }
+    if params.is_empty() {
+        bail!(
+            "`{}` takes no arguments, so there's nothing to fuzz",
+            symbol
+        );
+    }
+    let output = match &sig.output {
+        ReturnType::Default => String::new(),
+        ReturnType::Type(_, ty) => format!(" -> {}", c_return_type(symbol, ty)?),
+    };
+    let declaration = format!(
+        "fn {}({}){};",
+        symbol,
+        params
+            .iter()
+            .map(|(name, ty, _)| format!("{}: {}", name, ty))
+            .collect::<Vec<_>>()
+            .join(", "),
+        output
+    );
+
+    let mut arguments = Vec::new();
+    let mut params = params.into_iter().peekable();
+    while let Some((name, ty, original)) = params.next() {
+        let argument = match pointer_to_bytes(original) {
+            Some(mutable) => match params.peek() {
+                Some((len, ty, _)) if is_integer(ty) && is_length(len) => {
+                    params.next();
+                    (name, Argument::Buffer { mutable })
+                }
+                _ if is_void_pointer(original) => bail!(
+                    "`{}` takes `{}` without a length after it; add a fuzz target by hand to \
+                     pick what it points to",
+                    symbol,
+                    name
+                ),
+                _ => (name, Argument::String { mutable }),
+            },
+            None => (name, Argument::Scalar(ty)),
+        };
+        arguments.push(argument);
+    }
+
+    // A buffer and its length, and nothing else, are the fuzzer's input.
+    if let [(name, Argument::Buffer { mutable })] = arguments.as_slice() {
+        let (setup, pointer) = if *mutable {
+            (
+                format!("    let mut {} = data.to_vec();\n", name),
+                format!("{}.as_mut_ptr().cast()", name),
+            )
+        } else {
+            (String::new(), "data.as_ptr().cast()".to_string())
+        };
+        let length = if *mutable { name.as_str() } else { "data" };
+        return Ok(Harness {
+            declaration,
+            body: format!(
+                "{}    unsafe {{\n        {}({}, {}.len() as _);\n    }}",
+                setup, symbol, pointer, length
+            ),
+            description: "the fuzzer's raw input and its length",
+        });
+    }
+
+    // Otherwise the numbers come first, then the buffers and strings, with
+    // the last of them taking the rest of the input.
+    let last = arguments
+        .iter()
+        .rposition(|(_, argument)| !matches!(argument, Argument::Scalar(_)));
+    // A lone string only takes the rest of the input.
+    let binding = if arguments.len() == 1 && last.is_some() {
+        ""
+    } else {
+        "mut "
+    };
+    let mut body = format!("    let {}u = Unstructured::new(data);\n", binding);
+    for (name, argument) in &arguments {
+        if let Argument::Scalar(ty) = argument {
+            body.push_str(&format!(
+                "    let {}: {} = match u.arbitrary() {{\n        Ok(value) => value,\n        \
+                 Err(_) => return,\n    }};\n",
+                name, ty
+            ));
+        }
+    }
+    let mut passed = Vec::new();
+    for (i, (name, argument)) in arguments.iter().enumerate() {
+        match argument {
+            Argument::Scalar(_) => passed.push(name.clone()),
+            Argument::Buffer { mutable } | Argument::String { mutable } => {
+                let bytes = if Some(i) == last {
+                    "u.take_rest().to_vec()".to_string()
+                } else {
+                    "match u.arbitrary::<Vec<u8>>() {\n        Ok(bytes) => bytes,\n        \
+                     Err(_) => return,\n    }"
+                        .to_string()
+                };
+                // A string is pushed its NUL, so it's always bound mutably.
+                let binding = if *mutable || matches!(argument, Argument::String { .. }) {
+                    "mut "
+                } else {
+                    ""
+                };
+                body.push_str(&format!("    let {}{} = {};\n", binding, name, bytes));
+                let pointer = if *mutable { "as_mut_ptr" } else { "as_ptr" };
+                if let Argument::String { .. } = argument {
+                    body.push_str(&format!("    {}.push(0);\n", name));
+                }
+                passed.push(format!("{}.{}().cast()", name, pointer));
+                if let Argument::Buffer { .. } = argument {
+                    passed.push(format!("{}.len() as _", name));
+                }
+            }
+        }
+    }
+    body.push_str(&format!(
+        "    unsafe {{\n        {}({});\n    }}",
+        symbol,
+        passed.join(", ")
+    ));
+    Ok(Harness {
+        declaration,
+        body,
+        description: "arguments made from the fuzzer's input",
+    })
+}
+
+/// Whether `ty` is a pointer to bytes, and if so, whether it's `*mut`.
+fn pointer_to_bytes(ty: &Type) -> Option<bool> {
+    match ty {
+        Type::Ptr(ptr) if BYTES.contains(&last_segment(&ptr.elem)?.as_str()) => {
+            Some(ptr.mutability.is_some())
+        }
+        _ => None,
+    }
+}
+
+fn is_void_pointer(ty: &Type) -> bool {
+    matches!(ty, Type::Ptr(ptr) if last_segment(&ptr.elem).as_deref() == Some("c_void"))
+}
+
+/// Whether an argument named `name` is the length of the buffer before it.
+fn is_length(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name == "n"
+        || ["len", "size", "count", "cap"]
+            .iter()
+            .any(|word| name.contains(word))
+}
+
+/// Whether an argument declared as `ty` is an integer.
+fn is_integer(ty: &str) -> bool {
+    INTEGERS.iter().any(|(_, declared)| *declared == ty)
+}
+
+/// How an argument of type `ty` is declared in the fuzz target, where only
+/// the standard library's types are in scope.
+fn c_type(symbol: &str, ty: &Type) -> Result<String> {
+    if let Type::Ptr(ptr) = ty {
+        let pointee = match last_segment(&ptr.elem).as_deref() {
+            Some("c_void") => "std::ffi::c_void".to_string(),
+            Some(name) if BYTES.contains(&name) => c_type(symbol, &ptr.elem)?,
+            _ => bail!(
+                "`{}` takes a `{}`; add a fuzz target by hand to make what it points to",
+                symbol,
+                ty.to_token_stream()
+            ),
+        };
+        let mutability = if ptr.mutability.is_some() {
+            "mut"
+        } else {
+            "const"
+        };
+        return Ok(format!("*{} {}", mutability, pointee));
+    }
+    let name = last_segment(ty).unwrap_or_default();
+    match INTEGERS.iter().chain(SCALARS).find(|(c, _)| *c == name) {
+        Some((_, declared)) => Ok(declared.to_string()),
+        None => bail!(
+            "`{}` takes a `{}`, which isn't a C type that `cargo fuzz` can make; add a fuzz \
+             target by hand",
+            symbol,
+            ty.to_token_stream()
+        ),
+    }
+}
+
+/// How the return type `ty` is declared in the fuzz target. The value is
+/// ignored, so a pointer to anything is declared as a `c_void` pointer.
+fn c_return_type(symbol: &str, ty: &Type) -> Result<String> {
+    match ty {
+        Type::Ptr(ptr) if pointer_to_bytes(ty).is_none() => Ok(format!(
+            "*{} std::ffi::c_void",
+            if ptr.mutability.is_some() {
+                "mut"
+            } else {
+                "const"
+            }
+        )),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Ok("()".to_string()),
+        ty => c_type(symbol, ty),
+    }
+}
+
+/// The last segment of the path of the type `ty`, like `c_char` for
+/// `std::os::raw::c_char`.
+fn last_segment(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => Some(ty.path.segments.last()?.ident.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,335 @@
+//! Flamegraphs of where a fuzz target spends its time, for `cargo fuzz
+//! profile`.
+//!
+//! The stacks sampled by `perf record`, or by DTrace where there's no `perf`,
+//! are folded into one line per distinct stack, from the outermost frame to
+//! the innermost, with the number of samples it had: the format of Brendan
+//! Gregg's `stackcollapse` scripts, which `flamegraph.pl` and `inferno` read
+//! too. The SVG is drawn from those lines, with the width of each frame
+//! proportional to the samples that passed through it.
+
+use crate::{crash, utils};
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+
+/// The number of samples of each stack, by its frames from the outermost to
+/// the innermost, joined with `;`.
+pub type Folded = BTreeMap<String, u64>;
+
+/// The width of the SVG, and the height of each frame in it, in pixels.
+const WIDTH: f64 = 1200.0;
+const FRAME_HEIGHT: f64 = 16.0;
+
+/// Frames narrower than this many pixels aren't drawn.
+const MIN_WIDTH: f64 = 0.1;
+
+/// Run `cmd` under `perf record`, or else DTrace, sampling its stacks
+/// `frequency` times a second, with the recording kept in `dir`. Returns how
+/// the command exited, and the sampled stacks, folded.
+pub fn record(cmd: &Command, frequency: u32, dir: &Path) -> Result<(ExitStatus, Folded)> {
+    let perf = utils::find_on_path("perf").is_some();
+    let (mut profiler, output) = if perf {
+        let data = dir.join("perf.data");
+        let mut perf = Command::new("perf");
+        perf.args(["record", "--call-graph", "fp", "--quiet", "-F"])
+            .arg(frequency.to_string())
+            .arg("-o")
+            .arg(&data)
+            .arg("--")
+            .arg(cmd.get_program())
+            .args(cmd.get_args());
+        (perf, data)
+    } else if utils::find_on_path("dtrace").is_some() {
+        // DTrace splits the command on whitespace, without any quoting.
+        let command: Vec<_> = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy())
+            .collect();
+        let stacks = dir.join("dtrace.stacks");
+        let mut dtrace = Command::new("dtrace");
+        dtrace
+            .args(["-q", "-x", "ustackframes=100", "-n"])
+            .arg(format!(
+                "profile-{} /pid == $target/ {{ @[ustack()] = count(); }}",
+                frequency
+            ))
+            .arg("-o")
+            .arg(&stacks)
+            .arg("-c")
+            .arg(command.join(" "));
+        (dtrace, stacks)
+    } else {
+        bail!("profiling needs `perf` on Linux, or `dtrace`, and neither is on PATH");
+    };
+    if let Some(dir) = cmd.get_current_dir() {
+        profiler.current_dir(dir);
+    }
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => profiler.env(key, value),
+            None => profiler.env_remove(key),
+        };
+    }
+    let status = profiler
+        .stdin(Stdio::null())
+        .status()
+        .with_context(|| format!("failed to execute: {:?}", profiler))?;
+    if !output.exists() {
+        bail!(
+            "the profiler exited with {} without recording anything",
+            status
+        );
+    }
+
+    if !perf {
+        let stacks = fs::read_to_string(&output)
+            .with_context(|| format!("failed to read {}", output.display()))?;
+        return Ok((status, fold_dtrace(&stacks)));
+    }
+    let mut script = Command::new("perf");
+    script.arg("script").arg("-i").arg(&output);
+    let printed = script
+        .output()
+        .with_context(|| format!("failed to execute: {:?}", script))?;
+    if !printed.status.success() {
+        bail!(
+            "`perf script` failed on {}: {}",
+            output.display(),
+            String::from_utf8_lossy(&printed.stderr).trim()
+        );
+    }
+    Ok((status, fold_perf(&String::from_utf8_lossy(&printed.stdout))))
+}
+
+/// Fold the samples that `perf script` printed: a header line per sample,
+/// then a line per frame, innermost first, like
+/// `55d4c3a1b2c3 parser::parse+0x23 (/path/to/binary)`, and a blank line.
+pub fn fold_perf(script: &str) -> Folded {
+    let mut folded = Folded::new();
+    let mut frames = Vec::new();
+    let mut in_sample = false;
+    for line in script.lines().chain([""]) {
+        if line.trim().is_empty() {
+            if in_sample {
+                add_stack(&mut folded, &mut frames, 1);
+            }
+            in_sample = false;
+        } else if !in_sample {
+            in_sample = true;
+        } else {
+            let frame = line.trim();
+            let symbol = frame.split_once(' ').map_or(frame, |(_, rest)| rest);
+            let symbol = match symbol.rfind(" (") {
+                Some(at) => &symbol[..at],
+                None => symbol,
+            };
+            frames.push(function_name(symbol));
+        }
+    }
+    folded
+}
+
+/// Fold the stacks that DTrace's `ustack()` aggregation printed: a line per
+/// frame, innermost first, like `binary`parser::parse+0x23`, then the number
+/// of samples.
+pub fn fold_dtrace(output: &str) -> Folded {
+    let mut folded = Folded::new();
+    let mut frames = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.parse::<u64>() {
+            Ok(count) => add_stack(&mut folded, &mut frames, count),
+            Err(_) => {
+                let symbol = line.split_once('`').map_or(line, |(_, symbol)| symbol);
+                frames.push(function_name(symbol));
+            }
+        }
+    }
+    folded
+}
+
+/// Add a stack, given innermost frame first, to `folded`, and clear it.
+fn add_stack(folded: &mut Folded, frames: &mut Vec<String>, count: u64) {
+    if frames.is_empty() {
+        return;
+    }
+    frames.reverse();
+    *folded.entry(frames.join(";")).or_default() += count;
+    frames.clear();
+}
+
+/// A frame's function name, without the offset into it, the hash of a
+/// legacy-mangled Rust symbol, or the `;` that separates frames.
+fn function_name(symbol: &str) -> String {
+    let symbol = match symbol.rfind("+0x") {
+        Some(at) => &symbol[..at],
+        None => symbol,
+    };
+    let symbol = match symbol.rsplit_once("::h") {
+        Some((path, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            path
+        }
+        _ => symbol,
+    };
+    if symbol.is_empty() {
+        "[unknown]".to_string()
+    } else {
+        symbol.replace(';', ":")
+    }
+}
+
+/// Render the folded stacks in the format that `flamegraph.pl` reads.
+pub fn render_folded(folded: &Folded) -> String {
+    let mut out = String::new();
+    for (stack, count) in folded {
+        let _ = writeln!(out, "{} {}", stack, count);
+    }
+    out
+}
+
+/// The functions that the most samples were taken in, rather than in
+/// something they called, with their samples, the most first.
+pub fn hottest(folded: &Folded, count: usize) -> Vec<(&str, u64)> {
+    let mut samples = BTreeMap::<&str, u64>::new();
+    for (stack, n) in folded {
+        let leaf = stack.rsplit(';').next().unwrap_or(stack);
+        *samples.entry(leaf).or_default() += n;
+    }
+    let mut hottest: Vec<_> = samples.into_iter().collect();
+    hottest.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    hottest.truncate(count);
+    hottest
+}
+
+/// A frame of the flamegraph, and the frames it called.
+#[derive(Default)]
+struct Node<'a> {
+    samples: u64,
+    children: BTreeMap<&'a str, Node<'a>>,
+}
+
+/// Render the folded stacks as an SVG flamegraph titled `title`.
+pub fn render_svg(title: &str, folded: &Folded) -> String {
+    let mut root = Node::default();
+    for (stack, &count) in folded {
+        root.samples += count;
+        let mut node = &mut root;
+        for frame in stack.split(';') {
+            node = node.children.entry(frame).or_default();
+            node.samples += count;
+        }
+    }
+    let depth = max_depth(&root);
+    let height = (depth as f64 + 3.0) * FRAME_HEIGHT;
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r##"<?xml version="1.0" standalone="no"?>
+<svg version="1.1" width="{width}" height="{height}" viewBox="0 0 {width} {height}" xmlns="http://www.w3.org/2000/svg">
+<style>text {{ font-family: monospace; font-size: 12px; }} rect {{ stroke: white; stroke-width: 0.5; }}</style>
+<rect x="0" y="0" width="{width}" height="{height}" fill="#eeeeee"/>
+<text x="{center}" y="{title_y}" text-anchor="middle" style="font-size: 16px">{title}</text>"##,
+        width = WIDTH,
+        height = height,
+        center = WIDTH / 2.0,
+        title_y = FRAME_HEIGHT * 1.5,
+        title = escape(title),
+    );
+    let scale = WIDTH / root.samples.max(1) as f64;
+    draw(
+        &mut out,
+        "all",
+        &root,
+        root.samples,
+        0.0,
+        height - FRAME_HEIGHT,
+        scale,
+    );
+    let _ = writeln!(out, "</svg>");
+    out
+}
+
+fn max_depth(node: &Node) -> usize {
+    node.children
+        .values()
+        .map(|child| max_depth(child) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Draw `node` at `x`, on the row whose top is at `y`, and the frames it
+/// called on the rows above it.
+fn draw(out: &mut String, name: &str, node: &Node, total: u64, x: f64, y: f64, scale: f64) {
+    let width = node.samples as f64 * scale;
+    if width < MIN_WIDTH {
+        return;
+    }
+    let _ = writeln!(
+        out,
+        r#"<g><title>{name} ({samples} samples, {percent:.2}%)</title><rect x="{x:.1}" y="{y:.1}" width="{width:.1}" height="{height:.1}" fill="{color}"/>"#,
+        name = escape(name),
+        samples = node.samples,
+        percent = node.samples as f64 * 100.0 / total.max(1) as f64,
+        x = x,
+        y = y,
+        width = width,
+        height = FRAME_HEIGHT,
+        color = color(name),
+    );
+    // About 7 pixels per character of the 12px monospace font.
+    let fits = ((width - 6.0) / 7.0).max(0.0) as usize;
+    if fits >= 3 {
+        let label = if name.chars().count() > fits {
+            let mut label: String = name.chars().take(fits - 2).collect();
+            label.push_str("..");
+            label
+        } else {
+            name.to_string()
+        };
+        let _ = write!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}">{}</text>"#,
+            x + 3.0,
+            y + FRAME_HEIGHT - 4.0,
+            escape(&label)
+        );
+    }
+    let _ = writeln!(out, "</g>");
+    let mut child_x = x;
+    for (child_name, child) in &node.children {
+        draw(
+            out,
+            child_name,
+            child,
+            total,
+            child_x,
+            y - FRAME_HEIGHT,
+            scale,
+        );
+        child_x += child.samples as f64 * scale;
+    }
+}
+
+/// A warm color that's the same for every frame of a function.
+fn color(name: &str) -> String {
+    let hash = crash::fnv1a(name.as_bytes());
+    let red = 205 + (hash % 50);
+    let green = (hash >> 8) % 230;
+    let blue = (hash >> 16) % 55;
+    format!("rgb({},{},{})", red, green, blue)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
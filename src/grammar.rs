@@ -0,0 +1,863 @@
+//! Generating the fuzz target that `cargo fuzz add --grammar` writes for an
+//! EBNF grammar.
+//!
+//! Each rule of the grammar becomes a function that appends a string the rule
+//! matches, with the fuzzer's input picking the alternatives, the optional
+//! parts and the repetitions. Once the input runs out, or the rules are nested
+//! too deep, every choice is made the way that finishes soonest, so that every
+//! input makes a string that the grammar matches.
+//!
+//! Grammars can be written in ISO EBNF, W3C EBNF, or a mix of the two:
+//!
+//! * rules are defined with `=`, `::=` or `:=`, and may end with `;` or `.`;
+//! * names may be written bare, like `digit`, or in angle brackets;
+//! * terminals are quoted with `"` or `'`, and may use the escapes `\n`,
+//!   `\r`, `\t`, `\0`, `\\` and `\"` or `\'`;
+//! * `[a-z_]` and `[^"]` are character classes, where a negated class picks
+//!   from the printable ASCII characters; other brackets are optional parts;
+//! * `{ ... }` repeats, and `?`, `*` and `+` make the part before them
+//!   optional or repeated;
+//! * parts are separated by `,` or whitespace, and alternatives by `|`;
+//! * comments are `(* ... *)`, `/* ... */`, or `//` to the end of the line.
+//!
+//! The first rule is where every string starts.
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+/// The characters that negated character classes pick from.
+const PRINTABLE: (char, char) = (' ', '~');
+
+/// The ranges of characters in a character class, inclusive.
+type Ranges = Vec<(char, char)>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Literal(String),
+    /// Any one of the characters in these ranges.
+    Class(Ranges),
+    Rule(String),
+    Sequence(Vec<Expr>),
+    Choice(Vec<Expr>),
+    Optional(Box<Expr>),
+    /// Any number of the expression, or with `at_least_one`, one or more.
+    Repeat {
+        expr: Box<Expr>,
+        at_least_one: bool,
+    },
+}
+
+#[derive(Debug)]
+struct Rule {
+    name: String,
+    /// The rule as it's written in the grammar, on one line.
+    text: String,
+    expr: Expr,
+}
+
+/// A parsed grammar.
+#[derive(Debug)]
+pub struct Grammar {
+    rules: Vec<Rule>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Name(String),
+    Literal(String),
+    Class(Ranges),
+    Defines,
+    End,
+    Bar,
+    Comma,
+    Open(char),
+    Close(char),
+    Question,
+    Star,
+    Plus,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Name(name) => format!("`{}`", name),
+            Token::Literal(literal) => format!("{:?}", literal),
+            Token::Class(_) => "a character class".to_string(),
+            Token::Defines => "`=`".to_string(),
+            Token::End => "`;`".to_string(),
+            Token::Bar => "`|`".to_string(),
+            Token::Comma => "`,`".to_string(),
+            Token::Open(c) | Token::Close(c) => format!("`{}`", c),
+            Token::Question => "`?`".to_string(),
+            Token::Star => "`*`".to_string(),
+            Token::Plus => "`+`".to_string(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Lexeme {
+    token: Token,
+    line: usize,
+    /// Where the token is in the grammar's source, in bytes.
+    start: usize,
+    end: usize,
+}
+
+/// Parse the grammar in `source`, and check that every rule it uses is
+/// defined and can be finished.
+pub fn parse(source: &str) -> Result<Grammar> {
+    let mut parser = Parser {
+        source,
+        lexemes: tokenize(source)?,
+        pos: 0,
+    };
+    let mut rules: Vec<Rule> = Vec::new();
+    while parser.pos < parser.lexemes.len() {
+        let rule = parser.rule()?;
+        if rules.iter().any(|r| r.name == rule.name) {
+            bail!("rule `{}` is defined more than once", rule.name);
+        }
+        rules.push(rule);
+    }
+    if rules.is_empty() {
+        bail!("the grammar has no rules");
+    }
+    let grammar = Grammar { rules };
+    for rule in &grammar.rules {
+        let mut used = BTreeSet::new();
+        rule.expr.rules(&mut used);
+        if let Some(undefined) = used.iter().find(|name| grammar.rule(name).is_none()) {
+            bail!(
+                "rule `{}` uses `{}`, which isn't defined",
+                rule.name,
+                undefined
+            );
+        }
+    }
+    let heights = grammar.heights();
+    if let Some(rule) = grammar.rules.iter().find(|r| heights[&r.name].is_none()) {
+        bail!(
+            "rule `{}` can never be finished: every way of expanding it expands it again",
+            rule.name
+        );
+    }
+    Ok(grammar)
+}
+
+fn tokenize(source: &str) -> Result<Vec<Lexeme>> {
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let at = |i: usize| chars.get(i).map(|&(_, c)| c);
+    let offset = |i: usize| chars.get(i).map_or(source.len(), |&(offset, _)| offset);
+    let mut lexemes = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while let Some(c) = at(i) {
+        let start = i;
+        let token = match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+                continue;
+            }
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' | '/' if at(i + 1) == Some('*') => {
+                let close = if c == '(' { ')' } else { '/' };
+                let comment_line = line;
+                i += 2;
+                loop {
+                    match at(i) {
+                        Some('*') if at(i + 1) == Some(close) => {
+                            i += 2;
+                            break;
+                        }
+                        Some(c) => {
+                            line += usize::from(c == '\n');
+                            i += 1;
+                        }
+                        None => bail!("line {}: the comment is never closed", comment_line),
+                    }
+                }
+                continue;
+            }
+            '/' if at(i + 1) == Some('/') => {
+                while at(i).is_some_and(|c| c != '\n') {
+                    i += 1;
+                }
+                continue;
+            }
+            '"' | '\'' => {
+                let mut literal = String::new();
+                i += 1;
+                loop {
+                    match at(i) {
+                        Some(q) if q == c => break,
+                        Some('\\') => {
+                            literal.push(unescape(at(i + 1), line)?);
+                            i += 2;
+                        }
+                        Some('\n') | None => bail!("line {}: the string is never closed", line),
+                        Some(c) => {
+                            literal.push(c);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+                Token::Literal(literal)
+            }
+            '[' => match class(&chars[i + 1..], line)? {
+                Some((ranges, len)) => {
+                    i += len + 2;
+                    Token::Class(ranges)
+                }
+                None => {
+                    i += 1;
+                    Token::Open('[')
+                }
+            },
+            '(' | '{' => {
+                i += 1;
+                Token::Open(c)
+            }
+            ')' | ']' | '}' => {
+                i += 1;
+                Token::Close(c)
+            }
+            ':' if at(i + 1) == Some(':') && at(i + 2) == Some('=') => {
+                i += 3;
+                Token::Defines
+            }
+            ':' if at(i + 1) == Some('=') => {
+                i += 2;
+                Token::Defines
+            }
+            '=' => {
+                i += 1;
+                Token::Defines
+            }
+            ';' | '.' => {
+                i += 1;
+                Token::End
+            }
+            '|' => {
+                i += 1;
+                Token::Bar
+            }
+            ',' => {
+                i += 1;
+                Token::Comma
+            }
+            '?' => {
+                i += 1;
+                Token::Question
+            }
+            '*' => {
+                i += 1;
+                Token::Star
+            }
+            '+' => {
+                i += 1;
+                Token::Plus
+            }
+            '<' => {
+                i += 1;
+                while at(i).is_some_and(|c| c != '>' && c != '\n') {
+                    i += 1;
+                }
+                if at(i) != Some('>') {
+                    bail!("line {}: the name in angle brackets is never closed", line);
+                }
+                i += 1;
+                Token::Name(source[offset(start + 1)..offset(i - 1)].trim().to_string())
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while at(i).is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+                    i += 1;
+                }
+                Token::Name(source[offset(start)..offset(i)].to_string())
+            }
+            c => bail!("line {}: unexpected `{}`", line, c),
+        };
+        lexemes.push(Lexeme {
+            token,
+            line,
+            start: offset(start),
+            end: offset(i),
+        });
+    }
+    Ok(lexemes)
+}
+
+fn unescape(c: Option<char>, line: usize) -> Result<char> {
+    Ok(match c {
+        Some('n') => '\n',
+        Some('r') => '\r',
+        Some('t') => '\t',
+        Some('0') => '\0',
+        Some(c @ ('\\' | '"' | '\'' | ']' | '-' | '^')) => c,
+        Some(c) => bail!("line {}: unknown escape `\\{}`", line, c),
+        None => bail!("line {}: the string is never closed", line),
+    })
+}
+
+/// The ranges of the character class that `chars` starts with, after its
+/// `[`, and how many characters it takes up before its `]`, or `None` if the
+/// bracket starts an optional part instead: a class either starts with `^`,
+/// or has a range like `a-z` and no whitespace or quotes.
+fn class(chars: &[(usize, char)], line: usize) -> Result<Option<(Ranges, usize)>> {
+    let mut len = 0;
+    let mut plain = true;
+    while let Some(&(_, c)) = chars.get(len) {
+        match c {
+            ']' | '\n' => break,
+            '\\' => len += 2,
+            c => {
+                plain &= !(c.is_whitespace() || c == '"' || c == '\'');
+                len += 1;
+            }
+        }
+    }
+    if chars.get(len).map(|&(_, c)| c) != Some(']') || len == 0 {
+        return Ok(None);
+    }
+    let body: Vec<char> = chars[..len].iter().map(|&(_, c)| c).collect();
+    let negated = body[0] == '^';
+    let has_range = body.windows(3).any(|w| w[1] == '-' && w[0] != '\\');
+    if !(negated || plain && has_range) {
+        return Ok(None);
+    }
+
+    let mut members = Vec::new();
+    let mut i = usize::from(negated);
+    while i < body.len() {
+        let next = |i: &mut usize| -> Result<char> {
+            let c = body[*i];
+            *i += 1;
+            if c == '\\' {
+                *i += 1;
+                return unescape(body.get(*i - 1).copied(), line);
+            }
+            Ok(c)
+        };
+        let low = next(&mut i)?;
+        let high = if body.get(i) == Some(&'-') && i + 1 < body.len() {
+            i += 1;
+            next(&mut i)?
+        } else {
+            low
+        };
+        if high < low {
+            bail!("line {}: the range `{}-{}` is backwards", line, low, high);
+        }
+        members.push((low, high));
+    }
+    let ranges = if negated {
+        complement(&members)
+    } else {
+        members
+    };
+    if ranges.is_empty() {
+        bail!(
+            "line {}: the character class has no printable characters",
+            line
+        );
+    }
+    Ok(Some((ranges, len)))
+}
+
+/// The printable ASCII characters that aren't in `ranges`.
+fn complement(ranges: &[(char, char)]) -> Ranges {
+    let (first, last) = PRINTABLE;
+    let excluded = |c: char| ranges.iter().any(|&(low, high)| (low..=high).contains(&c));
+    let mut complement = Ranges::new();
+    for c in first..=last {
+        if excluded(c) {
+            continue;
+        }
+        match complement.last_mut() {
+            Some((_, high)) if (*high as u32) + 1 == c as u32 => *high = c,
+            _ => complement.push((c, c)),
+        }
+    }
+    complement
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    lexemes: Vec<Lexeme>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.lexemes.get(self.pos).map(|lexeme| &lexeme.token)
+    }
+
+    fn next(&mut self) -> Option<&Lexeme> {
+        self.pos += 1;
+        self.lexemes.get(self.pos - 1)
+    }
+
+    /// An error for the token at the parser's position.
+    fn unexpected(&self, expected: &str) -> anyhow::Error {
+        match self.lexemes.get(self.pos) {
+            Some(lexeme) => anyhow!(
+                "line {}: expected {}, found {}",
+                lexeme.line,
+                expected,
+                lexeme.token.describe()
+            ),
+            None => anyhow!("expected {}, found the end of the grammar", expected),
+        }
+    }
+
+    fn rule(&mut self) -> Result<Rule> {
+        let start = self.pos;
+        let name = match self.peek() {
+            Some(Token::Name(name)) => name.clone(),
+            _ => return Err(self.unexpected("a rule's name")),
+        };
+        self.pos += 1;
+        if self.peek() != Some(&Token::Defines) {
+            return Err(self.unexpected(&format!("`=` after `{}`", name)));
+        }
+        self.pos += 1;
+        let expr = self.choice()?;
+        match self.peek() {
+            Some(Token::End) => self.pos += 1,
+            None | Some(Token::Name(_)) => {}
+            _ => return Err(self.unexpected("`;` or another rule")),
+        }
+        let text = &self.source[self.lexemes[start].start..self.lexemes[self.pos - 1].end];
+        Ok(Rule {
+            name,
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            expr,
+        })
+    }
+
+    fn choice(&mut self) -> Result<Expr> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some(&Token::Bar) {
+            self.pos += 1;
+            alternatives.push(self.sequence()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Expr::Choice(alternatives)
+        })
+    }
+
+    fn sequence(&mut self) -> Result<Expr> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Token::End | Token::Bar | Token::Close(_)) => break,
+                // The next rule, when this one doesn't end with `;`.
+                Some(Token::Name(_))
+                    if self.lexemes.get(self.pos + 1).map(|l| &l.token)
+                        == Some(&Token::Defines) =>
+                {
+                    break
+                }
+                Some(Token::Comma) if !items.is_empty() => self.pos += 1,
+                _ => items.push(self.item()?),
+            }
+        }
+        Ok(if items.len() == 1 {
+            items.pop().unwrap()
+        } else {
+            Expr::Sequence(items)
+        })
+    }
+
+    fn item(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+        loop {
+            expr = match self.peek() {
+                Some(Token::Question) => Expr::Optional(Box::new(expr)),
+                Some(Token::Star) => Expr::Repeat {
+                    expr: Box::new(expr),
+                    at_least_one: false,
+                },
+                Some(Token::Plus) => Expr::Repeat {
+                    expr: Box::new(expr),
+                    at_least_one: true,
+                },
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return Err(self.unexpected("a name, string or bracket")),
+        };
+        let line = self.lexemes[self.pos].line;
+        let expr = match token {
+            Token::Name(name) => {
+                self.pos += 1;
+                return Ok(Expr::Rule(name));
+            }
+            Token::Literal(literal) => {
+                self.pos += 1;
+                return Ok(Expr::Literal(literal));
+            }
+            Token::Class(ranges) => {
+                self.pos += 1;
+                return Ok(Expr::Class(ranges));
+            }
+            Token::Open(open) => {
+                self.pos += 1;
+                let expr = self.choice()?;
+                let close = match open {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                };
+                match self.next() {
+                    Some(Lexeme {
+                        token: Token::Close(c),
+                        ..
+                    }) if *c == close => {}
+                    _ => {
+                        self.pos -= 1;
+                        return Err(self.unexpected(&format!(
+                            "`{}` to close the `{}` on line {}",
+                            close, open, line
+                        )));
+                    }
+                }
+                match open {
+                    '(' => expr,
+                    '[' => Expr::Optional(Box::new(expr)),
+                    _ => Expr::Repeat {
+                        expr: Box::new(expr),
+                        at_least_one: false,
+                    },
+                }
+            }
+            _ => return Err(self.unexpected("a name, string or bracket")),
+        };
+        Ok(expr)
+    }
+}
+
+impl Expr {
+    /// Add the names of the rules this uses to `rules`.
+    fn rules<'a>(&'a self, rules: &mut BTreeSet<&'a str>) {
+        match self {
+            Expr::Literal(_) | Expr::Class(_) => {}
+            Expr::Rule(name) => {
+                rules.insert(name);
+            }
+            Expr::Sequence(items) | Expr::Choice(items) => {
+                items.iter().for_each(|item| item.rules(rules))
+            }
+            Expr::Optional(expr) | Expr::Repeat { expr, .. } => expr.rules(rules),
+        }
+    }
+
+    /// How deep the rules have to nest, at least, to finish this, given how
+    /// deep they have to for each rule, or `None` if it can't be finished.
+    fn height(&self, heights: &HashMap<String, Option<u32>>) -> Option<u32> {
+        match self {
+            Expr::Literal(_) | Expr::Class(_) | Expr::Optional(_) => Some(0),
+            Expr::Rule(name) => heights.get(name).copied().flatten().map(|h| h + 1),
+            Expr::Sequence(items) => items
+                .iter()
+                .try_fold(0, |height, item| Some(height.max(item.height(heights)?))),
+            Expr::Choice(items) => items.iter().filter_map(|item| item.height(heights)).min(),
+            Expr::Repeat {
+                expr,
+                at_least_one: true,
+            } => expr.height(heights),
+            Expr::Repeat { .. } => Some(0),
+        }
+    }
+
+    /// Whether generating this needs the fuzzer's input.
+    fn uses_input(&self) -> bool {
+        match self {
+            Expr::Literal(_) => false,
+            Expr::Sequence(items) => items.iter().any(Expr::uses_input),
+            _ => true,
+        }
+    }
+}
+
+impl Grammar {
+    fn rule(&self, name: &str) -> Option<&Rule> {
+        self.rules.iter().find(|rule| rule.name == name)
+    }
+
+    /// How deep the rules have to nest, at least, to finish each rule.
+    fn heights(&self) -> HashMap<String, Option<u32>> {
+        let mut heights: HashMap<String, Option<u32>> =
+            self.rules.iter().map(|r| (r.name.clone(), None)).collect();
+        loop {
+            let mut changed = false;
+            for rule in &self.rules {
+                let height = rule.expr.height(&heights);
+                if height.is_some()
+                    && (heights[&rule.name].is_none() || height < heights[&rule.name])
+                {
+                    heights.insert(rule.name.clone(), height);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return heights;
+            }
+        }
+    }
+
+    /// The name of the function that generates the first rule, which every
+    /// string starts from.
+    pub fn start(&self) -> String {
+        self.function_names()[&self.rules[0].name].clone()
+    }
+
+    /// The function for each rule: `rule_` and its name in snake case, made
+    /// unique.
+    fn function_names(&self) -> HashMap<String, String> {
+        let mut taken = BTreeSet::new();
+        self.rules
+            .iter()
+            .map(|rule| {
+                let snake: String = rule
+                    .name
+                    .chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() {
+                            c.to_ascii_lowercase()
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect();
+                let mut function = format!("rule_{}", snake);
+                let mut n = 2;
+                while !taken.insert(function.clone()) {
+                    function = format!("rule_{}_{}", snake, n);
+                    n += 1;
+                }
+                (rule.name.clone(), function)
+            })
+            .collect()
+    }
+
+    /// The functions that generate strings from the grammar, and the helpers
+    /// they use.
+    pub fn generator(&self) -> String {
+        let heights = self.heights();
+        let functions = self.function_names();
+        let mut used = Helpers::default();
+        let mut rules = String::new();
+        for rule in &self.rules {
+            let mut body = String::new();
+            let mut emitter = Emitter {
+                heights: &heights,
+                functions: &functions,
+                used: &mut used,
+                out: &mut body,
+            };
+            emitter.emit(&rule.expr, 1);
+            let (u, depth) = if rule.expr.uses_input() {
+                ("u", "depth")
+            } else {
+                ("_u", "_depth")
+            };
+            let _ = write!(
+                rules,
+                "\n// {}\nfn {}({}: &mut Unstructured<'_>, {}: u32, out: &mut String) -> Result<()> {{\n{}    Ok(())\n}}\n",
+                rule.text, functions[&rule.name], u, depth, body
+            );
+        }
+        used.render() + &rules
+    }
+}
+
+/// The helper functions that the generated rules call.
+#[derive(Default)]
+struct Helpers {
+    choose: bool,
+    more: bool,
+    class: bool,
+}
+
+impl Helpers {
+    fn render(&self) -> String {
+        let mut out = String::new();
+        if !(self.choose || self.more || self.class) {
+            return out;
+        }
+        out.push_str(
+            r#"
+// How deep the rules may nest before every choice is made the way that
+// finishes soonest.
+const MAX_DEPTH: u32 = 32;
+
+/// Whether to stop growing the input and finish it as soon as possible.
+fn exhausted(u: &Unstructured<'_>, depth: u32) -> bool {
+    depth >= MAX_DEPTH || u.is_empty()
+}
+"#,
+        );
+        if self.choose || self.class {
+            out.push_str(
+                r#"
+/// Pick one of `n` alternatives, or the one that finishes soonest.
+fn choose(u: &mut Unstructured<'_>, depth: u32, n: u32, soonest: u32) -> Result<u32> {
+    if exhausted(u, depth) {
+        return Ok(soonest);
+    }
+    u.int_in_range(0..=n - 1)
+}
+"#,
+            );
+        }
+        if self.more {
+            out.push_str(
+                r#"
+/// Whether to add another optional or repeated part.
+fn more(u: &mut Unstructured<'_>, depth: u32) -> Result<bool> {
+    Ok(!exhausted(u, depth) && u.arbitrary()?)
+}
+"#,
+            );
+        }
+        if self.class {
+            out.push_str(
+                r#"
+/// Pick a character in one of `ranges`.
+fn class(u: &mut Unstructured<'_>, depth: u32, ranges: &[(char, char)]) -> Result<char> {
+    let (low, high) = ranges[choose(u, depth, ranges.len() as u32, 0)? as usize];
+    if exhausted(u, depth) {
+        return Ok(low);
+    }
+    let c = u.int_in_range(low as u32..=high as u32)?;
+    Ok(char::from_u32(c).unwrap_or(low))
+}
+"#,
+            );
+        }
+        out
+    }
+}
+
+struct Emitter<'a> {
+    heights: &'a HashMap<String, Option<u32>>,
+    functions: &'a HashMap<String, String>,
+    used: &'a mut Helpers,
+    out: &'a mut String,
+}
+
+impl Emitter<'_> {
+    /// Write the statements that generate `expr`, indented `indent` levels.
+    fn emit(&mut self, expr: &Expr, indent: usize) {
+        let pad = "    ".repeat(indent);
+        match expr {
+            Expr::Literal(literal) if literal.is_empty() => {}
+            Expr::Literal(literal) if literal.chars().count() == 1 => {
+                let _ = writeln!(
+                    self.out,
+                    "{}out.push({:?});",
+                    pad,
+                    literal.chars().next().unwrap()
+                );
+            }
+            Expr::Literal(literal) => {
+                let _ = writeln!(self.out, "{}out.push_str({:?});", pad, literal);
+            }
+            Expr::Class(ranges) => {
+                self.used.class = true;
+                let ranges: Vec<String> = ranges
+                    .iter()
+                    .map(|(low, high)| format!("({:?}, {:?})", low, high))
+                    .collect();
+                let _ = writeln!(
+                    self.out,
+                    "{}out.push(class(u, depth, &[{}])?);",
+                    pad,
+                    ranges.join(", ")
+                );
+            }
+            Expr::Rule(name) => {
+                let _ = writeln!(
+                    self.out,
+                    "{}{}(u, depth + 1, out)?;",
+                    pad, self.functions[name]
+                );
+            }
+            Expr::Sequence(items) => {
+                for item in items {
+                    self.emit(item, indent);
+                }
+            }
+            Expr::Choice(alternatives) => {
+                self.used.choose = true;
+                let soonest = alternatives
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, alternative)| {
+                        alternative.height(self.heights).unwrap_or(u32::MAX)
+                    })
+                    .map_or(0, |(i, _)| i);
+                let _ = writeln!(
+                    self.out,
+                    "{}match choose(u, depth, {}, {})? {{",
+                    pad,
+                    alternatives.len(),
+                    soonest
+                );
+                for (i, alternative) in alternatives.iter().enumerate() {
+                    let arm = if i + 1 == alternatives.len() {
+                        "_".to_string()
+                    } else {
+                        i.to_string()
+                    };
+                    let _ = writeln!(self.out, "{}    {} => {{", pad, arm);
+                    self.emit(alternative, indent + 2);
+                    let _ = writeln!(self.out, "{}    }}", pad);
+                }
+                let _ = writeln!(self.out, "{}}}", pad);
+            }
+            Expr::Optional(expr) => {
+                self.used.more = true;
+                let _ = writeln!(self.out, "{}if more(u, depth)? {{", pad);
+                self.emit(expr, indent + 1);
+                let _ = writeln!(self.out, "{}}}", pad);
+            }
+            Expr::Repeat {
+                expr,
+                at_least_one: false,
+            } => {
+                self.used.more = true;
+                let _ = writeln!(self.out, "{}while more(u, depth)? {{", pad);
+                self.emit(expr, indent + 1);
+                let _ = writeln!(self.out, "{}}}", pad);
+            }
+            Expr::Repeat {
+                expr,
+                at_least_one: true,
+            } => {
+                self.used.more = true;
+                let _ = writeln!(self.out, "{}loop {{", pad);
+                self.emit(expr, indent + 1);
+                let _ = writeln!(self.out, "{}    if !more(u, depth)? {{", pad);
+                let _ = writeln!(self.out, "{}        break;", pad);
+                let _ = writeln!(self.out, "{}    }}", pad);
+                let _ = writeln!(self.out, "{}}}", pad);
+            }
+        }
+    }
+}
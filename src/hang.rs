@@ -0,0 +1,167 @@
+//! Saving the input that a fuzzer hung on, for `cargo fuzz run --save-hangs`.
+//!
+//! When libFuzzer's alarm reports a timeout, the fuzzer is about to exit, and
+//! a fuzz target stuck where the alarm can't interrupt it never gets reported
+//! at all. So the fuzz target is built with the trace recorder, which writes
+//! each input before it runs, and the newest input in the trace directory is
+//! the one that the fuzzer is working on. Once an input has run for half the
+//! timeout, its stacks are sampled with `eu-stack`, or else `gdb`, while it's
+//! still stuck. Should it then time out, or still be running after three
+//! times the timeout, by when the alarm should have gone off, the input is saved as `hangs/hang-<time>-<hash>` in the target's
+//! artifacts directory, named after when it started, with the sample next to
+//! it in `<name>.stacks.txt`.
+
+use crate::{crash, stats};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The prefix of the inputs saved in the hangs directory.
+const HANG_PREFIX: &str = "hang-";
+
+/// Watches the inputs a fuzzer runs for one that hangs.
+#[derive(Debug)]
+pub struct Watcher {
+    trace_dir: PathBuf,
+    timeout: Duration,
+    /// The input that's running, and when it started.
+    current: Option<(PathBuf, SystemTime)>,
+    /// The input whose stacks were sampled, and the sample, if a sampler
+    /// could take one.
+    sample: Option<(PathBuf, Option<String>)>,
+    /// Whether the fuzzer's alarm went off.
+    alarmed: bool,
+}
+
+impl Watcher {
+    /// Watch the inputs that the trace recorder writes to `trace_dir`, which
+    /// time out after `timeout`.
+    pub fn new(trace_dir: &Path, timeout: Duration) -> Watcher {
+        Watcher {
+            trace_dir: trace_dir.to_path_buf(),
+            timeout,
+            current: None,
+            sample: None,
+            alarmed: false,
+        }
+    }
+
+    /// Notice the fuzzer's alarm going off in its output.
+    pub fn observe(&mut self, line: &str) {
+        self.alarmed |= line.starts_with("ALARM: working on the last Unit");
+    }
+
+    /// Sample the stacks of the fuzzer `pid`, if its input has run for long
+    /// enough and they weren't sampled yet.
+    pub fn tick(&mut self, pid: u32) {
+        self.current = newest_input(&self.trace_dir);
+        let (input, started) = match &self.current {
+            Some(current) => current,
+            None => return,
+        };
+        let running = started.elapsed().unwrap_or_default();
+        let sampled = self.sample.as_ref().is_some_and(|(path, _)| path == input);
+        if running >= self.timeout / 2 && !sampled {
+            self.sample = Some((input.clone(), sample_stacks(pid)));
+        }
+    }
+
+    /// Whether the input has run for so long past the timeout that the
+    /// fuzzer's alarm isn't going to go off.
+    pub fn is_stuck(&self) -> bool {
+        !self.alarmed
+            && self.current.as_ref().is_some_and(|(_, started)| {
+                started.elapsed().unwrap_or_default() >= self.timeout * 3
+            })
+    }
+
+    /// Save the input that the fuzzer was running to `dir`, along with the
+    /// sample of its stacks, or else the fuzzer's `report` of the timeout.
+    /// Returns the path of the saved input, or `None` if there was none.
+    pub fn save(&self, dir: &Path, report: &str) -> Result<Option<PathBuf>> {
+        let (input, started) = match newest_input(&self.trace_dir) {
+            Some(current) => current,
+            None => return Ok(None),
+        };
+        let data =
+            fs::read(&input).with_context(|| format!("failed to read {}", input.display()))?;
+        let started = started
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = dir.join(format!(
+            "{}{}-{:016x}",
+            HANG_PREFIX,
+            stats::format_file_timestamp(started),
+            crash::fnv1a(&data)
+        ));
+        fs::create_dir_all(dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+        fs::write(&path, &data).with_context(|| format!("failed to write {}", path.display()))?;
+
+        let stacks = match &self.sample {
+            Some((sampled, Some(stacks))) if *sampled == input => stacks.clone(),
+            _ => format!(
+                "No sample of the stacks was taken; sampling them needs `eu-stack` or `gdb` \
+                 on PATH. The fuzzer reported:\n\n{}",
+                report
+            ),
+        };
+        let stacks_path = stacks_path(&path);
+        fs::write(&stacks_path, stacks)
+            .with_context(|| format!("failed to write {}", stacks_path.display()))?;
+        Ok(Some(path))
+    }
+}
+
+/// The file that the stacks of a saved hang are in.
+pub fn stacks_path(hang: &Path) -> PathBuf {
+    let mut name = hang.file_name().unwrap_or_default().to_os_string();
+    name.push(".stacks.txt");
+    hang.with_file_name(name)
+}
+
+/// The newest input in the trace directory, which the trace recorder names
+/// after its sequence number, and when it was written.
+fn newest_input(trace_dir: &Path) -> Option<(PathBuf, SystemTime)> {
+    let newest = fs::read_dir(trace_dir)
+        .ok()?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .max()?;
+    let modified = fs::metadata(&newest).ok()?.modified().ok()?;
+    Some((newest, modified))
+}
+
+/// The stacks of every thread of `pid`, from the first sampler that's
+/// installed and works.
+fn sample_stacks(pid: u32) -> Option<String> {
+    let pid = pid.to_string();
+    let samplers: [&[&str]; 2] = [
+        &["eu-stack", "-p", &pid],
+        &[
+            "gdb",
+            "-batch",
+            "-nx",
+            "-p",
+            &pid,
+            "-ex",
+            "thread apply all bt",
+        ],
+    ];
+    samplers.iter().find_map(|sampler| {
+        let output = Command::new(sampler[0])
+            .args(&sampler[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        let stacks = String::from_utf8_lossy(&output.stdout).into_owned();
+        if output.status.success() && !stacks.trim().is_empty() {
+            Some(stacks)
+        } else {
+            None
+        }
+    })
+}
@@ -0,0 +1,69 @@
+//! Each fuzz target's health at a glance, for `cargo fuzz list --verbose`.
+
+use crate::stats;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// How a fuzz target is doing, from what's on disk.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Health {
+    pub corpus_entries: usize,
+    pub corpus_bytes: u64,
+    /// Artifacts in the target's artifacts directory, not counting divergence
+    /// reports.
+    pub artifacts: usize,
+    /// When the last recorded run started, in seconds since the Unix epoch.
+    pub last_run: Option<u64>,
+    /// The speed of the latest recorded run that reported one.
+    pub execs_per_sec: Option<u64>,
+    /// The share of the fuzzed crate's code regions that the last `cargo fuzz
+    /// coverage` covered, if its profile and build are still there.
+    pub coverage_percent: Option<f64>,
+}
+
+/// Render the targets' health as a table.
+pub fn render(targets: &[(&str, Health)]) -> String {
+    let width = targets
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(Some("target".len()))
+        .max()
+        .unwrap_or_default();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<width$} {:>14} {:>9} {:>17} {:>9} {:>8}",
+        "target",
+        "corpus",
+        "artifacts",
+        "last run (UTC)",
+        "execs/s",
+        "coverage",
+        width = width
+    );
+    for (name, health) in targets {
+        let _ = writeln!(
+            out,
+            "{:<width$} {:>14} {:>9} {:>17} {:>9} {:>8}",
+            name,
+            format!(
+                "{} ({})",
+                health.corpus_entries,
+                stats::format_size(health.corpus_bytes)
+            ),
+            health.artifacts,
+            health
+                .last_run
+                .map_or("never".to_string(), stats::format_timestamp),
+            health
+                .execs_per_sec
+                .map_or("-".to_string(), |speed| speed.to_string()),
+            health
+                .coverage_percent
+                .map_or("-".to_string(), |percent| format!("{:.1}%", percent)),
+            width = width
+        );
+    }
+    out
+}
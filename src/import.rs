@@ -0,0 +1,142 @@
+//! Reading inputs out of other fuzzers' outputs and test suites, for
+//! `cargo fuzz corpus import`.
+
+use crate::export;
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a batch of imported inputs came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// An AFL or AFL++ output directory, whose `queue` directories (one per
+    /// fuzzer instance) hold its corpus.
+    Afl,
+    /// A proptest regression file, whose `cc <hex>` lines are the seeds of
+    /// failing cases.
+    Proptest,
+    /// Any other directory, all of whose files are inputs.
+    Directory,
+    /// Any other file, which is a single input.
+    File,
+    /// A file written by `cargo fuzz corpus export` in this format.
+    Export(export::Format),
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Source::Afl => "AFL output directory",
+            Source::Proptest => "proptest regression file",
+            Source::Directory => "directory",
+            Source::File => "file",
+            Source::Export(format) => return write!(f, "{} export", format),
+        })
+    }
+}
+
+/// Read the inputs at `path`, telling what kind of source it is from its
+/// contents, unless it's an export in `format`.
+pub fn read_inputs(path: &Path, format: Option<export::Format>) -> Result<(Source, Vec<Vec<u8>>)> {
+    if let Some(format) = format {
+        let data = read(path)?;
+        let text = String::from_utf8(data)
+            .with_context(|| format!("{} isn't a {} export", path.display(), format))?;
+        let inputs = export::parse(format, &text)
+            .with_context(|| format!("{} isn't a {} export", path.display(), format))?;
+        return Ok((Source::Export(format), inputs));
+    }
+    if path.is_dir() {
+        let queues = afl_queues(path)?;
+        if !queues.is_empty() {
+            let mut inputs = Vec::new();
+            for queue in queues {
+                for file in visible_files(&queue, false)? {
+                    inputs.push(read(&file)?);
+                }
+            }
+            return Ok((Source::Afl, inputs));
+        }
+        let inputs = visible_files(path, true)?
+            .iter()
+            .map(|file| read(file))
+            .collect::<Result<_>>()?;
+        return Ok((Source::Directory, inputs));
+    }
+    if !path.is_file() {
+        bail!("nothing to import at {}", path.display());
+    }
+
+    let data = read(path)?;
+    if let Some(seeds) = proptest_seeds(&data) {
+        return Ok((Source::Proptest, seeds));
+    }
+    Ok((Source::File, vec![data]))
+}
+
+/// The `queue` directories of an AFL output directory: its own, as written by
+/// a single AFL instance, or those of its subdirectories, as written by AFL++
+/// and parallel instances.
+fn afl_queues(dir: &Path) -> Result<Vec<PathBuf>> {
+    let is_output = |dir: &Path| dir.join("queue").is_dir() && dir.join("fuzzer_stats").is_file();
+    if is_output(dir) {
+        return Ok(vec![dir.join("queue")]);
+    }
+    let mut queues = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let path = entry
+            .with_context(|| format!("failed to read directory entry inside {}", dir.display()))?
+            .path();
+        if path.is_dir() && is_output(&path) {
+            queues.push(path.join("queue"));
+        }
+    }
+    queues.sort();
+    Ok(queues)
+}
+
+/// The files in a directory, and in its subdirectories if `recursive`,
+/// skipping hidden ones like AFL's `.state`.
+fn visible_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let entry = entry
+            .with_context(|| format!("failed to read directory entry inside {}", dir.display()))?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() && recursive {
+            files.extend(visible_files(&path, true)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    fs::read(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// The seeds in a proptest regression file, or `None` if it isn't one: every
+/// line is either blank, a `#` comment, or `cc <hex seed>` followed by an
+/// optional comment.
+fn proptest_seeds(data: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let text = std::str::from_utf8(data).ok()?;
+    let mut seeds = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let hex = line.strip_prefix("cc ")?.split_whitespace().next()?;
+        seeds.push(export::decode_hex(hex)?);
+    }
+    Some(seeds).filter(|seeds| !seeds.is_empty())
+}
@@ -0,0 +1,152 @@
+//! Parsing libFuzzer's log output.
+
+use crate::options::StallLimit;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// One of libFuzzer's periodic status lines, like
+/// `#1024 NEW cov: 45 ft: 60 corp: 10/200b lim: 4 exec/s: 512 rss: 30Mb`, or
+/// in `-fork` mode, `#4096: cov: 45 ft: 60 corp: 10 exec/s 512 ...`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    /// The number of inputs executed so far.
+    pub execs: u64,
+    /// What happened, e.g. `INITED`, `NEW`, `REDUCE`, `pulse`, or `DONE`, or
+    /// `fork` for the `-fork` mode's summary lines.
+    pub event: String,
+    /// Number of covered code blocks or edges.
+    pub coverage: Option<u64>,
+    /// Number of coverage features, which includes the likes of counters and
+    /// value profiles too.
+    pub features: Option<u64>,
+    pub corpus_entries: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    pub execs_per_sec: Option<u64>,
+    pub rss_mb: Option<u64>,
+}
+
+impl Status {
+    pub fn parse(line: &str) -> Option<Status> {
+        let mut words = line.strip_prefix('#')?.split_whitespace();
+        let execs = words.next()?;
+        let (execs, event) = match execs.strip_suffix(':') {
+            Some(execs) => (execs.parse().ok()?, "fork".to_string()),
+            None => (execs.parse().ok()?, words.next()?.to_string()),
+        };
+        let mut status = Status {
+            execs,
+            event,
+            ..Status::default()
+        };
+        while let Some(key) = words.next() {
+            let value = match words.next() {
+                Some(value) => value,
+                None => break,
+            };
+            match key {
+                "cov:" => status.coverage = value.parse().ok(),
+                "ft:" => status.features = value.parse().ok(),
+                "corp:" => {
+                    let (entries, bytes) = value.split_once('/').unwrap_or((value, ""));
+                    status.corpus_entries = entries.parse().ok();
+                    status.corpus_bytes = parse_size(bytes);
+                }
+                "exec/s:" | "exec/s" => status.execs_per_sec = value.parse().ok(),
+                "rss:" => status.rss_mb = value.trim_end_matches("Mb").parse().ok(),
+                // Everything after `MS:` describes the mutation sequence.
+                "MS:" => break,
+                _ => {}
+            }
+        }
+        Some(status)
+    }
+
+    /// Whether this status line reports new coverage.
+    pub fn is_new_coverage(&self) -> bool {
+        self.event == "NEW"
+    }
+}
+
+/// Tracks when a fuzzer last found new coverage, to tell when it has
+/// plateaued.
+#[derive(Debug)]
+pub struct StallDetector {
+    limit: StallLimit,
+    features: u64,
+    execs: u64,
+    last_new_execs: u64,
+    last_new: Instant,
+}
+
+impl StallDetector {
+    pub fn new(limit: StallLimit) -> StallDetector {
+        StallDetector {
+            limit,
+            features: 0,
+            execs: 0,
+            last_new_execs: 0,
+            last_new: Instant::now(),
+        }
+    }
+
+    /// Update the detector with a line of the fuzzer's output.
+    pub fn observe(&mut self, line: &str) {
+        let status = match Status::parse(line) {
+            Some(status) => status,
+            None => return,
+        };
+        self.execs = self.execs.max(status.execs);
+        // Features are finer grained than coverage, so prefer them.
+        if let Some(features) = status.features.or(status.coverage) {
+            if features > self.features {
+                self.features = features;
+                self.last_new_execs = self.execs;
+                self.last_new = Instant::now();
+            }
+        }
+    }
+
+    /// Whether the fuzzer has gone without new coverage for too long.
+    pub fn is_stalled(&self) -> bool {
+        match self.limit {
+            StallLimit::Seconds(secs) => self.last_new.elapsed().as_secs() >= secs,
+            StallLimit::Execs(execs) => self.execs - self.last_new_execs >= execs,
+        }
+    }
+}
+
+/// Parse sizes like `200b`, `3Kb`, or `2Mb` into bytes.
+fn parse_size(size: &str) -> Option<u64> {
+    let (number, unit) = size.split_at(size.find(|c: char| !c.is_ascii_digit())?);
+    let number: u64 = number.parse().ok()?;
+    match unit {
+        "b" => Some(number),
+        "Kb" => Some(number << 10),
+        "Mb" => Some(number << 20),
+        _ => None,
+    }
+}
+
+/// The prefix of the inputs libFuzzer writes when they leak memory.
+pub const LEAK_PREFIX: &str = "leak-";
+
+/// The prefix of the inputs libFuzzer writes when they take longer than
+/// `-report_slow_units` seconds to run, which aren't failures.
+pub const SLOW_UNIT_PREFIX: &str = "slow-unit-";
+
+/// Parse the line libFuzzer prints after running an input file given on the
+/// command line, like `Executed fuzz/slow/x/1234 in 52 ms`, into the file and
+/// the milliseconds it took.
+pub fn parse_executed(line: &str) -> Option<(PathBuf, u64)> {
+    let rest = line.strip_prefix("Executed ")?;
+    let (path, time) = rest.rsplit_once(" in ")?;
+    let ms = time.strip_suffix(" ms")?.trim().parse().ok()?;
+    Some((PathBuf::from(path), ms))
+}
+
+/// Parse the line libFuzzer prints after writing an input to disk, like
+/// `artifact_prefix='fuzz/artifacts/x/'; Test unit written to fuzz/artifacts/x/crash-1234`.
+pub fn parse_written_artifact(line: &str) -> Option<PathBuf> {
+    let path = &line[line.find("Test unit written to ")? + "Test unit written to ".len()..];
+    Some(PathBuf::from(path.trim()))
+}
@@ -10,17 +10,57 @@ use structopt::StructOpt;
 
 #[macro_use]
 mod templates;
+mod annotate;
+mod api;
+mod bench;
+mod bloat;
+mod blockers;
+mod ci;
+mod completions;
+mod config;
+mod coverage;
+mod crash;
+mod dashboard;
+mod decoder;
+mod dict;
+mod distribute;
+mod doctor;
+mod engine_args;
+mod exit;
+mod export;
+mod exports;
+mod flamegraph;
+mod grammar;
+mod hang;
+mod health;
+mod import;
+mod libfuzzer;
+mod message;
+mod miri;
+mod monitor;
+mod notify;
 mod options;
 mod project;
+mod provenance;
+mod replay;
+mod report;
+mod rustc_wrapper;
+mod sarif;
+mod session;
+mod signature;
+mod slow;
+mod split;
+mod stats;
+mod status;
 mod utils;
 
-static FUZZ_TARGETS_DIR_OLD: &'static str = "fuzzers";
-static FUZZ_TARGETS_DIR: &'static str = "fuzz_targets";
+static FUZZ_TARGETS_DIR_OLD: &str = "fuzzers";
+static FUZZ_TARGETS_DIR: &str = "fuzz_targets";
 
 // It turns out that `clap`'s `long_about()` makes `cargo fuzz --help`
 // unreadable, and its `before_help()` injects our long about text before the
 // version, so change the default template slightly.
-const LONG_ABOUT_TEMPLATE: &'static str = "\
+const LONG_ABOUT_TEMPLATE: &str = "\
 {bin} {version}
 {about}
 
@@ -33,9 +73,24 @@ USAGE:
 
 {after-help}";
 
-const RUN_BEFORE_HELP: &'static str = "\
+// The same, for subcommands without any `after_help()`, which clap would
+// otherwise render as "unknown after-help".
+const BEFORE_HELP_TEMPLATE: &str = "\
+{bin} {version}
+{about}
+
+USAGE:
+    {usage}
+
+{before-help}
+
+{all-args}";
+
+const RUN_BEFORE_HELP: &str = "\
 The fuzz target name is the same as the name of the fuzz target script in
-fuzz/fuzz_targets/, i.e. the name picked when running `cargo fuzz add`.
+fuzz/fuzz_targets/, i.e. the name picked when running `cargo fuzz add`. A target
+in a subdirectory, like fuzz/fuzz_targets/parser/headers.rs, is named after its
+path, like `parser_headers`, and can also be given as `parser/headers`.
 
 This will run the script inside the fuzz target with varying inputs until it
 finds a crash, at which point it will save the crash input to the artifact
@@ -44,10 +99,60 @@ libFuzzer options below), this will run indefinitely.
 
 By default fuzz targets are built with optimizations equivalent to
 `cargo build --release`, but with debug assertions and overflow checks enabled.
-Address Sanitizer is also enabled by default.";
-
-const RUN_AFTER_HELP: &'static str = "\
-
+Address Sanitizer is also enabled by default. `--dev` builds are much faster,
+and keep light optimizations unless the `dev` profile sets an opt-level;
+`--profile <name>` builds with any other Cargo profile.
+
+The exit code is 0 when fuzzing stops without finding a new crash, 2 when it
+finds one, 3 when building the fuzz target fails, and 1 for other errors.
+Crashes with the same signature as an input in `--ignore-known-crashes <dir>`
+don't count as new; fuzzing restarts after them, and after new crashes until
+`--max-crashes` unique ones are found.
+
+`--focus-changed <base>` replays the corpus through a coverage build, and seeds
+the fuzzer with only the inputs that reach lines of the fuzzed crate's sources
+changed since the git revision <base>, from fuzz/focus/<target>. New inputs
+found there are added to the corpus afterwards. `--only-matching <regex>`,
+`--only-len <range>`, and `--only-prefix <hex>` seed it with only the inputs
+whose contents pass them, the same way.
+
+`--mode leaks` checks for memory leaks after every input, with a malloc limit
+of 256 MB unless one is given, and saves the inputs that leak to
+fuzz/artifacts/<target>/leaks/. Leaks listed in fuzz/lsan.supp, in
+LeakSanitizer's suppressions format, aren't reported.
+`--mode quick` builds without a sanitizer, with debug assertions, overflow
+checks and `-C opt-level=1`, for fast feedback, and keeps that build apart from
+the others.
+
+`--save-hangs` saves the input of a timeout to fuzz/artifacts/<target>/hangs/,
+named after when it started, along with the fuzzer's stacks, sampled with
+`eu-stack` or `gdb` once the input has run for half the timeout. A fuzz target
+stuck past three times the timeout, where libFuzzer's alarm can't stop it, is
+killed and its input saved the same way.
+
+`--auto-tmin` minimizes each new crash after the run, for up to
+`--auto-tmin-time` seconds (60 by default), keeping the original artifact and
+saving the minimized input under the same name to
+fuzz/artifacts/<target>/minimized/. The summary previews it in hex and as a
+byte string.
+
+`--status-addr <addr>`, like 127.0.0.1:8925, serves the fuzzer's progress while
+it runs: its speed, coverage, corpus size, and crashes, as JSON at /status and
+as Prometheus metrics at /metrics.
+
+`--notify-webhook <url>` posts a JSON event to the URL, like a Slack incoming
+webhook, for new artifacts, when the coverage stays the same for
+`--notify-plateau` (1h by default), and when the run finishes.
+
+`--seed <N>` fuzzes deterministically: with that seed, a single job, and
+100000 runs unless `-runs` says otherwise, putting new inputs in a scratch
+directory so that the corpus stays as it was. The seed, the runs, the build
+settings, the libFuzzer arguments, the dictionary, hashes of the corpus and the
+binary, and the versions of cargo-fuzz, rustc, and libfuzzer-sys are written to
+fuzz/manifests/<target>-seed-<N>.json. `--replay-manifest <file>` runs the same
+way again, warning about anything that changed since.";
+
+const RUN_AFTER_HELP: &str = "\
 A full list of libFuzzer options can be found at
 http://llvm.org/docs/LibFuzzer.html#options
 
@@ -63,26 +168,404 @@ include:
   * `-max_total_time=<time>`: Will limit the amount of time to fuzz before it
     gives up
 
-  * `-timeout=<time>`: Will limit the amount of time for a single run before it
-    considers that run a failure
-
   * `-only_ascii`: Only provide ASCII input
 
   * `-dict=<file>`: Use a keyword dictionary from specified file. See
     http://llvm.org/docs/LibFuzzer.html#dictionaries\
 ";
 
-const BUILD_BEFORE_HELP: &'static str = "\
+const WATCH_BEFORE_HELP: &str = "\
+This watches the fuzzed crate's sources and manifest, the fuzz targets, and the
+fuzz crate's manifest and configuration. Whenever any of them change, the
+fuzzer is stopped, the fuzz target is rebuilt, and fuzzing resumes from the
+existing corpus.
+
+If the build fails, or the fuzzer finds a crash or exits, fuzzing resumes after
+the next change.";
+
+const CI_BEFORE_HELP: &str = "\
+This runs a whole CI fuzzing job in one command:
+
+  1. If `--remote` is given, or `corpus-remote` is set in fuzz.toml, the
+     corpora are pulled from remote storage, like `cargo fuzz corpus pull`.
+  2. Every fuzz target (or every one matching the glob) is built.
+  3. Each target is fuzzed in turn for its share of `--total-time`. The crashes
+     already in its artifacts directory are reproduced first, and only crashes
+     with a signature none of them has count as new.
+  4. The corpora are minimized, like `cargo fuzz cmin`, unless `--no-minimize`
+     is given, and pushed back to the remote storage.
+
+A summary is printed at the end, and written as JUnit XML with `--junit` or as
+JSON with `--json`. It exits with 2 if any target found a new crash, and with 1
+if any target failed otherwise.";
+
+const VERIFY_FIXES_BEFORE_HELP: &str = "\
+Every artifact in the artifacts directory of each fuzz target (or each one
+matching the glob) is replayed against a fresh build of the target, and each is
+reported as fixed or as still crashing, with its crash. Artifacts in
+subdirectories, like the leaks that `run --mode leaks` saves, aren't replayed.
+
+With `--move-fixed`, the artifacts that no longer crash are moved to
+fuzz/artifacts/<target>/fixed/, out of the way of later runs. It exits with 2 if
+any artifact still crashes.";
+
+const BUILD_BEFORE_HELP: &str = "\
 By default fuzz targets are built with optimizations equivalent to
 `cargo build --release`, but with debug assertions and overflow checks enabled.
-Address Sanitizer is also enabled by default.";
-
-const BUILD_AFTER_HELP: &'static str = "\
+Address Sanitizer is also enabled by default. `--dev` builds are much faster,
+and keep light optimizations unless the `dev` profile sets an opt-level;
+`--profile <name>` builds with any other Cargo profile.
+
+The flags for rustc are cargo-fuzz's own, then those in CARGO_ENCODED_RUSTFLAGS
+or else RUSTFLAGS, then `--rustflags-extra`, with later flags taking precedence.
+`cargo fuzz build --print-flags` prints them. Like any RUSTFLAGS, they replace
+rustflags set in Cargo's configuration files.
+
+The exit code is 0 when the fuzz targets build, 3 when building one fails, and
+1 for other errors.";
+
+const CHECK_BEFORE_HELP: &str = "\
+This type-checks the fuzz targets with `cargo check` and the `fuzzing` cfg set,
+without instrumenting them for fuzzing, so compile errors show up in seconds
+rather than after a full build. Every target is checked even if some fail, and
+the ones with errors are listed at the end. `--all-targets` checks the fuzz
+crate's tests and examples too.
+
+With `--replay`, fuzz/tests/corpus_replay.rs is (re)generated with a test for
+each fuzz target that runs it on every input in its corpus, and the tests are
+run with `cargo test`. They run under a plain `cargo test` in the fuzz
+directory too, as a smoke check that no corpus input crashes.";
+
+const BUILD_AFTER_HELP: &str = "\
 Sanitizers perform checks necessary for detecting bugs in unsafe code
 at the cost of some performance. For more information on sanitizers see
 https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html\
 ";
 
+const COVERAGE_BEFORE_HELP: &str = "\
+This builds the fuzz target with source-based code coverage instrumentation,
+replays its corpus, and merges the raw profiles into
+fuzz/coverage/<target>/coverage.profdata. With `--format html`,
+`--format lcov`, or `--format cobertura`, a report covering the fuzzed crate's
+sources is generated next to it.
+
+With `--upload codecov` or `--upload coveralls`, the coverage of the fuzzed
+crate is uploaded for the commit that it's checked out at, under the flag
+`fuzz`, so that it shows up next to the coverage of its other tests. This needs
+curl on PATH, and a token in $CODECOV_TOKEN or $COVERALLS_REPO_TOKEN. The
+services' URLs can be changed with $CODECOV_URL and $COVERALLS_ENDPOINT.
+
+With `--diff <base.profdata>`, the coverage is compared with a baseline
+profile, like a copy of an earlier coverage.profdata: a table of covered
+regions per file is printed, the source lines that gained or lost coverage are
+written to fuzz/coverage/<target>/coverage.diff, and the command fails if any
+region lost coverage.
+
+With `--uncovered`, the public functions, inherent methods, and modules of the
+fuzzed crate that the corpus has no coverage of at all are listed, largest
+first, to show which APIs still need fuzz targets.
+
+The coverage build leaves out the fuzzing instrumentation and, with Rust 1.60
+or newer, uses the stable `-C instrument-coverage` flag, so it works on a
+stable toolchain. This needs the `llvm-tools-preview` rustup component, or
+`llvm-profdata` and `llvm-cov` on PATH.";
+
+const EXPLAIN_BEFORE_HELP: &str = "\
+The input and the rest of the corpus are replayed separately through a coverage
+build of the fuzz target, like `cargo fuzz coverage`, and the code regions of
+the fuzzed crate's sources that only the input covers are printed with their
+source lines. An input that covers nothing new may still have been kept for
+reaching code more often than the others, or for comparison operands that
+steered libFuzzer, which region coverage doesn't show.
+
+An input that crashes the fuzz target leaves no coverage profile to read.";
+
+const SUGGEST_SPLIT_BEFORE_HELP: &str = "\
+Each input of the target's corpus is replayed on its own through a coverage
+build of the fuzz target, like `cargo fuzz coverage`, to find the public
+functions of the fuzzed crate it reaches. Functions that another one it
+reaches calls, going by the crate's sources, are left out as reached through
+it; the rest are its entry points. Entry points that an input reaches together
+are grouped, and when the corpus falls into more than one group, each is
+suggested as a fuzz target of its own, named after the target and the group's
+first entry point.
+
+With `--scaffold`, each suggested target is added as a copy of the target's
+source, to trim down to its entry points, with the inputs that reach them
+copied into its corpus. Inputs that crash the fuzz target, or reach no entry
+point, aren't copied.";
+
+const BLOCKERS_BEFORE_HELP: &str = "\
+The fuzz target is built with a recorder of the comparisons that sanitizer
+coverage instruments, and replays the corpus (or the given inputs) once. The
+comparisons whose operands were never equal, and that look like ones the
+fuzzer can't guess its way past, are printed with their source locations:
+
+  * comparisons with a constant wider than a byte, or `match` arms for one,
+    like a magic number, with the constant as dictionary entries to add to
+    fuzz/dictionaries/<target>/;
+  * comparisons of two values at least four bytes wide that both vary with the
+    input, like a checksum, which may be worth skipping under `cfg(fuzzing)`.
+
+Only comparisons in the fuzzed crate's repository are shown. This is only
+supported with the libFuzzer engine, on and for Linux, and an input that
+crashes the fuzz target stops the replay.";
+
+const CORPUS_BEFORE_HELP: &str = "\
+Each fuzz target's corpus in fuzz/corpus/<target> is synced with
+<remote>/<target>. Syncing never deletes inputs on either side, so pulling
+merges the remote corpus into the local one, and runs on different machines
+accumulate coverage.
+
+`s3://` remotes need the AWS CLI, and `gs://` remotes need gsutil, on PATH.
+
+`corpus import` copies inputs found elsewhere into fuzz/corpus/<target>: the
+queues of AFL output directories, the seeds in proptest regression files, and
+the files in any other directory. Inputs already in the corpus are skipped.
+
+`corpus export` writes a fuzz target's corpus, or with `--artifacts` its
+artifacts, as text: an input per line in `hex` or `base64`, or a `base64-json`
+document with each input's name. `corpus import --format <format>` reads the
+inputs back exactly, so they can go through bug trackers, configuration files,
+and anything else that mangles binary data.
+
+`corpus from-tests` seeds a corpus from the fuzzed crate's tests. The first
+time, it adds a `fuzz_record` module to the crate, whose `record` function the
+function that the fuzz target calls has to call with its input. It then runs
+`cargo test` with `--cfg cargo_fuzz_record`, which makes `record` save each
+input, and adds the new ones to fuzz/corpus/<target>. Without that cfg,
+`record` does nothing.
+
+`corpus cross-pollinate` replays every fuzz target's corpus and seeds, or those
+of the targets matching a glob, against each of the other targets, and adds the
+inputs that reach coverage a target's own corpus and seeds don't to its corpus,
+so that targets of the same format share what they find. Inputs that crash a
+target are saved to its artifacts directory.
+
+`corpus info` shows where each input came from: a seed put there by hand, a
+`cargo fuzz run` (with the ID of the run, which its stats record too), `tmin
+--batch`, `corpus import`, `corpus from-tests`, `corpus pull`, or `corpus
+cross-pollinate`, and when.
+This is recorded in fuzz/provenance/<target>.json.
+
+`corpus trim --to-max-len` truncates the inputs in fuzz/corpus/<target> over
+the target's `max-len` in fuzz/fuzz.toml, or over `--max-len <bytes>`, and
+drops those that it makes duplicates. `--drop` removes them instead.";
+
+const STATS_BEFORE_HELP: &str = "\
+Every `cargo fuzz run` appends the execs per second, corpus size, coverage, new
+coverage, and crashes of the run to fuzz/stats/<target>.jsonl. This prints the
+most recent runs, and how the latest one compares with the ones before it.";
+
+const STATUS_BEFORE_HELP: &str = "\
+This shows how many of each fuzz target's corpus inputs and artifacts git
+tracks. The artifacts that it doesn't are listed with a warning: the crashes
+they reproduce are only on this machine until they're committed, or turned into
+regression tests with `cargo fuzz gen-test`.
+
+`cargo fuzz init` ignores the artifacts and, unless `--lfs-corpus` is given,
+the corpus in fuzz/.gitignore.";
+
+const OSS_FUZZ_INIT_BEFORE_HELP: &str = "\
+This writes the project.yaml, Dockerfile, and build.sh that onboard the crate to
+OSS-Fuzz (https://google.github.io/oss-fuzz/) to fuzz/oss-fuzz/<project>. The
+build script builds every fuzz target with the features set in fuzz/fuzz.toml,
+and installs each target's dictionary, seed corpus, and libFuzzer arguments
+from fuzz/fuzz.toml alongside it.
+
+Copy the directory to projects/ in a checkout of the OSS-Fuzz repository to
+test it and submit it.";
+
+const SESSION_BEFORE_HELP: &str = "\
+`cargo fuzz session save <name> <target>` builds the fuzz target and copies its
+corpus, the libFuzzer arguments, a random seed, and a hash of the fuzz target's
+binary to fuzz/sessions/<name>. The session directory can be copied to other
+checkouts to share it.
+
+`cargo fuzz session resume <name>` fuzzes with the session's corpus, arguments,
+and seed, so that resuming a session with an unchanged fuzz target replays the
+same inputs. New inputs are added to the session's corpus as they are found, so
+resuming again later picks up where the last run left off. Resuming fails if
+the fuzz target's binary has changed since the session was saved, unless
+`--force` is given.";
+
+const CLEAN_BEFORE_HELP: &str = "\
+Each configuration of engine, sanitizer, and extra instrumentation that fuzz
+targets are built with has a directory of its own, like
+fuzz/target/<triple>/libfuzzer-address/ or
+fuzz/target/<triple>/libfuzzer-none-coverage/, so that switching between them
+doesn't rebuild everything. This removes those directories, for every target
+triple, or only the ones of the configurations given with `--config`.";
+
+const RM_BEFORE_HELP: &str = "\
+This removes the fuzz target's source file, its [[bin]] in fuzz/Cargo.toml, its
+settings in fuzz/fuzz.toml, and the regression tests that `cargo fuzz gen-test`
+wrote for it. Its corpus and artifacts are kept unless `--data` is given, which
+also removes its coverage, dictionaries, statistics, and everything else under
+fuzz/ named after it.";
+
+const MV_BEFORE_HELP: &str = "\
+This renames the fuzz target's source file, its [[bin]] in fuzz/Cargo.toml, its
+settings in fuzz/fuzz.toml, its regression tests, and its corpus, artifacts,
+and everything else under fuzz/ named after it. A corpus that fuzz.toml puts
+somewhere else stays where it is.";
+
+const MIGRATE_BEFORE_HELP: &str = "\
+Fuzz crates made by `cargo fuzz init` have a library, in fuzz/src/lib.rs, that
+their fuzz targets share: it re-exports the fuzzed crate, and holds what several
+fuzz targets use, which is built once rather than into each of them. This adds
+one to a fuzz crate from before there was one, along with its [lib] in
+fuzz/Cargo.toml, and leaves its fuzz targets as they are.";
+
+const SERVE_BEFORE_HELP: &str = "\
+Run this on the machine that keeps the project's corpora. It serves each fuzz
+target's corpus over HTTP to `cargo fuzz worker`s, adds the inputs they find to
+fuzz/corpus/<target>/, and saves the crash artifacts they find to
+fuzz/artifacts/<target>/. There's no authentication, so only listen on a trusted
+network.";
+
+const WORKER_BEFORE_HELP: &str = "\
+Run this on each machine with a checkout of the project. The worker fuzzes the
+target in rounds of `--sync-interval` seconds. Before each round, it downloads
+the coordinator's corpus inputs that its own corpus is missing and uploads the
+ones the coordinator is missing, and after each round, it uploads any crash
+artifacts found.";
+
+const DOCTOR_BEFORE_HELP: &str = "\
+Checks that rustc is a nightly compiler, that a C++ compiler is available to
+build libFuzzer with, that the llvm-tools-preview component is installed for
+`cargo fuzz coverage`, and that the sanitizer runtimes for the target are
+installed. On Linux, it also checks that ptrace_scope lets LeakSanitizer and
+debuggers work, and whether crashes dump core through a slow crash reporter.
+
+Each problem found is printed with how to fix it. The command fails if any of
+them stop fuzzing from working.";
+
+const COMPLETIONS_BEFORE_HELP: &str = "\
+Prints a completion script for `cargo fuzz` and `cargo-fuzz` in bash, zsh or
+fish. Fuzz target names, artifacts and corpus directories are completed from
+the fuzz directory of the project in the current directory each time, so new
+targets complete without regenerating the script. For example:
+
+    cargo fuzz completions bash > ~/.local/share/bash-completion/completions/cargo-fuzz
+    cargo fuzz completions zsh > ~/.zfunc/_cargo-fuzz
+    cargo fuzz completions fish > ~/.config/fish/completions/cargo-fuzz.fish
+
+The bash script also takes over completing `cargo`, handing everything but
+`cargo fuzz` to Cargo's own completions, so source it after them.";
+
+const BENCH_BEFORE_HELP: &str = "\
+`cargo fuzz run --slow-threshold-ms <ms>` saves the inputs that took longer than
+that to run to fuzz/slow/<target>/: the slow units libFuzzer reports, and new
+corpus inputs that are as slow when timed again. It records how long each one
+took in fuzz/slow/<target>.json.
+
+This builds the fuzz target, runs each of those inputs again, and compares how
+long they take now with the recorded times, for example after optimizing the
+code they're slow in.
+
+With `--corpus`, it replays the whole corpus `--runs` times through an optimized
+build of the fuzz target that has no instrumentation or sanitizer, and reports
+its throughput in inputs and bytes per second, and the median and 99th
+percentile of how long an input takes. `--update` records them in
+fuzz/bench/<target>.json to compare with next time.";
+
+const MIRI_BEFORE_HELP: &str = "\
+This builds the fuzz target against a stand-in for libfuzzer-sys and runs it
+under Miri on each input of its corpus and seed corpora, or on the given inputs
+and directories of inputs, like artifacts. Miri catches undefined behavior that
+the sanitizers miss, like invalid values, aliasing violations, and reads of
+freed memory that has been reused, but it is far slower than native code, so
+each input runs on its own for at most `--timeout` seconds.
+
+Inputs whose file names match a `--skip` glob or one in the target's
+`miri-skip` list in fuzz.toml are skipped. Each input Miri finds undefined
+behavior in is saved to fuzz/miri/<target>/ along with Miri's report, and a
+summary is printed and saved there too. The command fails if it found any.
+
+Miri needs a nightly toolchain with the miri component: run
+`rustup +nightly component add miri` and use `cargo +nightly fuzz miri`.";
+
+const REPRO_BEFORE_HELP: &str = "\
+This rebuilds the fuzz target with full debug info, in a target directory of
+its own, and runs it on the artifact with RUST_BACKTRACE=1 and the sanitizer
+set up to symbolize its reports with llvm-symbolizer.
+
+With `--debugger gdb` or `--debugger lldb`, the fuzz target runs under
+`rust-gdb` or `rust-lldb` instead, and sanitizers abort on the first error so
+that the debugger stops there. `--print-command` prints the command, with its
+environment, instead of running it.
+
+The exit code is 0 when the artifact runs without crashing, and 2 when the
+crash is reproduced.";
+
+const PROFILE_BEFORE_HELP: &str = "\
+The fuzz target is built with frame pointers, and replays its corpus once, or
+with `--time`, fuzzes for that long, under `perf record`, or DTrace where
+there's no `perf`. The sampled stacks are folded into
+fuzz/profile/<target>/stacks.folded, in the format of `flamegraph.pl` and
+`inferno`, and drawn as fuzz/profile/<target>/flamegraph.svg. The functions
+the most samples were taken in are listed, as the first places to look for
+what limits the executions per second.
+
+The build uses the same sanitizer as `cargo fuzz run`, so its overhead shows up
+in the profile as it does while fuzzing; use `--sanitizer none` to leave it
+out.";
+
+const PACKAGE_REPRO_BEFORE_HELP: &str = "\
+This builds the fuzz target like `cargo fuzz repro` does, with full debug info,
+and writes a .tar.gz archive with the binary, the artifact, a repro.sh that
+runs one on the other with the project's sanitizer options, and a README.txt,
+for someone who doesn't have cargo-fuzz or Rust installed to reproduce the
+crash with.
+
+The binary has the fuzzed crate, its dependencies, libFuzzer and the sanitizer
+runtime linked in statically, and only needs the system's C and C++ runtime
+libraries. The archive is created with the `tar` command.";
+
+const BISECT_BEFORE_HELP: &str = "\
+This runs `git bisect` between the --good and --bad revisions in a temporary
+git worktree, so your own checkout is left alone. At each revision the fuzz
+target is rebuilt and run on the artifact: revisions where it crashes are bad,
+ones where it doesn't are good, and ones where the fuzz target doesn't exist or
+fails to build are skipped. Both ends are checked first.
+
+The first bad commit is printed to stdout when it's found.";
+
+const REPORT_BEFORE_HELP: &str = "\
+This reproduces the crash like `cargo fuzz repro`, and prints a Markdown report
+of it to paste into an issue: the cargo-fuzz and rustc versions, the fuzz
+target's source, the input as a byte string literal (or base64, for long
+inputs), the symbolized backtrace, and the command that reproduces it. With
+`--minimize`, the input is minimized first.
+
+With `--github <owner>/<repo>`, an issue with the report is opened in that
+repository instead, through the GitHub API at $GITHUB_API_URL (or
+https://api.github.com). This needs curl on PATH, and a token that can create
+issues in $GITHUB_TOKEN.";
+
+const ANNOTATE_BEFORE_HELP: &str = "\
+This reproduces the crash like `cargo fuzz repro`, and adds a comment above the
+line it happens at, like
+
+    // FUZZ-CRASH: <hash> reproduced by fuzz/artifacts/<target>/crash-...
+
+The line is the panic's location, or else the innermost frame of the backtrace
+that's in the fuzzed crate or the fuzz target. The hash is the one `cargo fuzz
+triage` groups crashes by, and a line that already has the crash's comment is
+left alone, so `grep -r FUZZ-CRASH` lists the code with open fuzz findings.
+
+With `--diff`, the change is printed as a unified diff instead of made.";
+
+const DICT_BEFORE_HELP: &str = "\
+This scans the fuzzed crate's sources and the fuzz target for string and byte
+string literals, and for integer literals used as match patterns, and writes
+them to fuzz/dictionaries/<target>.dict. Format strings are skipped.
+
+`cargo fuzz run` passes that dictionary to the fuzzing engine whenever it
+exists, merged with any dictionaries in fuzz/dictionaries/<target>/, unless
+`--dict` or another `-dict=` is given.";
+
 /// A trait for running our various commands.
 trait RunCommand {
     /// Run this command!
@@ -102,6 +585,8 @@ trait RunCommand {
         .required(false)
         .hidden(true)),
 )]
+// The command is parsed once, so there's nothing to gain from boxing `Run`.
+#[allow(clippy::large_enum_variant)]
 enum Command {
     /// Initialize the fuzz directory
     Init(options::Init),
@@ -109,6 +594,18 @@ enum Command {
     /// Add a new fuzz target
     Add(options::Add),
 
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(RM_BEFORE_HELP))]
+    /// Remove a fuzz target
+    Rm(options::Rm),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(MV_BEFORE_HELP))]
+    /// Rename a fuzz target
+    Mv(options::Mv),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(MIGRATE_BEFORE_HELP))]
+    /// Update a fuzz crate to the layout that `init` makes
+    Migrate(options::Migrate),
+
     #[structopt(
         template(LONG_ABOUT_TEMPLATE),
         before_help(BUILD_BEFORE_HELP),
@@ -117,6 +614,14 @@ enum Command {
     /// Build fuzz targets
     Build(options::Build),
 
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(CHECK_BEFORE_HELP))]
+    /// Check fuzz targets, or replay their corpora under `cargo test`
+    Check(options::Check),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(CLEAN_BEFORE_HELP))]
+    /// Remove the builds of fuzz targets
+    Clean(options::Clean),
+
     /// Print the `std::fmt::Debug` output for an input
     Fmt(options::Fmt),
 
@@ -131,11 +636,125 @@ enum Command {
     /// Run a fuzz target
     Run(options::Run),
 
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(WATCH_BEFORE_HELP))]
+    /// Run a fuzz target, rebuilding and restarting it when its sources change
+    Watch(options::Watch),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(CI_BEFORE_HELP))]
+    /// Fuzz every target for a share of a time budget, and fail on new crashes
+    Ci(options::Ci),
+
     /// Minify a corpus
     Cmin(options::Cmin),
 
     /// Minify a test case
     Tmin(options::Tmin),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(REPRO_BEFORE_HELP))]
+    /// Reproduce a crash, optionally under a debugger
+    Repro(options::Repro),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(PACKAGE_REPRO_BEFORE_HELP))]
+    /// Package a crash and a fuzz target binary that reproduces it into an
+    /// archive
+    PackageRepro(options::PackageRepro),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(REPORT_BEFORE_HELP))]
+    /// Write a Markdown report of a crash, or open a GitHub issue with it
+    Report(options::Report),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(ANNOTATE_BEFORE_HELP))]
+    /// Mark the source line that a crash happens at with a comment
+    Annotate(options::Annotate),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(COVERAGE_BEFORE_HELP))]
+    /// Generate a coverage report for a fuzz target's corpus
+    Coverage(options::Coverage),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(EXPLAIN_BEFORE_HELP))]
+    /// Show the code that an input covers and no other corpus input does
+    Explain(options::Explain),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(SUGGEST_SPLIT_BEFORE_HELP))]
+    /// Suggest splitting a fuzz target that feeds unrelated entry points
+    SuggestSplit(options::SuggestSplit),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(BLOCKERS_BEFORE_HELP))]
+    /// Find the magic numbers and checksums that the corpus never gets past
+    Blockers(options::Blockers),
+
+    /// Group a target's crash artifacts by unique bug
+    Triage(options::Triage),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(VERIFY_FIXES_BEFORE_HELP))]
+    /// Check which crash artifacts still crash the current code
+    VerifyFixes(options::VerifyFixes),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(BISECT_BEFORE_HELP))]
+    /// Find the git commit that made an artifact start crashing
+    Bisect(options::Bisect),
+
+    /// Generate a regression test that replays an artifact
+    GenTest(options::GenTest),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(CORPUS_BEFORE_HELP))]
+    /// Sync corpora with remote storage, import and export their inputs,
+    /// seed them from tests, share them between targets, show where their
+    /// inputs came from, or trim them to a maximum length
+    Corpus(options::Corpus),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(DICT_BEFORE_HELP))]
+    /// Generate a libFuzzer dictionary from the fuzzed crate's source
+    Dict(options::Dict),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(STATS_BEFORE_HELP))]
+    /// Show statistics of a fuzz target's past runs
+    Stats(options::Stats),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(STATUS_BEFORE_HELP))]
+    /// Show which of the fuzz targets' inputs and artifacts git tracks
+    Status(options::Status),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(OSS_FUZZ_INIT_BEFORE_HELP))]
+    /// Generate the files needed to fuzz the crate on OSS-Fuzz
+    OssFuzzInit(options::OssFuzzInit),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(BENCH_BEFORE_HELP))]
+    /// Time a fuzz target's slow inputs again
+    Bench(options::Bench),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(PROFILE_BEFORE_HELP))]
+    /// Profile a fuzz target under `perf` or DTrace, and draw a flamegraph
+    Profile(options::Profile),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(MIRI_BEFORE_HELP))]
+    /// Replay a fuzz target's inputs under Miri to find undefined behavior
+    Miri(options::Miri),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(SESSION_BEFORE_HELP))]
+    /// Save and resume fuzzing sessions
+    Session(options::Session),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(SERVE_BEFORE_HELP))]
+    /// Coordinate fuzzing a project's targets across machines
+    Serve(options::Serve),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(WORKER_BEFORE_HELP))]
+    /// Fuzz a target for a `cargo fuzz serve` coordinator
+    Worker(options::Worker),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(DOCTOR_BEFORE_HELP))]
+    /// Check the environment for problems that stop fuzzing from working
+    Doctor(options::Doctor),
+
+    #[structopt(template(BEFORE_HELP_TEMPLATE), before_help(COMPLETIONS_BEFORE_HELP))]
+    /// Print a shell completion script
+    Completions(options::Completions),
+
+    #[structopt(name = "__complete", setting(structopt::clap::AppSettings::Hidden))]
+    /// Print the completions of the last word of a command line, for the
+    /// completion scripts
+    Complete(options::Complete),
 }
 
 impl RunCommand for Command {
@@ -143,16 +762,55 @@ impl RunCommand for Command {
         match self {
             Command::Init(x) => x.run_command(),
             Command::Add(x) => x.run_command(),
+            Command::Rm(x) => x.run_command(),
+            Command::Mv(x) => x.run_command(),
+            Command::Migrate(x) => x.run_command(),
             Command::Build(x) => x.run_command(),
+            Command::Check(x) => x.run_command(),
+            Command::Clean(x) => x.run_command(),
             Command::List(x) => x.run_command(),
             Command::Fmt(x) => x.run_command(),
             Command::Run(x) => x.run_command(),
+            Command::Ci(x) => x.run_command(),
+            Command::Watch(x) => x.run_command(),
             Command::Cmin(x) => x.run_command(),
             Command::Tmin(x) => x.run_command(),
+            Command::Repro(x) => x.run_command(),
+            Command::PackageRepro(x) => x.run_command(),
+            Command::Report(x) => x.run_command(),
+            Command::Annotate(x) => x.run_command(),
+            Command::Coverage(x) => x.run_command(),
+            Command::Explain(x) => x.run_command(),
+            Command::SuggestSplit(x) => x.run_command(),
+            Command::Blockers(x) => x.run_command(),
+            Command::Triage(x) => x.run_command(),
+            Command::VerifyFixes(x) => x.run_command(),
+            Command::Bisect(x) => x.run_command(),
+            Command::GenTest(x) => x.run_command(),
+            Command::Corpus(x) => x.run_command(),
+            Command::Dict(x) => x.run_command(),
+            Command::Stats(x) => x.run_command(),
+            Command::Status(x) => x.run_command(),
+            Command::OssFuzzInit(x) => x.run_command(),
+            Command::Bench(x) => x.run_command(),
+            Command::Profile(x) => x.run_command(),
+            Command::Miri(x) => x.run_command(),
+            Command::Session(x) => x.run_command(),
+            Command::Serve(x) => x.run_command(),
+            Command::Worker(x) => x.run_command(),
+            Command::Doctor(x) => x.run_command(),
+            Command::Completions(x) => x.run_command(),
+            Command::Complete(x) => x.run_command(),
         }
     }
 }
 
-fn main() -> Result<()> {
-    Command::from_args().run_command()
+fn main() {
+    if rustc_wrapper::is_wrapping() {
+        std::process::exit(rustc_wrapper::run());
+    }
+    if let Err(error) = Command::from_args().run_command() {
+        eprintln!("Error: {:?}", error);
+        std::process::exit(exit::code(&error));
+    }
 }
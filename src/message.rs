@@ -0,0 +1,113 @@
+//! Machine-readable output, enabled with `--message-format json`.
+//!
+//! Every message is printed to stdout as a single line of JSON with a `reason`
+//! field saying what kind of message it is, just like Cargo's own
+//! `--message-format json`. Human-readable output, including libFuzzer's logs,
+//! still goes to stderr.
+
+use crate::health::Health;
+use serde::Serialize;
+use std::fmt as stdfmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+impl stdfmt::Display for MessageFormat {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MessageFormat::Human => "human",
+                MessageFormat::Json => "json",
+            }
+        )
+    }
+}
+
+impl FromStr for MessageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(MessageFormat::Human),
+            "json" => Ok(MessageFormat::Json),
+            _ => Err(format!("unknown message format: {}", s)),
+        }
+    }
+}
+
+impl MessageFormat {
+    pub fn is_json(self) -> bool {
+        self == MessageFormat::Json
+    }
+
+    /// Print a message, if machine-readable output was requested.
+    pub fn emit(self, message: &Message) {
+        if self.is_json() {
+            println!(
+                "{}",
+                serde_json::to_string(message).expect("messages always serialize")
+            );
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message<'a> {
+    /// A fuzz target that exists in the project, as printed by `list`, with
+    /// its health for `list --verbose`.
+    FuzzTarget {
+        name: &'a str,
+        #[serde(flatten, skip_serializing_if = "Option::is_none")]
+        health: Option<&'a Health>,
+    },
+
+    /// Building one or all fuzz targets finished.
+    BuildFinished {
+        target: Option<&'a str>,
+        success: bool,
+    },
+
+    /// The fuzzer found an input that covers new code.
+    NewCoverage {
+        target: &'a str,
+        execs: u64,
+        coverage: Option<u64>,
+        features: Option<u64>,
+        corpus_entries: Option<u64>,
+        corpus_bytes: Option<u64>,
+        execs_per_sec: Option<u64>,
+    },
+
+    /// The fuzzer wrote a crashing (or leaking, timing out, ...) input.
+    CrashFound { target: &'a str, artifact: String },
+
+    /// A fuzz target stopped running, either by itself or because its coverage
+    /// stalled with `--until-new-coverage-stalls`.
+    RunFinished {
+        target: &'a str,
+        success: bool,
+        exit_code: Option<i32>,
+        stalled: bool,
+    },
+
+    /// A corpus was minimized with `cmin`.
+    CorpusMinimized {
+        target: &'a str,
+        corpus: String,
+        success: bool,
+    },
+
+    /// A test case was minimized with `tmin`.
+    TestCaseMinimized {
+        target: &'a str,
+        artifact: Option<String>,
+        success: bool,
+    },
+}
@@ -0,0 +1,102 @@
+//! Replaying a fuzz target's inputs under Miri, for `cargo fuzz miri`.
+//!
+//! Miri interprets the fuzz target and checks each operation against Rust's
+//! rules, so it catches undefined behavior that the sanitizers can't see,
+//! like invalid values, aliasing violations, and reads through dangling
+//! pointers that happen to land in live memory. It's orders of magnitude
+//! slower than native code, so each input runs on its own, under a timeout.
+
+use std::fmt::{self, Write as _};
+use std::path::PathBuf;
+
+/// What happened when Miri ran the fuzz target on an input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// Miri found undefined behavior, which it described with this line.
+    UndefinedBehavior(String),
+    /// Miri doesn't support something the fuzz target does, like calling C
+    /// code, so it couldn't finish the run.
+    Unsupported(String),
+    /// The fuzz target panicked, which fuzzing finds without Miri.
+    Panicked,
+    /// Miri stopped with any other error, like a memory leak.
+    Failed(String),
+    TimedOut,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Passed => f.write_str("passed"),
+            Outcome::UndefinedBehavior(error) => write!(f, "undefined behavior: {}", error),
+            Outcome::Unsupported(error) => write!(f, "unsupported: {}", error),
+            Outcome::Panicked => f.write_str("panicked"),
+            Outcome::Failed(error) => write!(f, "failed: {}", error),
+            Outcome::TimedOut => f.write_str("timed out"),
+        }
+    }
+}
+
+/// Classify a run from its exit code and Miri's report on stderr.
+pub fn classify(code: Option<i32>, stderr: &str) -> Outcome {
+    let error = |prefix: &str| {
+        stderr
+            .lines()
+            .find_map(|line| line.strip_prefix(prefix))
+            .map(|error| error.trim().to_string())
+    };
+    if code == Some(0) {
+        Outcome::Passed
+    } else if let Some(error) = error("error: Undefined Behavior:") {
+        Outcome::UndefinedBehavior(error)
+    } else if let Some(error) = error("error: unsupported operation:") {
+        Outcome::Unsupported(error)
+    } else if code == Some(101) {
+        Outcome::Panicked
+    } else {
+        Outcome::Failed(error("error:").unwrap_or_else(|| match code {
+            Some(code) => format!("exit code {}", code),
+            None => "killed by a signal".to_string(),
+        }))
+    }
+}
+
+/// A summary of the outcomes of a replay, with each input that had undefined
+/// behavior and where it was saved.
+pub fn render(target: &str, results: &[(PathBuf, Outcome, Option<PathBuf>)]) -> String {
+    let count = |matches: fn(&Outcome) -> bool| results.iter().filter(|r| matches(&r.1)).count();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Replayed {} input(s) of `{}` under Miri:",
+        results.len(),
+        target
+    );
+    let counts = [
+        ("passed", count(|o| *o == Outcome::Passed)),
+        (
+            "undefined behavior",
+            count(|o| matches!(o, Outcome::UndefinedBehavior(_))),
+        ),
+        (
+            "unsupported by Miri",
+            count(|o| matches!(o, Outcome::Unsupported(_))),
+        ),
+        ("panicked", count(|o| *o == Outcome::Panicked)),
+        ("failed", count(|o| matches!(o, Outcome::Failed(_)))),
+        ("timed out", count(|o| *o == Outcome::TimedOut)),
+    ];
+    for (what, count) in counts.iter().filter(|(_, count)| *count > 0) {
+        let _ = writeln!(out, "  {:>6} {}", count, what);
+    }
+    for (input, outcome, saved) in results {
+        if let Outcome::UndefinedBehavior(error) = outcome {
+            let _ = writeln!(out, "\n{}\n  {}", input.display(), error);
+            if let Some(saved) = saved {
+                let _ = writeln!(out, "  saved to {}", saved.display());
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,315 @@
+//! Sampling a fuzzer's memory and CPU use over time, for `cargo fuzz run
+//! --monitor`, to spot slow leaks that a short run never takes to the RSS
+//! limit.
+//!
+//! A sample is of the fuzzer's whole process tree, so that the jobs and the
+//! fork mode's children count too. Samples are read from `/proc`, so this
+//! only works on Linux. Each one is appended to a CSV file as it's taken, so
+//! that the file is complete even if the run is interrupted.
+
+use crate::libfuzzer;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How often the fuzzer is sampled.
+pub const INTERVAL: Duration = Duration::from_secs(5);
+
+const HEADER: &str = "seconds,rss_mb,cpu_percent,execs,execs_per_sec";
+
+/// The fuzzer's resource use at one point of the run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// Seconds since fuzzing started.
+    pub seconds: u64,
+    pub rss_kb: u64,
+    /// The CPU time the fuzzer used since the sample before, as a share of
+    /// one CPU, or `None` for the first sample of a process.
+    pub cpu_percent: Option<f64>,
+    /// The inputs run so far, over all restarts of the fuzzer.
+    pub execs: Option<u64>,
+    /// The inputs run per second between the fuzzer's last two reports of
+    /// its progress, which come further apart the longer it runs.
+    pub execs_per_sec: Option<u64>,
+}
+
+/// Samples a fuzzer every `INTERVAL` while it runs, across restarts.
+#[derive(Debug)]
+pub struct Monitor {
+    path: PathBuf,
+    file: fs::File,
+    started: Instant,
+    next: Instant,
+    ticks_per_sec: u64,
+    page_kb: u64,
+    /// The process that was sampled last, and its tree's CPU time then.
+    last_cpu: Option<(u32, Instant, u64)>,
+    /// The inputs run by the fuzzer's earlier processes.
+    earlier_execs: u64,
+    /// When the fuzzer last reported its progress, and the inputs run in all
+    /// by then, and the same for the report before.
+    progress: Option<(Instant, u64)>,
+    previous_progress: Option<(Instant, u64)>,
+    samples: Vec<Sample>,
+}
+
+impl Monitor {
+    /// Start monitoring, writing the samples to the CSV file at `path`.
+    pub fn new(path: &Path) -> Result<Monitor> {
+        if !Path::new("/proc/self/stat").is_file() {
+            bail!("`--monitor` reads the fuzzer's resource use from /proc, which only Linux has");
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+        }
+        let mut file = fs::File::create(path)
+            .with_context(|| format!("failed to create {}", path.display()))?;
+        writeln!(file, "{}", HEADER)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        let now = Instant::now();
+        Ok(Monitor {
+            path: path.to_path_buf(),
+            file,
+            started: now,
+            next: now,
+            ticks_per_sec: getconf("CLK_TCK").unwrap_or(100),
+            page_kb: getconf("PAGESIZE").map_or(4, |bytes| bytes / 1024),
+            last_cpu: None,
+            earlier_execs: 0,
+            progress: None,
+            previous_progress: None,
+            samples: Vec::new(),
+        })
+    }
+
+    /// Keep track of how many inputs the fuzzer has run from its output.
+    pub fn observe(&mut self, line: &str) {
+        let status = match libfuzzer::Status::parse(line) {
+            Some(status) => status,
+            None => return,
+        };
+        // A restarted fuzzer counts from 0 again.
+        let current = self.progress.map_or(0, |(_, execs)| execs) - self.earlier_execs;
+        if status.execs < current {
+            self.earlier_execs += current;
+        }
+        self.previous_progress = self.progress;
+        self.progress = Some((Instant::now(), self.earlier_execs + status.execs));
+    }
+
+    /// Sample the process tree of `pid`, if it's time to.
+    pub fn tick(&mut self, pid: u32) -> Result<()> {
+        let now = Instant::now();
+        if now < self.next {
+            return Ok(());
+        }
+        self.next += INTERVAL;
+        let (rss_pages, cpu_ticks) = match process_tree(pid) {
+            Some(usage) => usage,
+            None => return Ok(()),
+        };
+
+        let cpu_percent = match self.last_cpu {
+            Some((last_pid, then, ticks)) if last_pid == pid && cpu_ticks >= ticks => {
+                let elapsed = now.duration_since(then).as_secs_f64();
+                let used = (cpu_ticks - ticks) as f64 / self.ticks_per_sec as f64;
+                Some(100.0 * used / elapsed.max(f64::EPSILON))
+            }
+            _ => None,
+        };
+        self.last_cpu = Some((pid, now, cpu_ticks));
+        let execs_per_sec = match (self.previous_progress, self.progress) {
+            (Some((then, before)), Some((at, execs))) => {
+                let elapsed = at.duration_since(then).as_secs_f64();
+                Some(((execs - before) as f64 / elapsed.max(f64::EPSILON)).round() as u64)
+            }
+            _ => None,
+        };
+
+        let sample = Sample {
+            seconds: now.duration_since(self.started).as_secs(),
+            rss_kb: rss_pages * self.page_kb,
+            cpu_percent,
+            execs: self.progress.map(|(_, execs)| execs),
+            execs_per_sec,
+        };
+        writeln!(self.file, "{}", csv_row(&sample))
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        self.samples.push(sample);
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+}
+
+fn csv_row(sample: &Sample) -> String {
+    let or_empty = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_default();
+    format!(
+        "{},{:.1},{},{},{}",
+        sample.seconds,
+        sample.rss_kb as f64 / 1024.0,
+        sample
+            .cpu_percent
+            .map(|cpu| format!("{:.1}", cpu))
+            .unwrap_or_default(),
+        or_empty(sample.execs),
+        or_empty(sample.execs_per_sec)
+    )
+}
+
+/// The resident pages and the CPU time in clock ticks of `pid` and all its
+/// descendants, or `None` if it's gone.
+fn process_tree(pid: u32) -> Option<(u64, u64)> {
+    let mut processes = HashMap::new();
+    for entry in fs::read_dir("/proc").ok()? {
+        let id = match entry
+            .ok()
+            .and_then(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        {
+            Some(id) => id,
+            None => continue,
+        };
+        if let Some(stat) = fs::read_to_string(format!("/proc/{}/stat", id))
+            .ok()
+            .and_then(|stat| parse_stat(&stat))
+        {
+            processes.insert(id, stat);
+        }
+    }
+    processes.get(&pid)?;
+
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while i < tree.len() {
+        let parent = tree[i];
+        tree.extend(
+            processes
+                .iter()
+                .filter(|(_, stat)| stat.ppid == parent)
+                .map(|(id, _)| *id),
+        );
+        i += 1;
+    }
+    Some(tree.iter().fold((0, 0), |(rss, cpu), id| {
+        let stat = &processes[id];
+        (rss + stat.rss_pages, cpu + stat.cpu_ticks)
+    }))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Stat {
+    ppid: u32,
+    cpu_ticks: u64,
+    rss_pages: u64,
+}
+
+/// Parse `/proc/<pid>/stat`, whose second field is the command's name in
+/// parentheses, which may itself contain spaces and parentheses.
+fn parse_stat(stat: &str) -> Option<Stat> {
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3)?.parse::<u64>().ok();
+    Some(Stat {
+        ppid: field(4)? as u32,
+        cpu_ticks: field(14)? + field(15)?,
+        rss_pages: field(24)?,
+    })
+}
+
+fn getconf(name: &str) -> Option<u64> {
+    let output = Command::new("getconf").arg(name).output().ok()?;
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// A summary of the samples of a run, for stderr: the peak RSS, how RSS
+/// changed after the fuzzer warmed up, and the execution speed over time.
+pub fn summary(target: &str, samples: &[Sample], path: &Path) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Monitored `{}` with {} sample(s), in {}:",
+        target,
+        samples.len(),
+        path.display()
+    );
+    let peak = match samples.iter().max_by_key(|s| s.rss_kb) {
+        Some(peak) => peak,
+        None => return out,
+    };
+    let mb = |kb: u64| kb as f64 / 1024.0;
+    let _ = writeln!(
+        out,
+        "\tpeak RSS: {:.1} MB, at {}s",
+        mb(peak.rss_kb),
+        peak.seconds
+    );
+
+    // The fuzzer's memory grows while it loads the corpus, so the growth
+    // that matters is over the rest of the run.
+    let warmed = &samples[samples.len() / 4];
+    let last = &samples[samples.len() - 1];
+    if samples.len() >= 4 {
+        let growth = mb(last.rss_kb) - mb(warmed.rss_kb);
+        let per_hour = growth * 3600.0 / (last.seconds - warmed.seconds) as f64;
+        let _ = writeln!(
+            out,
+            "\tRSS: {:.1} MB at {}s, {:.1} MB at {}s ({:+.1} MB/hour)",
+            mb(warmed.rss_kb),
+            warmed.seconds,
+            mb(last.rss_kb),
+            last.seconds,
+            per_hour
+        );
+        if is_growing(&samples[samples.len() / 4..]) {
+            let _ = writeln!(
+                out,
+                "\twarning: RSS kept growing after warming up, which may be a slow leak; \
+                 `--mode leaks` finds the inputs that leak"
+            );
+        }
+    }
+
+    let speeds: Vec<(u64, u64)> = samples
+        .iter()
+        .filter_map(|s| Some((s.seconds, s.execs_per_sec?)))
+        .collect();
+    if let (Some(first), Some(last)) = (speeds.first(), speeds.last()) {
+        let mean = speeds.iter().map(|(_, speed)| speed).sum::<u64>() / speeds.len() as u64;
+        let _ = writeln!(
+            out,
+            "\texecs/s: {} at {}s, {} at {}s, {} on average",
+            first.1, first.0, last.1, last.0, mean
+        );
+    }
+    let cpu: Vec<f64> = samples.iter().filter_map(|s| s.cpu_percent).collect();
+    if !cpu.is_empty() {
+        let _ = writeln!(
+            out,
+            "\tCPU: {:.0}% on average",
+            cpu.iter().sum::<f64>() / cpu.len() as f64
+        );
+    }
+    out
+}
+
+/// Whether RSS rose by more than a twentieth over each half of `samples`,
+/// rather than levelling off like it does once the fuzzer's caches fill up.
+fn is_growing(samples: &[Sample]) -> bool {
+    if samples.len() < 4 {
+        return false;
+    }
+    let grew = |from: &Sample, to: &Sample| to.rss_kb > from.rss_kb + from.rss_kb / 20;
+    let middle = &samples[samples.len() / 2];
+    grew(&samples[0], middle) && grew(middle, &samples[samples.len() - 1])
+}
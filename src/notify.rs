@@ -0,0 +1,319 @@
+//! Posting a run's events to a webhook, for `cargo fuzz run
+//! --notify-webhook`, so that a long unattended run pings someone as soon as
+//! it finds something.
+//!
+//! Each event is POSTed as a JSON object with its kind in `event`, and a
+//! summary in `text`, which is what Slack's incoming webhooks show:
+//!
+//! * `crash-found`, with the inputs saved to the target's artifacts
+//!   directory, as soon as there are any, so that fork mode reports crashes
+//!   without stopping. Fork mode can save a flood of them, so after the
+//!   first, the rest are sent at most once a minute, together.
+//! * `coverage-plateau`, once the fuzzer has found no new coverage for the
+//!   plateau limit, and again whenever it plateaus after finding more.
+//! * `run-finished`, with whether the run succeeded.
+//!
+//! A webhook that can't be reached only gets a warning: that's no reason to
+//! stop fuzzing.
+
+use crate::options::StallLimit;
+use crate::{libfuzzer, stats, utils};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the artifacts directory and the plateau are checked.
+const INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long to gather crashes after notifying some, before notifying more.
+const CRASH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How long the coverage has to stay the same to be a plateau, unless
+/// `--notify-plateau` says otherwise.
+pub const DEFAULT_PLATEAU: StallLimit = StallLimit::Seconds(60 * 60);
+
+/// The longest a webhook gets to answer.
+const TIMEOUT_SECS: &str = "30";
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event<'a> {
+    #[serde(rename_all = "kebab-case")]
+    CrashFound {
+        target: &'a str,
+        artifacts: Vec<String>,
+    },
+    #[serde(rename_all = "kebab-case")]
+    CoveragePlateau {
+        target: &'a str,
+        coverage: Option<u64>,
+        features: Option<u64>,
+        /// The plateau limit, like `3600 seconds`.
+        limit: String,
+    },
+    #[serde(rename_all = "kebab-case")]
+    RunFinished {
+        target: &'a str,
+        success: bool,
+        crashes: usize,
+        duration_secs: u64,
+    },
+}
+
+impl Event<'_> {
+    /// A one-line summary of the event, for chat.
+    pub fn text(&self) -> String {
+        match self {
+            Event::CrashFound { target, artifacts } => match artifacts.as_slice() {
+                [artifact] => format!("Fuzzing `{}` found a crash: `{}`", target, artifact),
+                _ => format!(
+                    "Fuzzing `{}` found {} crashes: `{}` and {} more",
+                    target,
+                    artifacts.len(),
+                    artifacts.first().map_or("", String::as_str),
+                    artifacts.len().saturating_sub(1)
+                ),
+            },
+            Event::CoveragePlateau {
+                target,
+                coverage,
+                features,
+                limit,
+            } => {
+                let or_unknown = |value: &Option<u64>| {
+                    value.map_or("unknown".to_string(), |value| value.to_string())
+                };
+                format!(
+                    "Fuzzing `{}` found no new coverage for {} (cov: {}, ft: {})",
+                    target,
+                    limit,
+                    or_unknown(coverage),
+                    or_unknown(features)
+                )
+            }
+            Event::RunFinished {
+                target,
+                success,
+                crashes,
+                duration_secs,
+            } => format!(
+                "Finished fuzzing `{}` after {}: {}",
+                target,
+                stats::format_duration(*duration_secs),
+                if *success {
+                    "ok".to_string()
+                } else if *crashes > 0 {
+                    format!("{} crash(es)", crashes)
+                } else {
+                    "failed".to_string()
+                }
+            ),
+        }
+    }
+}
+
+/// The JSON body that's posted for `event`.
+pub fn payload(event: &Event) -> String {
+    #[derive(Serialize)]
+    struct Payload<'a> {
+        text: String,
+        #[serde(flatten)]
+        event: &'a Event<'a>,
+    }
+    serde_json::to_string(&Payload {
+        text: event.text(),
+        event,
+    })
+    .expect("events always serialize")
+}
+
+/// Post `event` to the webhook at `url`, warning rather than failing if it
+/// can't be.
+pub fn post(url: &str, event: &Event) {
+    let data = [
+        "--header",
+        "Content-Type: application/json",
+        "--max-time",
+        TIMEOUT_SECS,
+        "--data-binary",
+        "@-",
+    ];
+    if let Err(e) = utils::curl_post(url, &data, payload(event).as_bytes()) {
+        eprintln!("warning: failed to notify the webhook: {:#}", e);
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    plateau: libfuzzer::StallDetector,
+    best: Option<u64>,
+    coverage: Option<u64>,
+    features: Option<u64>,
+    /// Whether the current plateau was notified already.
+    plateaued: bool,
+    /// The artifacts that were found already, notified or not.
+    seen: HashSet<PathBuf>,
+    /// The artifacts found since crashes were last notified, and when that
+    /// was.
+    pending: Vec<PathBuf>,
+    last_crashes: Option<Instant>,
+    finished: bool,
+}
+
+/// Notifies a webhook of a fuzz target's crashes and plateaus from a
+/// background thread, for as long as it's fuzzed.
+#[derive(Clone, Debug)]
+pub struct Notifier {
+    url: String,
+    target: String,
+    artifacts: PathBuf,
+    limit: StallLimit,
+    started: SystemTime,
+    state: Arc<Mutex<State>>,
+    /// Held while posting, so that the events arrive in order, and all of
+    /// them before the run finishes.
+    posting: Arc<Mutex<()>>,
+}
+
+impl Notifier {
+    /// Start notifying `url` of the new inputs in `artifacts` and of
+    /// `target` going without new coverage for `limit`.
+    pub fn start(url: &str, target: &str, artifacts: &Path, limit: StallLimit) -> Notifier {
+        let notifier = Notifier {
+            url: url.to_string(),
+            target: target.to_string(),
+            artifacts: artifacts.to_path_buf(),
+            limit,
+            started: SystemTime::now(),
+            state: Arc::new(Mutex::new(State {
+                plateau: libfuzzer::StallDetector::new(limit),
+                best: None,
+                coverage: None,
+                features: None,
+                plateaued: false,
+                seen: HashSet::new(),
+                pending: Vec::new(),
+                last_crashes: None,
+                finished: false,
+            })),
+            posting: Arc::new(Mutex::new(())),
+        };
+        let ticking = notifier.clone();
+        thread::spawn(move || {
+            while !ticking.state.lock().unwrap().finished {
+                ticking.tick(false);
+                thread::sleep(INTERVAL);
+            }
+        });
+        notifier
+    }
+
+    /// Keep track of the fuzzer's coverage from its output.
+    pub fn observe(&self, line: &str) {
+        let status = match libfuzzer::Status::parse(line) {
+            Some(status) => status,
+            None => return,
+        };
+        let mut state = self.state.lock().unwrap();
+        state.plateau.observe(line);
+        state.coverage = status.coverage.or(state.coverage);
+        state.features = status.features.or(state.features);
+        // Finding more ends the plateau, so the next one is news again.
+        let found = status.features.or(status.coverage);
+        if found > state.best {
+            state.best = found;
+            state.plateaued = false;
+        }
+    }
+
+    /// Notify the crashes saved since they were last notified, if it's time
+    /// to or `flush` says so, and the plateau if the fuzzer just reached one.
+    fn tick(&self, flush: bool) {
+        let _posting = self.posting.lock().unwrap();
+        let mut events = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.finished {
+                return;
+            }
+            for artifact in new_artifacts(&self.artifacts, self.started) {
+                if state.seen.insert(artifact.clone()) {
+                    state.pending.push(artifact);
+                }
+            }
+            let due = state
+                .last_crashes
+                .is_none_or(|last| last.elapsed() >= CRASH_INTERVAL);
+            if !state.pending.is_empty() && (due || flush) {
+                state.last_crashes = Some(Instant::now());
+                events.push(Event::CrashFound {
+                    target: &self.target,
+                    artifacts: state
+                        .pending
+                        .drain(..)
+                        .map(|artifact| artifact.display().to_string())
+                        .collect(),
+                });
+            }
+            if !state.plateaued && state.best.is_some() && state.plateau.is_stalled() {
+                state.plateaued = true;
+                events.push(Event::CoveragePlateau {
+                    target: &self.target,
+                    coverage: state.coverage,
+                    features: state.features,
+                    limit: self.limit.to_string(),
+                });
+            }
+        }
+        for event in &events {
+            post(&self.url, event);
+        }
+    }
+
+    /// Notify the crashes that are left, and that the run finished.
+    pub fn finish(&self, success: bool) {
+        self.tick(true);
+        let _posting = self.posting.lock().unwrap();
+        let crashes = {
+            let mut state = self.state.lock().unwrap();
+            state.finished = true;
+            state.seen.len()
+        };
+        post(
+            &self.url,
+            &Event::RunFinished {
+                target: &self.target,
+                success,
+                crashes,
+                duration_secs: self.started.elapsed().unwrap_or_default().as_secs(),
+            },
+        );
+    }
+}
+
+/// The inputs saved to `dir` since `since`, leaving out slow units, which
+/// aren't failures.
+fn new_artifacts(dir: &Path, since: SystemTime) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut artifacts: Vec<_> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+            let name = entry.file_name();
+            let new = metadata.is_file() && metadata.modified().ok()? > since;
+            (new && !name
+                .to_string_lossy()
+                .starts_with(libfuzzer::SLOW_UNIT_PREFIX))
+            .then(|| entry.path())
+        })
+        .collect();
+    artifacts.sort();
+    artifacts
+}
@@ -1,27 +1,133 @@
 mod add;
+mod annotate;
+mod bench;
+mod bisect;
+mod blockers;
 mod build;
+mod check;
+mod ci;
+mod clean;
 mod cmin;
+mod completions;
+mod corpus;
+mod coverage;
+mod dict;
+mod doctor;
+mod explain;
+mod features;
+mod filter;
 mod fmt;
+mod gen_test;
 mod init;
 mod list;
+mod migrate;
+mod miri;
+mod mv;
+mod oss_fuzz_init;
+mod package_repro;
+mod profile;
+mod report;
+mod repro;
+mod rm;
 mod run;
+mod serve;
+mod session;
+mod stats;
+mod status;
+mod suggest_split;
 mod tmin;
+mod triage;
+mod verify_fixes;
+mod watch;
+mod worker;
 
 pub use self::{
-    add::Add, build::Build, cmin::Cmin, fmt::Fmt, init::Init, list::List, run::Run, tmin::Tmin,
+    add::{Add, BinInput},
+    annotate::Annotate,
+    bench::Bench,
+    bisect::Bisect,
+    blockers::Blockers,
+    build::Build,
+    check::Check,
+    ci::Ci,
+    clean::Clean,
+    cmin::Cmin,
+    completions::{Complete, Completions},
+    corpus::{
+        Corpus, CorpusCrossPollinate, CorpusExport, CorpusFromTests, CorpusImport, CorpusInfo,
+        CorpusSync, CorpusTrim,
+    },
+    coverage::{Coverage, CoverageFormat, CoverageUpload},
+    dict::Dict,
+    doctor::Doctor,
+    explain::Explain,
+    features::{EngineFeature, EngineFeatures},
+    filter::InputFilter,
+    fmt::Fmt,
+    gen_test::GenTest,
+    init::{Init, Vcs},
+    list::List,
+    migrate::Migrate,
+    miri::Miri,
+    mv::Mv,
+    oss_fuzz_init::OssFuzzInit,
+    package_repro::PackageRepro,
+    profile::Profile,
+    report::Report,
+    repro::{Debugger, Repro},
+    rm::Rm,
+    run::{Run, RunMode, StallLimit},
+    serve::Serve,
+    session::{Session, SessionResume, SessionSave},
+    stats::Stats,
+    status::Status,
+    suggest_split::SuggestSplit,
+    tmin::Tmin,
+    triage::Triage,
+    verify_fixes::VerifyFixes,
+    watch::Watch,
+    worker::Worker,
 };
 
+use crate::message::MessageFormat;
 use std::fmt as stdfmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 use structopt::StructOpt;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Sanitizer {
     Address,
     Leak,
     Memory,
     Thread,
     None,
+    /// Each of `Sanitizer::EACH` in turn.
+    All,
+}
+
+impl Sanitizer {
+    /// The sanitizers that `--sanitizer all` builds and runs with, in order.
+    /// The leak sanitizer is left out, since the address sanitizer already
+    /// detects leaks.
+    pub const EACH: &'static [Sanitizer] = &[
+        Sanitizer::Address,
+        Sanitizer::Memory,
+        Sanitizer::Thread,
+        Sanitizer::None,
+    ];
+
+    /// Whether fuzz targets can be built for `triple` with this sanitizer.
+    /// Windows only has the address sanitizer, and only with MSVC.
+    pub fn supports(self, triple: &str) -> bool {
+        if triple.ends_with("-windows-msvc") {
+            matches!(self, Sanitizer::Address | Sanitizer::None)
+        } else if triple.contains("-windows") {
+            self == Sanitizer::None
+        } else {
+            true
+        }
+    }
 }
 
 impl stdfmt::Display for Sanitizer {
@@ -35,6 +141,7 @@ impl stdfmt::Display for Sanitizer {
                 Sanitizer::Memory => "memory",
                 Sanitizer::Thread => "thread",
                 Sanitizer::None => "",
+                Sanitizer::All => "all",
             }
         )
     }
@@ -50,25 +157,164 @@ impl FromStr for Sanitizer {
             "memory" => Ok(Sanitizer::Memory),
             "thread" => Ok(Sanitizer::Thread),
             "none" => Ok(Sanitizer::None),
+            "all" => Ok(Sanitizer::All),
             _ => Err(format!("unknown sanitizer: {}", s)),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Libfuzzer,
+    Honggfuzz,
+    Libafl,
+}
+
+impl stdfmt::Display for Engine {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Engine::Libfuzzer => "libfuzzer",
+                Engine::Honggfuzz => "honggfuzz",
+                Engine::Libafl => "libafl",
+            }
+        )
+    }
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "libfuzzer" => Ok(Engine::Libfuzzer),
+            "honggfuzz" => Ok(Engine::Honggfuzz),
+            "libafl" => Ok(Engine::Libafl),
+            _ => Err(format!("unknown engine: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetTemplate {
+    Default,
+    Arbitrary,
+    Roundtrip,
+    Grammar,
+}
+
+impl stdfmt::Display for TargetTemplate {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TargetTemplate::Default => "default",
+                TargetTemplate::Arbitrary => "arbitrary",
+                TargetTemplate::Roundtrip => "roundtrip",
+                TargetTemplate::Grammar => "grammar",
+            }
+        )
+    }
+}
+
+impl FromStr for TargetTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(TargetTemplate::Default),
+            "arbitrary" => Ok(TargetTemplate::Arbitrary),
+            "roundtrip" => Ok(TargetTemplate::Roundtrip),
+            "grammar" => Ok(TargetTemplate::Grammar),
+            _ => Err(format!("unknown template: {}", s)),
+        }
+    }
+}
+
+impl serde::Serialize for Sanitizer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Sanitizer::None => serializer.serialize_str("none"),
+            sanitizer => serializer.collect_str(sanitizer),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Sanitizer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl serde::Serialize for Engine {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Engine {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Clone, Debug, StructOpt)]
 pub struct BuildOptions {
     #[structopt(short = "D", long = "dev", conflicts_with = "release")]
-    /// Build artifacts in development mode, without optimizations
+    /// Build artifacts in development mode, with light optimizations unless
+    /// the `dev` profile sets an opt-level
     pub dev: bool,
 
     #[structopt(short = "O", long = "release", conflicts_with = "dev")]
     /// Build artifacts in release mode, with optimizations
     pub release: bool,
 
+    #[structopt(
+        long = "profile",
+        value_name = "NAME",
+        conflicts_with_all(&["dev", "release"])
+    )]
+    /// Build artifacts with a Cargo profile: `dev`, `release`, or a custom one
+    /// [default: release]
+    pub profile: Option<String>,
+
     #[structopt(short = "a", long = "debug-assertions")]
     /// Build artifacts with debug assertions and overflow checks enabled (default if not -O)
     pub debug_assertions: bool,
 
+    #[structopt(
+        long = "debuginfo-level",
+        value_name = "LEVEL",
+        possible_values(&[
+            "0",
+            "1",
+            "2",
+            "none",
+            "line-directives-only",
+            "line-tables-only",
+            "limited",
+            "full",
+        ])
+    )]
+    /// Build artifacts with this much debug info, like `line-tables-only`,
+    /// which is enough for stack traces [default: the profile's]
+    pub debuginfo_level: Option<String>,
+
+    #[structopt(
+        long = "split-debuginfo",
+        value_name = "MODE",
+        possible_values(&["off", "packed", "unpacked"])
+    )]
+    /// Keep debug info out of the fuzz target's binary: `unpacked` leaves it
+    /// in the object files, which makes linking faster, and `packed` puts
+    /// it in a file next to the binary [default: the profile's]
+    pub split_debuginfo: Option<String>,
+
     /// Build target with verbose output from `cargo build`
     #[structopt(short = "v", long = "verbose")]
     pub verbose: bool,
@@ -92,11 +338,19 @@ pub struct BuildOptions {
     #[structopt(
         short = "s",
         long = "sanitizer",
-        possible_values(&["address", "leak", "memory", "thread", "none"]),
-        default_value = "address",
+        possible_values(&["address", "leak", "memory", "thread", "none", "all"])
+    )]
+    /// Use a specific sanitizer, or with `all`, build and run with each of
+    /// address, memory, thread, and no sanitizer in turn [default: address]
+    pub sanitizer: Option<Sanitizer>,
+
+    #[structopt(
+        long = "engine",
+        possible_values(&["libfuzzer", "honggfuzz", "libafl"])
     )]
-    /// Use a specific sanitizer
-    pub sanitizer: Sanitizer,
+    /// Fuzzing engine to build and run fuzz targets with, where `libafl` is
+    /// experimental [default: libfuzzer]
+    pub engine: Option<Engine>,
 
     #[structopt(
         name = "triple",
@@ -106,7 +360,75 @@ pub struct BuildOptions {
     /// Target triple of the fuzz target
     pub triple: String,
 
+    #[structopt(long = "toolchain", value_name = "NAME")]
+    /// Rustup toolchain to build with, like `nightly-2024-05-01`
+    /// [default: `toolchain` in fuzz.toml]
+    pub toolchain: Option<String>,
+
+    #[structopt(long = "libfuzzer-archive", value_name = "PATH", parse(from_os_str))]
+    /// Link this static libFuzzer archive in place of the one that
+    /// `libfuzzer-sys` builds, like a patched libFuzzer
+    /// [default: `libfuzzer-archive` in fuzz.toml]
+    pub libfuzzer_archive: Option<PathBuf>,
+
+    #[structopt(long = "runner", value_name = "COMMAND")]
+    /// Command to run the fuzz target through, like an emulator or a script
+    /// that runs it remotely, with the binary and its arguments appended
+    /// [default: $CARGO_TARGET_<TRIPLE>_RUNNER]
+    pub runner: Option<String>,
+
+    #[structopt(long = "coverage-crates", value_name = "NAMES")]
+    /// Only instrument these crates, comma-separated, for the engine's
+    /// coverage feedback, like the parser that the fuzz target wraps
+    /// [default: every crate]
+    pub coverage_crates: Option<String>,
+
     #[structopt(short = "Z", value_name = "FLAG")]
     /// Unstable (nightly-only) flags to Cargo
     pub unstable_flags: Vec<String>,
+
+    #[structopt(
+        long = "rustflags-extra",
+        value_name = "FLAGS",
+        allow_hyphen_values(true)
+    )]
+    /// Extra flags for rustc, passed after `cargo fuzz`'s own and those in
+    /// `RUSTFLAGS`, so that they take precedence
+    pub rustflags_extra: Option<String>,
+
+    #[structopt(
+        long = "message-format",
+        possible_values(&["human", "json"]),
+        default_value = "human"
+    )]
+    /// Also print machine-readable JSON messages to stdout
+    pub message_format: MessageFormat,
+
+    #[structopt(skip)]
+    /// Instrument the build for source-based code coverage instead of fuzzing
+    pub coverage: bool,
+
+    #[structopt(skip)]
+    /// Build with full debug info, for running under a debugger
+    pub debuginfo: bool,
+
+    #[structopt(skip)]
+    /// Build with frame pointers, for `cargo fuzz profile` to walk the stack
+    /// with
+    pub profiling: bool,
+
+    #[structopt(skip)]
+    /// Build for `cargo fuzz run --mode quick`, with light optimizations and
+    /// overflow checks, in a directory of its own
+    pub quick: bool,
+
+    #[structopt(skip)]
+    /// Link in the recorder of the inputs the fuzz target runs, for
+    /// `cargo fuzz run --record-trace`
+    pub record_trace: bool,
+
+    #[structopt(skip)]
+    /// Link in the recorder of the comparisons the fuzz target makes, for
+    /// `cargo fuzz blockers`
+    pub record_cmps: bool,
 }
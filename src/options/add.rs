@@ -1,17 +1,145 @@
 use crate::{project::FuzzProject, RunCommand};
 use anyhow::Result;
+use std::fmt as stdfmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
 
+/// How a fuzz target made with `--bin` hands each input to the binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinInput {
+    Stdin,
+    File,
+}
+
+impl stdfmt::Display for BinInput {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BinInput::Stdin => "stdin",
+                BinInput::File => "file",
+            }
+        )
+    }
+}
+
+impl FromStr for BinInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdin" => Ok(BinInput::Stdin),
+            "file" => Ok(BinInput::File),
+            _ => Err(format!("unknown input: {}", s)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, StructOpt)]
 pub struct Add {
-    #[structopt(required = true)]
-    /// Name of the new fuzz target
-    pub target: String,
+    #[structopt(required_unless_one(&["function", "ffi"]))]
+    /// Name of the new fuzz target, or a path like `parser/headers` to put it
+    /// in a subdirectory of `fuzz_targets`, named `parser_headers`. Defaults
+    /// to the name of the function given with `--for` or `--ffi`
+    pub target: Option<String>,
+
+    #[structopt(
+        long = "for",
+        value_name = "FUNCTION",
+        conflicts_with_all(&["arbitrary", "differential", "bin"])
+    )]
+    /// Generate a fuzz target that calls this public function of the fuzzed
+    /// crate, like `my_crate::header::parse`, from its signature
+    pub function: Option<String>,
+
+    #[structopt(
+        long = "ffi",
+        value_name = "SYMBOL",
+        conflicts_with_all(&[
+            "function",
+            "arbitrary",
+            "differential",
+            "bin",
+            "reject-invalid",
+            "grammar"
+        ])
+    )]
+    /// Generate a fuzz target that calls the `extern "C"` function that the
+    /// fuzzed crate exports as this symbol, through its C ABI
+    pub ffi: Option<String>,
+
+    #[structopt(long = "arbitrary", value_name = "TYPE")]
+    /// Fuzz values of this type, built by its `Arbitrary` implementation,
+    /// instead of raw bytes. A bare type name is looked up in the fuzzed crate
+    pub arbitrary: Option<String>,
+
+    #[structopt(long = "differential", conflicts_with("arbitrary"))]
+    /// Compare the outputs of two implementations on every input, and report
+    /// where they diverge
+    pub differential: bool,
+
+    #[structopt(long = "reference", value_name = "SPEC", requires("differential"))]
+    /// Add a dependency named `reference` for the implementation to compare
+    /// against: a crate like `name` or `name@version`, or a git repository of
+    /// an earlier version of the fuzzed crate, like `https://host/repo#rev`
+    pub reference: Option<String>,
+
+    #[structopt(
+        long = "bin",
+        value_name = "BIN",
+        conflicts_with_all(&["arbitrary", "differential"])
+    )]
+    /// Generate a fuzz target that runs this binary of the fuzzed crate on
+    /// each input, and counts being killed by a signal or panicking as a crash
+    pub bin: Option<String>,
+
+    #[structopt(
+        long = "input",
+        possible_values(&["stdin", "file"]),
+        default_value = "stdin"
+    )]
+    /// How the binary given with `--bin` gets each input: over stdin, or as
+    /// the path of a file in its arguments
+    pub input: BinInput,
+
+    #[structopt(
+        long = "reject-invalid",
+        conflicts_with_all(&["arbitrary", "differential", "bin"])
+    )]
+    /// Generate a fuzz target that returns `Corpus::Reject` for invalid
+    /// inputs, like those a parser fails on, to keep them out of the corpus.
+    /// Needs libfuzzer-sys 0.4.4 or later
+    pub reject_invalid: bool,
+
+    #[structopt(
+        long = "grammar",
+        value_name = "FILE",
+        parse(from_os_str),
+        conflicts_with_all(&["function", "arbitrary", "differential", "bin", "reject-invalid"])
+    )]
+    /// Generate a fuzz target whose inputs are strings generated from this
+    /// EBNF grammar, starting from its first rule
+    pub grammar: Option<PathBuf>,
+
+    #[structopt(long = "with-bypass-module")]
+    /// Also add a `fuzz_bypass` module to the fuzzed crate's library, whose
+    /// functions skip checks like checksums and signatures when fuzzing
+    pub with_bypass_module: bool,
 }
 
 impl RunCommand for Add {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::find_existing()?;
+        if self.target.is_none() {
+            self.target = self
+                .function
+                .as_deref()
+                .and_then(|f| f.rsplit("::").next())
+                .or(self.ffi.as_deref())
+                .map(String::from);
+        }
         project.add_target(self)
     }
 }
@@ -0,0 +1,29 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Annotate {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "diff")]
+    /// Print the annotation as a diff instead of adding it to the source
+    pub diff: bool,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// Path to the artifact that crashes the fuzz target
+    pub artifact: PathBuf,
+}
+
+impl RunCommand for Annotate {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_annotate(self)
+    }
+}
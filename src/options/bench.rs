@@ -0,0 +1,41 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Bench {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(
+        long = "runs",
+        default_value = "3",
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid number of runs".to_string()),
+        }),
+    )]
+    /// Number of times to run each input, averaging the time they take
+    pub runs: u64,
+
+    #[structopt(long = "corpus")]
+    /// Time the whole corpus instead of the slow inputs, with an optimized
+    /// build that has no instrumentation or sanitizer
+    pub corpus: bool,
+
+    #[structopt(long = "update")]
+    /// Record the new timings as the ones to compare with next time
+    pub update: bool,
+}
+
+impl RunCommand for Bench {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_bench(self)
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Bisect {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// The artifact that crashes the fuzz target
+    pub artifact: PathBuf,
+
+    #[structopt(long = "good", value_name = "REV", required(true))]
+    /// A git revision where the artifact doesn't crash the fuzz target
+    pub good: String,
+
+    #[structopt(long = "bad", value_name = "REV", default_value = "HEAD")]
+    /// A git revision where the artifact crashes the fuzz target
+    pub bad: String,
+}
+
+impl RunCommand for Bisect {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_bisect(self)
+    }
+}
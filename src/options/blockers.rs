@@ -0,0 +1,26 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Blockers {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(parse(from_os_str))]
+    /// Inputs, or directories of inputs, to replay instead of the target's
+    /// corpus
+    pub inputs: Vec<PathBuf>,
+}
+
+impl RunCommand for Blockers {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_blockers(self)
+    }
+}
@@ -7,6 +7,16 @@ pub struct Build {
     #[structopt(flatten)]
     pub build: BuildOptions,
 
+    #[structopt(long = "print-flags")]
+    /// Print the flags for rustc that the fuzz targets would be built with,
+    /// and where each comes from, instead of building them
+    pub print_flags: bool,
+
+    #[structopt(long = "analyze-size", conflicts_with = "print-flags")]
+    /// After building, break down the size of each fuzz target's binary by
+    /// crate, libFuzzer and sanitizer runtime, and how much is debug info
+    pub analyze_size: bool,
+
     /// Name of the fuzz target to build, or build all targets if not supplied
     pub target: Option<String>,
 }
@@ -14,6 +24,13 @@ pub struct Build {
 impl RunCommand for Build {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::find_existing()?;
-        project.exec_build(&self.build, self.target.as_ref().map(|s| s.as_str()))
+        if self.print_flags {
+            return project.print_build_flags(&self.build, self.target.as_deref());
+        }
+        project.exec_build(&self.build, self.target.as_deref())?;
+        if self.analyze_size {
+            project.exec_analyze_size(&self.build, self.target.as_deref())?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,25 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Check {
+    #[structopt(long = "replay")]
+    /// Also replay each fuzz target's corpus under `cargo test`, with the
+    /// tests generated in fuzz/tests/corpus_replay.rs
+    pub replay: bool,
+
+    #[structopt(long = "all-targets", conflicts_with_all(&["replay", "target"]))]
+    /// Also check the fuzz crate's tests and examples
+    pub all_targets: bool,
+
+    /// Name of the fuzz target to check, or all of them if not given
+    pub target: Option<String>,
+}
+
+impl RunCommand for Check {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_check(self)
+    }
+}
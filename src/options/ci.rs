@@ -0,0 +1,50 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Ci {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(
+        long = "total-time",
+        value_name = "SECONDS",
+        default_value = "600",
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1 second".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a whole number of seconds".to_string()),
+        }),
+    )]
+    /// How long to fuzz for in all, split evenly between the fuzz targets
+    pub total_time: u64,
+
+    #[structopt(long = "remote", value_name = "URL")]
+    /// Remote storage to pull the corpora from before fuzzing and push them to
+    /// after [default: `corpus-remote` from fuzz.toml, if set]
+    pub remote: Option<String>,
+
+    #[structopt(long = "no-minimize")]
+    /// Don't minimize the corpora after fuzzing
+    pub no_minimize: bool,
+
+    #[structopt(long = "junit", value_name = "PATH", parse(from_os_str))]
+    /// Write the results as a JUnit XML report to this file
+    pub junit: Option<PathBuf>,
+
+    #[structopt(long = "json", value_name = "PATH", parse(from_os_str))]
+    /// Write the results as JSON to this file
+    pub json: Option<PathBuf>,
+
+    /// A glob of the names of the fuzz targets to fuzz [default: all of them]
+    pub targets: Option<String>,
+}
+
+impl RunCommand for Ci {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_ci(self)
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Clean {
+    #[structopt(long = "config", value_name = "NAME")]
+    /// Only remove the builds of this configuration, like `libfuzzer-none`,
+    /// or of those matching a glob, like `*-coverage`. May be given more than
+    /// once
+    pub config: Vec<String>,
+}
+
+impl RunCommand for Clean {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_clean(self)
+    }
+}
@@ -1,6 +1,11 @@
-use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use crate::{
+    options::{BuildOptions, InputFilter},
+    project::FuzzProject,
+    RunCommand,
+};
 use anyhow::Result;
 use std::path::PathBuf;
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[derive(Clone, Debug, StructOpt)]
@@ -8,18 +13,93 @@ pub struct Cmin {
     #[structopt(flatten)]
     pub build: BuildOptions,
 
-    #[structopt(required(true))]
-    /// Name of the fuzz target
-    pub target: String,
+    #[structopt(required_unless("all"))]
+    /// Name of the fuzz target, or a glob of the targets to minimize with
+    /// `--all`
+    pub target: Option<String>,
 
-    #[structopt(parse(from_os_str))]
+    #[structopt(parse(from_os_str), conflicts_with("all"))]
     /// The corpus directory to minify into
     pub corpus: Option<PathBuf>,
+
+    #[structopt(short = "A", long = "all")]
+    /// Minimize the corpora of all fuzz targets (or all matching the given
+    /// glob)
+    pub all: bool,
+
+    #[structopt(
+        short = "j",
+        long = "jobs",
+        requires("all"),
+        validator(|v| Err(From::from(match v.parse::<u16>() {
+            Ok(0) => "0 jobs?",
+            Err(_) => "must be a valid integer representing a sane number of jobs",
+            _ => return Ok(()),
+        }))),
+    )]
+    /// Number of corpora to minimize concurrently with `--all` [default: 1]
+    pub jobs: Option<usize>,
+
+    #[structopt(
+        long = "prune-older-than",
+        value_name = "AGE",
+        parse(try_from_str = parse_age)
+    )]
+    /// Only remove inputs older than this, like `12h`, `7d`, or `4w`: newer
+    /// inputs are kept even if they add no coverage
+    pub prune_older_than: Option<Duration>,
+
+    #[structopt(
+        long = "preserve",
+        value_name = "DIR",
+        parse(from_os_str),
+        number_of_values(1),
+        conflicts_with("all")
+    )]
+    /// Keep every input in this directory in the minimized corpus, whether
+    /// or not it adds coverage, copying in those that aren't there yet
+    pub preserve: Vec<PathBuf>,
+
+    #[structopt(long = "pin-artifacts")]
+    /// Keep the inputs that a crash artifact of the target starts with,
+    /// which the fuzzer likely grew it from, for crashes that need state
+    /// that a prefix sets up
+    pub pin_artifacts: bool,
+
+    #[structopt(flatten)]
+    pub filter: InputFilter,
+}
+
+pub(super) fn parse_age(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("invalid age: {}", s))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid age: {} (expected a number of seconds, minutes, hours, days, or weeks)",
+                s
+            ))
+        }
+    };
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("age too large: {}", s))
 }
 
 impl RunCommand for Cmin {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::find_existing()?;
-        project.exec_cmin(self)
+        if self.all {
+            project.exec_cmin_all(self)
+        } else {
+            project.exec_cmin(self)
+        }
     }
 }
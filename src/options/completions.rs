@@ -0,0 +1,46 @@
+use crate::{
+    completions::{self, Expected, Shell},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Completions {
+    #[structopt(possible_values(&["bash", "zsh", "fish"]))]
+    /// The shell to print the completion script for
+    pub shell: Shell,
+}
+
+impl RunCommand for Completions {
+    fn run_command(&mut self) -> Result<()> {
+        print!("{}", completions::script(self.shell));
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Complete {
+    #[structopt(last(true))]
+    /// The command line, up to and including the word to complete
+    pub words: Vec<String>,
+}
+
+impl RunCommand for Complete {
+    fn run_command(&mut self) -> Result<()> {
+        let candidates = match completions::expected(&crate::Command::clap(), &self.words) {
+            Expected::Words(words) => words,
+            Expected::Path => Vec::new(),
+            // Outside a project, there's nothing to complete.
+            expected => match FuzzProject::find_existing() {
+                Ok(project) => project.completions(&expected)?,
+                Err(_) => Vec::new(),
+            },
+        };
+        for candidate in candidates {
+            println!("{}", candidate);
+        }
+        Ok(())
+    }
+}
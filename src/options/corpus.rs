@@ -0,0 +1,195 @@
+use super::cmin::parse_age;
+use crate::{
+    export::Format, options::BuildOptions, project::FuzzProject, provenance::Origin, RunCommand,
+};
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub enum Corpus {
+    /// Upload the local corpora to remote storage
+    Push(CorpusSync),
+
+    /// Download the remote corpora, merging them into the local ones
+    Pull(CorpusSync),
+
+    /// Copy inputs from AFL output directories, proptest regression files, or
+    /// plain directories into a fuzz target's corpus
+    Import(CorpusImport),
+
+    /// Write a fuzz target's corpus or artifacts as text, to embed them where
+    /// binary data gets mangled, and to import them back from
+    Export(CorpusExport),
+
+    /// Run the fuzzed crate's tests, and add the inputs they pass to the
+    /// function that records them to a fuzz target's corpus
+    FromTests(CorpusFromTests),
+
+    /// Show where the inputs in a fuzz target's corpus came from, and when
+    Info(CorpusInfo),
+
+    /// Replay each fuzz target's corpus against the other targets, adding the
+    /// inputs that reach new coverage to their corpora
+    CrossPollinate(CorpusCrossPollinate),
+
+    /// Truncate the inputs in a fuzz target's corpus that are over its
+    /// maximum input length, or drop them
+    Trim(CorpusTrim),
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusSync {
+    #[structopt(long = "remote", value_name = "URL")]
+    /// Remote storage to sync with, like `s3://bucket/prefix` or
+    /// `gs://bucket/prefix` [default: `corpus-remote` from fuzz.toml]
+    pub remote: Option<String>,
+
+    /// Name of the fuzz target to sync, or all targets if omitted
+    pub target: Option<String>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusImport {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "cmin")]
+    /// Minimize the corpus after importing
+    pub cmin: bool,
+
+    #[structopt(
+        long = "format",
+        possible_values(&["hex", "base64", "base64-json"])
+    )]
+    /// Read the files as written by `corpus export` in this format, instead
+    /// of telling what they are from their contents
+    pub format: Option<Format>,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(parse(from_os_str), required(true), min_values(1))]
+    /// AFL output directories, proptest regression files, directories, or
+    /// files to import
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusExport {
+    #[structopt(
+        long = "format",
+        possible_values(&["hex", "base64", "base64-json"]),
+        default_value = "base64-json"
+    )]
+    /// Write an input per line in hex or base64, or a JSON document with each
+    /// input's name and base64 contents
+    pub format: Format,
+
+    #[structopt(long = "artifacts")]
+    /// Export the fuzz target's artifacts instead of its corpus
+    pub artifacts: bool,
+
+    #[structopt(short = "o", long = "output", value_name = "PATH", parse(from_os_str))]
+    /// Write the export to this file [default: stdout]
+    pub output: Option<PathBuf>,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(parse(from_os_str), conflicts_with = "artifacts")]
+    /// Files or directories of inputs to export instead of the corpus
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusFromTests {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "cmin")]
+    /// Minimize the corpus after adding the inputs
+    pub cmin: bool,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(last(true))]
+    /// Additional arguments passed through to `cargo test`, like the names of
+    /// the tests to run
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusInfo {
+    #[structopt(long = "since", value_name = "AGE", parse(try_from_str = parse_age))]
+    /// Only show inputs added within this long, like `24h` or `7d`
+    pub since: Option<Duration>,
+
+    #[structopt(
+        long = "origin",
+        possible_values(&[
+            "seed",
+            "fuzz",
+            "tmin",
+            "import",
+            "from-tests",
+            "pull",
+            "cross-pollinate"
+        ])
+    )]
+    /// Only show inputs with this origin
+    pub origin: Option<Origin>,
+
+    #[structopt(long = "run", value_name = "ID")]
+    /// Only show inputs found by the run with this ID
+    pub run: Option<String>,
+
+    /// Name of the fuzz target
+    pub target: String,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusCrossPollinate {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    /// Glob of the fuzz targets to share inputs between [default: all of
+    /// them]
+    pub targets: Option<String>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct CorpusTrim {
+    #[structopt(long = "to-max-len", required_unless = "max-len")]
+    /// Trim to the fuzz target's `max-len` from fuzz.toml
+    pub to_max_len: bool,
+
+    #[structopt(long = "max-len", value_name = "BYTES", conflicts_with = "to-max-len")]
+    /// Trim to this many bytes instead
+    pub max_len: Option<u64>,
+
+    #[structopt(long = "drop")]
+    /// Remove the inputs over the limit instead of truncating them
+    pub drop: bool,
+
+    /// Name of the fuzz target
+    pub target: String,
+}
+
+impl RunCommand for Corpus {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        match self {
+            Corpus::Push(sync) => project.exec_corpus_sync(sync, true),
+            Corpus::Pull(sync) => project.exec_corpus_sync(sync, false),
+            Corpus::Import(import) => project.exec_corpus_import(import),
+            Corpus::Export(export) => project.exec_corpus_export(export),
+            Corpus::FromTests(from) => project.exec_corpus_from_tests(from),
+            Corpus::Info(info) => project.exec_corpus_info(info),
+            Corpus::CrossPollinate(cross) => project.exec_corpus_cross_pollinate(cross),
+            Corpus::Trim(trim) => project.exec_corpus_trim(trim),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    options::{BuildOptions, InputFilter},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::Result;
+use std::fmt as stdfmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageFormat {
+    Profdata,
+    Html,
+    Lcov,
+    Cobertura,
+}
+
+impl stdfmt::Display for CoverageFormat {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CoverageFormat::Profdata => "profdata",
+                CoverageFormat::Html => "html",
+                CoverageFormat::Lcov => "lcov",
+                CoverageFormat::Cobertura => "cobertura",
+            }
+        )
+    }
+}
+
+impl FromStr for CoverageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "profdata" => Ok(CoverageFormat::Profdata),
+            "html" => Ok(CoverageFormat::Html),
+            "lcov" => Ok(CoverageFormat::Lcov),
+            "cobertura" => Ok(CoverageFormat::Cobertura),
+            _ => Err(format!("unknown coverage format: {}", s)),
+        }
+    }
+}
+
+/// A service that `cargo fuzz coverage --upload` sends the report to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageUpload {
+    Codecov,
+    Coveralls,
+}
+
+impl stdfmt::Display for CoverageUpload {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                CoverageUpload::Codecov => "Codecov",
+                CoverageUpload::Coveralls => "Coveralls",
+            }
+        )
+    }
+}
+
+impl FromStr for CoverageUpload {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "codecov" => Ok(CoverageUpload::Codecov),
+            "coveralls" => Ok(CoverageUpload::Coveralls),
+            _ => Err(format!("unknown coverage service: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Coverage {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(parse(from_os_str))]
+    /// Custom corpus directories to replay, defaults to the target's corpus
+    pub corpus: Vec<PathBuf>,
+
+    #[structopt(flatten)]
+    pub filter: InputFilter,
+
+    #[structopt(
+        short = "f",
+        long = "format",
+        possible_values(&["profdata", "html", "lcov", "cobertura"]),
+        default_value = "profdata",
+    )]
+    /// Format of the coverage report
+    pub format: CoverageFormat,
+
+    #[structopt(long = "upload", possible_values(&["codecov", "coveralls"]))]
+    /// Upload the coverage of the fuzzed crate to Codecov, with the token in
+    /// $CODECOV_TOKEN, or to Coveralls, with the token in
+    /// $COVERALLS_REPO_TOKEN
+    pub upload: Option<CoverageUpload>,
+
+    #[structopt(long = "diff", parse(from_os_str))]
+    /// Compare the coverage with a baseline `.profdata` file, and fail if any
+    /// region lost coverage
+    pub diff: Option<PathBuf>,
+
+    #[structopt(long = "cmin-first")]
+    /// Minimize the corpus into a scratch directory first, keeping only the
+    /// inputs that add coverage, and replay those. The corpus itself isn't
+    /// changed
+    pub cmin_first: bool,
+
+    #[structopt(
+        short = "j",
+        long = "jobs",
+        validator(|v| Err(From::from(match v.parse::<u16>() {
+            Ok(0) => "0 jobs?",
+            Err(_) => "must be a valid integer representing a sane number of jobs",
+            _ => return Ok(()),
+        }))),
+    )]
+    /// Number of processes to replay the corpus with concurrently, each with
+    /// a shard of the inputs [default: 1]
+    pub jobs: Option<usize>,
+
+    #[structopt(long = "uncovered")]
+    /// List the public functions and modules of the fuzzed crate that no
+    /// input reaches, largest first
+    pub uncovered: bool,
+}
+
+impl RunCommand for Coverage {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_coverage(self)
+    }
+}
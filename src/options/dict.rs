@@ -0,0 +1,22 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Dict {
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    /// Where to write the dictionary [default: fuzz/dictionaries/<target>.dict]
+    pub output: Option<PathBuf>,
+}
+
+impl RunCommand for Dict {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_dict(self)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{doctor, RunCommand};
+use anyhow::{bail, Result};
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Doctor {
+    #[structopt(
+        name = "triple",
+        long = "target",
+        default_value(crate::utils::default_target())
+    )]
+    /// Target triple to check the sanitizer runtimes of
+    pub triple: String,
+}
+
+impl RunCommand for Doctor {
+    fn run_command(&mut self) -> Result<()> {
+        let checks = doctor::checks(&self.triple);
+        print!("{}", doctor::render(&checks));
+        let errors = checks
+            .iter()
+            .filter(|c| c.status == doctor::Status::Error)
+            .count();
+        if errors > 0 {
+            bail!("found {} problem(s) that stop fuzzing from working", errors);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Explain {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// Path to the input to explain
+    pub input: PathBuf,
+
+    #[structopt(parse(from_os_str))]
+    /// Custom corpus directories to compare the input with, defaults to the
+    /// target's corpus
+    pub corpus: Vec<PathBuf>,
+}
+
+impl RunCommand for Explain {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_explain(self)
+    }
+}
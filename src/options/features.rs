@@ -0,0 +1,105 @@
+use crate::config::TargetConfig;
+use std::fmt as stdfmt;
+use structopt::StructOpt;
+
+/// A search strategy of the fuzzing engine that `cargo fuzz run` can turn on
+/// or off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineFeature {
+    /// Treat the values the fuzz target compares as coverage too.
+    ValueProfile,
+    /// Schedule the inputs that recently found new coverage more often.
+    Entropic,
+    /// Mutate inputs with the values the fuzz target compares them with.
+    CmpLog,
+}
+
+impl EngineFeature {
+    /// The flag that turns it on, which `--no-` turns off.
+    pub fn flag(self) -> &'static str {
+        match self {
+            EngineFeature::ValueProfile => "--value-profile",
+            EngineFeature::Entropic => "--entropic",
+            EngineFeature::CmpLog => "--cmp-log",
+        }
+    }
+}
+
+impl stdfmt::Display for EngineFeature {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(f, "{}", self.flag().trim_start_matches('-'))
+    }
+}
+
+/// Flags that turn the engine's search strategies on or off, over the
+/// defaults in fuzz.toml. The last of a flag and its `--no-` form wins.
+#[derive(Clone, Debug, Default, StructOpt)]
+pub struct EngineFeatures {
+    #[structopt(long = "value-profile", overrides_with = "no-value-profile")]
+    /// Treat the values the fuzz target compares as coverage too, which gets
+    /// past magic numbers and checksums at the cost of a bigger corpus
+    pub value_profile: bool,
+
+    #[structopt(long = "no-value-profile", overrides_with = "value-profile")]
+    /// Turn off `--value-profile`, if fuzz.toml turns it on
+    pub no_value_profile: bool,
+
+    #[structopt(long = "entropic", overrides_with = "no-entropic")]
+    /// Schedule the inputs that recently found new coverage more often
+    /// [libFuzzer's default]
+    pub entropic: bool,
+
+    #[structopt(long = "no-entropic", overrides_with = "entropic")]
+    /// Schedule inputs by how much coverage they have instead
+    pub no_entropic: bool,
+
+    #[structopt(long = "cmp-log", overrides_with = "no-cmp-log")]
+    /// Mutate inputs with the values the fuzz target compares them with
+    /// [default]
+    pub cmp_log: bool,
+
+    #[structopt(long = "no-cmp-log", overrides_with = "cmp-log")]
+    /// Don't use the values the fuzz target compares inputs with
+    pub no_cmp_log: bool,
+}
+
+impl EngineFeatures {
+    /// The flags that set each feature the way `config` does.
+    pub fn from_config(config: &TargetConfig) -> EngineFeatures {
+        EngineFeatures {
+            value_profile: config.value_profile == Some(true),
+            no_value_profile: config.value_profile == Some(false),
+            entropic: config.entropic == Some(true),
+            no_entropic: config.entropic == Some(false),
+            cmp_log: config.cmp_log == Some(true),
+            no_cmp_log: config.cmp_log == Some(false),
+        }
+    }
+
+    /// Whether each feature is turned on or off, by a flag or else by
+    /// `config`, for those that either sets.
+    pub fn resolve(&self, config: &TargetConfig) -> Vec<(EngineFeature, bool)> {
+        let flag = |on: bool, off: bool| match (on, off) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        };
+        [
+            (
+                EngineFeature::ValueProfile,
+                flag(self.value_profile, self.no_value_profile).or(config.value_profile),
+            ),
+            (
+                EngineFeature::Entropic,
+                flag(self.entropic, self.no_entropic).or(config.entropic),
+            ),
+            (
+                EngineFeature::CmpLog,
+                flag(self.cmp_log, self.no_cmp_log).or(config.cmp_log),
+            ),
+        ]
+        .iter()
+        .filter_map(|&(feature, on)| Some((feature, on?)))
+        .collect()
+    }
+}
@@ -0,0 +1,200 @@
+use regex::bytes::Regex;
+use std::fmt as stdfmt;
+use std::ops::Bound;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+/// Flags that select the corpus inputs a command passes to the fuzzer. An
+/// input is selected if it passes every filter that's given.
+#[derive(Clone, Debug, Default, StructOpt)]
+pub struct InputFilter {
+    #[structopt(long = "only-matching", value_name = "REGEX")]
+    /// Only use the corpus inputs whose contents match this regex. Use
+    /// `(?-u)` for a byte-oriented match, like `(?-u)^\x7fELF`
+    pub only_matching: Option<Regex>,
+
+    #[structopt(long = "only-len", value_name = "RANGE")]
+    /// Only use the corpus inputs whose length in bytes is in this range,
+    /// like `16`, `4..64`, `..=255`, or `1024..`
+    pub only_len: Option<LenRange>,
+
+    #[structopt(long = "only-prefix", value_name = "HEX")]
+    /// Only use the corpus inputs that start with these bytes, given in hex
+    /// like `89504e47`
+    pub only_prefix: Option<HexBytes>,
+}
+
+impl InputFilter {
+    /// Whether any filter was given.
+    pub fn is_active(&self) -> bool {
+        self.only_matching.is_some() || self.only_len.is_some() || self.only_prefix.is_some()
+    }
+
+    /// Whether an input with these contents is selected.
+    pub fn matches(&self, input: &[u8]) -> bool {
+        self.only_len
+            .as_ref()
+            .is_none_or(|r| r.contains(input.len()))
+            && self
+                .only_prefix
+                .as_ref()
+                .is_none_or(|p| input.starts_with(&p.0))
+            && self
+                .only_matching
+                .as_ref()
+                .is_none_or(|r| r.is_match(input))
+    }
+}
+
+impl stdfmt::Display for InputFilter {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        let mut filters = Vec::new();
+        if let Some(regex) = &self.only_matching {
+            filters.push(format!("match `{}`", regex));
+        }
+        if let Some(len) = &self.only_len {
+            filters.push(format!("are {} byte(s) long", len));
+        }
+        if let Some(prefix) = &self.only_prefix {
+            filters.push(format!("start with `{}`", prefix));
+        }
+        write!(f, "{}", filters.join(" and "))
+    }
+}
+
+/// A range of input lengths, in Rust's range syntax.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LenRange {
+    pub start: Bound<usize>,
+    pub end: Bound<usize>,
+}
+
+impl LenRange {
+    pub fn contains(&self, len: usize) -> bool {
+        let above = match self.start {
+            Bound::Included(start) => len >= start,
+            Bound::Excluded(start) => len > start,
+            Bound::Unbounded => true,
+        };
+        let below = match self.end {
+            Bound::Included(end) => len <= end,
+            Bound::Excluded(end) => len < end,
+            Bound::Unbounded => true,
+        };
+        above && below
+    }
+}
+
+impl stdfmt::Display for LenRange {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        match (self.start, self.end) {
+            (Bound::Included(start), Bound::Included(end)) if start == end => {
+                write!(f, "{}", start)
+            }
+            (start, end) => {
+                if let Bound::Included(start) = start {
+                    write!(f, "{}", start)?;
+                }
+                match end {
+                    Bound::Included(end) => write!(f, "..={}", end),
+                    Bound::Excluded(end) => write!(f, "..{}", end),
+                    _ => write!(f, ".."),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for LenRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid length range: {} (expected a length, or a range like `4..64`)",
+                s
+            )
+        };
+        let parse = |n: &str| n.parse::<usize>().map_err(|_| invalid());
+        let (start, end) = match s.find("..") {
+            None => {
+                let len = parse(s)?;
+                return Ok(LenRange {
+                    start: Bound::Included(len),
+                    end: Bound::Included(len),
+                });
+            }
+            Some(split) => (&s[..split], &s[split + 2..]),
+        };
+        let start = match start {
+            "" => Bound::Unbounded,
+            start => Bound::Included(parse(start)?),
+        };
+        let end = match end.strip_prefix('=') {
+            Some(end) => Bound::Included(parse(end)?),
+            None if end.is_empty() => Bound::Unbounded,
+            None => Bound::Excluded(parse(end)?),
+        };
+        let empty = match (start, end) {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (Bound::Included(start), Bound::Excluded(end)) => start >= end,
+            (_, Bound::Excluded(end)) => end == 0,
+            _ => false,
+        };
+        if empty {
+            return Err(format!("invalid length range: {} (no length is in it)", s));
+        }
+        Ok(LenRange { start, end })
+    }
+}
+
+/// Bytes given in hex on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HexBytes(pub Vec<u8>);
+
+impl stdfmt::Display for HexBytes {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HexBytes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        // Checked up front, since `from_str_radix` takes a leading `+`.
+        if digits.is_empty()
+            || !digits.len().is_multiple_of(2)
+            || !digits.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Err(format!(
+                "invalid hex bytes: {} (expected an even number of hex digits)",
+                s
+            ));
+        }
+        (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+            .collect::<Result<_, _>>()
+            .map(HexBytes)
+            .map_err(|_| format!("invalid hex bytes: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HexBytes;
+
+    #[test]
+    fn hex_bytes() {
+        assert_eq!("0xf00d".parse::<HexBytes>().unwrap().0, [0xf0, 0x0d]);
+        assert_eq!("F00D".parse::<HexBytes>().unwrap().0, [0xf0, 0x0d]);
+        for invalid in ["", "0x", "f00", "+f00", "-f00", "f0 0", "0xzz"] {
+            assert!(invalid.parse::<HexBytes>().is_err(), "{}", invalid);
+        }
+    }
+}
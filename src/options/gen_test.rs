@@ -0,0 +1,22 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct GenTest {
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// Path to the artifact to generate a regression test for
+    pub artifact: PathBuf,
+}
+
+impl RunCommand for GenTest {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.gen_regression_test(self)
+    }
+}
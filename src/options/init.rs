@@ -1,5 +1,8 @@
-use crate::{project::FuzzProject, RunCommand};
+use crate::{options::TargetTemplate, project::FuzzProject, RunCommand};
 use anyhow::Result;
+use std::fmt as stdfmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[derive(Clone, Debug, StructOpt)]
@@ -12,6 +15,78 @@ pub struct Init {
     )]
     /// Name of the first fuzz target to create
     pub target: String,
+
+    #[structopt(
+        long = "template",
+        possible_values(&["default", "arbitrary", "roundtrip", "grammar"]),
+        default_value = "default"
+    )]
+    /// Template for the first fuzz target: `arbitrary` fuzzes a type deriving
+    /// `Arbitrary`, `roundtrip` checks that decoding and re-encoding agree,
+    /// and `grammar` generates inputs from a small grammar
+    pub template: TargetTemplate,
+
+    #[structopt(long = "add-to-workspace")]
+    /// Make the fuzz crate a member of the enclosing Cargo workspace, instead
+    /// of excluding it from the workspace and giving it one of its own
+    pub add_to_workspace: bool,
+
+    #[structopt(long = "libfuzzer-sys", value_name = "SOURCE")]
+    /// Depend on `libfuzzer-sys` from here instead of crates.io: a git URL
+    /// with an optional `#<rev>`, a path relative to the fuzz directory, or a
+    /// version
+    pub libfuzzer_sys: Option<String>,
+
+    #[structopt(long = "libfuzzer-archive", value_name = "PATH", parse(from_os_str))]
+    /// Link this static libFuzzer archive in place of the one that
+    /// `libfuzzer-sys` builds, by setting `libfuzzer-archive` in fuzz.toml,
+    /// relative to the fuzz directory
+    pub libfuzzer_archive: Option<PathBuf>,
+
+    #[structopt(
+        long = "vcs",
+        possible_values(&["git", "none"]),
+        default_value = "git"
+    )]
+    /// Version control to set the fuzz directory up for: `git` writes a
+    /// .gitignore for the generated directories and a .gitattributes that
+    /// keeps git from diffing inputs as text, and `none` writes neither
+    pub vcs: Vcs,
+
+    #[structopt(long = "lfs-corpus")]
+    /// Track the corpus with Git LFS instead of ignoring it
+    pub lfs_corpus: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vcs {
+    Git,
+    None,
+}
+
+impl stdfmt::Display for Vcs {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Vcs::Git => "git",
+                Vcs::None => "none",
+            }
+        )
+    }
+}
+
+impl FromStr for Vcs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "git" => Ok(Vcs::Git),
+            "none" => Ok(Vcs::None),
+            _ => Err(format!("unknown version control system: {}", s)),
+        }
+    }
 }
 
 impl RunCommand for Init {
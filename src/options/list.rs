@@ -1,13 +1,29 @@
-use crate::{project::FuzzProject, RunCommand};
+use crate::{message::MessageFormat, project::FuzzProject, RunCommand};
 use anyhow::Result;
 use structopt::StructOpt;
 
 #[derive(Clone, Debug, StructOpt)]
-pub struct List {}
+pub struct List {
+    /// Only list the fuzz targets in this subdirectory of `fuzz_targets`
+    pub dir: Option<String>,
+
+    #[structopt(
+        long = "message-format",
+        possible_values(&["human", "json"]),
+        default_value = "human"
+    )]
+    /// Print the targets as JSON messages instead
+    pub message_format: MessageFormat,
+
+    #[structopt(short = "v", long = "verbose")]
+    /// Show each target's corpus size, artifacts, last run, speed, and the
+    /// coverage of the last `cargo fuzz coverage`
+    pub verbose: bool,
+}
 
 impl RunCommand for List {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::find_existing()?;
-        project.list_targets()
+        project.list_targets(self)
     }
 }
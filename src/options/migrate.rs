@@ -0,0 +1,13 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Migrate {}
+
+impl RunCommand for Migrate {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_migrate()
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Miri {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(parse(from_os_str))]
+    /// Inputs, like artifacts, or directories of inputs to replay, instead of
+    /// the target's corpus
+    pub inputs: Vec<PathBuf>,
+
+    #[structopt(
+        long = "timeout",
+        value_name = "SECONDS",
+        default_value = "60",
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1 second".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid number of seconds".to_string()),
+        }),
+    )]
+    /// Seconds Miri may take on each input before it's reported as timed out
+    pub timeout: u64,
+
+    #[structopt(long = "skip", value_name = "GLOB", number_of_values = 1)]
+    /// Skip the inputs whose file names match this glob, as well as those
+    /// matching `miri-skip` in fuzz.toml. May be given more than once
+    pub skip: Vec<String>,
+}
+
+impl RunCommand for Miri {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_miri(self)
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Mv {
+    /// Name of the fuzz target to rename
+    pub old: String,
+
+    /// New name of the fuzz target, or a path like `parser/headers` to move
+    /// it into a subdirectory of `fuzz_targets`
+    pub new: String,
+}
+
+impl RunCommand for Mv {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_mv(self)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct OssFuzzInit {
+    #[structopt(long = "repo")]
+    /// URL of the git repository to clone [default: `package.repository`, or
+    /// the `origin` remote]
+    pub repo: Option<String>,
+
+    #[structopt(long = "contact")]
+    /// Email address of the primary contact for bug reports [default: the
+    /// first author's email address]
+    pub contact: Option<String>,
+
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    /// Directory to write the project files to [default: fuzz/oss-fuzz/<project>]
+    pub output: Option<PathBuf>,
+
+    #[structopt(long = "force")]
+    /// Overwrite existing project files
+    pub force: bool,
+}
+
+impl RunCommand for OssFuzzInit {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_oss_fuzz_init(self)
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct PackageRepro {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// Path to the artifact to reproduce
+    pub artifact: PathBuf,
+
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    /// Where to write the archive [default:
+    /// fuzz/repro/<target>-<artifact>.tar.gz]
+    pub output: Option<PathBuf>,
+}
+
+impl RunCommand for PackageRepro {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_package_repro(self)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Profile {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(
+        long = "time",
+        value_name = "SECS",
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid number of seconds".to_string()),
+        }),
+    )]
+    /// Fuzz for this many seconds instead of replaying the corpus once
+    pub time: Option<u64>,
+
+    #[structopt(
+        long = "frequency",
+        value_name = "HZ",
+        default_value = "997",
+        validator(|v| match v.parse::<u32>() {
+            Ok(0) => Err("must be at least 1".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid number of samples per second".to_string()),
+        }),
+    )]
+    /// Number of times per second to sample the fuzzer's stack
+    pub frequency: u32,
+
+    #[structopt(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Profile {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_profile(self)
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Report {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "minimize")]
+    /// Minimize the input, like `cargo fuzz tmin`, before reporting it
+    pub minimize: bool,
+
+    #[structopt(long = "github", value_name = "OWNER/REPO")]
+    /// Open an issue with the report in this GitHub repository, with the
+    /// token in $GITHUB_TOKEN, instead of printing it
+    pub github: Option<String>,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// Path to the artifact to report
+    pub artifact: PathBuf,
+}
+
+impl RunCommand for Report {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_report(self)
+    }
+}
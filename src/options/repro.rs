@@ -0,0 +1,88 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::fmt as stdfmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use structopt::StructOpt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Debugger {
+    Gdb,
+    Lldb,
+}
+
+impl Debugger {
+    /// The Rust wrapper around the debugger, which loads Rust's pretty
+    /// printers.
+    pub fn program(self) -> &'static str {
+        match self {
+            Debugger::Gdb => "rust-gdb",
+            Debugger::Lldb => "rust-lldb",
+        }
+    }
+}
+
+impl stdfmt::Display for Debugger {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Debugger::Gdb => "gdb",
+                Debugger::Lldb => "lldb",
+            }
+        )
+    }
+}
+
+impl FromStr for Debugger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gdb" => Ok(Debugger::Gdb),
+            "lldb" => Ok(Debugger::Lldb),
+            _ => Err(format!("unknown debugger: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Repro {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(required(true), parse(from_os_str))]
+    /// Path to the artifact to reproduce
+    pub artifact: PathBuf,
+
+    #[structopt(long = "debugger", possible_values(&["gdb", "lldb"]))]
+    /// Run the fuzz target under `rust-gdb` or `rust-lldb`
+    pub debugger: Option<Debugger>,
+
+    #[structopt(long = "trace")]
+    /// First run the inputs that `cargo fuzz run --record-trace` recorded, in
+    /// the order the fuzzer ran them, to reproduce crashes that depend on the
+    /// state earlier inputs left behind
+    pub trace: bool,
+
+    #[structopt(long = "print-command")]
+    /// Print the command that reproduces the artifact, or starts the debugger,
+    /// instead of running it
+    pub print_command: bool,
+
+    #[structopt(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Repro {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_repro(self)
+    }
+}
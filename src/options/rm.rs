@@ -0,0 +1,21 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Rm {
+    /// Name of the fuzz target to remove
+    pub target: String,
+
+    #[structopt(long = "data")]
+    /// Also remove the target's corpus, artifacts, coverage, dictionaries and
+    /// everything else cargo fuzz keeps for it
+    pub data: bool,
+}
+
+impl RunCommand for Rm {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_rm(self)
+    }
+}
@@ -1,5 +1,12 @@
-use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
-use anyhow::Result;
+use crate::{
+    options::{BuildOptions, EngineFeatures, InputFilter, Sanitizer},
+    project::FuzzProject,
+    RunCommand,
+};
+use anyhow::{bail, Result};
+use std::fmt as stdfmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[derive(Clone, Debug, StructOpt)]
@@ -7,34 +14,385 @@ pub struct Run {
     #[structopt(flatten)]
     pub build: BuildOptions,
 
-    #[structopt(required(true))]
-    /// Name of the fuzz target
-    pub target: String,
+    #[structopt(required_unless_one(&["all", "replay-manifest"]))]
+    /// Name of the fuzz target, or its path in `fuzz_targets` like
+    /// `parser/headers`, or a glob of either for the targets to run with
+    /// `--all`
+    pub target: Option<String>,
 
+    #[structopt(conflicts_with("all"))]
     /// Custom corpus directories or artifact files.
     pub corpus: Vec<String>,
 
+    #[structopt(short = "A", long = "all")]
+    /// Run all fuzz targets (or all matching the given glob) concurrently
+    pub all: bool,
+
+    #[structopt(long = "max-total-time", value_name = "SECONDS")]
+    /// Stop fuzzing each target after this many seconds
+    pub max_total_time: Option<u64>,
+
+    #[structopt(long = "until-new-coverage-stalls", value_name = "LIMIT")]
+    /// Stop fuzzing each target once it finds no new coverage for this long,
+    /// like `300s`, `10m`, `1h`, or `1000000execs`
+    pub until_new_coverage_stalls: Option<StallLimit>,
+
     #[structopt(
         short = "j",
         long = "jobs",
-        default_value = "1",
         validator(|v| Err(From::from(match v.parse::<u16>() {
             Ok(0) => "0 jobs?",
             Err(_) => "must be a valid integer representing a sane number of jobs",
             _ => return Ok(()),
         }))),
     )]
-    /// Number of concurrent jobs to run
-    pub jobs: u32,
+    /// Number of concurrent jobs to run [default: 1]
+    pub jobs: Option<u32>,
+
+    #[structopt(
+        long = "fork",
+        value_name = "N",
+        conflicts_with_all(&["all", "jobs"]),
+        validator(|v| match v.parse::<u32>() {
+            Ok(0) => Err("0 jobs?".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid integer representing a sane number of jobs".to_string()),
+        }),
+    )]
+    /// Fuzz in libFuzzer's fork mode with N jobs, which keeps fuzzing after
+    /// crashes, OOMs, and timeouts, and restarts libFuzzer if it exits
+    /// unexpectedly
+    pub fork: Option<u32>,
+
+    #[structopt(
+        long = "ignore-known-crashes",
+        value_name = "DIR",
+        parse(from_os_str),
+        conflicts_with("all")
+    )]
+    /// Don't fail on crashes with the same signature as one of the inputs in
+    /// this directory, and keep fuzzing after them instead
+    pub ignore_known_crashes: Option<PathBuf>,
+
+    #[structopt(
+        long = "max-crashes",
+        value_name = "N",
+        conflicts_with_all(&["all", "fork"]),
+        validator(|v| match v.parse::<usize>() {
+            Ok(0) => Err("must be at least 1".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid number of crashes".to_string()),
+        }),
+    )]
+    /// Keep fuzzing after each new crash, until this many unique crashes have
+    /// been found [default: 1]
+    pub max_crashes: Option<usize>,
+
+    #[structopt(
+        long = "timeout",
+        value_name = "SECONDS",
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1 second".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a whole number of seconds".to_string()),
+        }),
+    )]
+    /// Report an input that runs for longer than this many seconds as a
+    /// timeout [libFuzzer's default: 1200]
+    pub timeout: Option<u64>,
+
+    #[structopt(
+        long = "rss-limit",
+        value_name = "MB",
+        validator(|v| match v.parse::<u64>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a whole number of megabytes".to_string()),
+        }),
+    )]
+    /// Report the fuzzer using more than this many megabytes of memory as an
+    /// out-of-memory crash, or never if 0 [libFuzzer's default: 2048]
+    pub rss_limit: Option<u64>,
+
+    #[structopt(
+        long = "malloc-limit",
+        value_name = "MB",
+        validator(|v| match v.parse::<u64>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a whole number of megabytes".to_string()),
+        }),
+    )]
+    /// Report a single allocation of more than this many megabytes as an
+    /// out-of-memory crash [default: the RSS limit]
+    pub malloc_limit: Option<u64>,
+
+    #[structopt(
+        long = "record-trace",
+        value_name = "N",
+        conflicts_with_all(&["all", "fork", "jobs", "tui"]),
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a valid number of inputs".to_string()),
+        }),
+    )]
+    /// Record the last N inputs the fuzzer ran to fuzz/traces/<target>/, to
+    /// replay before a crash with `cargo fuzz repro --trace`
+    pub record_trace: Option<u64>,
+
+    #[structopt(
+        long = "save-hangs",
+        conflicts_with_all(&["all", "fork", "jobs", "tui"])
+    )]
+    /// Save the input of each timeout to fuzz/artifacts/<target>/hangs/, named
+    /// after when it hung, with a sample of the fuzzer's stacks while it hung
+    pub save_hangs: bool,
+
+    #[structopt(long = "auto-tmin", conflicts_with_all(&["all", "fork", "tui"]))]
+    /// Minimize each new crash after the run, keeping the original artifact
+    /// and saving the minimized one to fuzz/artifacts/<target>/minimized/
+    pub auto_tmin: bool,
+
+    #[structopt(long = "auto-tmin-time", value_name = "SECS", requires("auto-tmin"))]
+    /// Seconds to spend minimizing each crash with `--auto-tmin` [default:
+    /// 60]
+    pub auto_tmin_time: Option<u64>,
+
+    #[structopt(
+        long = "focus-changed",
+        value_name = "BASE",
+        conflicts_with_all(&["all", "corpus"])
+    )]
+    /// Seed the fuzzer with only the corpus inputs that reach code changed
+    /// since the git revision BASE, like `origin/main`
+    pub focus_changed: Option<String>,
+
+    #[structopt(flatten)]
+    pub filter: InputFilter,
+
+    #[structopt(flatten)]
+    pub features: EngineFeatures,
+
+    #[structopt(
+        long = "mode",
+        possible_values(&["leaks", "quick"]),
+        conflicts_with_all(&["all", "fork", "tui"])
+    )]
+    /// Tune the fuzzer to find one kind of bug: `leaks` detects memory leaks
+    /// after every input, and saves the inputs that leak to
+    /// fuzz/artifacts/<target>/leaks/. `quick` gives fast feedback instead:
+    /// it builds without a sanitizer, with debug assertions, overflow checks
+    /// and `-C opt-level=1`, in a build directory of its own
+    pub mode: Option<RunMode>,
+
+    #[structopt(long = "slow-threshold-ms", value_name = "MS", conflicts_with("all"))]
+    /// Save the inputs that take longer than this many milliseconds to run
+    /// to fuzz/slow/<target>/, for `cargo fuzz bench`
+    pub slow_threshold_ms: Option<u64>,
+
+    #[structopt(long = "monitor", conflicts_with_all(&["all", "tui"]))]
+    /// Sample the fuzzer's memory and CPU use every few seconds, write them
+    /// to fuzz/monitor/<target>/, and summarize them after the run
+    pub monitor: bool,
+
+    #[structopt(
+        long = "status-addr",
+        value_name = "ADDR",
+        conflicts_with_all(&["all", "tui"])
+    )]
+    /// Serve the fuzzer's progress on ADDR, like 127.0.0.1:8925, as JSON at
+    /// /status and as Prometheus metrics at /metrics
+    pub status_addr: Option<String>,
+
+    #[structopt(
+        long = "notify-webhook",
+        value_name = "URL",
+        conflicts_with_all(&["all", "tui"])
+    )]
+    /// POST a JSON event to URL, like a Slack incoming webhook, for each
+    /// crash found, when the coverage plateaus, and when the run finishes
+    pub notify_webhook: Option<String>,
+
+    #[structopt(
+        long = "notify-plateau",
+        value_name = "LIMIT",
+        requires("notify-webhook")
+    )]
+    /// How long the coverage has to stay the same for `--notify-webhook` to
+    /// report a plateau, like `30m` or `1000000execs` [default: 1h]
+    pub notify_plateau: Option<StallLimit>,
+
+    #[structopt(
+        long = "tui",
+        conflicts_with_all(&[
+            "all",
+            "fork",
+            "max-crashes",
+            "ignore-known-crashes",
+            "until-new-coverage-stalls",
+        ])
+    )]
+    /// Show a live dashboard of each job's progress, and write the fuzzer's
+    /// output to fuzz/logs/<target>/ instead
+    pub tui: bool,
+
+    #[structopt(long = "sarif", value_name = "FILE", parse(from_os_str))]
+    /// Write the crashes found to a SARIF file, for GitHub code scanning
+    pub sarif: Option<PathBuf>,
+
+    #[structopt(
+        long = "asan-option",
+        value_name = "KEY=VALUE",
+        number_of_values = 1,
+        validator(|v| match v.split_once('=') {
+            Some((key, _)) if !key.is_empty() && !v.contains(':') => Ok(()),
+            _ => Err("must be a single option like `detect_odr_violation=0`".to_string()),
+        }),
+    )]
+    /// Set an AddressSanitizer option, over those from ASAN_OPTIONS and
+    /// fuzz/asan_options. May be given more than once
+    pub asan_options: Vec<String>,
+
+    #[structopt(
+        long = "dict",
+        value_name = "FILE",
+        number_of_values = 1,
+        parse(from_os_str)
+    )]
+    /// Fuzz with this dictionary instead of the target's dictionaries in
+    /// fuzz/dictionaries. May be given more than once to merge several
+    pub dicts: Vec<PathBuf>,
+
+    #[structopt(
+        long = "seed",
+        value_name = "N",
+        conflicts_with_all(&[
+            "all",
+            "jobs",
+            "fork",
+            "tui",
+            "max-total-time",
+            "until-new-coverage-stalls",
+            "focus-changed",
+        ])
+    )]
+    /// Fuzz deterministically, with this seed, a fixed number of runs, and a
+    /// single job, and write a reproducibility manifest of the run to
+    /// fuzz/manifests/
+    pub seed: Option<u32>,
+
+    #[structopt(
+        long = "replay-manifest",
+        value_name = "FILE",
+        parse(from_os_str),
+        conflicts_with_all(&["all", "seed"])
+    )]
+    /// Fuzz again exactly like the deterministic run that wrote this
+    /// reproducibility manifest
+    pub replay_manifest: Option<PathBuf>,
 
     #[structopt(last(true))]
     /// Additional libFuzzer arguments passed through to the binary
     pub args: Vec<String>,
 }
 
+/// How long a fuzzer may go without finding new coverage before it's
+/// considered to have plateaued.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StallLimit {
+    Seconds(u64),
+    Execs(u64),
+}
+
+impl stdfmt::Display for StallLimit {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        match self {
+            StallLimit::Seconds(secs) => write!(f, "{} seconds", secs),
+            StallLimit::Execs(execs) => write!(f, "{} execs", execs),
+        }
+    }
+}
+
+impl FromStr for StallLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: u64 = number
+            .parse()
+            .map_err(|_| format!("invalid stall limit: {}", s))?;
+        let seconds = |unit: u64| {
+            number
+                .checked_mul(unit)
+                .map(StallLimit::Seconds)
+                .ok_or_else(|| format!("stall limit too large: {}", s))
+        };
+        match unit {
+            "" | "s" => seconds(1),
+            "m" => seconds(60),
+            "h" => seconds(60 * 60),
+            "execs" => Ok(StallLimit::Execs(number)),
+            _ => Err(format!(
+                "invalid stall limit: {} (expected a number of seconds, minutes, hours, or execs)",
+                s
+            )),
+        }
+    }
+}
+
+/// The kind of bug that `--mode` tunes the fuzzer to find.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunMode {
+    Leaks,
+    Quick,
+}
+
+impl stdfmt::Display for RunMode {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        match self {
+            RunMode::Leaks => write!(f, "leaks"),
+            RunMode::Quick => write!(f, "quick"),
+        }
+    }
+}
+
+impl FromStr for RunMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leaks" => Ok(RunMode::Leaks),
+            "quick" => Ok(RunMode::Quick),
+            _ => Err(format!("unknown mode: {}", s)),
+        }
+    }
+}
+
 impl RunCommand for Run {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::find_existing()?;
-        project.exec_fuzz(self)
+        if !self.all {
+            self.target = self.target.as_deref().map(|t| project.resolve_target(t));
+        }
+        if self.mode == Some(RunMode::Quick) {
+            match self.build.sanitizer {
+                None | Some(Sanitizer::None) => {}
+                Some(sanitizer) => bail!(
+                    "`--mode quick` builds without a sanitizer, so it can't be combined with \
+                     `--sanitizer {}`",
+                    sanitizer
+                ),
+            }
+            self.build.sanitizer = Some(Sanitizer::None);
+            self.build.debug_assertions = true;
+            self.build.quick = true;
+        }
+        if self.all {
+            project.exec_fuzz_all(self)
+        } else if let Some(manifest) = &self.replay_manifest {
+            project.exec_replay_manifest(self, manifest)
+        } else {
+            project.exec_fuzz(self)
+        }
     }
 }
@@ -0,0 +1,17 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Serve {
+    #[structopt(long = "bind", value_name = "ADDR", default_value = "0.0.0.0:7878")]
+    /// Address to listen for workers on
+    pub bind: String,
+}
+
+impl RunCommand for Serve {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_serve(self)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub enum Session {
+    /// Save a fuzz target's corpus and fuzzing settings as a named session
+    Save(SessionSave),
+
+    /// Resume fuzzing from a saved session
+    Resume(SessionResume),
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct SessionSave {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "seed", value_name = "N")]
+    /// Seed for libFuzzer's random number generator [default: from `-seed=`
+    /// in the libFuzzer arguments, or a random one]
+    pub seed: Option<u32>,
+
+    #[structopt(long = "force")]
+    /// Replace an existing session with the same name
+    pub force: bool,
+
+    /// Name of the session
+    pub name: String,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(last(true))]
+    /// libFuzzer arguments to resume the session with
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct SessionResume {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "force")]
+    /// Resume the session even if the fuzz target has changed since it was
+    /// saved
+    pub force: bool,
+
+    /// Name of the session
+    pub name: String,
+
+    #[structopt(last(true))]
+    /// Additional libFuzzer arguments, passed after the session's own
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Session {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        match self {
+            Session::Save(save) => project.exec_session_save(save),
+            Session::Resume(resume) => project.exec_session_resume(resume),
+        }
+    }
+}
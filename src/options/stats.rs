@@ -0,0 +1,21 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Stats {
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(short = "n", long = "last", default_value = "10")]
+    /// Number of most recent runs to show
+    pub last: usize,
+}
+
+impl RunCommand for Stats {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_stats(self)
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Status {
+    /// A glob of the names of the fuzz targets to check [default: all of
+    /// them]
+    pub targets: Option<String>,
+}
+
+impl RunCommand for Status {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_status(self)
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct SuggestSplit {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(long)]
+    /// Add the suggested fuzz targets, as copies of the target to trim down,
+    /// each with the inputs of the corpus that reach its entry points
+    pub scaffold: bool,
+}
+
+impl RunCommand for SuggestSplit {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_suggest_split(self)
+    }
+}
@@ -25,14 +25,37 @@ pub struct Tmin {
     /// Number of minimization attempts to perform
     pub runs: u32,
 
-    #[structopt(parse(from_os_str))]
-    /// Path to the failing test case to be minimized
-    pub test_case: PathBuf,
+    #[structopt(parse(from_os_str), required_unless("batch"))]
+    /// Path to the failing test case to be minimized, or with `--batch`, the
+    /// directory of test cases (defaults to the target's artifacts)
+    pub test_case: Option<PathBuf>,
+
+    #[structopt(short = "b", long = "batch")]
+    /// Minimize every test case in a directory, keep the smallest one for each
+    /// unique crash, and rename it to `crash-<hash>-<length>`
+    pub batch: bool,
+
+    #[structopt(
+        short = "j",
+        long = "jobs",
+        requires("batch"),
+        validator(|v| Err(From::from(match v.parse::<u16>() {
+            Ok(0) => "0 jobs?",
+            Err(_) => "must be a valid integer representing a sane number of jobs",
+            _ => return Ok(()),
+        }))),
+    )]
+    /// Number of test cases to minimize concurrently with `--batch` [default: 1]
+    pub jobs: Option<usize>,
 }
 
 impl RunCommand for Tmin {
     fn run_command(&mut self) -> Result<()> {
         let project = FuzzProject::find_existing()?;
-        project.exec_tmin(self)
+        if self.batch {
+            project.exec_tmin_batch(self)
+        } else {
+            project.exec_tmin(self)
+        }
     }
 }
@@ -0,0 +1,29 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Triage {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(required(true))]
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(parse(from_os_str))]
+    /// The artifacts directory to triage, defaults to the target's artifacts
+    pub artifacts: Option<PathBuf>,
+
+    #[structopt(long = "sarif", value_name = "FILE", parse(from_os_str))]
+    /// Also write the unique crashes to a SARIF file, for GitHub code scanning
+    pub sarif: Option<PathBuf>,
+}
+
+impl RunCommand for Triage {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_triage(self)
+    }
+}
@@ -0,0 +1,25 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct VerifyFixes {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    #[structopt(long = "move-fixed")]
+    /// Move the artifacts that no longer crash to the `fixed` subdirectory of
+    /// their target's artifacts directory
+    pub move_fixed: bool,
+
+    /// A glob of the names of the fuzz targets whose artifacts to replay
+    /// [default: all of them]
+    pub targets: Option<String>,
+}
+
+impl RunCommand for VerifyFixes {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_verify_fixes(self)
+    }
+}
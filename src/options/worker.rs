@@ -0,0 +1,48 @@
+use crate::{options::BuildOptions, project::FuzzProject, RunCommand};
+use anyhow::Result;
+use structopt::StructOpt;
+
+#[derive(Clone, Debug, StructOpt)]
+pub struct Worker {
+    #[structopt(flatten)]
+    pub build: BuildOptions,
+
+    /// Name of the fuzz target
+    pub target: String,
+
+    #[structopt(long = "connect", value_name = "ADDR")]
+    /// Address of the `cargo fuzz serve` coordinator, like `fuzz-01:7878`
+    pub connect: String,
+
+    #[structopt(
+        long = "sync-interval",
+        value_name = "SECONDS",
+        default_value = "60",
+        validator(|v| match v.parse::<u64>() {
+            Ok(0) => Err("must be at least 1 second".to_string()),
+            Ok(_) => Ok(()),
+            Err(_) => Err("must be a whole number of seconds".to_string()),
+        }),
+    )]
+    /// Seconds to fuzz for between syncing the corpus with the coordinator
+    pub sync_interval: u64,
+
+    #[structopt(long = "max-total-time", value_name = "SECONDS")]
+    /// Stop after fuzzing for this many seconds [default: never]
+    pub max_total_time: Option<u64>,
+
+    #[structopt(short = "j", long = "jobs")]
+    /// Number of concurrent jobs to run [default: 1]
+    pub jobs: Option<u32>,
+
+    #[structopt(last(true))]
+    /// Additional libFuzzer arguments passed through to the binary
+    pub args: Vec<String>,
+}
+
+impl RunCommand for Worker {
+    fn run_command(&mut self) -> Result<()> {
+        let project = FuzzProject::find_existing()?;
+        project.exec_worker(self)
+    }
+}
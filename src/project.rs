@@ -1,14 +1,122 @@
-use crate::options::{self, BuildOptions, Sanitizer};
+use crate::annotate;
+use crate::api;
+use crate::bench;
+use crate::bloat;
+use crate::blockers;
+use crate::ci;
+use crate::completions::Expected;
+use crate::config::{Config, TargetConfig};
+use crate::coverage;
+use crate::crash::{self, Crash};
+use crate::dashboard;
+use crate::decoder;
+use crate::dict;
+use crate::distribute;
+use crate::engine_args;
+use crate::exit::{self, ExitCode};
+use crate::export;
+use crate::exports;
+use crate::flamegraph;
+use crate::grammar;
+use crate::hang;
+use crate::health::{self, Health};
+use crate::import;
+use crate::libfuzzer;
+use crate::message::{Message, MessageFormat};
+use crate::miri;
+use crate::monitor;
+use crate::notify;
+use crate::options::{
+    self, BuildOptions, Engine, EngineFeature, RunMode, Sanitizer, TargetTemplate, Vcs,
+};
+use crate::provenance::{self, Origin};
+use crate::replay;
+use crate::report;
+use crate::rustc_wrapper;
+use crate::sarif;
+use crate::session;
+use crate::signature;
+use crate::slow;
+use crate::split;
+use crate::stats;
+use crate::status;
+use crate::utils;
 use anyhow::{anyhow, bail, Context, Result};
-use std::collections::HashSet;
-use std::io::Read;
-use std::io::Write;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, Once, OnceLock};
 use std::{
-    env, ffi, fs,
-    process::{Command, Stdio},
-    time,
+    env, ffi, fs, iter,
+    process::{Child, Command, ExitStatus, Output, Stdio},
+    thread, time,
 };
+use structopt::StructOpt;
+
+/// How often to check whether a fuzzer's coverage has stalled.
+const STALL_POLL_INTERVAL: time::Duration = time::Duration::from_millis(500);
+
+/// How often `watch` checks for changed sources.
+const WATCH_POLL_INTERVAL: time::Duration = time::Duration::from_millis(500);
+
+/// The environment variable that tells the fuzz targets `run` starts where
+/// their artifacts go.
+const ARTIFACTS_ENV: &str = "CARGO_FUZZ_ARTIFACTS";
+
+/// The module that `cargo fuzz add --with-bypass-module` adds to the fuzzed
+/// crate.
+const BYPASS_MODULE: &str = "fuzz_bypass";
+
+/// The module of the fuzzed crate whose `record` function saves the inputs
+/// that its tests pass to a function, for `cargo fuzz corpus from-tests`.
+const RECORD_MODULE: &str = "fuzz_record";
+
+/// The environment variable that tells `fuzz_record::record` where to save
+/// the inputs it records.
+const RECORD_DIR_ENV: &str = "CARGO_FUZZ_RECORD_DIR";
+
+/// Where the comparison recorder that `cargo fuzz blockers` links in writes
+/// what it saw.
+const CMP_LOG_ENV: &str = "CARGO_FUZZ_CMP_LOG";
+
+/// The prefix of the reports that targets made by `cargo fuzz add
+/// --differential` write to the artifacts directory when two implementations
+/// diverge, followed by the FNV-1a hash of the input.
+const DIVERGENCE_REPORT_PREFIX: &str = "divergence-";
+
+/// The prefix that crash artifacts get instead of libFuzzer's `crash-` when
+/// the crash was a stack overflow.
+const STACK_OVERFLOW_PREFIX: &str = "stack-overflow-";
+
+/// The flags that `run --mode quick` builds with, on top of debug assertions:
+/// light enough optimizations to build fast, and overflow checks.
+const QUICK_RUSTFLAGS: &str = " -Copt-level=1 -Coverflow-checks=on";
+
+/// How many of the functions the most samples were taken in `cargo fuzz
+/// profile` lists.
+const MAX_HOTTEST_LISTED: usize = 15;
+
+/// The version of LibAFL that the harness for `--engine libafl` is built with.
+const LIBAFL_VERSION: &str = "0.16.1";
+
+/// The name of the tests `cargo fuzz check --replay` generates in `fuzz/tests`.
+const CORPUS_REPLAY_TESTS: &str = "corpus_replay";
+
+/// The malloc limit, in megabytes, that `run --mode leaks` fuzzes with unless
+/// another one is given.
+const LEAKS_MALLOC_LIMIT_MB: u64 = 256;
+
+/// How many of the inputs it seeds the fuzzer with `run --focus-changed` lists.
+const MAX_FOCUS_LISTED: usize = 10;
+
+/// How many bytes of a minimized input `run --auto-tmin` shows.
+const PREVIEW_BYTES: usize = 64;
+
+/// How many inputs `coverage` replays before suggesting `--cmin-first`.
+const LARGE_CORPUS: usize = 10_000;
+
+/// How many inputs `run --seed` tries, unless `-runs` says otherwise.
+const DETERMINISTIC_RUNS: u64 = 100_000;
 
 pub struct FuzzProject {
     /// Path to the root cargo project
@@ -16,15 +124,33 @@ pub struct FuzzProject {
     /// Not the project with fuzz targets, but the project being fuzzed
     root_project: PathBuf,
     targets: Vec<String>,
+    /// The `path` of each fuzz target that sets one, relative to the fuzz
+    /// crate
+    target_paths: BTreeMap<String, PathBuf>,
+    /// The `required-features` of the fuzz targets that have any
+    required_features: BTreeMap<String, Vec<String>>,
+    /// Settings from `fuzz/fuzz.toml`
+    config: Config,
+    /// The Cargo target directory, once something has asked for it
+    cargo_target_dir: OnceLock<PathBuf>,
 }
 
 impl FuzzProject {
     /// Find an existing `cargo fuzz` project by starting at the current
     /// directory and walking up the filesystem.
     pub fn find_existing() -> Result<Self> {
+        FuzzProject::open(find_package()?)
+    }
+
+    /// Open the `cargo fuzz` project of the crate in `root_project`.
+    fn open(root_project: PathBuf) -> Result<Self> {
         let mut project = FuzzProject {
-            root_project: find_package()?,
+            root_project,
             targets: Vec::new(),
+            target_paths: BTreeMap::new(),
+            required_features: BTreeMap::new(),
+            config: Config::default(),
+            cargo_target_dir: OnceLock::new(),
         };
         let manifest = project.manifest()?;
         if !is_fuzz_manifest(&manifest) {
@@ -37,6 +163,9 @@ impl FuzzProject {
             );
         }
         project.targets = collect_targets(&manifest);
+        project.target_paths = collect_target_paths(&manifest);
+        project.required_features = collect_required_features(&manifest);
+        project.config = Config::load(&project.config_path())?;
         Ok(project)
     }
 
@@ -47,9 +176,23 @@ impl FuzzProject {
         let project = FuzzProject {
             root_project: find_package()?,
             targets: Vec::new(),
+            target_paths: BTreeMap::new(),
+            required_features: BTreeMap::new(),
+            config: Config::default(),
+            cargo_target_dir: OnceLock::new(),
         };
         let fuzz_project = project.path();
         let root_project_name = project.root_project_name()?;
+        let workspace_root = find_workspace_root(&project.root_project)?;
+        if init.add_to_workspace && workspace_root.is_none() {
+            bail!(
+                "`--add-to-workspace` was given, but {} isn't part of a Cargo workspace",
+                project.root_project.display()
+            );
+        }
+        if init.lfs_corpus && init.vcs == Vcs::None {
+            bail!("`--lfs-corpus` needs `--vcs git`");
+        }
 
         // TODO: check if the project is already initialized
         fs::create_dir(&fuzz_project)
@@ -63,19 +206,101 @@ impl FuzzProject {
         let cargo_toml = fuzz_project.join("Cargo.toml");
         let mut cargo = fs::File::create(&cargo_toml)
             .with_context(|| format!("failed to create {}", cargo_toml.display()))?;
+        let mut keys: Vec<String> = match &init.libfuzzer_sys {
+            Some(spec) => libfuzzer_sys_source(spec)?,
+            None => vec![("version", "0.3".to_string())],
+        }
+        .into_iter()
+        .map(|(key, value)| format!("{} = {}", key, toml::Value::String(value)))
+        .collect();
+        // Deriving `Arbitrary` needs a feature of `libfuzzer-sys`.
+        if let TargetTemplate::Arbitrary = init.template {
+            keys.push(r#"features = ["arbitrary-derive"]"#.to_string());
+        }
+        let libfuzzer_sys = match keys.as_slice() {
+            [key] if key.starts_with("version = ") => key["version = ".len()..].to_string(),
+            _ => format!("{{ {} }}", keys.join(", ")),
+        };
+        // A `no_std` crate's default features are often for the device it runs
+        // on, so leave them out until they're asked for.
+        let no_std = project.root_is_no_std()?;
         cargo
-            .write_fmt(toml_template!(root_project_name))
+            .write_fmt(toml_template!(root_project_name, libfuzzer_sys, no_std))
             .with_context(|| format!("failed to write to {}", cargo_toml.display()))?;
+        if !init.add_to_workspace {
+            cargo
+                .write_fmt(toml_workspace_template!())
+                .with_context(|| format!("failed to write to {}", cargo_toml.display()))?;
+        }
+
+        // Cargo refuses to build a crate that is inside a workspace's
+        // directory without being one of its members, even if the crate has a
+        // workspace of its own, so either add it as a member or exclude it.
+        if let Some(workspace_root) = &workspace_root {
+            let key = if init.add_to_workspace {
+                "members"
+            } else {
+                "exclude"
+            };
+            let entry = fuzz_project
+                .strip_prefix(workspace_root)
+                .unwrap_or(&fuzz_project)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let workspace_manifest = workspace_root.join("Cargo.toml");
+            add_to_manifest_list(&workspace_manifest, "workspace", key, &entry)?;
+            eprintln!(
+                "Added `{}` to `workspace.{}` in {}",
+                entry,
+                key,
+                workspace_manifest.display()
+            );
+        }
+
+        project.create_fuzz_lib()?;
+
+        if let Some(archive) = &init.libfuzzer_archive {
+            let config_path = project.config_path();
+            fs::write(
+                &config_path,
+                format!(
+                    "libfuzzer-archive = {}\n",
+                    toml::Value::String(archive.display().to_string())
+                ),
+            )
+            .with_context(|| format!("failed to write {}", config_path.display()))?;
+        }
 
-        let gitignore = fuzz_project.join(".gitignore");
-        let mut ignore = fs::File::create(&gitignore)
-            .with_context(|| format!("failed to create {}", gitignore.display()))?;
-        ignore
-            .write_fmt(gitignore_template!())
-            .with_context(|| format!("failed to write to {}", gitignore.display()))?;
+        if init.vcs == Vcs::Git {
+            let (ignored_corpus, corpus_attributes) = if init.lfs_corpus {
+                ("", "filter=lfs diff=lfs merge=lfs -text")
+            } else {
+                ("corpus\n", "binary")
+            };
+            let gitignore = fuzz_project.join(".gitignore");
+            let mut ignore = fs::File::create(&gitignore)
+                .with_context(|| format!("failed to create {}", gitignore.display()))?;
+            ignore
+                .write_fmt(gitignore_template!(ignored_corpus))
+                .with_context(|| format!("failed to write to {}", gitignore.display()))?;
+            let gitattributes = fuzz_project.join(".gitattributes");
+            let mut attributes = fs::File::create(&gitattributes)
+                .with_context(|| format!("failed to create {}", gitattributes.display()))?;
+            attributes
+                .write_fmt(gitattributes_template!(corpus_attributes))
+                .with_context(|| format!("failed to write to {}", gitattributes.display()))?;
+            if init.lfs_corpus {
+                eprintln!(
+                    "The corpus is tracked with Git LFS; run `git lfs install` once if you \
+                     haven't, so that its inputs are stored there"
+                );
+            }
+        }
 
         project
-            .create_target_template(&init.target)
+            .create_target_template(&init.target, init.template)
             .with_context(|| {
                 format!(
                     "could not create template file for target {:?}",
@@ -85,633 +310,10838 @@ impl FuzzProject {
         Ok(project)
     }
 
-    pub fn list_targets(&self) -> Result<()> {
+    pub fn list_targets(&self, list: &options::List) -> Result<()> {
+        let prefix = list
+            .dir
+            .as_ref()
+            .map(|dir| format!("{}/", dir.trim_end_matches('/')));
+        let mut healths = Vec::new();
         for bin in &self.targets {
-            println!("{}", bin);
+            if let Some(prefix) = &prefix {
+                if !self.target_subpath(bin).starts_with(prefix.as_str()) {
+                    continue;
+                }
+            }
+            let health = if list.verbose {
+                Some(self.target_health(bin)?)
+            } else {
+                None
+            };
+            if list.message_format.is_json() {
+                list.message_format.emit(&Message::FuzzTarget {
+                    name: bin,
+                    health: health.as_ref(),
+                });
+            } else if let Some(health) = health {
+                healths.push((bin.as_str(), health));
+            } else {
+                println!("{}", bin);
+            }
+        }
+        if !healths.is_empty() {
+            print!("{}", health::render(&healths));
         }
         Ok(())
     }
 
-    /// Create a new fuzz target.
-    pub fn add_target(&self, add: &options::Add) -> Result<()> {
-        // Create corpus and artifact directories for the newly added target
-        self.corpus_for(&add.target)?;
-        self.artifacts_for(&add.target)?;
-        self.create_target_template(&add.target)
-            .with_context(|| format!("could not add target {:?}", add.target))
+    /// The fuzz targets, artifacts or corpus directories that a word of a
+    /// command line can be, for the shell completions. Nothing is created
+    /// that isn't there yet.
+    pub fn completions(&self, expected: &Expected) -> Result<Vec<String>> {
+        let targets = match expected {
+            Expected::Target => return Ok(self.targets.clone()),
+            Expected::Artifact(Some(target)) | Expected::Corpus(Some(target)) => {
+                vec![target.clone()]
+            }
+            _ => self.targets.clone(),
+        };
+        let mut dirs = BTreeSet::new();
+        for target in &targets {
+            match expected {
+                Expected::Artifact(_) => {
+                    dirs.insert(self.path().join("artifacts").join(target));
+                }
+                Expected::Corpus(_) => {
+                    dirs.insert(self.corpus_path(target));
+                    let seeds = self.config.target(Some(target)).seeds;
+                    dirs.extend(seeds.iter().flatten().map(|seeds| self.path().join(seeds)));
+                }
+                _ => {}
+            }
+        }
+        let mut candidates = Vec::new();
+        for dir in dirs.into_iter().filter(|dir| dir.is_dir()) {
+            if let Expected::Corpus(_) = expected {
+                candidates.push(dir);
+            } else {
+                candidates.extend(collect_files(&dir)?);
+            }
+        }
+        Ok(candidates
+            .iter()
+            .map(|path| strip_current_dir_prefix(path).display().to_string())
+            .collect())
     }
 
-    /// Add a new fuzz target script with a given name
-    fn create_target_template(&self, target: &str) -> Result<()> {
-        let target_path = self.target_path(target);
-
-        // If the user manually created a fuzz project, but hasn't created any
-        // targets yet, the `fuzz_targets` directory might not exist yet,
-        // despite a `fuzz/Cargo.toml` manifest with the `metadata.cargo-fuzz`
-        // key present. Make sure it does exist.
-        fs::create_dir_all(self.fuzz_targets_dir())
-            .context("ensuring that `fuzz_targets` directory exists failed")?;
-
-        let mut script = fs::OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&target_path)
-            .with_context(|| format!("could not create target script file at {:?}", target_path))?;
-        script.write_fmt(target_template!())?;
-
-        let mut cargo = fs::OpenOptions::new()
-            .append(true)
-            .open(self.manifest_path())?;
-        Ok(cargo.write_fmt(toml_bin_template!(target))?)
+    /// How a fuzz target is doing, from its corpus, artifacts, recorded runs,
+    /// and coverage profile.
+    fn target_health(&self, target: &str) -> Result<Health> {
+        // Listing doesn't make the directories, so one that isn't there yet
+        // is empty.
+        let files = |dir: &Path| {
+            if dir.is_dir() {
+                collect_files(dir)
+            } else {
+                Ok(Vec::new())
+            }
+        };
+        let (corpus_entries, corpus_bytes) = corpus_size(&files(&self.corpus_path(target))?);
+        let artifacts = files(&self.path().join("artifacts").join(target))?
+            .iter()
+            .filter(|path| !is_divergence_report(path))
+            .count();
+        let stats_path = self.stats_path(target);
+        let records = if stats_path.is_file() {
+            stats::load(&stats_path)?
+        } else {
+            Vec::new()
+        };
+        Ok(Health {
+            corpus_entries,
+            corpus_bytes,
+            artifacts,
+            last_run: records.last().map(|record| record.started),
+            execs_per_sec: records.iter().rev().find_map(|record| record.execs_per_sec),
+            coverage_percent: self.last_coverage_percent(target),
+        })
     }
 
-    fn cargo(&self, subcommand: &str, build: &BuildOptions) -> Result<Command> {
-        let mut cmd = Command::new("cargo");
-        cmd.arg(subcommand)
-            .arg("--manifest-path")
-            .arg(self.manifest_path())
-            // --target=<TARGET> won't pass rustflags to build scripts
-            .arg("--target")
-            .arg(&build.triple);
-        // we default to release mode unless debug mode is explicitly requested
-        if !build.dev {
-            cmd.arg("--release");
+    /// The share of the fuzzed crate's code regions covered by the profile
+    /// that `cargo fuzz coverage` last wrote for `target`, or `None` if the
+    /// profile or the coverage build it was made with are gone.
+    fn last_coverage_percent(&self, target: &str) -> Option<f64> {
+        let profdata = self
+            .path()
+            .join("coverage")
+            .join(target)
+            .join("coverage.profdata");
+        // `cargo fuzz coverage` with the default options built this.
+        let mut build = options::BuildOptions::from_iter(["cargo-fuzz"]);
+        build.coverage = true;
+        build.sanitizer = Some(Sanitizer::None);
+        let binary = self.target_binary(&build, target);
+        if !profdata.is_file() || !binary.is_file() {
+            return None;
         }
-        if build.verbose {
-            cmd.arg("--verbose");
-        }
-        if build.no_default_features {
-            cmd.arg("--no-default-features");
+        let sources = self.root_project.join("src");
+        let export = export_coverage(&binary, &profdata, &sources).ok()?;
+        let regions = coverage::parse_export(&export, &sources).ok()?;
+        if regions.is_empty() {
+            return None;
         }
-        if build.all_features {
-            cmd.arg("--all-features");
+        let covered = regions.values().filter(|covered| **covered).count();
+        Some(100.0 * covered as f64 / regions.len() as f64)
+    }
+
+    /// Create a new fuzz target.
+    ///
+    /// A target given as a path like `parser/headers` goes in that
+    /// subdirectory of `fuzz_targets`, and is named `parser_headers`.
+    pub fn add_target(&self, add: &options::Add) -> Result<()> {
+        let target = add
+            .target
+            .as_deref()
+            .ok_or_else(|| anyhow!("no fuzz target name given"))?;
+        let name = nested_target_name(target)?;
+        if self.targets.contains(&name) {
+            bail!("a fuzz target named `{}` already exists", name);
         }
-        if let Some(ref features) = build.features {
-            cmd.arg("--features").arg(features);
+        // Create corpus and artifact directories for the newly added target
+        if add.reject_invalid {
+            self.check_corpus_support()?;
         }
-        for flag in &build.unstable_flags {
-            cmd.arg("-Z").arg(flag);
+        self.corpus_for(&name)?;
+        self.artifacts_for(&name)?;
+        match &add.arbitrary {
+            Some(ty) => self.create_arbitrary_target(target, ty),
+            None if add.grammar.is_some() => {
+                self.create_grammar_target(target, add.grammar.as_deref().unwrap())
+            }
+            None if add.differential => {
+                self.create_differential_target(target, add.reference.as_deref())
+            }
+            None if add.ffi.is_some() => {
+                self.create_ffi_target(target, add.ffi.as_deref().unwrap())
+            }
+            None => match (&add.function, &add.bin) {
+                (Some(function), _) => {
+                    self.create_function_target(target, function, add.reject_invalid)
+                }
+                (None, Some(bin)) => self.create_bin_target(target, bin, add.input),
+                (None, None) if add.reject_invalid => {
+                    self.create_target(target, reject_invalid_target_template!())
+                }
+                (None, None) => self.create_target_template(target, TargetTemplate::Default),
+            },
         }
-        if let Sanitizer::Memory = build.sanitizer {
-            cmd.arg("-Z").arg("build-std");
+        .with_context(|| format!("could not add target {:?}", target))?;
+        if add.with_bypass_module {
+            self.create_bypass_module()
+                .context("could not add the bypass module")?;
         }
+        Ok(())
+    }
 
-        let mut rustflags: String = "--cfg fuzzing \
-                                     -Cpasses=sancov \
-                                     -Cllvm-args=-sanitizer-coverage-level=4 \
-                                     -Cllvm-args=-sanitizer-coverage-trace-compares \
-                                     -Cllvm-args=-sanitizer-coverage-inline-8bit-counters \
-                                     -Cllvm-args=-sanitizer-coverage-pc-table \
-                                     -Clink-dead-code"
-            .to_owned();
-        match build.sanitizer {
-            Sanitizer::None => {}
-            Sanitizer::Memory => {
-                // Memory sanitizer requires more flags to function than others:
-                // https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html#memorysanitizer
-                rustflags.push_str(" -Zsanitizer=memory -Zsanitizer-memory-track-origins")
-            }
-            _ => rustflags.push_str(&format!(
-                " -Zsanitizer={sanitizer}",
-                sanitizer = build.sanitizer
-            )),
+    /// Add the `fuzz_bypass` module next to the root of the fuzzed crate's
+    /// library, and declare it there, unless it already exists.
+    fn create_bypass_module(&self) -> Result<()> {
+        let path = self.create_root_module(
+            BYPASS_MODULE,
+            &bypass_module_template!().to_string(),
+            "Skips checks that the fuzzer can't get past in fuzzing builds.",
+        )?;
+        eprintln!(
+            "Route checksum and signature checks through `crate::{0}::checksum_matches` and \
+             `crate::{0}::verify`, which pass every input when fuzzing; see {1}\n",
+            BYPASS_MODULE,
+            strip_current_dir_prefix(&path).display()
+        );
+        Ok(())
+    }
+
+    /// Make sure that the fuzz crate's `libfuzzer-sys` lets fuzz targets
+    /// return `Corpus`, which 0.4.4 added: that its manifest allows a version
+    /// that does, and that its lockfile doesn't pin one that doesn't. A
+    /// `libfuzzer-sys` from git, a path, or fuzz.toml is taken to be new
+    /// enough.
+    fn check_corpus_support(&self) -> Result<()> {
+        const CORPUS_VERSION: Version = (0, 4, 4);
+        if self.config.libfuzzer_sys.is_some() {
+            return Ok(());
         }
-        if build.triple.contains("-linux-") {
-            rustflags.push_str(" -Cllvm-args=-sanitizer-coverage-stack-depth");
+        let manifest = self.manifest()?;
+        let requirement = match manifest
+            .get("dependencies")
+            .and_then(|d| d.get("libfuzzer-sys"))
+        {
+            Some(toml::Value::String(requirement)) => requirement.clone(),
+            Some(toml::Value::Table(dependency)) => {
+                match dependency.get("version").and_then(toml::Value::as_str) {
+                    Some(requirement) if !dependency.contains_key("git") => requirement.to_string(),
+                    _ => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+        let (lowest, newest) = match version_range(&requirement) {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+        if newest < CORPUS_VERSION {
+            bail!(
+                "returning `Corpus` from a fuzz target needs libfuzzer-sys 0.4.4 or later, but \
+                 {} depends on libfuzzer-sys {}; update it to \"0.4\"",
+                strip_current_dir_prefix(&self.manifest_path()).display(),
+                requirement
+            );
         }
-        if !build.release || build.debug_assertions {
-            rustflags.push_str(" -Cdebug-assertions");
+        // Cargo keeps a locked version for as long as it meets the
+        // requirement.
+        let locked = self
+            .locked_version("libfuzzer-sys")
+            .and_then(|locked| version_range(&format!("={}", locked.rsplit(' ').next()?)))
+            .map(|(locked, _)| locked)
+            .filter(|locked| (lowest..=newest).contains(locked));
+        if let Some(locked) = locked.filter(|locked| *locked < CORPUS_VERSION) {
+            bail!(
+                "returning `Corpus` from a fuzz target needs libfuzzer-sys 0.4.4 or later, but \
+                 {} pins libfuzzer-sys {}.{}.{}; update it with `cargo update -p libfuzzer-sys` \
+                 in {}",
+                strip_current_dir_prefix(&self.path().join("Cargo.lock")).display(),
+                locked.0,
+                locked.1,
+                locked.2,
+                strip_current_dir_prefix(&self.path()).display()
+            );
         }
+        Ok(())
+    }
 
-        // If release mode is enabled then we force 1 CGU to be used in rustc.
-        // This will result in slower compilations but it looks like the sancov
-        // passes otherwise add `notEligibleToImport` annotations to functions
-        // in LLVM IR, meaning that *nothing* can get imported with ThinLTO.
-        // This means that in release mode, where ThinLTO is critical for
-        // performance, we're taking a huge hit relative to actual release mode.
-        // Local tests have once showed this to be a ~3x faster runtime where
-        // otherwise functions like `Vec::as_ptr` aren't inlined.
-        if !build.dev {
-            rustflags.push_str(" -C codegen-units=1");
+    /// Add a module named `name` with `contents` next to the root of the
+    /// fuzzed crate's library, unless it already exists, and declare it
+    /// there with `comment`, unless it's already declared. Returns the path
+    /// of the module.
+    fn create_root_module(&self, name: &str, contents: &str, comment: &str) -> Result<PathBuf> {
+        let lib = self.root_lib_path()?;
+        let source = match self.root_lib_source()? {
+            Some(source) => source,
+            None => bail!("the fuzzed crate has no library to add it to"),
+        };
+        let path = lib.with_file_name(format!("{}.rs", name));
+        if path.exists() {
+            eprintln!(
+                "{} already exists; leaving it as it is",
+                strip_current_dir_prefix(&path).display()
+            );
+        } else {
+            fs::write(&path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
         }
 
-        if let Ok(other_flags) = env::var("RUSTFLAGS") {
-            rustflags.push_str(" ");
-            rustflags.push_str(&other_flags);
+        let declared = source.lines().any(|line| {
+            line.split_whitespace()
+                .skip_while(|word| word.starts_with("pub"))
+                .collect::<Vec<_>>()
+                == ["mod", &format!("{};", name)]
+        });
+        if !declared {
+            let mut lib_file = fs::OpenOptions::new()
+                .append(true)
+                .open(&lib)
+                .with_context(|| format!("failed to open {}", lib.display()))?;
+            let separator = if source.is_empty() || source.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            write!(lib_file, "{}\n// {}\nmod {};\n", separator, comment, name)
+                .with_context(|| format!("failed to write {}", lib.display()))?;
         }
-        cmd.env("RUSTFLAGS", rustflags);
+        Ok(path)
+    }
 
-        // For asan and tsan we have default options. Merge them to the given
-        // options, so users can still provide their own options to e.g. disable
-        // the leak sanitizer.  Options are colon-separated.
-        match build.sanitizer {
-            Sanitizer::Address => {
-                let mut asan_opts = env::var("ASAN_OPTIONS").unwrap_or_default();
-                if !asan_opts.is_empty() {
-                    asan_opts.push(':');
-                }
-                asan_opts.push_str("detect_odr_violation=0");
-                cmd.env("ASAN_OPTIONS", asan_opts);
+    /// Add a new fuzz target script at a given path in `fuzz_targets`
+    fn create_target_template(&self, target: &str, template: TargetTemplate) -> Result<()> {
+        if template == TargetTemplate::Default && self.root_is_no_std()? {
+            let crate_name = self.root_project_name()?.replace('-', "_");
+            if self.root_has_panic_handler()? {
+                eprintln!(
+                    "warning: `{}` defines a `#[panic_handler]`, which conflicts with the one \
+                     `std` provides to fuzz targets; leave it out with `#[cfg(not(fuzzing))]`",
+                    crate_name
+                );
             }
+            return self.create_target(target, no_std_target_template!(crate_name));
+        }
+        self.create_target(
+            target,
+            match template {
+                TargetTemplate::Default => target_template!(),
+                TargetTemplate::Arbitrary => arbitrary_target_template!(),
+                TargetTemplate::Roundtrip => roundtrip_target_template!(),
+                TargetTemplate::Grammar => grammar_target_template!(),
+            },
+        )
+    }
 
-            Sanitizer::Thread => {
-                let mut tsan_opts = env::var("TSAN_OPTIONS").unwrap_or_default();
-                if !tsan_opts.is_empty() {
-                    tsan_opts.push(':');
-                }
-                tsan_opts.push_str("report_signal_unsafe=0");
-                cmd.env("TSAN_OPTIONS", tsan_opts);
+    /// Add a new fuzz target that runs the fuzzed crate's binary `bin` on each
+    /// input, and name `bin` in its config, so that it's built with it.
+    fn create_bin_target(&self, target: &str, bin: &str, input: options::BinInput) -> Result<()> {
+        let name = nested_target_name(target)?;
+        let bins = self.root_bins()?;
+        if !bins.iter().any(|b| b == bin) {
+            if bins.is_empty() {
+                bail!("the fuzzed crate has no binaries");
             }
-
-            _ => {}
+            bail!(
+                "the fuzzed crate has no binary named `{}`; it has {}",
+                bin,
+                bins.iter()
+                    .map(|b| format!("`{}`", b))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+        if self.config.targets.contains_key(&name) {
+            bail!(
+                "{} already has settings for `{}`",
+                self.config_path().display(),
+                name
+            );
         }
+        match input {
+            options::BinInput::Stdin => self.create_target(target, bin_stdin_target_template!(bin)),
+            options::BinInput::File => self.create_target(target, bin_file_target_template!(bin)),
+        }?;
 
-        Ok(cmd)
+        let config_path = self.config_path();
+        let mut config = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&config_path)
+            .with_context(|| format!("failed to open {}", config_path.display()))?;
+        write!(config, "\n[targets.{}]\nbin = \"{}\"\n", name, bin)
+            .with_context(|| format!("failed to write {}", config_path.display()))?;
+        Ok(())
     }
 
-    fn cargo_run(&self, build: &options::BuildOptions, fuzz_target: &str) -> Result<Command> {
-        let mut cmd = self.cargo("run", build)?;
-        cmd.arg("--bin").arg(fuzz_target);
-
-        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
-        artifact_arg.push(self.artifacts_for(&fuzz_target)?);
-        cmd.arg("--").arg(artifact_arg);
-
-        Ok(cmd)
+    /// The names of the fuzzed crate's binaries.
+    fn root_bins(&self) -> Result<Vec<String>> {
+        let manifest = self.root_project.join("Cargo.toml");
+        let mut metadata = utils::toolchain_command("cargo");
+        metadata
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--no-deps")
+            .arg("--manifest-path")
+            .arg(&manifest);
+        let output = metadata
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", metadata))?;
+        if !output.status.success() {
+            bail!(
+                "failed to read the fuzzed crate's metadata: {:?}\n\n{}",
+                metadata,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .context("failed to parse the fuzzed crate's metadata")?;
+        let manifest = fs::canonicalize(&manifest).unwrap_or(manifest);
+        let package = metadata["packages"].as_array().and_then(|packages| {
+            packages.iter().find(|package| {
+                package["manifest_path"]
+                    .as_str()
+                    .and_then(|path| fs::canonicalize(path).ok())
+                    == Some(manifest.clone())
+            })
+        });
+        let targets = package
+            .and_then(|package| package["targets"].as_array())
+            .cloned()
+            .unwrap_or_default();
+        Ok(targets
+            .iter()
+            .filter(|target| {
+                target["kind"]
+                    .as_array()
+                    .is_some_and(|kinds| kinds.iter().any(|kind| kind == "bin"))
+            })
+            .filter_map(|target| target["name"].as_str().map(String::from))
+            .collect())
     }
 
-    pub fn exec_build(
-        &self,
-        build: &options::BuildOptions,
-        fuzz_target: Option<&str>,
-    ) -> Result<()> {
-        let mut cmd = self.cargo("build", build)?;
-
-        if let Some(fuzz_target) = fuzz_target {
-            cmd.arg("--bin").arg(fuzz_target);
+    /// Add a new fuzz target that fuzzes values of the type `ty`, along with
+    /// an example that prints the value an input decodes to.
+    ///
+    /// If the fuzzed crate only implements `Arbitrary` with its `arbitrary`
+    /// feature enabled, the fuzz crate's dependency on it enables that
+    /// feature.
+    fn create_arbitrary_target(&self, target: &str, ty: &str) -> Result<()> {
+        let name = nested_target_name(target)?;
+        let crate_name = self.root_project_name()?;
+        let ty = if ty.contains("::") {
+            ty.to_string()
         } else {
-            cmd.arg("--bins");
-        }
+            format!("{}::{}", crate_name.replace('-', "_"), ty)
+        };
 
-        let status = cmd
-            .status()
-            .with_context(|| format!("failed to execute: {:?}", cmd))?;
-        if !status.success() {
-            bail!("failed to build fuzz script: {:?}", cmd);
+        let example_path = self
+            .path()
+            .join("examples")
+            .join(format!("{}_debug", name))
+            .with_extension("rs");
+        if example_path.exists() {
+            bail!("{} already exists", example_path.display());
         }
 
-        Ok(())
-    }
+        self.enable_root_arbitrary_feature()?;
+        self.create_target(target, typed_target_template!(name, ty))?;
 
-    fn get_artifacts_since(
-        &self,
-        target: &str,
-        since: &time::SystemTime,
-    ) -> Result<HashSet<PathBuf>> {
-        let mut artifacts = HashSet::new();
+        fs::create_dir_all(example_path.parent().unwrap())
+            .context("ensuring that `examples` directory exists failed")?;
+        let mut example = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&example_path)
+            .with_context(|| format!("could not create example file at {:?}", example_path))?;
+        example.write_fmt(arbitrary_debug_template!(name, ty))?;
 
-        let artifacts_dir = self.artifacts_for(target)?;
+        eprintln!(
+            "Print the `{}` value that an input decodes to with:\n\n\t\
+             cargo run --manifest-path {} --example {}_debug -- <input>\n",
+            ty,
+            strip_current_dir_prefix(&self.manifest_path()).display(),
+            name
+        );
+        Ok(())
+    }
 
-        for entry in fs::read_dir(&artifacts_dir).with_context(|| {
-            format!(
-                "failed to read directory entries of {}",
-                artifacts_dir.display()
+    /// If the fuzzed crate only implements `Arbitrary` with its `arbitrary`
+    /// feature enabled, enable that feature in the fuzz crate's dependency on
+    /// it.
+    fn enable_root_arbitrary_feature(&self) -> Result<()> {
+        if !self.root_has_arbitrary_feature()? {
+            return Ok(());
+        }
+        let dependency = self.root_dependency_name()?.ok_or_else(|| {
+            anyhow!(
+                "{} has no `path = \"..\"` dependency on `{}`",
+                self.manifest_path().display(),
+                self.root_project_name().unwrap_or_default()
             )
-        })? {
-            let entry = entry.with_context(|| {
-                format!(
-                    "failed to read directory entry inside {}",
-                    artifacts_dir.display()
-                )
-            })?;
+        })?;
+        add_to_manifest_list(
+            &self.manifest_path(),
+            &format!("dependencies.{}", dependency),
+            "features",
+            "arbitrary",
+        )
+    }
 
-            let metadata = entry
-                .metadata()
-                .context("failed to read artifact metadata")?;
-            let modified = metadata
-                .modified()
-                .context("failed to get artifact modification time")?;
-            if !metadata.is_file() || modified <= *since {
-                continue;
-            }
+    /// Add a new fuzz target that calls the function at `path` in the fuzzed
+    /// crate, generated from the function's signature.
+    fn create_function_target(&self, target: &str, path: &str, reject_invalid: bool) -> Result<()> {
+        let crate_name = self.root_project_name()?.replace('-', "_");
+        let lib = self.root_lib_path()?;
+        if !lib.is_file() {
+            bail!("`{}` has no library for a fuzz target to call", crate_name);
+        }
+        let function = signature::find(&lib, &crate_name, path)?;
+        let harness = signature::harness(&function, reject_invalid)?;
+        if harness.arbitrary {
+            self.enable_root_arbitrary_feature()?;
+        }
+        self.create_target(target, function_target_template!(function.path, harness))
+    }
 
-            artifacts.insert(entry.path());
+    /// Add a new fuzz target that calls the `extern "C"` function the fuzzed
+    /// crate exports as `symbol` through its C ABI.
+    fn create_ffi_target(&self, target: &str, symbol: &str) -> Result<()> {
+        let crate_name = self.root_project_name()?.replace('-', "_");
+        let lib = self.root_lib_path()?;
+        if !lib.is_file() {
+            bail!("`{}` has no library for a fuzz target to call", crate_name);
         }
+        let export = exports::find(&lib, symbol)?;
+        let harness = exports::harness(&export)?;
+        self.create_target(
+            target,
+            ffi_target_template!(crate_name, export.symbol, harness),
+        )
+    }
 
-        Ok(artifacts)
+    /// Add a new fuzz target that generates its inputs from the grammar in
+    /// the file at `path`.
+    fn create_grammar_target(&self, target: &str, path: &Path) -> Result<()> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let grammar = grammar::parse(&source)
+            .with_context(|| format!("could not parse the grammar in {}", path.display()))?;
+        self.create_target(
+            target,
+            grammar_file_target_template!(
+                strip_current_dir_prefix(path).display(),
+                grammar.start(),
+                grammar.generator()
+            ),
+        )
     }
 
-    fn run_fuzz_target_debug_formatter(
-        &self,
-        build: &BuildOptions,
-        target: &str,
-        artifact: &Path,
-    ) -> Result<String> {
-        let debug_output = tempfile::NamedTempFile::new().context("failed to create temp file")?;
+    /// Add a new fuzz target that compares two implementations, and, given a
+    /// `reference`, a dependency on the implementation to compare against.
+    fn create_differential_target(&self, target: &str, reference: Option<&str>) -> Result<()> {
+        let crate_name = self.root_project_name()?.replace('-', "_");
+        let implementations = match reference {
+            Some(spec) => {
+                self.add_reference_dependency(spec)?;
+                format!("`{}` and `reference`", crate_name)
+            }
+            None => format!("`{}` and another implementation", crate_name),
+        };
+        // The fuzz crate's library has the glue for reporting divergences,
+        // unless it's from before there was one.
+        let shared_glue = match self.fuzz_lib()? {
+            Some((lib, path)) => fs::read_to_string(path)
+                .is_ok_and(|source| source.contains("pub fn report_divergence"))
+                .then_some(lib),
+            None => None,
+        };
+        let (mut uses, glue) = match shared_glue {
+            Some(lib) => (
+                vec![format!("use {}::report_divergence;", lib)],
+                String::new(),
+            ),
+            None => (
+                vec![
+                    "use std::fmt::Debug;".to_string(),
+                    "use std::{env, fs, path::Path};".to_string(),
+                ],
+                divergence_glue_template!("").to_string(),
+            ),
+        };
+        uses.push("use libfuzzer_sys::fuzz_target;".to_string());
+        uses.sort();
+        let uses: String = uses.iter().map(|line| format!("{}\n", line)).collect();
+        self.create_target(
+            target,
+            differential_target_template!(implementations, uses, glue),
+        )?;
+        eprintln!(
+            "Divergences are reported with the failing input, in \
+             fuzz/artifacts/{}/divergence-<hash>.json\n",
+            nested_target_name(target)?
+        );
+        Ok(())
+    }
 
-        let mut cmd = self.cargo_run(&build, &target)?;
-        cmd.stdin(Stdio::null());
-        cmd.env("RUST_LIBFUZZER_DEBUG_PATH", &debug_output.path());
-        cmd.arg(&artifact);
+    /// The name and the root source file of the library that the fuzz
+    /// crate's fuzz targets share, if it has one.
+    fn fuzz_lib(&self) -> Result<Option<(String, PathBuf)>> {
+        let manifest = self.manifest()?;
+        let lib = manifest.get("lib");
+        let path = lib
+            .and_then(|lib| lib.get("path"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("src/lib.rs");
+        let path = self.path().join(path);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let name = lib
+            .and_then(|lib| lib.get("name"))
+            .or_else(|| manifest.get("package").and_then(|p| p.get("name")))
+            .and_then(toml::Value::as_str)
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} has no package name",
+                    strip_current_dir_prefix(&self.manifest_path()).display()
+                )
+            })?;
+        Ok(Some((name.replace('-', "_"), path)))
+    }
 
-        let output = cmd
-            .output()
-            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+    /// Write the root of the library that the fuzz targets share, re-exporting
+    /// the fuzzed crate's library if it has one.
+    fn create_fuzz_lib(&self) -> Result<PathBuf> {
+        let manifest = self.manifest()?;
+        let lib = manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or_default()
+            .replace('-', "_");
+        let reexport = if self.root_lib_path()?.is_file() {
+            format!(
+                "\npub use {};\n",
+                self.root_project_name()?.replace('-', "_")
+            )
+        } else {
+            String::new()
+        };
+        let path = self.path().join("src").join("lib.rs");
+        fs::create_dir_all(path.parent().unwrap())
+            .with_context(|| format!("failed to create {}", path.parent().unwrap().display()))?;
+        fs::write(&path, fuzz_lib_template!(lib, reexport).to_string())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
 
-        if !output.status.success() {
+    /// Give a fuzz crate from before `cargo fuzz init` made one the library
+    /// that its fuzz targets share, for `cargo fuzz migrate`.
+    pub fn exec_migrate(&self) -> Result<()> {
+        if let Some((lib, path)) = self.fuzz_lib()? {
+            eprintln!(
+                "The fuzz targets already share a library, `{}`, in {}",
+                lib,
+                strip_current_dir_prefix(&path).display()
+            );
+            return Ok(());
+        }
+        if self.manifest()?.get("lib").is_some() {
             bail!(
-                "Fuzz target '{target}' exited with failure when attemping to \
-                 debug formatting an interesting input that we discovered!\n\n\
-                 Artifact: {artifact}\n\n\
-                 Command: {cmd:?}\n\n\
-                 Status: {status}\n\n\
-                 === stdout ===\n\
-                 {stdout}\n\n\
-                 === stderr ===\n\
-                 {stderr}",
-                target = target,
-                status = output.status,
-                cmd = cmd,
-                artifact = artifact.display(),
-                stdout = String::from_utf8_lossy(&output.stdout),
-                stderr = String::from_utf8_lossy(&output.stderr),
+                "the `[lib]` in {} has no source file",
+                strip_current_dir_prefix(&self.manifest_path()).display()
             );
         }
-
-        let debug = fs::read_to_string(&debug_output).context("failed to read temp file")?;
-        Ok(debug)
+        let manifest_path = self.manifest_path();
+        let mut manifest = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+        // Keep the `[lib]` ahead of the fuzz targets, as `init` does.
+        let bins = manifest
+            .split_inclusive('\n')
+            .take_while(|line| !line.trim_start().starts_with("[[bin]]"))
+            .map(str::len)
+            .sum::<usize>();
+        let lib = toml_lib_template!().to_string();
+        if bins < manifest.len() {
+            manifest.insert_str(bins, &format!("{}\n", lib.trim_start()));
+        } else {
+            manifest.push_str(&lib);
+        }
+        let path = self.create_fuzz_lib()?;
+        fs::write(&manifest_path, manifest)
+            .with_context(|| format!("failed to write {}", manifest_path.display()))?;
+        let (lib, _) = self
+            .fuzz_lib()?
+            .ok_or_else(|| anyhow!("failed to add the fuzz crate's library"))?;
+        eprintln!(
+            "Added {}, a library that the fuzz targets can share as `{}`; it's built once \
+             rather than into each fuzz target. The fuzz targets themselves are unchanged.",
+            strip_current_dir_prefix(&path).display(),
+            lib
+        );
+        Ok(())
     }
 
-    /// Prints the debug output of an input test case
-    pub fn debug_fmt_input(&self, debugfmt: &options::Fmt) -> Result<()> {
-        if !debugfmt.input.exists() {
+    /// Add a dependency named `reference` to the fuzz crate: the crate with a
+    /// given name and version, or the fuzzed crate from a git repository.
+    fn add_reference_dependency(&self, spec: &str) -> Result<()> {
+        let manifest = self.manifest()?;
+        if manifest
+            .get("dependencies")
+            .and_then(|d| d.get("reference"))
+            .is_some()
+        {
             bail!(
-                "Input test case does not exist: {}",
-                debugfmt.input.display()
+                "{} already has a dependency named `reference`",
+                self.manifest_path().display()
             );
         }
+        let dependency = if spec.contains("://") {
+            let (url, rev) = match spec.split_once('#') {
+                Some((url, rev)) => (url, Some(rev)),
+                None => (spec, None),
+            };
+            let mut dependency = format!(
+                "package = \"{}\"\ngit = \"{}\"\n",
+                self.root_project_name()?,
+                url
+            );
+            if let Some(rev) = rev {
+                dependency.push_str(&format!("rev = \"{}\"\n", rev));
+            }
+            dependency
+        } else {
+            let (name, version) = spec.split_once('@').unwrap_or((spec, "*"));
+            if name.is_empty() || version.is_empty() {
+                bail!("invalid reference: {}", spec);
+            }
+            format!("package = \"{}\"\nversion = \"{}\"\n", name, version)
+        };
 
-        let debug = self
-            .run_fuzz_target_debug_formatter(&debugfmt.build, &debugfmt.target, &debugfmt.input)
-            .with_context(|| {
-                format!(
-                    "failed to run `cargo fuzz fmt` on input: {}",
-                    debugfmt.input.display()
-                )
-            })?;
+        let manifest_path = self.manifest_path();
+        let contents = fs::read_to_string(&manifest_path)
+            .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+        // Keep the dependencies above the fuzz targets' `[[bin]]` tables.
+        let insert_at = contents.find("\n[[bin]]").map_or(contents.len(), |i| i + 1);
+        let mut updated = contents[..insert_at].to_string();
+        if !updated.ends_with("\n\n") {
+            updated.push('\n');
+        }
+        updated.push_str("[dependencies.reference]\n");
+        updated.push_str(&dependency);
+        updated.push('\n');
+        updated.push_str(&contents[insert_at..]);
+        fs::write(&manifest_path, updated)
+            .with_context(|| format!("failed to write {}", manifest_path.display()))
+    }
 
-        eprintln!("\nOutput of `std::fmt::Debug`:\n");
-        for l in debug.lines() {
-            eprintln!("{}", l);
+    /// Write a new fuzz target script, and add it to the fuzz crate's manifest.
+    fn create_target(&self, target: &str, script: std::fmt::Arguments) -> Result<()> {
+        let name = nested_target_name(target)?;
+        if self.targets.contains(&name) {
+            bail!("a fuzz target named `{}` already exists", name);
         }
+        let mut target_path = self.fuzz_targets_dir().join(target);
+        target_path.set_extension("rs");
 
-        Ok(())
-    }
+        // If the user manually created a fuzz project, but hasn't created any
+        // targets yet, the `fuzz_targets` directory might not exist yet,
+        // despite a `fuzz/Cargo.toml` manifest with the `metadata.cargo-fuzz`
+        // key present. Make sure it does exist, along with the subdirectory
+        // of a nested target.
+        fs::create_dir_all(target_path.parent().unwrap())
+            .context("ensuring that `fuzz_targets` directory exists failed")?;
 
-    /// Fuzz a given fuzz target
-    pub fn exec_fuzz(&self, run: &options::Run) -> Result<()> {
-        self.exec_build(&run.build, Some(&run.target))?;
-        let mut cmd = self.cargo_run(&run.build, &run.target)?;
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&target_path)
+            .with_context(|| format!("could not create target script file at {:?}", target_path))?;
+        file.write_fmt(script)?;
 
-        for arg in &run.args {
-            cmd.arg(arg);
+        let mut cargo = fs::OpenOptions::new()
+            .append(true)
+            .open(self.manifest_path())?;
+        Ok(cargo.write_fmt(toml_bin_template!(name, target))?)
+    }
+
+    /// Remove a fuzz target's source, its `[[bin]]` in the fuzz crate's
+    /// manifest, its settings in `fuzz.toml` and its regression tests, and
+    /// with `--data`, everything else cargo fuzz keeps for it.
+    pub fn exec_rm(&self, rm: &options::Rm) -> Result<()> {
+        let target = &rm.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
         }
+        self.edit_manifest(target, |table| is_bin_named(table, target).then(Vec::new))?;
+        self.edit_config(|table| target_table(table, target).map(|_| Vec::new()))?;
 
-        if !run.corpus.is_empty() {
-            for corpus in &run.corpus {
-                cmd.arg(corpus);
+        let source = self.target_path(target);
+        let mut removed = vec![source.clone(), self.regression_tests_path(target)];
+        if rm.data {
+            removed.extend(self.target_data(target).into_iter().flatten());
+        }
+        for path in &removed {
+            if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else if path.is_file() {
+                fs::remove_file(path)
+            } else {
+                continue;
             }
-        } else {
-            cmd.arg(self.corpus_for(&run.target)?);
+            .with_context(|| format!("failed to remove {}", path.display()))?;
+            eprintln!("Removed {}", strip_current_dir_prefix(path).display());
         }
+        remove_empty_dirs(source.parent().unwrap(), &self.fuzz_targets_dir());
+        self.refresh_corpus_replay_tests()?;
+        eprintln!("Removed fuzz target `{}`", target);
+        Ok(())
+    }
 
-        if run.jobs != 1 {
-            cmd.arg(format!("-fork={}", run.jobs));
+    /// Rename a fuzz target, along with its source, its `[[bin]]`, its
+    /// settings in `fuzz.toml`, its regression tests and everything else
+    /// cargo fuzz keeps for it.
+    ///
+    /// Like with `cargo fuzz add`, a new name given as a path like
+    /// `parser/headers` moves the source into that subdirectory of
+    /// `fuzz_targets`, and names the target `parser_headers`.
+    pub fn exec_mv(&self, mv: &options::Mv) -> Result<()> {
+        let old = &mv.old;
+        if !self.targets.contains(old) {
+            bail!("no fuzz target named `{}`", old);
         }
-
-        // When libfuzzer finds failing inputs, those inputs will end up in the
-        // artifacts directory. To easily filter old artifacts from new ones,
-        // get the current time, and then later we only consider files modified
-        // after now.
-        let before_fuzzing = time::SystemTime::now();
-
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
-        let status = child
-            .wait()
-            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
-        if status.success() {
-            return Ok(());
+        let new = nested_target_name(&mv.new)?;
+        if self.targets.contains(&new) {
+            bail!("a fuzz target named `{}` already exists", new);
+        }
+        if self.config.targets.contains_key(&new) {
+            bail!(
+                "{} already has settings for `{}`",
+                self.config_path().display(),
+                new
+            );
         }
 
-        // Get and print the `Debug` formatting of any new artifacts, along with
-        // tips about how to reproduce failures and/or minimize test cases.
-
-        let new_artifacts = self.get_artifacts_since(&run.target, &before_fuzzing)?;
-
-        for artifact in new_artifacts {
-            // To make the artifact a little easier to read, strip the current
-            // directory prefix when possible.
-            let artifact = strip_current_dir_prefix(&artifact);
-
-            eprintln!("\n{:─<80}", "");
-            eprintln!("\nFailing input:\n\n\t{}\n", artifact.display());
+        let old_source = self.target_path(old);
+        let new_source = self.fuzz_targets_dir().join(&mv.new).with_extension("rs");
+        let mut moves = vec![
+            (old_source.clone(), new_source.clone()),
+            (
+                self.regression_tests_path(old),
+                self.regression_tests_path(&new),
+            ),
+        ];
+        // Each kind of data moves to where the new name keeps it, except a
+        // corpus that `fuzz.toml` puts elsewhere, which stays.
+        moves.extend(
+            self.target_data(old)
+                .into_iter()
+                .zip(self.target_data(&new))
+                .filter_map(|(from, to)| Some((from?, to?))),
+        );
+        moves.retain(|(from, _)| from.exists());
+        if let Some((_, to)) = moves.iter().find(|(_, to)| to.exists()) {
+            bail!("{} already exists", to.display());
+        }
 
-            // Note: ignore errors when running the debug formatter. This most
-            // likely just means that we're dealing with a fuzz target that uses
-            // an older version of the libfuzzer crate, and doesn't support
-            // `RUST_LIBFUZZER_DEBUG_PATH`.
-            if let Ok(debug) =
-                self.run_fuzz_target_debug_formatter(&run.build, &run.target, artifact)
-            {
-                eprintln!("Output of `std::fmt::Debug`:\n");
-                for l in debug.lines() {
-                    eprintln!("\t{}", l);
+        let new_path = new_source
+            .strip_prefix(self.path())
+            .unwrap_or(&new_source)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        self.edit_manifest(old, |table| {
+            if !is_bin_named(table, old) {
+                return None;
+            }
+            let mut renamed = Vec::new();
+            for line in table {
+                match toml_key_value(line) {
+                    Some(("name", _)) => {
+                        renamed.push(format!("name = \"{}\"", new));
+                        renamed.push(format!("path = \"{}\"", new_path));
+                    }
+                    Some(("path", _)) => {}
+                    _ => renamed.push(line.to_string()),
                 }
-                eprintln!();
             }
+            Some(renamed)
+        })?;
+        self.edit_config(|table| {
+            let rest = target_table(table, old)?;
+            let mut renamed = vec![format!("[targets.{}{}", new, rest)];
+            renamed.extend(table[1..].iter().map(|line| line.to_string()));
+            Some(renamed)
+        })?;
 
+        for (from, to) in &moves {
+            fs::create_dir_all(to.parent().unwrap())
+                .with_context(|| format!("could not make a directory at {}", to.display()))?;
+            fs::rename(from, to).with_context(|| {
+                format!("failed to move {} to {}", from.display(), to.display())
+            })?;
             eprintln!(
-                "Reproduce with:\n\n\tcargo fuzz run {target} {artifact}\n",
-                target = &run.target,
-                artifact = artifact.display()
-            );
-            eprintln!(
-                "Minimize test case with:\n\n\tcargo fuzz tmin {target} {artifact}\n",
-                target = &run.target,
-                artifact = artifact.display()
+                "Moved {} to {}",
+                strip_current_dir_prefix(from).display(),
+                strip_current_dir_prefix(to).display()
             );
         }
+        remove_empty_dirs(old_source.parent().unwrap(), &self.fuzz_targets_dir());
 
-        eprintln!("{:─<80}\n", "");
-        bail!("Fuzz target exited with {}", status)
+        // The regression tests run the target's binary by name.
+        let tests_path = self.regression_tests_path(&new);
+        if tests_path.is_file() {
+            let tests = fs::read_to_string(&tests_path)
+                .with_context(|| format!("failed to read {}", tests_path.display()))?
+                .replace(
+                    &format!("CARGO_BIN_EXE_{}\"", old),
+                    &format!("CARGO_BIN_EXE_{}\"", new),
+                )
+                .replace(&format!("`{}`", old), &format!("`{}`", new))
+                .replace(&format!("\"{}-{{}}", old), &format!("\"{}-{{}}", new));
+            fs::write(&tests_path, tests)
+                .with_context(|| format!("failed to write {}", tests_path.display()))?;
+        }
+        self.refresh_corpus_replay_tests()?;
+        eprintln!("Renamed fuzz target `{}` to `{}`", old, new);
+        Ok(())
     }
 
-    pub fn exec_tmin(&self, tmin: &options::Tmin) -> Result<()> {
-        self.exec_build(&tmin.build, Some(&tmin.target))?;
-        let mut cmd = self.cargo_run(&tmin.build, &tmin.target)?;
-        cmd.arg("-minimize_crash=1")
-            .arg(format!("-runs={}", tmin.runs))
-            .arg(&tmin.test_case);
-
-        let before_tmin = time::SystemTime::now();
-
-        let mut child = cmd
-            .spawn()
-            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
-        let status = child
-            .wait()
-            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
-        if !status.success() {
-            eprintln!("\n{:─<80}\n", "");
-            return Err(anyhow!("Command `{:?}` exited with {}", cmd, status))
-                .with_context(|| {
-                    "Test case minimization failed.\n\
-                     \n\
-                     Usually this isn't a hard error, and just means that libfuzzer\n\
-                     doesn't know how to minimize the test case any further while\n\
-                     still reproducing the original crash.\n\
-                     \n\
-                     See the logs above for details."
-                })
-                .into();
+    /// Rewrite the tables of the fuzz crate's manifest that `edit` returns
+    /// replacements for, failing if there are none.
+    fn edit_manifest(
+        &self,
+        target: &str,
+        edit: impl FnMut(&[&str]) -> Option<Vec<String>>,
+    ) -> Result<()> {
+        let path = self.manifest_path();
+        let manifest = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let (manifest, edited) = edit_toml_tables(&manifest, edit);
+        if edited == 0 {
+            bail!(
+                "could not find the `[[bin]]` of `{}` in {}",
+                target,
+                path.display()
+            );
         }
+        fs::write(&path, manifest).with_context(|| format!("failed to write {}", path.display()))
+    }
 
-        // Find and display the most recently modified artifact, which is
-        // presumably the result of minification. Yeah, this is a little hacky,
-        // but it seems to work. I don't want to parse libfuzzer's stderr output
-        // and hope it never changes.
-        let minimized_artifact = self
-            .get_artifacts_since(&tmin.target, &before_tmin)?
-            .into_iter()
-            .max_by_key(|a| {
-                a.metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(time::SystemTime::UNIX_EPOCH)
-            });
+    /// Rewrite the tables of `fuzz.toml` that `edit` returns replacements
+    /// for, if there are any.
+    fn edit_config(&self, edit: impl FnMut(&[&str]) -> Option<Vec<String>>) -> Result<()> {
+        let path = self.config_path();
+        if !path.is_file() {
+            return Ok(());
+        }
+        let config = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        match edit_toml_tables(&config, edit) {
+            (_, 0) => Ok(()),
+            (config, _) => fs::write(&path, config)
+                .with_context(|| format!("failed to write {}", path.display())),
+        }
+    }
 
-        if let Some(artifact) = minimized_artifact {
-            let artifact = strip_current_dir_prefix(&artifact);
+    /// Everything cargo fuzz keeps for a target besides its source and
+    /// regression tests, where it keeps it by default, in the same order for
+    /// every target. A corpus that `fuzz.toml` puts elsewhere is `None`, since
+    /// other targets may share it.
+    fn target_data(&self, target: &str) -> Vec<Option<PathBuf>> {
+        let dir = |name: &str| self.path().join(name).join(target);
+        let corpus =
+            Some(dir("corpus")).filter(|_| self.config.target(Some(target)).corpus.is_none());
+        let mut data = vec![corpus];
+        data.extend(
+            [
+                dir("artifacts"),
+                dir("coverage"),
+                self.trace_dir(target),
+                self.focus_dir(target),
+                dir("slow"),
+                self.slow_timings_path(target),
+                dir("logs"),
+                self.stats_path(target),
+                self.provenance_path(target),
+                self.bench_path(target),
+                self.dict_path(target),
+                dir("dictionaries"),
+                dir("miri"),
+                dir("profile"),
+            ]
+            .map(Some),
+        );
+        data
+    }
 
-            eprintln!("\n{:─<80}\n", "");
-            eprintln!("Minimized artifact:\n\n\t{}\n", artifact.display());
+    /// Regenerate `fuzz/tests/corpus_replay.rs`, if there is one, for the
+    /// fuzz targets in the manifest as it is now.
+    fn refresh_corpus_replay_tests(&self) -> Result<()> {
+        let tests_path = self
+            .path()
+            .join("tests")
+            .join(CORPUS_REPLAY_TESTS)
+            .with_extension("rs");
+        if tests_path.is_file() {
+            FuzzProject::open(self.root_project.clone())?.write_corpus_replay_tests()?;
+        }
+        Ok(())
+    }
 
-            // Note: ignore errors when running the debug formatter. This most
-            // likely just means that we're dealing with a fuzz target that uses
-            // an older version of the libfuzzer crate, and doesn't support
-            // `RUST_LIBFUZZER_DEBUG_PATH`.
-            if let Ok(debug) =
-                self.run_fuzz_target_debug_formatter(&tmin.build, &tmin.target, artifact)
-            {
-                eprintln!("Output of `std::fmt::Debug`:\n");
-                for l in debug.lines() {
-                    eprintln!("\t{}", l);
+    /// The build options for a fuzz target (or for all targets if `None`),
+    /// with anything not given on the command line filled in from the
+    /// project's configuration file, and the target's `required-features`
+    /// enabled on top of any other features.
+    fn build_options_for(&self, build: &BuildOptions, target: Option<&str>) -> BuildOptions {
+        let mut build = build.clone();
+        self.config.target(target).apply(&mut build);
+        if build.toolchain.is_none() {
+            build.toolchain = self.config.toolchain.clone();
+        }
+        // The archive on the command line is relative to the current
+        // directory, and the one in fuzz.toml to the fuzz directory.
+        if build.libfuzzer_archive.is_none() {
+            build.libfuzzer_archive = self
+                .config
+                .libfuzzer_archive
+                .as_ref()
+                .map(|archive| self.path().join(archive));
+        }
+        // `--profile dev` and `--profile release` are the same as `--dev` and
+        // `--release`, so only custom profiles are left in `profile`.
+        match build.profile.as_deref() {
+            Some("dev") => {
+                build.dev = true;
+                build.profile = None;
+            }
+            Some("release") => {
+                build.release = true;
+                build.profile = None;
+            }
+            _ => {}
+        }
+        let required = target.and_then(|t| self.required_features.get(t));
+        if let Some(required) = required.filter(|_| !build.all_features) {
+            let mut features: Vec<&str> = build
+                .features
+                .iter()
+                .flat_map(|f| f.split([',', ' ']))
+                .filter(|f| !f.is_empty())
+                .collect();
+            for feature in required {
+                if !features.contains(&feature.as_str()) {
+                    features.push(feature);
                 }
-                eprintln!();
             }
-
-            eprintln!(
-                "Reproduce with:\n\n\tcargo fuzz run {target} {artifact}\n",
-                target = &tmin.target,
-                artifact = artifact.display()
-            );
+            build.features = Some(features.join(","));
         }
-
-        Ok(())
+        build
     }
 
-    pub fn exec_cmin(&self, cmin: &options::Cmin) -> Result<()> {
-        self.exec_build(&cmin.build, Some(&cmin.target))?;
-        let mut cmd = self.cargo_run(&cmin.build, &cmin.target)?;
-
-        let corpus = if let Some(corpus) = cmin.corpus.clone() {
-            corpus
-        } else {
-            self.corpus_for(&cmin.target)?
-        };
-        let corpus = corpus
-            .to_str()
-            .ok_or_else(|| anyhow!("corpus must be valid unicode"))?
-            .to_owned();
+    fn cargo(
+        &self,
+        subcommand: &str,
+        build: &BuildOptions,
+        target: Option<&str>,
+    ) -> Result<Command> {
+        // Each engine, sanitizer and kind of instrumentation builds into a
+        // directory of its own, so that they don't invalidate each other.
+        let target_dir = self.target_dir(&self.build_options_for(build, target));
+        self.cargo_in(
+            &self.manifest_path(),
+            &target_dir,
+            subcommand,
+            build,
+            target,
+        )
+    }
 
-        let tmp = tempfile::TempDir::new_in(self.path())?;
-        let tmp_corpus = tmp.path().join("corpus");
-        fs::create_dir(&tmp_corpus)?;
+    /// Like `cargo`, but for the crate at `manifest`, built into `target_dir`.
+    fn cargo_in(
+        &self,
+        manifest: &Path,
+        target_dir: &Path,
+        subcommand: &str,
+        build: &BuildOptions,
+        target: Option<&str>,
+    ) -> Result<Command> {
+        let build = &self.build_options_for(build, target);
+        let sanitizer = single_sanitizer(build)?;
+        if let Some(toolchain) = &build.toolchain {
+            use_toolchain(toolchain, build, sanitizer)?;
+        }
+        let mut cmd = utils::toolchain_command("cargo");
+        cmd.arg(subcommand)
+            .arg("--manifest-path")
+            .arg(manifest)
+            // --target=<TARGET> won't pass rustflags to build scripts
+            .arg("--target")
+            .arg(&build.triple)
+            .arg("--target-dir")
+            .arg(target_dir);
+        // we default to release mode unless debug mode or a custom profile is
+        // explicitly requested
+        match &build.profile {
+            Some(profile) => {
+                cmd.arg("--profile").arg(profile);
+            }
+            None if !build.dev => {
+                cmd.arg("--release");
+            }
+            None => {}
+        }
+        // Unoptimized code fuzzes too slowly to find much, so dev builds get
+        // light optimizations unless the dev profile asks for something else.
+        if build.dev
+            && env::var_os("CARGO_PROFILE_DEV_OPT_LEVEL").is_none()
+            && self.profile_setting("dev", "opt-level")?.is_none()
+        {
+            cmd.env("CARGO_PROFILE_DEV_OPT_LEVEL", "1");
+        }
+        if build.verbose {
+            cmd.arg("--verbose");
+        }
+        if build.no_default_features {
+            cmd.arg("--no-default-features");
+        }
+        if build.all_features {
+            cmd.arg("--all-features");
+        }
+        if let Some(ref features) = build.features {
+            cmd.arg("--features").arg(features);
+        }
+        for flag in &build.unstable_flags {
+            cmd.arg("-Z").arg(flag);
+        }
+        // The standard library has to be instrumented too, or the memory
+        // sanitizer reports false positives in every program, and the thread
+        // sanitizer misses races in its synchronization primitives.
+        if let Sanitizer::Memory | Sanitizer::Thread = sanitizer {
+            cmd.arg("-Z").arg("build-std");
+        }
 
-        cmd.arg("-merge=1").arg(&tmp_corpus).arg(&corpus);
+        // Cargo ignores `RUSTFLAGS` when `CARGO_ENCODED_RUSTFLAGS` is set, and
+        // the encoded flags keep paths with spaces in them intact.
+        let rustflags: Vec<String> = self
+            .rustflag_layers(build, sanitizer)?
+            .into_iter()
+            .flat_map(|(_, flags)| flags)
+            .collect();
+        cmd.env("CARGO_ENCODED_RUSTFLAGS", rustflags.join("\x1f"))
+            .env_remove("RUSTFLAGS");
+        // Rustflags can't differ between crates, so `cargo fuzz` wraps rustc
+        // to drop the coverage instrumentation from the crates not listed.
+        if let Some(crates) = build.coverage_crates.as_ref().filter(|_| !build.coverage) {
+            let exe = env::current_exe().context("failed to find the cargo-fuzz executable")?;
+            cmd.env(
+                rustc_wrapper::CRATES_ENV,
+                rustc_wrapper::parse_crates(crates).join(","),
+            )
+            .env(
+                rustc_wrapper::INNER_WRAPPER_ENV,
+                env::var_os("RUSTC_WRAPPER").unwrap_or_default(),
+            )
+            .env("RUSTC_WRAPPER", exe);
+        }
+        match build.engine.unwrap_or(Engine::Libfuzzer) {
+            // libhfuzz provides a `main` that drives the same
+            // `LLVMFuzzerTestOneInput` that libFuzzer would, so link it in
+            // libFuzzer's place.
+            Engine::Honggfuzz => {
+                cmd.env("CUSTOM_LIBFUZZER_PATH", utils::honggfuzz_libs()?.0);
+            }
+            // The generated LibAFL harness provides the `main` that libFuzzer
+            // would, so link it in libFuzzer's place too.
+            Engine::Libafl => {
+                cmd.env("CUSTOM_LIBFUZZER_PATH", self.build_libafl_harness(build)?)
+                    .env("CUSTOM_LIBFUZZER_STD_CXX", "none");
+            }
+            Engine::Libfuzzer => {
+                if let Some(archive) = &build.libfuzzer_archive {
+                    cmd.env("CUSTOM_LIBFUZZER_PATH", libfuzzer_archive(archive)?);
+                }
+            }
+        }
+        // A `libfuzzer-sys` from fuzz.toml replaces the crates.io release for
+        // the fuzz crate, without changing its manifest.
+        if let Some(spec) = self
+            .config
+            .libfuzzer_sys
+            .as_ref()
+            .filter(|_| manifest == self.manifest_path())
+        {
+            for (key, value) in libfuzzer_sys_source(spec)? {
+                let value = match key {
+                    "path" => self.path().join(value).display().to_string(),
+                    _ => value,
+                };
+                cmd.arg("--config").arg(format!(
+                    "patch.crates-io.libfuzzer-sys.{}={}",
+                    key,
+                    toml::Value::String(value)
+                ));
+            }
+        }
 
-        // Spawn cmd in child process instead of exec-ing it
-        let status = cmd
-            .status()
-            .with_context(|| format!("could not execute command: {:?}", cmd))?;
-        if status.success() {
-            // move corpus directory into tmp to auto delete it
-            fs::rename(&corpus, tmp.path().join("old"))?;
-            fs::rename(tmp.path().join("corpus"), corpus)?;
-        } else {
-            println!("Failed to minimize corpus: {}", status);
+        // `cargo run` runs the fuzz target through the runner Cargo is
+        // configured with for the target, which `--runner` overrides.
+        if let Some(runner) = &build.runner {
+            cmd.env(runner_var(&build.triple), runner);
         }
 
-        Ok(())
+        self.set_sanitizer_options(&mut cmd, sanitizer, &build.triple)?;
+
+        Ok(cmd)
     }
 
-    fn path(&self) -> PathBuf {
-        self.root_project.join("fuzz")
+    /// The flags that fuzz targets are built with, in the order they're
+    /// passed to rustc, grouped by where they come from: `cargo fuzz`'s own,
+    /// then the environment's `CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS`, then
+    /// `--rustflags-extra`. Flags that come later take precedence.
+    fn rustflag_layers(
+        &self,
+        build: &BuildOptions,
+        sanitizer: Sanitizer,
+    ) -> Result<Vec<(&'static str, Vec<String>)>> {
+        let mut layers = vec![("cargo fuzz", self.fuzzing_rustflags(build, sanitizer)?)];
+        layers.extend(env_rustflags());
+        if let Some(extra) = &build.rustflags_extra {
+            layers.push((
+                "--rustflags-extra",
+                extra.split_whitespace().map(String::from).collect(),
+            ));
+        }
+        Ok(layers)
     }
 
-    fn manifest_path(&self) -> PathBuf {
+    /// The flags that `cargo fuzz` itself builds fuzz targets with, for the
+    /// instrumentation that the engine and the sanitizer need.
+    fn fuzzing_rustflags(&self, build: &BuildOptions, sanitizer: Sanitizer) -> Result<Vec<String>> {
+        let engine = build.engine.unwrap_or(Engine::Libfuzzer);
+        let mut rustflags: String = "--cfg fuzzing".to_owned();
+        // Flags with paths in them, which may have spaces.
+        let mut paths = Vec::new();
+        // Coverage builds only replay inputs, so they don't need the fuzzing
+        // instrumentation, which also keeps them buildable on stable Rust.
+        if !build.coverage {
+            rustflags.push_str(
+                " -Cpasses=sancov \
+                 -Cllvm-args=-sanitizer-coverage-level=4 \
+                 -Cllvm-args=-sanitizer-coverage-trace-compares \
+                 -Clink-dead-code",
+            );
+        }
+        match engine {
+            Engine::Libfuzzer if build.coverage => {}
+            Engine::Libfuzzer => rustflags.push_str(
+                " -Cllvm-args=-sanitizer-coverage-inline-8bit-counters \
+                 -Cllvm-args=-sanitizer-coverage-pc-table",
+            ),
+            Engine::Honggfuzz => {
+                if let Some(host) = utils::host_triple().filter(|host| *host != build.triple) {
+                    bail!(
+                        "the honggfuzz engine can only build fuzz targets for the host ({}), \
+                         not {}",
+                        host,
+                        build.triple
+                    );
+                }
+                rustflags.push_str(
+                    " -Cllvm-args=-sanitizer-coverage-trace-pc-guard \
+                     -Cllvm-args=-sanitizer-coverage-trace-divs",
+                );
+                let (_, libhfcommon) = utils::honggfuzz_libs()?;
+                paths.push(format!("-Clink-arg={}", libhfcommon.display()));
+            }
+            Engine::Libafl => {
+                rustflags.push_str(" -Cllvm-args=-sanitizer-coverage-inline-8bit-counters")
+            }
+        }
+        if !sanitizer.supports(&build.triple) {
+            let supported = if Sanitizer::Address.supports(&build.triple) {
+                "`--sanitizer address` or `--sanitizer none`"
+            } else {
+                "`--sanitizer none`"
+            };
+            bail!(
+                "the {} sanitizer isn't supported for {}; use {}",
+                sanitizer,
+                build.triple,
+                supported
+            );
+        }
+        rustflags.push_str(&sanitizer_rustflags(sanitizer));
+        if build.triple.contains("-linux-") && engine == Engine::Libfuzzer && !build.coverage {
+            rustflags.push_str(" -Cllvm-args=-sanitizer-coverage-stack-depth");
+        }
+        if build.triple.ends_with("-windows-msvc") && engine == Engine::Libfuzzer {
+            // libFuzzer's `main` is in the `libfuzzer-sys` rlib, and the MSVC
+            // linker only pulls it out of there when told to.
+            rustflags.push_str(" -Clink-arg=/include:main");
+        }
+        if !build.release || build.debug_assertions {
+            rustflags.push_str(" -Cdebug-assertions");
+        }
+        if build.quick {
+            rustflags.push_str(QUICK_RUSTFLAGS);
+        }
+        if build.profiling {
+            rustflags.push_str(" -Cforce-frame-pointers=yes");
+        }
+        if build.coverage {
+            // `-C instrument-coverage` is stable since Rust 1.60.
+            if utils::rustc_minor_version().is_some_and(|minor| minor >= 60) {
+                rustflags.push_str(" -Cinstrument-coverage");
+            } else {
+                rustflags.push_str(" -Zinstrument-coverage");
+            }
+        }
+        rustflags.push_str(&debuginfo_rustflags(build));
+        if build.debuginfo {
+            // Cargo strips debug info from profiles that don't ask for it,
+            // which comes before the rustflags. Frame pointers let the
+            // sanitizers unwind even through a stack that overflowed.
+            rustflags.push_str(" -Cdebuginfo=2 -Cstrip=none -Cforce-frame-pointers=yes");
+        }
+        if build.record_trace {
+            // Have libFuzzer call the recorder, which calls the fuzz target.
+            let recorder = self.build_trace_recorder(build)?;
+            paths.push(format!("-Clink-arg={}", recorder.display()));
+            rustflags.push_str(" -Clink-arg=-Wl,--wrap=LLVMFuzzerTestOneInput");
+        }
+        if build.record_cmps {
+            // Have the instrumentation call the recorder, which calls
+            // libFuzzer.
+            let recorder = self.build_cmp_recorder(build)?;
+            paths.push(format!("-Clink-arg={}", recorder.display()));
+            for hook in blockers::HOOKS {
+                rustflags.push_str(&format!(" -Clink-arg=-Wl,--wrap={}", hook));
+            }
+            rustflags.push_str(" -Clink-arg=-ldl");
+        }
+
+        // If release mode is enabled then we force 1 CGU to be used in rustc.
+        // This will result in slower compilations but it looks like the sancov
+        // passes otherwise add `notEligibleToImport` annotations to functions
+        // in LLVM IR, meaning that *nothing* can get imported with ThinLTO.
+        // This means that in release mode, where ThinLTO is critical for
+        // performance, we're taking a huge hit relative to actual release mode.
+        // Local tests have once showed this to be a ~3x faster runtime where
+        // otherwise functions like `Vec::as_ptr` aren't inlined.
+        if !build.dev && build.profile.is_none() {
+            rustflags.push_str(" -Ccodegen-units=1");
+        }
+
+        let mut flags: Vec<String> = rustflags.split_whitespace().map(String::from).collect();
+        flags.extend(paths);
+        Ok(flags)
+    }
+
+    fn cargo_run(&self, build: &options::BuildOptions, fuzz_target: &str) -> Result<Command> {
+        let mut cmd = self.cargo("run", build, Some(fuzz_target))?;
+        cmd.arg("--bin").arg(fuzz_target);
+        self.set_target_env(&mut cmd, fuzz_target);
+
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(self.artifacts_for(fuzz_target)?);
+        cmd.arg("--").arg(artifact_arg);
+
+        Ok(cmd)
+    }
+
+    /// Like `cargo_run`, but runs the already-built fuzz target directly, so
+    /// that killing the command kills the fuzzer too.
+    fn binary_run(&self, build: &BuildOptions, fuzz_target: &str) -> Result<Command> {
+        let options = self.build_options_for(build, Some(fuzz_target));
+        let sanitizer = single_sanitizer(&options)?;
+        let mut cmd = binary_command(&options, &self.target_binary(build, fuzz_target));
+        self.set_target_env(&mut cmd, fuzz_target);
+        self.set_sanitizer_options(&mut cmd, sanitizer, &options.triple)?;
+
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(self.artifacts_for(fuzz_target)?);
+        cmd.arg(artifact_arg);
+
+        Ok(cmd)
+    }
+
+    /// Set the environment variables configured for a fuzz target.
+    fn set_target_env(&self, cmd: &mut Command, target: &str) {
+        cmd.envs(self.config.target(Some(target)).env.unwrap_or_default());
+    }
+
+    /// Run a fuzz target's `pre-run` or `post-run` hook from the config file,
+    /// if it has one, for `command` like `"run"`.
+    fn run_hook(&self, target: &str, hook: Hook, command: &str) -> Result<()> {
+        let config = self.config.target(Some(target));
+        let (name, script) = match hook {
+            Hook::PreRun => ("pre-run", config.pre_run),
+            Hook::PostRun => ("post-run", config.post_run),
+        };
+        let script = match script {
+            Some(script) => script,
+            None => return Ok(()),
+        };
+        let mut cmd = shell(&script);
+        cmd.current_dir(self.path())
+            .env("CARGO_FUZZ_TARGET", target)
+            .env("CARGO_FUZZ_COMMAND", command);
+        self.set_target_env(&mut cmd, target);
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute the {} hook: {}", name, script))?;
+        if !status.success() {
+            bail!("the {} hook of {} failed with {}", name, target, status);
+        }
+        Ok(())
+    }
+
+    /// Run a fuzz target's `on-crash` hook, if it has one, for each of the new
+    /// `artifacts` that `command` found, with what reproducing it tells about
+    /// the crash in environment variables. A hook that fails only gets a
+    /// warning, so that it can't hide the crash itself, and so does an
+    /// artifact that can't be reproduced, whose hook runs without the crash's
+    /// variables.
+    fn run_crash_hooks(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        command: &str,
+        artifacts: &[PathBuf],
+    ) {
+        let script = match self.config.target(Some(target)).on_crash {
+            Some(script) => script,
+            None => return,
+        };
+        let options = self.build_options_for(build, Some(target));
+        let engine = options.engine.unwrap_or(Engine::Libfuzzer);
+        let sanitizer = options.sanitizer.unwrap_or(Sanitizer::Address);
+        // Artifacts reproduce with libFuzzer whichever engine found them.
+        let mut libfuzzer = build.clone();
+        libfuzzer.engine = Some(Engine::Libfuzzer);
+
+        for artifact in artifacts {
+            let artifact = match fs::canonicalize(artifact) {
+                Ok(artifact) => artifact,
+                Err(e) => {
+                    eprintln!(
+                        "warning: skipping the on-crash hook of {} for {}, which can't be \
+                         found: {}",
+                        target,
+                        strip_current_dir_prefix(artifact).display(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            let crash = match self.reproduce_crash(&libfuzzer, target, &artifact) {
+                Ok(crash) => crash,
+                Err(e) => {
+                    eprintln!(
+                        "warning: failed to reproduce {} for the on-crash hook of {}: {:#}",
+                        strip_current_dir_prefix(&artifact).display(),
+                        target,
+                        e
+                    );
+                    None
+                }
+            };
+            let line = substitute(
+                &script,
+                &[
+                    ("{artifact}", &hook_quote(&artifact.to_string_lossy())),
+                    ("{target}", &hook_quote(target)),
+                ],
+            );
+            let mut cmd = shell(&line);
+            cmd.current_dir(self.path())
+                .env("CARGO_FUZZ_TARGET", target)
+                .env("CARGO_FUZZ_COMMAND", command)
+                .env("CARGO_FUZZ_ARTIFACT", &artifact)
+                .env("CARGO_FUZZ_ENGINE", engine.to_string())
+                .env("CARGO_FUZZ_SANITIZER", sanitizer.to_string());
+            if let Some(crash) = &crash {
+                cmd.env("CARGO_FUZZ_CRASH_KIND", &crash.kind)
+                    .env("CARGO_FUZZ_SIGNATURE", crash.signature())
+                    .env("CARGO_FUZZ_STACK_HASH", crash.hash());
+            }
+            self.set_target_env(&mut cmd, target);
+            match cmd.status() {
+                Ok(status) if status.success() => {}
+                Ok(status) => eprintln!(
+                    "warning: the on-crash hook of {} failed on {} with {}",
+                    target,
+                    strip_current_dir_prefix(&artifact).display(),
+                    status
+                ),
+                Err(e) => eprintln!(
+                    "warning: failed to execute the on-crash hook of {}: {}",
+                    target, e
+                ),
+            }
+        }
+    }
+
+    /// Run `f` between a fuzz target's `pre-run` and `post-run` hooks. The
+    /// `post-run` hook runs even if `f` fails, but not if `pre-run` does.
+    fn with_hooks<T>(
+        &self,
+        target: &str,
+        command: &str,
+        f: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        self.run_hook(target, Hook::PreRun, command)?;
+        let result = f();
+        match (result, self.run_hook(target, Hook::PostRun, command)) {
+            (result, Ok(())) => result,
+            (Ok(_), Err(e)) => Err(e),
+            (Err(e), Err(post)) => {
+                eprintln!("warning: {:#}", post);
+                Err(e)
+            }
+        }
+    }
+
+    pub fn exec_build(
+        &self,
+        build: &options::BuildOptions,
+        fuzz_target: Option<&str>,
+    ) -> Result<()> {
+        let options = self.build_options_for(build, fuzz_target);
+        if options.sanitizer == Some(Sanitizer::All) {
+            let each = Sanitizer::EACH
+                .iter()
+                .filter(|s| s.supports(&options.triple));
+            for &sanitizer in each {
+                let mut build = build.clone();
+                build.sanitizer = Some(sanitizer);
+                self.exec_build(&build, fuzz_target)?;
+            }
+            return Ok(());
+        }
+        let mut cmd = self.cargo("build", build, fuzz_target)?;
+
+        if let Some(fuzz_target) = fuzz_target {
+            cmd.arg("--bin").arg(fuzz_target);
+        } else {
+            cmd.arg("--bins");
+        }
+        if build.message_format.is_json() {
+            cmd.arg("--message-format").arg("json-render-diagnostics");
+        }
+
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        build.message_format.emit(&Message::BuildFinished {
+            target: fuzz_target,
+            success: status.success(),
+        });
+        if !status.success() {
+            return Err(exit::fail(
+                ExitCode::BuildFailed,
+                format!("failed to build fuzz script: {:?}", cmd),
+            ));
+        }
+
+        match fuzz_target {
+            Some(target) => self.build_target_bin(build, target)?,
+            None => {
+                for target in &self.targets {
+                    self.build_target_bin(build, target)?;
+                }
+            }
+        }
+
+        // Cargo skips the targets whose `required-features` aren't enabled
+        // when building all of them, so build those with their features.
+        if fuzz_target.is_none() && !build.all_features {
+            for target in self.required_features.keys() {
+                self.exec_build(build, Some(target))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the binary of the fuzzed crate that a fuzz target runs, if its
+    /// config names one, and put it next to the fuzz target's binary, where
+    /// the fuzz target looks for it.
+    fn build_target_bin(&self, build: &BuildOptions, target: &str) -> Result<()> {
+        let bin = match self.config.target(Some(target)).bin {
+            Some(bin) => bin,
+            None => return Ok(()),
+        };
+        let options = self.build_options_for(build, Some(target));
+        let sanitizer = single_sanitizer(&options)?;
+        let target_dir = self.target_dir(&options).join("bins");
+        let mut cmd = self.cargo_in(
+            &self.root_project.join("Cargo.toml"),
+            &target_dir,
+            "build",
+            build,
+            Some(target),
+        )?;
+        cmd.arg("--bin").arg(&bin).env(
+            "CARGO_ENCODED_RUSTFLAGS",
+            bin_rustflags(&options, sanitizer).join("\x1f"),
+        );
+        if build.message_format.is_json() {
+            cmd.arg("--message-format").arg("json-render-diagnostics");
+        }
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !status.success() {
+            return Err(exit::fail(
+                ExitCode::BuildFailed,
+                format!("failed to build `{}` for `{}`: {:?}", bin, target, cmd),
+            ));
+        }
+
+        let file_name = if options.triple.contains("-windows") {
+            format!("{}.exe", bin)
+        } else {
+            bin
+        };
+        let built = target_dir
+            .join(&options.triple)
+            .join(profile_dir(&options))
+            .join(&file_name);
+        let copy = self.target_binary(build, target).with_file_name(&file_name);
+        fs::copy(&built, &copy)
+            .with_context(|| format!("failed to copy {} to {}", built.display(), copy.display()))?;
+        Ok(())
+    }
+
+    /// Print the flags for rustc that a fuzz target, or all of them, would be
+    /// built with, grouped by where they come from, in the order that they're
+    /// passed in.
+    pub fn print_build_flags(&self, build: &BuildOptions, target: Option<&str>) -> Result<()> {
+        let build = self.build_options_for(build, target);
+        let sanitizer = single_sanitizer(&build)?;
+        for (i, (source, flags)) in self.rustflag_layers(&build, sanitizer)?.iter().enumerate() {
+            if i > 0 {
+                println!();
+            }
+            println!("# {}", source);
+            // Keep flags like `--cfg fuzzing` with their values.
+            let mut flags = flags.iter().peekable();
+            while let Some(flag) = flags.next() {
+                match flags.next_if(|value| flag.starts_with('-') && !value.starts_with('-')) {
+                    Some(value) => println!("{} {}", flag, value),
+                    None => println!("{}", flag),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Break down the size of the fuzz target's binary, or each one's, after
+    /// building them, for `cargo fuzz build --analyze-size`.
+    pub fn exec_analyze_size(&self, build: &BuildOptions, target: Option<&str>) -> Result<()> {
+        let options = self.build_options_for(build, target);
+        if options.sanitizer == Some(Sanitizer::All) {
+            let each = Sanitizer::EACH
+                .iter()
+                .filter(|s| s.supports(&options.triple));
+            for &sanitizer in each {
+                let mut build = build.clone();
+                build.sanitizer = Some(sanitizer);
+                self.exec_analyze_size(&build, target)?;
+            }
+            return Ok(());
+        }
+        let targets = match target {
+            Some(target) => vec![target.to_string()],
+            None => self.targets.clone(),
+        };
+        let mut mostly_debuginfo = false;
+        for target in &targets {
+            let binary = self.target_binary(build, target);
+            let report = bloat::analyze(&binary)?;
+            mostly_debuginfo |= report.mostly_debuginfo();
+            let sanitizer = self
+                .build_options_for(build, Some(target))
+                .sanitizer
+                .unwrap_or(Sanitizer::Address);
+            let name = format!("{} ({} sanitizer)", target, sanitizer);
+            println!();
+            print!("{}", bloat::render(&name, &report));
+        }
+        if mostly_debuginfo {
+            eprintln!();
+            eprintln!(
+                "note: most of the binary is debug info; `--split-debuginfo unpacked` keeps it \
+                 out of the binary, which also makes linking faster"
+            );
+            if build.debuginfo_level.is_none() {
+                eprintln!(
+                    "note: `--debuginfo-level line-tables-only` keeps only what stack traces need"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn get_artifacts_since(
+        &self,
+        target: &str,
+        since: &time::SystemTime,
+    ) -> Result<HashSet<PathBuf>> {
+        let mut artifacts = HashSet::new();
+
+        let artifacts_dir = self.artifacts_for(target)?;
+
+        for entry in fs::read_dir(&artifacts_dir).with_context(|| {
+            format!(
+                "failed to read directory entries of {}",
+                artifacts_dir.display()
+            )
+        })? {
+            let entry = entry.with_context(|| {
+                format!(
+                    "failed to read directory entry inside {}",
+                    artifacts_dir.display()
+                )
+            })?;
+
+            let metadata = entry
+                .metadata()
+                .context("failed to read artifact metadata")?;
+            let modified = metadata
+                .modified()
+                .context("failed to get artifact modification time")?;
+            if !metadata.is_file() || modified <= *since {
+                continue;
+            }
+            // Slow units aren't failures, and divergence reports describe
+            // failures rather than being inputs.
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with(libfuzzer::SLOW_UNIT_PREFIX)
+                || name.starts_with(DIVERGENCE_REPORT_PREFIX)
+            {
+                continue;
+            }
+
+            artifacts.insert(entry.path());
+        }
+
+        Ok(artifacts)
+    }
+
+    fn run_fuzz_target_debug_formatter(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+    ) -> Result<String> {
+        let debug_output = tempfile::NamedTempFile::new().context("failed to create temp file")?;
+
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.stdin(Stdio::null());
+        cmd.env("RUST_LIBFUZZER_DEBUG_PATH", debug_output.path());
+        cmd.arg(artifact);
+
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+
+        if !output.status.success() {
+            bail!(
+                "Fuzz target '{target}' exited with failure when attemping to \
+                 debug formatting an interesting input that we discovered!\n\n\
+                 Artifact: {artifact}\n\n\
+                 Command: {cmd:?}\n\n\
+                 Status: {status}\n\n\
+                 === stdout ===\n\
+                 {stdout}\n\n\
+                 === stderr ===\n\
+                 {stderr}",
+                target = target,
+                status = output.status,
+                cmd = cmd,
+                artifact = artifact.display(),
+                stdout = String::from_utf8_lossy(&output.stdout),
+                stderr = String::from_utf8_lossy(&output.stderr),
+            );
+        }
+
+        let debug = fs::read_to_string(&debug_output).context("failed to read temp file")?;
+        Ok(debug)
+    }
+
+    /// Run a fuzz target on a single input, capturing its output.
+    ///
+    /// Any artifact that libFuzzer writes while reproducing a crash goes to a
+    /// temporary directory, so that we don't add copies of the input to the
+    /// target's artifacts directory.
+    fn run_fuzz_target_on_input(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        input: &Path,
+    ) -> Result<Output> {
+        let scratch = tempfile::TempDir::new().context("failed to create temp dir")?;
+
+        let mut cmd = self.cargo_run(build, target)?;
+        cmd.stdin(Stdio::null());
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(scratch.path().join(""));
+        cmd.arg(artifact_arg);
+        cmd.arg(input);
+
+        cmd.output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))
+    }
+
+    /// Run a fuzz target on an artifact, and parse the crash it causes, or
+    /// return `None` if it no longer crashes.
+    fn reproduce_crash(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+    ) -> Result<Option<Crash>> {
+        let output = self.run_fuzz_target_on_input(build, target, artifact)?;
+        Ok(crash_from_output(&output))
+    }
+
+    /// Reproduce the artifacts that fuzz targets wrote since a run started,
+    /// and write their crashes to a SARIF file. The file is written even when
+    /// there are none, so that CI can always upload it.
+    fn write_run_sarif(
+        &self,
+        build: &BuildOptions,
+        targets: &[&str],
+        since: &time::SystemTime,
+        path: &Path,
+    ) -> Result<()> {
+        // Artifacts reproduce with libFuzzer whichever engine found them.
+        let mut build = build.clone();
+        build.engine = Some(Engine::Libfuzzer);
+
+        let mut crashes = Vec::new();
+        for target in targets {
+            let mut artifacts: Vec<_> = self
+                .get_artifacts_since(target, since)?
+                .into_iter()
+                .collect();
+            artifacts.sort();
+            for artifact in artifacts {
+                if let Some(crash) = self.reproduce_crash(&build, target, &artifact)? {
+                    crashes.push((*target, crash, artifact));
+                }
+            }
+        }
+        let findings: Vec<_> = crashes
+            .iter()
+            .map(|(target, crash, artifact)| sarif::Finding {
+                target,
+                crash,
+                artifact: strip_current_dir_prefix(artifact),
+            })
+            .collect();
+        self.write_sarif(path, &findings)
+    }
+
+    fn write_sarif(&self, path: &Path, findings: &[sarif::Finding]) -> Result<()> {
+        // Code scanning resolves paths relative to the repository's root.
+        fs::write(path, sarif::render(findings, self.repo_root()))
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        eprintln!(
+            "Wrote {} crash(es) as SARIF to:\n\n\t{}\n",
+            findings.len(),
+            strip_current_dir_prefix(path).display()
+        );
+        Ok(())
+    }
+
+    /// Prints the debug output of an input test case
+    pub fn debug_fmt_input(&self, debugfmt: &options::Fmt) -> Result<()> {
+        let target = &debugfmt.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        if !debugfmt.input.exists() {
+            bail!(
+                "Input test case does not exist: {}",
+                debugfmt.input.display()
+            );
+        }
+
+        let decoder = self.build_shim(&debugfmt.build, target, decoder::Shim::Decoder)?;
+        let mut cmd = Command::new(&decoder);
+        cmd.arg(&debugfmt.input).stdin(Stdio::null());
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        if !output.status.success() {
+            bail!(
+                "failed to decode {} as an input to `{}`",
+                debugfmt.input.display(),
+                target
+            );
+        }
+
+        eprintln!("\nOutput of `std::fmt::Debug`:\n");
+        for l in String::from_utf8_lossy(&output.stdout).lines() {
+            eprintln!("{}", l);
+        }
+
+        Ok(())
+    }
+
+    /// Fuzz a given fuzz target, between its `pre-run` and `post-run` hooks
+    pub fn exec_fuzz(&self, run: &options::Run) -> Result<()> {
+        let target = run
+            .target
+            .as_deref()
+            .ok_or_else(|| anyhow!("no fuzz target given"))?;
+        self.print_run_sanitizer_options(run, target, "")?;
+        self.with_hooks(target, "run", || self.fuzz(run))
+    }
+
+    /// Fuzz deterministically for `run --seed`, and write a reproducibility
+    /// manifest of the run to `fuzz/manifests/`.
+    fn exec_fuzz_seeded(&self, run: &options::Run, target: &str, seed: u32) -> Result<()> {
+        if run.filter.is_active() {
+            bail!("`--seed` can't be combined with the input filters");
+        }
+        let build = libfuzzer_only(
+            &self.build_options_for(&run.build, Some(target)),
+            "run --seed",
+        )?;
+        let config = self.config.target(Some(target));
+        if config.jobs.unwrap_or(1) != 1 {
+            eprintln!("Fuzzing with a single job instead of the configured jobs, for determinism");
+        }
+
+        let mut runs = DETERMINISTIC_RUNS;
+        let mut args = Vec::new();
+        for arg in config.args.iter().flatten().chain(&run.args) {
+            if let Some(value) = arg.strip_prefix("-runs=") {
+                runs = value
+                    .parse()
+                    .with_context(|| format!("invalid libFuzzer argument: {}", arg))?;
+                continue;
+            }
+            let option = arg.split('=').next().unwrap_or(arg);
+            if ["-seed", "-jobs", "-workers", "-fork", "-max_total_time"].contains(&option) {
+                bail!("`{}` can't be combined with `--seed`", arg);
+            }
+            args.push(arg.clone());
+        }
+
+        self.exec_build(&build, Some(target))?;
+        let dictionary = if args.iter().any(|arg| arg.starts_with("-dict=")) {
+            None
+        } else {
+            match self.dict_for(target, &run.dicts)? {
+                Some(dict) => Some(
+                    fs::read_to_string(&dict)
+                        .with_context(|| format!("failed to read {}", dict.display()))?,
+                ),
+                None => None,
+            }
+        };
+        let corpora = self.replay_corpora(run, target)?;
+        let features = self.engine_features(run, target)?;
+        let feature = |feature| {
+            features
+                .iter()
+                .find(|(f, _)| *f == feature)
+                .map(|(_, on)| *on)
+        };
+        let manifest = replay::Manifest {
+            target: target.to_string(),
+            triple: build.triple.clone(),
+            settings: TargetConfig {
+                sanitizer: Some(single_sanitizer(&build)?),
+                engine: Some(Engine::Libfuzzer),
+                dev: Some(build.dev),
+                release: Some(build.release),
+                profile: build.profile.clone(),
+                debug_assertions: Some(build.debug_assertions),
+                features: build.features.clone(),
+                no_default_features: Some(build.no_default_features),
+                all_features: Some(build.all_features),
+                coverage_crates: build
+                    .coverage_crates
+                    .as_deref()
+                    .map(rustc_wrapper::parse_crates),
+                timeout: run.timeout.or(config.timeout),
+                rss_limit: run.rss_limit.or(config.rss_limit),
+                malloc_limit: run.malloc_limit.or(config.malloc_limit),
+                max_len: config.max_len,
+                args: Some(args),
+                value_profile: feature(EngineFeature::ValueProfile),
+                entropic: feature(EngineFeature::Entropic),
+                cmp_log: feature(EngineFeature::CmpLog),
+                ..TargetConfig::default()
+            },
+            seed,
+            runs,
+            dictionary,
+            corpus_hash: replay::corpus_hash(&corpora)?,
+            binary_hash: session::binary_hash(&self.target_binary(&build, target))?,
+            cargo_fuzz_version: env!("CARGO_PKG_VERSION").to_string(),
+            rustc_version: utils::rustc_release().map(String::from),
+            engine_version: self.locked_version("libfuzzer-sys"),
+        };
+        let path = self
+            .path()
+            .join("manifests")
+            .join(format!("{}-seed-{}.json", target, seed));
+        manifest.save(&path)?;
+        eprintln!(
+            "Wrote a reproducibility manifest of this run to:\n\n\t{}\n",
+            strip_current_dir_prefix(&path).display()
+        );
+        self.fuzz_from_manifest(run, &manifest, build, &corpora)
+    }
+
+    /// Fuzz again like the deterministic run that wrote a reproducibility
+    /// manifest, for `run --replay-manifest`, warning about anything that
+    /// changed since and would make it go differently.
+    pub fn exec_replay_manifest(&self, run: &options::Run, path: &Path) -> Result<()> {
+        let manifest = replay::Manifest::load(path)?;
+        let target = &manifest.target;
+        if !self.targets.contains(target) {
+            bail!(
+                "{} is a manifest of `{}`, which isn't a fuzz target of this project",
+                path.display(),
+                target
+            );
+        }
+        if run.target.as_ref().is_some_and(|t| t != target) {
+            bail!(
+                "{} is a manifest of `{}`, not `{}`",
+                path.display(),
+                target,
+                run.target.as_deref().unwrap_or_default()
+            );
+        }
+
+        // Options given on the command line take precedence over the
+        // manifest's, like they do over the config file's.
+        let mut build = run.build.clone();
+        manifest.settings.apply(&mut build);
+        if build.triple == utils::default_target() {
+            build.triple = manifest.triple.clone();
+        }
+        let build = libfuzzer_only(&build, "run --replay-manifest")?;
+        self.exec_build(&build, Some(target))?;
+        let corpora = self.replay_corpora(run, target)?;
+        let current = replay::Manifest {
+            corpus_hash: replay::corpus_hash(&corpora)?,
+            binary_hash: session::binary_hash(&self.target_binary(&build, target))?,
+            cargo_fuzz_version: env!("CARGO_PKG_VERSION").to_string(),
+            rustc_version: utils::rustc_release().map(String::from),
+            engine_version: self.locked_version("libfuzzer-sys"),
+            ..manifest.clone()
+        };
+        for difference in manifest.differences(&current) {
+            eprintln!(
+                "warning: {} since {} was written, so the run may go differently",
+                difference,
+                path.display()
+            );
+        }
+        eprintln!(
+            "Replaying the run of `{}` with seed {} for {} run(s)",
+            target, manifest.seed, manifest.runs
+        );
+        self.fuzz_from_manifest(run, &manifest, build, &corpora)
+    }
+
+    /// The corpora that a deterministic run of a target starts from: those
+    /// given on the command line, or else its corpus and seeds.
+    fn replay_corpora(&self, run: &options::Run, target: &str) -> Result<Vec<PathBuf>> {
+        if !run.corpus.is_empty() {
+            return Ok(run.corpus.iter().map(PathBuf::from).collect());
+        }
+        let mut corpora = vec![self.corpus_for(target)?];
+        corpora.extend(self.seeds_for(target)?);
+        Ok(corpora)
+    }
+
+    /// Fuzz with a reproducibility manifest's seed, runs, dictionary and
+    /// settings, and a single job. New inputs go to a scratch directory rather
+    /// than the corpus, so that the corpus is the same for a replay.
+    fn fuzz_from_manifest(
+        &self,
+        run: &options::Run,
+        manifest: &replay::Manifest,
+        build: BuildOptions,
+        corpora: &[PathBuf],
+    ) -> Result<()> {
+        let scratch = tempfile::tempdir().context("failed to create a temp directory")?;
+        let mut args = manifest.settings.args.clone().unwrap_or_default();
+        if let Some(max_len) = manifest.settings.max_len {
+            args.insert(0, format!("-max_len={}", max_len));
+        }
+        if let Some(dictionary) = &manifest.dictionary {
+            let dict = scratch.path().join(format!("{}.dict", manifest.target));
+            fs::write(&dict, dictionary)
+                .with_context(|| format!("failed to write {}", dict.display()))?;
+            args.insert(0, format!("-dict={}", dict.display()));
+        }
+        args.push(format!("-seed={}", manifest.seed));
+        args.push(format!("-runs={}", manifest.runs));
+        let new_inputs = scratch.path().join("corpus");
+        fs::create_dir(&new_inputs)
+            .with_context(|| format!("could not make a directory at {}", new_inputs.display()))?;
+        let corpus = iter::once(&new_inputs)
+            .chain(corpora)
+            .map(|dir| dir.display().to_string())
+            .collect();
+        let run = options::Run {
+            build,
+            target: Some(manifest.target.clone()),
+            corpus,
+            all: false,
+            max_total_time: None,
+            until_new_coverage_stalls: None,
+            // Fuzzing with several jobs isn't deterministic.
+            jobs: Some(1),
+            fork: None,
+            ignore_known_crashes: run.ignore_known_crashes.clone(),
+            max_crashes: run.max_crashes,
+            timeout: manifest.settings.timeout,
+            rss_limit: manifest.settings.rss_limit,
+            malloc_limit: manifest.settings.malloc_limit,
+            record_trace: run.record_trace,
+            save_hangs: run.save_hangs,
+            auto_tmin: run.auto_tmin,
+            auto_tmin_time: run.auto_tmin_time,
+            focus_changed: None,
+            filter: options::InputFilter::default(),
+            features: options::EngineFeatures::from_config(&manifest.settings),
+            mode: run.mode,
+            slow_threshold_ms: run.slow_threshold_ms,
+            monitor: run.monitor,
+            status_addr: run.status_addr.clone(),
+            notify_webhook: run.notify_webhook.clone(),
+            notify_plateau: run.notify_plateau,
+            tui: false,
+            sarif: run.sarif.clone(),
+            asan_options: run.asan_options.clone(),
+            dicts: Vec::new(),
+            seed: None,
+            replay_manifest: None,
+            args,
+        };
+        self.fuzz(&run)
+    }
+
+    /// The version of a package that the fuzz crate's lockfile pins it to,
+    /// like `libfuzzer-sys 0.4.7`.
+    fn locked_version(&self, package: &str) -> Option<String> {
+        let lockfile = fs::read_to_string(self.path().join("Cargo.lock")).ok()?;
+        let lockfile: toml::Value = toml::from_str(&lockfile).ok()?;
+        lockfile
+            .get("package")?
+            .as_array()?
+            .iter()
+            .find(|p| p.get("name").and_then(toml::Value::as_str) == Some(package))
+            .and_then(|p| p.get("version")?.as_str())
+            .map(|version| format!("{} {}", package, version))
+    }
+
+    fn fuzz(&self, run: &options::Run) -> Result<()> {
+        let target = run
+            .target
+            .as_deref()
+            .ok_or_else(|| anyhow!("no fuzz target given"))?;
+        if let Some(seed) = run.seed {
+            return self.exec_fuzz_seeded(run, target, seed);
+        }
+        let build = self.build_options_for(&run.build, Some(target));
+        // Inputs that aren't tracked yet were there before this run.
+        self.track_corpus(target, Origin::Seed, None)?;
+        self.warn_over_max_len(run, target)?;
+        self.warn_engine_args(run, target, &build);
+        if run.focus_changed.is_some() || run.filter.is_active() {
+            return self.exec_fuzz_focused(run, target);
+        }
+        if run.mode == Some(RunMode::Leaks) {
+            let build = libfuzzer_only(&build, "run --mode leaks")?;
+            if !matches!(
+                build.sanitizer.unwrap_or(Sanitizer::Address),
+                Sanitizer::Address | Sanitizer::Leak
+            ) {
+                bail!("`cargo fuzz run --mode leaks` needs the address or leak sanitizer");
+            }
+        }
+        if build.sanitizer == Some(Sanitizer::All) {
+            return self.exec_fuzz_each_sanitizer(run, target);
+        }
+        if let Some(engine) = build.engine.filter(|e| *e != Engine::Libfuzzer) {
+            if run.monitor {
+                bail!("`--monitor` doesn't support the {} engine", engine);
+            }
+            if run.status_addr.is_some() {
+                bail!("`--status-addr` doesn't support the {} engine", engine);
+            }
+            if run.notify_webhook.is_some() {
+                bail!("`--notify-webhook` doesn't support the {} engine", engine);
+            }
+            if run.auto_tmin {
+                bail!("`--auto-tmin` doesn't support the {} engine", engine);
+            }
+            return self.exec_other_engine(run, target, engine);
+        }
+        if run.fork.is_some() {
+            return self.exec_fuzz_fork(run, target);
+        }
+        if run.tui {
+            return self.exec_fuzz_tui(run, target);
+        }
+        if (run.record_trace.is_some() || run.save_hangs) && !run.build.record_trace {
+            let jobs = run.jobs.or(self.config.target(Some(target)).jobs);
+            if jobs.unwrap_or(1) != 1 {
+                bail!(
+                    "`{}` only supports fuzzing with a single job",
+                    if run.save_hangs {
+                        "--save-hangs"
+                    } else {
+                        "--record-trace"
+                    }
+                );
+            }
+            let mut run = run.clone();
+            run.build.record_trace = true;
+            return self.fuzz(&run);
+        }
+        self.exec_build(&run.build, Some(target))?;
+        let mut known_crashes = self.known_crashes(run, target)?;
+        let max_crashes = run.max_crashes.unwrap_or(1);
+        // Telling unique crashes apart means reproducing each one.
+        let dedup = run.ignore_known_crashes.is_some() || max_crashes > 1;
+
+        // When libfuzzer finds failing inputs, those inputs will end up in the
+        // artifacts directory. To easily filter old artifacts from new ones,
+        // get the current time, and then later we only consider files modified
+        // after now.
+        let before_fuzzing = time::SystemTime::now();
+        let start = time::Instant::now();
+
+        let format = run.build.message_format;
+        // A fuzzer whose coverage stalls gets killed.
+        let stall = run.until_new_coverage_stalls.is_some();
+        let mut monitor = self.monitor(run, target)?;
+        let mut hangs = self.hang_watcher(run, target)?;
+        let status_server = start_status_server(run, target)?;
+        let notifier = self.notifier(run, target)?;
+        let mut new_crashes = Vec::new();
+        let mut ignored = 0;
+        // Whether the fuzzer failed without saving an artifact.
+        let mut unexplained = false;
+        let (status, stalled) = loop {
+            let mut attempt = run.clone();
+            attempt.max_total_time = run
+                .max_total_time
+                .map(|secs| secs.saturating_sub(start.elapsed().as_secs()).max(1));
+            // Sampling a hang's stacks needs the fuzzer's own process.
+            let mut cmd = self.fuzz_command(&attempt, target, stall || run.save_hangs)?;
+            let before_attempt = time::SystemTime::now();
+            let mut recorder = stats::RunRecorder::new();
+            let mut reported = false;
+            let mut crash_report = String::new();
+            let (status, stalled) = spawn_watched(
+                &mut cmd,
+                run.until_new_coverage_stalls
+                    .map(libfuzzer::StallDetector::new),
+                monitor.as_mut(),
+                hangs.as_mut(),
+                |line| {
+                    emit_fuzzer_messages(format, target, line);
+                    recorder.observe(line);
+                    if let Some(server) = &status_server {
+                        server.observe(line);
+                    }
+                    if let Some(notifier) = &notifier {
+                        notifier.observe(line);
+                    }
+                    reported |= libfuzzer::Status::parse(line).is_some();
+                    if !crash_report.is_empty() || crash::starts_report(line) {
+                        crash_report.push_str(line);
+                        crash_report.push('\n');
+                    }
+                },
+            )?;
+            self.record_run(run, target, recorder)?;
+            if stalled || status.success() {
+                break (status, stalled);
+            }
+
+            let mut artifacts: Vec<_> = self
+                .get_artifacts_since(target, &before_attempt)?
+                .into_iter()
+                .collect();
+            artifacts.sort();
+            if let Some(hangs) = &hangs {
+                self.save_hang(target, hangs, &crash_report, &mut artifacts)?;
+            }
+            if artifacts.is_empty() {
+                unexplained = true;
+                break (status, stalled);
+            }
+            if let Some(server) = &status_server {
+                server.add_crashes(artifacts.len());
+            }
+            label_stack_overflows(&crash_report, &mut artifacts)?;
+            if !dedup {
+                new_crashes.extend(artifacts);
+                break (status, stalled);
+            }
+            let unknown =
+                self.unknown_crashes(&run.build, target, &artifacts, &mut known_crashes)?;
+            ignored += artifacts.len() - unknown.len();
+            new_crashes.extend(unknown);
+
+            // A crash before libFuzzer reported any progress came from the
+            // corpus, and would only happen again.
+            let out_of_time = run
+                .max_total_time
+                .is_some_and(|secs| start.elapsed().as_secs() >= secs);
+            if new_crashes.len() >= max_crashes || !reported || out_of_time {
+                break (status, stalled);
+            }
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!(
+                "Found {} of at most {} new unique crash(es), and ignored {} known or \
+                 duplicate one(s); restarting `{}`\n",
+                new_crashes.len(),
+                max_crashes,
+                ignored,
+                target
+            );
+        };
+
+        self.save_slow_inputs(run, target, &before_fuzzing)?;
+        print_monitor_summary(target, monitor.as_ref());
+        if run.mode == Some(RunMode::Leaks) {
+            self.save_leaks(target, &mut new_crashes)?;
+        }
+        self.run_crash_hooks(&run.build, target, "run", &new_crashes);
+
+        // Only finding known crashes is as good as finding none at all.
+        let crashed = !new_crashes.is_empty();
+        if crashed && run.record_trace.is_some() {
+            eprintln!(
+                "The inputs that ran up to the crash are in {}; replay them before the crash \
+                 with:\n\n\tcargo fuzz repro {} <artifact> --trace\n",
+                strip_current_dir_prefix(&self.trace_dir(target)).display(),
+                target
+            );
+        }
+        let success = stalled || status.success() || (!crashed && !unexplained);
+        format.emit(&Message::RunFinished {
+            target,
+            success,
+            exit_code: status.code(),
+            stalled,
+        });
+        if let Some(notifier) = &notifier {
+            notifier.finish(success);
+        }
+        if let Some(path) = &run.sarif {
+            self.write_run_sarif(&run.build, &[target], &before_fuzzing, path)?;
+        }
+        if ignored > 0 {
+            eprintln!("\nIgnored {} known or duplicate crash(es).", ignored);
+        }
+        if stalled {
+            eprintln!(
+                "\nStopped fuzzing `{}`: no new coverage for {}.",
+                target,
+                run.until_new_coverage_stalls.unwrap()
+            );
+            return Ok(());
+        }
+        if success {
+            return Ok(());
+        }
+
+        self.print_artifacts(&run.build, target, &new_crashes)?;
+        if run.auto_tmin {
+            let limit = time::Duration::from_secs(run.auto_tmin_time.unwrap_or(60));
+            self.auto_minimize(&run.build, target, &new_crashes, limit)?;
+        }
+        eprintln!("{:─<80}\n", "");
+        if unexplained {
+            bail!("Fuzz target exited with {}", crash::describe_exit(status));
+        }
+        if dedup {
+            return Err(exit::fail(
+                ExitCode::CrashFound,
+                format!("found {} new unique crash(es)", new_crashes.len()),
+            ));
+        }
+        Err(exit::fail(
+            ExitCode::CrashFound,
+            format!("Fuzz target exited with {}", crash::describe_exit(status)),
+        ))
+    }
+
+    /// Minimize each crash that `run --auto-tmin` found for at most `limit`,
+    /// into the `minimized` subdirectory of the target's artifacts directory,
+    /// and print a preview of each minimized input. The original artifacts
+    /// are left as they are.
+    fn auto_minimize(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifacts: &[PathBuf],
+        limit: time::Duration,
+    ) -> Result<()> {
+        let dir = self.artifacts_for(target)?.join("minimized");
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+        for artifact in artifacts {
+            let name = artifact.file_name().unwrap_or_default();
+            let minimized = dir.join(name);
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!(
+                "Minimizing {} for up to {}s...",
+                strip_current_dir_prefix(artifact).display(),
+                limit.as_secs()
+            );
+            let success =
+                self.minimize_with_deadline(build, target, artifact, &minimized, limit)?;
+            build.message_format.emit(&Message::TestCaseMinimized {
+                target,
+                artifact: success
+                    .then(|| strip_current_dir_prefix(&minimized).display().to_string()),
+                success,
+            });
+            if !success {
+                eprintln!("\nlibFuzzer couldn't make it any smaller while it still crashed.");
+                continue;
+            }
+            let original = fs::read(artifact)
+                .with_context(|| format!("failed to read {}", artifact.display()))?;
+            let data = fs::read(&minimized)
+                .with_context(|| format!("failed to read {}", minimized.display()))?;
+            eprintln!(
+                "\nMinimized input ({} of {} bytes):\n\n\t{}\n\n\thex:     {}\n\tescaped: {}\n",
+                data.len(),
+                original.len(),
+                strip_current_dir_prefix(&minimized).display(),
+                export::encode_hex(&data[..data.len().min(PREVIEW_BYTES)]),
+                export::escape(&data, PREVIEW_BYTES)
+            );
+        }
+        Ok(())
+    }
+
+    /// Minimize a crashing input into `minimized`, stopping libFuzzer once
+    /// `limit` is up, and return whether it made a smaller input that still
+    /// crashes.
+    fn minimize_with_deadline(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifact: &Path,
+        minimized: &Path,
+        limit: time::Duration,
+    ) -> Result<bool> {
+        // Anything libFuzzer writes besides the minimized input goes to a
+        // scratch directory rather than the target's artifacts.
+        let scratch = tempfile::TempDir::new().context("failed to create temp dir")?;
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(scratch.path().join(""));
+        let mut exact_artifact_arg = ffi::OsString::from("-exact_artifact_path=");
+        exact_artifact_arg.push(minimized);
+        let mut cmd = self.binary_run(build, target)?;
+        // Each of libFuzzer's minimization steps stops after the total time,
+        // so none outlives the deadline for long.
+        cmd.arg(&artifact_arg)
+            .arg("-minimize_crash=1")
+            .arg("-runs=255")
+            .arg(format!("-max_total_time={}", limit.as_secs().max(1)))
+            .arg(&exact_artifact_arg)
+            .arg(artifact)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+        let deadline = time::Instant::now() + limit;
+        while child.try_wait()?.is_none() {
+            if time::Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                break;
+            }
+            thread::sleep(STALL_POLL_INTERVAL);
+        }
+
+        // Each step writes a smaller input that crashed, so whatever is there
+        // is the smallest one so far, but check that it still crashes.
+        let smaller = fs::metadata(minimized)
+            .ok()
+            .zip(fs::metadata(artifact).ok())
+            .is_some_and(|(minimized, original)| minimized.len() < original.len());
+        if smaller {
+            let mut cmd = self.binary_run(build, target)?;
+            cmd.arg(&artifact_arg).arg(minimized).stdin(Stdio::null());
+            let output = cmd
+                .output()
+                .with_context(|| format!("failed to run command: {:?}", cmd))?;
+            if crash_from_output(&output).is_some() {
+                return Ok(true);
+            }
+        }
+        let _ = fs::remove_file(minimized);
+        Ok(false)
+    }
+
+    /// Start sampling the fuzzer's resource use for `run --monitor`, into a
+    /// file of this run's in `fuzz/monitor/<target>/`.
+    fn monitor(&self, run: &options::Run, target: &str) -> Result<Option<monitor::Monitor>> {
+        if !run.monitor {
+            return Ok(None);
+        }
+        let path = self
+            .path()
+            .join("monitor")
+            .join(target)
+            .join(format!("{}.csv", stats::run_id()));
+        monitor::Monitor::new(&path).map(Some)
+    }
+
+    /// Start watching for hangs for `run --save-hangs`.
+    fn hang_watcher(&self, run: &options::Run, target: &str) -> Result<Option<hang::Watcher>> {
+        if !run.save_hangs {
+            return Ok(None);
+        }
+        // libFuzzer's default timeout.
+        let timeout = self.limits(run, target)?.timeout_secs.unwrap_or(1200);
+        if timeout == 0 {
+            bail!("`--save-hangs` needs a timeout, but the timeout is 0");
+        }
+        Ok(Some(hang::Watcher::new(
+            &self.trace_dir(target),
+            time::Duration::from_secs(timeout),
+        )))
+    }
+
+    /// Start notifying the webhook of `run --notify-webhook` of the target's
+    /// crashes and plateaus.
+    fn notifier(&self, run: &options::Run, target: &str) -> Result<Option<notify::Notifier>> {
+        let url = match &run.notify_webhook {
+            Some(url) => url,
+            None => return Ok(None),
+        };
+        Ok(Some(notify::Notifier::start(
+            url,
+            target,
+            &self.artifacts_for(target)?,
+            run.notify_plateau.unwrap_or(notify::DEFAULT_PLATEAU),
+        )))
+    }
+
+    /// Save the input that the fuzzer hung on, if it did, to the target's
+    /// hangs directory, in place of any `timeout-` artifact that libFuzzer
+    /// wrote for it among `artifacts`.
+    fn save_hang(
+        &self,
+        target: &str,
+        hangs: &hang::Watcher,
+        report: &str,
+        artifacts: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let timed_out = Crash::parse(report).is_some_and(|crash| crash.kind == "timeout");
+        if !timed_out && !hangs.is_stuck() {
+            return Ok(());
+        }
+        let hang = match hangs.save(&self.hangs_dir(target)?, report)? {
+            Some(hang) => hang,
+            None => return Ok(()),
+        };
+        for artifact in artifacts.iter() {
+            let is_timeout = artifact
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("timeout-"));
+            if is_timeout {
+                fs::remove_file(artifact)
+                    .with_context(|| format!("failed to remove {}", artifact.display()))?;
+            }
+        }
+        artifacts.retain(|artifact| artifact.exists());
+        eprintln!(
+            "\nSaved the input that hung as {}, and the stacks it was stuck in to {}",
+            strip_current_dir_prefix(&hang).display(),
+            strip_current_dir_prefix(&hang::stacks_path(&hang)).display()
+        );
+        artifacts.push(hang);
+        Ok(())
+    }
+
+    /// Move the inputs that leaked among `artifacts` to the target's leaks
+    /// directory, updating their paths.
+    fn save_leaks(&self, target: &str, artifacts: &mut [PathBuf]) -> Result<()> {
+        let dir = self.leaks_dir(target)?;
+        let mut saved = 0;
+        for artifact in artifacts.iter_mut() {
+            let name = match artifact.file_name() {
+                Some(name) if name.to_string_lossy().starts_with(libfuzzer::LEAK_PREFIX) => name,
+                _ => continue,
+            };
+            let leak = dir.join(name);
+            fs::rename(&*artifact, &leak).with_context(|| {
+                format!(
+                    "failed to move {} to {}",
+                    artifact.display(),
+                    leak.display()
+                )
+            })?;
+            *artifact = leak;
+            saved += 1;
+        }
+        if saved > 0 {
+            eprintln!(
+                "\nSaved {} input(s) that leak memory to {}",
+                saved,
+                strip_current_dir_prefix(&dir).display()
+            );
+        }
+        Ok(())
+    }
+
+    /// The signatures of the crashes in `--ignore-known-crashes`, if given.
+    fn known_crashes(&self, run: &options::Run, target: &str) -> Result<BTreeSet<String>> {
+        let dir = match &run.ignore_known_crashes {
+            Some(dir) => dir,
+            None => return Ok(BTreeSet::new()),
+        };
+        let mut known = BTreeSet::new();
+        for input in collect_files(dir)? {
+            if let Some(crash) = self.reproduce_crash(&run.build, target, &input)? {
+                known.insert(crash.signature());
+            }
+        }
+        eprintln!(
+            "Loaded {} known crash signature(s) from {}\n",
+            known.len(),
+            dir.display()
+        );
+        Ok(known)
+    }
+
+    /// Reproduce artifacts, and keep one for each crash signature that isn't
+    /// already in `known`, adding those to it. Artifacts that don't reproduce
+    /// can't be told apart, so they're always kept.
+    fn unknown_crashes(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifacts: &[PathBuf],
+        known: &mut BTreeSet<String>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut unknown = Vec::new();
+        for artifact in artifacts {
+            match self.reproduce_crash(build, target, artifact)? {
+                Some(crash) if !known.insert(crash.signature()) => {}
+                _ => unknown.push(artifact.clone()),
+            }
+        }
+        Ok(unknown)
+    }
+
+    /// Fuzz a target with only the inputs that `--focus-changed` and the
+    /// input filters select, copied to `focus_dir`, and copy the inputs that
+    /// fuzzing adds back into the corpus they came from.
+    fn exec_fuzz_focused(&self, run: &options::Run, target: &str) -> Result<()> {
+        let mut focused = run.clone();
+        focused.focus_changed = None;
+        focused.filter = options::InputFilter::default();
+        let inputs = match self.focus_inputs(run, target)? {
+            Some(inputs) => inputs,
+            None => return self.fuzz(&focused),
+        };
+
+        let dirs: Vec<PathBuf> = run
+            .corpus
+            .iter()
+            .map(PathBuf::from)
+            .filter(|path| path.is_dir())
+            .collect();
+        if !run.corpus.is_empty() && dirs.is_empty() {
+            // Artifact files are only run once each, so there's nothing to
+            // copy back.
+            focused.corpus = inputs.iter().map(|i| i.display().to_string()).collect();
+            return self.fuzz(&focused);
+        }
+
+        let dir = self.focus_dir(target);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("failed to remove {}", dir.display()))?;
+        }
+        copy_inputs(&inputs, &dir)?;
+        focused.corpus = vec![dir.display().to_string()];
+        let result = self.fuzz(&focused);
+        // The seeds stay where they are, out of the corpus.
+        let seeds = if run.corpus.is_empty() {
+            self.seeds_for(target)?
+        } else {
+            Vec::new()
+        };
+        for input in &inputs {
+            if seeds.iter().any(|seeds| input.starts_with(seeds)) {
+                let copy = dir.join(input.file_name().unwrap());
+                fs::remove_file(&copy)
+                    .with_context(|| format!("failed to remove {}", copy.display()))?;
+            }
+        }
+        let corpus = match dirs.into_iter().next() {
+            Some(corpus) => corpus,
+            None => self.corpus_for(target)?,
+        };
+        self.merge_focus_corpus(target, &dir, &corpus)?;
+        result
+    }
+
+    /// The inputs of the corpus that `run` fuzzes that pass its input
+    /// filters, ranked by `rank_changed_inputs` with `--focus-changed`.
+    /// Returns `None` to fuzz with the whole corpus.
+    fn focus_inputs(&self, run: &options::Run, target: &str) -> Result<Option<Vec<PathBuf>>> {
+        let mut inputs = Vec::new();
+        if run.corpus.is_empty() {
+            inputs = collect_files(&self.corpus_for(target)?)?;
+            for seeds in self.seeds_for(target)? {
+                inputs.extend(collect_files(&seeds)?);
+            }
+        }
+        for corpus in run.corpus.iter().map(Path::new) {
+            if corpus.is_dir() {
+                inputs.extend(collect_files(corpus)?);
+            } else {
+                inputs.push(corpus.to_path_buf());
+            }
+        }
+
+        let fallback = if run.filter.is_active() {
+            inputs = filter_inputs(&run.filter, inputs)?;
+            "every input that passes the filters"
+        } else {
+            "the whole corpus"
+        };
+        let ranked = match &run.focus_changed {
+            Some(base) => self.rank_changed_inputs(run, target, base, &inputs, fallback)?,
+            None => None,
+        };
+        match ranked {
+            None if run.filter.is_active() => Ok(Some(inputs)),
+            ranked => Ok(ranked),
+        }
+    }
+
+    /// Rank `inputs` by how many of the code regions changed since the git
+    /// revision `base` each one covers, keeping only those that cover any.
+    /// Returns `None`, to fuzz with `fallback` instead, when nothing changed
+    /// or no input reaches the changes.
+    fn rank_changed_inputs(
+        &self,
+        run: &options::Run,
+        target: &str,
+        base: &str,
+        inputs: &[PathBuf],
+        fallback: &str,
+    ) -> Result<Option<Vec<PathBuf>>> {
+        let sources = self.root_project.join("src");
+        let mut diff = Command::new("git");
+        diff.current_dir(&self.root_project)
+            .args([
+                "diff",
+                "--unified=0",
+                "--no-prefix",
+                "--no-color",
+                "--no-ext-diff",
+            ])
+            .arg("--relative")
+            .arg(base)
+            .arg("--")
+            .arg(&sources);
+        let output = diff
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", diff))?;
+        if !output.status.success() {
+            bail!(
+                "failed to diff the sources against `{}`: {:?}\n\n{}",
+                base,
+                diff,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let changed =
+            coverage::changed_lines(&String::from_utf8_lossy(&output.stdout), &self.root_project);
+        if changed.is_empty() {
+            eprintln!(
+                "warning: nothing in {} changed since `{}`, fuzzing with {}",
+                strip_current_dir_prefix(&sources).display(),
+                base,
+                fallback
+            );
+            return Ok(None);
+        }
+
+        let mut build = libfuzzer_only(
+            &self.build_options_for(&run.build, Some(target)),
+            "run --focus-changed",
+        )?;
+        build.coverage = true;
+        build.sanitizer = Some(Sanitizer::None);
+        self.exec_build(&build, Some(target))?;
+        let binary = self.target_binary(&build, target);
+
+        // Each input is replayed on its own, to tell which changes it reaches.
+        let scratch = tempfile::TempDir::new_in(self.path())?;
+        let mut changed_regions = BTreeSet::new();
+        let mut ranked = Vec::new();
+        let mut crashed = 0;
+        for (i, input) in inputs.iter().enumerate() {
+            let raw_dir = scratch.path().join(i.to_string());
+            let regions = match replay_coverage(&build, &binary, input, &raw_dir, &sources)? {
+                (_, Some(regions)) => regions,
+                (_, None) => {
+                    crashed += 1;
+                    continue;
+                }
+            };
+            let mut score = 0;
+            for (region, covered) in regions {
+                if coverage::touches(&region, &changed) {
+                    score += covered as usize;
+                    changed_regions.insert(region);
+                }
+            }
+            if score > 0 {
+                ranked.push((score, input));
+            }
+        }
+        if crashed > 0 {
+            eprintln!(
+                "warning: skipped {} input(s) that crashed without writing a coverage profile",
+                crashed
+            );
+        }
+        if ranked.is_empty() {
+            eprintln!(
+                "warning: none of the {} input(s) in the corpus reach the {} code region(s) \
+                 changed since `{}`, fuzzing with {}",
+                inputs.len(),
+                changed_regions.len(),
+                base,
+                fallback
+            );
+            return Ok(None);
+        }
+        ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+        eprintln!(
+            "{} of {} input(s) reach the {} code region(s) changed since `{}`:\n",
+            ranked.len(),
+            inputs.len(),
+            changed_regions.len(),
+            base
+        );
+        for (score, input) in ranked.iter().take(MAX_FOCUS_LISTED) {
+            let name = input.file_name().unwrap_or_default().to_string_lossy();
+            eprintln!("{:>8}  {}", score, name);
+        }
+        if ranked.len() > MAX_FOCUS_LISTED {
+            eprintln!("{:>8}  ...", "");
+        }
+        eprintln!();
+        Ok(Some(
+            ranked.into_iter().map(|(_, input)| input.clone()).collect(),
+        ))
+    }
+
+    /// Copy the inputs that fuzzing added to a focused corpus back into
+    /// `corpus`.
+    fn merge_focus_corpus(&self, target: &str, dir: &Path, corpus: &Path) -> Result<()> {
+        for input in collect_files(dir)? {
+            let copy = corpus.join(input.file_name().unwrap());
+            if !copy.exists() {
+                fs::copy(&input, &copy).with_context(|| {
+                    format!("failed to copy {} to {}", input.display(), copy.display())
+                })?;
+            }
+        }
+        if self.is_corpus_of(corpus, target)? {
+            self.track_corpus(target, Origin::Fuzz, Some(stats::run_id()))?;
+        }
+        Ok(())
+    }
+
+    /// Save the inputs of a run since `since` that took longer than
+    /// `--slow-threshold-ms` to `fuzz/slow/<target>/`: the slow units that
+    /// libFuzzer wrote as artifacts, which are moved there, and the new corpus
+    /// inputs that are still as slow when timed again.
+    fn save_slow_inputs(
+        &self,
+        run: &options::Run,
+        target: &str,
+        since: &time::SystemTime,
+    ) -> Result<()> {
+        let threshold = match run.slow_threshold_ms {
+            Some(threshold) => threshold,
+            None => return Ok(()),
+        };
+        let is_new = |path: &PathBuf| {
+            fs::metadata(path)
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified > *since)
+        };
+        let is_slow_unit = |path: &Path| {
+            path.file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with(libfuzzer::SLOW_UNIT_PREFIX))
+        };
+        let corpus = match run.corpus.first().map(PathBuf::from) {
+            Some(dir) if dir.is_dir() => dir,
+            _ => self.corpus_for(target)?,
+        };
+        let mut inputs: Vec<PathBuf> = collect_files(&self.artifacts_for(target)?)?
+            .into_iter()
+            .filter(|path| is_slow_unit(path) && is_new(path))
+            .collect();
+        inputs.extend(collect_files(&corpus)?.into_iter().filter(is_new));
+        if inputs.is_empty() {
+            return Ok(());
+        }
+
+        let timings = self.time_inputs(&run.build, target, &inputs, 1)?;
+        let dir = self.slow_dir(target)?;
+        let timings_path = self.slow_timings_path(target);
+        let mut recorded = slow::load(&timings_path)?;
+        let mut saved = 0;
+        for input in &inputs {
+            let ms = timings.get(input).copied();
+            if !is_slow_unit(input) && ms.is_none_or(|ms| ms < threshold) {
+                continue;
+            }
+            let name = input.file_name().unwrap().to_string_lossy().into_owned();
+            let copy = dir.join(&name);
+            fs::copy(input, &copy).with_context(|| {
+                format!("failed to copy {} to {}", input.display(), copy.display())
+            })?;
+            if is_slow_unit(input) {
+                fs::remove_file(input)
+                    .with_context(|| format!("failed to remove {}", input.display()))?;
+            }
+            if let Some(ms) = ms {
+                recorded.insert(name, ms);
+            }
+            saved += 1;
+        }
+        slow::save(&timings_path, &recorded)?;
+        if saved > 0 {
+            eprintln!(
+                "\nSaved {} input(s) slower than {} ms to {}; time them again with \
+                 `cargo fuzz bench {}`",
+                saved,
+                threshold,
+                strip_current_dir_prefix(&dir).display(),
+                target
+            );
+        }
+        Ok(())
+    }
+
+    /// Time how long each input takes to run, in milliseconds, averaged over
+    /// `runs` runs. Inputs that make the fuzz target fail aren't timed.
+    fn time_inputs(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        inputs: &[PathBuf],
+        runs: u64,
+    ) -> Result<BTreeMap<PathBuf, u64>> {
+        // Inputs that crash would be written to the target's artifacts.
+        let scratch = tempfile::TempDir::new().context("failed to create temp dir")?;
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(scratch.path().join(""));
+
+        let mut timings = BTreeMap::new();
+        let mut rest = inputs;
+        while !rest.is_empty() {
+            let mut cmd = self.binary_run(build, target)?;
+            cmd.arg(&artifact_arg)
+                .arg(format!("-runs={}", runs))
+                .args(rest)
+                .stdin(Stdio::null());
+            let output = cmd
+                .output()
+                .with_context(|| format!("failed to run command: {:?}", cmd))?;
+            let mut timed = 0;
+            for (input, ms) in String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .filter_map(libfuzzer::parse_executed)
+            {
+                timings.insert(input, ms / runs);
+                timed += 1;
+            }
+            if output.status.success() {
+                break;
+            }
+            // Skip the input that the target failed on, and time the rest.
+            rest = &rest[(timed + 1).min(rest.len())..];
+        }
+        Ok(timings)
+    }
+
+    /// Fuzz a target in libFuzzer's fork mode, where the crashes, OOMs, and
+    /// timeouts of the jobs it forks are saved as artifacts without stopping
+    /// the run. If libFuzzer itself exits unexpectedly after it got going, it
+    /// is restarted for whatever remains of `--max-total-time`, and a summary
+    /// of the whole run is printed at the end.
+    fn exec_fuzz_fork(&self, run: &options::Run, target: &str) -> Result<()> {
+        self.exec_build(&run.build, Some(target))?;
+        let mut known_crashes = self.known_crashes(run, target)?;
+        let before_fuzzing = time::SystemTime::now();
+        let start = time::Instant::now();
+        let format = run.build.message_format;
+        let stall = run.until_new_coverage_stalls.is_some();
+        let mut monitor = self.monitor(run, target)?;
+        let status_server = start_status_server(run, target)?;
+        let notifier = self.notifier(run, target)?;
+
+        let mut restarts = 0;
+        let mut last_status = None;
+        let (status, stalled) = loop {
+            let mut attempt = run.clone();
+            attempt.max_total_time = run
+                .max_total_time
+                .map(|secs| secs.saturating_sub(start.elapsed().as_secs()).max(1));
+            let mut cmd = self.fuzz_command(&attempt, target, stall)?;
+            let mut recorder = stats::RunRecorder::new();
+            let mut reported = false;
+            let (status, stalled) = spawn_watched(
+                &mut cmd,
+                run.until_new_coverage_stalls
+                    .map(libfuzzer::StallDetector::new),
+                monitor.as_mut(),
+                None,
+                |line| {
+                    emit_fuzzer_messages(format, target, line);
+                    recorder.observe(line);
+                    if let Some(server) = &status_server {
+                        server.observe(line);
+                    }
+                    if let Some(notifier) = &notifier {
+                        notifier.observe(line);
+                    }
+                    if let Some(status) = libfuzzer::Status::parse(line) {
+                        reported = true;
+                        last_status = Some(status);
+                    }
+                },
+            )?;
+            self.record_run(run, target, recorder)?;
+
+            // libFuzzer failing before it reported any progress would most
+            // likely fail again the same way.
+            let out_of_time = run
+                .max_total_time
+                .is_some_and(|secs| start.elapsed().as_secs() >= secs);
+            if stalled || status.success() || !reported || out_of_time {
+                break (status, stalled);
+            }
+            restarts += 1;
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!(
+                "libFuzzer exited with {}; restarting fork mode (restart {})\n",
+                status, restarts
+            );
+        };
+        self.save_slow_inputs(run, target, &before_fuzzing)?;
+        print_monitor_summary(target, monitor.as_ref());
+
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(target, &before_fuzzing)?
+            .into_iter()
+            .collect();
+        artifacts.sort();
+        let saved = artifacts.len();
+        if run.ignore_known_crashes.is_some() {
+            artifacts = self.unknown_crashes(&run.build, target, &artifacts, &mut known_crashes)?;
+        }
+        self.run_crash_hooks(&run.build, target, "run", &artifacts);
+        let mut kinds = BTreeMap::<String, usize>::new();
+        for artifact in &artifacts {
+            let name = artifact
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let kind = name.split('-').next().unwrap_or_default().to_string();
+            *kinds.entry(kind).or_default() += 1;
+        }
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("Fork mode summary for `{}`:\n", target);
+        eprintln!("\t{:<12} {}", "jobs:", run.fork.unwrap_or(1));
+        eprintln!("\t{:<12} {}", "restarts:", restarts);
+        if let Some(last) = &last_status {
+            eprintln!("\t{:<12} {}", "execs:", last.execs);
+            if let Some(coverage) = last.coverage {
+                eprintln!("\t{:<12} {}", "coverage:", coverage);
+            }
+        }
+        eprintln!("\t{:<12} {}", "artifacts:", artifacts.len());
+        for (kind, count) in &kinds {
+            eprintln!("\t  {:<10} {}", format!("{}:", kind), count);
+        }
+        if saved > artifacts.len() {
+            eprintln!("\t{:<12} {}", "ignored:", saved - artifacts.len());
+        }
+
+        let success = artifacts.is_empty() && (stalled || status.success());
+        format.emit(&Message::RunFinished {
+            target,
+            success,
+            exit_code: status.code(),
+            stalled,
+        });
+        if let Some(notifier) = &notifier {
+            notifier.finish(success);
+        }
+        if let Some(path) = &run.sarif {
+            self.write_run_sarif(&run.build, &[target], &before_fuzzing, path)?;
+        }
+        if !artifacts.is_empty() {
+            // Fork mode can save many artifacts for the same bug, so rather
+            // than printing each one, point to the tools that sort them out.
+            eprintln!("\nNew artifacts:\n");
+            for artifact in &artifacts {
+                eprintln!("\t{}", strip_current_dir_prefix(artifact).display());
+            }
+            eprintln!(
+                "\nGroup them by unique crash with:\n\n\tcargo fuzz triage {0}\n\n\
+                 Minimize and deduplicate them with:\n\n\tcargo fuzz tmin --batch {0}\n",
+                target
+            );
+            eprintln!("{:─<80}\n", "");
+            return Err(exit::fail(
+                ExitCode::CrashFound,
+                format!("fork mode saved {} new artifact(s)", artifacts.len()),
+            ));
+        }
+        eprintln!();
+        if !stalled && !status.success() {
+            bail!("Fuzz target exited with {}", crash::describe_exit(status));
+        }
+        Ok(())
+    }
+
+    /// Fuzz a target with one libFuzzer process per job, all sharing its
+    /// corpus, writing their output to `fuzz/logs/<target>/` and showing a
+    /// dashboard of their progress instead. Like a plain run, this stops at
+    /// the first crash.
+    fn exec_fuzz_tui(&self, run: &options::Run, target: &str) -> Result<()> {
+        self.exec_build(&run.build, Some(target))?;
+        let config = self.config.target(Some(target));
+        let jobs = run.jobs.or(config.jobs).unwrap_or(1) as usize;
+        let logs = self.logs_dir(target)?;
+        let corpus = match run.corpus.first().map(PathBuf::from) {
+            Some(dir) if dir.is_dir() => dir,
+            _ => self.corpus_for(target)?,
+        };
+        let count_corpus = || collect_files(&corpus).map_or(0, |files| files.len());
+        let dashboard = Arc::new(Mutex::new(dashboard::Dashboard::new(
+            target,
+            jobs,
+            strip_current_dir_prefix(&logs),
+            count_corpus(),
+        )));
+        let terminal = std::io::stderr().is_terminal();
+        let before_fuzzing = time::SystemTime::now();
+
+        // Each worker is a libFuzzer process of its own, rather than a job of
+        // `-fork` mode, so that each one's progress can be told apart.
+        let mut worker_run = run.clone();
+        worker_run.jobs = Some(1);
+        let format = run.build.message_format;
+        let mut workers = Vec::new();
+        for i in 0..jobs {
+            let path = logs.join(format!("worker-{}.log", i));
+            let log = fs::File::create(&path)
+                .with_context(|| format!("failed to create {}", path.display()))?;
+            let mut cmd = self.fuzz_command(&worker_run, target, true)?;
+            cmd.stdin(Stdio::null())
+                .stdout(log.try_clone().context("failed to clone the log file")?)
+                .stderr(Stdio::piped());
+            let mut child = cmd
+                .spawn()
+                .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+            let stderr = child.stderr.take().expect("stderr is piped");
+            let dashboard = dashboard.clone();
+            let target = target.to_string();
+            let reader = thread::spawn(move || {
+                let mut log = log;
+                let mut recorder = stats::RunRecorder::new();
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    let _ = writeln!(log, "{}", line);
+                    emit_fuzzer_messages(format, &target, &line);
+                    recorder.observe(&line);
+                    dashboard.lock().unwrap().observe(i, &line);
+                }
+                recorder
+            });
+            workers.push(Some((cmd, child, reader, path)));
+        }
+
+        // Wait for every worker to exit, stopping the rest once one fails.
+        let mut failure = None;
+        let mut running = jobs;
+        while running > 0 {
+            for (i, slot) in workers.iter_mut().enumerate() {
+                let (cmd, child, _, _) = match slot {
+                    Some(worker) => worker,
+                    None => continue,
+                };
+                let status = match child.try_wait().with_context(|| {
+                    format!("failed to wait on child process for command: {:?}", cmd)
+                })? {
+                    Some(status) => status,
+                    None if failure.is_some() => {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        let (_, _, reader, _) = slot.take().unwrap();
+                        if let Ok(recorder) = reader.join() {
+                            self.record_run(run, target, recorder)?;
+                        }
+                        dashboard.lock().unwrap().exited(i, None, None);
+                        running -= 1;
+                        continue;
+                    }
+                    None => continue,
+                };
+                let (_, _, reader, path) = slot.take().unwrap();
+                if let Ok(recorder) = reader.join() {
+                    self.record_run(run, target, recorder)?;
+                }
+                let crash = if status.success() {
+                    None
+                } else {
+                    failure.get_or_insert(status);
+                    fs::read(&path)
+                        .ok()
+                        .and_then(|log| Crash::parse(&String::from_utf8_lossy(&log)))
+                };
+                dashboard.lock().unwrap().exited(i, Some(status), crash);
+                running -= 1;
+            }
+
+            let mut dashboard = dashboard.lock().unwrap();
+            dashboard.corpus = count_corpus();
+            if terminal {
+                eprint!("{}{}", dashboard::CLEAR, dashboard.render());
+            }
+            drop(dashboard);
+            if running > 0 {
+                thread::sleep(STALL_POLL_INTERVAL);
+            }
+        }
+        if !terminal {
+            eprint!("{}", dashboard.lock().unwrap().render());
+        }
+        self.save_slow_inputs(run, target, &before_fuzzing)?;
+
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(target, &before_fuzzing)?
+            .into_iter()
+            .collect();
+        artifacts.sort();
+        self.run_crash_hooks(&run.build, target, "run", &artifacts);
+        format.emit(&Message::RunFinished {
+            target,
+            success: failure.is_none(),
+            exit_code: failure.map_or(Some(0), |status| status.code()),
+            stalled: false,
+        });
+        if let Some(path) = &run.sarif {
+            self.write_run_sarif(&run.build, &[target], &before_fuzzing, path)?;
+        }
+        let status = match failure {
+            Some(status) => status,
+            None => return Ok(()),
+        };
+        if artifacts.is_empty() {
+            bail!("Fuzz target exited with {}", crash::describe_exit(status));
+        }
+        eprintln!();
+        self.print_artifacts(&run.build, target, &artifacts)?;
+        eprintln!("{:─<80}\n", "");
+        Err(exit::fail(
+            ExitCode::CrashFound,
+            format!("Fuzz target exited with {}", crash::describe_exit(status)),
+        ))
+    }
+
+    /// Fuzz a target with each of `Sanitizer::EACH` in turn, each build in a
+    /// target directory of its own, and summarize how each run ended.
+    fn exec_fuzz_each_sanitizer(&self, run: &options::Run, target: &str) -> Result<()> {
+        if run.sarif.is_some() {
+            bail!("`--sarif` can't be used with `--sanitizer all`");
+        }
+        if run.status_addr.is_some() {
+            bail!("`--status-addr` can't be used with `--sanitizer all`");
+        }
+        let triple = self.build_options_for(&run.build, Some(target)).triple;
+        let mut results = Vec::new();
+        for &sanitizer in Sanitizer::EACH.iter().filter(|s| s.supports(&triple)) {
+            let name = match sanitizer {
+                Sanitizer::None => "no sanitizer".to_string(),
+                sanitizer => format!("the {} sanitizer", sanitizer),
+            };
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!("Fuzzing `{}` with {}\n", target, name);
+            let mut run = run.clone();
+            run.build.sanitizer = Some(sanitizer);
+            let result = self.fuzz(&run);
+            if let Err(e) = &result {
+                eprintln!("{:#}", e);
+            }
+            results.push((name, result));
+        }
+
+        let mut failed = 0;
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("Summary:\n");
+        for (name, result) in &results {
+            match result {
+                Ok(()) => eprintln!("\t{}: ok", name),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("\t{}: {}", name, e);
+                }
+            }
+        }
+        eprintln!("\n{:─<80}\n", "");
+
+        if failed > 0 {
+            let message = format!(
+                "fuzzing `{}` failed with {} of {} sanitizers",
+                target,
+                failed,
+                results.len()
+            );
+            // A crash with any sanitizer matters most, and otherwise only
+            // failing to build with every one of them counts as a build failure.
+            let errors = results
+                .iter()
+                .filter_map(|(_, result)| result.as_ref().err());
+            let code = if errors.clone().any(|e| exit::is(e, ExitCode::CrashFound)) {
+                Some(ExitCode::CrashFound)
+            } else if failed == results.len()
+                && errors.clone().all(|e| exit::is(e, ExitCode::BuildFailed))
+            {
+                Some(ExitCode::BuildFailed)
+            } else {
+                None
+            };
+            return Err(match code {
+                Some(code) => exit::fail(code, message),
+                None => anyhow!(message),
+            });
+        }
+        Ok(())
+    }
+
+    /// Fuzz a given fuzz target with honggfuzz or LibAFL.
+    ///
+    /// Unlike libFuzzer, these keep going after finding a crash, and exit
+    /// successfully either way, so crashes are told apart by the new artifacts
+    /// they leave behind.
+    fn exec_other_engine(&self, run: &options::Run, target: &str, engine: Engine) -> Result<()> {
+        if run.until_new_coverage_stalls.is_some() {
+            bail!("`--until-new-coverage-stalls` is only supported with the libFuzzer engine");
+        }
+        if run.fork.is_some() {
+            bail!("`--fork` is only supported with the libFuzzer engine");
+        }
+        if self
+            .build_options_for(&run.build, Some(target))
+            .runner
+            .is_some()
+        {
+            bail!("`--runner` is only supported with the libFuzzer engine");
+        }
+        if run.slow_threshold_ms.is_some() {
+            bail!("`--slow-threshold-ms` is only supported with the libFuzzer engine");
+        }
+        if run.tui {
+            bail!("`--tui` is only supported with the libFuzzer engine");
+        }
+        self.exec_build(&run.build, Some(target))?;
+        let mut cmd = self.fuzz_command(run, target, false)?;
+
+        let before_fuzzing = time::SystemTime::now();
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        self.track_corpus(target, Origin::Fuzz, Some(stats::run_id()))?;
+        let mut artifacts: Vec<_> = self
+            .get_artifacts_since(target, &before_fuzzing)?
+            .into_iter()
+            .collect();
+        artifacts.sort();
+        self.run_crash_hooks(&run.build, target, "run", &artifacts);
+
+        let format = run.build.message_format;
+        for artifact in &artifacts {
+            format.emit(&Message::CrashFound {
+                target,
+                artifact: artifact.display().to_string(),
+            });
+        }
+        format.emit(&Message::RunFinished {
+            target,
+            success: status.success() && artifacts.is_empty(),
+            exit_code: status.code(),
+            stalled: false,
+        });
+        if let Some(path) = &run.sarif {
+            self.write_run_sarif(&run.build, &[target], &before_fuzzing, path)?;
+        }
+
+        if !artifacts.is_empty() {
+            // The artifacts reproduce with libFuzzer too, which we know how to
+            // debug format.
+            let mut build = run.build.clone();
+            build.engine = Some(Engine::Libfuzzer);
+            self.print_new_artifacts(&build, target, &before_fuzzing)?;
+            eprintln!("{:─<80}\n", "");
+            return Err(exit::fail(
+                ExitCode::CrashFound,
+                format!("{} found {} crash(es)", engine, artifacts.len()),
+            ));
+        }
+        if !status.success() {
+            bail!("{} exited with {}", engine, status);
+        }
+        Ok(())
+    }
+
+    /// Fuzz every fuzz target, or every one matching a glob, concurrently.
+    ///
+    /// Each target's output is prefixed with its name, and a summary of how
+    /// each target exited is printed once they're all done.
+    pub fn exec_fuzz_all(&self, run: &options::Run) -> Result<()> {
+        if run.filter.is_active() {
+            bail!("the input filters can only be used to run a single fuzz target");
+        }
+        let targets: Vec<&String> = self
+            .targets
+            .iter()
+            .filter(|t| {
+                run.target.as_ref().is_none_or(|g| {
+                    utils::glob_match(g, t) || utils::glob_match(g, &self.target_subpath(t))
+                })
+            })
+            .collect();
+        if targets.is_empty() {
+            match &run.target {
+                Some(glob) => bail!("no fuzz targets match `{}`", glob),
+                None => bail!("no fuzz targets found"),
+            }
+        }
+        for target in &targets {
+            single_sanitizer(&self.build_options_for(&run.build, Some(target)))?;
+        }
+
+        self.exec_build(&run.build, None)?;
+        // Targets configured to use another engine need a build of their own.
+        let engine = self.build_options_for(&run.build, None).engine;
+        for target in &targets {
+            if self.build_options_for(&run.build, Some(target)).engine != engine {
+                self.exec_build(&run.build, Some(target))?;
+            }
+        }
+        for target in &targets {
+            self.print_run_sanitizer_options(run, target, &format!("[{}] ", target))?;
+        }
+        for target in &targets {
+            self.run_hook(target, Hook::PreRun, "run")?;
+        }
+        let before_fuzzing = time::SystemTime::now();
+
+        let mut children = Vec::new();
+        for target in &targets {
+            self.track_corpus(target, Origin::Seed, None)?;
+            let stall = run.until_new_coverage_stalls.is_some();
+            let mut cmd = self.fuzz_command(run, target, stall)?;
+            cmd.stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            let mut child = cmd
+                .spawn()
+                .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+            let format = run.build.message_format;
+            let prefix = format!("[{}] ", target);
+            let stdout = child.stdout.take().map(|out| {
+                let prefix = prefix.clone();
+                thread::spawn(move || {
+                    for line in BufReader::new(out).lines().map_while(Result::ok) {
+                        // Keep stdout for JSON messages only.
+                        if format.is_json() {
+                            eprintln!("{}{}", prefix, line);
+                        } else {
+                            println!("{}{}", prefix, line);
+                        }
+                    }
+                })
+            });
+            let stall = run
+                .until_new_coverage_stalls
+                .map(|limit| Arc::new(Mutex::new(libfuzzer::StallDetector::new(limit))));
+            let recorder = Arc::new(Mutex::new(Some(stats::RunRecorder::new())));
+            let stderr = child.stderr.take().map(|err| {
+                let target = target.to_string();
+                let stall = stall.clone();
+                let recorder = recorder.clone();
+                thread::spawn(move || {
+                    for line in BufReader::new(err).lines().map_while(Result::ok) {
+                        eprintln!("{}{}", prefix, line);
+                        emit_fuzzer_messages(format, &target, &line);
+                        if let Some(stall) = &stall {
+                            stall.lock().unwrap().observe(&line);
+                        }
+                        if let Some(recorder) = recorder.lock().unwrap().as_mut() {
+                            recorder.observe(&line);
+                        }
+                    }
+                })
+            });
+            children.push(Some((target, cmd, child, stdout, stderr, stall, recorder)));
+        }
+
+        // Wait for every target to exit, stopping any whose coverage stalls.
+        let mut results = Vec::new();
+        while results.len() < targets.len() {
+            for slot in children.iter_mut() {
+                let (_, cmd, child, _, _, stall, _) = match slot {
+                    Some(child) => child,
+                    None => continue,
+                };
+                let exited = child.try_wait().with_context(|| {
+                    format!("failed to wait on child process for command: {:?}", cmd)
+                })?;
+                let stalled = exited.is_none()
+                    && stall
+                        .as_ref()
+                        .is_some_and(|s| s.lock().unwrap().is_stalled());
+                let status = match exited {
+                    Some(status) => status,
+                    None if stalled => {
+                        let _ = child.kill();
+                        child.wait().with_context(|| {
+                            format!("failed to wait on child process for command: {:?}", cmd)
+                        })?
+                    }
+                    None => continue,
+                };
+                let (target, _, _, stdout, stderr, _, recorder) = slot.take().unwrap();
+                for thread in stdout.into_iter().chain(stderr) {
+                    let _ = thread.join();
+                }
+                if let Some(recorder) = recorder.lock().unwrap().take() {
+                    self.record_run(run, target, recorder)?;
+                }
+                if let Err(e) = self.run_hook(target, Hook::PostRun, "run") {
+                    eprintln!("warning: {:#}", e);
+                }
+                // honggfuzz exits successfully even when it found crashes.
+                let crashed = !self
+                    .get_artifacts_since(target, &before_fuzzing)?
+                    .is_empty();
+                run.build.message_format.emit(&Message::RunFinished {
+                    target,
+                    success: stalled || (status.success() && !crashed),
+                    exit_code: status.code(),
+                    stalled,
+                });
+                results.push((target, status, stalled, crashed));
+            }
+            if results.len() < targets.len() {
+                thread::sleep(STALL_POLL_INTERVAL);
+            }
+        }
+        results.sort_by_key(|(target, _, _, _)| *target);
+
+        if let Some(path) = &run.sarif {
+            let targets: Vec<&str> = targets.iter().map(|t| t.as_str()).collect();
+            self.write_run_sarif(&run.build, &targets, &before_fuzzing, path)?;
+        }
+
+        let mut failed = 0;
+        for (target, status, stalled, crashed) in &results {
+            if !stalled && (!status.success() || *crashed) {
+                failed += 1;
+                let mut artifacts: Vec<_> = self
+                    .get_artifacts_since(target, &before_fuzzing)?
+                    .into_iter()
+                    .collect();
+                artifacts.sort();
+                self.run_crash_hooks(&run.build, target, "run", &artifacts);
+                self.print_new_artifacts(&run.build, target, &before_fuzzing)?;
+            }
+        }
+
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("Summary:\n");
+        for (target, status, stalled, crashed) in &results {
+            if *stalled {
+                eprintln!("\t{}: ok (coverage stalled)", target);
+            } else if status.success() && !crashed {
+                eprintln!("\t{}: ok", target);
+            } else {
+                let artifacts = self.get_artifacts_since(target, &before_fuzzing)?.len();
+                eprintln!(
+                    "\t{}: exited with {} ({} new artifact(s))",
+                    target, status, artifacts
+                );
+            }
+        }
+        eprintln!("\n{:─<80}\n", "");
+
+        if failed > 0 {
+            let message = format!("{} of {} fuzz targets failed", failed, results.len());
+            if results
+                .iter()
+                .any(|(_, _, stalled, crashed)| *crashed && !stalled)
+            {
+                return Err(exit::fail(ExitCode::CrashFound, message));
+            }
+            bail!(message);
+        }
+        Ok(())
+    }
+
+    /// Fuzz every target, or every one matching a glob, in turn for a share
+    /// of a time budget, failing if any finds a crash that isn't already in
+    /// its artifacts directory. The corpora are pulled before fuzzing, and
+    /// minimized and pushed after.
+    pub fn exec_ci(&self, ci: &options::Ci) -> Result<()> {
+        let targets: Vec<&String> = self
+            .targets
+            .iter()
+            .filter(|t| {
+                ci.targets.as_ref().is_none_or(|g| {
+                    utils::glob_match(g, t) || utils::glob_match(g, &self.target_subpath(t))
+                })
+            })
+            .collect();
+        if targets.is_empty() {
+            match &ci.targets {
+                Some(glob) => bail!("no fuzz targets match `{}`", glob),
+                None => bail!("no fuzz targets found"),
+            }
+        }
+        for target in &targets {
+            single_sanitizer(&self.build_options_for(&ci.build, Some(target)))?;
+        }
+        let remote = |target: &str| {
+            let configured = self.config.target(Some(target)).corpus_remote.is_some();
+            (ci.remote.is_some() || configured).then(|| options::CorpusSync {
+                remote: ci.remote.clone(),
+                target: Some(target.to_string()),
+            })
+        };
+        for target in &targets {
+            if let Some(sync) = remote(target) {
+                self.exec_corpus_sync(&sync, false)?;
+            }
+        }
+
+        self.exec_build(&ci.build, None)?;
+        let engine = self.build_options_for(&ci.build, None).engine;
+        for target in &targets {
+            if self.build_options_for(&ci.build, Some(target)).engine != engine {
+                self.exec_build(&ci.build, Some(target))?;
+            }
+        }
+        // Artifacts reproduce, and corpora are minimized, with libFuzzer
+        // whichever engine fuzzes.
+        let mut libfuzzer = ci.build.clone();
+        libfuzzer.engine = Some(Engine::Libfuzzer);
+
+        let budget = (ci.total_time / targets.len() as u64).max(1);
+        let mut results = Vec::new();
+        for target in &targets {
+            let mut known = BTreeSet::new();
+            for artifact in collect_files(&self.artifacts_for(target)?)? {
+                if let Some(crash) = self.reproduce_crash(&libfuzzer, target, &artifact)? {
+                    known.insert(crash.signature());
+                }
+            }
+            eprintln!(
+                "Fuzzing `{}` for {} seconds, with {} known crash signature(s)\n",
+                target,
+                budget,
+                known.len()
+            );
+
+            let run = options::Run {
+                build: ci.build.clone(),
+                target: Some(target.to_string()),
+                corpus: Vec::new(),
+                all: false,
+                max_total_time: Some(budget),
+                until_new_coverage_stalls: None,
+                jobs: None,
+                fork: None,
+                ignore_known_crashes: None,
+                max_crashes: None,
+                timeout: None,
+                rss_limit: None,
+                malloc_limit: None,
+                record_trace: None,
+                save_hangs: false,
+                auto_tmin: false,
+                auto_tmin_time: None,
+                focus_changed: None,
+                filter: options::InputFilter::default(),
+                features: options::EngineFeatures::default(),
+                mode: None,
+                slow_threshold_ms: None,
+                monitor: false,
+                status_addr: None,
+                notify_webhook: None,
+                notify_plateau: None,
+                tui: false,
+                sarif: None,
+                asan_options: Vec::new(),
+                dicts: Vec::new(),
+                seed: None,
+                replay_manifest: None,
+                args: Vec::new(),
+            };
+            self.track_corpus(target, Origin::Seed, None)?;
+            let started = time::Instant::now();
+            let before_fuzzing = time::SystemTime::now();
+            let status = self.with_hooks(target, "run", || {
+                let mut cmd = self.fuzz_command(&run, target, false)?;
+                cmd.stdin(Stdio::null())
+                    .status()
+                    .with_context(|| format!("failed to execute: {:?}", cmd))
+            })?;
+            let seconds = started.elapsed().as_secs();
+            self.track_corpus(target, Origin::Fuzz, None)?;
+
+            let mut artifacts: Vec<_> = self
+                .get_artifacts_since(target, &before_fuzzing)?
+                .into_iter()
+                .collect();
+            artifacts.sort();
+            let mut new_crashes = Vec::new();
+            let mut new_artifacts = Vec::new();
+            let mut known_crashes = 0;
+            for artifact in &artifacts {
+                let (hash, description) =
+                    match self.reproduce_crash(&libfuzzer, target, artifact)? {
+                        Some(crash) if !known.insert(crash.signature()) => {
+                            known_crashes += 1;
+                            continue;
+                        }
+                        Some(crash) => (
+                            Some(crash.hash()),
+                            crash
+                                .to_string()
+                                .lines()
+                                .next()
+                                .unwrap_or_default()
+                                .to_string(),
+                        ),
+                        None => (None, "doesn't reproduce".to_string()),
+                    };
+                new_crashes.push(ci::NewCrash {
+                    artifact: strip_current_dir_prefix(artifact).display().to_string(),
+                    hash,
+                    description,
+                });
+                new_artifacts.push(artifact.clone());
+            }
+            self.run_crash_hooks(&ci.build, target, "ci", &new_artifacts);
+            let error = (!status.success() && artifacts.is_empty()).then(|| {
+                format!(
+                    "the fuzzer exited with {} without saving an artifact",
+                    status
+                )
+            });
+            results.push(ci::TargetResult {
+                target: target.to_string(),
+                seconds,
+                new_crashes,
+                known_crashes,
+                error,
+                corpus_size: None,
+            });
+        }
+
+        for (target, result) in targets.iter().zip(&mut results) {
+            if !ci.no_minimize {
+                let cmin = options::Cmin {
+                    build: libfuzzer.clone(),
+                    target: Some(target.to_string()),
+                    corpus: None,
+                    all: false,
+                    jobs: None,
+                    prune_older_than: None,
+                    preserve: Vec::new(),
+                    pin_artifacts: false,
+                    filter: options::InputFilter::default(),
+                };
+                self.exec_cmin(&cmin)?;
+                result.corpus_size = Some(collect_files(&self.corpus_for(target)?)?.len());
+            }
+            if let Some(sync) = remote(target) {
+                self.exec_corpus_sync(&sync, true)?;
+            }
+        }
+
+        if let Some(path) = &ci.junit {
+            fs::write(path, ci::render_junit(&results))
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        if let Some(path) = &ci.json {
+            fs::write(path, ci::render_json(&results))
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("Summary:\n");
+        eprint!("{}", ci::render_text(&results));
+        eprintln!("\n{:─<80}\n", "");
+
+        let crashed = results.iter().filter(|r| !r.new_crashes.is_empty()).count();
+        let failed = results.iter().filter(|r| !r.passed()).count();
+        if crashed > 0 {
+            return Err(exit::fail(
+                ExitCode::CrashFound,
+                format!(
+                    "{} of {} fuzz targets found new crashes",
+                    crashed,
+                    results.len()
+                ),
+            ));
+        }
+        if failed > 0 {
+            bail!("{} of {} fuzz targets failed", failed, results.len());
+        }
+        Ok(())
+    }
+
+    /// Fuzz a target, and rebuild and restart it whenever its sources change.
+    pub fn exec_watch(&self, watch: &options::Watch) -> Result<()> {
+        single_sanitizer(&self.build_options_for(&watch.build, Some(&watch.target)))?;
+        let run = options::Run {
+            build: watch.build.clone(),
+            target: Some(watch.target.clone()),
+            corpus: watch.corpus.clone(),
+            all: false,
+            max_total_time: None,
+            until_new_coverage_stalls: None,
+            jobs: watch.jobs,
+            fork: None,
+            ignore_known_crashes: None,
+            max_crashes: None,
+            timeout: None,
+            rss_limit: None,
+            malloc_limit: None,
+            record_trace: None,
+            save_hangs: false,
+            auto_tmin: false,
+            auto_tmin_time: None,
+            focus_changed: None,
+            filter: options::InputFilter::default(),
+            features: options::EngineFeatures::default(),
+            mode: None,
+            slow_threshold_ms: None,
+            monitor: false,
+            status_addr: None,
+            notify_webhook: None,
+            notify_plateau: None,
+            tui: false,
+            sarif: None,
+            asan_options: Vec::new(),
+            dicts: Vec::new(),
+            seed: None,
+            replay_manifest: None,
+            args: watch.args.clone(),
+        };
+        let target = &watch.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+
+        let fuzz_targets_dir = self.fuzz_targets_dir();
+        let mut sources = self.watched_sources(&fuzz_targets_dir)?;
+        loop {
+            let mut fuzzer = match self.exec_build(&run.build, Some(target)) {
+                Ok(()) => {
+                    let before_fuzzing = time::SystemTime::now();
+                    let mut cmd = self.fuzz_command(&run, target, true)?;
+                    let child = cmd
+                        .spawn()
+                        .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+                    Some((child, before_fuzzing))
+                }
+                Err(e) => {
+                    eprintln!("Error: {:?}\n\nWaiting for changes...\n", e);
+                    None
+                }
+            };
+
+            let changed = loop {
+                thread::sleep(WATCH_POLL_INTERVAL);
+                let now = self.watched_sources(&fuzz_targets_dir)?;
+                if now != sources {
+                    // Editors often write several files at once, so give them
+                    // a moment to finish.
+                    thread::sleep(WATCH_POLL_INTERVAL);
+                    sources = self.watched_sources(&fuzz_targets_dir)?;
+                    break true;
+                }
+                if let Some((child, before_fuzzing)) = &mut fuzzer {
+                    if let Some(status) = child.try_wait()? {
+                        if !status.success() {
+                            self.print_new_artifacts(&run.build, target, before_fuzzing)?;
+                            eprintln!("{:─<80}\n", "");
+                        }
+                        eprintln!(
+                            "Fuzz target exited with {}; waiting for changes...\n",
+                            status
+                        );
+                        fuzzer = None;
+                    }
+                }
+            };
+
+            if let Some((mut child, _)) = fuzzer {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            if changed {
+                eprintln!("\n{:─<80}\n", "");
+                eprintln!("Sources changed; rebuilding `{}`...\n", target);
+            }
+        }
+    }
+
+    /// The modification times of every file that `watch` rebuilds on.
+    fn watched_sources(
+        &self,
+        fuzz_targets_dir: &Path,
+    ) -> Result<BTreeMap<PathBuf, time::SystemTime>> {
+        let mut files = collect_rust_sources(&self.root_project.join("src"))?;
+        files.extend(collect_rust_sources(fuzz_targets_dir)?);
+        files.push(self.root_project.join("Cargo.toml"));
+        files.push(self.root_project.join("build.rs"));
+        files.push(self.manifest_path());
+        files.push(self.config_path());
+
+        let mut sources = BTreeMap::new();
+        for file in files {
+            // Files that don't exist, like an optional `build.rs`, are skipped
+            // here but still notice being created.
+            if let Ok(modified) = fs::metadata(&file).and_then(|m| m.modified()) {
+                sources.insert(file, modified);
+            }
+        }
+        Ok(sources)
+    }
+
+    /// Build the command that fuzzes a target with the given run options.
+    ///
+    /// A `killable` command runs the fuzz target directly rather than through
+    /// `cargo run`, which would leave the fuzzer running when killed.
+    fn fuzz_command(&self, run: &options::Run, target: &str, killable: bool) -> Result<Command> {
+        let config = self.config.target(Some(target));
+        match self.build_options_for(&run.build, Some(target)).engine {
+            Some(Engine::Honggfuzz) => return self.honggfuzz_command(run, target),
+            Some(Engine::Libafl) => return self.libafl_command(run, target),
+            _ => {}
+        }
+        let mut cmd = if killable {
+            self.binary_run(&run.build, target)?
+        } else {
+            self.cargo_run(&run.build, target)?
+        };
+        cmd.env(ARTIFACTS_ENV, self.artifacts_for(target)?);
+        // Watching for hangs only needs the input that's running.
+        if let Some(len) = run.record_trace.or(run.save_hangs.then_some(1)) {
+            // Only keep the trace of the latest run.
+            let dir = self.trace_dir(target);
+            if dir.exists() {
+                fs::remove_dir_all(&dir)
+                    .with_context(|| format!("failed to remove {}", dir.display()))?;
+            }
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+            cmd.env("CARGO_FUZZ_TRACE_DIR", dir)
+                .env("CARGO_FUZZ_TRACE_LEN", len.to_string());
+        }
+
+        if let Some(max_total_time) = run.max_total_time {
+            cmd.arg(format!("-max_total_time={}", max_total_time));
+        }
+        let limits = self.limits(run, target)?;
+        if let Some(timeout) = limits.timeout_secs {
+            cmd.arg(format!("-timeout={}", timeout));
+        }
+        if run.mode == Some(RunMode::Leaks) {
+            cmd.arg("-detect_leaks=1");
+        }
+        // libFuzzer's SEGV handler runs on the stack that overflowed, and
+        // dies without a report, so leave SEGVs to the sanitizer's handler,
+        // which has a stack of its own.
+        if self.build_options_for(&run.build, Some(target)).sanitizer != Some(Sanitizer::None) {
+            cmd.arg("-handle_segv=0");
+        }
+        cmd.envs(self.run_sanitizer_options(run, target)?);
+        if let Some(rss_limit) = limits.rss_limit_mb {
+            cmd.arg(format!("-rss_limit_mb={}", rss_limit));
+        }
+        if let Some(malloc_limit) = limits.malloc_limit_mb {
+            cmd.arg(format!("-malloc_limit_mb={}", malloc_limit));
+        }
+        // Before the engine arguments, so that a `-max_len` among them wins.
+        if let Some(max_len) = config.max_len {
+            cmd.arg(format!("-max_len={}", max_len));
+        }
+        for (feature, on) in self.engine_features(run, target)? {
+            cmd.arg(format!("{}={}", libfuzzer_feature_flag(feature), on as u8));
+        }
+
+        // Fork mode's defaults go first, then the target's dictionary, and
+        // arguments from the config file next, so that those given on the
+        // command line take precedence.
+        if let Some(threshold) = run.slow_threshold_ms {
+            // libFuzzer only reports slow units in whole seconds, so inputs
+            // slower than a threshold below that are found by timing them.
+            cmd.arg(format!("-report_slow_units={}", (threshold / 1000).max(1)));
+        }
+        if let Some(fork) = run.fork {
+            cmd.arg(format!("-fork={}", fork))
+                .arg("-ignore_crashes=1")
+                .arg("-ignore_ooms=1")
+                .arg("-ignore_timeouts=1");
+        }
+        let has_dict = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&run.args)
+            .any(|arg| arg.starts_with("-dict="));
+        if !has_dict {
+            if let Some(dict) = self.dict_for(target, &run.dicts)? {
+                let mut dict_arg = ffi::OsString::from("-dict=");
+                dict_arg.push(&dict);
+                cmd.arg(dict_arg);
+            }
+        }
+        for arg in config.args.iter().flatten().chain(&run.args) {
+            cmd.arg(arg);
+        }
+
+        // libFuzzer adds new inputs to the first corpus directory only, so
+        // the seeds after it are left alone.
+        if !run.corpus.is_empty() {
+            for corpus in &run.corpus {
+                cmd.arg(corpus);
+            }
+        } else {
+            cmd.arg(self.corpus_for(target)?);
+            cmd.args(self.seeds_for(target)?);
+        }
+
+        let jobs = run.jobs.or(config.jobs).unwrap_or(1);
+        if jobs != 1 && run.fork.is_none() {
+            cmd.arg(format!("-fork={}", jobs));
+        }
+
+        Ok(cmd)
+    }
+
+    /// Build the command that fuzzes a target with honggfuzz, translating the
+    /// run options into honggfuzz's flags. Engine arguments, from the config
+    /// file and the command line, are passed to honggfuzz as they are.
+    fn honggfuzz_command(&self, run: &options::Run, target: &str) -> Result<Command> {
+        if run.until_new_coverage_stalls.is_some() {
+            bail!("`--until-new-coverage-stalls` is only supported with the libFuzzer engine");
+        }
+        let config = self.config.target(Some(target));
+        let build = self.build_options_for(&run.build, Some(target));
+
+        let mut cmd = Command::new(utils::honggfuzz()?);
+        self.set_target_env(&mut cmd, target);
+        self.set_sanitizer_options(
+            &mut cmd,
+            build.sanitizer.unwrap_or(Sanitizer::Address),
+            &build.triple,
+        )?;
+        cmd.envs(self.run_sanitizer_options(run, target)?);
+
+        // honggfuzz reads from and adds new inputs to a single corpus.
+        let corpus = match run.corpus.as_slice() {
+            [] if !self.seeds_for(target)?.is_empty() => {
+                bail!("honggfuzz only supports a single corpus directory, not seed corpora")
+            }
+            [] => self.corpus_for(target)?,
+            [corpus] => PathBuf::from(corpus),
+            _ => bail!("honggfuzz only supports a single corpus directory"),
+        };
+        cmd.arg("--input")
+            .arg(corpus)
+            .arg("--crashdir")
+            .arg(self.artifacts_for(target)?)
+            .env(ARTIFACTS_ENV, self.artifacts_for(target)?);
+
+        if let Some(max_total_time) = run.max_total_time {
+            cmd.arg("--run_time").arg(max_total_time.to_string());
+        }
+        let limits = self.limits(run, target)?;
+        if limits.malloc_limit_mb.is_some() {
+            bail!("a malloc limit is only supported with the libFuzzer engine");
+        }
+        if let Some(timeout) = limits.timeout_secs {
+            cmd.arg("--timeout").arg(timeout.to_string());
+        }
+        if let Some(rss_limit) = limits.rss_limit_mb {
+            cmd.arg("--rlimit_rss").arg(rss_limit.to_string());
+        }
+        if let Some(max_len) = config.max_len {
+            cmd.arg("--max_file_size").arg(max_len.to_string());
+        }
+        if let Some(jobs) = run.jobs.or(config.jobs) {
+            cmd.arg("--threads").arg(jobs.to_string());
+        }
+        for (feature, on) in self.engine_features(run, target)? {
+            check_engine_feature(Engine::Honggfuzz, feature, on)?;
+        }
+
+        let has_dict = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&run.args)
+            .any(|arg| arg == "--dict" || arg == "-w");
+        if !has_dict {
+            if let Some(dict) = self.dict_for(target, &run.dicts)? {
+                cmd.arg("--dict").arg(dict);
+            }
+        }
+        for arg in config.args.iter().flatten().chain(&run.args) {
+            cmd.arg(arg);
+        }
+
+        cmd.arg("--").arg(self.target_binary(&build, target));
+        Ok(cmd)
+    }
+
+    /// Build the command that fuzzes a target with the LibAFL harness,
+    /// translating the run options into its flags. Engine arguments, from the
+    /// config file and the command line, are passed to it as they are.
+    fn libafl_command(&self, run: &options::Run, target: &str) -> Result<Command> {
+        if run.until_new_coverage_stalls.is_some() {
+            bail!("`--until-new-coverage-stalls` is only supported with the libFuzzer engine");
+        }
+        let config = self.config.target(Some(target));
+        let build = self.build_options_for(&run.build, Some(target));
+
+        let mut cmd = Command::new(self.target_binary(&build, target));
+        self.set_target_env(&mut cmd, target);
+        self.set_sanitizer_options(
+            &mut cmd,
+            build.sanitizer.unwrap_or(Sanitizer::Address),
+            &build.triple,
+        )?;
+        cmd.envs(self.run_sanitizer_options(run, target)?);
+        cmd.arg("--artifacts")
+            .arg(self.artifacts_for(target)?)
+            .env(ARTIFACTS_ENV, self.artifacts_for(target)?);
+
+        // The harness's clients sync through a broker listening on a port, so
+        // give each run a free one, or concurrent runs would sync with each
+        // other.
+        let port = std::net::TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|listener| listener.local_addr())
+            .context("failed to find a free port for LibAFL's broker")?
+            .port();
+        cmd.arg("--broker-port").arg(port.to_string());
+
+        if let Some(max_total_time) = run.max_total_time {
+            cmd.arg("--max-total-time").arg(max_total_time.to_string());
+        }
+        let limits = self.limits(run, target)?;
+        if limits.rss_limit_mb.is_some() || limits.malloc_limit_mb.is_some() {
+            bail!("memory limits are only supported with the libFuzzer and honggfuzz engines");
+        }
+        if config.max_len.is_some() {
+            bail!("`max-len` is only supported with the libFuzzer and honggfuzz engines");
+        }
+        if let Some(timeout) = limits.timeout_secs {
+            cmd.arg("--timeout").arg((timeout * 1000).to_string());
+        }
+        // One client per job, each on a core of its own.
+        let jobs = run.jobs.or(config.jobs).unwrap_or(1).max(1);
+        cmd.arg("--cores").arg(format!("0-{}", jobs - 1));
+        for (feature, on) in self.engine_features(run, target)? {
+            check_engine_feature(Engine::Libafl, feature, on)?;
+        }
+
+        let has_dict = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&run.args)
+            .any(|arg| arg == "--dict");
+        if !has_dict {
+            if let Some(dict) = self.dict_for(target, &run.dicts)? {
+                cmd.arg("--dict").arg(dict);
+            }
+        }
+        for arg in config.args.iter().flatten().chain(&run.args) {
+            cmd.arg(arg);
+        }
+
+        // New inputs go to the first corpus directory.
+        if !run.corpus.is_empty() {
+            cmd.args(&run.corpus);
+        } else {
+            cmd.arg(self.corpus_for(target)?);
+            cmd.args(self.seeds_for(target)?);
+        }
+        Ok(cmd)
+    }
+
+    /// Generate the LibAFL harness that `--engine libafl` links into fuzz
+    /// targets in libFuzzer's place, build it, and return the path of the
+    /// static library.
+    ///
+    /// The harness is built with the same toolchain and for the same target
+    /// as the fuzz targets, so that the standard library it bundles is the one
+    /// they link anyway.
+    fn build_libafl_harness(&self, build: &BuildOptions) -> Result<PathBuf> {
+        if let sanitizer @ (Sanitizer::Memory | Sanitizer::Thread) = single_sanitizer(build)? {
+            bail!(
+                "the libafl engine doesn't support the {} sanitizer, which needs an \
+                 instrumented standard library",
+                sanitizer
+            );
+        }
+        let dir = self.cargo_target_dir().join("libafl-harness");
+        let files = [
+            (
+                dir.join("Cargo.toml"),
+                libafl_harness_toml_template!(LIBAFL_VERSION).to_string(),
+            ),
+            (
+                dir.join("src").join("lib.rs"),
+                libafl_harness_template!().to_string(),
+            ),
+        ];
+        for (path, contents) in &files {
+            // Rewriting unchanged files would make Cargo rebuild the harness.
+            if fs::read_to_string(path).ok().as_ref() == Some(contents) {
+                continue;
+            }
+            let parent = path.parent().unwrap();
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+            fs::write(path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+
+        let target_dir = dir.join("target");
+        let mut cmd = utils::toolchain_command("cargo");
+        cmd.arg("build")
+            .arg("--release")
+            .arg("--manifest-path")
+            .arg(dir.join("Cargo.toml"))
+            .arg("--target")
+            .arg(&build.triple)
+            .arg("--target-dir")
+            .arg(&target_dir);
+        if build.verbose {
+            cmd.arg("--verbose");
+        }
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !status.success() {
+            return Err(exit::fail(
+                ExitCode::BuildFailed,
+                format!("failed to build the LibAFL harness: {:?}", cmd),
+            ));
+        }
+        let harness = target_dir
+            .join(&build.triple)
+            .join("release")
+            .join("libcargo_fuzz_libafl.a");
+
+        // `libfuzzer-sys` bundles the harness into its own library, and Cargo
+        // doesn't know to rebuild it when the harness changes, so clean it out
+        // of fuzz targets' target directory whenever it was built against an
+        // older harness.
+        let modified = fs::metadata(&harness)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("failed to read the metadata of {}", harness.display()))?;
+        let stamp = format!("{:?}", modified);
+        let profile_dir = self
+            .target_dir(build)
+            .join(&build.triple)
+            .join(profile_dir(build));
+        let stamp_path = profile_dir.join("libafl-harness.stamp");
+        if fs::read_to_string(&stamp_path).ok() != Some(stamp.clone()) {
+            let mut cmd = utils::toolchain_command("cargo");
+            cmd.arg("clean")
+                .arg("--quiet")
+                .arg("--manifest-path")
+                .arg(self.manifest_path())
+                .arg("--target-dir")
+                .arg(self.target_dir(build))
+                .arg("--target")
+                .arg(&build.triple)
+                .arg("--package")
+                .arg("libfuzzer-sys");
+            match &build.profile {
+                Some(profile) => {
+                    cmd.arg("--profile").arg(profile);
+                }
+                None if !build.dev => {
+                    cmd.arg("--release");
+                }
+                None => {}
+            }
+            let status = cmd
+                .status()
+                .with_context(|| format!("failed to execute: {:?}", cmd))?;
+            if !status.success() {
+                bail!("failed to clean out the old LibAFL harness: {:?}", cmd);
+            }
+            fs::create_dir_all(&profile_dir).with_context(|| {
+                format!("could not make a directory at {}", profile_dir.display())
+            })?;
+            fs::write(&stamp_path, stamp)
+                .with_context(|| format!("failed to write {}", stamp_path.display()))?;
+        }
+        Ok(harness)
+    }
+
+    /// Compile the recorder that `--record-trace` links into fuzz targets, and
+    /// return the path of its object file.
+    fn build_trace_recorder(&self, build: &BuildOptions) -> Result<PathBuf> {
+        if build
+            .engine
+            .is_some_and(|engine| engine != Engine::Libfuzzer)
+        {
+            bail!("`--record-trace` is only supported with the libFuzzer engine");
+        }
+        if !build.triple.contains("-linux-") || utils::host_triple() != Some(&build.triple) {
+            bail!("`--record-trace` is only supported when fuzzing on and for Linux");
+        }
+        let dir = self.target_dir(build).join("trace-recorder");
+        compile_c_object(
+            &dir,
+            "recorder",
+            &trace_recorder_template!().to_string(),
+            "the trace recorder",
+        )
+    }
+
+    /// Compile the recorder of the comparisons a fuzz target makes, which
+    /// `cargo fuzz blockers` links in.
+    fn build_cmp_recorder(&self, build: &BuildOptions) -> Result<PathBuf> {
+        let dir = self.target_dir(build).join("cmp-recorder");
+        compile_c_object(
+            &dir,
+            "recorder",
+            &cmp_recorder_template!().to_string(),
+            "the comparison recorder",
+        )
+    }
+
+    /// Get and print the `Debug` formatting of any new artifacts, along with
+    /// tips about how to reproduce failures and/or minimize test cases.
+    /// Build the decoder that prints a fuzz target's inputs for `cargo fuzz
+    /// fmt`, in `fuzz/target/decoder/<package>`, or the benchmark that times
+    /// them for `cargo fuzz bench --corpus`, in `fuzz/target/bench/<package>`,
+    /// and return the path of its binary.
+    fn build_shim(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        shim: decoder::Shim,
+    ) -> Result<PathBuf> {
+        let build = self.build_options_for(build, Some(target));
+        let dir = self.write_shim(target, shim)?;
+        let target_dir = dir.join("target");
+        let mut cmd = shim_cargo(&build, &dir, target, &["rustc"]);
+        let profile = match shim {
+            decoder::Shim::Decoder | decoder::Shim::Miri => "debug",
+            // The benchmark is optimized, and built with `--cfg fuzzing` like
+            // the fuzz target, so that it takes the same paths through the
+            // code, but without instrumentation or a sanitizer.
+            decoder::Shim::Bench => {
+                cmd.arg("--release");
+                let mut rustflags = vec!["--cfg".to_string(), "fuzzing".to_string()];
+                rustflags.extend(env_rustflags().into_iter().flat_map(|(_, flags)| flags));
+                cmd.env("CARGO_ENCODED_RUSTFLAGS", rustflags.join("\x1f"))
+                    .env_remove("RUSTFLAGS");
+                "release"
+            }
+        };
+        // The fuzz target's body isn't built into the decoder, which leaves
+        // whatever only it uses unused.
+        cmd.arg("--").arg("--cap-lints=allow");
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !status.success() {
+            return Err(exit::fail(
+                ExitCode::BuildFailed,
+                format!(
+                    "failed to build the {} for `{}`: {:?}",
+                    shim.name(),
+                    target,
+                    cmd
+                ),
+            ));
+        }
+        Ok(target_dir
+            .join(profile)
+            .join(format!("{}{}", target, env::consts::EXE_SUFFIX)))
+    }
+
+    /// Write the crate that builds a fuzz target against a stand-in for
+    /// `libfuzzer-sys`, in `fuzz/target/<shim>/<package>`, and return its
+    /// directory.
+    fn write_shim(&self, target: &str, shim: decoder::Shim) -> Result<PathBuf> {
+        let mut metadata = utils::toolchain_command("cargo");
+        metadata
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--manifest-path")
+            .arg(self.manifest_path());
+        let output = metadata
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", metadata))?;
+        if !output.status.success() {
+            bail!(
+                "failed to read the fuzz crate's metadata: {:?}\n\n{}",
+                metadata,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        let arbitrary = decoder::arbitrary_version(&String::from_utf8_lossy(&output.stdout))?;
+
+        // Fuzz crates in the same workspace share a target directory.
+        let fuzz_manifest = self.manifest()?;
+        let package = fuzz_manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("fuzz");
+        let dir = self.cargo_target_dir().join(shim.name()).join(package);
+        let shim_dir = dir.join("libfuzzer-sys");
+        let manifest = decoder::manifest(
+            shim,
+            &fuzz_manifest,
+            &self.path(),
+            &shim_dir,
+            target,
+            &self.target_path(target),
+        )?;
+        let files = [
+            (dir.join("Cargo.toml"), manifest),
+            (
+                shim_dir.join("Cargo.toml"),
+                decoder_shim_toml_template!(shim.command(), arbitrary).to_string(),
+            ),
+            (
+                shim_dir.join("src").join("lib.rs"),
+                match shim {
+                    decoder::Shim::Decoder => decoder_shim_template!().to_string(),
+                    decoder::Shim::Bench => bench_shim_template!().to_string(),
+                    decoder::Shim::Miri => miri_shim_template!().to_string(),
+                },
+            ),
+        ];
+        for (path, contents) in &files {
+            // Rewriting unchanged files would make Cargo rebuild the decoder.
+            if fs::read_to_string(path).ok().as_ref() == Some(contents) {
+                continue;
+            }
+            let parent = path.parent().unwrap();
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+            fs::write(path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        // Start from the versions the fuzz crate is locked to.
+        let lockfile = dir.join("Cargo.lock");
+        let fuzz_lockfile = self.path().join("Cargo.lock");
+        if !lockfile.exists() && fuzz_lockfile.exists() {
+            fs::copy(&fuzz_lockfile, &lockfile)
+                .with_context(|| format!("failed to copy {}", fuzz_lockfile.display()))?;
+        }
+
+        Ok(dir)
+    }
+
+    fn print_new_artifacts(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        since: &time::SystemTime,
+    ) -> Result<()> {
+        let mut new_artifacts: Vec<_> = self
+            .get_artifacts_since(target, since)?
+            .into_iter()
+            .collect();
+        new_artifacts.sort();
+        self.print_artifacts(build, target, &new_artifacts)
+    }
+
+    /// Print the `Debug` formatting of artifacts, along with tips about how to
+    /// reproduce failures and/or minimize test cases.
+    fn print_artifacts(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        artifacts: &[PathBuf],
+    ) -> Result<()> {
+        for artifact in artifacts {
+            // To make the artifact a little easier to read, strip the current
+            // directory prefix when possible.
+            let artifact = strip_current_dir_prefix(artifact);
+
+            eprintln!("\n{:─<80}", "");
+            eprintln!("\nFailing input:\n\n\t{}\n", artifact.display());
+            if let Some(report) = divergence_report(artifact) {
+                eprintln!(
+                    "Divergence report:\n\n\t{}\n",
+                    strip_current_dir_prefix(&report).display()
+                );
+            }
+            if is_stack_overflow(artifact) {
+                eprintln!(
+                    "The input overflows the stack, through recursion that's unbounded or \
+                     deeper than the\nstack allows. Sanitizers make stack frames larger, so \
+                     check whether it overflows\nwith `--sanitizer none` too. Traces stop at \
+                     255 frames; to see where the recursion\nstarts, reproduce it with a \
+                     smaller stack, which `repro` builds with frame pointers to\nunwind:\n\n\t\
+                     (ulimit -s 512; cargo fuzz repro {} {})\n",
+                    target,
+                    artifact.display()
+                );
+            }
+
+            // Note: ignore errors when running the debug formatter. This most
+            // likely just means that we're dealing with a fuzz target that uses
+            // an older version of the libfuzzer crate, and doesn't support
+            // `RUST_LIBFUZZER_DEBUG_PATH`.
+            if let Ok(debug) = self.run_fuzz_target_debug_formatter(build, target, artifact) {
+                eprintln!("Output of `std::fmt::Debug`:\n");
+                for l in debug.lines() {
+                    eprintln!("\t{}", l);
+                }
+                eprintln!();
+            }
+
+            eprintln!(
+                "Reproduce with:\n\n\tcargo fuzz run {target} {artifact}\n",
+                target = target,
+                artifact = artifact.display()
+            );
+            eprintln!(
+                "Debug with:\n\n\tcargo fuzz repro {target} {artifact} --debugger gdb\n",
+                target = target,
+                artifact = artifact.display()
+            );
+            eprintln!(
+                "Minimize test case with:\n\n\tcargo fuzz tmin {target} {artifact}\n",
+                target = target,
+                artifact = artifact.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Rebuild a fuzz target with debug info and run it on an artifact,
+    /// directly or under a debugger, with the sanitizer set up to symbolize
+    /// its reports.
+    pub fn exec_repro(&self, repro: &options::Repro) -> Result<()> {
+        let target = &repro.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        if !repro.artifact.is_file() {
+            bail!("no artifact at {}", repro.artifact.display());
+        }
+        let build = libfuzzer_only(&repro.build, "repro")?;
+        let mut inputs = Vec::new();
+        if repro.trace {
+            let dir = self.trace_dir(target);
+            if dir.is_dir() {
+                inputs = collect_files(&dir)?;
+            }
+            if inputs.is_empty() {
+                bail!(
+                    "no trace recorded for `{}`; record one with `cargo fuzz run --record-trace`",
+                    target
+                );
+            }
+            // The fuzzer recorded the crashing input last.
+            if fs::read(inputs.last().unwrap()).ok() == fs::read(&repro.artifact).ok() {
+                inputs.pop();
+            }
+            eprintln!(
+                "Running {} input(s) from {} before the artifact",
+                inputs.len(),
+                strip_current_dir_prefix(&dir).display()
+            );
+        }
+        inputs.push(repro.artifact.clone());
+        let mut cmd = self.repro_command(&build, target, &inputs, repro.debugger)?;
+        cmd.args(&repro.args);
+
+        if repro.print_command {
+            println!("{}", shell_command(&cmd));
+            return Ok(());
+        }
+        let status = cmd.status().with_context(|| match repro.debugger {
+            Some(debugger) => format!(
+                "failed to start `{}`; is {} installed?",
+                debugger.program(),
+                debugger
+            ),
+            None => format!("failed to run command: {:?}", cmd),
+        })?;
+        if repro.debugger.is_some() || status.success() {
+            if repro.debugger.is_none() {
+                eprintln!(
+                    "\n`{}` ran {} without crashing",
+                    target,
+                    strip_current_dir_prefix(&repro.artifact).display()
+                );
+            }
+            return Ok(());
+        }
+        Err(exit::fail(
+            ExitCode::CrashFound,
+            format!(
+                "reproduced the crash: fuzz target exited with {}",
+                crash::describe_exit(status)
+            ),
+        ))
+    }
+
+    /// Write a Markdown report of a crash, with the versions it was found
+    /// with, the fuzz target's source, the input, and a symbolized backtrace,
+    /// and print it or open a GitHub issue with it.
+    pub fn exec_report(&self, report: &options::Report) -> Result<()> {
+        let target = &report.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        if !report.artifact.is_file() {
+            bail!("no artifact at {}", report.artifact.display());
+        }
+        let build = libfuzzer_only(&report.build, "report")?;
+        let scratch = tempfile::TempDir::new().context("failed to create temp dir")?;
+        let input_path = if report.minimize {
+            self.exec_build(&build, Some(target))?;
+            let tmin = options::Tmin {
+                build: build.clone(),
+                target: target.clone(),
+                runs: 255,
+                test_case: None,
+                batch: false,
+                jobs: None,
+            };
+            let minimized = scratch.path().join("minimized");
+            if self
+                .minimize_test_case(&build, &tmin, &report.artifact, &minimized)?
+                .is_none()
+            {
+                bail!(
+                    "`{}` ran {} without crashing",
+                    target,
+                    strip_current_dir_prefix(&report.artifact).display()
+                );
+            }
+            minimized
+        } else {
+            report.artifact.clone()
+        };
+        let input = fs::read(&input_path)
+            .with_context(|| format!("failed to read {}", input_path.display()))?;
+
+        let mut cmd =
+            self.repro_command(&build, target, std::slice::from_ref(&input_path), None)?;
+        let output = cmd
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        let crash = match crash_from_output(&output) {
+            Some(crash) => crash,
+            None => bail!(
+                "`{}` ran {} without crashing",
+                target,
+                strip_current_dir_prefix(&report.artifact).display()
+            ),
+        };
+        let rustc_version = utils::toolchain_command("rustc")
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let options = self.build_options_for(&build, Some(target));
+        let source_path = self.target_path(target);
+        let source = fs::read_to_string(&source_path)
+            .with_context(|| format!("failed to read {}", source_path.display()))?;
+        let report_md = report::Report {
+            target,
+            crash: &crash,
+            cargo_fuzz_version: env!("CARGO_PKG_VERSION"),
+            rustc_version: &rustc_version,
+            triple: &options.triple,
+            sanitizer: match single_sanitizer(&options)? {
+                Sanitizer::None => "none".to_string(),
+                sanitizer => sanitizer.to_string(),
+            },
+            source: &source,
+            input: &input,
+            minimized: report.minimize,
+            output: &String::from_utf8_lossy(&output.stderr),
+        };
+
+        let repo = match &report.github {
+            Some(repo) => repo,
+            None => {
+                print!("{}", report_md.render());
+                return Ok(());
+            }
+        };
+        let url = open_github_issue(repo, &report_md.title(), &report_md.render())?;
+        eprintln!("Opened {}", url);
+        Ok(())
+    }
+
+    pub fn exec_annotate(&self, annotate: &options::Annotate) -> Result<()> {
+        let target = &annotate.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        if !annotate.artifact.is_file() {
+            bail!("no artifact at {}", annotate.artifact.display());
+        }
+        let build = libfuzzer_only(&annotate.build, "annotate")?;
+        let mut cmd = self.repro_command(
+            &build,
+            target,
+            std::slice::from_ref(&annotate.artifact),
+            None,
+        )?;
+        let output = cmd
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        let crash = match crash_from_output(&output) {
+            Some(crash) => crash,
+            None => bail!(
+                "`{}` ran {} without crashing",
+                target,
+                strip_current_dir_prefix(&annotate.artifact).display()
+            ),
+        };
+        let hash = crash.hash();
+        let (path, line) = match annotate::crash_line(
+            &crash,
+            &self.root_project,
+            &[&self.path(), &self.root_project],
+        ) {
+            Some(found) => found,
+            None => bail!(
+                "crash {} doesn't happen at a line of the fuzzed crate or the fuzz target:\n\n{}",
+                hash,
+                crash
+            ),
+        };
+        let source = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+
+        let artifact = annotate
+            .artifact
+            .canonicalize()
+            .unwrap_or_else(|_| annotate.artifact.clone());
+        let artifact = artifact
+            .strip_prefix(&self.root_project)
+            .unwrap_or(&annotate.artifact);
+        let artifact = artifact.display().to_string();
+        let shown = strip_current_dir_prefix(&path);
+        let annotated = match annotate::insert(&source, line, &hash, &artifact) {
+            Some(annotated) => annotated,
+            None => {
+                eprintln!(
+                    "{}:{} is already annotated with crash {}",
+                    shown.display(),
+                    line,
+                    hash
+                );
+                return Ok(());
+            }
+        };
+        if annotate.diff {
+            let relative = path.strip_prefix(self.repo_root()).unwrap_or(&path);
+            print!(
+                "{}",
+                annotate::diff(
+                    &relative.display().to_string(),
+                    &source,
+                    line,
+                    &annotate::comment(&hash, &artifact)
+                )
+            );
+        } else {
+            fs::write(&path, annotated)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            eprintln!("Annotated {}:{} with crash {}", shown.display(), line, hash);
+        }
+        Ok(())
+    }
+
+    /// The root of the git repository that the fuzzed crate is in, or the
+    /// crate's root if it's not in one.
+    fn repo_root(&self) -> &Path {
+        self.root_project
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(&self.root_project)
+    }
+
+    /// Build a fuzz target with debug info, and make the command that runs it
+    /// on some inputs in order, the last of them an artifact, directly or
+    /// under a debugger, with the sanitizer set up to symbolize its reports.
+    fn repro_command(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        inputs: &[PathBuf],
+        debugger: Option<options::Debugger>,
+    ) -> Result<Command> {
+        let mut build = build.clone();
+        build.debuginfo = true;
+        self.exec_build(&build, Some(target))?;
+        let options = self.build_options_for(&build, Some(target));
+        let sanitizer = single_sanitizer(&options)?;
+        let binary = self.target_binary(&build, target);
+
+        let mut cmd = match debugger {
+            Some(debugger) => {
+                let mut cmd = Command::new(debugger.program());
+                cmd.arg(match debugger {
+                    options::Debugger::Gdb => "--args",
+                    options::Debugger::Lldb => "--",
+                })
+                .arg(&binary);
+                cmd
+            }
+            None => binary_command(&options, &binary),
+        };
+        // As when fuzzing, so that a stack overflow gets a report.
+        if sanitizer != Sanitizer::None {
+            cmd.arg("-handle_segv=0");
+        }
+        cmd.args(inputs);
+        cmd.env("RUST_BACKTRACE", "1");
+        // Aborting on errors stops the debugger where the sanitizer found one.
+        let extra: &[&str] = match debugger {
+            Some(_) => &["symbolize=1", "abort_on_error=1"],
+            None => &["symbolize=1"],
+        };
+        if let Some((var, opts)) = self.sanitizer_options(sanitizer, extra)? {
+            if let Ok(symbolizer) = utils::llvm_tool("llvm-symbolizer") {
+                cmd.env(var.replace("_OPTIONS", "_SYMBOLIZER_PATH"), symbolizer);
+            }
+            cmd.env(var, opts);
+        }
+        Ok(cmd)
+    }
+
+    /// Package a fuzz target binary and an artifact it crashes on, with a
+    /// script that reproduces the crash, into a `.tar.gz` archive.
+    pub fn exec_package_repro(&self, package: &options::PackageRepro) -> Result<()> {
+        let target = &package.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        if !package.artifact.is_file() {
+            bail!("no artifact at {}", package.artifact.display());
+        }
+        let artifact = package.artifact.file_name().unwrap().to_string_lossy();
+        let mut build = libfuzzer_only(&package.build, "package-repro")?;
+        build.debuginfo = true;
+        let options = self.build_options_for(&build, Some(target));
+        let sanitizer = single_sanitizer(&options)?;
+        self.exec_build(&build, Some(target))?;
+        let binary = self.target_binary(&build, target);
+        let binary_name = binary.file_name().unwrap().to_string_lossy().into_owned();
+
+        let name = format!("{}-{}", target, artifact);
+        let scratch = tempfile::TempDir::new_in(self.path())?;
+        let dir = scratch.path().join(&name);
+        fs::create_dir(&dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+        fs::copy(&binary, dir.join(&binary_name))
+            .with_context(|| format!("failed to copy {}", binary.display()))?;
+        // A fuzz target that runs a binary of the fuzzed crate looks for it
+        // next to itself.
+        if let Some(bin) = self.config.target(Some(target)).bin {
+            let bin = binary
+                .with_file_name(bin)
+                .with_extension(binary.extension().unwrap_or_default());
+            fs::copy(&bin, dir.join(bin.file_name().unwrap()))
+                .with_context(|| format!("failed to copy {}", bin.display()))?;
+        }
+        fs::copy(&package.artifact, dir.join(&*artifact))
+            .with_context(|| format!("failed to copy {}", package.artifact.display()))?;
+
+        // The same command `cargo fuzz repro` would run, from inside the
+        // archive.
+        let mut cmd = Command::new(format!("./{}", binary_name));
+        self.set_target_env(&mut cmd, target);
+        cmd.env("RUST_BACKTRACE", "1");
+        if let Some((var, opts)) = self.sanitizer_options(sanitizer, &["symbolize=1"])? {
+            cmd.env(var, opts);
+        }
+        cmd.arg(&*artifact);
+        let command = shell_command(&cmd);
+        let script = dir.join("repro.sh");
+        fs::write(&script, package_repro_script_template!(command).to_string())
+            .with_context(|| format!("failed to write {}", script.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("failed to make {} executable", script.display()))?;
+        }
+        let sanitizer = match sanitizer {
+            Sanitizer::None => "no sanitizer".to_string(),
+            sanitizer => format!("the {} sanitizer", sanitizer),
+        };
+        let readme = dir.join("README.txt");
+        fs::write(
+            &readme,
+            package_repro_readme_template!(
+                target,
+                self.root_project_name()?,
+                command,
+                options.triple,
+                sanitizer,
+                utils::rustc_release().unwrap_or("(unknown version)")
+            )
+            .to_string(),
+        )
+        .with_context(|| format!("failed to write {}", readme.display()))?;
+
+        let output = match &package.output {
+            Some(output) => output.clone(),
+            None => self.path().join("repro").join(format!("{}.tar.gz", name)),
+        };
+        if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+        }
+        // `tar -C` changes directory before it writes the archive.
+        let absolute = env::current_dir()?.join(&output);
+        let mut tar = Command::new("tar");
+        tar.arg("-czf")
+            .arg(&absolute)
+            .arg("-C")
+            .arg(scratch.path())
+            .arg(&name);
+        let status = tar
+            .status()
+            .with_context(|| format!("failed to run `tar`; is it installed? {:?}", tar))?;
+        if !status.success() {
+            bail!("failed to create the archive: {:?}", tar);
+        }
+        eprintln!(
+            "Reproducer for {} written to:\n\n\t{}\n",
+            strip_current_dir_prefix(&package.artifact).display(),
+            strip_current_dir_prefix(&output).display()
+        );
+        Ok(())
+    }
+
+    pub fn exec_tmin(&self, tmin: &options::Tmin) -> Result<()> {
+        let build = libfuzzer_only(&tmin.build, "tmin")?;
+        self.exec_build(&build, Some(&tmin.target))?;
+        let test_case = tmin
+            .test_case
+            .as_ref()
+            .ok_or_else(|| anyhow!("no test case given"))?;
+        let mut cmd = self.cargo_run(&build, &tmin.target)?;
+        cmd.arg("-minimize_crash=1")
+            .arg(format!("-runs={}", tmin.runs))
+            .arg(test_case);
+
+        let before_tmin = time::SystemTime::now();
+
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+        let status = child
+            .wait()
+            .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
+        if !status.success() {
+            build.message_format.emit(&Message::TestCaseMinimized {
+                target: &tmin.target,
+                artifact: None,
+                success: false,
+            });
+            eprintln!("\n{:─<80}\n", "");
+            return Err(anyhow!("Command `{:?}` exited with {}", cmd, status)).with_context(|| {
+                "Test case minimization failed.\n\
+                     \n\
+                     Usually this isn't a hard error, and just means that libfuzzer\n\
+                     doesn't know how to minimize the test case any further while\n\
+                     still reproducing the original crash.\n\
+                     \n\
+                     See the logs above for details."
+            });
+        }
+
+        // Find and display the most recently modified artifact, which is
+        // presumably the result of minification. Yeah, this is a little hacky,
+        // but it seems to work. I don't want to parse libfuzzer's stderr output
+        // and hope it never changes.
+        let minimized_artifact = self
+            .get_artifacts_since(&tmin.target, &before_tmin)?
+            .into_iter()
+            .max_by_key(|a| {
+                a.metadata()
+                    .and_then(|m| m.modified())
+                    .unwrap_or(time::SystemTime::UNIX_EPOCH)
+            });
+        build.message_format.emit(&Message::TestCaseMinimized {
+            target: &tmin.target,
+            artifact: minimized_artifact
+                .as_ref()
+                .map(|a| strip_current_dir_prefix(a).display().to_string()),
+            success: true,
+        });
+
+        if let Some(artifact) = minimized_artifact {
+            let artifact = strip_current_dir_prefix(&artifact);
+
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!("Minimized artifact:\n\n\t{}\n", artifact.display());
+
+            // Note: ignore errors when running the debug formatter. This most
+            // likely just means that we're dealing with a fuzz target that uses
+            // an older version of the libfuzzer crate, and doesn't support
+            // `RUST_LIBFUZZER_DEBUG_PATH`.
+            if let Ok(debug) = self.run_fuzz_target_debug_formatter(&build, &tmin.target, artifact)
+            {
+                eprintln!("Output of `std::fmt::Debug`:\n");
+                for l in debug.lines() {
+                    eprintln!("\t{}", l);
+                }
+                eprintln!();
+            }
+
+            eprintln!(
+                "Reproduce with:\n\n\tcargo fuzz run {target} {artifact}\n",
+                target = &tmin.target,
+                artifact = artifact.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Minimize every test case in a directory, deduplicate the results by
+    /// crash signature, and replace the test cases with the smallest input for
+    /// each unique crash, named `crash-<hash>-<length>`.
+    ///
+    /// Test cases that no longer crash are left alone.
+    pub fn exec_tmin_batch(&self, tmin: &options::Tmin) -> Result<()> {
+        let build = libfuzzer_only(&tmin.build, "tmin")?;
+        self.exec_build(&build, Some(&tmin.target))?;
+
+        let dir = match &tmin.test_case {
+            Some(dir) => dir.clone(),
+            None => self.artifacts_for(&tmin.target)?,
+        };
+        let test_cases: Vec<PathBuf> = collect_files(&dir)?
+            .into_iter()
+            .filter(|path| !is_divergence_report(path))
+            .collect();
+        if test_cases.is_empty() {
+            eprintln!("No test cases found in {}", dir.display());
+            return Ok(());
+        }
+        let in_corpus = self.is_corpus_of(&dir, &tmin.target)?;
+        if in_corpus {
+            self.track_corpus(&tmin.target, Origin::Seed, None)?;
+        }
+        let scratch = tempfile::TempDir::new_in(self.path())?;
+
+        // Each worker takes the next test case off the queue until it's empty.
+        let queue = Mutex::new(test_cases.iter().enumerate());
+        let results = Mutex::new(Vec::new());
+        let jobs = tmin.jobs.unwrap_or(1).min(test_cases.len());
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| loop {
+                    let (i, test_case) = match queue.lock().unwrap().next() {
+                        Some(next) => next,
+                        None => break,
+                    };
+                    let minimized = scratch.path().join(format!("minimized-{}", i));
+                    let result = self
+                        .minimize_test_case(&build, tmin, test_case, &minimized)
+                        .with_context(|| format!("failed to minimize {}", test_case.display()));
+                    match &result {
+                        Ok(Some(crash)) => eprintln!(
+                            "{}: crash {}",
+                            strip_current_dir_prefix(test_case).display(),
+                            crash.hash()
+                        ),
+                        Ok(None) => eprintln!(
+                            "{}: no longer crashes",
+                            strip_current_dir_prefix(test_case).display()
+                        ),
+                        Err(_) => {}
+                    }
+                    results.lock().unwrap().push((test_case, minimized, result));
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(test_case, _, _)| *test_case);
+
+        // Keep the smallest minimized input of each crash.
+        let mut crashes: BTreeMap<String, (Crash, PathBuf, u64)> = BTreeMap::new();
+        let mut no_longer_crashing = Vec::new();
+        let mut failed = 0;
+        for (test_case, minimized, result) in &results {
+            match result {
+                Ok(Some(crash)) => {
+                    let len = fs::metadata(minimized)?.len();
+                    let smallest = crashes
+                        .entry(crash.hash())
+                        .or_insert_with(|| (crash.clone(), minimized.clone(), len));
+                    if len < smallest.2 {
+                        *smallest = (crash.clone(), minimized.clone(), len);
+                    }
+                }
+                Ok(None) => no_longer_crashing.push(*test_case),
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("{:#}", e);
+                }
+            }
+        }
+        if failed > 0 {
+            bail!(
+                "failed to minimize {} of {} test cases; {} was left as it was",
+                failed,
+                results.len(),
+                dir.display()
+            );
+        }
+
+        // Only replace the crashing test cases once every survivor is safely
+        // in place.
+        let mut survivors = Vec::new();
+        for (hash, (crash, minimized, len)) in &crashes {
+            let survivor = dir.join(format!("crash-{}-{}", hash, len));
+            fs::copy(minimized, &survivor)
+                .with_context(|| format!("failed to write {}", survivor.display()))?;
+            build.message_format.emit(&Message::TestCaseMinimized {
+                target: &tmin.target,
+                artifact: Some(strip_current_dir_prefix(&survivor).display().to_string()),
+                success: true,
+            });
+            survivors.push((crash, survivor));
+        }
+        for (test_case, _, result) in &results {
+            let is_survivor = survivors.iter().any(|(_, s)| s == *test_case);
+            if matches!(result, Ok(Some(_))) && !is_survivor {
+                fs::remove_file(test_case)
+                    .with_context(|| format!("failed to remove {}", test_case.display()))?;
+            }
+        }
+        if in_corpus {
+            self.track_corpus(&tmin.target, Origin::Tmin, None)?;
+        }
+
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!(
+            "Minimized {} test case(s) into {} unique crash(es).",
+            results.len() - no_longer_crashing.len(),
+            survivors.len()
+        );
+        for (crash, survivor) in &survivors {
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!("Crash {}: {}\n", crash.hash(), crash);
+            eprintln!(
+                "Reproduce with:\n\n\tcargo fuzz run {target} {artifact}\n",
+                target = &tmin.target,
+                artifact = strip_current_dir_prefix(survivor).display()
+            );
+        }
+        if !no_longer_crashing.is_empty() {
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!("No longer reproducing, left as they were:\n");
+            for test_case in &no_longer_crashing {
+                eprintln!("\t{}", strip_current_dir_prefix(test_case).display());
+            }
+            eprintln!();
+        }
+        eprintln!("{:─<80}\n", "");
+        Ok(())
+    }
+
+    /// Minimize one crashing test case into `minimized`, and return the crash
+    /// that the minimized input causes, or `None` if the test case doesn't
+    /// crash.
+    ///
+    /// If libFuzzer can't minimize the test case, or the minimized input
+    /// doesn't reproduce, the test case is copied to `minimized` as it is.
+    fn minimize_test_case(
+        &self,
+        build: &BuildOptions,
+        tmin: &options::Tmin,
+        test_case: &Path,
+        minimized: &Path,
+    ) -> Result<Option<Crash>> {
+        // Anything libFuzzer writes besides the minimized input goes to a
+        // scratch directory rather than the target's artifacts.
+        let scratch = tempfile::TempDir::new().context("failed to create temp dir")?;
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(scratch.path().join(""));
+        let run = |args: &[&ffi::OsStr]| -> Result<Output> {
+            let mut cmd = self.binary_run(build, &tmin.target)?;
+            cmd.arg(&artifact_arg).args(args).stdin(Stdio::null());
+            cmd.output()
+                .with_context(|| format!("failed to run command: {:?}", cmd))
+        };
+
+        let crash = match crash_from_output(&run(&[test_case.as_os_str()])?) {
+            Some(crash) => crash,
+            None => return Ok(None),
+        };
+
+        let mut exact_artifact_arg = ffi::OsString::from("-exact_artifact_path=");
+        exact_artifact_arg.push(minimized);
+        let output = run(&[
+            "-minimize_crash=1".as_ref(),
+            format!("-runs={}", tmin.runs).as_ref(),
+            &exact_artifact_arg,
+            test_case.as_os_str(),
+        ])?;
+        if output.status.success() && minimized.is_file() {
+            if let Some(crash) = crash_from_output(&run(&[minimized.as_os_str()])?) {
+                return Ok(Some(crash));
+            }
+        }
+        fs::copy(test_case, minimized)
+            .with_context(|| format!("failed to copy {}", test_case.display()))?;
+        Ok(Some(crash))
+    }
+
+    pub fn exec_cmin(&self, cmin: &options::Cmin) -> Result<()> {
+        let build = libfuzzer_only(&cmin.build, "cmin")?;
+        let target = cmin.target.as_ref().unwrap();
+        self.exec_build(&build, Some(target))?;
+        let mut cmd = self.cargo_run(&build, target)?;
+
+        let corpus = if let Some(corpus) = cmin.corpus.clone() {
+            corpus
+        } else {
+            self.corpus_for(target)?
+        };
+        let delta = self.with_hooks(target, "cmin", || {
+            self.minimize_corpus(&build, target, &mut cmd, &corpus, cmin, false)
+        })?;
+        if let Some(delta) = delta {
+            eprintln!("{}", delta);
+        }
+        Ok(())
+    }
+
+    /// Minimize the corpora of all fuzz targets, or all those matching a glob,
+    /// and print how much smaller each one got.
+    pub fn exec_cmin_all(&self, cmin: &options::Cmin) -> Result<()> {
+        let build = libfuzzer_only(&cmin.build, "cmin")?;
+        let targets: Vec<&String> = self
+            .targets
+            .iter()
+            .filter(|t| cmin.target.as_ref().is_none_or(|g| utils::glob_match(g, t)))
+            .collect();
+        if targets.is_empty() {
+            match &cmin.target {
+                Some(glob) => bail!("no fuzz targets match `{}`", glob),
+                None => bail!("no fuzz targets found"),
+            }
+        }
+        self.exec_build(&build, None)?;
+
+        // Each worker takes the next target off the queue until it's empty.
+        let queue = Mutex::new(targets.iter());
+        let results = Mutex::new(Vec::new());
+        let jobs = cmin.jobs.unwrap_or(1).min(targets.len());
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| loop {
+                    let target = match queue.lock().unwrap().next() {
+                        Some(target) => *target,
+                        None => break,
+                    };
+                    let result = self.with_hooks(target, "cmin", || {
+                        let corpus = self.corpus_for(target)?;
+                        let mut cmd = self.binary_run(&build, target)?;
+                        self.minimize_corpus(&build, target, &mut cmd, &corpus, cmin, true)
+                    });
+                    if let Ok(Some(delta)) = &result {
+                        eprintln!("{}", delta);
+                    }
+                    results.lock().unwrap().push((target, result));
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(target, _)| *target);
+
+        let mut failed = 0;
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("Summary:\n");
+        for (target, result) in &results {
+            match result {
+                Ok(Some(delta)) => eprintln!("\t{}", delta),
+                Ok(None) => {
+                    failed += 1;
+                    eprintln!("\t{}: failed to minimize corpus", target);
+                }
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("\t{}: {:#}", target, e);
+                }
+            }
+        }
+        eprintln!("\n{:─<80}\n", "");
+
+        if failed > 0 {
+            bail!("failed to minimize {} of {} corpora", failed, results.len());
+        }
+        Ok(())
+    }
+
+    /// Minimize `corpus` in place with `cmd`, a command that runs the fuzz
+    /// target, keeping only the inputs that add coverage beyond the target's
+    /// seeds if it's the target's corpus. With `quiet`, libFuzzer's output is
+    /// only printed if it fails.
+    ///
+    /// Returns `None` if libFuzzer failed, leaving the corpus as it was.
+    fn minimize_corpus(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        cmd: &mut Command,
+        corpus: &Path,
+        cmin: &options::Cmin,
+        quiet: bool,
+    ) -> Result<Option<CorpusDelta>> {
+        let corpus_str = corpus
+            .to_str()
+            .ok_or_else(|| anyhow!("corpus must be valid unicode"))?
+            .to_owned();
+        // Record the provenance of the inputs before the merge rewrites them.
+        let tracked = self.is_corpus_of(corpus, target)?;
+        if tracked {
+            self.track_corpus(target, Origin::Seed, None)?;
+        }
+        let mut before = collect_files(corpus)?;
+        let before_size = corpus_size(&before);
+
+        let tmp = tempfile::TempDir::new_in(self.path())?;
+        let tmp_corpus = tmp.path().join("corpus");
+        fs::create_dir(&tmp_corpus)?;
+
+        // Merge only the inputs that pass the filters, from a scratch copy,
+        // and keep the rest as they are.
+        let mut unselected = Vec::new();
+        let merged = if cmin.filter.is_active() {
+            let selected = filter_inputs(&cmin.filter, before.clone())?;
+            unselected = before
+                .into_iter()
+                .filter(|i| !selected.contains(i))
+                .collect();
+            before = selected;
+            let merged = tmp.path().join("selected");
+            copy_inputs(&before, &merged)?;
+            merged
+        } else {
+            corpus.to_path_buf()
+        };
+
+        // The merge only adds the inputs with coverage that the seeds, already
+        // in its output, don't have. They're taken out again afterwards.
+        let mut seed_inputs = Vec::new();
+        if tracked {
+            for seeds in self.seeds_for(target)? {
+                seed_inputs.extend(collect_files(&seeds)?);
+            }
+        }
+        copy_inputs(&seed_inputs, &tmp_corpus)?;
+
+        cmd.arg("-merge=1").arg(&tmp_corpus).arg(&merged);
+
+        // Spawn cmd in child process instead of exec-ing it
+        let (status, log) = if quiet {
+            let output = cmd
+                .output()
+                .with_context(|| format!("could not execute command: {:?}", cmd))?;
+            (output.status, output.stderr)
+        } else {
+            let status = cmd
+                .status()
+                .with_context(|| format!("could not execute command: {:?}", cmd))?;
+            (status, Vec::new())
+        };
+        build.message_format.emit(&Message::CorpusMinimized {
+            target,
+            corpus: corpus_str,
+            success: status.success(),
+        });
+        if !status.success() {
+            if quiet {
+                eprintln!("{}", String::from_utf8_lossy(&log));
+            }
+            eprintln!("Failed to minimize corpus of `{}`: {}", target, status);
+            return Ok(None);
+        }
+
+        // Put back the inputs that are too new to prune, and the pinned ones,
+        // unless the merge kept an input with the same contents.
+        let mut keep = Vec::new();
+        if let Some(age) = cmin.prune_older_than {
+            // An age from before the clock's earliest time keeps everything.
+            let cutoff = time::SystemTime::now().checked_sub(age);
+            for input in &before {
+                let is_new = cutoff.is_none_or(|cutoff| {
+                    fs::metadata(input)
+                        .and_then(|m| m.modified())
+                        .is_ok_and(|modified| modified > cutoff)
+                });
+                if is_new {
+                    keep.push(input.clone());
+                }
+            }
+        }
+        let mut pinned = Vec::new();
+        for dir in &cmin.preserve {
+            pinned.extend(collect_files(dir)?);
+        }
+        if cmin.pin_artifacts {
+            pinned.extend(self.artifact_prefixes(target, &before)?);
+        }
+        keep.extend(pinned.iter().cloned());
+        if !keep.is_empty() {
+            let mut kept = HashSet::new();
+            for input in collect_files(&tmp_corpus)? {
+                kept.insert(fs::read(&input)?);
+            }
+            let mut put_back = 0;
+            for input in &keep {
+                let data = fs::read(input)
+                    .with_context(|| format!("failed to read {}", input.display()))?;
+                if kept.insert(data) {
+                    fs::copy(input, tmp_corpus.join(input.file_name().unwrap()))?;
+                    put_back += pinned.contains(input) as usize;
+                }
+            }
+            if put_back > 0 && !quiet {
+                eprintln!(
+                    "Kept {} pinned input(s) of `{}` that add no coverage",
+                    put_back, target
+                );
+            }
+        }
+
+        for input in &seed_inputs {
+            let copy = tmp_corpus.join(input.file_name().unwrap());
+            if copy.exists() {
+                fs::remove_file(&copy)
+                    .with_context(|| format!("failed to remove {}", copy.display()))?;
+            }
+        }
+        copy_inputs(&unselected, &tmp_corpus)?;
+
+        let after = corpus_size(&collect_files(&tmp_corpus)?);
+        // move corpus directory into tmp to auto delete it
+        fs::rename(corpus, tmp.path().join("old"))?;
+        fs::rename(&tmp_corpus, corpus)?;
+        if tracked {
+            self.track_corpus(target, Origin::Seed, None)?;
+        }
+        Ok(Some(CorpusDelta {
+            target: target.to_string(),
+            before: before_size,
+            after,
+        }))
+    }
+
+    /// The non-empty inputs among `inputs` that a crash artifact of `target`
+    /// starts with, which the fuzzer likely grew the artifact from.
+    fn artifact_prefixes(&self, target: &str, inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let dir = self.artifacts_for(target)?;
+        let mut artifacts = Vec::new();
+        for artifact in collect_files(&dir)? {
+            let name = artifact.file_name().unwrap().to_string_lossy();
+            if !name.starts_with(libfuzzer::SLOW_UNIT_PREFIX) {
+                artifacts.push(
+                    fs::read(&artifact)
+                        .with_context(|| format!("failed to read {}", artifact.display()))?,
+                );
+            }
+        }
+        let mut prefixes = Vec::new();
+        for input in inputs {
+            let data =
+                fs::read(input).with_context(|| format!("failed to read {}", input.display()))?;
+            if !data.is_empty() && artifacts.iter().any(|artifact| artifact.starts_with(&data)) {
+                prefixes.push(input.clone());
+            }
+        }
+        Ok(prefixes)
+    }
+
+    /// Find the first commit where an artifact crashes a fuzz target, with
+    /// `git bisect` in a worktree of its own, and print it.
+    pub fn exec_bisect(&self, bisect: &options::Bisect) -> Result<()> {
+        let build = libfuzzer_only(&bisect.build, "bisect")?;
+        let toplevel = git(&self.root_project, &["rev-parse", "--show-toplevel"])?;
+        let toplevel = fs::canonicalize(toplevel.trim())
+            .with_context(|| format!("failed to resolve {}", toplevel.trim()))?;
+        let crate_dir = fs::canonicalize(&self.root_project)
+            .with_context(|| format!("failed to resolve {}", self.root_project.display()))?
+            .strip_prefix(&toplevel)?
+            .to_path_buf();
+
+        // Copy the artifact out first, in case it's in the repository.
+        let scratch = tempfile::TempDir::new()?;
+        let artifact = scratch.path().join("artifact");
+        fs::copy(&bisect.artifact, &artifact)
+            .with_context(|| format!("failed to read {}", bisect.artifact.display()))?;
+
+        // Revisions like `HEAD` mean something else once bisecting starts.
+        let resolve = |rev: &str| -> Result<String> {
+            let commit = format!("{}^{{commit}}", rev);
+            let sha = git(&toplevel, &["rev-parse", "--verify", "--quiet", &commit])
+                .with_context(|| format!("`{}` isn't a git revision", rev))?;
+            Ok(sha.trim().to_string())
+        };
+        let revisions = (resolve(&bisect.good)?, resolve(&bisect.bad)?);
+
+        let worktree = scratch.path().join("worktree");
+        let worktree_str = worktree
+            .to_str()
+            .ok_or_else(|| anyhow!("temporary directory must be valid unicode"))?;
+        git(
+            &toplevel,
+            &[
+                "worktree",
+                "add",
+                "--detach",
+                "--quiet",
+                worktree_str,
+                &revisions.1,
+            ],
+        )?;
+        let result =
+            self.bisect_worktree(&build, bisect, &revisions, &worktree, &crate_dir, &artifact);
+        let _ = git(&toplevel, &["worktree", "remove", "--force", worktree_str]);
+        let found = result?;
+
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("{}", found.trim_end());
+        if let Some(commit) = found.split_whitespace().next() {
+            println!("{}", commit);
+        }
+        Ok(())
+    }
+
+    /// Bisect between the good and bad `revisions` in `worktree`, which
+    /// starts out at the bad one, and return git's description of the first
+    /// bad commit.
+    fn bisect_worktree(
+        &self,
+        build: &BuildOptions,
+        bisect: &options::Bisect,
+        (good, bad): &(String, String),
+        worktree: &Path,
+        crate_dir: &Path,
+        artifact: &Path,
+    ) -> Result<String> {
+        let project_dir = worktree.join(crate_dir);
+        let test = || -> Result<BisectVerdict> {
+            let revision = git(worktree, &["log", "-1", "--format=%h %s"])?;
+            eprintln!("\nTesting {}", revision.trim());
+            let verdict = self.bisect_test(build, &bisect.target, &project_dir, artifact)?;
+            eprintln!(
+                "{}: {}",
+                revision.split_whitespace().next().unwrap_or(""),
+                verdict
+            );
+            Ok(verdict)
+        };
+
+        match test()? {
+            BisectVerdict::Crashes(_) => {}
+            verdict => bail!("can't bisect from `{}`, where {}", bisect.bad, verdict),
+        }
+        git(worktree, &["checkout", "--quiet", "--detach", good])?;
+        match test()? {
+            BisectVerdict::Passes => {}
+            verdict => bail!("can't bisect from `{}`, where {}", bisect.good, verdict),
+        }
+
+        let mut output = git(worktree, &["bisect", "start", bad, good])?;
+        while !output.contains("is the first bad commit") {
+            eprint!("{}", output);
+            let verdict = match test()? {
+                BisectVerdict::Crashes(_) => "bad",
+                BisectVerdict::Passes => "good",
+                BisectVerdict::Skip(_) => "skip",
+            };
+            output = git(worktree, &["bisect", verdict])?;
+        }
+        Ok(output)
+    }
+
+    /// Build the fuzz target of the project in `project_dir` at the revision
+    /// checked out there, and see whether the artifact crashes it.
+    fn bisect_test(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        project_dir: &Path,
+        artifact: &Path,
+    ) -> Result<BisectVerdict> {
+        let project = match FuzzProject::open(project_dir.to_path_buf()) {
+            Ok(project) => project,
+            Err(e) => return Ok(BisectVerdict::Skip(format!("{:#}", e))),
+        };
+        if !project.targets.iter().any(|t| t == target) {
+            return Ok(BisectVerdict::Skip(format!(
+                "there's no fuzz target named `{}`",
+                target
+            )));
+        }
+        if project.exec_build(build, Some(target)).is_err() {
+            return Ok(BisectVerdict::Skip(
+                "the fuzz target fails to build".to_string(),
+            ));
+        }
+        Ok(match project.reproduce_crash(build, target, artifact)? {
+            Some(crash) => BisectVerdict::Crashes(crash),
+            None => BisectVerdict::Passes,
+        })
+    }
+
+    /// Replay every artifact of some targets, report which still crash, and
+    /// with `--move-fixed`, move the others to their `fixed` subdirectory.
+    pub fn exec_verify_fixes(&self, verify: &options::VerifyFixes) -> Result<()> {
+        let targets: Vec<&String> = self
+            .targets
+            .iter()
+            .filter(|t| {
+                verify.targets.as_ref().is_none_or(|g| {
+                    utils::glob_match(g, t) || utils::glob_match(g, &self.target_subpath(t))
+                })
+            })
+            .collect();
+        if targets.is_empty() {
+            match &verify.targets {
+                Some(glob) => bail!("no fuzz targets match `{}`", glob),
+                None => bail!("no fuzz targets found"),
+            }
+        }
+        let build = libfuzzer_only(&verify.build, "verify-fixes")?;
+
+        let (mut replayed, mut fixed, mut crashing) = (0, 0, 0);
+        for target in targets {
+            let artifacts_dir = self.artifacts_for(target)?;
+            let artifacts: Vec<PathBuf> = collect_files(&artifacts_dir)?
+                .into_iter()
+                .filter(|path| !is_divergence_report(path))
+                .collect();
+            if artifacts.is_empty() {
+                continue;
+            }
+            self.exec_build(&build, Some(target))?;
+
+            let mut still_crashing = Vec::new();
+            let mut no_longer_crashing = Vec::new();
+            for artifact in artifacts {
+                match self.reproduce_crash(&build, target, &artifact)? {
+                    Some(crash) => still_crashing.push((artifact, crash)),
+                    None => no_longer_crashing.push(artifact),
+                }
+            }
+            replayed += still_crashing.len() + no_longer_crashing.len();
+            fixed += no_longer_crashing.len();
+            crashing += still_crashing.len();
+
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!(
+                "`{}`: {} fixed, {} still crashing\n",
+                target,
+                no_longer_crashing.len(),
+                still_crashing.len()
+            );
+            for (artifact, crash) in &still_crashing {
+                eprintln!(
+                    "\tstill crashes: {}\n\t  {}",
+                    strip_current_dir_prefix(artifact).display(),
+                    crash.to_string().replace("\n\t", "\n\t    ")
+                );
+            }
+            let fixed_dir = artifacts_dir.join("fixed");
+            for artifact in &no_longer_crashing {
+                eprintln!("\tfixed: {}", strip_current_dir_prefix(artifact).display());
+                if verify.move_fixed {
+                    fs::create_dir_all(&fixed_dir).with_context(|| {
+                        format!("could not make a directory at {}", fixed_dir.display())
+                    })?;
+                    let moved = fixed_dir.join(artifact.file_name().unwrap());
+                    fs::rename(artifact, &moved).with_context(|| {
+                        format!(
+                            "failed to move {} to {}",
+                            artifact.display(),
+                            moved.display()
+                        )
+                    })?;
+                }
+            }
+            if verify.move_fixed && !no_longer_crashing.is_empty() {
+                eprintln!(
+                    "\nMoved {} fixed artifact(s) to {}",
+                    no_longer_crashing.len(),
+                    strip_current_dir_prefix(&fixed_dir).display()
+                );
+            }
+        }
+
+        eprintln!("\n{:─<80}\n", "");
+        if replayed == 0 {
+            eprintln!("No artifacts to replay.");
+            return Ok(());
+        }
+        eprintln!(
+            "Replayed {} artifact(s): {} fixed, {} still crashing.",
+            replayed, fixed, crashing
+        );
+        if crashing > 0 {
+            return Err(exit::fail(
+                ExitCode::CrashFound,
+                format!("{} artifact(s) still crash", crashing),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Re-run every artifact of a fuzz target and group them by crash
+    /// signature, printing one representative input per unique crash.
+    pub fn exec_triage(&self, triage: &options::Triage) -> Result<()> {
+        let build = libfuzzer_only(&triage.build, "triage")?;
+        self.exec_build(&build, Some(&triage.target))?;
+
+        let artifacts_dir = match &triage.artifacts {
+            Some(dir) => dir.clone(),
+            None => self.artifacts_for(&triage.target)?,
+        };
+        let artifacts: Vec<PathBuf> = collect_files(&artifacts_dir)?
+            .into_iter()
+            .filter(|path| !is_divergence_report(path))
+            .collect();
+        if artifacts.is_empty() {
+            eprintln!("No artifacts found in {}", artifacts_dir.display());
+            return Ok(());
+        }
+
+        let mut crashes: BTreeMap<String, (Crash, Vec<PathBuf>)> = BTreeMap::new();
+        let mut no_longer_crashing = Vec::new();
+        for artifact in &artifacts {
+            let crash = match self.reproduce_crash(&build, &triage.target, artifact)? {
+                Some(crash) => crash,
+                None => {
+                    no_longer_crashing.push(artifact.clone());
+                    continue;
+                }
+            };
+            crashes
+                .entry(crash.signature())
+                .or_insert_with(|| (crash, Vec::new()))
+                .1
+                .push(artifact.clone());
+        }
+
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!(
+            "Found {} unique crash(es) in {} artifact(s).",
+            crashes.len(),
+            artifacts.len()
+        );
+
+        let mut findings = Vec::new();
+        for (crash, inputs) in crashes.values_mut() {
+            // The smallest input is usually the easiest one to debug.
+            inputs.sort_by_key(|a| a.metadata().map(|m| m.len()).unwrap_or(u64::MAX));
+            let representative = strip_current_dir_prefix(&inputs[0]);
+            findings.push(sarif::Finding {
+                target: &triage.target,
+                crash,
+                artifact: representative,
+            });
+
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!("Crash {}: {}\n", crash.hash(), crash);
+            eprintln!("Representative input:\n\n\t{}\n", representative.display());
+            if inputs.len() > 1 {
+                eprintln!("Duplicates:\n");
+                for duplicate in &inputs[1..] {
+                    eprintln!("\t{}", strip_current_dir_prefix(duplicate).display());
+                }
+                eprintln!();
+            }
+            eprintln!(
+                "Reproduce with:\n\n\tcargo fuzz run {target} {artifact}\n",
+                target = &triage.target,
+                artifact = representative.display()
+            );
+        }
+
+        if !no_longer_crashing.is_empty() {
+            eprintln!("\n{:─<80}\n", "");
+            eprintln!("No longer reproducing:\n");
+            for artifact in &no_longer_crashing {
+                eprintln!("\t{}", strip_current_dir_prefix(artifact).display());
+            }
+            eprintln!();
+        }
+
+        if let Some(path) = &triage.sarif {
+            self.write_sarif(path, &findings)?;
+        }
+
+        eprintln!("{:─<80}\n", "");
+        Ok(())
+    }
+
+    /// Replay a fuzz target's corpus with coverage instrumentation and merge
+    /// the results into a profdata file, optionally rendering a report.
+    pub fn exec_coverage(&self, coverage: &options::Coverage) -> Result<()> {
+        self.with_hooks(&coverage.target, "coverage", || {
+            self.collect_coverage(coverage)
+        })
+    }
+
+    fn collect_coverage(&self, coverage: &options::Coverage) -> Result<()> {
+        let mut build = libfuzzer_only(&coverage.build, "coverage")?;
+        if let Some(base) = &coverage.diff {
+            if !base.is_file() {
+                bail!("no baseline coverage profile at {}", base.display());
+            }
+        }
+        build.coverage = true;
+        build.sanitizer = Some(Sanitizer::None);
+        self.exec_build(&build, Some(&coverage.target))?;
+        let binary = self.target_binary(&build, &coverage.target);
+        let options = self.build_options_for(&build, Some(&coverage.target));
+
+        let coverage_dir = self.coverage_for(&coverage.target)?;
+        let raw_dir = coverage_dir.join("raw");
+        if raw_dir.exists() {
+            fs::remove_dir_all(&raw_dir)
+                .with_context(|| format!("failed to remove {}", raw_dir.display()))?;
+        }
+        fs::create_dir_all(&raw_dir)
+            .with_context(|| format!("could not make a directory at {}", raw_dir.display()))?;
+
+        let mut corpora = if coverage.corpus.is_empty() {
+            vec![self.corpus_for(&coverage.target)?]
+        } else {
+            coverage.corpus.clone()
+        };
+        // Replay only the inputs that pass the filters, from a scratch copy.
+        let scratch = tempfile::TempDir::new_in(self.path())?;
+        if coverage.filter.is_active() {
+            let mut inputs = Vec::new();
+            for corpus in &corpora {
+                inputs.extend(collect_files(corpus)?);
+            }
+            let inputs = filter_inputs(&coverage.filter, inputs)?;
+            let selected = scratch.path().join("corpus");
+            copy_inputs(&inputs, &selected)?;
+            corpora = vec![selected];
+        }
+        if coverage.cmin_first {
+            corpora = vec![self.merge_for_coverage(coverage, &corpora, scratch.path())?];
+        } else {
+            let mut inputs = 0;
+            for corpus in &corpora {
+                inputs += collect_files(corpus)?.len();
+            }
+            if inputs >= LARGE_CORPUS {
+                eprintln!(
+                    "note: replaying {} inputs; `--cmin-first` replays only the ones that add \
+                     coverage, and `--jobs` replays them in parallel",
+                    inputs
+                );
+            }
+        }
+
+        // Each job replays a shard of the inputs, writing profiles of its own.
+        let jobs = coverage.jobs.unwrap_or(1);
+        if jobs > 1 {
+            let mut inputs = Vec::new();
+            for corpus in &corpora {
+                inputs.extend(collect_files(corpus)?);
+            }
+            corpora = shard_inputs(&inputs, jobs, &scratch.path().join("shards"))?;
+        }
+        let mut children = Vec::new();
+        for (i, corpus) in corpora.iter().enumerate() {
+            let mut cmd = binary_command(&options, &binary);
+            self.set_target_env(&mut cmd, &coverage.target);
+            cmd.env(
+                "LLVM_PROFILE_FILE",
+                raw_dir.join(format!("default-{}-%p.profraw", i)),
+            )
+            .arg("-runs=0")
+            .arg(corpus);
+            if jobs > 1 {
+                // libFuzzer's output from several processes at once is noise.
+                cmd.stdout(Stdio::null()).stderr(Stdio::null());
+                let child = cmd
+                    .spawn()
+                    .with_context(|| format!("failed to execute: {:?}", cmd))?;
+                children.push((corpus, child));
+                continue;
+            }
+            let status = cmd
+                .status()
+                .with_context(|| format!("failed to execute: {:?}", cmd))?;
+            if !status.success() {
+                eprintln!(
+                    "warning: replaying {} exited with {}, coverage may be incomplete",
+                    corpus.display(),
+                    status
+                );
+            }
+        }
+        for (corpus, mut child) in children {
+            let status = child.wait().with_context(|| {
+                format!("failed to wait for the replay of {}", corpus.display())
+            })?;
+            if !status.success() {
+                eprintln!(
+                    "warning: replaying shard {} exited with {}, coverage may be incomplete",
+                    corpus.file_name().unwrap_or_default().to_string_lossy(),
+                    status
+                );
+            }
+        }
+
+        let profraws = collect_files(&raw_dir)?;
+        if profraws.is_empty() {
+            bail!("replaying the corpus did not produce any raw coverage profiles");
+        }
+        let profdata = coverage_dir.join("coverage.profdata");
+        if let Some(base) = &coverage.diff {
+            if fs::canonicalize(base).ok() == fs::canonicalize(&profdata).ok() {
+                bail!(
+                    "{} is about to be overwritten; copy it somewhere else to use it as a baseline",
+                    base.display()
+                );
+            }
+        }
+        merge_coverage(&profraws, &profdata)?;
+
+        // Only annotate the sources of the crate being fuzzed, not those of
+        // the fuzz targets or any dependencies.
+        let sources = self.root_project.join("src");
+        let report = match coverage.format {
+            options::CoverageFormat::Profdata => profdata.clone(),
+            options::CoverageFormat::Html => {
+                let html_dir = coverage_dir.join("html");
+                let mut show = Command::new(utils::llvm_tool("llvm-cov")?);
+                show.arg("show")
+                    .arg(&binary)
+                    .arg(format!("-instr-profile={}", profdata.display()))
+                    .arg("-format=html")
+                    .arg(format!("-output-dir={}", html_dir.display()))
+                    .arg("-show-line-counts-or-regions")
+                    .arg(&sources);
+                let status = show
+                    .status()
+                    .with_context(|| format!("failed to execute: {:?}", show))?;
+                if !status.success() {
+                    bail!("failed to generate HTML coverage report: {:?}", show);
+                }
+                html_dir.join("index.html")
+            }
+            options::CoverageFormat::Lcov => {
+                let lcov = coverage_dir.join("lcov.info");
+                fs::write(&lcov, export_lcov(&binary, &profdata, &sources)?)
+                    .with_context(|| format!("failed to write {}", lcov.display()))?;
+                lcov
+            }
+            options::CoverageFormat::Cobertura => {
+                let cobertura = coverage_dir.join("cobertura.xml");
+                let lines = coverage::parse_lcov(&export_lcov(&binary, &profdata, &sources)?);
+                let timestamp = time::SystemTime::now()
+                    .duration_since(time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_secs());
+                let xml = coverage::render_cobertura(&lines, &self.root_project, timestamp);
+                fs::write(&cobertura, xml)
+                    .with_context(|| format!("failed to write {}", cobertura.display()))?;
+                cobertura
+            }
+        };
+
+        eprintln!(
+            "Coverage {} written to:\n\n\t{}\n",
+            coverage.format,
+            strip_current_dir_prefix(&report).display()
+        );
+
+        if let Some(upload) = coverage.upload {
+            let lcov = export_lcov(&binary, &profdata, &sources)?;
+            self.upload_coverage(upload, &lcov)?;
+        }
+        if coverage.uncovered {
+            self.print_uncovered(&binary, &profdata, &sources)?;
+        }
+        match &coverage.diff {
+            Some(base) => self.diff_coverage(&binary, base, &profdata, &sources, &coverage_dir),
+            None => Ok(()),
+        }
+    }
+
+    /// Upload the coverage in an lcov report to Codecov or Coveralls, for the
+    /// commit that the fuzzed crate is checked out at, under the flag `fuzz`
+    /// to tell it apart from the coverage of other tests.
+    fn upload_coverage(&self, upload: options::CoverageUpload, lcov: &str) -> Result<()> {
+        let commit = git_output(&self.root_project, &["rev-parse", "HEAD"]).ok_or_else(|| {
+            anyhow!("uploading coverage needs the fuzzed crate to be in a git repository")
+        })?;
+        let branch = git_output(&self.root_project, &["rev-parse", "--abbrev-ref", "HEAD"])
+            .filter(|branch| branch != "HEAD");
+        match upload {
+            options::CoverageUpload::Codecov => {
+                let token = env::var("CODECOV_TOKEN")
+                    .map_err(|_| anyhow!("uploading to Codecov needs a token in $CODECOV_TOKEN"))?;
+                let api =
+                    env::var("CODECOV_URL").unwrap_or_else(|_| "https://codecov.io".to_string());
+                let mut url = format!(
+                    "{}/upload/v2?token={}&commit={}&flags=fuzz",
+                    api.trim_end_matches('/'),
+                    token,
+                    commit
+                );
+                if let Some(branch) = &branch {
+                    url.push_str(&format!("&branch={}", branch));
+                }
+                utils::curl_post(&url, &["--data-binary", "@-"], lcov.as_bytes())
+            }
+            options::CoverageUpload::Coveralls => {
+                let token = env::var("COVERALLS_REPO_TOKEN").map_err(|_| {
+                    anyhow!("uploading to Coveralls needs a token in $COVERALLS_REPO_TOKEN")
+                })?;
+                let api = env::var("COVERALLS_ENDPOINT")
+                    .unwrap_or_else(|_| "https://coveralls.io".to_string());
+                // Coveralls names files relative to the root of the repository.
+                let root = git_output(&self.root_project, &["rev-parse", "--show-toplevel"])
+                    .map_or_else(|| self.root_project.clone(), PathBuf::from);
+                let job = serde_json::json!({
+                    "repo_token": token,
+                    "service_name": "cargo-fuzz",
+                    "flag_name": "fuzz",
+                    "git": { "head": { "id": commit }, "branch": branch },
+                    "source_files": coverage::coveralls_source_files(
+                        &coverage::parse_lcov(lcov),
+                        &root,
+                    )?,
+                });
+                utils::curl_post(
+                    &format!("{}/api/v1/jobs", api.trim_end_matches('/')),
+                    &["--form", "json_file=@-;filename=coveralls.json"],
+                    job.to_string().as_bytes(),
+                )
+            }
+        }
+        .with_context(|| format!("failed to upload the coverage to {}", upload))?;
+        eprintln!(
+            "Uploaded the coverage of commit {} to {}",
+            &commit[..commit.len().min(12)],
+            upload
+        );
+        Ok(())
+    }
+
+    /// Merge `corpora` into a directory in `scratch` with the regular fuzzing
+    /// build of the target, keeping only the inputs that add coverage, for
+    /// `coverage --cmin-first`.
+    fn merge_for_coverage(
+        &self,
+        coverage: &options::Coverage,
+        corpora: &[PathBuf],
+        scratch: &Path,
+    ) -> Result<PathBuf> {
+        let build = libfuzzer_only(&coverage.build, "coverage")?;
+        self.exec_build(&build, Some(&coverage.target))?;
+        let minimized = scratch.join("minimized");
+        fs::create_dir(&minimized)
+            .with_context(|| format!("could not make a directory at {}", minimized.display()))?;
+        let mut before = 0;
+        for corpus in corpora {
+            before += collect_files(corpus)?.len();
+        }
+
+        let mut cmd = self.binary_run(&build, &coverage.target)?;
+        cmd.arg("-merge=1").arg(&minimized).args(corpora);
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !output.status.success() {
+            bail!(
+                "minimizing the corpus failed: {:?}\n\n{}",
+                cmd,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        eprintln!(
+            "Minimized the corpus from {} to {} input(s) before replaying it.",
+            before,
+            collect_files(&minimized)?.len()
+        );
+        Ok(minimized)
+    }
+
+    /// Print the public functions and modules of the fuzzed crate that the
+    /// merged profile has no coverage of, so that new fuzz targets can be
+    /// written for them.
+    fn print_uncovered(&self, binary: &Path, profdata: &Path, sources: &Path) -> Result<()> {
+        let lib = self.root_lib_path()?;
+        let crate_name = self.root_project_name()?.replace('-', "_");
+        if !lib.is_file() {
+            bail!("`{}` has no library to find the public API of", crate_name);
+        }
+        let api = api::public_items(&lib, &crate_name)?;
+        let regions =
+            coverage::parse_export(&export_coverage(binary, profdata, sources)?, sources)?;
+        let uncovered = coverage::uncovered(&api, &regions);
+        if uncovered.is_empty() {
+            eprintln!("Every public function of `{}` is covered.", crate_name);
+            return Ok(());
+        }
+        eprintln!(
+            "{} public function(s) or module(s) of `{}` aren't covered at all, largest first:\n",
+            uncovered.len(),
+            crate_name
+        );
+        print!("{}", coverage::render_uncovered(&uncovered, &api));
+        Ok(())
+    }
+
+    /// Compare the regions covered by a baseline profile with those covered by
+    /// the profile that was just merged, both read through the current build
+    /// of the target.
+    fn diff_coverage(
+        &self,
+        binary: &Path,
+        base: &Path,
+        profdata: &Path,
+        sources: &Path,
+        coverage_dir: &Path,
+    ) -> Result<()> {
+        let base_regions =
+            coverage::parse_export(&export_coverage(binary, base, sources)?, sources)?;
+        let regions =
+            coverage::parse_export(&export_coverage(binary, profdata, sources)?, sources)?;
+        let files = coverage::diff(&base_regions, &regions);
+
+        let annotated_path = coverage_dir.join("coverage.diff");
+        let annotated = coverage::render_annotated(&files, &self.root_project)?;
+        fs::write(&annotated_path, annotated)
+            .with_context(|| format!("failed to write {}", annotated_path.display()))?;
+
+        print!("{}", coverage::render_summary(&files, &self.root_project));
+        eprintln!(
+            "\nLines whose coverage changed written to:\n\n\t{}\n",
+            strip_current_dir_prefix(&annotated_path).display()
+        );
+
+        let lost: usize = files.values().map(|f| f.lost.len()).sum();
+        if lost > 0 {
+            bail!(
+                "{} region(s) lost coverage compared to {}",
+                lost,
+                base.display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Replay a target's corpus with a recorder of the comparisons it makes,
+    /// and print those that look like magic numbers or checksums it never
+    /// gets past.
+    pub fn exec_blockers(&self, blockers: &options::Blockers) -> Result<()> {
+        let target = &blockers.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let mut build = libfuzzer_only(&blockers.build, "blockers")?;
+        if !build.triple.contains("-linux-") || utils::host_triple() != Some(&build.triple) {
+            bail!("`cargo fuzz blockers` is only supported on and for Linux");
+        }
+        build.record_cmps = true;
+        build.debuginfo = true;
+
+        // libFuzzer replays whole directories, so inputs given one by one
+        // are copied into one.
+        let scratch = tempfile::TempDir::new().context("failed to create temp dir")?;
+        let given = scratch.path().join("inputs");
+        let mut dirs = Vec::new();
+        if blockers.inputs.is_empty() {
+            dirs.push(self.corpus_for(target)?);
+            dirs.extend(self.seeds_for(target)?);
+        }
+        for (i, path) in blockers.inputs.iter().enumerate() {
+            if path.is_dir() {
+                dirs.push(path.clone());
+            } else if path.is_file() {
+                if !given.is_dir() {
+                    fs::create_dir(&given).with_context(|| {
+                        format!("could not make a directory at {}", given.display())
+                    })?;
+                    dirs.push(given.clone());
+                }
+                fs::copy(path, given.join(i.to_string()))
+                    .with_context(|| format!("failed to copy {}", path.display()))?;
+            } else {
+                bail!("there is no input at {}", path.display());
+            }
+        }
+        let mut inputs = 0;
+        for dir in &dirs {
+            inputs += collect_files(dir)?.len();
+        }
+
+        self.exec_build(&build, Some(target))?;
+        let log = scratch.path().join("cmps.log");
+        let mut cmd = self.binary_run(&build, target)?;
+        let mut artifact_arg = ffi::OsString::from("-artifact_prefix=");
+        artifact_arg.push(scratch.path().join(""));
+        cmd.arg(artifact_arg)
+            .arg("-runs=0")
+            .args(&dirs)
+            .env(CMP_LOG_ENV, &log)
+            .stdin(Stdio::null());
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run command: {:?}", cmd))?;
+        if !output.status.success() {
+            bail!(
+                "`{}` crashed while replaying its inputs, so the replay stopped; \
+                 remove the input that crashes it, or pass others:\n\n{}",
+                target,
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            );
+        }
+        let sites = blockers::parse_log(
+            &fs::read_to_string(&log)
+                .with_context(|| format!("failed to read {}", log.display()))?,
+        )?;
+
+        let sites: Vec<_> = sites
+            .into_iter()
+            .filter_map(|site| Some((site.blocker()?, site)))
+            .collect();
+        let frames = if sites.is_empty() {
+            Vec::new()
+        } else {
+            let symbolizer = utils::llvm_tool("llvm-symbolizer")?;
+            let binary = self.target_binary(&build, target);
+            let addresses: String = sites
+                .iter()
+                .map(|(_, site)| format!("{:#x}\n", site.offset))
+                .collect();
+            let mut cmd = Command::new(&symbolizer);
+            cmd.arg("--obj")
+                .arg(&binary)
+                .arg("--demangle")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped());
+            let mut child = cmd
+                .spawn()
+                .with_context(|| format!("failed to run command: {:?}", cmd))?;
+            child
+                .stdin
+                .take()
+                .unwrap()
+                .write_all(addresses.as_bytes())
+                .context("failed to write to llvm-symbolizer")?;
+            let output = child
+                .wait_with_output()
+                .with_context(|| format!("failed to run command: {:?}", cmd))?;
+            blockers::parse_symbolizer(&String::from_utf8_lossy(&output.stdout))
+        };
+        let root = self.repo_root();
+        let findings = sites
+            .iter()
+            .zip(&frames)
+            .filter_map(|((blocker, site), frames)| {
+                let (function, location) = blockers::source_frame(frames, root)?;
+                Some(blockers::Finding {
+                    function,
+                    location,
+                    blocker: *blocker,
+                    width: site.width,
+                    count: site.count,
+                    closest: site.closest,
+                })
+            })
+            .collect();
+        print!(
+            "{}",
+            blockers::render(target, inputs, &blockers::merge(findings))
+        );
+        Ok(())
+    }
+
+    /// Replay an input and the rest of the corpus separately with coverage
+    /// instrumentation, and print the regions of the fuzzed crate that only
+    /// the input covers.
+    pub fn exec_explain(&self, explain: &options::Explain) -> Result<()> {
+        let target = &explain.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let input = fs::read(&explain.input)
+            .with_context(|| format!("failed to read {}", explain.input.display()))?;
+        let mut build = libfuzzer_only(&explain.build, "explain")?;
+        build.coverage = true;
+        build.sanitizer = Some(Sanitizer::None);
+        self.exec_build(&build, Some(target))?;
+        let binary = self.target_binary(&build, target);
+        let options = self.build_options_for(&build, Some(target));
+
+        // Copy every other input into a directory of its own, leaving out the
+        // input itself and any copies of it.
+        let scratch = tempfile::TempDir::new_in(self.path())?;
+        let others = scratch.path().join("others");
+        fs::create_dir(&others)
+            .with_context(|| format!("could not make a directory at {}", others.display()))?;
+        let corpora = if explain.corpus.is_empty() {
+            vec![self.corpus_for(target)?]
+        } else {
+            explain.corpus.clone()
+        };
+        let mut count = 0;
+        for corpus in &corpora {
+            for file in collect_files(corpus)? {
+                let data = fs::read(&file)
+                    .with_context(|| format!("failed to read {}", file.display()))?;
+                if data != input {
+                    let copy = others.join(count.to_string());
+                    fs::write(&copy, data)
+                        .with_context(|| format!("failed to write {}", copy.display()))?;
+                    count += 1;
+                }
+            }
+        }
+
+        let sources = self.root_project.join("src");
+        let replay = |path: &Path, name: &str, what: &str| -> Result<coverage::Regions> {
+            let raw_dir = scratch.path().join(name);
+            let (status, regions) = replay_coverage(&options, &binary, path, &raw_dir, &sources)?;
+            let regions = match regions {
+                Some(regions) => regions,
+                None => bail!(
+                    "replaying {} exited with {} without writing a coverage profile; \
+                     inputs that crash the fuzz target can't be explained",
+                    what,
+                    status
+                ),
+            };
+            if !status.success() {
+                eprintln!(
+                    "warning: replaying {} exited with {}, coverage may be incomplete",
+                    what, status
+                );
+            }
+            Ok(regions)
+        };
+        let input_regions = replay(&explain.input, "input-raw", "the input")?;
+        let other_regions = if count > 0 {
+            replay(&others, "others-raw", "the other inputs")?
+        } else {
+            coverage::Regions::new()
+        };
+
+        let files = coverage::unique(&input_regions, &other_regions);
+        let unique: usize = files.values().map(Vec::len).sum();
+        let covered = input_regions.values().filter(|&&covered| covered).count();
+        if unique == 0 {
+            eprintln!(
+                "{} covers {} region(s) of {}, all of which the other {} input(s) cover too.\n\n\
+                 libFuzzer may have kept it for reaching them more often, or for the operands \
+                 of its comparisons, which region coverage doesn't show.",
+                explain.input.display(),
+                covered,
+                strip_current_dir_prefix(&sources).display(),
+                count
+            );
+            return Ok(());
+        }
+        eprintln!(
+            "{} covers {} region(s) of {}, {} of which none of the other {} input(s) do:\n",
+            explain.input.display(),
+            covered,
+            strip_current_dir_prefix(&sources).display(),
+            unique,
+            count
+        );
+        print!("{}", coverage::render_unique(&files, &self.root_project)?);
+        Ok(())
+    }
+
+    /// Replay each input of a target's corpus with coverage instrumentation,
+    /// group the fuzzed crate's entry points that the inputs reach together,
+    /// and suggest a fuzz target for each group, adding them with
+    /// `--scaffold`.
+    pub fn exec_suggest_split(&self, suggest: &options::SuggestSplit) -> Result<()> {
+        let target = &suggest.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let lib = self.root_lib_path()?;
+        let crate_name = self.root_project_name()?.replace('-', "_");
+        if !lib.is_file() {
+            bail!(
+                "`{}` has no library to find the entry points of",
+                crate_name
+            );
+        }
+        let api = api::public_items(&lib, &crate_name)?;
+
+        let mut build = libfuzzer_only(&suggest.build, "suggest-split")?;
+        build.coverage = true;
+        build.sanitizer = Some(Sanitizer::None);
+        self.exec_build(&build, Some(target))?;
+        let binary = self.target_binary(&build, target);
+        let options = self.build_options_for(&build, Some(target));
+
+        let mut inputs = collect_files(&self.corpus_for(target)?)?;
+        for seeds in self.seeds_for(target)? {
+            inputs.extend(collect_files(&seeds)?);
+        }
+        if inputs.is_empty() {
+            bail!(
+                "`{}` has no corpus to find the entry points it reaches with; fuzz it first",
+                target
+            );
+        }
+
+        // Each input is replayed on its own, to tell which entry points it
+        // reaches.
+        let sources = self.root_project.join("src");
+        let scratch = tempfile::TempDir::new_in(self.path())?;
+        let mut reached = Vec::new();
+        let mut crashed = 0;
+        for (i, input) in inputs.iter().enumerate() {
+            let raw_dir = scratch.path().join(i.to_string());
+            match replay_coverage(&options, &binary, input, &raw_dir, &sources)? {
+                (_, Some(regions)) => {
+                    let functions = split::reached(&api, &regions);
+                    reached.push((input.clone(), split::entry_points(&api, &functions)));
+                }
+                (_, None) => crashed += 1,
+            }
+        }
+        if crashed > 0 {
+            eprintln!(
+                "warning: skipped {} input(s) that crashed without writing a coverage profile",
+                crashed
+            );
+        }
+        let (groups, unreached) = split::group(target, reached);
+        if !unreached.is_empty() {
+            eprintln!(
+                "warning: {} input(s) reach none of the public functions of `{}`",
+                unreached.len(),
+                crate_name
+            );
+        }
+        if groups.len() < 2 {
+            eprintln!(
+                "The inputs of `{}` all reach the same entry points of `{}`, so there's \
+                 nothing to split.",
+                target, crate_name
+            );
+            return Ok(());
+        }
+        eprintln!(
+            "No input of `{}` reaches entry points of `{}` from more than one of these {} \
+             groups, which could each be fuzzed by a target of its own:\n",
+            target,
+            crate_name,
+            groups.len()
+        );
+        print!("{}", split::render(&groups));
+        if !suggest.scaffold {
+            eprintln!("\nAdd them with `--scaffold`.");
+            return Ok(());
+        }
+
+        for group in &groups {
+            if self.targets.contains(&group.name) {
+                bail!("a fuzz target named `{}` already exists", group.name);
+            }
+        }
+        let source = fs::read_to_string(self.target_path(target))
+            .with_context(|| format!("failed to read {}", self.target_path(target).display()))?;
+        eprintln!();
+        for group in &groups {
+            let entry_points: String = group
+                .entry_points
+                .iter()
+                .map(|entry_point| format!("//     {}\n", entry_point))
+                .collect();
+            self.create_target(
+                &group.name,
+                split_target_template!(target, entry_points, source),
+            )?;
+            let corpus = self.corpus_for(&group.name)?;
+            for input in &group.inputs {
+                let copy = corpus.join(input.file_name().unwrap());
+                fs::copy(input, &copy)
+                    .with_context(|| format!("failed to copy {}", input.display()))?;
+            }
+            eprintln!(
+                "Added {} with {} input(s) in {}",
+                strip_current_dir_prefix(&self.target_path(&group.name)).display(),
+                group.inputs.len(),
+                strip_current_dir_prefix(&corpus).display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Generate a test in `fuzz/tests/<target>.rs` that replays an artifact
+    /// through the fuzz target, so that the bug it found stays fixed.
+    pub fn gen_regression_test(&self, gen_test: &options::GenTest) -> Result<()> {
+        if !self.targets.contains(&gen_test.target) {
+            bail!("no fuzz target named `{}`", gen_test.target);
+        }
+        let input = fs::read(&gen_test.artifact)
+            .with_context(|| format!("failed to read {}", gen_test.artifact.display()))?;
+        let artifact_name = gen_test
+            .artifact
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| anyhow!("{} is not a file", gen_test.artifact.display()))?;
+        let test_name = identifier(&artifact_name);
+
+        let tests_path = self.regression_tests_path(&gen_test.target);
+        let tests_dir = tests_path.parent().unwrap();
+        fs::create_dir_all(tests_dir)
+            .with_context(|| format!("could not make a directory at {}", tests_dir.display()))?;
+
+        let mut tests = if tests_path.exists() {
+            fs::read_to_string(&tests_path)
+                .with_context(|| format!("failed to read {}", tests_path.display()))?
+        } else {
+            regression_tests_template!(gen_test.target).to_string()
+        };
+        if tests.contains(&format!("fn {}()", test_name)) {
+            bail!(
+                "{} already has a regression test for {}",
+                tests_path.display(),
+                artifact_name
+            );
+        }
+        // Indented to line up as the second argument of `run_fuzz_target`.
+        let literal = utils::byte_string_literal(&input, 92).replace("\\\n", "\\\n        ");
+        tests += &regression_test_template!(test_name, artifact_name, literal).to_string();
+        fs::write(&tests_path, tests)
+            .with_context(|| format!("failed to write {}", tests_path.display()))?;
+
+        eprintln!(
+            "Added regression test `{}` to:\n\n\t{}\n",
+            test_name,
+            strip_current_dir_prefix(&tests_path).display()
+        );
+        eprintln!(
+            "Run it with:\n\n\tcargo test --manifest-path {} --test {}\n",
+            strip_current_dir_prefix(&self.manifest_path()).display(),
+            gen_test.target
+        );
+        Ok(())
+    }
+
+    /// Type-check the fuzz targets, or, with `--replay`, regenerate the tests
+    /// that replay their corpora under `cargo test` and run them.
+    pub fn exec_check(&self, check: &options::Check) -> Result<()> {
+        if let Some(target) = &check.target {
+            if !self.targets.contains(target) {
+                bail!("no fuzz target named `{}`", target);
+            }
+        }
+
+        if !check.replay {
+            let mut failed = BTreeSet::new();
+            let mut success =
+                self.check_targets(check.target.as_deref(), check.all_targets, &mut failed)?;
+            // Cargo skips the targets whose `required-features` aren't enabled
+            // when checking all of them, so check those with their features.
+            if check.target.is_none() {
+                for target in self.required_features.keys() {
+                    success &= self.check_targets(Some(target), false, &mut failed)?;
+                }
+            }
+            if success {
+                return Ok(());
+            }
+            let failed: Vec<String> = failed.into_iter().collect();
+            return Err(exit::fail(
+                ExitCode::BuildFailed,
+                if failed.is_empty() {
+                    "failed to check the fuzz targets".to_string()
+                } else {
+                    format!("failed to check {}", failed.join(", "))
+                },
+            ));
+        }
+
+        let tests_path = self.write_corpus_replay_tests()?;
+        eprintln!(
+            "Generated corpus replay tests in {}",
+            strip_current_dir_prefix(&tests_path).display()
+        );
+        let mut cmd = utils::toolchain_command("cargo");
+        cmd.arg("test")
+            .arg("--manifest-path")
+            .arg(self.manifest_path())
+            .arg("--test")
+            .arg(CORPUS_REPLAY_TESTS);
+        if let Some(target) = &check.target {
+            cmd.arg("--").arg("--exact").arg(identifier(target));
+        }
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !status.success() {
+            bail!("replaying the corpus failed: {:?}", cmd);
+        }
+        Ok(())
+    }
+
+    /// Type-check one fuzz target, or all of them (and the fuzz crate's tests
+    /// and examples with `all_targets`), with `cargo check` and the `fuzzing`
+    /// cfg that fuzzing builds set, but none of their instrumentation.
+    ///
+    /// The compiler's diagnostics are printed as usual, and the targets they're
+    /// errors in are added to `failed`. Returns whether the check succeeded.
+    fn check_targets(
+        &self,
+        target: Option<&str>,
+        all_targets: bool,
+        failed: &mut BTreeSet<String>,
+    ) -> Result<bool> {
+        let mut cmd = utils::toolchain_command("cargo");
+        cmd.arg("check")
+            .arg("--manifest-path")
+            .arg(self.manifest_path())
+            .arg("--keep-going")
+            .arg("--message-format");
+        // Cargo doesn't say which target a diagnostic is from unless it's
+        // printed as JSON, so print the diagnostics it renders in there.
+        if std::io::stderr().is_terminal() {
+            cmd.arg("json-diagnostic-rendered-ansi");
+        } else {
+            cmd.arg("json");
+        }
+        match target {
+            Some(target) => {
+                cmd.arg("--bin").arg(target);
+                if let Some(features) = self.required_features.get(target) {
+                    cmd.arg("--features").arg(features.join(","));
+                }
+            }
+            None if all_targets => {
+                cmd.arg("--all-targets");
+            }
+            None => {
+                cmd.arg("--bins");
+            }
+        }
+        let mut rustflags = vec!["--cfg".to_string(), "fuzzing".to_string()];
+        rustflags.extend(env_rustflags().into_iter().flat_map(|(_, flags)| flags));
+        cmd.env("CARGO_ENCODED_RUSTFLAGS", rustflags.join("\x1f"))
+            .env_remove("RUSTFLAGS")
+            .stdout(Stdio::piped());
+
+        let output = cmd
+            .spawn()
+            .and_then(|child| child.wait_with_output())
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let message: serde_json::Value = match serde_json::from_str(line) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+            if message["reason"] != "compiler-message" {
+                continue;
+            }
+            if let Some(rendered) = message["message"]["rendered"].as_str() {
+                eprint!("{}", rendered);
+            }
+            if message["message"]["level"] != "error" {
+                continue;
+            }
+            if let Some(name) = message["target"]["name"].as_str() {
+                failed.insert(match message["target"]["kind"][0].as_str() {
+                    Some("bin") => format!("fuzz target `{}`", name),
+                    Some(kind) => format!("{} `{}`", kind, name),
+                    None => format!("`{}`", name),
+                });
+            }
+        }
+        Ok(output.status.success())
+    }
+
+    /// Write `fuzz/tests/corpus_replay.rs`, with a test for each fuzz target
+    /// that replays its corpus. Targets with `required-features` only get
+    /// their test when those features are enabled, since Cargo doesn't build
+    /// them otherwise.
+    fn write_corpus_replay_tests(&self) -> Result<PathBuf> {
+        let tests_dir = self.path().join("tests");
+        fs::create_dir_all(&tests_dir)
+            .with_context(|| format!("could not make a directory at {}", tests_dir.display()))?;
+        let tests_path = tests_dir.join(CORPUS_REPLAY_TESTS).with_extension("rs");
+
+        let mut tests = corpus_replay_tests_template!().to_string();
+        for target in &self.targets {
+            let cfg = match self.required_features.get(target) {
+                Some(features) if !features.is_empty() => format!(
+                    "#[cfg(all({}))]\n",
+                    features
+                        .iter()
+                        .map(|feature| format!("feature = {:?}", feature))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                _ => String::new(),
+            };
+            tests += &corpus_replay_test_template!(identifier(target), target, cfg).to_string();
+        }
+        fs::write(&tests_path, tests)
+            .with_context(|| format!("failed to write {}", tests_path.display()))?;
+        Ok(tests_path)
+    }
+
+    /// Sync the corpora of one or all fuzz targets with remote storage.
+    ///
+    /// Neither direction deletes anything, so corpora from different machines
+    /// are merged, and libFuzzer's content-hashed file names keep duplicates
+    /// out.
+    pub fn exec_corpus_sync(&self, sync: &options::CorpusSync, push: bool) -> Result<()> {
+        let targets = match &sync.target {
+            Some(target) => {
+                if !self.targets.contains(target) {
+                    bail!("no fuzz target named `{}`", target);
+                }
+                vec![target.clone()]
+            }
+            None => self.targets.clone(),
+        };
+
+        for target in &targets {
+            let remote = match sync
+                .remote
+                .clone()
+                .or_else(|| self.config.target(Some(target)).corpus_remote)
+            {
+                Some(remote) => format!("{}/{}", remote.trim_end_matches('/'), target),
+                None => bail!(
+                    "no remote to sync with: pass `--remote <URL>` or set `corpus-remote` in {}",
+                    strip_current_dir_prefix(&self.config_path()).display()
+                ),
+            };
+            let local = self.corpus_for(target)?;
+            let local = local.to_str().ok_or_else(|| {
+                anyhow!("corpus directory {} is not valid UTF-8", local.display())
+            })?;
+
+            let (from, to) = if push {
+                (local, remote.as_str())
+            } else {
+                (remote.as_str(), local)
+            };
+            if !push {
+                self.track_corpus(target, Origin::Seed, None)?;
+            }
+            let mut cmd = sync_command(&remote)?;
+            cmd.arg(from).arg(to);
+            eprintln!("Syncing {} to {}", from, to);
+            let status = cmd
+                .status()
+                .with_context(|| format!("failed to run {:?}", cmd))?;
+            if !status.success() {
+                bail!("failed to sync the corpus for `{}`: {}", target, status);
+            }
+            if !push {
+                self.track_corpus(target, Origin::Pull, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve the corpora of every fuzz target to `cargo fuzz worker`s, and
+    /// collect the inputs and crash artifacts they find.
+    pub fn exec_serve(&self, serve: &options::Serve) -> Result<()> {
+        let mut targets = BTreeMap::new();
+        for target in &self.targets {
+            let dirs = distribute::TargetDirs {
+                corpus: self.corpus_for(target)?,
+                artifacts: self.artifacts_for(target)?,
+            };
+            targets.insert(target.clone(), dirs);
+        }
+        let listener = std::net::TcpListener::bind(&serve.bind)
+            .with_context(|| format!("failed to listen on {}", serve.bind))?;
+        eprintln!(
+            "Coordinating {} fuzz target(s) on {}",
+            targets.len(),
+            listener.local_addr()?
+        );
+        let store = Arc::new(distribute::Store::new(targets));
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("warning: failed to accept a connection: {}", e);
+                    continue;
+                }
+            };
+            let store = store.clone();
+            thread::spawn(move || {
+                if let Err(e) = store.handle(stream) {
+                    eprintln!("warning: failed to answer a worker: {:#}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Fuzz a target in rounds, syncing its corpus with a `cargo fuzz serve`
+    /// coordinator before each one and sending it the crash artifacts found.
+    pub fn exec_worker(&self, worker: &options::Worker) -> Result<()> {
+        let target = &worker.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        single_sanitizer(&self.build_options_for(&worker.build, Some(target)))?;
+        let client = distribute::Client::new(&worker.connect);
+        self.exec_build(&worker.build, Some(target))?;
+        self.track_corpus(target, Origin::Seed, None)?;
+
+        let start = time::Instant::now();
+        let mut sent = 0;
+        loop {
+            self.sync_with_coordinator(&client, target)?;
+            let remaining = worker
+                .max_total_time
+                .map(|secs| secs.saturating_sub(start.elapsed().as_secs()));
+            if remaining == Some(0) {
+                break;
+            }
+            let round = remaining.map_or(worker.sync_interval, |r| r.min(worker.sync_interval));
+            let run = options::Run {
+                build: worker.build.clone(),
+                target: Some(target.clone()),
+                corpus: Vec::new(),
+                all: false,
+                max_total_time: Some(round),
+                until_new_coverage_stalls: None,
+                jobs: worker.jobs,
+                fork: None,
+                ignore_known_crashes: None,
+                max_crashes: None,
+                timeout: None,
+                rss_limit: None,
+                malloc_limit: None,
+                record_trace: None,
+                save_hangs: false,
+                auto_tmin: false,
+                auto_tmin_time: None,
+                focus_changed: None,
+                filter: options::InputFilter::default(),
+                features: options::EngineFeatures::default(),
+                mode: None,
+                slow_threshold_ms: None,
+                monitor: false,
+                status_addr: None,
+                notify_webhook: None,
+                notify_plateau: None,
+                tui: false,
+                sarif: None,
+                asan_options: Vec::new(),
+                dicts: Vec::new(),
+                seed: None,
+                replay_manifest: None,
+                args: worker.args.clone(),
+            };
+            let before_fuzzing = time::SystemTime::now();
+            let mut cmd = self.fuzz_command(&run, target, true)?;
+            let status = cmd
+                .status()
+                .with_context(|| format!("failed to execute: {:?}", cmd))?;
+            self.track_corpus(target, Origin::Fuzz, None)?;
+            let artifacts = self.get_artifacts_since(target, &before_fuzzing)?;
+            if !status.success() && artifacts.is_empty() {
+                bail!(
+                    "the fuzzer exited with {} without saving an artifact",
+                    status
+                );
+            }
+            let mut artifacts: Vec<PathBuf> = artifacts.into_iter().collect();
+            artifacts.sort();
+            self.run_crash_hooks(&worker.build, target, "worker", &artifacts);
+            for artifact in &artifacts {
+                let name = artifact
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or_else(|| anyhow!("invalid artifact name: {}", artifact.display()))?;
+                let data = fs::read(artifact)
+                    .with_context(|| format!("failed to read {}", artifact.display()))?;
+                client.upload_artifact(target, name, &data)?;
+                eprintln!("Sent {} to the coordinator", artifact.display());
+                sent += 1;
+            }
+        }
+        eprintln!(
+            "Fuzzed for {} seconds and sent {} artifact(s) to the coordinator",
+            start.elapsed().as_secs(),
+            sent
+        );
+        Ok(())
+    }
+
+    /// Download the coordinator's corpus inputs that a target's corpus is
+    /// missing, and upload those it has that the coordinator's is missing.
+    fn sync_with_coordinator(&self, client: &distribute::Client, target: &str) -> Result<()> {
+        let corpus = self.corpus_for(target)?;
+        let local = distribute::hash_corpus(&corpus)?;
+        let remote: BTreeSet<String> = client.list(target)?.into_iter().collect();
+        let mut pulled = 0;
+        for hash in remote.iter().filter(|hash| !local.contains_key(*hash)) {
+            let path = corpus.join(hash);
+            fs::write(&path, client.download(target, hash)?)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            pulled += 1;
+        }
+        let mut pushed = 0;
+        for (hash, path) in &local {
+            if !remote.contains(hash) {
+                let data =
+                    fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+                client.upload(target, &data)?;
+                pushed += 1;
+            }
+        }
+        if pulled > 0 {
+            self.track_corpus(target, Origin::Pull, None)?;
+        }
+        eprintln!(
+            "Synced with the coordinator: pulled {} and pushed {} input(s)",
+            pulled, pushed
+        );
+        Ok(())
+    }
+
+    /// Replay the corpora of fuzz targets against each other, adding to each
+    /// target's corpus the other targets' inputs that reach coverage its own
+    /// corpus and seeds don't.
+    pub fn exec_corpus_cross_pollinate(&self, cross: &options::CorpusCrossPollinate) -> Result<()> {
+        let targets: Vec<&String> = self
+            .targets
+            .iter()
+            .filter(|t| {
+                cross
+                    .targets
+                    .as_ref()
+                    .is_none_or(|g| utils::glob_match(g, t))
+            })
+            .collect();
+        if targets.len() < 2 {
+            match &cross.targets {
+                Some(glob) => bail!(
+                    "cross-pollinating needs at least two fuzz targets, but {} match `{}`",
+                    targets.len(),
+                    glob
+                ),
+                None => bail!("cross-pollinating needs at least two fuzz targets"),
+            }
+        }
+        let build = libfuzzer_only(&cross.build, "corpus cross-pollinate")?;
+        self.exec_build(&build, None)?;
+
+        // Each target's corpus, followed by its seeds.
+        let mut corpora = Vec::new();
+        for target in &targets {
+            let mut dirs = vec![self.corpus_for(target)?];
+            dirs.extend(self.seeds_for(target)?);
+            corpora.push((*target, dirs));
+        }
+        let mut results = Vec::new();
+        for (target, own) in &corpora {
+            // Targets can share corpora, which have nothing to add.
+            let mut others: Vec<&PathBuf> = corpora
+                .iter()
+                .filter(|(other, _)| other != target)
+                .flat_map(|(_, dirs)| dirs)
+                .filter(|dir| !own.contains(dir))
+                .collect();
+            others.sort();
+            others.dedup();
+            if others.is_empty() {
+                continue;
+            }
+            let since = time::SystemTime::now();
+            let added = self.with_hooks(target, "corpus cross-pollinate", || {
+                self.pollinate(&build, target, own, &others)
+            })?;
+            eprintln!(
+                "`{}`: added {} input(s) from the other targets' corpora",
+                target, added
+            );
+            let crashes = self.get_artifacts_since(target, &since)?.len();
+            results.push((*target, added, crashes));
+        }
+
+        eprintln!("\n{:─<80}\n", "");
+        eprintln!("Summary:\n");
+        for (target, added, crashes) in &results {
+            eprint!("\t{}: {} new input(s)", target, added);
+            if *crashes > 0 {
+                eprint!(
+                    ", {} crash(es) saved to {}",
+                    crashes,
+                    strip_current_dir_prefix(&self.artifacts_for(target)?).display()
+                );
+            }
+            eprintln!();
+        }
+        eprintln!("\n{:─<80}\n", "");
+        Ok(())
+    }
+
+    /// Merge the inputs in `others` that reach new coverage of `target` into
+    /// its corpus, the first of `own`, counting the coverage of its seeds, the
+    /// rest of `own`, as already reached. Returns how many inputs were added.
+    fn pollinate(
+        &self,
+        build: &BuildOptions,
+        target: &str,
+        own: &[PathBuf],
+        others: &[&PathBuf],
+    ) -> Result<usize> {
+        self.track_corpus(target, Origin::Seed, None)?;
+        let tmp = tempfile::TempDir::new_in(self.path())?;
+        let merged = tmp.path().join("corpus");
+        let mut existing = Vec::new();
+        for dir in own {
+            existing.extend(collect_files(dir)?);
+        }
+        copy_inputs(&existing, &merged)?;
+        let before: HashSet<PathBuf> = collect_files(&merged)?.into_iter().collect();
+
+        // libFuzzer adds the inputs with new coverage to the first directory,
+        // and survives the inputs that crash, saving them as artifacts.
+        let mut cmd = self.binary_run(build, target)?;
+        cmd.arg("-merge=1").arg(&merged).args(others);
+        let output = cmd
+            .output()
+            .with_context(|| format!("could not execute command: {:?}", cmd))?;
+        if !output.status.success() {
+            bail!(
+                "failed to merge the other targets' corpora into that of `{}`: {:?}\n\n{}",
+                target,
+                cmd,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let corpus = &own[0];
+        let mut added = 0;
+        for input in collect_files(&merged)? {
+            if before.contains(&input) {
+                continue;
+            }
+            let dest = corpus.join(input.file_name().unwrap());
+            fs::copy(&input, &dest).with_context(|| {
+                format!("failed to copy {} to {}", input.display(), dest.display())
+            })?;
+            added += 1;
+        }
+        self.track_corpus(target, Origin::CrossPollinate, None)?;
+        Ok(added)
+    }
+
+    /// Copy inputs from other fuzzers' outputs and test suites into a fuzz
+    /// target's corpus, skipping any whose contents are already in it.
+    pub fn exec_corpus_import(&self, import: &options::CorpusImport) -> Result<()> {
+        if !self.targets.contains(&import.target) {
+            bail!("no fuzz target named `{}`", import.target);
+        }
+        let corpus = self.corpus_for(&import.target)?;
+        let mut seen = HashSet::new();
+        for file in collect_files(&corpus)? {
+            let data =
+                fs::read(&file).with_context(|| format!("failed to read {}", file.display()))?;
+            seen.insert(crash::fnv1a(&data));
+        }
+
+        self.track_corpus(&import.target, Origin::Seed, None)?;
+        let (mut imported, mut duplicates) = (0, 0);
+        for path in &import.paths {
+            let (source, inputs) = import::read_inputs(path, import.format)?;
+            eprintln!(
+                "Importing {} input(s) from {} {}",
+                inputs.len(),
+                source,
+                path.display()
+            );
+            for input in inputs {
+                let hash = crash::fnv1a(&input);
+                if !seen.insert(hash) {
+                    duplicates += 1;
+                    continue;
+                }
+                let dest = corpus.join(format!("{:016x}", hash));
+                fs::write(&dest, &input)
+                    .with_context(|| format!("failed to write {}", dest.display()))?;
+                imported += 1;
+            }
+        }
+        eprintln!(
+            "Imported {} new input(s) into {}, skipping {} duplicate(s)",
+            imported,
+            strip_current_dir_prefix(&corpus).display(),
+            duplicates
+        );
+        self.track_corpus(&import.target, Origin::Import, None)?;
+
+        if import.cmin {
+            self.exec_cmin(&options::Cmin {
+                build: import.build.clone(),
+                target: Some(import.target.clone()),
+                corpus: None,
+                all: false,
+                jobs: None,
+                prune_older_than: None,
+                preserve: Vec::new(),
+                pin_artifacts: false,
+                filter: options::InputFilter::default(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Truncate the inputs in a fuzz target's corpus that are over its
+    /// maximum input length, or remove them. Truncated inputs are renamed
+    /// after their new contents, and dropped if the corpus already has them.
+    pub fn exec_corpus_trim(&self, trim: &options::CorpusTrim) -> Result<()> {
+        let target = &trim.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let max_len = match trim.max_len {
+            Some(max_len) if !trim.to_max_len => max_len,
+            _ => self.config.target(Some(target)).max_len.ok_or_else(|| {
+                anyhow!(
+                    "`{}` has no `max-len` in fuzz/fuzz.toml; give one with `--max-len`",
+                    target
+                )
+            })?,
+        };
+        let corpus = self.corpus_for(target)?;
+        self.track_corpus(target, Origin::Seed, None)?;
+
+        let (mut truncated, mut dropped) = (0, 0);
+        for input in collect_files(&corpus)? {
+            let mut data =
+                fs::read(&input).with_context(|| format!("failed to read {}", input.display()))?;
+            if data.len() as u64 <= max_len {
+                continue;
+            }
+            fs::remove_file(&input)
+                .with_context(|| format!("failed to remove {}", input.display()))?;
+            if trim.drop {
+                dropped += 1;
+                continue;
+            }
+            data.truncate(max_len as usize);
+            let dest = corpus.join(format!("{:016x}", crash::fnv1a(&data)));
+            if dest.exists() {
+                dropped += 1;
+                continue;
+            }
+            fs::write(&dest, &data)
+                .with_context(|| format!("failed to write {}", dest.display()))?;
+            truncated += 1;
+        }
+        eprintln!(
+            "Truncated {} and dropped {} input(s) of {} over {} bytes",
+            truncated,
+            dropped,
+            strip_current_dir_prefix(&corpus).display(),
+            max_len
+        );
+        self.track_corpus(target, Origin::Seed, None)
+    }
+
+    /// Run the fuzzed crate's tests with its `fuzz_record` module recording,
+    /// and add the inputs they record to a fuzz target's corpus.
+    ///
+    /// The first time, this adds the module, which the crate then has to call.
+    pub fn exec_corpus_from_tests(&self, from: &options::CorpusFromTests) -> Result<()> {
+        if !self.targets.contains(&from.target) {
+            bail!("no fuzz target named `{}`", from.target);
+        }
+        let module = self
+            .root_lib_path()?
+            .with_file_name(format!("{}.rs", RECORD_MODULE));
+        if !module.is_file() {
+            self.create_root_module(
+                RECORD_MODULE,
+                &record_module_template!().to_string(),
+                "Records the inputs of the tests for `cargo fuzz corpus from-tests`.",
+            )?;
+        }
+        let call = format!("{}::record(", RECORD_MODULE);
+        let mut called = false;
+        for file in collect_rust_sources(module.parent().unwrap())? {
+            if file != module {
+                called |= fs::read_to_string(&file).is_ok_and(|source| source.contains(&call));
+            }
+        }
+        if !called {
+            bail!(
+                "nothing calls `crate::{0}::record` yet; call it with the input at the start of \
+                 the function that `{1}` fuzzes, like `crate::{0}::record(data);`, and run this \
+                 again; see {2}",
+                RECORD_MODULE,
+                from.target,
+                strip_current_dir_prefix(&module).display()
+            );
+        }
+
+        let recorded = tempfile::TempDir::new_in(self.path())?;
+        // The cfg makes `record` record; the tests get a target directory of
+        // their own, since it rebuilds everything.
+        let mut rustflags = vec!["--cfg".to_string(), "cargo_fuzz_record".to_string()];
+        rustflags.extend(env_rustflags().into_iter().flat_map(|(_, flags)| flags));
+        let mut cmd = utils::toolchain_command("cargo");
+        cmd.arg("test")
+            .arg("--manifest-path")
+            .arg(self.root_project.join("Cargo.toml"))
+            .args(&from.args)
+            .env(
+                "CARGO_TARGET_DIR",
+                self.cargo_target_dir().join("from-tests"),
+            )
+            .env("CARGO_ENCODED_RUSTFLAGS", rustflags.join("\x1f"))
+            .env_remove("RUSTFLAGS")
+            .env(RECORD_DIR_ENV, recorded.path());
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        let inputs = collect_files(recorded.path())?;
+        if !status.success() {
+            if inputs.is_empty() {
+                bail!("the tests failed without recording any inputs: {:?}", cmd);
+            }
+            eprintln!("warning: some tests failed; adding the inputs they recorded anyway");
+        }
+
+        let corpus = self.corpus_for(&from.target)?;
+        let mut seen = HashSet::new();
+        for file in collect_files(&corpus)? {
+            let data =
+                fs::read(&file).with_context(|| format!("failed to read {}", file.display()))?;
+            seen.insert(crash::fnv1a(&data));
+        }
+        self.track_corpus(&from.target, Origin::Seed, None)?;
+        let mut added = 0;
+        for input in &inputs {
+            let data =
+                fs::read(input).with_context(|| format!("failed to read {}", input.display()))?;
+            let hash = crash::fnv1a(&data);
+            if seen.insert(hash) {
+                let dest = corpus.join(format!("{:016x}", hash));
+                fs::write(&dest, &data)
+                    .with_context(|| format!("failed to write {}", dest.display()))?;
+                added += 1;
+            }
+        }
+        eprintln!(
+            "The tests recorded {} distinct input(s); added {} new input(s) to {}",
+            inputs.len(),
+            added,
+            strip_current_dir_prefix(&corpus).display()
+        );
+        self.track_corpus(&from.target, Origin::FromTests, None)?;
+
+        if from.cmin {
+            self.exec_cmin(&options::Cmin {
+                build: from.build.clone(),
+                target: Some(from.target.clone()),
+                corpus: None,
+                all: false,
+                jobs: None,
+                prune_older_than: None,
+                preserve: Vec::new(),
+                pin_artifacts: false,
+                filter: options::InputFilter::default(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Write a fuzz target's corpus or artifacts, or the given inputs, as
+    /// text that `corpus import --format` reads back.
+    pub fn exec_corpus_export(&self, export: &options::CorpusExport) -> Result<()> {
+        if !self.targets.contains(&export.target) {
+            bail!("no fuzz target named `{}`", export.target);
+        }
+        let files = if export.artifacts {
+            collect_files(&self.artifacts_for(&export.target)?)?
+                .into_iter()
+                .filter(|path| !is_divergence_report(path))
+                .collect()
+        } else if export.paths.is_empty() {
+            collect_files(&self.corpus_for(&export.target)?)?
+        } else {
+            let mut files = Vec::new();
+            for path in &export.paths {
+                if path.is_dir() {
+                    files.extend(collect_files(path)?);
+                } else if path.is_file() {
+                    files.push(path.clone());
+                } else {
+                    bail!("nothing to export at {}", path.display());
+                }
+            }
+            files
+        };
+        let mut inputs = Vec::new();
+        for file in &files {
+            let data =
+                fs::read(file).with_context(|| format!("failed to read {}", file.display()))?;
+            let name = file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            inputs.push((name, data));
+        }
+
+        let text = export::render(export.format, &export.target, &inputs);
+        match &export.output {
+            Some(output) => {
+                fs::write(output, text)
+                    .with_context(|| format!("failed to write {}", output.display()))?;
+                eprintln!(
+                    "Exported {} input(s) of `{}` as {} to {}",
+                    inputs.len(),
+                    export.target,
+                    export.format,
+                    output.display()
+                );
+            }
+            None => print!("{}", text),
+        }
+        Ok(())
+    }
+
+    /// Print where the inputs in a fuzz target's corpus came from, and when.
+    pub fn exec_corpus_info(&self, info: &options::CorpusInfo) -> Result<()> {
+        let target = &info.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let index = provenance::load(&self.provenance_path(target))?;
+        let cutoff = info.since.map(|age| {
+            time::SystemTime::now()
+                .checked_sub(age)
+                .and_then(|cutoff| cutoff.duration_since(time::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs())
+        });
+        let filtering = cutoff.is_some() || info.origin.is_some() || info.run.is_some();
+
+        let corpus = self.corpus_for(target)?;
+        let mut inputs = Vec::new();
+        for input in collect_files(&corpus)? {
+            let data =
+                fs::read(&input).with_context(|| format!("failed to read {}", input.display()))?;
+            let entry = index.get(&format!("{:016x}", crash::fnv1a(&data)));
+            let shown = match entry {
+                Some(entry) => {
+                    cutoff.is_none_or(|cutoff| entry.discovered >= cutoff)
+                        && info.origin.is_none_or(|origin| entry.origin == origin)
+                        && info
+                            .run
+                            .as_ref()
+                            .is_none_or(|run| entry.run.as_ref() == Some(run))
+                }
+                None => !filtering,
+            };
+            if shown {
+                let name = input.file_name().unwrap().to_string_lossy().into_owned();
+                inputs.push((name, entry));
+            }
+        }
+        if inputs.is_empty() {
+            eprintln!(
+                "No matching inputs in {}",
+                strip_current_dir_prefix(&corpus).display()
+            );
+            return Ok(());
+        }
+        eprintln!(
+            "Provenance of the inputs in {}:\n",
+            strip_current_dir_prefix(&corpus).display()
+        );
+        print!("{}", provenance::render(&inputs));
+        Ok(())
+    }
+
+    /// Generate a dictionary for a fuzz target from the literals in the fuzzed
+    /// crate's sources and in the target itself.
+    pub fn exec_dict(&self, dict: &options::Dict) -> Result<()> {
+        if !self.targets.contains(&dict.target) {
+            bail!("no fuzz target named `{}`", dict.target);
+        }
+        let mut sources = collect_rust_sources(&self.root_project.join("src"))?;
+        sources.push(self.target_path(&dict.target));
+
+        let mut entries = BTreeSet::new();
+        for source in &sources {
+            let source = fs::read_to_string(source)
+                .with_context(|| format!("failed to read {}", source.display()))?;
+            dict::scan(&source, &mut entries);
+        }
+
+        let output = match &dict.output {
+            Some(output) => output.clone(),
+            None => self.dict_path(&dict.target),
+        };
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+        }
+        fs::write(&output, dict::render(&entries))
+            .with_context(|| format!("failed to write {}", output.display()))?;
+
+        eprintln!(
+            "Wrote {} dictionary entries from {} source file(s) to:\n\n\t{}\n",
+            entries.len(),
+            sources.len(),
+            strip_current_dir_prefix(&output).display()
+        );
+        Ok(())
+    }
+
+    /// Print the statistics of a fuzz target's most recent runs.
+    pub fn exec_stats(&self, stats: &options::Stats) -> Result<()> {
+        if !self.targets.contains(&stats.target) {
+            bail!("no fuzz target named `{}`", stats.target);
+        }
+        let path = self.stats_path(&stats.target);
+        if !path.is_file() {
+            eprintln!(
+                "No runs of `{}` recorded yet; `cargo fuzz run {}` records them in {}",
+                stats.target,
+                stats.target,
+                strip_current_dir_prefix(&path).display()
+            );
+            return Ok(());
+        }
+        let records = stats::load(&path)?;
+        let shown = &records[records.len().saturating_sub(stats.last)..];
+        eprintln!(
+            "Last {} of {} run(s) of `{}`:\n",
+            shown.len(),
+            records.len(),
+            stats.target
+        );
+        print!("{}", stats::render(shown));
+        Ok(())
+    }
+
+    /// Show how much of each fuzz target's corpus and artifacts, or those of
+    /// each one matching a glob, git tracks, and warn about the artifacts it
+    /// doesn't, since those crashes are only on this machine.
+    pub fn exec_status(&self, status: &options::Status) -> Result<()> {
+        let targets: Vec<&String> = self
+            .targets
+            .iter()
+            .filter(|t| {
+                status.targets.as_ref().is_none_or(|g| {
+                    utils::glob_match(g, t) || utils::glob_match(g, &self.target_subpath(t))
+                })
+            })
+            .collect();
+        if targets.is_empty() {
+            match &status.targets {
+                Some(glob) => bail!("no fuzz targets match `{}`", glob),
+                None => bail!("no fuzz targets found"),
+            }
+        }
+        if git_output(&self.path(), &["rev-parse", "--is-inside-work-tree"]).is_none() {
+            bail!(
+                "{} isn't in a git repository",
+                strip_current_dir_prefix(&self.path()).display()
+            );
+        }
+
+        let mut untracked = Vec::new();
+        for target in targets {
+            let corpus_dir = self.corpus_for(target)?;
+            let corpus = collect_files(&corpus_dir)?;
+            let tracked_corpus = tracked_files(&corpus_dir)?;
+            let artifacts_dir = self.artifacts_for(target)?;
+            let artifacts: Vec<PathBuf> = collect_files(&artifacts_dir)?
+                .into_iter()
+                .filter(|path| !is_divergence_report(path))
+                .collect();
+            let tracked_artifacts = tracked_files(&artifacts_dir)?;
+            println!(
+                "{}: {} of {} corpus input(s) and {} of {} artifact(s) tracked",
+                target,
+                corpus
+                    .iter()
+                    .filter(|p| tracked_corpus.contains(*p))
+                    .count(),
+                corpus.len(),
+                artifacts
+                    .iter()
+                    .filter(|p| tracked_artifacts.contains(*p))
+                    .count(),
+                artifacts.len()
+            );
+            untracked.extend(
+                artifacts
+                    .into_iter()
+                    .filter(|p| !tracked_artifacts.contains(p)),
+            );
+        }
+
+        if !untracked.is_empty() {
+            eprintln!(
+                "\nwarning: {} artifact(s) aren't tracked by git, so the crashes they \
+                 reproduce are only on this machine:\n",
+                untracked.len()
+            );
+            for artifact in &untracked {
+                eprintln!("\t{}", strip_current_dir_prefix(artifact).display());
+            }
+            eprintln!(
+                "\nCommit the ones worth keeping with `git add -f <artifact>`, or turn them \
+                 into regression tests with `cargo fuzz gen-test <target> <artifact>`."
+            );
+        }
+        Ok(())
+    }
+
+    /// Time a target's saved slow inputs again, and compare that with how
+    /// long they took when they were saved.
+    pub fn exec_bench(&self, bench: &options::Bench) -> Result<()> {
+        let target = &bench.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        if bench.corpus {
+            return self.exec_bench_corpus(bench);
+        }
+        let dir = self.path().join("slow").join(target);
+        let inputs = if dir.is_dir() {
+            collect_files(&dir)?
+        } else {
+            Vec::new()
+        };
+        if inputs.is_empty() {
+            eprintln!(
+                "No slow inputs of `{}` saved yet; `cargo fuzz run {} --slow-threshold-ms <ms>` \
+                 saves them to {}, or use `--corpus` to time the whole corpus",
+                target,
+                target,
+                strip_current_dir_prefix(&dir).display()
+            );
+            return Ok(());
+        }
+
+        let build = libfuzzer_only(&bench.build, "bench")?;
+        self.exec_build(&build, Some(target))?;
+        eprintln!(
+            "Timing {} slow input(s) of `{}`, {} run(s) each\n",
+            inputs.len(),
+            target,
+            bench.runs
+        );
+        let current: slow::Timings = self
+            .time_inputs(&build, target, &inputs, bench.runs)?
+            .into_iter()
+            .filter_map(|(input, ms)| Some((input.file_name()?.to_str()?.to_string(), ms)))
+            .collect();
+        let timings_path = self.slow_timings_path(target);
+        let mut recorded = slow::load(&timings_path)?;
+        print!("{}", slow::render(&recorded, &current));
+        if bench.update {
+            recorded.extend(current);
+            slow::save(&timings_path, &recorded)?;
+        }
+        Ok(())
+    }
+
+    /// Replay a target's corpus through an optimized build without
+    /// instrumentation, and compare its throughput with the recorded one.
+    fn exec_bench_corpus(&self, bench: &options::Bench) -> Result<()> {
+        let target = &bench.target;
+        let mut inputs = collect_files(&self.corpus_for(target)?)?;
+        for seeds in self.seeds_for(target)? {
+            inputs.extend(collect_files(&seeds)?);
+        }
+        if inputs.is_empty() {
+            bail!("the corpus of `{}` is empty; fuzz it first", target);
+        }
+
+        let binary = self.build_shim(&bench.build, target, decoder::Shim::Bench)?;
+        // A big corpus wouldn't fit on the command line.
+        let mut list = tempfile::NamedTempFile::new()?;
+        for input in &inputs {
+            let input = input
+                .to_str()
+                .ok_or_else(|| anyhow!("corpus input paths must be valid unicode"))?;
+            writeln!(list, "{}", input)?;
+        }
+        list.flush()?;
+        eprintln!(
+            "Replaying {} corpus input(s) of `{}`, {} run(s) each\n",
+            inputs.len(),
+            target,
+            bench.runs
+        );
+        let mut cmd = Command::new(&binary);
+        cmd.arg(bench.runs.to_string())
+            .arg(list.path())
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit());
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !output.status.success() {
+            bail!(
+                "failed to time the corpus of `{}`: {}",
+                target,
+                output.status
+            );
+        }
+
+        let mut timings = Vec::new();
+        let mut panicked = 0;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        for (input, line) in inputs.iter().zip(stdout.lines()) {
+            match line.parse::<u64>() {
+                Ok(nanos) => {
+                    let len = fs::metadata(input).map_or(0, |m| m.len());
+                    timings.push((nanos / bench.runs, len));
+                }
+                Err(_) => panicked += 1,
+            }
+        }
+        if panicked > 0 {
+            eprintln!("Skipped {} input(s) that panicked\n", panicked);
+        }
+        if timings.is_empty() {
+            bail!("every input in the corpus of `{}` panicked", target);
+        }
+
+        let current = bench::Throughput::measure(&timings);
+        let path = self.bench_path(target);
+        let recorded = bench::load(&path)?;
+        print!("{}", bench::render(recorded.as_ref(), &current));
+        if bench.update {
+            bench::save(&path, &current)?;
+        }
+        Ok(())
+    }
+
+    /// Replay a target's corpus, or fuzz it for a while, under a sampling
+    /// profiler, and draw a flamegraph of where the time went.
+    pub fn exec_profile(&self, profile: &options::Profile) -> Result<()> {
+        let target = &profile.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let corpus = self.corpus_for(target)?;
+        let seeds = self.seeds_for(target)?;
+        if profile.time.is_none() {
+            let mut inputs = collect_files(&corpus)?.len();
+            for seeds in &seeds {
+                inputs += collect_files(seeds)?.len();
+            }
+            if inputs == 0 {
+                bail!(
+                    "the corpus of `{}` is empty; fuzz it first, or profile fuzzing it with \
+                     `--time <secs>`",
+                    target
+                );
+            }
+        }
+
+        let mut build = libfuzzer_only(&profile.build, "profile")?;
+        build.profiling = true;
+        self.exec_build(&build, Some(target))?;
+        let dir = self.path().join("profile").join(target);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+
+        let mut cmd = self.binary_run(&build, target)?;
+        match profile.time {
+            Some(secs) => cmd.arg(format!("-max_total_time={}", secs)),
+            None => cmd.arg("-runs=0"),
+        };
+        cmd.args(&profile.args).arg(&corpus).args(&seeds);
+        eprintln!(
+            "{} `{}` under a profiler, sampling {} times a second",
+            match profile.time {
+                Some(secs) => format!("Fuzzing for {} second(s) with", secs),
+                None => "Replaying the corpus of".to_string(),
+            },
+            target,
+            profile.frequency
+        );
+        let (status, folded) = flamegraph::record(&cmd, profile.frequency, &dir)?;
+        if !status.success() {
+            eprintln!(
+                "warning: `{}` exited with {}, so the profile stops there",
+                target, status
+            );
+        }
+        if folded.is_empty() {
+            bail!("no stacks of `{}` were sampled", target);
+        }
+
+        let stacks = dir.join("stacks.folded");
+        fs::write(&stacks, flamegraph::render_folded(&folded))
+            .with_context(|| format!("failed to write {}", stacks.display()))?;
+        let svg = dir.join("flamegraph.svg");
+        let title = format!("cargo fuzz profile {}", target);
+        fs::write(&svg, flamegraph::render_svg(&title, &folded))
+            .with_context(|| format!("failed to write {}", svg.display()))?;
+
+        let samples: u64 = folded.values().sum();
+        eprintln!(
+            "\nThe functions the most of the {} sample(s) were taken in:\n",
+            samples
+        );
+        for (function, count) in flamegraph::hottest(&folded, MAX_HOTTEST_LISTED) {
+            println!(
+                "{:>8} {:>6.1}%  {}",
+                count,
+                count as f64 * 100.0 / samples as f64,
+                function
+            );
+        }
+        eprintln!(
+            "\nWrote the flamegraph to {}, and the folded stacks to {}",
+            strip_current_dir_prefix(&svg).display(),
+            strip_current_dir_prefix(&stacks).display()
+        );
+        Ok(())
+    }
+
+    /// Replay a target's corpus, or the given inputs, under Miri, and save the
+    /// inputs it finds undefined behavior in to `fuzz/miri/<target>/`.
+    pub fn exec_miri(&self, miri: &options::Miri) -> Result<()> {
+        let target = &miri.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let mut inputs = Vec::new();
+        if miri.inputs.is_empty() {
+            inputs.extend(collect_files(&self.corpus_for(target)?)?);
+            for seeds in self.seeds_for(target)? {
+                inputs.extend(collect_files(&seeds)?);
+            }
+        } else {
+            for path in &miri.inputs {
+                if path.is_dir() {
+                    inputs.extend(collect_files(path)?);
+                } else if path.is_file() {
+                    inputs.push(path.clone());
+                } else {
+                    bail!("there is no input at {}", path.display());
+                }
+            }
+        }
+        let config = self.config.target(Some(target));
+        let skip: Vec<&String> = config
+            .miri_skip
+            .iter()
+            .flatten()
+            .chain(&miri.skip)
+            .collect();
+        let total = inputs.len();
+        inputs.retain(|input| {
+            let name = input.file_name().unwrap_or_default().to_string_lossy();
+            !skip.iter().any(|glob| utils::glob_match(glob, &name))
+        });
+        if inputs.len() < total {
+            eprintln!(
+                "Skipping {} input(s) of `{}` on the skip list",
+                total - inputs.len(),
+                target
+            );
+        }
+        if inputs.is_empty() {
+            eprintln!("No inputs of `{}` to replay under Miri", target);
+            return Ok(());
+        }
+
+        let installed = utils::toolchain_command("cargo")
+            .args(["miri", "--version"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !installed {
+            bail!(
+                "Miri isn't installed for this toolchain; run `rustup +nightly component add \
+                 miri` and use `cargo +nightly fuzz miri`"
+            );
+        }
+        let build = self.build_options_for(&miri.build, Some(target));
+        let dir = self.write_shim(target, decoder::Shim::Miri)?;
+        // Without an input, the stand-in does nothing, so this only builds
+        // the fuzz target, and Miri's standard library the first time.
+        let mut cmd = shim_cargo(&build, &dir, target, &["miri", "run"]);
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to execute: {:?}", cmd))?;
+        if !status.success() {
+            return Err(exit::fail(
+                ExitCode::BuildFailed,
+                format!("failed to build `{}` for Miri: {:?}", target, cmd),
+            ));
+        }
+
+        // The stand-in reads the input from a file, which Miri's isolation
+        // from the host would forbid.
+        let mut flags = env::var("MIRIFLAGS").unwrap_or_default();
+        flags.push_str(" -Zmiri-disable-isolation");
+        let findings = self.path().join("miri").join(target);
+        eprintln!(
+            "Replaying {} input(s) of `{}` under Miri, for up to {} second(s) each\n",
+            inputs.len(),
+            target,
+            miri.timeout
+        );
+        let mut results = Vec::new();
+        for input in &inputs {
+            let mut report = tempfile::tempfile()?;
+            let mut cmd = shim_cargo(&build, &dir, target, &["miri", "run", "--quiet"]);
+            cmd.arg("--")
+                .arg(input)
+                .env("MIRIFLAGS", flags.trim_start())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(report.try_clone()?);
+            let mut child = cmd
+                .spawn()
+                .with_context(|| format!("failed to execute: {:?}", cmd))?;
+            let deadline = time::Instant::now() + time::Duration::from_secs(miri.timeout);
+            let status = loop {
+                if let Some(status) = child.try_wait().with_context(|| {
+                    format!("failed to wait on child process for command: {:?}", cmd)
+                })? {
+                    break Some(status);
+                }
+                if time::Instant::now() >= deadline {
+                    kill_tree(&mut child);
+                    break None;
+                }
+                thread::sleep(time::Duration::from_millis(50));
+            };
+            let mut stderr = String::new();
+            report.seek(SeekFrom::Start(0))?;
+            report.read_to_string(&mut stderr)?;
+            let outcome = match status {
+                Some(status) => miri::classify(status.code(), &stderr),
+                None => miri::Outcome::TimedOut,
+            };
+            let input = strip_current_dir_prefix(input).to_path_buf();
+            eprintln!("{}: {}", input.display(), outcome);
+            let saved = match outcome {
+                miri::Outcome::UndefinedBehavior(_) => {
+                    let data = fs::read(&input)
+                        .with_context(|| format!("failed to read {}", input.display()))?;
+                    let path = findings.join(format!("ub-{:016x}", crash::fnv1a(&data)));
+                    fs::create_dir_all(&findings).with_context(|| {
+                        format!("could not make a directory at {}", findings.display())
+                    })?;
+                    fs::write(&path, &data)
+                        .with_context(|| format!("failed to write {}", path.display()))?;
+                    let report = path.with_extension("txt");
+                    fs::write(&report, &stderr)
+                        .with_context(|| format!("failed to write {}", report.display()))?;
+                    Some(strip_current_dir_prefix(&path).to_path_buf())
+                }
+                _ => None,
+            };
+            results.push((input, outcome, saved));
+        }
+
+        let summary = miri::render(target, &results);
+        println!("\n{}", summary.trim_end());
+        let found = results
+            .iter()
+            .filter(|(_, outcome, _)| matches!(outcome, miri::Outcome::UndefinedBehavior(_)))
+            .count();
+        if found > 0 {
+            let path = findings.join("summary.txt");
+            fs::write(&path, &summary)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            bail!(
+                "Miri found undefined behavior in {} input(s) of `{}`",
+                found,
+                target
+            );
+        }
+        Ok(())
+    }
+
+    /// Append the statistics of a finished run to the target's stats file,
+    /// and record the corpus inputs it found.
+    fn record_run(
+        &self,
+        run: &options::Run,
+        target: &str,
+        recorder: stats::RunRecorder,
+    ) -> Result<()> {
+        self.track_corpus(target, Origin::Fuzz, Some(stats::run_id()))?;
+        match recorder.finish() {
+            Some(mut record) => {
+                record.limits = self.limits(run, target)?;
+                stats::append(&self.stats_path(target), &record)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The resource limits to fuzz a target with, from the command line or
+    /// the config file. They mustn't also be given as libFuzzer arguments,
+    /// and a malloc limit can't be over the RSS limit.
+    fn limits(&self, run: &options::Run, target: &str) -> Result<stats::Limits> {
+        let config = self.config.target(Some(target));
+        let mut limits = stats::Limits {
+            timeout_secs: run.timeout.or(config.timeout),
+            rss_limit_mb: run.rss_limit.or(config.rss_limit),
+            malloc_limit_mb: run.malloc_limit.or(config.malloc_limit),
+        };
+        // Hunting leaks, a big allocation is reported where it's made, rather
+        // than growing the heap until the RSS limit hides which input leaked.
+        let malloc_arg = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&run.args)
+            .any(|arg| arg.starts_with("-malloc_limit_mb="));
+        if run.mode == Some(RunMode::Leaks) && limits.malloc_limit_mb.is_none() && !malloc_arg {
+            limits.malloc_limit_mb = Some(match limits.rss_limit_mb {
+                Some(rss) if rss != 0 => rss.min(LEAKS_MALLOC_LIMIT_MB),
+                _ => LEAKS_MALLOC_LIMIT_MB,
+            });
+        }
+        if limits.timeout_secs == Some(0) {
+            bail!("the timeout must be at least 1 second");
+        }
+        if let (Some(rss), Some(malloc)) = (limits.rss_limit_mb, limits.malloc_limit_mb) {
+            if rss != 0 && malloc > rss {
+                bail!(
+                    "the malloc limit ({} MB) is over the RSS limit ({} MB)",
+                    malloc,
+                    rss
+                );
+            }
+        }
+        let flags = [
+            (limits.timeout_secs, "--timeout", "-timeout="),
+            (limits.rss_limit_mb, "--rss-limit", "-rss_limit_mb="),
+            (
+                limits.malloc_limit_mb,
+                "--malloc-limit",
+                "-malloc_limit_mb=",
+            ),
+        ];
+        for arg in config.args.iter().flatten().chain(&run.args) {
+            for (limit, flag, engine_arg) in flags {
+                if limit.is_some() && arg.starts_with(engine_arg) {
+                    bail!(
+                        "`{}` sets the same limit as `{}` (or its setting in fuzz/fuzz.toml), \
+                         so give only one of them",
+                        arg,
+                        flag
+                    );
+                }
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Warn about the inputs that fuzzing a target starts from that are over
+    /// its maximum input length, which libFuzzer only sees the start of, so
+    /// that a `max-len` below what the corpus was built with doesn't go
+    /// unnoticed.
+    fn warn_over_max_len(&self, run: &options::Run, target: &str) -> Result<()> {
+        let config = self.config.target(Some(target));
+        let arg = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&run.args)
+            .rev()
+            .find_map(|arg| arg.strip_prefix("-max_len="));
+        let max_len = match arg.map(str::parse) {
+            Some(Ok(max_len)) => max_len,
+            Some(Err(_)) => return Ok(()),
+            None => match config.max_len {
+                Some(max_len) => max_len,
+                None => return Ok(()),
+            },
+        };
+        let mut over = 0;
+        for corpus in self.replay_corpora(run, target)? {
+            if !corpus.is_dir() {
+                continue;
+            }
+            for input in collect_files(&corpus)? {
+                let len = fs::metadata(&input)
+                    .with_context(|| format!("failed to read {}", input.display()))?
+                    .len();
+                if len > max_len {
+                    over += 1;
+                }
+            }
+        }
+        if over > 0 {
+            eprintln!(
+                "warning: {} input(s) of `{}` are over its max-len of {} bytes, and only their \
+                 first {} bytes are fuzzed; `cargo fuzz corpus trim {} --to-max-len` truncates \
+                 them, or drops them with `--drop`",
+                over, target, max_len, max_len, target
+            );
+        }
+        Ok(())
+    }
+
+    /// Warn about the libFuzzer arguments, from the config file or the
+    /// command line, that libFuzzer would ignore or take as something else.
+    /// The flags it knows are those of the libfuzzer-sys the fuzz crate's
+    /// lockfile pins, unless it links a libFuzzer archive of its own.
+    fn warn_engine_args(&self, run: &options::Run, target: &str, build: &BuildOptions) {
+        if build.engine.unwrap_or(Engine::Libfuzzer) != Engine::Libfuzzer {
+            return;
+        }
+        let config = self.config.target(Some(target));
+        let args: Vec<String> = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&run.args)
+            .cloned()
+            .collect();
+        let version = match build.libfuzzer_archive {
+            Some(_) => None,
+            None => self
+                .locked_version("libfuzzer-sys")
+                .and_then(|locked| version_range(&format!("={}", locked.rsplit(' ').next()?)))
+                .map(|(locked, _)| locked),
+        };
+        for warning in engine_args::check(&args, version) {
+            eprintln!("warning: {}", warning);
+        }
+    }
+
+    /// The engine features to fuzz a target with, turned on or off on the
+    /// command line or in the config file. They mustn't also be given as
+    /// libFuzzer arguments.
+    fn engine_features(
+        &self,
+        run: &options::Run,
+        target: &str,
+    ) -> Result<Vec<(EngineFeature, bool)>> {
+        let config = self.config.target(Some(target));
+        let features = run.features.resolve(&config);
+        for arg in config.args.iter().flatten().chain(&run.args) {
+            let option = arg.split('=').next().unwrap_or(arg);
+            for (feature, _) in &features {
+                if option == libfuzzer_feature_flag(*feature) {
+                    bail!(
+                        "`{}` turns {} on or off like `{}` (or its setting in \
+                         fuzz/fuzz.toml), so give only one of them",
+                        arg,
+                        feature,
+                        feature.flag()
+                    );
+                }
+            }
+        }
+        Ok(features)
+    }
+
+    /// Add the inputs in a target's corpus that aren't in its provenance index
+    /// yet to it with `origin` and `run`, and drop the inputs that are no
+    /// longer in the corpus.
+    ///
+    /// Commands that add inputs track the corpus as seeds first, so that only
+    /// the inputs they add get their origin.
+    fn track_corpus(&self, target: &str, origin: Origin, run: Option<&str>) -> Result<()> {
+        let path = self.provenance_path(target);
+        let mut index = provenance::load(&path)?;
+        let mut present = HashSet::new();
+        let mut changed = false;
+        for input in collect_files(&self.corpus_for(target)?)? {
+            let data =
+                fs::read(&input).with_context(|| format!("failed to read {}", input.display()))?;
+            let hash = format!("{:016x}", crash::fnv1a(&data));
+            if !index.contains_key(&hash) {
+                let modified = fs::metadata(&input)
+                    .and_then(|m| m.modified())
+                    .unwrap_or_else(|_| time::SystemTime::now());
+                let entry = provenance::Entry {
+                    origin,
+                    discovered: modified
+                        .duration_since(time::UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0),
+                    run: run.map(String::from),
+                };
+                index.insert(hash.clone(), entry);
+                changed = true;
+            }
+            present.insert(hash);
+        }
+        let tracked = index.len();
+        index.retain(|hash, _| present.contains(hash));
+        if changed || index.len() != tracked {
+            provenance::save(&path, &index)?;
+        }
+        Ok(())
+    }
+
+    /// Save a fuzz target's corpus, fuzzing settings, seed, and binary hash
+    /// as a session that `exec_session_resume` can pick up again.
+    pub fn exec_session_save(&self, save: &options::SessionSave) -> Result<()> {
+        let target = &save.target;
+        if !self.targets.contains(target) {
+            bail!("no fuzz target named `{}`", target);
+        }
+        let dir = self.session_dir(&save.name)?;
+        if dir.exists() {
+            if !save.force {
+                bail!(
+                    "there is already a session named `{}`; pass `--force` to replace it",
+                    save.name
+                );
+            }
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("failed to remove {}", dir.display()))?;
+        }
+
+        let build = libfuzzer_only(
+            &self.build_options_for(&save.build, Some(target)),
+            "session save",
+        )?;
+        self.exec_build(&build, Some(target))?;
+        let binary_hash = session::binary_hash(&self.target_binary(&build, target))?;
+
+        // The seed is stored on its own, and passed after the session's other
+        // arguments when resuming.
+        let config = self.config.target(Some(target));
+        let (seeds, args): (Vec<String>, Vec<String>) = config
+            .args
+            .iter()
+            .flatten()
+            .chain(&save.args)
+            .cloned()
+            .partition(|arg| arg.starts_with("-seed="));
+        let seed = match save.seed {
+            Some(seed) => seed,
+            None => match seeds.last() {
+                Some(arg) => arg["-seed=".len()..]
+                    .parse()
+                    .with_context(|| format!("invalid libFuzzer argument: {}", arg))?,
+                None => random_seed(),
+            },
+        };
+
+        let corpus_dir = dir.join("corpus");
+        fs::create_dir_all(&corpus_dir)
+            .with_context(|| format!("could not make a directory at {}", corpus_dir.display()))?;
+        let mut inputs = collect_files(&self.corpus_for(target)?)?;
+        for seeds in self.seeds_for(target)? {
+            inputs.extend(collect_files(&seeds)?);
+        }
+        for input in &inputs {
+            let copy = corpus_dir.join(input.file_name().unwrap());
+            fs::copy(input, &copy).with_context(|| {
+                format!("failed to copy {} to {}", input.display(), copy.display())
+            })?;
+        }
+
+        // Keep the dictionary with the session, since it steers the mutations.
+        if !args.iter().any(|arg| arg.starts_with("-dict=")) {
+            if let Some(dict) = self.dict_for(target, &[])? {
+                let copy = dir.join(format!("{}.dict", target));
+                fs::copy(&dict, &copy).with_context(|| {
+                    format!("failed to copy {} to {}", dict.display(), copy.display())
+                })?;
+            }
+        }
+
+        let session = session::Session {
+            target: target.clone(),
+            saved: time::SystemTime::now()
+                .duration_since(time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            triple: build.triple.clone(),
+            settings: TargetConfig {
+                sanitizer: Some(single_sanitizer(&build)?),
+                dev: Some(build.dev),
+                release: Some(build.release),
+                profile: build.profile.clone(),
+                debug_assertions: Some(build.debug_assertions),
+                features: build.features.clone(),
+                no_default_features: Some(build.no_default_features),
+                all_features: Some(build.all_features),
+                coverage_crates: build
+                    .coverage_crates
+                    .as_deref()
+                    .map(rustc_wrapper::parse_crates),
+                args: Some(args),
+                value_profile: config.value_profile,
+                entropic: config.entropic,
+                cmp_log: config.cmp_log,
+                ..TargetConfig::default()
+            },
+            seed,
+            binary_hash,
+        };
+        session.save(&dir)?;
+        eprintln!(
+            "Saved session `{}` of `{}` with {} corpus input(s) and seed {} to {}",
+            save.name,
+            target,
+            inputs.len(),
+            seed,
+            strip_current_dir_prefix(&dir).display()
+        );
+        Ok(())
+    }
+
+    /// Fuzz with a saved session's corpus, settings, and seed.
+    pub fn exec_session_resume(&self, resume: &options::SessionResume) -> Result<()> {
+        let dir = self.session_dir(&resume.name)?;
+        if !dir.is_dir() {
+            bail!("no session named `{}`", resume.name);
+        }
+        let session = session::Session::load(&dir)?;
+        let target = &session.target;
+        if !self.targets.contains(target) {
+            bail!(
+                "session `{}` fuzzes `{}`, which isn't a fuzz target of this project",
+                resume.name,
+                target
+            );
+        }
+
+        // Options given on the command line take precedence over the
+        // session's, like they do over the config file's.
+        let mut build = resume.build.clone();
+        session.settings.apply(&mut build);
+        if build.triple == utils::default_target() {
+            build.triple = session.triple.clone();
+        }
+        let build = libfuzzer_only(&build, "session resume")?;
+        self.exec_build(&build, Some(target))?;
+        let binary_hash = session::binary_hash(&self.target_binary(&build, target))?;
+        if binary_hash != session.binary_hash {
+            if !resume.force {
+                bail!(
+                    "the fuzz target `{}` has changed since session `{}` was saved, so it \
+                     wouldn't fuzz the same inputs; pass `--force` to resume it anyway",
+                    target,
+                    resume.name
+                );
+            }
+            eprintln!(
+                "warning: the fuzz target `{}` has changed since session `{}` was saved",
+                target, resume.name
+            );
+        }
+
+        let corpus = dir.join("corpus");
+        let mut args = session.settings.args.clone().unwrap_or_default();
+        let dict = dir.join(format!("{}.dict", target));
+        if dict.is_file() && !args.iter().any(|arg| arg.starts_with("-dict=")) {
+            args.insert(0, format!("-dict={}", dict.display()));
+        }
+        args.push(format!("-seed={}", session.seed));
+        args.extend(resume.args.iter().cloned());
+        eprintln!(
+            "Resuming session `{}` of `{}` from {} corpus input(s) with seed {}",
+            resume.name,
+            target,
+            collect_files(&corpus)?.len(),
+            session.seed
+        );
+        let run = options::Run {
+            build,
+            target: Some(target.clone()),
+            corpus: vec![corpus.display().to_string()],
+            all: false,
+            max_total_time: None,
+            until_new_coverage_stalls: None,
+            // Fuzzing with several jobs isn't deterministic.
+            jobs: Some(1),
+            fork: None,
+            ignore_known_crashes: None,
+            max_crashes: None,
+            timeout: None,
+            rss_limit: None,
+            malloc_limit: None,
+            record_trace: None,
+            save_hangs: false,
+            auto_tmin: false,
+            auto_tmin_time: None,
+            focus_changed: None,
+            filter: options::InputFilter::default(),
+            features: options::EngineFeatures::from_config(&session.settings),
+            mode: None,
+            slow_threshold_ms: None,
+            monitor: false,
+            status_addr: None,
+            notify_webhook: None,
+            notify_plateau: None,
+            tui: false,
+            sarif: None,
+            asan_options: Vec::new(),
+            dicts: Vec::new(),
+            seed: None,
+            replay_manifest: None,
+            args,
+        };
+        self.exec_fuzz(&run)
+    }
+
+    /// Write the project files that onboard the fuzzed crate to OSS-Fuzz.
+    pub fn exec_oss_fuzz_init(&self, init: &options::OssFuzzInit) -> Result<()> {
+        if self.targets.is_empty() {
+            bail!("there are no fuzz targets to build on OSS-Fuzz; add one with `cargo fuzz add`");
+        }
+        let name = self.root_project_name()?.to_lowercase().replace('_', "-");
+        let filename = self.root_project.join("Cargo.toml");
+        let data = fs::read(&filename)
+            .with_context(|| format!("failed to read {}", filename.display()))?;
+        let manifest: toml::Value = toml::from_slice(&data).with_context(|| {
+            format!(
+                "could not decode the manifest file at {}",
+                filename.display()
+            )
+        })?;
+        let package = manifest.get("package");
+        let package_str = |key: &str| {
+            package
+                .and_then(|p| p.get(key))
+                .and_then(toml::Value::as_str)
+                .map(String::from)
+        };
+
+        let repo = init
+            .repo
+            .clone()
+            .or_else(|| package_str("repository"))
+            .or_else(|| git_output(&self.root_project, &["remote", "get-url", "origin"]));
+        let repo = match repo {
+            Some(repo) => repo,
+            None => bail!("could not find the crate's git repository; pass its URL with `--repo`"),
+        };
+        let contact = init.contact.clone().or_else(|| {
+            package
+                .and_then(|p| p.get("authors"))
+                .and_then(toml::Value::as_array)
+                .and_then(|authors| {
+                    authors
+                        .iter()
+                        .filter_map(toml::Value::as_str)
+                        .find_map(author_email)
+                })
+        });
+        let contact = match contact {
+            Some(contact) => contact,
+            None => bail!("could not find an email address for the crate's authors; pass one with `--contact`"),
+        };
+        let homepage = package_str("homepage").unwrap_or_else(|| repo.clone());
+
+        let mut sanitizers = BTreeSet::new();
+        sanitizers.insert(Sanitizer::Address.to_string());
+        for config in std::iter::once(&self.config.defaults).chain(self.config.targets.values()) {
+            if config.sanitizer == Some(Sanitizer::Memory) {
+                sanitizers.insert(Sanitizer::Memory.to_string());
+            }
+        }
+        let sanitizers: String = sanitizers
+            .iter()
+            .map(|sanitizer| format!("  - {}\n", sanitizer))
+            .collect();
+
+        // The crate may be in a subdirectory of its repository, and may build
+        // into the target directory of an enclosing workspace. A target
+        // directory outside of the repository, like one set through
+        // `CARGO_TARGET_DIR`, won't be there on OSS-Fuzz.
+        let crate_dir = fs::canonicalize(&self.root_project).with_context(|| {
+            format!(
+                "could not canonicalize path: {}",
+                self.root_project.display()
+            )
+        })?;
+        let repo_root = git_output(&crate_dir, &["rev-parse", "--show-toplevel"])
+            .map(PathBuf::from)
+            .unwrap_or_else(|| crate_dir.clone());
+        let checkout = |path: &Path| {
+            let mut checkout = PathBuf::from(&name);
+            checkout.extend(path.strip_prefix(&repo_root).unwrap_or(path));
+            checkout
+        };
+        let cargo_target_dir = self.cargo_target_dir();
+        let target_dir = fs::canonicalize(&cargo_target_dir)
+            .unwrap_or(cargo_target_dir)
+            .strip_prefix(&repo_root)
+            .map(|dir| repo_root.join(dir))
+            .unwrap_or_else(|_| crate_dir.join("fuzz").join("target"));
+
+        let flags: BTreeMap<&String, String> = self
+            .targets
+            .iter()
+            .map(|target| {
+                (
+                    target,
+                    oss_fuzz_build_flags(&self.config.target(Some(target))),
+                )
+            })
+            .collect();
+        let mut builds = String::new();
+        if flags.values().collect::<BTreeSet<_>>().len() == 1 {
+            builds.push_str(&format!("cargo fuzz build{}\n", flags[&self.targets[0]]));
+        } else {
+            for (target, flags) in &flags {
+                builds.push_str(&format!("cargo fuzz build{} {}\n", flags, target));
+            }
+        }
+
+        let mut installs = String::new();
+        for target in &self.targets {
+            installs.push_str(&format!("\ncp $FUZZ_TARGET_OUTPUT_DIR/{0} $OUT/\n", target));
+            if self.dict_path(target).is_file() {
+                installs.push_str(&format!("cp fuzz/dictionaries/{0}.dict $OUT/\n", target));
+            }
+            installs.push_str(&format!(
+                "if [ -d fuzz/corpus/{0} ]; then zip -jqr $OUT/{0}_seed_corpus.zip fuzz/corpus/{0}; fi\n",
+                target
+            ));
+            let config = self.config.target(Some(target));
+            let mut options: Vec<String> = config
+                .args
+                .iter()
+                .flatten()
+                .filter_map(|arg| match arg.trim_start_matches('-').split_once('=') {
+                    Some((key, value)) => Some(format!("{} = {}", key, value)),
+                    None => {
+                        eprintln!(
+                            "warning: `{}` of `{}` isn't an option OSS-Fuzz can set, skipping it",
+                            arg, target
+                        );
+                        None
+                    }
+                })
+                .collect();
+            if let Some(max_len) = config.max_len {
+                options.insert(0, format!("max_len = {}", max_len));
+            }
+            if !options.is_empty() {
+                installs.push_str(&format!(
+                    "cat > $OUT/{}.options <<'EOF'\n[libfuzzer]\n{}\nEOF\n",
+                    target,
+                    options.join("\n")
+                ));
+            }
+        }
+
+        let output = match &init.output {
+            Some(output) => output.clone(),
+            None => self.path().join("oss-fuzz").join(&name),
+        };
+        let files = [
+            (
+                "project.yaml",
+                oss_fuzz_project_yaml_template!(homepage, contact, repo, sanitizers).to_string(),
+            ),
+            (
+                "Dockerfile",
+                oss_fuzz_dockerfile_template!(name, repo).to_string(),
+            ),
+            (
+                "build.sh",
+                oss_fuzz_build_template!(
+                    checkout(&crate_dir).display(),
+                    builds,
+                    checkout(&target_dir).display(),
+                    installs
+                )
+                .to_string(),
+            ),
+        ];
+        if !init.force {
+            for (file, _) in &files {
+                let path = output.join(file);
+                if path.exists() {
+                    bail!(
+                        "{} already exists; pass `--force` to overwrite it",
+                        path.display()
+                    );
+                }
+            }
+        }
+        fs::create_dir_all(&output)
+            .with_context(|| format!("could not make a directory at {}", output.display()))?;
+        for (file, contents) in &files {
+            let path = output.join(file);
+            fs::write(&path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+
+        eprintln!(
+            "Wrote the OSS-Fuzz project files for `{}` to:\n\n\t{}\n\n\
+             Copy them to `projects/{}` in a checkout of https://github.com/google/oss-fuzz\n\
+             to test the build and submit the project.",
+            name,
+            strip_current_dir_prefix(&output).display(),
+            name
+        );
+        Ok(())
+    }
+
+    fn path(&self) -> PathBuf {
+        self.root_project.join("fuzz")
+    }
+
+    fn manifest_path(&self) -> PathBuf {
         self.path().join("Cargo.toml")
     }
 
-    fn corpus_for(&self, target: &str) -> Result<PathBuf> {
-        let mut p = self.path();
-        p.push("corpus");
-        p.push(target);
-        fs::create_dir_all(&p)
-            .with_context(|| format!("could not make a corpus directory at {:?}", p))?;
-        Ok(p)
+    fn config_path(&self) -> PathBuf {
+        self.path().join("fuzz.toml")
+    }
+
+    /// Where `cargo fuzz run` records the statistics of a target's runs.
+    fn stats_path(&self, target: &str) -> PathBuf {
+        self.path()
+            .join("stats")
+            .join(target)
+            .with_extension("jsonl")
+    }
+
+    /// Whether `dir` is the corpus of `target` that `track_corpus` tracks.
+    fn is_corpus_of(&self, dir: &Path, target: &str) -> Result<bool> {
+        let corpus = self.corpus_for(target)?;
+        Ok(dir.canonicalize().ok() == corpus.canonicalize().ok())
+    }
+
+    /// Where `track_corpus` records the provenance of a target's corpus.
+    fn provenance_path(&self, target: &str) -> PathBuf {
+        self.path()
+            .join("provenance")
+            .join(target)
+            .with_extension("json")
+    }
+
+    /// The directory of a session saved with `cargo fuzz session save`.
+    fn session_dir(&self, name: &str) -> Result<PathBuf> {
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            bail!("invalid session name: {}", name);
+        }
+        Ok(self.path().join("sessions").join(name))
+    }
+
+    /// The regression tests that `cargo fuzz gen-test` writes for a target.
+    fn regression_tests_path(&self, target: &str) -> PathBuf {
+        self.path().join("tests").join(target).with_extension("rs")
+    }
+
+    /// Where `cargo fuzz run --record-trace` records the inputs it runs.
+    fn trace_dir(&self, target: &str) -> PathBuf {
+        self.path().join("traces").join(target)
+    }
+
+    /// Where `cargo fuzz run --focus-changed` and the input filters put the
+    /// inputs they seed the fuzzer with.
+    fn focus_dir(&self, target: &str) -> PathBuf {
+        self.path().join("focus").join(target)
+    }
+
+    /// Where `cargo fuzz run --mode leaks` saves the inputs that leak memory.
+    fn hangs_dir(&self, target: &str) -> Result<PathBuf> {
+        Ok(self.artifacts_for(target)?.join("hangs"))
+    }
+
+    fn leaks_dir(&self, target: &str) -> Result<PathBuf> {
+        let dir = self.artifacts_for(target)?.join("leaks");
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+        Ok(dir)
+    }
+
+    /// The LeakSanitizer suppressions that `cargo fuzz run --mode leaks` uses,
+    /// if the file exists.
+    fn lsan_suppressions_path(&self) -> PathBuf {
+        self.path().join("lsan.supp")
+    }
+
+    /// The AddressSanitizer suppressions that every fuzz target runs with, if
+    /// the file exists.
+    fn asan_suppressions_path(&self) -> PathBuf {
+        self.path().join("asan.supp")
+    }
+
+    /// The AddressSanitizer options that every fuzz target runs with, if the
+    /// file exists.
+    fn asan_options_path(&self) -> PathBuf {
+        self.path().join("asan_options")
+    }
+
+    /// The options in `fuzz/asan_options` and the suppressions in
+    /// `fuzz/asan.supp`, for the fuzz targets that use AddressSanitizer.
+    fn asan_file_options(&self) -> Result<Vec<String>> {
+        let mut opts = Vec::new();
+        let path = self.asan_options_path();
+        if path.is_file() {
+            let data = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            // Options are one per line or colon-separated, like in ASAN_OPTIONS.
+            let lines = data.lines().map(str::trim).filter(|l| !l.starts_with('#'));
+            for opt in lines.flat_map(|l| l.split(':')).map(str::trim) {
+                if opt.is_empty() {
+                    continue;
+                }
+                if !opt.contains('=') {
+                    bail!(
+                        "invalid option in {}: {} (expected KEY=VALUE)",
+                        path.display(),
+                        opt
+                    );
+                }
+                opts.push(opt.to_string());
+            }
+        }
+        let path = self.asan_suppressions_path();
+        if path.is_file() {
+            opts.push(format!("suppressions={}", path.display()));
+        }
+        Ok(opts)
+    }
+
+    /// The environment variable that holds a sanitizer's options, and its
+    /// options, if there are any: our defaults, then for AddressSanitizer the
+    /// options from `fuzz/asan_options` and `fuzz/asan.supp`, then those from
+    /// the environment, then `extra`. Options are colon-separated, and later
+    /// ones take precedence, so users can still override our defaults to e.g.
+    /// disable the leak sanitizer.
+    fn sanitizer_options(
+        &self,
+        sanitizer: Sanitizer,
+        extra: &[&str],
+    ) -> Result<Option<(&'static str, String)>> {
+        let (var, defaults): (_, &[&str]) = match sanitizer {
+            Sanitizer::Address => ("ASAN_OPTIONS", &["detect_odr_violation=0"]),
+            Sanitizer::Thread => ("TSAN_OPTIONS", &["report_signal_unsafe=0"]),
+            Sanitizer::Memory => ("MSAN_OPTIONS", &[]),
+            Sanitizer::Leak => ("LSAN_OPTIONS", &[]),
+            _ => return Ok(None),
+        };
+        let mut opts: Vec<String> = defaults.iter().map(|o| o.to_string()).collect();
+        if sanitizer == Sanitizer::Address {
+            opts.extend(self.asan_file_options()?);
+        }
+        opts.extend(env::var(var).ok().filter(|o| !o.is_empty()));
+        opts.extend(extra.iter().map(|o| o.to_string()));
+        Ok(Some((var, opts.join(":"))).filter(|(_, opts)| !opts.is_empty()))
+    }
+
+    /// Set a sanitizer's options on a command that runs a fuzz target.
+    ///
+    /// On MSVC targets, the address sanitizer's runtime DLL is also put on
+    /// `PATH`.
+    fn set_sanitizer_options(
+        &self,
+        cmd: &mut Command,
+        sanitizer: Sanitizer,
+        triple: &str,
+    ) -> Result<()> {
+        if let Some((var, opts)) = self.sanitizer_options(sanitizer, &[])? {
+            cmd.env(var, opts);
+        }
+        let runtime =
+            utils::msvc_asan_runtime_dir(triple).filter(|_| sanitizer == Sanitizer::Address);
+        if let Some(runtime) = runtime {
+            let path = env::var_os("PATH").unwrap_or_default();
+            let paths = iter::once(runtime).chain(env::split_paths(&path));
+            if let Ok(path) = env::join_paths(paths) {
+                cmd.env("PATH", path);
+            }
+        }
+        Ok(())
+    }
+
+    /// The sanitizer options that `cargo fuzz run` runs a fuzz target with,
+    /// beyond those every command does, for each variable that holds some.
+    fn run_sanitizer_options(
+        &self,
+        run: &options::Run,
+        target: &str,
+    ) -> Result<Vec<(&'static str, String)>> {
+        let sanitizer = self
+            .build_options_for(&run.build, Some(target))
+            .sanitizer
+            .unwrap_or(Sanitizer::Address);
+        let mut options = Vec::new();
+        let mut extra = Vec::new();
+        if run.mode == Some(RunMode::Leaks) {
+            let path = self.lsan_suppressions_path();
+            let suppressions = format!("suppressions={}", path.display());
+            let leak: &[&str] = if path.is_file() {
+                &[&suppressions]
+            } else {
+                &[]
+            };
+            if sanitizer == Sanitizer::Address {
+                // ASan's leak checking reads LeakSanitizer's options too.
+                options.extend(self.sanitizer_options(Sanitizer::Leak, leak)?);
+                extra.push("detect_leaks=1".to_string());
+            } else {
+                extra.extend(leak.iter().map(|o| o.to_string()));
+            }
+        }
+        if sanitizer == Sanitizer::Address {
+            extra.extend(run.asan_options.iter().cloned());
+        }
+        let extra: Vec<&str> = extra.iter().map(String::as_str).collect();
+        options.extend(self.sanitizer_options(sanitizer, &extra)?);
+        Ok(options)
+    }
+
+    /// Print the sanitizer options that `cargo fuzz run` runs a fuzz target
+    /// with, after `prefix`.
+    fn print_run_sanitizer_options(
+        &self,
+        run: &options::Run,
+        target: &str,
+        prefix: &str,
+    ) -> Result<()> {
+        let sanitizer = self.build_options_for(&run.build, Some(target)).sanitizer;
+        if !run.asan_options.is_empty()
+            && !matches!(
+                sanitizer.unwrap_or(Sanitizer::Address),
+                Sanitizer::Address | Sanitizer::All
+            )
+        {
+            bail!("`--asan-option` needs the address sanitizer");
+        }
+        for (var, opts) in self.run_sanitizer_options(run, target)? {
+            eprintln!("{}Sanitizer options: {}={}", prefix, var, opts);
+        }
+        Ok(())
+    }
+
+    /// Where `cargo fuzz run --slow-threshold-ms` saves a target's slow
+    /// inputs.
+    fn slow_dir(&self, target: &str) -> Result<PathBuf> {
+        let p = self.path().join("slow").join(target);
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a directory at {}", p.display()))?;
+        Ok(p)
+    }
+
+    /// Where `cargo fuzz run --tui` writes the output of each of a target's
+    /// workers.
+    fn logs_dir(&self, target: &str) -> Result<PathBuf> {
+        let p = self.path().join("logs").join(target);
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a directory at {}", p.display()))?;
+        Ok(p)
+    }
+
+    /// How long each of a target's slow inputs took to run.
+    fn slow_timings_path(&self, target: &str) -> PathBuf {
+        self.path().join("slow").join(target).with_extension("json")
+    }
+
+    /// The throughput of a target's corpus replay last recorded by `cargo fuzz
+    /// bench --corpus --update`.
+    fn bench_path(&self, target: &str) -> PathBuf {
+        self.path()
+            .join("bench")
+            .join(target)
+            .with_extension("json")
+    }
+
+    /// The dictionary that `cargo fuzz dict` generates for a target.
+    fn dict_path(&self, target: &str) -> PathBuf {
+        self.path()
+            .join("dictionaries")
+            .join(target)
+            .with_extension("dict")
+    }
+
+    /// The dictionary to fuzz a target with: the given dictionaries, or else
+    /// `fuzz/dictionaries/<target>.dict` and any dictionaries in
+    /// `fuzz/dictionaries/<target>/`, merged into one if there are several.
+    fn dict_for(&self, target: &str, dicts: &[PathBuf]) -> Result<Option<PathBuf>> {
+        let mut dicts = dicts.to_vec();
+        if let Some(missing) = dicts.iter().find(|dict| !dict.is_file()) {
+            bail!("no dictionary at {}", missing.display());
+        }
+        if dicts.is_empty() {
+            let dict = self.dict_path(target);
+            if dict.is_file() {
+                dicts.push(dict);
+            }
+            let dir = self.path().join("dictionaries").join(target);
+            if dir.is_dir() {
+                dicts.extend(
+                    collect_files(&dir)?
+                        .into_iter()
+                        .filter(|file| file.extension().is_some_and(|ext| ext == "dict")),
+                );
+            }
+        }
+        if dicts.len() < 2 {
+            return Ok(dicts.pop());
+        }
+
+        let mut contents = Vec::new();
+        for dict in &dicts {
+            let data = fs::read_to_string(dict)
+                .with_context(|| format!("failed to read {}", dict.display()))?;
+            contents.push((strip_current_dir_prefix(dict).display().to_string(), data));
+        }
+        let merged = self
+            .cargo_target_dir()
+            .join("dictionaries")
+            .join(format!("{}.dict", target));
+        fs::create_dir_all(merged.parent().unwrap()).with_context(|| {
+            format!(
+                "could not make a directory at {}",
+                merged.parent().unwrap().display()
+            )
+        })?;
+        fs::write(&merged, dict::merge(&contents))
+            .with_context(|| format!("failed to write {}", merged.display()))?;
+        Ok(Some(merged))
+    }
+
+    /// The working corpus of a target, which new inputs are added to.
+    fn corpus_path(&self, target: &str) -> PathBuf {
+        match self.config.target(Some(target)).corpus {
+            Some(corpus) => self.path().join(corpus),
+            None => self.path().join("corpus").join(target),
+        }
+    }
+
+    fn corpus_for(&self, target: &str) -> Result<PathBuf> {
+        let p = self.corpus_path(target);
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a corpus directory at {:?}", p))?;
+        Ok(p)
+    }
+
+    /// The read-only seed corpora configured for a target, which fuzzing
+    /// reads alongside its corpus but never adds inputs to.
+    fn seeds_for(&self, target: &str) -> Result<Vec<PathBuf>> {
+        let seeds = self.config.target(Some(target)).seeds.unwrap_or_default();
+        seeds
+            .iter()
+            .map(|seeds| {
+                let dir = self.path().join(seeds);
+                if !dir.is_dir() {
+                    bail!(
+                        "the seed corpus `{}` of `{}` in fuzz.toml isn't a directory",
+                        seeds,
+                        target
+                    );
+                }
+                Ok(dir)
+            })
+            .collect()
+    }
+
+    fn artifacts_for(&self, target: &str) -> Result<PathBuf> {
+        let mut p = self.path();
+        p.push("artifacts");
+        p.push(target);
+
+        // This adds a trailing slash, which is necessary for libFuzzer, because
+        // it does simple string concatenation when joining paths.
+        p.push("");
+
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a artifact directory at {:?}", p))?;
+
+        Ok(p)
+    }
+
+    fn coverage_for(&self, target: &str) -> Result<PathBuf> {
+        let mut p = self.path();
+        p.push("coverage");
+        p.push(target);
+        fs::create_dir_all(&p)
+            .with_context(|| format!("could not make a coverage directory at {:?}", p))?;
+        Ok(p)
+    }
+
+    /// The fuzz crate's Cargo target directory, resolved the way Cargo itself
+    /// does: from `CARGO_TARGET_DIR`, `build.target-dir` in a Cargo config
+    /// file, or the root of the workspace that the fuzz crate is a member of.
+    ///
+    /// Cargo is only asked once, since this is called for every target.
+    fn cargo_target_dir(&self) -> PathBuf {
+        self.cargo_target_dir
+            .get_or_init(|| self.resolve_cargo_target_dir())
+            .clone()
+    }
+
+    fn resolve_cargo_target_dir(&self) -> PathBuf {
+        let metadata = utils::toolchain_command("cargo")
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--no-deps")
+            .arg("--manifest-path")
+            .arg(self.manifest_path())
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| serde_json::from_slice::<serde_json::Value>(&output.stdout).ok());
+        match metadata
+            .as_ref()
+            .and_then(|m| m["target_directory"].as_str())
+        {
+            Some(target_dir) => PathBuf::from(target_dir),
+            None => env::var_os("CARGO_TARGET_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.path().join("target")),
+        }
+    }
+
+    /// The Cargo target directory that builds with these options end up in,
+    /// `<target dir>/<triple>/<configuration>`.
+    ///
+    /// Each build configuration gets a directory of its own, so that switching
+    /// between sanitizers or engines doesn't rebuild everything every time.
+    fn target_dir(&self, build: &BuildOptions) -> PathBuf {
+        self.cargo_target_dir()
+            .join(&build.triple)
+            .join(build_config(build))
+    }
+
+    /// Remove the builds of every configuration, or of those matching the
+    /// given globs, for every target triple.
+    pub fn exec_clean(&self, clean: &options::Clean) -> Result<()> {
+        let target_dir = self.cargo_target_dir();
+        let mut removed = 0;
+        let mut freed = 0;
+        let triples = match fs::read_dir(&target_dir) {
+            Ok(entries) => entries,
+            Err(_) => {
+                eprintln!("Nothing to clean");
+                return Ok(());
+            }
+        };
+        for triple in triples {
+            let triple = triple.with_context(|| {
+                format!(
+                    "failed to read directory entry inside {}",
+                    target_dir.display()
+                )
+            })?;
+            if !triple.path().is_dir() {
+                continue;
+            }
+            for config in fs::read_dir(triple.path())
+                .with_context(|| format!("failed to read {}", triple.path().display()))?
+            {
+                let path = config
+                    .with_context(|| {
+                        format!(
+                            "failed to read directory entry inside {}",
+                            triple.path().display()
+                        )
+                    })?
+                    .path();
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                let selected = clean.config.is_empty()
+                    || clean.config.iter().any(|g| utils::glob_match(g, &name));
+                if !path.is_dir() || !is_build_config(&name) || !selected {
+                    continue;
+                }
+                let size = dir_size(&path);
+                fs::remove_dir_all(&path)
+                    .with_context(|| format!("failed to remove {}", path.display()))?;
+                eprintln!(
+                    "Removed {} ({})",
+                    strip_current_dir_prefix(&path).display(),
+                    bench::format_bytes(size as f64)
+                );
+                removed += 1;
+                freed += size;
+            }
+        }
+        if removed == 0 {
+            eprintln!("Nothing to clean");
+        } else {
+            eprintln!(
+                "Removed {} build(s), freeing {}",
+                removed,
+                bench::format_bytes(freed as f64)
+            );
+        }
+        Ok(())
+    }
+
+    /// The path of a fuzz target's binary after building it.
+    fn target_binary(&self, build: &BuildOptions, target: &str) -> PathBuf {
+        let build = &self.build_options_for(build, Some(target));
+        let binary = if build.triple.contains("-windows") {
+            format!("{}.exe", target)
+        } else {
+            target.to_string()
+        };
+        self.target_dir(build)
+            .join(&build.triple)
+            .join(profile_dir(build))
+            .join(binary)
+    }
+
+    fn fuzz_targets_dir(&self) -> PathBuf {
+        let mut root = self.path();
+        if root.join(crate::FUZZ_TARGETS_DIR_OLD).exists() {
+            println!(
+                "warning: The `fuzz/fuzzers/` directory has renamed to `fuzz/fuzz_targets/`. \
+                 Please rename the directory as such. This will become a hard error in the \
+                 future."
+            );
+            root.push(crate::FUZZ_TARGETS_DIR_OLD);
+        } else {
+            root.push(crate::FUZZ_TARGETS_DIR);
+        }
+        root
+    }
+
+    fn target_path(&self, target: &str) -> PathBuf {
+        if let Some(path) = self.target_paths.get(target) {
+            return self.path().join(path);
+        }
+        let mut root = self.fuzz_targets_dir();
+        root.push(target);
+        root.set_extension("rs");
+        root
+    }
+
+    /// Where a fuzz target is in `fuzz_targets`, like `parser/headers` for
+    /// `fuzz_targets/parser/headers.rs`, or just its name if it's elsewhere.
+    fn target_subpath(&self, target: &str) -> String {
+        let path = self.target_path(target);
+        match path
+            .with_extension("")
+            .strip_prefix(self.fuzz_targets_dir())
+        {
+            Ok(subpath) => subpath
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            Err(_) => target.to_string(),
+        }
+    }
+
+    /// The name of a fuzz target given either by name or by where it is in
+    /// `fuzz_targets`, like `parser/headers` for `parser_headers`.
+    pub fn resolve_target(&self, target: &str) -> String {
+        if self.targets.iter().any(|t| t == target) {
+            return target.to_string();
+        }
+        let subpath = target.trim_end_matches(".rs");
+        self.targets
+            .iter()
+            .find(|t| self.target_subpath(t) == subpath)
+            .cloned()
+            .unwrap_or_else(|| target.to_string())
+    }
+
+    /// A setting of a Cargo profile, like `opt-level`, from the manifest that
+    /// the fuzz crate's profiles come from: its workspace root's, or its own.
+    fn profile_setting(&self, profile: &str, key: &str) -> Result<Option<toml::Value>> {
+        let manifest = match find_workspace_root(&self.path())? {
+            Some(root) if root != self.path() => {
+                let path = root.join("Cargo.toml");
+                let data = fs::read(&path)
+                    .with_context(|| format!("failed to read {}", path.display()))?;
+                toml::from_slice(&data).with_context(|| {
+                    format!("could not decode the manifest file at {}", path.display())
+                })?
+            }
+            _ => self.manifest()?,
+        };
+        Ok(manifest
+            .get("profile")
+            .and_then(|profiles| profiles.get(profile))
+            .and_then(|profile| profile.get(key))
+            .cloned())
+    }
+
+    fn manifest(&self) -> Result<toml::Value> {
+        let filename = self.manifest_path();
+        let mut file = fs::File::open(&filename)
+            .with_context(|| format!("could not read the manifest file: {}", filename.display()))?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        toml::from_slice(&data).with_context(|| {
+            format!(
+                "could not decode the manifest file at {}",
+                filename.display()
+            )
+        })
+    }
+
+    /// The key of the fuzz crate's dependency on the fuzzed crate, i.e. the
+    /// one with `path = ".."`.
+    fn root_dependency_name(&self) -> Result<Option<String>> {
+        let manifest = self.manifest()?;
+        let dependencies = manifest.get("dependencies").and_then(toml::Value::as_table);
+        Ok(dependencies.and_then(|dependencies| {
+            dependencies
+                .iter()
+                .find(|(_, dependency)| {
+                    dependency.get("path").and_then(toml::Value::as_str) == Some("..")
+                })
+                .map(|(name, _)| name.clone())
+        }))
+    }
+
+    /// Whether the fuzzed crate has an `arbitrary` feature, either declared in
+    /// `[features]` or implied by an optional `arbitrary` dependency.
+    fn root_has_arbitrary_feature(&self) -> Result<bool> {
+        let filename = self.root_project.join("Cargo.toml");
+        let data = fs::read(&filename)
+            .with_context(|| format!("failed to read {}", filename.display()))?;
+        let value: toml::Value = toml::from_slice(&data).with_context(|| {
+            format!(
+                "could not decode the manifest file at {}",
+                filename.display()
+            )
+        })?;
+        let feature = value.get("features").and_then(|f| f.get("arbitrary"));
+        let optional_dependency = value
+            .get("dependencies")
+            .and_then(|d| d.get("arbitrary"))
+            .and_then(|d| d.get("optional"))
+            .and_then(toml::Value::as_bool);
+        Ok(feature.is_some() || optional_dependency == Some(true))
+    }
+
+    /// The source of the fuzzed crate's library, or `None` if it has none.
+    fn root_lib_source(&self) -> Result<Option<String>> {
+        let path = self.root_lib_path()?;
+        if !path.is_file() {
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .with_context(|| format!("failed to read {}", path.display()))
+    }
+
+    /// The root source file of the fuzzed crate's library, which may not
+    /// exist.
+    fn root_lib_path(&self) -> Result<PathBuf> {
+        let filename = self.root_project.join("Cargo.toml");
+        let data = fs::read(&filename)
+            .with_context(|| format!("failed to read {}", filename.display()))?;
+        let value: toml::Value = toml::from_slice(&data).with_context(|| {
+            format!(
+                "could not decode the manifest file at {}",
+                filename.display()
+            )
+        })?;
+        let path = value
+            .get("lib")
+            .and_then(|lib| lib.get("path"))
+            .and_then(toml::Value::as_str)
+            .unwrap_or("src/lib.rs");
+        Ok(self.root_project.join(path))
+    }
+
+    /// Whether the fuzzed crate's library is always `#![no_std]`, rather than
+    /// only without some feature.
+    fn root_is_no_std(&self) -> Result<bool> {
+        Ok(self.root_lib_source()?.is_some_and(|source| {
+            source
+                .lines()
+                .any(|line| line.split_whitespace().collect::<String>() == "#![no_std]")
+        }))
+    }
+
+    /// Whether the fuzzed crate's library root defines a `#[panic_handler]`
+    /// without leaving it out of fuzzing builds.
+    fn root_has_panic_handler(&self) -> Result<bool> {
+        Ok(self.root_lib_source()?.is_some_and(|source| {
+            source.contains("#[panic_handler]") && !source.contains("fuzzing")
+        }))
+    }
+
+    fn root_project_name(&self) -> Result<String> {
+        let filename = self.root_project.join("Cargo.toml");
+        let mut file = fs::File::open(&filename)?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        let value: toml::Value = toml::from_slice(&data)?;
+        let name = value
+            .as_table()
+            .and_then(|v| v.get("package"))
+            .and_then(toml::Value::as_table)
+            .and_then(|v| v.get("name"))
+            .and_then(toml::Value::as_str);
+        if let Some(name) = name {
+            Ok(String::from(name))
+        } else if value.get("workspace").is_some() {
+            bail!(
+                "{} is a virtual workspace manifest; run `cargo fuzz init` \
+                 inside the workspace member that you want to fuzz",
+                filename.display()
+            );
+        } else {
+            bail!("{} (package.name) is malformed", filename.display());
+        }
+    }
+}
+
+fn collect_targets(value: &toml::Value) -> Vec<String> {
+    let bins = value
+        .as_table()
+        .and_then(|v| v.get("bin"))
+        .and_then(toml::Value::as_array);
+    let mut bins = if let Some(bins) = bins {
+        bins.iter()
+            .map(|bin| {
+                bin.as_table()
+                    .and_then(|v| v.get("name"))
+                    .and_then(toml::Value::as_str)
+            })
+            .filter_map(|name| name.map(String::from))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    // Always sort them, so that we have deterministic output.
+    bins.sort();
+    bins
+}
+
+/// The `path` of each `[[bin]]` in a fuzz crate's manifest that has one.
+fn collect_target_paths(value: &toml::Value) -> BTreeMap<String, PathBuf> {
+    let bins = value
+        .get("bin")
+        .and_then(toml::Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    bins.iter()
+        .filter_map(|bin| {
+            let name = bin.get("name")?.as_str()?;
+            let path = bin.get("path")?.as_str()?;
+            Some((name.to_string(), PathBuf::from(path)))
+        })
+        .collect()
+}
+
+/// The `required-features` of each `[[bin]]` in a fuzz crate's manifest that
+/// has any.
+fn collect_required_features(value: &toml::Value) -> BTreeMap<String, Vec<String>> {
+    let bins = value
+        .get("bin")
+        .and_then(toml::Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    bins.iter()
+        .filter_map(|bin| {
+            let name = bin.get("name")?.as_str()?;
+            let features: Vec<String> = bin
+                .get("required-features")?
+                .as_array()?
+                .iter()
+                .filter_map(|f| f.as_str().map(String::from))
+                .collect();
+            Some((name.to_string(), features)).filter(|(_, features)| !features.is_empty())
+        })
+        .collect()
+}
+
+fn is_fuzz_manifest(value: &toml::Value) -> bool {
+    let is_fuzz = value
+        .as_table()
+        .and_then(|v| v.get("package"))
+        .and_then(toml::Value::as_table)
+        .and_then(|v| v.get("metadata"))
+        .and_then(toml::Value::as_table)
+        .and_then(|v| v.get("cargo-fuzz"))
+        .and_then(toml::Value::as_bool);
+    is_fuzz == Some(true)
+}
+
+/// Returns the path for the first found non-fuzz Cargo package
+fn find_package() -> Result<PathBuf> {
+    let mut dir = env::current_dir()?;
+    let mut data = Vec::new();
+    loop {
+        let manifest_path = dir.join("Cargo.toml");
+        match fs::File::open(&manifest_path) {
+            Err(_) => {}
+            Ok(mut f) => {
+                data.clear();
+                f.read_to_end(&mut data)
+                    .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+                let value: toml::Value = toml::from_slice(&data).with_context(|| {
+                    format!(
+                        "could not decode the manifest file at {}",
+                        manifest_path.display()
+                    )
+                })?;
+                if !is_fuzz_manifest(&value) {
+                    // Not a cargo-fuzz project => must be a proper cargo project :)
+                    return Ok(dir);
+                }
+            }
+        }
+        if !dir.pop() {
+            break;
+        }
+    }
+    bail!("could not find a cargo project")
+}
+
+/// Find the root of the Cargo workspace that `package` is a member of, if any.
+///
+/// This asks Cargo rather than looking for the nearest manifest with a
+/// `[workspace]` table, because a package that merely sits inside a
+/// workspace's directory, without being one of its members, isn't part of it.
+fn find_workspace_root(package: &Path) -> Result<Option<PathBuf>> {
+    let output = utils::toolchain_command("cargo")
+        .arg("locate-project")
+        .arg("--workspace")
+        .arg("--message-format")
+        .arg("plain")
+        .arg("--manifest-path")
+        .arg(package.join("Cargo.toml"))
+        .stderr(Stdio::null())
+        .output()
+        .context("failed to run `cargo locate-project`")?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let manifest_path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let data = fs::read(&manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let value: toml::Value = toml::from_slice(&data).with_context(|| {
+        format!(
+            "could not decode the manifest file at {}",
+            manifest_path.display()
+        )
+    })?;
+    Ok(value
+        .get("workspace")
+        .and(manifest_path.parent())
+        .map(Path::to_path_buf))
+}
+
+/// Add `entry` to the `<table>.<key>` array of a manifest, keeping the rest
+/// of the file, comments and all, as it is. The table must be written out with
+/// its own `[<table>]` header.
+fn add_to_manifest_list(manifest_path: &Path, table: &str, key: &str, entry: &str) -> Result<()> {
+    let contents = fs::read_to_string(manifest_path)
+        .with_context(|| format!("failed to read {}", manifest_path.display()))?;
+    let header = format!("[{}]", table);
+    let has_entry = |contents: &str| -> Option<bool> {
+        let value: toml::Value = toml::from_str(contents).ok()?;
+        let list = table
+            .split('.')
+            .try_fold(&value, |value, name| value.get(name))?
+            .get(key);
+        Some(list.and_then(toml::Value::as_array).is_some_and(|list| {
+            list.iter()
+                .any(|e| e.as_str().map(|e| e.trim_end_matches('/')) == Some(entry))
+        }))
+    };
+    if has_entry(&contents) == Some(true) {
+        return Ok(());
+    }
+
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        lines.push((offset, line));
+        offset += line.len();
+    }
+    let updated = lines
+        .iter()
+        .position(|(_, line)| line.trim() == header)
+        .and_then(|header| {
+            let existing = lines[header + 1..]
+                .iter()
+                .take_while(|(_, line)| !line.trim_start().starts_with('['))
+                .find(|(_, line)| {
+                    line.trim_start()
+                        .strip_prefix(key)
+                        .is_some_and(|rest| rest.trim_start().starts_with('='))
+                });
+            match existing {
+                Some(&(start, _)) => {
+                    let open = start + contents[start..].find('[')?;
+                    let close = open + contents[open..].find(']')?;
+                    let items = &contents[open + 1..close];
+                    let quoted = format!("{:?}", entry);
+                    if items.contains('\n') {
+                        // One entry per line: add ours on a line of its own.
+                        let indent: String = items
+                            .lines()
+                            .rev()
+                            .find(|line| !line.trim().is_empty())
+                            .unwrap_or("    ")
+                            .chars()
+                            .take_while(|c| c.is_whitespace())
+                            .collect();
+                        let last = open + 1 + items.trim_end().len();
+                        let comma = if items.trim().is_empty() || items.trim_end().ends_with(',') {
+                            ""
+                        } else {
+                            ","
+                        };
+                        let line_start = open + 1 + items.rfind('\n')? + 1;
+                        Some(format!(
+                            "{}{}{}{}{},\n{}",
+                            &contents[..last],
+                            comma,
+                            &contents[last..line_start],
+                            if indent.is_empty() { "    " } else { &indent },
+                            quoted,
+                            &contents[line_start..]
+                        ))
+                    } else {
+                        let items = items.trim();
+                        let items = if items.is_empty() {
+                            quoted
+                        } else if items.ends_with(',') {
+                            format!("{} {}", items, quoted)
+                        } else {
+                            format!("{}, {}", items, quoted)
+                        };
+                        Some(format!(
+                            "{}{}{}",
+                            &contents[..open + 1],
+                            items,
+                            &contents[close..]
+                        ))
+                    }
+                }
+                None => {
+                    let (start, line) = lines[header];
+                    let end = start + line.len();
+                    let newline = if line.ends_with('\n') { "" } else { "\n" };
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    Some(format!(
+                        "{}{}{}{} = [{:?}]\n{}",
+                        &contents[..end],
+                        newline,
+                        indent,
+                        key,
+                        entry,
+                        &contents[end..]
+                    ))
+                }
+            }
+        })
+        .filter(|updated| has_entry(updated) == Some(true));
+    let updated = match updated {
+        Some(updated) => updated,
+        None => bail!(
+            "could not add `{}` to `{}.{}` in {}; please add it by hand",
+            entry,
+            table,
+            key,
+            manifest_path.display()
+        ),
+    };
+    fs::write(manifest_path, updated)
+        .with_context(|| format!("failed to write to {}", manifest_path.display()))
+}
+
+/// Spawn a command, forwarding its stderr line by line to ours while letting
+/// `on_line` inspect each line along the way.
+fn spawn_watched(
+    cmd: &mut Command,
+    mut stall: Option<libfuzzer::StallDetector>,
+    mut monitor: Option<&mut monitor::Monitor>,
+    mut hangs: Option<&mut hang::Watcher>,
+    mut on_line: impl FnMut(&str),
+) -> Result<(ExitStatus, bool)> {
+    cmd.stderr(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("failed to spawn command: {:?}", cmd))?;
+    let mut stderr = BufReader::new(child.stderr.take().expect("stderr is piped"));
+
+    // Read stderr on another thread, so that we notice a stall even while the
+    // fuzzer is quiet.
+    let (lines, received) = mpsc::channel();
+    let reader = thread::spawn(move || {
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            // Sanitizer reports can contain bytes of the input, so they aren't
+            // necessarily valid UTF-8.
+            match stderr.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(&['\r', '\n'][..]).to_string();
+            if lines.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut stalled = false;
+    loop {
+        match received.recv_timeout(STALL_POLL_INTERVAL) {
+            Ok(line) => {
+                eprintln!("{}", line);
+                on_line(&line);
+                if let Some(stall) = &mut stall {
+                    stall.observe(&line);
+                }
+                if let Some(monitor) = &mut monitor {
+                    monitor.observe(&line);
+                }
+                if let Some(hangs) = &mut hangs {
+                    hangs.observe(&line);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        if let Some(monitor) = &mut monitor {
+            monitor.tick(child.id())?;
+        }
+        if let Some(hangs) = &mut hangs {
+            hangs.tick(child.id());
+            // The fuzzer didn't notice the timeout, so it never will.
+            if hangs.is_stuck() {
+                let _ = child.kill();
+            }
+        }
+        if !stalled && stall.as_ref().is_some_and(|s| s.is_stalled()) {
+            stalled = true;
+            let _ = child.kill();
+        }
+    }
+    let _ = reader.join();
+
+    let status = child
+        .wait()
+        .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
+    Ok((status, stalled))
+}
+
+/// Compile a C source file in `dir` into an object file to link into fuzz
+/// targets, unless it's already compiled from the same source.
+fn compile_c_object(dir: &Path, name: &str, contents: &str, what: &str) -> Result<PathBuf> {
+    let source = dir.join(format!("{}.c", name));
+    let object = dir.join(format!("{}.o", name));
+    if fs::read_to_string(&source).ok().as_deref() == Some(contents) && object.is_file() {
+        return Ok(object);
+    }
+    fs::create_dir_all(dir)
+        .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+    fs::write(&source, contents)
+        .with_context(|| format!("failed to write {}", source.display()))?;
+    let mut cmd = Command::new(env::var_os("CC").unwrap_or_else(|| "cc".into()));
+    cmd.arg("-c")
+        .arg("-O2")
+        .arg("-fPIC")
+        .arg("-o")
+        .arg(&object)
+        .arg(&source);
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to execute: {:?}", cmd))?;
+    if !status.success() {
+        return Err(exit::fail(
+            ExitCode::BuildFailed,
+            format!("failed to build {}: {:?}", what, cmd),
+        ));
+    }
+    Ok(object)
+}
+
+/// Start serving the fuzzer's progress for `run --status-addr`.
+fn start_status_server(run: &options::Run, target: &str) -> Result<Option<status::Server>> {
+    let addr = match &run.status_addr {
+        Some(addr) => addr,
+        None => return Ok(None),
+    };
+    let server = status::Server::start(addr, target)?;
+    eprintln!(
+        "Serving the progress of `{}` on http://{}/status and http://{}/metrics",
+        target,
+        server.addr(),
+        server.addr()
+    );
+    Ok(Some(server))
+}
+
+fn print_monitor_summary(target: &str, monitor: Option<&monitor::Monitor>) {
+    if let Some(monitor) = monitor {
+        eprintln!(
+            "\n{}",
+            monitor::summary(
+                target,
+                monitor.samples(),
+                strip_current_dir_prefix(monitor.path())
+            )
+        );
+    }
+}
+
+/// Build options for subcommands that only work with libFuzzer. Targets that
+/// are configured to use another engine still work with these, since their
+/// sources are the same, but asking for another engine explicitly is an error.
+fn libfuzzer_only(build: &BuildOptions, subcommand: &str) -> Result<BuildOptions> {
+    if build.sanitizer == Some(Sanitizer::All) {
+        bail!(
+            "`cargo fuzz {}` doesn't support `--sanitizer all`",
+            subcommand
+        );
+    }
+    if let Some(engine) = build.engine.filter(|e| *e != Engine::Libfuzzer) {
+        bail!(
+            "`cargo fuzz {}` doesn't support the {} engine",
+            subcommand,
+            engine
+        );
+    }
+    let mut build = build.clone();
+    build.engine = Some(Engine::Libfuzzer);
+    Ok(build)
+}
+
+/// The one sanitizer to build or run with, or an error for `--sanitizer all`
+/// where only one build can be used.
+fn single_sanitizer(build: &BuildOptions) -> Result<Sanitizer> {
+    match build.sanitizer.unwrap_or(Sanitizer::Address) {
+        Sanitizer::All => bail!(
+            "`--sanitizer all` can only be used with `cargo fuzz build` and `cargo fuzz run <target>`"
+        ),
+        sanitizer => Ok(sanitizer),
+    }
+}
+
+/// A command as a line for a POSIX shell, with the environment variables set
+/// for it in front.
+fn shell_command(cmd: &Command) -> String {
+    let quote = |word: &ffi::OsStr| shell_quote(&word.to_string_lossy());
+    let mut words: Vec<String> = cmd
+        .get_envs()
+        .filter_map(|(var, value)| Some(format!("{}={}", var.to_string_lossy(), quote(value?))))
+        .collect();
+    words.push(quote(cmd.get_program()));
+    words.extend(cmd.get_args().map(quote));
+    words.join(" ")
+}
+
+/// A word quoted for a POSIX shell, unless it needs no quotes.
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+/// A word quoted for the shell that `shell` runs a script with: a POSIX
+/// shell, or `cmd` on Windows, whose quotes are double quotes that can't be
+/// escaped inside, which paths on Windows can't contain anyway.
+fn hook_quote(word: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", word.replace('"', ""))
+    } else {
+        shell_quote(word)
+    }
+}
+
+/// `template` with each of the `placeholders` replaced by its value, in a
+/// single pass, so that a value that contains another placeholder is left as
+/// it is.
+fn substitute(template: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    'scan: while !rest.is_empty() {
+        for (placeholder, value) in placeholders {
+            if let Some(after) = rest.strip_prefix(placeholder) {
+                out.push_str(value);
+                rest = after;
+                continue 'scan;
+            }
+        }
+        let c = rest.chars().next().unwrap();
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// A command that runs `script` with the platform's shell.
+fn shell(script: &str) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(script);
+    cmd
+}
+
+/// The flags for rustc that build code with `sanitizer`.
+fn sanitizer_rustflags(sanitizer: Sanitizer) -> String {
+    match sanitizer {
+        Sanitizer::None => String::new(),
+        Sanitizer::Memory => {
+            // Memory sanitizer requires more flags to function than others:
+            // https://doc.rust-lang.org/unstable-book/compiler-flags/sanitizer.html#memorysanitizer
+            " -Zsanitizer=memory -Zsanitizer-memory-track-origins".to_string()
+        }
+        _ => format!(" -Zsanitizer={sanitizer}", sanitizer = sanitizer),
+    }
+}
+
+/// The flags for rustc that a binary of the fuzzed crate that a fuzz target
+/// runs is built with: the sanitizer's, but not the fuzzing instrumentation,
+/// which nothing in the binary would collect, followed by those from the
+/// environment and `--rustflags-extra`.
+fn bin_rustflags(build: &BuildOptions, sanitizer: Sanitizer) -> Vec<String> {
+    let mut rustflags = "--cfg fuzzing".to_owned();
+    rustflags.push_str(&sanitizer_rustflags(sanitizer));
+    if !build.release || build.debug_assertions {
+        rustflags.push_str(" -Cdebug-assertions");
+    }
+    if build.quick {
+        rustflags.push_str(QUICK_RUSTFLAGS);
+    }
+    if build.profiling {
+        rustflags.push_str(" -Cforce-frame-pointers=yes");
+    }
+    rustflags.push_str(&debuginfo_rustflags(build));
+    if build.debuginfo {
+        rustflags.push_str(" -Cdebuginfo=2");
+    }
+    let mut flags: Vec<String> = rustflags.split_whitespace().map(String::from).collect();
+    flags.extend(env_rustflags().into_iter().flat_map(|(_, flags)| flags));
+    if let Some(extra) = &build.rustflags_extra {
+        flags.extend(extra.split_whitespace().map(String::from));
+    }
+    flags
+}
+
+/// The flags for rustc from `--debuginfo-level` and `--split-debuginfo`.
+fn debuginfo_rustflags(build: &BuildOptions) -> String {
+    let mut rustflags = String::new();
+    if let Some(level) = &build.debuginfo_level {
+        rustflags.push_str(&format!(" -Cdebuginfo={}", level));
+        // Cargo strips the debug info of profiles that don't ask for any.
+        if !["0", "none"].contains(&level.as_str()) {
+            rustflags.push_str(" -Cstrip=none");
+        }
+    }
+    if let Some(mode) = &build.split_debuginfo {
+        rustflags.push_str(&format!(" -Csplit-debuginfo={}", mode));
+    }
+    rustflags
+}
+
+/// The flags for rustc in the environment: `CARGO_ENCODED_RUSTFLAGS`, which
+/// Cargo prefers, or `RUSTFLAGS`, along with the variable they're from.
+fn env_rustflags() -> Option<(&'static str, Vec<String>)> {
+    if let Ok(flags) = env::var("CARGO_ENCODED_RUSTFLAGS") {
+        let flags = flags
+            .split('\x1f')
+            .filter(|flag| !flag.is_empty())
+            .map(String::from)
+            .collect();
+        return Some(("CARGO_ENCODED_RUSTFLAGS", flags));
+    }
+    let flags = env::var("RUSTFLAGS").ok()?;
+    Some((
+        "RUSTFLAGS",
+        flags.split_whitespace().map(String::from).collect(),
+    ))
+}
+
+/// The environment variable Cargo reads a target's runner from, e.g.
+/// `CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_RUNNER`.
+fn runner_var(triple: &str) -> String {
+    format!(
+        "CARGO_TARGET_{}_RUNNER",
+        triple.to_uppercase().replace(['-', '.'], "_")
+    )
+}
+
+/// A command that runs a fuzz target's binary, through the runner if there is
+/// one. Like Cargo, the runner is split into words at whitespace.
+fn binary_command(build: &BuildOptions, binary: &Path) -> Command {
+    let runner = build
+        .runner
+        .clone()
+        .or_else(|| env::var(runner_var(&build.triple)).ok());
+    let mut words = runner.iter().flat_map(|runner| runner.split_whitespace());
+    match words.next() {
+        Some(program) => {
+            let mut cmd = Command::new(program);
+            cmd.args(words).arg(binary);
+            cmd
+        }
+        None => {
+            static WARN: Once = Once::new();
+            let host = utils::host_triple().unwrap_or(&build.triple);
+            // Rosetta runs x86_64 binaries on Apple silicon.
+            let rosetta =
+                host.ends_with("-apple-darwin") && build.triple.ends_with("-apple-darwin");
+            if host != build.triple && !rosetta {
+                WARN.call_once(|| {
+                    eprintln!(
+                        "warning: running a fuzz target built for {} on a {} host without a \
+                         runner; set one with `--runner` if it fails to start",
+                        build.triple, host
+                    )
+                });
+            }
+            Command::new(binary)
+        }
+    }
+}
+
+/// A seed for libFuzzer's random number generator, which treats 0 as a
+/// request to pick one itself.
+fn random_seed() -> u32 {
+    let now = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .unwrap_or_default();
+    (now.as_secs() as u32 ^ now.subsec_nanos()).max(1)
+}
+
+/// Emit the JSON messages for a line of a fuzzer's output.
+fn emit_fuzzer_messages(format: MessageFormat, target: &str, line: &str) {
+    if !format.is_json() {
+        return;
+    }
+    if let Some(status) = libfuzzer::Status::parse(line) {
+        if status.is_new_coverage() {
+            format.emit(&Message::NewCoverage {
+                target,
+                execs: status.execs,
+                coverage: status.coverage,
+                features: status.features,
+                corpus_entries: status.corpus_entries,
+                corpus_bytes: status.corpus_bytes,
+                execs_per_sec: status.execs_per_sec,
+            });
+        }
+    } else if let Some(artifact) = libfuzzer::parse_written_artifact(line) {
+        format.emit(&Message::CrashFound {
+            target,
+            artifact: artifact.display().to_string(),
+        });
+    }
+}
+
+/// Run git in `dir`, returning its output, or failing with what it printed.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(dir).args(args);
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to execute: {:?}", cmd))?;
+    if !output.status.success() {
+        bail!(
+            "{:?} failed:\n\n{}{}",
+            cmd,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The files in `dir` that git tracks.
+fn tracked_files(dir: &Path) -> Result<HashSet<PathBuf>> {
+    Ok(git(dir, &["ls-files", "-z", "--", "."])?
+        .split('\0')
+        .filter(|name| !name.is_empty())
+        .map(|name| dir.join(name))
+        .collect())
+}
+
+/// Run git in `dir`, returning its trimmed output if it succeeded.
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let output = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(output).filter(|output| !output.is_empty())
+}
+
+/// The email address in a `Name <email>` entry of `package.authors`.
+fn author_email(author: &str) -> Option<String> {
+    let start = author.find('<')? + 1;
+    let end = start + author[start..].find('>')?;
+    Some(author[start..end].to_string()).filter(|email| email.contains('@'))
+}
+
+/// The `cargo fuzz build` flags that build a target with its configured
+/// features on OSS-Fuzz, which picks the sanitizer itself.
+fn oss_fuzz_build_flags(config: &TargetConfig) -> String {
+    let mut flags = String::from(" -O");
+    if config.debug_assertions == Some(true) {
+        flags.push_str(" --debug-assertions");
+    }
+    if config.no_default_features == Some(true) {
+        flags.push_str(" --no-default-features");
+    }
+    if config.all_features == Some(true) {
+        flags.push_str(" --all-features");
+    }
+    if let Some(features) = &config.features {
+        flags.push_str(&format!(" --features '{}'", features));
+    }
+    flags
+}
+
+/// Export the coverage of `sources` recorded in `profdata` as JSON.
+/// Merge raw coverage profiles into an indexed `.profdata` file.
+fn merge_coverage(profraws: &[PathBuf], profdata: &Path) -> Result<()> {
+    let mut merge = Command::new(utils::llvm_tool("llvm-profdata")?);
+    merge
+        .arg("merge")
+        .arg("-sparse")
+        .args(profraws)
+        .arg("-o")
+        .arg(profdata);
+    let status = merge
+        .status()
+        .with_context(|| format!("failed to execute: {:?}", merge))?;
+    if !status.success() {
+        bail!("failed to merge coverage profiles: {:?}", merge);
     }
+    Ok(())
+}
 
-    fn artifacts_for(&self, target: &str) -> Result<PathBuf> {
-        let mut p = self.path();
-        p.push("artifacts");
-        p.push(target);
+fn export_coverage(binary: &Path, profdata: &Path, sources: &Path) -> Result<String> {
+    let mut export = Command::new(utils::llvm_tool("llvm-cov")?);
+    export
+        .arg("export")
+        .arg(binary)
+        .arg(format!("-instr-profile={}", profdata.display()))
+        .arg("-format=text")
+        .arg(sources);
+    let output = export
+        .output()
+        .with_context(|| format!("failed to execute: {:?}", export))?;
+    if !output.status.success() {
+        bail!(
+            "failed to export coverage: {:?}\n\n{}",
+            export,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
 
-        // This adds a trailing slash, which is necessary for libFuzzer, because
-        // it does simple string concatenation when joining paths.
-        p.push("");
+/// Replay `inputs`, a file or a directory of them, through a coverage build of
+/// a fuzz target, writing the raw profiles to `raw_dir`, and read back the
+/// coverage of `sources`. There is no coverage when the fuzz target crashes
+/// before writing a profile.
+fn replay_coverage(
+    build: &BuildOptions,
+    binary: &Path,
+    inputs: &Path,
+    raw_dir: &Path,
+    sources: &Path,
+) -> Result<(ExitStatus, Option<coverage::Regions>)> {
+    fs::create_dir(raw_dir)
+        .with_context(|| format!("could not make a directory at {}", raw_dir.display()))?;
+    let mut cmd = binary_command(build, binary);
+    cmd.env("LLVM_PROFILE_FILE", raw_dir.join("default-%p.profraw"))
+        .arg("-runs=0")
+        .arg(inputs)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to execute: {:?}", cmd))?;
+    let profraws = collect_files(raw_dir)?;
+    if profraws.is_empty() {
+        return Ok((status, None));
+    }
+    let profdata = raw_dir.join("coverage.profdata");
+    merge_coverage(&profraws, &profdata)?;
+    let regions = coverage::parse_export(&export_coverage(binary, &profdata, sources)?, sources)?;
+    Ok((status, Some(regions)))
+}
 
-        fs::create_dir_all(&p)
-            .with_context(|| format!("could not make a artifact directory at {:?}", p))?;
+/// Returns the sorted paths of all the regular files directly inside `dir`.
+/// The libFuzzer flag that turns an engine feature on or off.
+fn libfuzzer_feature_flag(feature: EngineFeature) -> &'static str {
+    match feature {
+        EngineFeature::ValueProfile => "-use_value_profile",
+        EngineFeature::Entropic => "-entropic",
+        EngineFeature::CmpLog => "-use_cmp",
+    }
+}
 
-        Ok(p)
+/// Check that honggfuzz or LibAFL can fuzz with an engine feature turned on
+/// or off. Neither can switch them: both always mutate inputs with the values
+/// the fuzz target compares them with, and neither has value profiling or
+/// entropic scheduling.
+fn check_engine_feature(engine: Engine, feature: EngineFeature, on: bool) -> Result<()> {
+    if on == (feature == EngineFeature::CmpLog) {
+        return Ok(());
     }
+    let flag = if on {
+        feature.flag().to_string()
+    } else {
+        format!("--no-{}", feature)
+    };
+    bail!(
+        "the {} engine can't fuzz with `{}` (or its setting in fuzz/fuzz.toml); only \
+         libFuzzer can turn {} on and off",
+        engine,
+        flag,
+        feature
+    )
+}
 
-    fn fuzz_targets_dir(&self) -> PathBuf {
-        let mut root = self.path();
-        if root.join(crate::FUZZ_TARGETS_DIR_OLD).exists() {
-            println!(
-                "warning: The `fuzz/fuzzers/` directory has renamed to `fuzz/fuzz_targets/`. \
-                 Please rename the directory as such. This will become a hard error in the \
-                 future."
+/// A Cargo command on the crate that builds `target` against a stand-in for
+/// `libfuzzer-sys`, written to `dir`, with the features to build it with.
+fn shim_cargo(build: &BuildOptions, dir: &Path, target: &str, args: &[&str]) -> Command {
+    let mut cmd = utils::toolchain_command("cargo");
+    cmd.args(args)
+        .arg("--manifest-path")
+        .arg(dir.join("Cargo.toml"))
+        .arg("--bin")
+        .arg(target)
+        .arg("--target-dir")
+        .arg(dir.join("target"));
+    if build.verbose {
+        cmd.arg("--verbose");
+    }
+    if build.no_default_features {
+        cmd.arg("--no-default-features");
+    }
+    if build.all_features {
+        cmd.arg("--all-features");
+    }
+    if let Some(features) = &build.features {
+        cmd.arg("--features").arg(features);
+    }
+    cmd
+}
+
+/// Kill a child process and, on Unix, its own children, like the
+/// interpreter that `cargo miri run` starts, which would otherwise outlive
+/// Cargo.
+fn kill_tree(child: &mut Child) {
+    if cfg!(unix) {
+        let _ = Command::new("pkill")
+            .arg("-KILL")
+            .arg("-P")
+            .arg(child.id().to_string())
+            .status();
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn collect_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let entry = entry
+            .with_context(|| format!("failed to read directory entry inside {}", dir.display()))?;
+        if entry.file_type().map(|t| t.is_file()).unwrap_or(false) {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The total size of the files in `dir` and its subdirectories, not
+/// following symlinks.
+fn dir_size(dir: &Path) -> u64 {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| Some((entry.path(), entry.file_type().ok()?)))
+        .map(|(path, file_type)| {
+            if file_type.is_dir() {
+                dir_size(&path)
+            } else {
+                fs::symlink_metadata(&path).map_or(0, |m| m.len())
+            }
+        })
+        .sum()
+}
+
+/// The inputs among `inputs` that `filter` selects, after printing how many
+/// there are.
+fn filter_inputs(filter: &options::InputFilter, inputs: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
+    let total = inputs.len();
+    let mut selected = Vec::new();
+    for input in inputs {
+        let data =
+            fs::read(&input).with_context(|| format!("failed to read {}", input.display()))?;
+        if filter.matches(&data) {
+            selected.push(input);
+        }
+    }
+    if selected.is_empty() {
+        bail!("none of the {} input(s) in the corpus {}", total, filter);
+    }
+    eprintln!(
+        "{} of {} input(s) in the corpus {}",
+        selected.len(),
+        total,
+        filter
+    );
+    Ok(selected)
+}
+
+/// Copy `inputs` into `dir`, making it if it doesn't exist.
+fn copy_inputs(inputs: &[PathBuf], dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+    for input in inputs {
+        let copy = dir.join(input.file_name().unwrap());
+        fs::copy(input, &copy)
+            .with_context(|| format!("failed to copy {} to {}", input.display(), copy.display()))?;
+    }
+    Ok(())
+}
+
+/// Split `inputs` round-robin into `shards` directories under `dir`, hard
+/// linking them where possible, and return the directories.
+fn shard_inputs(inputs: &[PathBuf], shards: usize, dir: &Path) -> Result<Vec<PathBuf>> {
+    let dirs: Vec<PathBuf> = (0..shards.min(inputs.len()).max(1))
+        .map(|i| dir.join(i.to_string()))
+        .collect();
+    for shard in &dirs {
+        fs::create_dir_all(shard)
+            .with_context(|| format!("could not make a directory at {}", shard.display()))?;
+    }
+    for (i, input) in inputs.iter().enumerate() {
+        // Inputs from different corpora can have the same name.
+        let link = dirs[i % dirs.len()].join(i.to_string());
+        if fs::hard_link(input, &link).is_err() {
+            fs::copy(input, &link).with_context(|| {
+                format!("failed to copy {} to {}", input.display(), link.display())
+            })?;
+        }
+    }
+    Ok(dirs)
+}
+
+/// The number of inputs in a corpus and their total size in bytes.
+fn corpus_size(inputs: &[PathBuf]) -> (usize, u64) {
+    let bytes = inputs
+        .iter()
+        .filter_map(|input| fs::metadata(input).ok())
+        .map(|m| m.len())
+        .sum();
+    (inputs.len(), bytes)
+}
+
+/// The commands a fuzz target's config can have run around it.
+#[derive(Clone, Copy)]
+enum Hook {
+    PreRun,
+    PostRun,
+}
+
+/// What happens when `cargo fuzz bisect` runs the artifact at a revision.
+enum BisectVerdict {
+    Crashes(Crash),
+    Passes,
+    /// The revision can't be tested, for the given reason.
+    Skip(String),
+}
+
+impl std::fmt::Display for BisectVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BisectVerdict::Crashes(crash) => write!(f, "the artifact crashes ({})", crash.kind),
+            BisectVerdict::Passes => write!(f, "the artifact doesn't crash"),
+            BisectVerdict::Skip(reason) => write!(f, "skipped, since {}", reason),
+        }
+    }
+}
+
+/// How much `cmin` shrank a fuzz target's corpus.
+struct CorpusDelta {
+    target: String,
+    before: (usize, u64),
+    after: (usize, u64),
+}
+
+impl std::fmt::Display for CorpusDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} inputs, {} -> {} bytes",
+            self.target, self.before.0, self.after.0, self.before.1, self.after.1
+        )
+    }
+}
+
+/// Returns the sorted paths of all the `.rs` files inside `dir`, recursively.
+/// A missing `dir` has no files.
+fn collect_rust_sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory entries of {}", dir.display()))?
+    {
+        let entry = entry
+            .with_context(|| format!("failed to read directory entry inside {}", dir.display()))?;
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(collect_rust_sources(&path)?);
+        } else if path.extension() == Some(ffi::OsStr::new("rs")) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The command that syncs a directory to or from `remote`, without deleting
+/// anything, given the source and destination as its final two arguments.
+fn sync_command(remote: &str) -> Result<Command> {
+    if remote.starts_with("s3://") {
+        let mut cmd = Command::new("aws");
+        cmd.arg("s3").arg("sync").arg("--only-show-errors");
+        Ok(cmd)
+    } else if remote.starts_with("gs://") {
+        let mut cmd = Command::new("gsutil");
+        cmd.arg("-m").arg("-q").arg("rsync").arg("-r");
+        Ok(cmd)
+    } else {
+        bail!(
+            "unsupported remote `{}`: expected an `s3://` or `gs://` URL",
+            remote
+        )
+    }
+}
+
+/// Run Cargo, rustc and the LLVM tools from a rustup toolchain from now on,
+/// after checking that it's installed with what `build` needs: the standard
+/// library for the target, the sanitizer's runtime, the standard library's
+/// sources for the sanitizers that rebuild it, and the LLVM tools for
+/// coverage.
+fn use_toolchain(toolchain: &str, build: &BuildOptions, sanitizer: Sanitizer) -> Result<()> {
+    let mut rustc = Command::new("rustup");
+    rustc
+        .args(["run", toolchain, "rustc", "--print=sysroot"])
+        .env("RUSTUP_AUTO_INSTALL", "0");
+    let output = rustc.output().with_context(|| {
+        format!(
+            "failed to run {:?}; pinning a toolchain needs rustup",
+            rustc
+        )
+    })?;
+    if !output.status.success() {
+        bail!(
+            "the toolchain `{0}` that fuzz builds are pinned to isn't installed; \
+             run `rustup toolchain install {0}`",
+            toolchain
+        );
+    }
+    utils::pin_toolchain(toolchain)?;
+    let sysroot = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    let rustlib = sysroot.join("lib").join("rustlib");
+
+    let lib = rustlib.join(&build.triple).join("lib");
+    let files: Vec<String> = match fs::read_dir(&lib) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect(),
+        Err(_) => bail!(
+            "the toolchain `{0}` has no standard library for {1}; \
+             run `rustup target add {1} --toolchain {0}`",
+            toolchain,
+            build.triple
+        ),
+    };
+    let runtime = match sanitizer {
+        Sanitizer::Address => Some("asan"),
+        Sanitizer::Leak => Some("lsan"),
+        Sanitizer::Memory => Some("msan"),
+        Sanitizer::Thread => Some("tsan"),
+        Sanitizer::None | Sanitizer::All => None,
+    };
+    // The MSVC toolset has AddressSanitizer's runtime on Windows.
+    if let Some(runtime) = runtime.filter(|_| !build.triple.ends_with("-windows-msvc")) {
+        let runtime = format!("_rt.{}.", runtime);
+        if !files.iter().any(|f| f.contains(&runtime)) {
+            bail!(
+                "the toolchain `{0}` has no {1} sanitizer runtime for {2}; \
+                 run `rustup component add rust-std --target {2} --toolchain {0}`, \
+                 or build with `--sanitizer none`",
+                toolchain,
+                sanitizer,
+                build.triple
             );
-            root.push(crate::FUZZ_TARGETS_DIR_OLD);
-        } else {
-            root.push(crate::FUZZ_TARGETS_DIR);
         }
-        root
     }
+    if let Sanitizer::Memory | Sanitizer::Thread = sanitizer {
+        if !rustlib.join("src").join("rust").is_dir() {
+            bail!(
+                "the {1} sanitizer rebuilds the standard library, but the toolchain `{0}` \
+                 doesn't have its sources; run `rustup component add rust-src --toolchain {0}`",
+                toolchain,
+                sanitizer
+            );
+        }
+    }
+    if build.coverage {
+        let host = utils::host_triple().unwrap_or(&build.triple);
+        let profdata = rustlib
+            .join(host)
+            .join("bin")
+            .join(format!("llvm-profdata{}", env::consts::EXE_SUFFIX));
+        if !profdata.is_file() {
+            bail!(
+                "the toolchain `{0}` doesn't have the LLVM tools for coverage; \
+                 run `rustup component add llvm-tools-preview --toolchain {0}`",
+                toolchain
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Export the coverage of the files in `sources` from a profile of `binary`
+/// as an lcov report.
+fn export_lcov(binary: &Path, profdata: &Path, sources: &Path) -> Result<String> {
+    let mut export = Command::new(utils::llvm_tool("llvm-cov")?);
+    export
+        .arg("export")
+        .arg(binary)
+        .arg(format!("-instr-profile={}", profdata.display()))
+        .arg("-format=lcov")
+        .arg(sources);
+    let output = export
+        .output()
+        .with_context(|| format!("failed to execute: {:?}", export))?;
+    if !output.status.success() {
+        bail!(
+            "failed to export lcov coverage: {:?}\n\n{}",
+            export,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
 
-    fn target_path(&self, target: &str) -> PathBuf {
-        let mut root = self.fuzz_targets_dir();
-        root.push(target);
-        root.set_extension("rs");
-        root
+/// Open an issue in a GitHub repository, like `owner/repo`, through the API at
+/// $GITHUB_API_URL (or api.github.com) with the token in $GITHUB_TOKEN, using
+/// curl. Returns the URL of the new issue.
+fn open_github_issue(repo: &str, title: &str, body: &str) -> Result<String> {
+    if repo.split('/').count() != 2 || repo.split('/').any(str::is_empty) {
+        bail!("invalid GitHub repository `{}`: expected OWNER/REPO", repo);
     }
+    let token = env::var("GITHUB_TOKEN")
+        .map_err(|_| anyhow!("opening a GitHub issue needs a token in $GITHUB_TOKEN"))?;
+    let api = env::var("GITHUB_API_URL").unwrap_or_else(|_| "https://api.github.com".to_string());
 
-    fn manifest(&self) -> Result<toml::Value> {
-        let filename = self.manifest_path();
-        let mut file = fs::File::open(&filename)
-            .with_context(|| format!("could not read the manifest file: {}", filename.display()))?;
-        let mut data = Vec::new();
-        file.read_to_end(&mut data)?;
-        toml::from_slice(&data).with_context(|| {
+    // Keep the token out of the command line, where other users could see it.
+    let mut headers = tempfile::NamedTempFile::new().context("failed to create temp file")?;
+    writeln!(headers, "Authorization: Bearer {}", token)
+        .context("failed to write the request headers")?;
+    let mut cmd = Command::new("curl");
+    cmd.arg("--silent")
+        .arg("--show-error")
+        .arg("--fail")
+        .arg("--header")
+        .arg(format!("@{}", headers.path().display()))
+        .arg("--header")
+        .arg("Accept: application/vnd.github+json")
+        .arg("--data-binary")
+        .arg("@-")
+        .arg(format!(
+            "{}/repos/{}/issues",
+            api.trim_end_matches('/'),
+            repo
+        ))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped());
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("failed to run {:?}; is curl installed?", cmd))?;
+    let request = serde_json::json!({ "title": title, "body": body });
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(request.to_string().as_bytes())
+        .context("failed to send the issue to curl")?;
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
+    if !output.status.success() {
+        bail!(
+            "failed to open an issue in {}: curl exited with {}",
+            repo,
+            output.status
+        );
+    }
+    let response: serde_json::Value =
+        serde_json::from_slice(&output.stdout).context("could not decode GitHub's response")?;
+    response["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("GitHub's response has no issue URL"))
+}
+
+/// The crash that a fuzz target's output shows, or `None` if it exited
+/// successfully.
+fn crash_from_output(output: &Output) -> Option<Crash> {
+    if output.status.success() {
+        return None;
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Some(Crash::parse(&stderr).unwrap_or_else(|| {
+        Crash {
+            // A Windows process that crashes without libFuzzer or a sanitizer
+            // reporting it, like one that fails fast, exits with the exception.
+            kind: crash::exception(output.status)
+                .map_or("unknown", |(_, kind)| kind)
+                .to_string(),
+            message: Some(format!(
+                "fuzz target exited with {}",
+                crash::describe_exit(output.status)
+            )),
+            location: None,
+            frames: Vec::new(),
+        }
+    }))
+}
+
+/// Rename the crash artifacts of a fuzzer whose crash `report` shows a stack
+/// overflow, from libFuzzer's `crash-<hash>` to `stack-overflow-<hash>`,
+/// updating their paths.
+fn label_stack_overflows(report: &str, artifacts: &mut [PathBuf]) -> Result<()> {
+    let crash = match Crash::parse(report) {
+        Some(crash) if crash.kind == "stack-overflow" => crash,
+        _ => return Ok(()),
+    };
+    for artifact in artifacts.iter_mut() {
+        let hash = match artifact
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_prefix("crash-"))
+        {
+            Some(hash) => hash,
+            None => continue,
+        };
+        let labelled = artifact.with_file_name(format!("{}{}", STACK_OVERFLOW_PREFIX, hash));
+        fs::rename(&*artifact, &labelled).with_context(|| {
             format!(
-                "could not decode the manifest file at {}",
-                filename.display()
+                "failed to move {} to {}",
+                artifact.display(),
+                labelled.display()
             )
+        })?;
+        eprintln!(
+            "\nThe crash was a stack overflow{}; saved the input as {}",
+            crash
+                .message
+                .as_ref()
+                .map(|message| format!(" ({})", message))
+                .unwrap_or_default(),
+            strip_current_dir_prefix(&labelled).display()
+        );
+        *artifact = labelled;
+    }
+    Ok(())
+}
+
+/// Whether `path` is an artifact that `label_stack_overflows` labelled.
+fn is_stack_overflow(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(STACK_OVERFLOW_PREFIX))
+}
+
+/// The name of the configuration that builds with these options belong to,
+/// like `libfuzzer-address` or `libfuzzer-none-coverage`: the engine and the
+/// sanitizer, followed by any extra instrumentation.
+fn build_config(build: &BuildOptions) -> String {
+    let engine = build.engine.unwrap_or(Engine::Libfuzzer);
+    let mut config = match build.sanitizer.unwrap_or(Sanitizer::Address) {
+        Sanitizer::None => format!("{}-none", engine),
+        sanitizer => format!("{}-{}", engine, sanitizer),
+    };
+    if build.coverage {
+        config.push_str("-coverage");
+    }
+    if build.debuginfo {
+        config.push_str("-debuginfo");
+    }
+    if build.quick {
+        config.push_str("-quick");
+    }
+    if build.profiling {
+        config.push_str("-profiling");
+    }
+    if build.record_trace {
+        config.push_str("-trace");
+    }
+    if build.record_cmps {
+        config.push_str("-cmps");
+    }
+    if let Some(crates) = build.coverage_crates.as_ref().filter(|_| !build.coverage) {
+        let crates = rustc_wrapper::parse_crates(crates).join(",");
+        config.push_str(&format!(
+            "-crates{:08x}",
+            crash::fnv1a(crates.as_bytes()) as u32
+        ));
+    }
+    // `libfuzzer-sys` doesn't rebuild when the archive it links changes, so
+    // each archive gets a build of its own.
+    if let (Engine::Libfuzzer, Some(archive)) = (engine, &build.libfuzzer_archive) {
+        let archive = fs::canonicalize(archive).unwrap_or_else(|_| archive.clone());
+        config.push_str(&format!(
+            "-archive{:08x}",
+            crash::fnv1a(archive.to_string_lossy().as_bytes()) as u32
+        ));
+    }
+    config
+}
+
+/// Whether `name` is the name of a build configuration, from `build_config`.
+fn is_build_config(name: &str) -> bool {
+    let mut parts = name.split('-');
+    let engine = parts.next().and_then(|e| e.parse::<Engine>().ok());
+    let sanitizer = parts.next().and_then(|s| s.parse::<Sanitizer>().ok());
+    engine.is_some()
+        && sanitizer.is_some_and(|s| s != Sanitizer::All)
+        && parts.all(|part| {
+            [
+                "coverage",
+                "debuginfo",
+                "quick",
+                "profiling",
+                "trace",
+                "cmps",
+            ]
+            .contains(&part)
+                || ["crates", "archive"].iter().any(|prefix| {
+                    part.strip_prefix(prefix).is_some_and(|hash| {
+                        hash.len() == 8 && hash.chars().all(|c| c.is_ascii_hexdigit())
+                    })
+                })
         })
+}
+
+/// The absolute path of a custom libFuzzer archive, which has to exist,
+/// since `libfuzzer-sys` only finds out that it doesn't when linking.
+fn libfuzzer_archive(archive: &Path) -> Result<PathBuf> {
+    if !archive.is_file() {
+        bail!(
+            "the libFuzzer archive {} doesn't exist",
+            strip_current_dir_prefix(archive).display()
+        );
     }
+    fs::canonicalize(archive).with_context(|| format!("failed to resolve {}", archive.display()))
+}
 
-    fn root_project_name(&self) -> Result<String> {
-        let filename = self.root_project.join("Cargo.toml");
-        let mut file = fs::File::open(&filename)?;
-        let mut data = Vec::new();
-        file.read_to_end(&mut data)?;
-        let value: toml::Value = toml::from_slice(&data)?;
-        let name = value
-            .as_table()
-            .and_then(|v| v.get("package"))
-            .and_then(toml::Value::as_table)
-            .and_then(|v| v.get("name"))
-            .and_then(toml::Value::as_str);
-        if let Some(name) = name {
-            Ok(String::from(name))
-        } else {
-            bail!("{} (package.name) is malformed", filename.display());
+/// The keys of a `libfuzzer-sys` dependency on the source that `spec` names:
+/// a git URL with an optional `#<rev>`, a version requirement, or otherwise a
+/// path.
+fn libfuzzer_sys_source(spec: &str) -> Result<Vec<(&'static str, String)>> {
+    if spec.contains("://") {
+        let (url, rev) = match spec.split_once('#') {
+            Some((url, rev)) => (url, Some(rev)),
+            None => (spec, None),
+        };
+        if rev == Some("") {
+            bail!("invalid libfuzzer-sys source: {}", spec);
         }
+        let mut keys = vec![("git", url.to_string())];
+        keys.extend(rev.map(|rev| ("rev", rev.to_string())));
+        Ok(keys)
+    } else if spec.starts_with(|c: char| c.is_ascii_digit() || "=^~<>*".contains(c)) {
+        Ok(vec![("version", spec.to_string())])
+    } else if !spec.is_empty() {
+        Ok(vec![("path", spec.to_string())])
+    } else {
+        bail!("invalid libfuzzer-sys source: {}", spec)
     }
 }
 
-fn collect_targets(value: &toml::Value) -> Vec<String> {
-    let bins = value
-        .as_table()
-        .and_then(|v| v.get("bin"))
-        .and_then(toml::Value::as_array);
-    let mut bins = if let Some(bins) = bins {
-        bins.iter()
-            .map(|bin| {
-                bin.as_table()
-                    .and_then(|v| v.get("name"))
-                    .and_then(toml::Value::as_str)
-            })
-            .filter_map(|name| name.map(String::from))
-            .collect()
-    } else {
-        Vec::new()
+/// A version, as `(major, minor, patch)`.
+type Version = (u64, u64, u64);
+
+/// The lowest and the newest versions that a Cargo version requirement like
+/// `0.4`, `=0.3.5` or `>=0.4, <0.6` allows, going by its first comparator, or
+/// `None` for one like `*` or `<0.5`.
+fn version_range(requirement: &str) -> Option<(Version, Version)> {
+    let first = requirement.split(',').next()?.trim();
+    let version = first.trim_start_matches(|c| "=^~>".contains(c));
+    let operator = &first[..first.len() - version.len()];
+    let version = version.trim().split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(str::parse::<u64>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    let lowest = (major, minor, patch);
+    let newest = match operator {
+        "=" => lowest,
+        ">" | ">=" => (u64::MAX, u64::MAX, u64::MAX),
+        // A caret requirement allows newer releases that are compatible,
+        // which for `0.x` means the same minor version, like a tilde one.
+        "" | "^" if major > 0 => (major, u64::MAX, u64::MAX),
+        "" | "^" | "~" => (major, minor, u64::MAX),
+        _ => return None,
     };
-    // Always sort them, so that we have deterministic output.
-    bins.sort();
-    bins
+    Some((lowest, newest))
 }
 
-fn is_fuzz_manifest(value: &toml::Value) -> bool {
-    let is_fuzz = value
-        .as_table()
-        .and_then(|v| v.get("package"))
-        .and_then(toml::Value::as_table)
-        .and_then(|v| v.get("metadata"))
-        .and_then(toml::Value::as_table)
-        .and_then(|v| v.get("cargo-fuzz"))
-        .and_then(toml::Value::as_bool);
-    is_fuzz == Some(true)
+/// The directory under `<target dir>/<triple>` that Cargo builds with these
+/// options' profile into.
+fn profile_dir(build: &BuildOptions) -> &str {
+    match build.profile.as_deref() {
+        _ if build.dev => "debug",
+        None | Some("bench") => "release",
+        Some("test") => "debug",
+        Some(profile) => profile,
+    }
 }
 
-/// Returns the path for the first found non-fuzz Cargo package
-fn find_package() -> Result<PathBuf> {
-    let mut dir = env::current_dir()?;
-    let mut data = Vec::new();
-    loop {
-        let manifest_path = dir.join("Cargo.toml");
-        match fs::File::open(&manifest_path) {
-            Err(_) => {}
-            Ok(mut f) => {
-                data.clear();
-                f.read_to_end(&mut data)
-                    .with_context(|| format!("failed to read {}", manifest_path.display()))?;
-                let value: toml::Value = toml::from_slice(&data).with_context(|| {
-                    format!(
-                        "could not decode the manifest file at {}",
-                        manifest_path.display()
-                    )
-                })?;
-                if !is_fuzz_manifest(&value) {
-                    // Not a cargo-fuzz project => must be a proper cargo project :)
-                    return Ok(dir);
+/// The name of a fuzz target added at `path` in `fuzz_targets`: its
+/// components joined with underscores, so `parser/headers` is named
+/// `parser_headers`.
+fn nested_target_name(path: &str) -> Result<String> {
+    let components: Vec<&str> = path.split('/').collect();
+    if components
+        .iter()
+        .any(|c| c.is_empty() || *c == "." || *c == "..")
+    {
+        bail!("invalid fuzz target path: {}", path);
+    }
+    Ok(components.join("_"))
+}
+
+/// Rewrite the tables of a TOML document that `edit` returns replacements
+/// for, given the lines of each table from its header on, and return the
+/// document along with how many tables were rewritten.
+///
+/// This works on the text rather than a parsed document, so that the
+/// formatting and comments of everything else are kept.
+fn edit_toml_tables(
+    text: &str,
+    mut edit: impl FnMut(&[&str]) -> Option<Vec<String>>,
+) -> (String, usize) {
+    let mut tables: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in text.lines() {
+        if line.trim_start().starts_with('[') {
+            tables.push(Vec::new());
+        }
+        tables.last_mut().unwrap().push(line);
+    }
+    let mut out = String::new();
+    let mut edited = 0;
+    for (i, table) in tables.iter().enumerate() {
+        match (i > 0).then(|| edit(table)).flatten() {
+            Some(lines) => {
+                edited += 1;
+                for line in lines {
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+            None => {
+                for line in table {
+                    out.push_str(line);
+                    out.push('\n');
                 }
             }
         }
-        if !dir.pop() {
-            break;
+    }
+    (format!("{}\n", out.trim_end()), edited)
+}
+
+/// The key and unquoted value of a `key = value` line of TOML.
+fn toml_key_value(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once('=')?;
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value);
+    Some((key.trim(), value))
+}
+
+/// Whether a table of the fuzz crate's manifest is the `[[bin]]` of `target`.
+fn is_bin_named(table: &[&str], target: &str) -> bool {
+    table[0].trim() == "[[bin]]"
+        && table
+            .iter()
+            .any(|line| toml_key_value(line) == Some(("name", target)))
+}
+
+/// If a table of `fuzz.toml` is the settings of `target`, like
+/// `[targets.<target>]` or `[targets.<target>.env]`, the rest of its header
+/// after the target's name.
+fn target_table<'a>(table: &[&'a str], target: &str) -> Option<&'a str> {
+    let header = table[0].trim().strip_prefix("[targets.")?;
+    let rest = header
+        .strip_prefix(target)
+        .or_else(|| header.strip_prefix(&format!("\"{}\"", target)))?;
+    (rest.starts_with(']') || rest.starts_with('.')).then_some(rest)
+}
+
+/// Remove `dir` and the directories above it up to `root`, for as long as
+/// they're empty.
+fn remove_empty_dirs(dir: &Path, root: &Path) {
+    let mut dir = dir;
+    while dir != root && dir.starts_with(root) && fs::remove_dir(dir).is_ok() {
+        match dir.parent() {
+            Some(parent) => dir = parent,
+            None => break,
         }
     }
-    bail!("could not find a cargo project")
+}
+
+/// Turn an arbitrary name into a valid Rust identifier.
+fn identifier(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident.insert_str(0, "input_");
+    }
+    ident
+}
+
+fn is_divergence_report(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(DIVERGENCE_REPORT_PREFIX))
+}
+
+/// The divergence report written alongside an artifact, if there is one.
+fn divergence_report(artifact: &Path) -> Option<PathBuf> {
+    let data = fs::read(artifact).ok()?;
+    let report = artifact.with_file_name(format!(
+        "{}{:016x}.json",
+        DIVERGENCE_REPORT_PREFIX,
+        crash::fnv1a(&data)
+    ));
+    Some(report).filter(|report| report.is_file())
 }
 
 fn strip_current_dir_prefix(path: &Path) -> &Path {
     env::current_dir()
         .ok()
         .and_then(|curdir| path.strip_prefix(curdir).ok())
-        .unwrap_or(&path)
+        .unwrap_or(path)
 }
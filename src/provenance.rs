@@ -0,0 +1,147 @@
+//! Where the inputs in a fuzz target's corpus came from, recorded in
+//! `fuzz/provenance/<target>.json` and shown by `cargo fuzz corpus info`.
+//!
+//! Inputs are keyed by a hash of their contents rather than by file name,
+//! since `cargo fuzz cmin` renames the inputs it keeps. Inputs that `cargo
+//! fuzz` finds in a corpus without having added them itself are recorded as
+//! seeds.
+
+use crate::stats;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt as stdfmt;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// How an input got into the corpus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Origin {
+    /// Put there by hand, or before `cargo fuzz` tracked the corpus.
+    Seed,
+    /// Found by `cargo fuzz run`.
+    Fuzz,
+    /// Minimized by `cargo fuzz tmin --batch`.
+    Tmin,
+    /// Copied in by `cargo fuzz corpus import`.
+    Import,
+    /// Recorded from the fuzzed crate's tests by `cargo fuzz corpus
+    /// from-tests`.
+    FromTests,
+    /// Downloaded by `cargo fuzz corpus pull`.
+    Pull,
+    /// Found in another fuzz target's corpus by `cargo fuzz corpus
+    /// cross-pollinate`.
+    CrossPollinate,
+}
+
+impl stdfmt::Display for Origin {
+    fn fmt(&self, f: &mut stdfmt::Formatter) -> stdfmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Origin::Seed => "seed",
+                Origin::Fuzz => "fuzz",
+                Origin::Tmin => "tmin",
+                Origin::Import => "import",
+                Origin::FromTests => "from-tests",
+                Origin::Pull => "pull",
+                Origin::CrossPollinate => "cross-pollinate",
+            }
+        )
+    }
+}
+
+impl FromStr for Origin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "seed" => Ok(Origin::Seed),
+            "fuzz" => Ok(Origin::Fuzz),
+            "tmin" => Ok(Origin::Tmin),
+            "import" => Ok(Origin::Import),
+            "from-tests" => Ok(Origin::FromTests),
+            "pull" => Ok(Origin::Pull),
+            "cross-pollinate" => Ok(Origin::CrossPollinate),
+            _ => Err(format!("unknown origin: {}", s)),
+        }
+    }
+}
+
+/// The provenance of one input.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Entry {
+    pub origin: Origin,
+    /// When the input was added to the corpus, in seconds since the Unix
+    /// epoch.
+    pub discovered: u64,
+    /// The ID of the run that found the input, which is also in the run's
+    /// stats record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+}
+
+/// The provenance of each input in a corpus, by the hash of its contents.
+pub type Index = BTreeMap<String, Entry>;
+
+/// Load the index at `path`, or an empty one if there's no such file.
+pub fn load(path: &Path) -> Result<Index> {
+    if !path.exists() {
+        return Ok(Index::new());
+    }
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("could not decode {}", path.display()))
+}
+
+/// Save an index to `path`, replacing any there was.
+pub fn save(path: &Path, index: &Index) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+    }
+    let data = serde_json::to_string_pretty(index).expect("indexes always serialize");
+    fs::write(path, data + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Render a table of inputs and their provenance, oldest first, followed by
+/// how many came from each origin. Inputs that aren't in the index yet are
+/// shown as untracked.
+pub fn render(inputs: &[(String, Option<&Entry>)]) -> String {
+    let mut inputs: Vec<&(String, Option<&Entry>)> = inputs.iter().collect();
+    inputs.sort_by_key(|(name, entry)| (entry.map(|e| e.discovered), name.clone()));
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<42} {:<9} {:<20} run",
+        "input", "origin", "discovered (UTC)"
+    );
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for (name, entry) in &inputs {
+        let (origin, discovered, run) = match entry {
+            Some(entry) => (
+                entry.origin.to_string(),
+                stats::format_timestamp(entry.discovered),
+                entry.run.as_deref().unwrap_or("-"),
+            ),
+            None => ("untracked".to_string(), "-".to_string(), "-"),
+        };
+        let _ = writeln!(out, "{:<42} {:<9} {:<20} {}", name, origin, discovered, run);
+        *counts.entry(origin).or_default() += 1;
+    }
+
+    let _ = writeln!(out);
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(origin, count)| format!("{} {}", count, origin))
+        .collect();
+    let _ = writeln!(out, "{} input(s): {}", inputs.len(), counts.join(", "));
+    out
+}
@@ -0,0 +1,138 @@
+//! Reproducibility manifests, for `cargo fuzz run --seed` and
+//! `--replay-manifest`.
+//!
+//! A deterministic run fuzzes with a fixed seed, a fixed number of runs, and a
+//! single job, and writes everything else that decides which inputs it tries
+//! to a manifest: the build settings and libFuzzer arguments, the dictionary,
+//! and hashes of the corpus and the fuzz target's binary. Replaying the
+//! manifest with the same binary and corpus tries the same inputs in the same
+//! order. The versions of the toolchain and the engine are recorded too, to
+//! explain why a replay went differently.
+
+use crate::config::TargetConfig;
+use crate::crash::fnv1a;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The contents of a reproducibility manifest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Manifest {
+    pub target: String,
+    pub triple: String,
+    /// The build settings and libFuzzer arguments, like those of a target in
+    /// `fuzz.toml`, without the seed and the number of runs.
+    pub settings: TargetConfig,
+    /// The seed for libFuzzer's random number generator.
+    pub seed: u32,
+    /// How many inputs libFuzzer tries before stopping.
+    pub runs: u64,
+    /// The dictionary that the run fuzzed with, if any.
+    pub dictionary: Option<String>,
+    /// A hash of the corpus that the run started from.
+    pub corpus_hash: String,
+    /// A hash of the fuzz target's binary.
+    pub binary_hash: String,
+    pub cargo_fuzz_version: String,
+    pub rustc_version: Option<String>,
+    /// The version of the engine's crate, like `libfuzzer-sys 0.4.7`, from the
+    /// fuzz crate's lockfile.
+    pub engine_version: Option<String>,
+}
+
+impl Manifest {
+    /// Load the manifest at `path`.
+    pub fn load(path: &Path) -> Result<Manifest> {
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data).with_context(|| {
+            format!(
+                "could not decode the reproducibility manifest at {}",
+                path.display()
+            )
+        })
+    }
+
+    /// Save the manifest to `path`, making its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let dir = path.parent().unwrap();
+        fs::create_dir_all(dir)
+            .with_context(|| format!("could not make a directory at {}", dir.display()))?;
+        let data = serde_json::to_string_pretty(self).expect("manifests always serialize");
+        fs::write(path, data + "\n").with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// How a manifest written by a later run differs from this one in what
+    /// it doesn't control, as a message for each difference.
+    pub fn differences(&self, current: &Manifest) -> Vec<String> {
+        let mut differences = Vec::new();
+        if current.corpus_hash != self.corpus_hash {
+            differences.push("the corpus has changed".to_string());
+        }
+        if current.binary_hash != self.binary_hash {
+            differences.push("the fuzz target's binary has changed".to_string());
+        }
+        let versions = [
+            (
+                "cargo-fuzz",
+                &self.cargo_fuzz_version,
+                &current.cargo_fuzz_version,
+            ),
+            (
+                "rustc",
+                &or_unknown(&self.rustc_version),
+                &or_unknown(&current.rustc_version),
+            ),
+            (
+                "the engine",
+                &or_unknown(&self.engine_version),
+                &or_unknown(&current.engine_version),
+            ),
+        ];
+        for (what, then, now) in versions {
+            if then != now {
+                differences.push(format!("{} was {}, and is now {}", what, then, now));
+            }
+        }
+        differences
+    }
+}
+
+fn or_unknown(version: &Option<String>) -> String {
+    version.clone().unwrap_or_else(|| "unknown".to_string())
+}
+
+/// A hash of the names and contents of the inputs in `dirs`, in order.
+pub fn corpus_hash(dirs: &[PathBuf]) -> Result<String> {
+    let mut data = Vec::new();
+    for dir in dirs {
+        let mut inputs = Vec::new();
+        if dir.is_dir() {
+            for entry in
+                fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))?
+            {
+                let path = entry
+                    .with_context(|| {
+                        format!("failed to read directory entry inside {}", dir.display())
+                    })?
+                    .path();
+                if path.is_file() {
+                    inputs.push(path);
+                }
+            }
+        } else if dir.is_file() {
+            inputs.push(dir.clone());
+        }
+        inputs.sort();
+        for input in inputs {
+            data.extend_from_slice(input.file_name().unwrap().to_string_lossy().as_bytes());
+            data.push(0);
+            let contents =
+                fs::read(&input).with_context(|| format!("failed to read {}", input.display()))?;
+            data.extend_from_slice(&fnv1a(&contents).to_le_bytes());
+        }
+    }
+    Ok(format!("{:016x}", fnv1a(&data)))
+}
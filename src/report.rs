@@ -0,0 +1,146 @@
+//! Markdown reports of crashes, for `cargo fuzz report`, ready to paste into
+//! an issue.
+
+use crate::crash::Crash;
+use crate::utils;
+use std::fmt::Write as _;
+
+/// Inputs up to this many bytes are shown as a byte string literal, and longer
+/// ones as base64.
+const MAX_LITERAL_LEN: usize = 256;
+
+/// How many lines of the fuzz target's source to include.
+const MAX_SOURCE_LINES: usize = 60;
+
+/// How many lines of the backtrace to include.
+const MAX_BACKTRACE_LINES: usize = 200;
+
+pub struct Report<'a> {
+    pub target: &'a str,
+    pub crash: &'a Crash,
+    pub cargo_fuzz_version: &'a str,
+    pub rustc_version: &'a str,
+    pub triple: &'a str,
+    pub sanitizer: String,
+    /// The fuzz target's source.
+    pub source: &'a str,
+    pub input: &'a [u8],
+    pub minimized: bool,
+    /// The output of the fuzz target when it crashed.
+    pub output: &'a str,
+}
+
+impl Report<'_> {
+    /// A title for the issue.
+    pub fn title(&self) -> String {
+        match &self.crash.message {
+            Some(message) => format!(
+                "Fuzz target `{}` crashes: {}: {}",
+                self.target, self.crash.kind, message
+            ),
+            None => format!("Fuzz target `{}` crashes: {}", self.target, self.crash.kind),
+        }
+    }
+
+    /// The body of the issue.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "## {}\n", self.title());
+        let _ = writeln!(out, "- cargo-fuzz: {}", self.cargo_fuzz_version);
+        let _ = writeln!(out, "- rustc: {}", self.rustc_version);
+        let _ = writeln!(out, "- target: {}", self.triple);
+        let _ = writeln!(out, "- sanitizer: {}", self.sanitizer);
+        if let Some(location) = &self.crash.location {
+            let _ = writeln!(out, "- location: `{}`", location);
+        }
+
+        let _ = writeln!(out, "\n### Fuzz target\n\n```rust");
+        let lines: Vec<&str> = self.source.lines().collect();
+        for line in lines.iter().take(MAX_SOURCE_LINES) {
+            let _ = writeln!(out, "{}", line);
+        }
+        if lines.len() > MAX_SOURCE_LINES {
+            let _ = writeln!(out, "// ... {} more lines", lines.len() - MAX_SOURCE_LINES);
+        }
+        let _ = writeln!(out, "```");
+
+        let _ = writeln!(
+            out,
+            "\n### Input\n\n{} byte(s){}:\n",
+            self.input.len(),
+            if self.minimized { ", minimized" } else { "" }
+        );
+        let short = self.input.len() <= MAX_LITERAL_LEN;
+        if short {
+            let _ = writeln!(
+                out,
+                "```rust\n{}\n```",
+                utils::byte_string_literal(self.input, 80)
+            );
+        } else {
+            let _ = writeln!(out, "```text\n{}\n```", base64(self.input, 76));
+        }
+
+        let _ = writeln!(out, "\n### Backtrace\n\n```text");
+        let _ = writeln!(out, "{}", backtrace(self.output));
+        let _ = writeln!(out, "```");
+
+        let _ = writeln!(out, "\n### Reproduce\n");
+        if short {
+            let _ = writeln!(
+                out,
+                "```sh\necho '{}' | base64 -d > crash.bin\ncargo fuzz run {} crash.bin\n```",
+                base64(self.input, usize::MAX),
+                self.target
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                "Decode the input above into `crash.bin` with `base64 -d`, then run:\n\n\
+                 ```sh\ncargo fuzz run {} crash.bin\n```",
+                self.target
+            );
+        }
+        out
+    }
+}
+
+/// The part of a crashed fuzz target's output that describes the crash: from
+/// the panic or sanitizer error to its summary, or all of it if there's no
+/// such part.
+pub fn backtrace(output: &str) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.contains("panicked at") || line.contains("ERROR: "));
+    let lines = match start {
+        Some(start) => {
+            let rest = &lines[start..];
+            let end = rest
+                .iter()
+                .position(|line| line.starts_with("SUMMARY: "))
+                .map_or(rest.len(), |end| end + 1);
+            &rest[..end]
+        }
+        None => &lines[..],
+    };
+    let mut backtrace: Vec<&str> = lines.iter().take(MAX_BACKTRACE_LINES).copied().collect();
+    if lines.len() > MAX_BACKTRACE_LINES {
+        backtrace.push("...");
+    }
+    backtrace.join("\n")
+}
+
+/// Encode bytes as standard, padded base64, broken into lines of at most
+/// `width` characters.
+pub fn base64(bytes: &[u8], width: usize) -> String {
+    let encoded = crate::utils::encode_base64(bytes);
+    let mut wrapped = String::new();
+    for (i, c) in encoded.chars().enumerate() {
+        if i > 0 && i % width == 0 {
+            wrapped.push('\n');
+        }
+        wrapped.push(c);
+    }
+    wrapped
+}
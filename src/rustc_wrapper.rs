@@ -0,0 +1,72 @@
+//! Instrumenting only some crates for coverage, for `--coverage-crates`.
+//!
+//! Cargo passes the same rustflags to every crate it builds for the target,
+//! so `cargo fuzz` makes itself the `RUSTC_WRAPPER` for such builds. It's
+//! then run in place of rustc, and drops the sanitizer coverage pass from the
+//! flags of every crate that isn't listed, before running rustc (or the
+//! wrapper that was configured before) with the rest. The other sanitizer
+//! coverage flags only configure that pass, so they're left alone.
+
+use std::env;
+use std::ffi::OsString;
+use std::process::Command;
+
+/// The crates to instrument, comma-separated, which also tells `cargo fuzz`
+/// that it's been run as the wrapper.
+pub const CRATES_ENV: &str = "CARGO_FUZZ_COVERAGE_CRATES";
+
+/// The `RUSTC_WRAPPER` from before `cargo fuzz` took its place, if any.
+pub const INNER_WRAPPER_ENV: &str = "CARGO_FUZZ_INNER_RUSTC_WRAPPER";
+
+/// The names of the crates in a comma-separated list, as rustc spells them.
+pub fn parse_crates(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|name| name.trim().replace('-', "_"))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Whether `cargo fuzz` was run by Cargo as the wrapper of rustc, rather
+/// than as `cargo fuzz`.
+pub fn is_wrapping() -> bool {
+    env::var_os(CRATES_ENV).is_some() && env::args_os().nth(1).is_some_and(|arg| arg != "fuzz")
+}
+
+/// Run rustc with the arguments that Cargo gave the wrapper, and return its
+/// exit code.
+pub fn run() -> i32 {
+    let mut args = env::args_os().skip(1);
+    let rustc = args.next().expect("Cargo passes rustc to its wrapper");
+    let mut args: Vec<OsString> = args.collect();
+    let crates = parse_crates(&env::var(CRATES_ENV).unwrap_or_default());
+    let name = args
+        .windows(2)
+        .find(|pair| pair[0] == "--crate-name")
+        .map(|pair| pair[1].to_string_lossy().into_owned());
+    // Cargo asks rustc about itself without a crate.
+    if name.is_some_and(|name| !crates.contains(&name)) {
+        args.retain(|arg| !is_coverage_pass(arg));
+    }
+
+    let mut cmd = match env::var_os(INNER_WRAPPER_ENV).filter(|w| !w.is_empty()) {
+        Some(wrapper) => {
+            let mut cmd = Command::new(wrapper);
+            cmd.arg(rustc);
+            cmd
+        }
+        None => Command::new(rustc),
+    };
+    cmd.args(args).env_remove(CRATES_ENV);
+    match cmd.status() {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            eprintln!("error: failed to run {:?}: {}", cmd, e);
+            1
+        }
+    }
+}
+
+fn is_coverage_pass(arg: &OsString) -> bool {
+    arg.to_str()
+        .is_some_and(|arg| arg.starts_with("-Cpasses=sancov"))
+}
@@ -0,0 +1,139 @@
+//! Rendering crashes as SARIF, the format GitHub code scanning understands.
+//!
+//! Each unique crash becomes one result, located at the innermost source
+//! location in the repository, and fingerprinted with its crash hash so that
+//! code scanning can track it across runs.
+
+use crate::crash::Crash;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A crash found by a fuzz target.
+pub struct Finding<'a> {
+    pub target: &'a str,
+    pub crash: &'a Crash,
+    pub artifact: &'a Path,
+}
+
+/// Render findings as a SARIF 2.1.0 log. Source locations under `repo_root`
+/// become paths relative to it, and all other locations are left out.
+pub fn render(findings: &[Finding], repo_root: &Path) -> String {
+    let mut rules = BTreeMap::new();
+    let mut results = Vec::new();
+    for finding in findings {
+        let crash = finding.crash;
+        rules.entry(&crash.kind).or_insert_with(|| {
+            json!({
+                "id": crash.kind,
+                "name": crash.kind,
+                "shortDescription": { "text": format!("Fuzzing crash: {}", crash.kind) },
+                "defaultConfiguration": { "level": "error" },
+            })
+        });
+
+        // The panic location, if any, is where the bug was noticed. Otherwise
+        // it's the innermost frame that's part of the repository.
+        let locations: Vec<Value> = crash
+            .location
+            .iter()
+            .chain(crash.frames.iter().filter_map(|f| f.location.as_ref()))
+            .filter_map(|l| physical_location(l, repo_root))
+            .take(1)
+            .collect();
+        let frames: Vec<Value> = crash
+            .frames
+            .iter()
+            .map(|frame| {
+                let mut location = json!({ "logicalLocations": [{ "name": frame.function }] });
+                if let Some(physical) = frame
+                    .location
+                    .as_ref()
+                    .and_then(|l| physical_location(l, repo_root))
+                {
+                    location["physicalLocation"] = physical["physicalLocation"].clone();
+                }
+                json!({ "location": location })
+            })
+            .collect();
+
+        let mut headline = crash.kind.clone();
+        if let Some(message) = &crash.message {
+            headline.push_str(": ");
+            headline.push_str(message);
+        }
+        let mut result = json!({
+            "ruleId": crash.kind,
+            "level": "error",
+            "message": {
+                "text": format!(
+                    "Fuzz target `{}` crashed with {}\n\nReproduce with `cargo fuzz run {} {}`",
+                    finding.target,
+                    headline,
+                    finding.target,
+                    finding.artifact.display()
+                ),
+            },
+            "locations": locations,
+            "partialFingerprints": { "crashHash/v1": crash.hash() },
+        });
+        if !frames.is_empty() {
+            result["stacks"] = json!([{ "frames": frames }]);
+        }
+        results.push(result);
+    }
+
+    let sarif = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cargo-fuzz",
+                    "version": option_env!("CARGO_PKG_VERSION").unwrap_or("0.0.0"),
+                    "informationUri": "https://github.com/rust-fuzz/cargo-fuzz",
+                    "rules": rules.into_values().collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&sarif).expect("SARIF always serializes")
+}
+
+/// Turn a location like `/repo/src/lib.rs:10:5` into a SARIF physical location,
+/// if it's inside the repository.
+fn physical_location(location: &str, repo_root: &Path) -> Option<Value> {
+    let mut parts = location.rsplitn(3, ':');
+    let (path, line, column) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(column), Some(line), Some(path)) if line.parse::<u64>().is_ok() => {
+            (path, line.parse::<u64>().ok()?, column.parse::<u64>().ok())
+        }
+        (Some(line), Some(path), None) => (path, line.parse().ok()?, None),
+        _ => return None,
+    };
+    let path = PathBuf::from(path);
+    let relative = if path.is_absolute() {
+        path.strip_prefix(repo_root).ok()?.to_path_buf()
+    } else if repo_root.join(&path).exists() {
+        path
+    } else {
+        return None;
+    };
+    let uri = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut region = json!({ "startLine": line });
+    if let Some(column) = column {
+        region["startColumn"] = json!(column);
+    }
+    Some(json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": uri, "uriBaseId": "%SRCROOT%" },
+            "region": region,
+        },
+    }))
+}
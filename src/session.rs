@@ -0,0 +1,58 @@
+//! Saved fuzzing sessions, for `cargo fuzz session save` and `resume`.
+//!
+//! A session is a directory in `fuzz/sessions/<name>` holding a copy of a fuzz
+//! target's corpus and a `session.json` with everything else that determines
+//! how the fuzzer proceeds: the build settings, the libFuzzer arguments, the
+//! random seed, and a hash of the fuzz target's binary. Resuming the same
+//! session with the same binary replays the same inputs in the same order.
+
+use crate::config::TargetConfig;
+use crate::crash::fnv1a;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The contents of a session's `session.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Session {
+    pub target: String,
+    /// When the session was saved, in seconds since the Unix epoch.
+    pub saved: u64,
+    pub triple: String,
+    /// The build settings and libFuzzer arguments, like those of a target in
+    /// `fuzz.toml`.
+    pub settings: TargetConfig,
+    /// The seed for libFuzzer's random number generator.
+    pub seed: u32,
+    /// A hash of the fuzz target's binary when the session was saved.
+    pub binary_hash: String,
+}
+
+impl Session {
+    /// The name of the file in a session directory that holds the session.
+    pub const FILE: &'static str = "session.json";
+
+    /// Load the session saved in `dir`.
+    pub fn load(dir: &Path) -> Result<Session> {
+        let path = dir.join(Session::FILE);
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&data)
+            .with_context(|| format!("could not decode the session at {}", path.display()))
+    }
+
+    /// Save the session to `dir`, which must already exist.
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(Session::FILE);
+        let data = serde_json::to_string_pretty(self).expect("sessions always serialize");
+        fs::write(&path, data + "\n").with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+/// A hash of the contents of a fuzz target's binary.
+pub fn binary_hash(binary: &Path) -> Result<String> {
+    let data = fs::read(binary).with_context(|| format!("failed to read {}", binary.display()))?;
+    Ok(format!("{:016x}", fnv1a(&data)))
+}
@@ -0,0 +1,505 @@
+//! Generating the fuzz target that `cargo fuzz add --for` writes for a
+//! function of the fuzzed crate.
+//!
+//! The function is found by parsing the crate's sources from its library
+//! root, following `mod` declarations to the module given in its path. A
+//! function that takes only `&[u8]` or `&str` is called with the fuzzer's raw
+//! input; any other arguments are built with their `Arbitrary`
+//! implementations. Types named in the signature are qualified with the
+//! paths they're imported from, or the module the function is in, so that
+//! they resolve from the fuzz crate.
+
+use anyhow::{anyhow, bail, Context, Result};
+use quote::ToTokens;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use syn::{
+    FnArg, GenericArgument, GenericParam, Item, Pat, PathArguments, ReturnType, Type, UseTree,
+};
+
+/// The types that are in scope everywhere, and so are never qualified.
+const PRELUDE: &[&str] = &[
+    "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+    "i128", "isize", "f32", "f64", "String", "Vec", "Option", "Result", "Box",
+];
+
+/// A public function of the fuzzed crate.
+pub struct Function {
+    /// The function's path, starting with the crate's name.
+    pub path: String,
+    /// The path of the module the function is in.
+    module: String,
+    /// What the module's `use` declarations import, by the name they import
+    /// it as.
+    imports: BTreeMap<String, String>,
+    sig: syn::Signature,
+}
+
+/// The parts of a fuzz target that calls a function.
+pub struct Harness {
+    /// What the fuzz target's closure takes, like `data: &[u8]`.
+    pub input: String,
+    /// The body of the closure, which calls the function.
+    pub body: String,
+    /// How the function's arguments are made, for a comment in the target.
+    pub description: &'static str,
+    /// Whether the arguments are built with `Arbitrary`.
+    pub arbitrary: bool,
+    /// Whether the closure returns `Corpus` to reject invalid inputs.
+    pub rejects: bool,
+}
+
+/// Find the public function at `path`, like `my_crate::header::parse`, or
+/// `header::parse` relative to the crate root, in the crate named
+/// `crate_name` whose library root is `lib`.
+pub fn find(lib: &Path, crate_name: &str, path: &str) -> Result<Function> {
+    let mut segments: Vec<&str> = path.split("::").collect();
+    if segments.len() > 1 && (segments[0] == crate_name || segments[0] == "crate") {
+        segments.remove(0);
+    }
+    let (name, modules) = match segments.split_last() {
+        Some((name, modules)) if !name.is_empty() => (*name, modules),
+        _ => bail!("`{}` isn't the path of a function", path),
+    };
+
+    let mut module = crate_name.to_string();
+    let mut items = parse(lib)?;
+    let mut dir = lib.parent().unwrap().to_path_buf();
+    for name in modules {
+        let declaration = items
+            .into_iter()
+            .find_map(|item| match item {
+                Item::Mod(declaration) if declaration.ident == name => Some(declaration),
+                _ => None,
+            })
+            .ok_or_else(|| anyhow!("could not find the module `{}` in `{}`", name, module))?;
+        items = match declaration.content {
+            Some((_, items)) => items,
+            None => {
+                let file = dir.join(format!("{}.rs", name));
+                let file = if file.is_file() {
+                    file
+                } else {
+                    dir.join(name).join("mod.rs")
+                };
+                parse(&file)?
+            }
+        };
+        dir = dir.join(name);
+        module = format!("{}::{}", module, name);
+    }
+
+    let mut imports = BTreeMap::new();
+    for item in &items {
+        if let Item::Use(declaration) = item {
+            collect_imports(&declaration.tree, &[], &module, &mut imports);
+        }
+    }
+    let function = items
+        .into_iter()
+        .find_map(|item| match item {
+            Item::Fn(function) if function.sig.ident == name => Some(function),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "could not find a function named `{}` in `{}`; give the path of the module \
+                 that defines it, not one that re-exports it",
+                name,
+                module
+            )
+        })?;
+    let path = format!("{}::{}", module, name);
+    if !matches!(function.vis, syn::Visibility::Public(_)) {
+        bail!("`{}` isn't public, so a fuzz target can't call it", path);
+    }
+    Ok(Function {
+        path,
+        module,
+        imports,
+        sig: function.sig,
+    })
+}
+
+/// Generate the fuzz target's closure for `function`. With `reject_invalid`,
+/// the closure returns `Corpus`, and rejects the inputs that aren't valid
+/// UTF-8 for a `&str`, or that the function returns an `Err` or `None` for.
+pub fn harness(function: &Function, reject_invalid: bool) -> Result<Harness> {
+    let sig = &function.sig;
+    let path = &function.path;
+    if sig.asyncness.is_some() {
+        bail!(
+            "`{}` is async, so a fuzz target can't call it directly",
+            path
+        );
+    }
+    if sig.unsafety.is_some() {
+        bail!(
+            "`{}` is unsafe, so a fuzz target can't call it safely",
+            path
+        );
+    }
+    if sig
+        .generics
+        .params
+        .iter()
+        .any(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
+        bail!(
+            "`{}` is generic; add a fuzz target by hand to pick its types",
+            path
+        );
+    }
+
+    let mut arguments = Vec::new();
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let input = match input {
+            FnArg::Typed(input) => input,
+            FnArg::Receiver(_) => bail!("`{}` is a method, not a function", path),
+        };
+        let name = match &*input.pat {
+            Pat::Ident(pat) => pat.ident.to_string(),
+            _ => format!("arg{}", i),
+        };
+        arguments.push((name, &*input.ty));
+    }
+    if arguments.is_empty() {
+        bail!("`{}` takes no arguments, so there's nothing to fuzz", path);
+    }
+    let fallible = fallible_output(sig);
+    let takes_str = matches!(arguments.as_slice(), [(_, ty)] if is_reference_to(ty, "str"));
+    if reject_invalid && fallible.is_none() && !takes_str {
+        bail!(
+            "`{}` returns neither a `Result` nor an `Option`, so there's no telling which \
+             inputs to reject",
+            path
+        );
+    }
+    // The end of the closure's body, which calls the function.
+    let call = |args: &str| match fallible.filter(|_| reject_invalid) {
+        Some((valid, invalid)) => format!(
+            "    match {}({}) {{\n        {}(_) => Corpus::Keep,\n        \
+             {} => Corpus::Reject,\n    }}",
+            path, args, valid, invalid
+        ),
+        None if reject_invalid => format!("    let _ = {}({});\n    Corpus::Keep", path, args),
+        None => format!("    let _ = {}({});", path, args),
+    };
+
+    // Raw bytes and strings are passed straight through.
+    if let [(name, ty)] = arguments.as_slice() {
+        if is_reference_to(ty, "[u8]") {
+            return Ok(Harness {
+                input: format!("{}: &[u8]", name),
+                body: call(name),
+                description: "the fuzzer's raw input",
+                arbitrary: false,
+                rejects: reject_invalid,
+            });
+        }
+        if is_reference_to(ty, "str") {
+            let body = if reject_invalid {
+                format!(
+                    "    let {0} = match std::str::from_utf8(data) {{\n        \
+                     Ok({0}) => {0},\n        Err(_) => return Corpus::Reject,\n    }};\n{1}",
+                    name,
+                    call(name)
+                )
+            } else {
+                format!(
+                    "    if let Ok({0}) = std::str::from_utf8(data) {{\n        \
+                     let _ = {1}({0});\n    }}",
+                    name, path
+                )
+            };
+            return Ok(Harness {
+                input: "data: &[u8]".to_string(),
+                body,
+                description: "each input that's valid UTF-8",
+                arbitrary: false,
+                rejects: reject_invalid,
+            });
+        }
+    }
+
+    // Anything else is built with `Arbitrary`, as an owned value that's
+    // borrowed for arguments taken by reference.
+    let mut types = Vec::new();
+    let mut passed = Vec::new();
+    for (name, ty) in &arguments {
+        let (owned, borrow) = match ty {
+            Type::Reference(reference) => {
+                let owned = match &*reference.elem {
+                    Type::Slice(slice) => format!("Vec<{}>", function.type_string(&slice.elem)?),
+                    elem if is_named(elem, "str") => "String".to_string(),
+                    elem => function.type_string(elem)?,
+                };
+                let borrow = if reference.mutability.is_some() {
+                    "&mut "
+                } else {
+                    "&"
+                };
+                (owned, borrow)
+            }
+            ty => (function.type_string(ty)?, ""),
+        };
+        types.push(owned);
+        passed.push(format!("{}{}", borrow, name));
+    }
+    // The fuzz target's closure can't take its input mutably, so arguments
+    // taken by `&mut` are moved into a mutable binding first.
+    let (input, body) = if let [(name, _)] = arguments.as_slice() {
+        let input = if passed[0].starts_with("&mut ") {
+            format!("input: {}", types[0])
+        } else {
+            format!("{}: {}", name, types[0])
+        };
+        let mut body = String::new();
+        if passed[0].starts_with("&mut ") {
+            body.push_str(&format!("    let mut {} = input;\n", name));
+        }
+        body.push_str(&call(&passed[0]));
+        (input, body)
+    } else {
+        let names: Vec<String> = arguments
+            .iter()
+            .zip(&passed)
+            .map(|((name, _), passed)| {
+                if passed.starts_with("&mut ") {
+                    format!("mut {}", name)
+                } else {
+                    name.clone()
+                }
+            })
+            .collect();
+        (
+            format!("input: ({})", types.join(", ")),
+            format!(
+                "    let ({}) = input;\n{}",
+                names.join(", "),
+                call(&passed.join(", "))
+            ),
+        )
+    };
+    Ok(Harness {
+        input,
+        body,
+        description: "arguments built by their `Arbitrary` implementations",
+        arbitrary: true,
+        rejects: reject_invalid,
+    })
+}
+
+/// The patterns of the valid and the invalid values of what `sig` returns, if
+/// it's a `Result` or an `Option`.
+fn fallible_output(sig: &syn::Signature) -> Option<(&'static str, &'static str)> {
+    let ty = match &sig.output {
+        ReturnType::Type(_, ty) => ty,
+        ReturnType::Default => return None,
+    };
+    let name = match &**ty {
+        Type::Path(ty) if ty.qself.is_none() => ty.path.segments.last()?.ident.to_string(),
+        _ => return None,
+    };
+    match name.as_str() {
+        "Result" => Some(("Ok", "Err(_)")),
+        "Option" => Some(("Some", "None")),
+        _ => None,
+    }
+}
+
+impl Function {
+    /// How `ty` is written in the fuzz crate, with the types it names
+    /// qualified so that they resolve there.
+    fn type_string(&self, ty: &Type) -> Result<String> {
+        Ok(match ty {
+            Type::Path(ty) if ty.qself.is_none() => {
+                let mut segments = Vec::new();
+                for segment in &ty.path.segments {
+                    let arguments = match &segment.arguments {
+                        PathArguments::None => String::new(),
+                        PathArguments::AngleBracketed(arguments) => {
+                            let arguments = arguments
+                                .args
+                                .iter()
+                                .map(|argument| match argument {
+                                    GenericArgument::Type(ty) => self.type_string(ty),
+                                    GenericArgument::Lifetime(_) => Ok("'_".to_string()),
+                                    argument => Ok(tokens(argument)),
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            format!("<{}>", arguments.join(", "))
+                        }
+                        PathArguments::Parenthesized(_) => tokens(&segment.arguments),
+                    };
+                    segments.push(format!("{}{}", segment.ident, arguments));
+                }
+                let first = ty.path.segments[0].ident.to_string();
+                let rest = segments[1..].iter().map(|s| format!("::{}", s));
+                if ty.path.leading_colon.is_some() {
+                    format!("::{}", segments.join("::"))
+                } else if let Some(import) = self.imports.get(&first) {
+                    let args = &segments[0][first.len()..];
+                    format!("{}{}{}", import, args, rest.collect::<String>())
+                } else if let Some(module) = resolve(&first, &self.module) {
+                    format!("{}{}", module, rest.collect::<String>())
+                } else if segments.len() == 1 && !PRELUDE.contains(&first.as_str()) {
+                    format!("{}::{}", self.module, segments[0])
+                } else {
+                    segments.join("::")
+                }
+            }
+            Type::Reference(reference) => {
+                let mutability = if reference.mutability.is_some() {
+                    "mut "
+                } else {
+                    ""
+                };
+                format!("&{}{}", mutability, self.type_string(&reference.elem)?)
+            }
+            Type::Slice(slice) => format!("[{}]", self.type_string(&slice.elem)?),
+            Type::Array(array) => format!(
+                "[{}; {}]",
+                self.type_string(&array.elem)?,
+                tokens(&array.len)
+            ),
+            Type::Tuple(tuple) => {
+                let elems = tuple
+                    .elems
+                    .iter()
+                    .map(|elem| self.type_string(elem))
+                    .collect::<Result<Vec<_>>>()?;
+                match elems.as_slice() {
+                    [elem] => format!("({},)", elem),
+                    elems => format!("({})", elems.join(", ")),
+                }
+            }
+            Type::Paren(paren) => self.type_string(&paren.elem)?,
+            Type::ImplTrait(_) => bail!(
+                "`{}` takes an `impl Trait` argument; add a fuzz target by hand to pick its type",
+                self.path
+            ),
+            ty => bail!(
+                "can't generate a fuzz target for `{}`, which takes a `{}`",
+                self.path,
+                tokens(ty)
+            ),
+        })
+    }
+}
+
+/// The items of the source file at `path`.
+pub fn parse(path: &Path) -> Result<Vec<Item>> {
+    let source =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let file = syn::parse_file(&unwrap_unsafe_attributes(&source))
+        .map_err(|e| anyhow!("{}", e))
+        .with_context(|| format!("could not parse {}", path.display()))?;
+    Ok(file.items)
+}
+
+/// `source` with the 2024 edition's `#[unsafe(no_mangle)]` and the like
+/// written as `#[no_mangle]`, which is all the version of `syn` we use can
+/// parse. Lines stay where they were.
+fn unwrap_unsafe_attributes(source: &str) -> String {
+    const UNSAFE: &str = "#[unsafe(";
+    let mut out = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(start) = rest.find(UNSAFE) {
+        out.push_str(&rest[..start]);
+        out.push_str("#[");
+        rest = &rest[start + UNSAFE.len()..];
+        let mut depth = 1;
+        let end = rest.find(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        });
+        match end {
+            Some(end) if rest[end + 1..].starts_with(']') => {
+                out.push_str(&rest[..end]);
+                rest = &rest[end + 1..];
+            }
+            _ => out.push_str("unsafe("),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Record the full paths of the names that a `use` declaration in `module`
+/// imports.
+fn collect_imports(
+    tree: &UseTree,
+    prefix: &[String],
+    module: &str,
+    imports: &mut BTreeMap<String, String>,
+) {
+    let path = |last: &str| {
+        let mut segments = prefix.to_vec();
+        segments.push(last.to_string());
+        if let Some(first) = resolve(&segments[0], module) {
+            segments[0] = first;
+        }
+        segments.join("::")
+    };
+    match tree {
+        UseTree::Path(tree) => {
+            let mut prefix = prefix.to_vec();
+            prefix.push(tree.ident.to_string());
+            collect_imports(&tree.tree, &prefix, module, imports);
+        }
+        UseTree::Name(tree) => {
+            imports.insert(tree.ident.to_string(), path(&tree.ident.to_string()));
+        }
+        UseTree::Rename(tree) => {
+            imports.insert(tree.rename.to_string(), path(&tree.ident.to_string()));
+        }
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                collect_imports(tree, prefix, module, imports);
+            }
+        }
+        UseTree::Glob(_) => {}
+    }
+}
+
+/// The module that a path starting with `crate`, `self`, or `super` in
+/// `module` starts from.
+fn resolve(first: &str, module: &str) -> Option<String> {
+    match first {
+        "crate" => Some(module.split("::").next().unwrap().to_string()),
+        "self" => Some(module.to_string()),
+        "super" => module
+            .rsplit_once("::")
+            .map(|(parent, _)| parent.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether `ty` is a shared reference to the type written as `target`.
+fn is_reference_to(ty: &Type, target: &str) -> bool {
+    match ty {
+        Type::Reference(reference) if reference.mutability.is_none() => match &*reference.elem {
+            Type::Slice(slice) => target == "[u8]" && is_named(&slice.elem, "u8"),
+            elem => is_named(elem, target),
+        },
+        _ => false,
+    }
+}
+
+/// Whether `ty` is the plain type `name`.
+fn is_named(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(ty) => ty.qself.is_none() && ty.path.is_ident(name),
+        _ => false,
+    }
+}
+
+fn tokens(tokens: impl ToTokens) -> String {
+    tokens.to_token_stream().to_string()
+}
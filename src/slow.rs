@@ -0,0 +1,81 @@
+//! Inputs that are slow to run, saved by `cargo fuzz run --slow-threshold-ms`
+//! in `fuzz/slow/<target>/` and timed again by `cargo fuzz bench`.
+//!
+//! How long each input took when it was saved, or when it was last timed with
+//! `cargo fuzz bench --update`, is kept in `fuzz/slow/<target>.json`, so that
+//! `bench` can show whether an optimization made them faster.
+
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// The milliseconds each input took to run, by file name.
+pub type Timings = BTreeMap<String, u64>;
+
+/// Load the recorded timings at `path`, or none if there's no such file.
+pub fn load(path: &Path) -> Result<Timings> {
+    if !path.exists() {
+        return Ok(Timings::new());
+    }
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("could not decode {}", path.display()))
+}
+
+/// Save timings to `path`, replacing any there were.
+pub fn save(path: &Path, timings: &Timings) -> Result<()> {
+    let data = serde_json::to_string_pretty(timings).expect("timings always serialize");
+    fs::write(path, data + "\n").with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Render a table of the recorded and current timings of each input, slowest
+/// first, with the totals of the inputs that have both.
+pub fn render(recorded: &Timings, current: &Timings) -> String {
+    let mut inputs: Vec<&String> = recorded
+        .keys()
+        .chain(current.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    inputs.sort_by_key(|name| Reverse(current.get(*name).or(recorded.get(*name))));
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<48} {:>10} {:>10} {:>8}",
+        "input", "recorded", "now", "change"
+    );
+    let (mut total_recorded, mut total_current) = (0, 0);
+    for name in inputs {
+        let (before, now) = (recorded.get(name).copied(), current.get(name).copied());
+        if let (Some(before), Some(now)) = (before, now) {
+            total_recorded += before;
+            total_current += now;
+        }
+        render_row(&mut out, name, before, now);
+    }
+    render_row(&mut out, "total", Some(total_recorded), Some(total_current));
+    out
+}
+
+fn render_row(out: &mut String, name: &str, before: Option<u64>, now: Option<u64>) {
+    let ms = |ms: Option<u64>| ms.map_or("-".to_string(), |ms| format!("{} ms", ms));
+    let change = match (before, now) {
+        (Some(0), Some(_)) | (None, _) | (_, None) => "-".to_string(),
+        (Some(before), Some(now)) => format!(
+            "{:+.1}%",
+            (now as f64 - before as f64) / before as f64 * 100.0
+        ),
+    };
+    let _ = writeln!(
+        out,
+        "{:<48} {:>10} {:>10} {:>8}",
+        name,
+        ms(before),
+        ms(now),
+        change
+    );
+}
@@ -0,0 +1,146 @@
+//! Suggesting how to split a fuzz target that feeds several unrelated entry
+//! points of the fuzzed crate, for `cargo fuzz suggest-split`.
+//!
+//! The entry points are the crate's public functions. Each input is replayed
+//! on its own through a coverage build to find the ones it reaches, leaving
+//! out those that another one it reaches calls, going by the crate's sources,
+//! since they're reached through it. Entry points that some input reaches
+//! together belong together; each group of them that no input ties to
+//! another can be fuzzed by a target of its own, seeded with the inputs that
+//! reach it.
+
+use crate::api::{ApiItem, Kind};
+use crate::coverage::Regions;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+/// Entry points that are reached together, and the inputs that reach them.
+#[derive(Debug)]
+pub struct Group {
+    /// The suggested name of the fuzz target for the group.
+    pub name: String,
+    pub entry_points: BTreeSet<String>,
+    pub inputs: Vec<PathBuf>,
+}
+
+/// The public functions of `api` that a covered region is in.
+pub fn reached(api: &[ApiItem], regions: &Regions) -> BTreeSet<String> {
+    api.iter()
+        .filter(|item| item.kind == Kind::Function)
+        .filter(|item| {
+            regions.iter().any(|(region, &covered)| {
+                covered
+                    && item.spans.iter().any(|(file, (start, end))| {
+                        region.file == *file && region.start.0 >= *start && region.end.0 <= *end
+                    })
+            })
+        })
+        .map(|item| item.path.clone())
+        .collect()
+}
+
+/// The functions among `reached` that none of the others call, directly or
+/// through other public functions of `api`.
+pub fn entry_points(api: &[ApiItem], reached: &BTreeSet<String>) -> BTreeSet<String> {
+    let functions: BTreeMap<&str, &ApiItem> = api
+        .iter()
+        .filter(|item| item.kind == Kind::Function)
+        .map(|item| (item.path.as_str(), item))
+        .collect();
+    let callees = |item: &ApiItem| -> Vec<&str> {
+        functions
+            .keys()
+            .copied()
+            .filter(|path| {
+                *path != item.path && item.calls.iter().any(|call| ends_with(path, call))
+            })
+            .collect()
+    };
+
+    let mut called = BTreeSet::new();
+    for path in reached {
+        let mut stack = match functions.get(path.as_str()) {
+            Some(item) => callees(item),
+            None => continue,
+        };
+        let mut seen = BTreeSet::new();
+        while let Some(callee) = stack.pop() {
+            if callee != path && seen.insert(callee) {
+                stack.extend(callees(functions[callee]));
+            }
+        }
+        called.extend(seen);
+    }
+    reached
+        .iter()
+        .filter(|path| !called.contains(path.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Whether the last segment of `path` is `name`.
+fn ends_with(path: &str, name: &str) -> bool {
+    path.rsplit("::").next() == Some(name)
+}
+
+/// Group the entry points that some input reaches together, and name a fuzz
+/// target after `target` for each group. Returns the groups, the largest
+/// first, and the inputs that reach no entry point.
+pub fn group(target: &str, inputs: Vec<(PathBuf, BTreeSet<String>)>) -> (Vec<Group>, Vec<PathBuf>) {
+    let mut groups: Vec<(BTreeSet<String>, Vec<PathBuf>)> = Vec::new();
+    let mut unreached = Vec::new();
+    for (input, entry_points) in inputs {
+        if entry_points.is_empty() {
+            unreached.push(input);
+            continue;
+        }
+        let mut merged = (entry_points, vec![input]);
+        groups.retain_mut(|(group, inputs)| {
+            if group.is_disjoint(&merged.0) {
+                return true;
+            }
+            merged.0.append(group);
+            merged.1.append(inputs);
+            false
+        });
+        groups.push(merged);
+    }
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+
+    let mut names = BTreeSet::new();
+    let groups = groups
+        .into_iter()
+        .map(|(entry_points, mut inputs)| {
+            let first = entry_points.iter().next().unwrap();
+            let base = format!("{}_{}", target, first.rsplit("::").next().unwrap());
+            let mut name = base.clone();
+            for i in 2.. {
+                if names.insert(name.clone()) {
+                    break;
+                }
+                name = format!("{}_{}", base, i);
+            }
+            inputs.sort();
+            Group {
+                name,
+                entry_points,
+                inputs,
+            }
+        })
+        .collect();
+    unreached.sort();
+    (groups, unreached)
+}
+
+/// Render the groups, with the entry points of each.
+pub fn render(groups: &[Group]) -> String {
+    let mut out = String::new();
+    for group in groups {
+        let _ = writeln!(out, "{} ({} input(s))", group.name, group.inputs.len());
+        for entry_point in &group.entry_points {
+            let _ = writeln!(out, "    {}", entry_point);
+        }
+    }
+    out
+}
@@ -0,0 +1,310 @@
+//! Per-run statistics, recorded by `cargo fuzz run` in
+//! `fuzz/stats/<target>.jsonl` and summarized by `cargo fuzz stats`.
+//!
+//! Each line of a stats file is one run of the target, so comparing runs
+//! shows whether a change made the target slower or less covered.
+
+use crate::libfuzzer;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process;
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// What happened during one run of a fuzz target.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RunRecord {
+    /// The ID of the `cargo fuzz` invocation that ran the fuzzer, which the
+    /// provenance of the corpus inputs it found refers to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// When the run started, in seconds since the Unix epoch.
+    pub started: u64,
+    pub duration_secs: u64,
+    pub execs: u64,
+    pub execs_per_sec: Option<u64>,
+    pub corpus_entries: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    /// Covered edges at the end of the run.
+    pub coverage: Option<u64>,
+    /// Edges covered by the end of the run that weren't covered once the
+    /// initial corpus was loaded.
+    pub new_coverage: Option<u64>,
+    pub crashes: u64,
+    /// The resource limits the fuzzer ran with.
+    #[serde(flatten)]
+    pub limits: Limits,
+}
+
+/// The resource limits of a run that `cargo fuzz` set, from `--timeout`,
+/// `--rss-limit`, and `--malloc-limit` or the config file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Limits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_limit_mb: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub malloc_limit_mb: Option<u64>,
+}
+
+/// Builds a `RunRecord` from a fuzzer's output as it runs.
+#[derive(Debug)]
+pub struct RunRecorder {
+    started: SystemTime,
+    start: Instant,
+    initial_coverage: Option<u64>,
+    last: Option<libfuzzer::Status>,
+    crashes: u64,
+}
+
+impl RunRecorder {
+    pub fn new() -> RunRecorder {
+        RunRecorder {
+            started: SystemTime::now(),
+            start: Instant::now(),
+            initial_coverage: None,
+            last: None,
+            crashes: 0,
+        }
+    }
+
+    /// Update the record with a line of the fuzzer's output.
+    pub fn observe(&mut self, line: &str) {
+        if let Some(artifact) = libfuzzer::parse_written_artifact(line) {
+            let slow = artifact
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(libfuzzer::SLOW_UNIT_PREFIX));
+            self.crashes += !slow as u64;
+            return;
+        }
+        let status = match libfuzzer::Status::parse(line) {
+            Some(status) => status,
+            None => return,
+        };
+        // In `-fork` mode there is no `INITED` line, so fall back to the
+        // first status line.
+        if status.event == "INITED" || self.initial_coverage.is_none() {
+            self.initial_coverage = status.coverage;
+        }
+        self.last = Some(status);
+    }
+
+    /// The record of the run, or `None` if the fuzzer never reported its
+    /// status, like when it failed to start or isn't libFuzzer.
+    pub fn finish(self) -> Option<RunRecord> {
+        let last = self.last?;
+        let duration = self.start.elapsed();
+        let execs_per_sec = last
+            .execs_per_sec
+            .or_else(|| last.execs.checked_div(duration.as_secs()));
+        Some(RunRecord {
+            id: Some(run_id().to_string()),
+            started: self
+                .started
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            duration_secs: duration.as_secs(),
+            execs: last.execs,
+            execs_per_sec,
+            corpus_entries: last.corpus_entries,
+            corpus_bytes: last.corpus_bytes,
+            coverage: last.coverage,
+            new_coverage: last
+                .coverage
+                .zip(self.initial_coverage)
+                .map(|(end, start)| end.saturating_sub(start)),
+            crashes: self.crashes,
+            limits: Limits::default(),
+        })
+    }
+}
+
+/// The ID of this invocation of `cargo fuzz`, shared by every run it starts:
+/// when it started, in seconds since the Unix epoch, and its process ID.
+pub fn run_id() -> &'static str {
+    static ID: OnceLock<String> = OnceLock::new();
+    ID.get_or_init(|| {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        format!("{}-{}", started, process::id())
+    })
+}
+
+/// Append a record to a stats file, creating it if needed.
+pub fn append(path: &Path, record: &RunRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("could not make a directory at {}", parent.display()))?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let line = serde_json::to_string(record).expect("records always serialize");
+    writeln!(file, "{}", line).with_context(|| format!("failed to write to {}", path.display()))
+}
+
+/// Load every record in a stats file, oldest first.
+pub fn load(path: &Path) -> Result<Vec<RunRecord>> {
+    let data =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("could not decode line {} of {}", i + 1, path.display()))
+        })
+        .collect()
+}
+
+/// One of the values of a record that `render` compares across runs.
+type Metric = fn(&RunRecord) -> Option<u64>;
+
+/// Render records as a table, followed by how the latest run compares with
+/// the average of the ones before it.
+pub fn render(records: &[RunRecord]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "{:<20} {:>9} {:>10} {:>14} {:>9} {:>6} {:>8}",
+        "started (UTC)", "duration", "execs/s", "corpus", "coverage", "new", "crashes"
+    );
+    for record in records {
+        let corpus = match (record.corpus_entries, record.corpus_bytes) {
+            (Some(entries), Some(bytes)) => format!("{} ({})", entries, format_size(bytes)),
+            (Some(entries), None) => entries.to_string(),
+            _ => "-".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "{:<20} {:>9} {:>10} {:>14} {:>9} {:>6} {:>8}",
+            format_timestamp(record.started),
+            format_duration(record.duration_secs),
+            or_dash(record.execs_per_sec),
+            corpus,
+            or_dash(record.coverage),
+            record
+                .new_coverage
+                .map_or("-".to_string(), |n| format!("+{}", n)),
+            record.crashes,
+        );
+    }
+
+    if let Some((latest, previous)) = records.split_last().filter(|(_, p)| !p.is_empty()) {
+        let _ = writeln!(
+            out,
+            "\nThe latest run, compared to the average of the {} before it:\n",
+            previous.len()
+        );
+        let metrics: [(&str, Metric); 3] = [
+            ("execs/s", |r| r.execs_per_sec),
+            ("coverage", |r| r.coverage),
+            ("corpus entries", |r| r.corpus_entries),
+        ];
+        for (name, metric) in &metrics {
+            let values: Vec<u64> = previous.iter().filter_map(metric).collect();
+            if let (Some(value), false) = (metric(latest), values.is_empty()) {
+                let average = values.iter().sum::<u64>() as f64 / values.len() as f64;
+                let _ = writeln!(
+                    out,
+                    "\t{:<16} {:>10} ({})",
+                    format!("{}:", name),
+                    value,
+                    format_change(value as f64, average)
+                );
+            }
+        }
+    }
+    out
+}
+
+fn or_dash(value: Option<u64>) -> String {
+    value.map_or("-".to_string(), |v| v.to_string())
+}
+
+fn format_change(value: f64, average: f64) -> String {
+    if average == 0.0 {
+        return "no previous value".to_string();
+    }
+    let change = (value - average) / average * 100.0;
+    format!("{:+.1}% vs {:.0}", change, average)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1 << 20 {
+        format!("{}Mb", bytes >> 20)
+    } else if bytes >= 1 << 10 {
+        format!("{}Kb", bytes >> 10)
+    } else {
+        format!("{}b", bytes)
+    }
+}
+
+pub fn format_duration(secs: u64) -> String {
+    if secs >= 60 * 60 {
+        format!("{}h{:02}m", secs / (60 * 60), secs / 60 % 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Format seconds since the Unix epoch as a UTC date and time.
+pub fn format_timestamp(secs: u64) -> String {
+    let (year, month, day, time) = civil_from_secs(secs);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        time / (60 * 60),
+        time / 60 % 60
+    )
+}
+
+/// Format seconds since the Unix epoch as a UTC date and time to the second
+/// that can be part of a file name, and sorts in time order, like
+/// `20240131T235959Z`.
+pub fn format_file_timestamp(secs: u64) -> String {
+    let (year, month, day, time) = civil_from_secs(secs);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        time / (60 * 60),
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+/// The UTC year, month, day, and seconds into the day of seconds since the
+/// Unix epoch.
+fn civil_from_secs(secs: u64) -> (i64, i64, i64, u64) {
+    // Howard Hinnant's `civil_from_days` algorithm.
+    let days = (secs / (24 * 60 * 60)) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day, secs % (24 * 60 * 60))
+}
@@ -0,0 +1,254 @@
+//! Serving a running fuzzer's progress over HTTP, for `cargo fuzz run
+//! --status-addr`, so that dashboards and orchestration can watch it without
+//! scraping its output.
+//!
+//! It's plain HTTP/1.1, one request per connection, like `cargo fuzz serve`:
+//!
+//! * `GET /status` reports the progress as a JSON object.
+//! * `GET /metrics` reports it in Prometheus' text format, with the fuzz
+//!   target's name as the `target` label.
+//!
+//! The progress comes from libFuzzer's status lines, so it's only as fresh as
+//! the last one, and it adds up the inputs run over the fuzzer's restarts.
+
+use crate::{distribute, libfuzzer};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+/// A fuzzer's progress, as of its last status line.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Progress {
+    pub target: String,
+    pub uptime_secs: u64,
+    /// The inputs run so far, over all restarts of the fuzzer.
+    pub execs: u64,
+    pub execs_per_sec: Option<u64>,
+    pub coverage: Option<u64>,
+    pub features: Option<u64>,
+    pub corpus_entries: Option<u64>,
+    pub corpus_bytes: Option<u64>,
+    pub rss_mb: Option<u64>,
+    /// The crashes found so far, over all restarts of the fuzzer.
+    pub crashes: u64,
+}
+
+#[derive(Debug)]
+struct State {
+    progress: Progress,
+    started: Instant,
+    /// The inputs run by the fuzzer's earlier processes.
+    earlier_execs: u64,
+    /// The crashes that fork mode's current process has counted.
+    fork_crashes: u64,
+}
+
+/// Serves a fuzzer's progress on a background thread, for as long as
+/// cargo-fuzz runs.
+#[derive(Clone, Debug)]
+pub struct Server {
+    state: Arc<Mutex<State>>,
+    addr: SocketAddr,
+}
+
+impl Server {
+    /// Listen on `addr` for requests about the progress of fuzzing `target`.
+    pub fn start(addr: &str, target: &str) -> Result<Server> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("failed to listen on {}", addr))?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State {
+            progress: Progress {
+                target: target.to_string(),
+                ..Progress::default()
+            },
+            started: Instant::now(),
+            earlier_execs: 0,
+            fork_crashes: 0,
+        }));
+        let server = Server { state, addr };
+        let serving = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let serving = serving.clone();
+                thread::spawn(move || {
+                    // A client that hangs up early only hurts itself.
+                    let _ = serving.handle(stream);
+                });
+            }
+        });
+        Ok(server)
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Keep track of the fuzzer's progress from its output.
+    pub fn observe(&self, line: &str) {
+        let status = match libfuzzer::Status::parse(line) {
+            Some(status) => status,
+            None => return,
+        };
+        let mut state = self.state.lock().unwrap();
+        // A restarted fuzzer counts from 0 again.
+        let current = state.progress.execs - state.earlier_execs;
+        if status.execs < current {
+            state.earlier_execs += current;
+            state.fork_crashes = 0;
+        }
+        if let Some(crashes) = fork_crashes(line) {
+            state.progress.crashes += crashes.saturating_sub(state.fork_crashes);
+            state.fork_crashes = crashes;
+        }
+        let progress = &mut state.progress;
+        progress.execs = status.execs;
+        progress.execs_per_sec = status.execs_per_sec.or(progress.execs_per_sec);
+        progress.coverage = status.coverage.or(progress.coverage);
+        progress.features = status.features.or(progress.features);
+        progress.corpus_entries = status.corpus_entries.or(progress.corpus_entries);
+        progress.corpus_bytes = status.corpus_bytes.or(progress.corpus_bytes);
+        progress.rss_mb = status.rss_mb.or(progress.rss_mb);
+        state.progress.execs += state.earlier_execs;
+    }
+
+    /// Count crashes that the fuzzer found outside of fork mode, which
+    /// reports its own.
+    pub fn add_crashes(&self, crashes: usize) {
+        self.state.lock().unwrap().progress.crashes += crashes as u64;
+    }
+
+    pub fn progress(&self) -> Progress {
+        let state = self.state.lock().unwrap();
+        Progress {
+            uptime_secs: state.started.elapsed().as_secs(),
+            ..state.progress.clone()
+        }
+    }
+
+    fn handle(&self, stream: TcpStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+        if reader.fill_buf()?.is_empty() {
+            return Ok(());
+        }
+        let (status, content_type, body) = match distribute::read_request(&mut reader) {
+            Ok((method, _, _)) if method != "GET" => {
+                (405, "text/plain", b"only GET is supported\n".to_vec())
+            }
+            Ok((_, path, _)) => match path.as_str() {
+                "/status" => (
+                    200,
+                    "application/json",
+                    (serde_json::to_string_pretty(&self.progress())
+                        .expect("progress always serializes")
+                        + "\n")
+                        .into_bytes(),
+                ),
+                "/metrics" => (
+                    200,
+                    "text/plain; version=0.0.4",
+                    metrics(&self.progress()).into_bytes(),
+                ),
+                _ => (404, "text/plain", b"not found\n".to_vec()),
+            },
+            Err(e) => (400, "text/plain", format!("{:#}\n", e).into_bytes()),
+        };
+        distribute::write_message(
+            &mut stream,
+            &format!("HTTP/1.1 {} {}", status, distribute::reason(status)),
+            &[("Content-Type", content_type)],
+            &body,
+        )
+    }
+}
+
+/// The crashes that a `-fork` mode status line like `#4096: cov: 45 ...
+/// oom/timeout/crash: 0/0/2 ...` counts.
+fn fork_crashes(line: &str) -> Option<u64> {
+    let mut words = line.split_whitespace();
+    words.find(|word| *word == "oom/timeout/crash:")?;
+    words.next()?.rsplit('/').next()?.parse().ok()
+}
+
+/// The progress in Prometheus' text format.
+pub fn metrics(progress: &Progress) -> String {
+    let mut out = String::new();
+    let target = progress.target.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut metric = |name: &str, kind: &str, help: &str, value: Option<u64>| {
+        if let Some(value) = value {
+            let _ = writeln!(out, "# HELP cargo_fuzz_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE cargo_fuzz_{} {}", name, kind);
+            let _ = writeln!(
+                out,
+                "cargo_fuzz_{}{{target=\"{}\"}} {}",
+                name, target, value
+            );
+        }
+    };
+    metric(
+        "uptime_seconds",
+        "gauge",
+        "Seconds since fuzzing started.",
+        Some(progress.uptime_secs),
+    );
+    metric(
+        "execs_total",
+        "counter",
+        "Inputs run so far.",
+        Some(progress.execs),
+    );
+    metric(
+        "execs_per_second",
+        "gauge",
+        "Inputs run per second, as of libFuzzer's last report.",
+        progress.execs_per_sec,
+    );
+    metric(
+        "coverage",
+        "gauge",
+        "Covered code blocks or edges.",
+        progress.coverage,
+    );
+    metric(
+        "features",
+        "gauge",
+        "Covered features, which include counters and value profiles.",
+        progress.features,
+    );
+    metric(
+        "corpus_entries",
+        "gauge",
+        "Inputs in the fuzzer's corpus.",
+        progress.corpus_entries,
+    );
+    metric(
+        "corpus_bytes",
+        "gauge",
+        "Total size of the fuzzer's corpus.",
+        progress.corpus_bytes,
+    );
+    metric(
+        "rss_bytes",
+        "gauge",
+        "The fuzzer's resident memory.",
+        progress.rss_mb.map(|mb| mb << 20),
+    );
+    metric(
+        "crashes_total",
+        "counter",
+        "Crashes found so far.",
+        Some(progress.crashes),
+    );
+    out
+}
@@ -1,5 +1,5 @@
 macro_rules! toml_template {
-    ($name: expr) => {
+    ($name: expr, $libfuzzer_sys: expr, $no_std: expr) => {
         format_args!(
             r##"
 [package]
@@ -13,43 +13,89 @@ edition = "2018"
 cargo-fuzz = true
 
 [dependencies]
-libfuzzer-sys = "0.3"
+libfuzzer-sys = {1}
 
 [dependencies.{0}]
-path = ".."
+path = ".."{2}
+{3}"##,
+            $name,
+            $libfuzzer_sys,
+            if $no_std {
+                "\ndefault-features = false"
+            } else {
+                ""
+            },
+            toml_lib_template!()
+        )
+    };
+}
 
+macro_rules! toml_lib_template {
+    () => {
+        format_args!(
+            r#"
+[lib]
+path = "src/lib.rs"
+test = false
+doc = false
+"#
+        )
+    };
+}
+
+macro_rules! toml_workspace_template {
+    () => {
+        format_args!(
+            r##"
 # Prevent this from interfering with workspaces
 [workspace]
 members = ["."]
-"##,
-            $name
+"##
         )
     };
 }
 
 macro_rules! toml_bin_template {
-    ($name: expr) => {
+    ($name: expr, $path: expr) => {
         format_args!(
             r#"
 [[bin]]
 name = "{0}"
-path = "fuzz_targets/{0}.rs"
+path = "fuzz_targets/{1}.rs"
 test = false
 doc = false
 "#,
-            $name
+            $name, $path
         )
     };
 }
 
 macro_rules! gitignore_template {
-    () => {
+    ($corpus:expr) => {
         format_args!(
             r##"
 target
-corpus
-artifacts
-"##
+{corpus}artifacts
+coverage
+logs
+provenance
+traces
+focus
+profile
+"##,
+            corpus = $corpus,
+        )
+    };
+}
+
+macro_rules! gitattributes_template {
+    ($corpus:expr) => {
+        format_args!(
+            r##"# Fuzzer inputs are binary, so don't diff or merge them as text.
+corpus/** {corpus}
+artifacts/** binary
+"##,
+            corpus = $corpus,
         )
     };
 }
@@ -67,3 +113,1691 @@ fuzz_target!(|data: &[u8]| {{
         )
     };
 }
+
+macro_rules! reject_invalid_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::{{fuzz_target, Corpus}};
+
+fuzz_target!(|data: &[u8]| -> Corpus {{
+    // Parse the input here, and reject it if it isn't valid, so that the
+    // fuzzer keeps it out of the corpus and mutates the valid ones instead.
+    let input = match std::str::from_utf8(data) {{
+        Ok(input) => input,
+        Err(_) => return Corpus::Reject,
+    }};
+    // fuzzed code goes here
+    let _ = input;
+    Corpus::Keep
+}});
+"##
+        )
+    };
+}
+
+macro_rules! no_std_target_template {
+    ($name: expr) => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// `{0}` is `no_std`, but fuzz targets link it against `std`, which provides
+// the allocator and panic handler it would otherwise need. The fuzz crate
+// depends on it with `default-features = false`; enable the features to fuzz
+// in fuzz/Cargo.toml. Leave out code that only builds for the device, like a
+// `#[panic_handler]`, with `#[cfg(not(fuzzing))]`.
+fuzz_target!(|data: &[u8]| {{
+    // fuzzed code goes here
+    let _ = data;
+}});
+"##,
+            $name
+        )
+    };
+}
+
+macro_rules! bypass_module_template {
+    () => {
+        format_args!(
+            r##"//! Generated by `cargo fuzz add --with-bypass-module`.
+//!
+//! Checks that a fuzzer can't guess its way past, like checksums, MACs and
+//! signatures, keep it from the code behind them. Route them through these
+//! functions, and fuzzing builds skip them. `cargo fuzz blockers <target>`
+//! lists the comparisons worth routing here.
+//!
+//! # The `cfg(fuzzing)` contract
+//!
+//! `cargo fuzz` builds this crate and its dependencies with `--cfg fuzzing`,
+//! and nothing else should. Under it, the functions here pass every input;
+//! otherwise they do the check they're given, so release builds are
+//! unaffected. Keep `cfg(fuzzing)` to checks like these: code that does
+//! anything else differently when fuzzing isn't the code that ships, and the
+//! crashes found in it may not reproduce outside of `cargo fuzz`.
+
+// `fuzzing` isn't a cfg that Cargo knows about.
+#![allow(unknown_lints, unexpected_cfgs)]
+#![allow(dead_code)]
+
+/// Whether this build skips the checks.
+pub const BYPASSED: bool = cfg!(fuzzing);
+
+/// Whether `actual` is `expected`, like a checksum stored in an input and the
+/// one computed from it. Always true when fuzzing.
+#[inline]
+pub fn checksum_matches<T: PartialEq>(expected: T, actual: T) -> bool {{
+    BYPASSED || expected == actual
+}}
+
+/// Run `check`, like the verification of a signature, unless fuzzing, when it
+/// always passes.
+#[inline]
+pub fn verify<E>(check: impl FnOnce() -> Result<(), E>) -> Result<(), E> {{
+    if BYPASSED {{
+        Ok(())
+    }} else {{
+        check()
+    }}
+}}
+"##
+        )
+    };
+}
+
+macro_rules! record_module_template {
+    () => {
+        format_args!(
+            r##"//! Generated by `cargo fuzz corpus from-tests`.
+//!
+//! Unit tests exercise the inputs that matter to a crate, which makes them
+//! good seeds for its fuzz targets. Call `record` with the input at the start
+//! of the function that a fuzz target calls, and `cargo fuzz corpus
+//! from-tests <target>` runs this crate's tests and adds every input they
+//! pass it to the target's corpus.
+//!
+//! `record` only records in the builds of the tests that `cargo fuzz` makes,
+//! which set `--cfg cargo_fuzz_record`; everywhere else it does nothing.
+
+// `cargo_fuzz_record` isn't a cfg that Cargo knows about.
+#![allow(unknown_lints, unexpected_cfgs)]
+#![allow(dead_code)]
+
+/// Save `input` to the directory that `cargo fuzz corpus from-tests` reads
+/// the recorded inputs from, named after its contents.
+#[inline]
+pub fn record<T: AsRef<[u8]> + ?Sized>(input: &T) {{
+    #[cfg(cargo_fuzz_record)]
+    {{
+        extern crate std;
+        use std::format;
+
+        if let Some(dir) = std::env::var_os("CARGO_FUZZ_RECORD_DIR") {{
+            let input = input.as_ref();
+            let hash = input.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {{
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+            }});
+            let path = std::path::Path::new(&dir).join(format!("{{:016x}}", hash));
+            let _ = std::fs::write(path, input);
+        }}
+    }}
+    #[cfg(not(cargo_fuzz_record))]
+    let _ = input;
+}}
+"##
+        )
+    };
+}
+
+macro_rules! function_target_template {
+    ($function: expr, $harness: expr) => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::{4};
+
+// Generated from the signature of `{0}`, which is called
+// with {1}.{5}
+fuzz_target!(|{2}|{6} {{
+{3}
+}});
+"##,
+            $function,
+            $harness.description,
+            $harness.input,
+            $harness.body,
+            if $harness.rejects {
+                "{fuzz_target, Corpus}"
+            } else {
+                "fuzz_target"
+            },
+            if $harness.rejects {
+                "\n// The inputs it rejects are kept out of the corpus."
+            } else {
+                ""
+            },
+            if $harness.rejects { " -> Corpus" } else { "" }
+        )
+    };
+}
+
+macro_rules! ffi_target_template {
+    ($crate_name: expr, $symbol: expr, $harness: expr) => {
+        format_args!(
+            r##"#![no_main]
+{4}use libfuzzer_sys::fuzz_target;
+
+// Links in `{0}`, which exports `{1}`.
+use {0} as _;
+
+extern "C" {{
+    {2}
+}}
+
+// Calls `{1}` through its C ABI, with {3}.
+// Each buffer it's passed is a heap allocation of exactly its length, so
+// AddressSanitizer reports any access past the end of one.
+fuzz_target!(|data: &[u8]| {{
+{5}
+}});
+"##,
+            $crate_name,
+            $symbol,
+            $harness.declaration,
+            $harness.description,
+            if $harness.body.contains("Unstructured") {
+                "use libfuzzer_sys::arbitrary::Unstructured;\n"
+            } else {
+                ""
+            },
+            $harness.body
+        )
+    };
+}
+
+macro_rules! arbitrary_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::arbitrary::{{self, Arbitrary}};
+use libfuzzer_sys::fuzz_target;
+
+// The fuzzer builds values of this type out of its raw input bytes. Replace
+// its fields with whatever the fuzzed code takes as input.
+#[derive(Arbitrary, Debug)]
+struct Input {{
+    flag: bool,
+    count: u32,
+    name: String,
+    values: Vec<u8>,
+}}
+
+fuzz_target!(|input: Input| {{
+    // fuzzed code goes here
+    let _ = input;
+}});
+"##
+        )
+    };
+}
+
+macro_rules! typed_target_template {
+    ($target: expr, $ty: expr) => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// The fuzzer turns its inputs into values of type `{1}` with the
+// type's `Arbitrary` implementation. Print the value an input decodes to with
+// `cargo fuzz fmt {0} <input>`, or with the `{0}_debug` example.
+fuzz_target!(|input: {1}| {{
+    // fuzzed code goes here
+    let _ = input;
+}});
+"##,
+            $target, $ty
+        )
+    };
+}
+
+macro_rules! arbitrary_debug_template {
+    ($target: expr, $ty: expr) => {
+        format_args!(
+            r##"//! Print the value of type `{1}` that an input to the `{0}` fuzz
+//! target decodes to, without running the fuzz target. Run it with:
+//!
+//!     cargo run --example {0}_debug -- <input>
+
+use libfuzzer_sys::arbitrary::{{Arbitrary, Unstructured}};
+use std::{{env, fs, process}};
+
+fn main() {{
+    let path = match env::args_os().nth(1) {{
+        Some(path) => path,
+        None => {{
+            eprintln!("usage: {0}_debug <input>");
+            process::exit(2);
+        }}
+    }};
+    let data = fs::read(&path).expect("failed to read the input");
+    match <{1} as Arbitrary>::arbitrary_take_rest(Unstructured::new(&data)) {{
+        Ok(input) => println!("{{:#?}}", input),
+        Err(e) => {{
+            // The fuzz target skips inputs like this one without running.
+            eprintln!("the input does not decode to a value of type `{1}`: {{}}", e);
+            process::exit(1);
+        }}
+    }}
+}}
+"##,
+            $target, $ty
+        )
+    };
+}
+
+macro_rules! roundtrip_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// Replace these with the fuzzed crate's decoding and encoding functions.
+fn decode(data: &[u8]) -> Option<Vec<u8>> {{
+    Some(data.to_vec())
+}}
+
+fn encode(value: &[u8]) -> Vec<u8> {{
+    value.to_vec()
+}}
+
+// Anything that decodes successfully must encode to something that decodes
+// back to the same value.
+fuzz_target!(|data: &[u8]| {{
+    if let Some(value) = decode(data) {{
+        let encoded = encode(&value);
+        let roundtripped = decode(&encoded).expect("failed to decode an encoded value");
+        assert_eq!(value, roundtripped, "value changed after an encode/decode roundtrip");
+    }}
+}});
+"##
+        )
+    };
+}
+
+macro_rules! grammar_target_template {
+    () => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::arbitrary::{{Result, Unstructured}};
+use libfuzzer_sys::fuzz_target;
+
+// Inputs are generated from this grammar rather than being raw bytes, so the
+// fuzzed code mostly sees well-formed text. Replace it with the grammar of the
+// format being fuzzed:
+//
+//     expr   := number | "(" expr op expr ")" | "-" expr
+//     op     := " + " | " - " | " * " | " / "
+//     number := [0-9]+
+const MAX_DEPTH: u32 = 8;
+
+fn expr(u: &mut Unstructured<'_>, depth: u32, out: &mut String) -> Result<()> {{
+    if depth >= MAX_DEPTH || u.is_empty() {{
+        return number(u, out);
+    }}
+    match u.int_in_range(0..=2)? {{
+        0 => number(u, out),
+        1 => {{
+            out.push('(');
+            expr(u, depth + 1, out)?;
+            out.push_str(u.choose(&[" + ", " - ", " * ", " / "])?);
+            expr(u, depth + 1, out)?;
+            out.push(')');
+            Ok(())
+        }}
+        _ => {{
+            out.push('-');
+            expr(u, depth + 1, out)
+        }}
+    }}
+}}
+
+fn number(u: &mut Unstructured<'_>, out: &mut String) -> Result<()> {{
+    let n: u32 = u.arbitrary()?;
+    out.push_str(&n.to_string());
+    Ok(())
+}}
+
+fuzz_target!(|data: &[u8]| {{
+    let mut u = Unstructured::new(data);
+    let mut input = String::new();
+    if expr(&mut u, 0, &mut input).is_err() {{
+        return;
+    }}
+    // fuzzed code goes here
+    let _ = input;
+}});
+"##
+        )
+    };
+}
+
+macro_rules! grammar_file_target_template {
+    ($grammar: expr, $start: expr, $generator: expr) => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::arbitrary::{{Result, Unstructured}};
+use libfuzzer_sys::fuzz_target;
+
+// Inputs are generated from the grammar in `{0}` rather than being raw
+// bytes, so the fuzzed code mostly sees well-formed text. The fuzzer's input
+// picks the alternatives and repetitions. Generated by `cargo fuzz add
+// --grammar`; add the fuzz target again to pick up changes to the grammar.
+fuzz_target!(|data: &[u8]| {{
+    let mut u = Unstructured::new(data);
+    let mut input = String::new();
+    if {1}(&mut u, 0, &mut input).is_err() {{
+        return;
+    }}
+    // fuzzed code goes here
+    let _ = input;
+}});
+{2}"##,
+            $grammar, $start, $generator
+        )
+    };
+}
+
+macro_rules! differential_target_template {
+    ($implementations: expr, $uses: expr, $glue: expr) => {
+        format_args!(
+            r##"#![no_main]
+{1}
+// Replace these with the two implementations to compare: {0}.
+fn ours(data: &[u8]) -> Option<Vec<u8>> {{
+    Some(data.to_vec())
+}}
+
+fn reference(data: &[u8]) -> Option<Vec<u8>> {{
+    Some(data.to_vec())
+}}
+
+// Both implementations must give the same output for every input.
+fuzz_target!(|data: &[u8]| {{
+    let (ours, reference) = (ours(data), reference(data));
+    if ours != reference {{
+        report_divergence(data, &ours, &reference);
+    }}
+}});
+{2}"##,
+            $implementations, $uses, $glue
+        )
+    };
+}
+
+macro_rules! split_target_template {
+    ($target: expr, $entry_points: expr, $source: expr) => {
+        format_args!(
+            r##"// Split from `{0}` by `cargo fuzz suggest-split`, to fuzz only:
+//
+{1}//
+// Trim the calls of the fuzzed crate's other entry points out of this copy of
+// `{0}`.
+{2}"##,
+            $target, $entry_points, $source
+        )
+    };
+}
+
+macro_rules! divergence_glue_template {
+    ($visibility: expr) => {
+        format_args!(
+            r##"
+/// Write a report of the outputs that diverged to the artifacts directory,
+/// where `cargo fuzz` finds it next to the failing input, and fail.
+{0}fn report_divergence(data: &[u8], ours: &dyn Debug, reference: &dyn Debug) -> ! {{
+    let (ours, reference) = (format!("{{:?}}", ours), format!("{{:?}}", reference));
+    if let Some(dir) = env::var_os("CARGO_FUZZ_ARTIFACTS") {{
+        let hash = data.iter().fold(0xcbf2_9ce4_8422_2325, |hash: u64, &byte| {{
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        }});
+        let input: String = data.iter().map(|byte| format!("{{:02x}}", byte)).collect();
+        let report = format!(
+            "{{{{\n  \"input\": \"{{}}\",\n  \"ours\": {{}},\n  \"reference\": {{}}\n}}}}\n",
+            input,
+            json_string(&ours),
+            json_string(&reference)
+        );
+        let path = Path::new(&dir).join(format!("divergence-{{:016x}}.json", hash));
+        let _ = fs::write(path, report);
+    }}
+    panic!("implementations diverged\n     ours: {{}}\nreference: {{}}", ours, reference);
+}}
+
+fn json_string(s: &str) -> String {{
+    let mut out = String::from("\"");
+    for c in s.chars() {{
+        match c {{
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{{:04x}}", c as u32)),
+            c => out.push(c),
+        }}
+    }}
+    out.push('"');
+    out
+}}
+"##,
+            $visibility
+        )
+    };
+}
+
+macro_rules! fuzz_lib_template {
+    ($lib: expr, $reexport: expr) => {
+        format_args!(
+            r##"//! Code that the fuzz targets share, which is built once for all of them
+//! rather than into each one. Put what several of them need here, like helpers
+//! and `Arbitrary` input types, and use it as `{0}::...`.
+{1}
+use std::fmt::Debug;
+use std::{{env, fs, path::Path}};
+{2}"##,
+            $lib,
+            $reexport,
+            divergence_glue_template!("pub ")
+        )
+    };
+}
+
+macro_rules! regression_tests_template {
+    ($target: expr) => {
+        format_args!(
+            r##"//! Regression tests for the `{0}` fuzz target, generated by
+//! `cargo fuzz gen-test`. Run them with `cargo test` in the `fuzz` directory.
+
+use std::process::Command;
+use std::{{env, fs}};
+
+/// Run the fuzz target on an input, and assert that it doesn't crash.
+fn run_fuzz_target(name: &str, input: &[u8]) {{
+    let path = env::temp_dir().join(format!("{0}-{{}}-{{}}", std::process::id(), name));
+    fs::write(&path, input).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_{0}"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    let _ = fs::remove_file(&path);
+    assert!(
+        output.status.success(),
+        "fuzz target `{0}` crashed on {{}}:\n{{}}",
+        name,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}}
+"##,
+            $target
+        )
+    };
+}
+
+macro_rules! regression_test_template {
+    ($test_name: expr, $artifact_name: expr, $input: expr) => {
+        format_args!(
+            r##"
+#[test]
+fn {0}() {{
+    run_fuzz_target(
+        "{1}",
+        {2},
+    );
+}}
+"##,
+            $test_name, $artifact_name, $input
+        )
+    };
+}
+
+macro_rules! corpus_replay_tests_template {
+    () => {
+        format_args!(
+            r##"//! Replays every fuzz target's corpus, so that `cargo test` in the `fuzz`
+//! directory checks that none of its inputs crash. Generated by `cargo fuzz
+//! check --replay`, which regenerates it for the current fuzz targets.
+
+use std::fs;
+use std::path::{{Path, PathBuf}};
+use std::process::Command;
+
+/// How many inputs to pass to each run of a fuzz target.
+const BATCH_SIZE: usize = 64;
+
+/// Run the fuzz target on every input in its corpus, and assert that it
+/// doesn't crash on any of them.
+#[allow(dead_code)]
+fn replay_corpus(target: &str, binary: &str) {{
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("corpus")
+        .join(target);
+    let mut inputs: Vec<PathBuf> = match fs::read_dir(&corpus) {{
+        Ok(entries) => entries
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => return,
+    }};
+    inputs.sort();
+    for batch in inputs.chunks(BATCH_SIZE) {{
+        let output = Command::new(binary).args(batch).output().unwrap();
+        assert!(
+            output.status.success(),
+            "fuzz target `{{}}` crashed on an input in {{}}:\n{{}}",
+            target,
+            corpus.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }}
+}}
+"##
+        )
+    };
+}
+
+macro_rules! corpus_replay_test_template {
+    ($test_name: expr, $target: expr, $cfg: expr) => {
+        format_args!(
+            r##"
+{2}#[test]
+fn {0}() {{
+    replay_corpus("{1}", env!("CARGO_BIN_EXE_{1}"));
+}}
+"##,
+            $test_name, $target, $cfg
+        )
+    };
+}
+
+macro_rules! libafl_harness_toml_template {
+    ($libafl: expr) => {
+        format_args!(
+            r##"[package]
+name = "cargo-fuzz-libafl"
+version = "0.0.0"
+authors = ["Automatically generated"]
+publish = false
+edition = "2021"
+
+[lib]
+name = "cargo_fuzz_libafl"
+crate-type = ["staticlib"]
+
+[dependencies]
+libafl = "{0}"
+libafl_bolts = "{0}"
+
+[dependencies.libafl_targets]
+version = "{0}"
+features = ["libfuzzer", "libfuzzer_no_link_main", "sancov_8bit", "sancov_cmplog", "observers"]
+
+# Keep this from being part of any workspace it happens to be in
+[workspace]
+"##,
+            $libafl
+        )
+    };
+}
+
+macro_rules! libafl_harness_template {
+    () => {
+        format_args!(
+            r##"//! A LibAFL fuzzer for a `cargo fuzz` fuzz target, generated by `cargo fuzz`.
+//!
+//! This is linked into the fuzz target in libFuzzer's place, and drives its
+//! `LLVMFuzzerTestOneInput` in-process, restarting after every crash.
+
+use std::ffi::{{c_char, c_int, CStr}};
+use std::path::PathBuf;
+use std::time::{{Duration, Instant}};
+
+use libafl::corpus::{{Corpus, InMemoryOnDiskCorpus, OnDiskCorpus}};
+use libafl::events::{{
+    ClientDescription, EventConfig, Launcher, LlmpRestartingEventManager, SendExiting,
+}};
+use libafl::executors::{{inprocess::InProcessExecutor, ExitKind, ShadowExecutor}};
+use libafl::feedbacks::{{
+    CrashFeedback, DifferentIsNovel, MapFeedback, TimeFeedback, TimeoutFeedback,
+}};
+use libafl::generators::RandBytesGenerator;
+use libafl::inputs::{{BytesInput, HasTargetBytes}};
+use libafl::monitors::MultiMonitor;
+use libafl::mutators::{{
+    havoc_mutations, token_mutations::I2SRandReplace, tokens_mutations, HavocScheduledMutator,
+    Tokens,
+}};
+use libafl::observers::{{CanTrack, HitcountsIterableMapObserver, TimeObserver}};
+use libafl::schedulers::{{IndexesLenTimeMinimizerScheduler, QueueScheduler}};
+use libafl::stages::{{ShadowTracingStage, StdMutationalStage}};
+use libafl::state::{{HasCorpus, StdState}};
+use libafl::{{
+    feedback_and_fast, feedback_or, feedback_or_fast, Error, Fuzzer, HasMetadata, StdFuzzer,
+}};
+use libafl_bolts::core_affinity::Cores;
+use libafl_bolts::rands::StdRand;
+use libafl_bolts::shmem::{{ShMemProvider, StdShMem, StdShMemProvider}};
+use libafl_bolts::simd::MaxReducer;
+use libafl_bolts::tuples::{{tuple_list, Merge}};
+use libafl_bolts::ToSlice;
+use libafl_targets::{{
+    counters_maps_observer, libfuzzer_initialize, libfuzzer_test_one_input, CmpLogObserver,
+}};
+
+type State =
+    StdState<InMemoryOnDiskCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>;
+type Manager = LlmpRestartingEventManager<(), BytesInput, State, StdShMem, StdShMemProvider>;
+
+struct Options {{
+    cores: String,
+    broker_port: u16,
+    timeout: Duration,
+    max_total_time: Option<Duration>,
+    artifacts: PathBuf,
+    dict: Option<PathBuf>,
+    corpora: Vec<PathBuf>,
+}}
+
+fn parse_options(args: &[String]) -> Options {{
+    let mut options = Options {{
+        cores: "0".to_string(),
+        broker_port: 1337,
+        timeout: Duration::from_secs(1),
+        max_total_time: None,
+        artifacts: PathBuf::from("."),
+        dict: None,
+        corpora: Vec::new(),
+    }};
+    let mut args = args.iter().skip(1);
+    while let Some(arg) = args.next() {{
+        let mut value = || {{
+            args.next()
+                .unwrap_or_else(|| panic!("missing value for `{{}}`", arg))
+                .clone()
+        }};
+        match arg.as_str() {{
+            "--cores" => options.cores = value(),
+            "--broker-port" => options.broker_port = value().parse().expect("invalid port"),
+            "--timeout" => {{
+                options.timeout = Duration::from_millis(value().parse().expect("invalid timeout"))
+            }}
+            "--max-total-time" => {{
+                options.max_total_time = Some(Duration::from_secs(
+                    value().parse().expect("invalid max total time"),
+                ))
+            }}
+            "--artifacts" => options.artifacts = PathBuf::from(value()),
+            "--dict" => options.dict = Some(PathBuf::from(value())),
+            _ => options.corpora.push(PathBuf::from(arg)),
+        }}
+    }}
+    options
+}}
+
+/// The entry point of the fuzz target, in place of libFuzzer's.
+#[no_mangle]
+pub extern "C" fn main(argc: c_int, argv: *const *const c_char) -> c_int {{
+    let args: Vec<String> = (0..argc as usize)
+        .map(|i| {{
+            unsafe {{ CStr::from_ptr(*argv.add(i)) }}
+                .to_string_lossy()
+                .into_owned()
+        }})
+        .collect();
+    let options = parse_options(&args);
+    match fuzz(&args, &options) {{
+        Ok(()) | Err(Error::ShuttingDown) => 0,
+        Err(err) => {{
+            eprintln!("error: {{}}", err);
+            1
+        }}
+    }}
+}}
+
+fn fuzz(args: &[String], options: &Options) -> Result<(), Error> {{
+    let cores = Cores::from_cmdline(&options.cores)?;
+    let deadline = options.max_total_time.map(|time| Instant::now() + time);
+    let shmem_provider = StdShMemProvider::new()?;
+    let monitor = MultiMonitor::new(|status| eprintln!("{{}}", status));
+
+    let mut run_client = |state: Option<State>, mut mgr: Manager, _client: ClientDescription| {{
+        let edges_observer =
+            HitcountsIterableMapObserver::new(unsafe {{ counters_maps_observer("edges") }})
+                .track_indices();
+        let time_observer = TimeObserver::new("time");
+        let cmplog_observer = CmpLogObserver::new("cmplog", true);
+
+        let map_feedback = MapFeedback::<_, DifferentIsNovel, _, MaxReducer>::new(&edges_observer);
+        let mut feedback = feedback_or!(map_feedback, TimeFeedback::new(&time_observer));
+        // Only keep crashes that reach new coverage, rather than every input
+        // that crashes in the same place.
+        let mut objective = feedback_and_fast!(
+            feedback_or_fast!(CrashFeedback::new(), TimeoutFeedback::new()),
+            MapFeedback::<_, DifferentIsNovel, _, MaxReducer>::with_name(
+                "objective_edges",
+                &edges_observer
+            )
+        );
+
+        let mut state = match state {{
+            Some(state) => state,
+            None => StdState::new(
+                StdRand::new(),
+                InMemoryOnDiskCorpus::with_meta_format_and_prefix(
+                    &options.corpora[0],
+                    None,
+                    None,
+                    false,
+                )?,
+                OnDiskCorpus::with_meta_format_and_prefix(
+                    &options.artifacts,
+                    None,
+                    Some("crash-".to_string()),
+                    false,
+                )?,
+                &mut feedback,
+                &mut objective,
+            )?,
+        }};
+        if let Some(dict) = &options.dict {{
+            if !state.has_metadata::<Tokens>() {{
+                state.add_metadata(Tokens::from_file(dict)?);
+            }}
+        }}
+
+        let scheduler =
+            IndexesLenTimeMinimizerScheduler::new(&edges_observer, QueueScheduler::new());
+        let mut fuzzer = StdFuzzer::new(scheduler, feedback, objective);
+
+        let mut harness = |input: &BytesInput| {{
+            let target = input.target_bytes();
+            unsafe {{
+                libfuzzer_test_one_input(&target.to_slice());
+            }}
+            ExitKind::Ok
+        }};
+        let executor = InProcessExecutor::builder()
+            .harness(&mut harness)
+            .observers(tuple_list!(edges_observer, time_observer))
+            .fuzzer(&mut fuzzer)
+            .state(&mut state)
+            .event_mgr(&mut mgr)
+            .timeout(options.timeout)
+            .build()?;
+        let mut executor = ShadowExecutor::new(executor, tuple_list!(cmplog_observer));
+
+        unsafe {{
+            libfuzzer_initialize(args);
+        }}
+        if state.must_load_initial_inputs() {{
+            state.load_initial_inputs(&mut fuzzer, &mut executor, &mut mgr, &options.corpora)?;
+            if state.corpus().count() == 0 {{
+                let mut generator = RandBytesGenerator::new(core::num::NonZero::new(32).unwrap());
+                state.generate_initial_inputs(
+                    &mut fuzzer,
+                    &mut executor,
+                    &mut generator,
+                    &mut mgr,
+                    8,
+                )?;
+            }}
+        }}
+
+        let tracing = ShadowTracingStage::new();
+        let i2s = StdMutationalStage::new(HavocScheduledMutator::new(tuple_list!(
+            I2SRandReplace::new()
+        )));
+        let mutator = HavocScheduledMutator::new(havoc_mutations().merge(tokens_mutations()));
+        let mut stages = tuple_list!(tracing, i2s, StdMutationalStage::new(mutator));
+
+        loop {{
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {{
+                mgr.send_exiting()?;
+                return Err(Error::shutting_down());
+            }}
+            fuzzer.fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)?;
+        }}
+    }};
+
+    Launcher::builder()
+        .shmem_provider(shmem_provider)
+        .configuration(EventConfig::from_name("default"))
+        .monitor(monitor)
+        .run_client(&mut run_client)
+        .cores(&cores)
+        .broker_port(options.broker_port)
+        .build()
+        .launch()
+}}
+"##
+        )
+    };
+}
+
+macro_rules! trace_recorder_template {
+    () => {
+        format_args!(
+            r##"// Records the last inputs a fuzz target runs, for `cargo fuzz run
+// --record-trace`. Linked with `--wrap=LLVMFuzzerTestOneInput`, so that
+// libFuzzer calls this before the fuzz target.
+//
+// Each input is written to $CARGO_FUZZ_TRACE_DIR before it runs, so that the
+// trace survives the crash, named after its sequence number, and the input
+// $CARGO_FUZZ_TRACE_LEN before it is removed.
+
+#include <fcntl.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <unistd.h>
+
+int __real_LLVMFuzzerTestOneInput(const uint8_t *data, size_t size);
+
+static const char *trace_dir;
+static unsigned long long trace_len;
+static unsigned long long next;
+
+int __wrap_LLVMFuzzerTestOneInput(const uint8_t *data, size_t size) {{
+    if (!trace_dir) {{
+        const char *dir = getenv("CARGO_FUZZ_TRACE_DIR");
+        const char *len = getenv("CARGO_FUZZ_TRACE_LEN");
+        trace_dir = dir ? dir : "";
+        trace_len = len ? strtoull(len, NULL, 10) : 0;
+    }}
+    if (*trace_dir && trace_len) {{
+        char path[4096];
+        if (next >= trace_len) {{
+            snprintf(path, sizeof path, "%s/%020llu", trace_dir, next - trace_len);
+            unlink(path);
+        }}
+        snprintf(path, sizeof path, "%s/%020llu", trace_dir, next++);
+        int fd = open(path, O_WRONLY | O_CREAT | O_TRUNC, 0644);
+        if (fd >= 0) {{
+            size_t written = 0;
+            while (written < size) {{
+                ssize_t n = write(fd, data + written, size - written);
+                if (n <= 0) {{
+                    break;
+                }}
+                written += n;
+            }}
+            close(fd);
+        }}
+    }}
+    return __real_LLVMFuzzerTestOneInput(data, size);
+}}
+"##
+        )
+    };
+}
+
+macro_rules! cmp_recorder_template {
+    () => {
+        format_args!(
+            r##"// Records the comparisons a fuzz target makes, for `cargo fuzz blockers`.
+// Linked with `--wrap` for each of the sanitizer coverage comparison hooks,
+// so that the instrumented code calls these before libFuzzer's.
+//
+// Each comparison site, and each case of a switch, keeps a summary of the
+// values it saw, which is written to $CARGO_FUZZ_CMP_LOG at exit, a line per
+// site with its offset in the binary.
+
+#define _GNU_SOURCE
+#include <dlfcn.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#define SITES (1 << 16)
+
+enum kind {{ CONST_CMP, CMP, SWITCH_CASE }};
+
+struct site {{
+    uintptr_t pc;
+    uint32_t slot;
+    uint8_t used, kind, width, varied1, varied2, best;
+    uint64_t first1, first2, count, equal, below, above, best_value;
+}};
+
+static struct site sites[SITES];
+
+static void record(uintptr_t pc, uint32_t slot, uint8_t kind, uint8_t width, uint64_t a, uint64_t b) {{
+    size_t i = (pc * 31 + slot) % SITES;
+    for (size_t probes = 0; sites[i].used && (sites[i].pc != pc || sites[i].slot != slot); probes++) {{
+        if (probes == SITES) {{
+            return;
+        }}
+        i = (i + 1) % SITES;
+    }}
+    struct site *site = &sites[i];
+    if (!site->used) {{
+        site->used = 1;
+        site->pc = pc;
+        site->slot = slot;
+        site->kind = kind;
+        site->width = width;
+        site->first1 = a;
+        site->first2 = b;
+    }}
+    site->count++;
+    site->varied1 |= a != site->first1;
+    site->varied2 |= b != site->first2;
+    if (a == b) {{
+        site->equal++;
+    }} else if (b < a) {{
+        site->below++;
+    }} else {{
+        site->above++;
+    }}
+    uint8_t matching = 0;
+    for (uint8_t byte = 0; byte < width; byte++) {{
+        matching += ((a >> (8 * byte)) & 0xff) == ((b >> (8 * byte)) & 0xff);
+    }}
+    if (matching > site->best || site->count == 1) {{
+        site->best = matching;
+        site->best_value = b;
+    }}
+}}
+
+__attribute__((destructor)) static void write_log(void) {{
+    const char *path = getenv("CARGO_FUZZ_CMP_LOG");
+    Dl_info self;
+    if (!path || !dladdr((void *)record, &self)) {{
+        return;
+    }}
+    FILE *log = fopen(path, "w");
+    if (!log) {{
+        return;
+    }}
+    for (size_t i = 0; i < SITES; i++) {{
+        struct site *site = &sites[i];
+        Dl_info info;
+        if (!site->used || !dladdr((void *)site->pc, &info) || info.dli_fbase != self.dli_fbase) {{
+            continue;
+        }}
+        fprintf(log, "%lx %u %u %u %u %u %u %llx %llx %llu %llu %llu %llu %llx\n",
+                (unsigned long)(site->pc - (uintptr_t)self.dli_fbase), site->slot, site->kind,
+                site->width, site->varied1, site->varied2, site->best,
+                (unsigned long long)site->first1, (unsigned long long)site->first2,
+                (unsigned long long)site->count, (unsigned long long)site->equal,
+                (unsigned long long)site->below, (unsigned long long)site->above,
+                (unsigned long long)site->best_value);
+    }}
+    fclose(log);
+}}
+
+#define PC ((uintptr_t)__builtin_return_address(0) - 1)
+
+#define WRAP_CMP(name, type, kind)                                        \
+    void __real_##name(type a, type b);                                   \
+    void __wrap_##name(type a, type b) {{                                  \
+        record(PC, 0, kind, sizeof(type), a, b);                          \
+        __real_##name(a, b);                                              \
+    }}
+
+WRAP_CMP(__sanitizer_cov_trace_cmp1, uint8_t, CMP)
+WRAP_CMP(__sanitizer_cov_trace_cmp2, uint16_t, CMP)
+WRAP_CMP(__sanitizer_cov_trace_cmp4, uint32_t, CMP)
+WRAP_CMP(__sanitizer_cov_trace_cmp8, uint64_t, CMP)
+WRAP_CMP(__sanitizer_cov_trace_const_cmp1, uint8_t, CONST_CMP)
+WRAP_CMP(__sanitizer_cov_trace_const_cmp2, uint16_t, CONST_CMP)
+WRAP_CMP(__sanitizer_cov_trace_const_cmp4, uint32_t, CONST_CMP)
+WRAP_CMP(__sanitizer_cov_trace_const_cmp8, uint64_t, CONST_CMP)
+
+void __real___sanitizer_cov_trace_switch(uint64_t value, uint64_t *cases);
+
+// `cases` holds the number of cases, the width of the value in bits, and then
+// the case values.
+void __wrap___sanitizer_cov_trace_switch(uint64_t value, uint64_t *cases) {{
+    for (uint64_t i = 0; i < cases[0]; i++) {{
+        record(PC, i + 1, SWITCH_CASE, cases[1] / 8, cases[i + 2], value);
+    }}
+    __real___sanitizer_cov_trace_switch(value, cases);
+}}
+"##
+        )
+    };
+}
+
+macro_rules! oss_fuzz_project_yaml_template {
+    ($homepage: expr, $contact: expr, $repo: expr, $sanitizers: expr) => {
+        format_args!(
+            r##"homepage: "{0}"
+language: rust
+primary_contact: "{1}"
+main_repo: "{2}"
+sanitizers:
+{3}fuzzing_engines:
+  - libfuzzer
+"##,
+            $homepage, $contact, $repo, $sanitizers
+        )
+    };
+}
+
+macro_rules! oss_fuzz_dockerfile_template {
+    ($name: expr, $repo: expr) => {
+        format_args!(
+            r##"# Generated by `cargo fuzz oss-fuzz-init`.
+FROM gcr.io/oss-fuzz-base/base-builder-rust
+RUN git clone --depth 1 {1} {0}
+WORKDIR $SRC/{0}
+COPY build.sh $SRC/
+"##,
+            $name, $repo
+        )
+    };
+}
+
+macro_rules! oss_fuzz_build_template {
+    ($dir: expr, $builds: expr, $output_dir: expr, $installs: expr) => {
+        format_args!(
+            r##"#!/bin/bash -eu
+# Generated by `cargo fuzz oss-fuzz-init`.
+
+cd $SRC/{0}
+{1}
+FUZZ_TARGET_OUTPUT_DIR=$SRC/{2}/x86_64-unknown-linux-gnu/libfuzzer-address/x86_64-unknown-linux-gnu/release
+{3}"##,
+            $dir, $builds, $output_dir, $installs
+        )
+    };
+}
+
+macro_rules! decoder_shim_toml_template {
+    ($command: expr, $arbitrary: expr) => {
+        format_args!(
+            r##"# Generated by `{0}`.
+[package]
+name = "libfuzzer-sys"
+version = "0.0.0"
+authors = ["Automatically generated"]
+publish = false
+edition = "2018"
+
+[dependencies]
+arbitrary = "={1}"
+
+[features]
+default = ["link_libfuzzer"]
+link_libfuzzer = []
+arbitrary-derive = ["arbitrary/derive"]
+"##,
+            $command, $arbitrary
+        )
+    };
+}
+
+macro_rules! decoder_shim_template {
+    () => {
+        format_args!(
+            r##"//! A stand-in for `libfuzzer-sys`, generated by `cargo fuzz fmt`.
+//!
+//! A fuzz target built against it decodes the input file it's given the way
+//! the real `fuzz_target!` would, and prints the `Debug` formatting of the
+//! result instead of fuzzing.
+
+pub use arbitrary;
+
+pub enum Corpus {{
+    Keep,
+    Reject,
+}}
+
+pub fn fuzzer_mutate(_data: &mut [u8], size: usize, _max_size: usize) -> usize {{
+    size
+}}
+
+#[doc(hidden)]
+pub fn read_input() -> Vec<u8> {{
+    let path = match std::env::args_os().nth(1) {{
+        Some(path) => path,
+        None => {{
+            eprintln!("usage: decoder <input>");
+            std::process::exit(2);
+        }}
+    }};
+    std::fs::read(&path).unwrap_or_else(|e| {{
+        eprintln!("error: failed to read {{:?}}: {{}}", path, e);
+        std::process::exit(2);
+    }})
+}}
+
+#[macro_export]
+macro_rules! fuzz_target {{
+    (init: $init:expr, $($rest:tt)*) => {{
+        $crate::fuzz_target!($($rest)*);
+    }};
+
+    (|$bytes:ident| $body:block) => {{
+        $crate::fuzz_target!(|$bytes: &[u8]| $body);
+    }};
+
+    (|$data:ident: &[u8]| $(-> $rty:ty)? $body:block) => {{
+        #[no_mangle]
+        pub extern "C" fn main(
+            _argc: ::std::os::raw::c_int,
+            _argv: *const *const ::std::os::raw::c_char,
+        ) -> ::std::os::raw::c_int {{
+            println!("{{:?}}", $crate::read_input());
+            0
+        }}
+    }};
+
+    (|$data:ident: $dty:ty| $(-> $rty:ty)? $body:block) => {{
+        #[no_mangle]
+        pub extern "C" fn main(
+            _argc: ::std::os::raw::c_int,
+            _argv: *const *const ::std::os::raw::c_char,
+        ) -> ::std::os::raw::c_int {{
+            use $crate::arbitrary::{{Arbitrary, Unstructured}};
+
+            let bytes = $crate::read_input();
+            let min = <$dty as Arbitrary>::size_hint(0).0;
+            if bytes.len() < min {{
+                eprintln!(
+                    "note: the fuzz target skips inputs shorter than the {{}} byte(s) `{{}}` \
+                     needs, so it never ran this one",
+                    min,
+                    stringify!($dty),
+                );
+            }}
+            match <$dty as Arbitrary>::arbitrary_take_rest(Unstructured::new(&bytes)) {{
+                Ok(data) => {{
+                    println!("{{:#?}}", data);
+                    0
+                }}
+                Err(e) => {{
+                    eprintln!("error: the input isn't a valid `{{}}`: {{}}", stringify!($dty), e);
+                    1
+                }}
+            }}
+        }}
+    }};
+}}
+
+#[macro_export]
+macro_rules! fuzz_mutator {{
+    ($($tt:tt)*) => {{}};
+}}
+
+#[macro_export]
+macro_rules! fuzz_crossover {{
+    ($($tt:tt)*) => {{}};
+}}
+"##
+        )
+    };
+}
+
+macro_rules! bench_shim_template {
+    () => {
+        format_args!(
+            r##"//! A stand-in for `libfuzzer-sys`, generated by `cargo fuzz bench`.
+//!
+//! A fuzz target built against it runs each input in the file of input paths
+//! it's given the number of times it's given, and prints how many nanoseconds
+//! those runs took altogether, or `-` if the input panicked, one line each.
+
+pub use arbitrary;
+
+use std::io::{{BufRead, BufReader}};
+use std::panic::{{self, AssertUnwindSafe}};
+use std::time::Instant;
+
+pub enum Corpus {{
+    Keep,
+    Reject,
+}}
+
+pub fn fuzzer_mutate(_data: &mut [u8], size: usize, _max_size: usize) -> usize {{
+    size
+}}
+
+fn usage() -> ! {{
+    eprintln!("usage: bench <runs> <inputs>");
+    std::process::exit(2);
+}}
+
+#[doc(hidden)]
+pub fn bench<R>(mut run: impl FnMut(&[u8]) -> R) -> std::os::raw::c_int {{
+    let mut args = std::env::args_os().skip(1);
+    let runs: u32 = match args.next().and_then(|runs| runs.to_str()?.parse().ok()) {{
+        Some(runs) => runs,
+        None => usage(),
+    }};
+    let list = match args.next() {{
+        Some(list) => list,
+        None => usage(),
+    }};
+    let list = std::fs::File::open(&list).unwrap_or_else(|e| {{
+        eprintln!("error: failed to read {{:?}}: {{}}", list, e);
+        std::process::exit(2);
+    }});
+    for path in BufReader::new(list).lines() {{
+        let path = path.expect("the list of inputs is valid UTF-8");
+        let input = std::fs::read(&path).unwrap_or_else(|e| {{
+            eprintln!("error: failed to read {{:?}}: {{}}", path, e);
+            std::process::exit(2);
+        }});
+        let mut nanos = 0;
+        let mut panicked = false;
+        for _ in 0..runs {{
+            let start = Instant::now();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {{
+                std::hint::black_box(run(std::hint::black_box(&input)));
+            }}));
+            nanos += start.elapsed().as_nanos();
+            if result.is_err() {{
+                panicked = true;
+                break;
+            }}
+        }}
+        if panicked {{
+            println!("-");
+        }} else {{
+            println!("{{}}", nanos);
+        }}
+    }}
+    0
+}}
+
+#[macro_export]
+macro_rules! fuzz_target {{
+    (init: $init:expr, $($rest:tt)*) => {{
+        $crate::fuzz_target!(@bench {{ $init; }} $($rest)*);
+    }};
+
+    (|$bytes:ident| $body:block) => {{
+        $crate::fuzz_target!(@bench {{}} |$bytes: &[u8]| $body);
+    }};
+
+    (|$data:ident: &[u8]| $(-> $rty:ty)? $body:block) => {{
+        $crate::fuzz_target!(@bench {{}} |$data: &[u8]| $(-> $rty)? $body);
+    }};
+
+    (|$data:ident: $dty:ty| $(-> $rty:ty)? $body:block) => {{
+        $crate::fuzz_target!(@bench {{}} |$data: $dty| $(-> $rty)? $body);
+    }};
+
+    (@bench $init:block |$data:ident: &[u8]| $(-> $rty:ty)? $body:block) => {{
+        #[no_mangle]
+        pub extern "C" fn main(
+            _argc: ::std::os::raw::c_int,
+            _argv: *const *const ::std::os::raw::c_char,
+        ) -> ::std::os::raw::c_int {{
+            $init
+            $crate::bench(|$data: &[u8]| $(-> $rty)? $body)
+        }}
+    }};
+
+    (@bench $init:block |$data:ident: $dty:ty| $(-> $rty:ty)? $body:block) => {{
+        #[no_mangle]
+        pub extern "C" fn main(
+            _argc: ::std::os::raw::c_int,
+            _argv: *const *const ::std::os::raw::c_char,
+        ) -> ::std::os::raw::c_int {{
+            use $crate::arbitrary::{{Arbitrary, Unstructured}};
+
+            $init
+            let mut run = |$data: $dty| $(-> $rty)? $body;
+            $crate::bench(move |bytes: &[u8]| {{
+                // Like the real `fuzz_target!`, skip the inputs that are too
+                // short to decode.
+                if bytes.len() < <$dty as Arbitrary>::size_hint(0).0 {{
+                    return;
+                }}
+                if let Ok(data) = <$dty as Arbitrary>::arbitrary_take_rest(Unstructured::new(bytes)) {{
+                    let _ = run(data);
+                }}
+            }})
+        }}
+    }};
+}}
+
+#[macro_export]
+macro_rules! fuzz_mutator {{
+    ($($tt:tt)*) => {{}};
+}}
+
+#[macro_export]
+macro_rules! fuzz_crossover {{
+    ($($tt:tt)*) => {{}};
+}}
+"##
+        )
+    };
+}
+
+macro_rules! miri_shim_template {
+    () => {
+        format_args!(
+            r##"//! A stand-in for `libfuzzer-sys`, generated by `cargo fuzz miri`.
+//!
+//! A fuzz target built against it runs once on the input file it's given,
+//! under Miri, which reports any undefined behavior the run has. It exits
+//! with 101 if the fuzz target panicked. Without an input it does nothing,
+//! which builds it before any input's run is timed.
+
+pub use arbitrary;
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{{self, AssertUnwindSafe}};
+
+pub enum Corpus {{
+    Keep,
+    Reject,
+}}
+
+pub fn fuzzer_mutate(_data: &mut [u8], size: usize, _max_size: usize) -> usize {{
+    size
+}}
+
+#[doc(hidden)]
+pub fn run<R>(argc: isize, argv: *const *const u8, run: impl FnOnce(&[u8]) -> R) -> isize {{
+    if argc < 2 {{
+        return 0;
+    }}
+    // Miri doesn't set up the runtime for `#![no_main]` binaries, so the
+    // input's path comes from `argv` rather than `std::env::args`.
+    let path = unsafe {{ CStr::from_ptr(*argv.add(1) as *const c_char) }};
+    let path = path.to_string_lossy().into_owned();
+    let input = match std::fs::read(&path) {{
+        Ok(input) => input,
+        Err(e) => {{
+            eprintln!("error: failed to read {{:?}}: {{}}", path, e);
+            return 2;
+        }}
+    }};
+    match panic::catch_unwind(AssertUnwindSafe(|| {{
+        let _ = run(&input);
+    }})) {{
+        Ok(()) => 0,
+        Err(_) => 101,
+    }}
+}}
+
+#[macro_export]
+macro_rules! fuzz_target {{
+    (init: $init:expr, $($rest:tt)*) => {{
+        $crate::fuzz_target!(@miri {{ $init; }} $($rest)*);
+    }};
+
+    (|$bytes:ident| $body:block) => {{
+        $crate::fuzz_target!(@miri {{}} |$bytes: &[u8]| $body);
+    }};
+
+    (|$data:ident: &[u8]| $(-> $rty:ty)? $body:block) => {{
+        $crate::fuzz_target!(@miri {{}} |$data: &[u8]| $(-> $rty)? $body);
+    }};
+
+    (|$data:ident: $dty:ty| $(-> $rty:ty)? $body:block) => {{
+        $crate::fuzz_target!(@miri {{}} |$data: $dty| $(-> $rty)? $body);
+    }};
+
+    (@miri $init:block |$data:ident: &[u8]| $(-> $rty:ty)? $body:block) => {{
+        #[no_mangle]
+        pub fn miri_start(argc: isize, argv: *const *const u8) -> isize {{
+            $init
+            $crate::run(argc, argv, |$data: &[u8]| $(-> $rty)? $body)
+        }}
+    }};
+
+    (@miri $init:block |$data:ident: $dty:ty| $(-> $rty:ty)? $body:block) => {{
+        #[no_mangle]
+        pub fn miri_start(argc: isize, argv: *const *const u8) -> isize {{
+            use $crate::arbitrary::{{Arbitrary, Unstructured}};
+
+            $init
+            let run = |$data: $dty| $(-> $rty)? $body;
+            $crate::run(argc, argv, move |bytes: &[u8]| {{
+                // Like the real `fuzz_target!`, skip the inputs that are too
+                // short to decode.
+                if bytes.len() < <$dty as Arbitrary>::size_hint(0).0 {{
+                    return;
+                }}
+                if let Ok(data) = <$dty as Arbitrary>::arbitrary_take_rest(Unstructured::new(bytes)) {{
+                    let _ = run(data);
+                }}
+            }})
+        }}
+    }};
+}}
+
+#[macro_export]
+macro_rules! fuzz_mutator {{
+    ($($tt:tt)*) => {{}};
+}}
+
+#[macro_export]
+macro_rules! fuzz_crossover {{
+    ($($tt:tt)*) => {{}};
+}}
+"##
+        )
+    };
+}
+
+macro_rules! package_repro_script_template {
+    ($command: expr) => {
+        format_args!(
+            r##"#!/bin/sh
+# Generated by `cargo fuzz package-repro`.
+cd "$(dirname "$0")"
+{0}
+"##,
+            $command
+        )
+    };
+}
+
+macro_rules! package_repro_readme_template {
+    ($target: expr, $krate: expr, $command: expr, $triple: expr, $sanitizer: expr, $rustc: expr) => {
+        format_args!(
+            r##"This archive reproduces a crash that fuzzing found in `{1}`, with its
+`{0}` fuzz target and the input that crashes it.
+
+To reproduce the crash, run `./repro.sh`, which runs:
+
+    {2}
+
+The fuzz target was built for {3} with {4} by rustc {5}. Run it on a
+machine of the same kind; installing Rust or cargo-fuzz isn't needed.
+"##,
+            $target, $krate, $command, $triple, $sanitizer, $rustc
+        )
+    };
+}
+
+macro_rules! bin_stdin_target_template {
+    ($bin: expr) => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::io::Write;
+use std::process::{{Command, ExitStatus, Output, Stdio}};
+
+// Runs the fuzzed crate's `{0}` binary on every input, fed to it over stdin.
+// `cargo fuzz build` builds it next to this fuzz target, because fuzz.toml
+// names it as the target's `bin`. Only being killed by a signal or panicking
+// counts as a crash. The fuzzer gets no coverage feedback from the binary, so
+// fuzzing the functions it calls directly finds bugs much faster, where that's
+// an option.
+fuzz_target!(|data: &[u8]| {{
+    let mut child = command()
+        .stdin(Stdio::piped())
+        .spawn()
+        .expect("failed to run `{0}`");
+    // The binary may exit before it has read all of its input.
+    let _ = child.stdin.take().unwrap().write_all(data);
+    check(child.wait_with_output().unwrap());
+}});
+{1}"##,
+            $bin,
+            bin_target_helpers_template!($bin)
+        )
+    };
+}
+
+macro_rules! bin_file_target_template {
+    ($bin: expr) => {
+        format_args!(
+            r##"#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::io::Write;
+use std::process::{{Command, ExitStatus, Output, Stdio}};
+
+// Runs the fuzzed crate's `{0}` binary on every input, with the path of a file
+// that holds it as its argument. `cargo fuzz build` builds it next to this
+// fuzz target, because fuzz.toml names it as the target's `bin`. Only being
+// killed by a signal or panicking counts as a crash. The fuzzer gets no
+// coverage feedback from the binary, so fuzzing the functions it calls
+// directly finds bugs much faster, where that's an option.
+fuzz_target!(|data: &[u8]| {{
+    // A file of this process's own, so that fuzzing jobs don't overwrite each
+    // other's inputs.
+    let path = std::env::temp_dir().join(format!("cargo-fuzz-{0}-{{}}", std::process::id()));
+    std::fs::write(&path, data).unwrap();
+    let output = command()
+        .arg(&path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run `{0}`");
+    check(output);
+}});
+{1}"##,
+            $bin,
+            bin_target_helpers_template!($bin)
+        )
+    };
+}
+
+macro_rules! bin_target_helpers_template {
+    ($bin: expr) => {
+        format_args!(
+            r##"
+/// The command that runs `{0}`, with its output captured and sanitizers set to
+/// abort on errors, so that they're told apart from ordinary failures.
+fn command() -> Command {{
+    let exe = std::env::current_exe().unwrap();
+    let bin = exe.with_file_name(format!("{0}{{}}", std::env::consts::EXE_SUFFIX));
+    let mut cmd = Command::new(bin);
+    cmd.stdout(Stdio::null()).stderr(Stdio::piped());
+    for var in ["ASAN_OPTIONS", "MSAN_OPTIONS", "TSAN_OPTIONS"] {{
+        let options = format!("{{}}:abort_on_error=1", std::env::var(var).unwrap_or_default());
+        cmd.env(var, options.trim_start_matches(':'));
+    }}
+    cmd
+}}
+
+/// Crash if `{0}` did, printing what it printed.
+fn check(output: Output) {{
+    if crashed(output.status) {{
+        std::io::stderr().write_all(&output.stderr).unwrap();
+        panic!("`{0}` crashed: {{}}", output.status);
+    }}
+}}
+
+fn crashed(status: ExitStatus) -> bool {{
+    match status.code() {{
+        // Killed by a signal, like the sanitizers' aborts.
+        None => true,
+        // Rust's exit code for a panic.
+        Some(101) => true,
+        // Windows exception codes, like 0xC0000005 for an access violation.
+        Some(code) => cfg!(windows) && code as u32 >= 0xC000_0000,
+    }}
+}}
+"##,
+            $bin
+        )
+    };
+}
+
+macro_rules! bash_completions_template {
+    () => {
+        format_args!(
+            r##"# Completions for `cargo fuzz` and `cargo-fuzz`, from `cargo fuzz completions
+# bash`. Source this after Cargo's own completions.
+
+_cargo_fuzz() {{
+    local IFS=$'\n'
+    local candidates
+    candidates="$(cargo-fuzz __complete -- "${{COMP_WORDS[@]:0:COMP_CWORD+1}}" 2>/dev/null)"
+    COMPREPLY=($(compgen -W "$candidates" -- "${{COMP_WORDS[COMP_CWORD]}}"))
+}}
+
+# Cargo's completions don't complete the arguments of external subcommands.
+_cargo_fuzz_cargo() {{
+    local i=1
+    if [[ ${{COMP_WORDS[1]}} == +* ]]; then
+        i=2
+    fi
+    if [[ $COMP_CWORD -gt $i && ${{COMP_WORDS[i]}} == fuzz ]]; then
+        _cargo_fuzz
+    elif declare -F _cargo >/dev/null; then
+        _cargo "$@"
+    fi
+}}
+
+complete -F _cargo_fuzz -o bashdefault -o default cargo-fuzz
+complete -F _cargo_fuzz_cargo -o bashdefault -o default cargo
+"##
+        )
+    };
+}
+
+macro_rules! zsh_completions_template {
+    () => {
+        format_args!(
+            r##"#compdef cargo-fuzz
+# Completions for `cargo fuzz` and `cargo-fuzz`, from `cargo fuzz completions
+# zsh`. Cargo's completions call `_cargo-fuzz` for `cargo fuzz`.
+
+_cargo-fuzz() {{
+    local -a candidates
+    candidates=("${{(@f)$(cargo-fuzz __complete -- "${{(@)words[1,CURRENT]}}" 2>/dev/null)}}")
+    if [[ -n ${{candidates[1]}} ]]; then
+        compadd -a candidates
+    else
+        _files
+    fi
+}}
+
+if [[ $funcstack[1] == _cargo-fuzz ]]; then
+    _cargo-fuzz "$@"
+else
+    compdef _cargo-fuzz cargo-fuzz
+fi
+"##
+        )
+    };
+}
+
+macro_rules! fish_completions_template {
+    () => {
+        format_args!(
+            r##"# Completions for `cargo fuzz` and `cargo-fuzz`, from `cargo fuzz completions
+# fish`.
+
+function __cargo_fuzz_complete
+    set -l candidates (cargo-fuzz __complete -- (commandline -opc) (commandline -ct) 2>/dev/null)
+    if set -q candidates[1]
+        printf '%s\n' $candidates
+    else
+        __fish_complete_path (commandline -ct)
+    end
+end
+
+complete -c cargo-fuzz -f -a '(__cargo_fuzz_complete)'
+complete -c cargo -n '__fish_seen_subcommand_from fuzz' -f -a '(__cargo_fuzz_complete)'
+"##
+        )
+    };
+}
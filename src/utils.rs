@@ -5,7 +5,487 @@ pub fn default_target() -> &'static str {
 }
 
 /// The default target to pass to cargo, to workaround issue #11.
-#[cfg(not(target_os = "macos"))]
+#[cfg(windows)]
+pub fn default_target() -> &'static str {
+    "x86_64-pc-windows-msvc"
+}
+
+/// The default target to pass to cargo, to workaround issue #11.
+#[cfg(not(any(target_os = "macos", windows)))]
 pub fn default_target() -> &'static str {
     "x86_64-unknown-linux-gnu"
 }
+
+/// The rustup toolchain that fuzz builds are pinned to, if any.
+static TOOLCHAIN: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+/// Pin the `cargo` and `rustc` that cargo-fuzz runs from then on to the rustup
+/// toolchain `toolchain`. A process only ever builds with one toolchain, so
+/// pinning another one after that is an error.
+pub fn pin_toolchain(toolchain: &str) -> anyhow::Result<()> {
+    let pinned = TOOLCHAIN.get_or_init(|| toolchain.to_string());
+    if pinned != toolchain {
+        anyhow::bail!(
+            "can't build with the toolchain `{}`, since fuzz builds are already pinned to `{}`",
+            toolchain,
+            pinned
+        );
+    }
+    Ok(())
+}
+
+/// A command that runs `program`, `cargo` or `rustc`, with the pinned
+/// toolchain, if any.
+///
+/// The toolchain is passed to each command rather than set in cargo-fuzz's
+/// own environment, which isn't safe to change once there are other threads.
+pub fn toolchain_command(program: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new(program);
+    if let Some(toolchain) = TOOLCHAIN.get() {
+        cmd.env("RUSTUP_TOOLCHAIN", toolchain);
+    }
+    cmd
+}
+
+/// The output of `rustc -vV`, or `None` if `rustc` couldn't be run.
+fn rustc_verbose_version() -> Option<&'static str> {
+    // Cached separately once a toolchain is pinned, so that asking before then
+    // doesn't answer for the wrong `rustc` afterwards.
+    static DEFAULT: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+    static PINNED: std::sync::OnceLock<Option<String>> = std::sync::OnceLock::new();
+    let version = if TOOLCHAIN.get().is_some() {
+        &PINNED
+    } else {
+        &DEFAULT
+    };
+    version
+        .get_or_init(|| {
+            let output = toolchain_command("rustc").arg("-vV").output().ok()?;
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        })
+        .as_deref()
+}
+
+/// The target triple of the host, according to `rustc`.
+pub fn host_triple() -> Option<&'static str> {
+    rustc_verbose_version()?
+        .lines()
+        .find_map(|l| l.strip_prefix("host: "))
+}
+
+/// The release of `rustc`, like `1.62.0` or `1.62.0-nightly`.
+pub fn rustc_release() -> Option<&'static str> {
+    rustc_verbose_version()?
+        .lines()
+        .find_map(|l| l.strip_prefix("release: "))
+}
+
+/// The minor version of `rustc`, like 62 for `1.62.0` or `1.62.0-nightly`.
+pub fn rustc_minor_version() -> Option<u32> {
+    rustc_release()?.split('.').nth(1)?.parse().ok()
+}
+
+/// The sysroot of `rustc`, where the standard library and rustup components
+/// for each target are installed.
+pub fn sysroot() -> Option<std::path::PathBuf> {
+    let output = toolchain_command("rustc")
+        .arg("--print=sysroot")
+        .output()
+        .ok()?;
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(sysroot).filter(|s| !s.is_empty()).map(Into::into)
+}
+
+/// Find an LLVM tool such as `llvm-profdata`, preferring the copy shipped with
+/// the `llvm-tools-preview` rustup component, since it matches the LLVM
+/// version used by rustc.
+pub fn llvm_tool(name: &str) -> anyhow::Result<std::path::PathBuf> {
+    use anyhow::Context;
+    use std::process::Command;
+
+    if let (Some(sysroot), Some(host)) = (sysroot(), host_triple()) {
+        let path = sysroot
+            .join("lib")
+            .join("rustlib")
+            .join(host)
+            .join("bin")
+            .join(format!("{}{}", name, std::env::consts::EXE_SUFFIX));
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+
+    Command::new(name)
+        .arg("--version")
+        .output()
+        .with_context(|| {
+            format!(
+                "could not find `{}`; install it with `rustup component add llvm-tools-preview`",
+                name
+            )
+        })?;
+    Ok(name.into())
+}
+
+/// Whether `name` matches a glob `pattern`, where `*` matches any run of
+/// characters and `?` matches any single character.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where to resume matching after the last `*`, if the attempt fails.
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// The directory of the MSVC toolset's AddressSanitizer runtime DLLs for a
+/// Windows target, which fuzz targets built with `-Zsanitizer=address` load
+/// when they start. Visual Studio's developer prompts set
+/// `$VCToolsInstallDir`, but don't put this directory on `PATH`.
+pub fn msvc_asan_runtime_dir(triple: &str) -> Option<std::path::PathBuf> {
+    fn msvc_arch(triple: &str) -> Option<&'static str> {
+        match triple.split('-').next()? {
+            "x86_64" => Some("x64"),
+            "i586" | "i686" => Some("x86"),
+            "aarch64" => Some("arm64"),
+            _ => None,
+        }
+    }
+
+    if !triple.ends_with("-windows-msvc") {
+        return None;
+    }
+    let tools = std::env::var_os("VCToolsInstallDir")?;
+    let host = msvc_arch(host_triple()?)?;
+    let dir = std::path::Path::new(&tools)
+        .join("bin")
+        .join(format!("Host{}", host))
+        .join(msvc_arch(triple)?);
+    Some(dir).filter(|dir| dir.is_dir())
+}
+
+/// Find an executable on `PATH`.
+pub fn find_on_path(name: &str) -> Option<std::path::PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Find the `honggfuzz` executable, either at `$HONGGFUZZ` or on `PATH`.
+pub fn honggfuzz() -> anyhow::Result<std::path::PathBuf> {
+    if let Some(honggfuzz) = std::env::var_os("HONGGFUZZ") {
+        return Ok(honggfuzz.into());
+    }
+    find_on_path("honggfuzz").ok_or_else(|| {
+        anyhow::anyhow!("could not find `honggfuzz` on PATH; install honggfuzz or set $HONGGFUZZ")
+    })
+}
+
+/// Find honggfuzz's `libhfuzz.a` and `libhfcommon.a`, which fuzz targets link
+/// against instead of libFuzzer.
+///
+/// They're looked for in `$HONGGFUZZ_LIB_DIR`, then next to the `honggfuzz`
+/// executable in its build tree, then in the `lib` directory it was installed
+/// alongside.
+pub fn honggfuzz_libs() -> anyhow::Result<(std::path::PathBuf, std::path::PathBuf)> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = std::env::var_os("HONGGFUZZ_LIB_DIR") {
+        let dir = std::path::PathBuf::from(dir);
+        candidates.push((dir.join("libhfuzz.a"), dir.join("libhfcommon.a")));
+    }
+    if let Some(dir) = honggfuzz()
+        .ok()
+        .and_then(|h| h.parent().map(|d| d.to_path_buf()))
+    {
+        candidates.push((
+            dir.join("libhfuzz").join("libhfuzz.a"),
+            dir.join("libhfcommon").join("libhfcommon.a"),
+        ));
+        let lib = dir.join("..").join("lib");
+        candidates.push((lib.join("libhfuzz.a"), lib.join("libhfcommon.a")));
+    }
+    candidates
+        .into_iter()
+        .find(|(hfuzz, common)| hfuzz.is_file() && common.is_file())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "could not find honggfuzz's `libhfuzz.a` and `libhfcommon.a`; \
+                 set $HONGGFUZZ_LIB_DIR to the directory containing them"
+            )
+        })
+}
+
+/// Format bytes as a Rust byte string literal, broken across lines with
+/// backslash continuations so that each line is at most `width` characters.
+pub fn byte_string_literal(bytes: &[u8], width: usize) -> String {
+    let mut literal = String::from("b\"");
+    let mut line_len = literal.len();
+    for &b in bytes {
+        // A backslash continuation also skips the next line's leading
+        // whitespace, so escape any whitespace starting a line.
+        let escaped = match b {
+            b' ' if line_len == 0 => "\\x20".to_string(),
+            b'"' => "\\\"".to_string(),
+            b'\\' => "\\\\".to_string(),
+            b'\n' => "\\n".to_string(),
+            b'\t' => "\\t".to_string(),
+            b'\r' => "\\r".to_string(),
+            0x20..=0x7e => (b as char).to_string(),
+            _ => format!("\\x{:02x}", b),
+        };
+        if line_len + escaped.len() > width - 1 {
+            literal.push_str("\\\n");
+            line_len = 0;
+            if b == b' ' {
+                literal.push_str("\\x20");
+                line_len += 4;
+                continue;
+            }
+        }
+        line_len += escaped.len();
+        literal.push_str(&escaped);
+    }
+    literal.push('"');
+    literal
+}
+
+/// MD5's per-round constants, from RFC 1321: the integer part of
+/// `abs(sin(i + 1)) * 2^32` for each round `i`.
+const MD5_K: [u32; 64] = [
+    0xd76a_a478,
+    0xe8c7_b756,
+    0x2420_70db,
+    0xc1bd_ceee,
+    0xf57c_0faf,
+    0x4787_c62a,
+    0xa830_4613,
+    0xfd46_9501,
+    0x6980_98d8,
+    0x8b44_f7af,
+    0xffff_5bb1,
+    0x895c_d7be,
+    0x6b90_1122,
+    0xfd98_7193,
+    0xa679_438e,
+    0x49b4_0821,
+    0xf61e_2562,
+    0xc040_b340,
+    0x265e_5a51,
+    0xe9b6_c7aa,
+    0xd62f_105d,
+    0x0244_1453,
+    0xd8a1_e681,
+    0xe7d3_fbc8,
+    0x21e1_cde6,
+    0xc337_07d6,
+    0xf4d5_0d87,
+    0x455a_14ed,
+    0xa9e3_e905,
+    0xfcef_a3f8,
+    0x676f_02d9,
+    0x8d2a_4c8a,
+    0xfffa_3942,
+    0x8771_f681,
+    0x6d9d_6122,
+    0xfde5_380c,
+    0xa4be_ea44,
+    0x4bde_cfa9,
+    0xf6bb_4b60,
+    0xbebf_bc70,
+    0x289b_7ec6,
+    0xeaa1_27fa,
+    0xd4ef_3085,
+    0x0488_1d05,
+    0xd9d4_d039,
+    0xe6db_99e5,
+    0x1fa2_7cf8,
+    0xc4ac_5665,
+    0xf429_2244,
+    0x432a_ff97,
+    0xab94_23a7,
+    0xfc93_a039,
+    0x655b_59c3,
+    0x8f0c_cc92,
+    0xffef_f47d,
+    0x8584_5dd1,
+    0x6fa8_7e4f,
+    0xfe2c_e6e0,
+    0xa301_4314,
+    0x4e08_11a1,
+    0xf753_7e82,
+    0xbd3a_f235,
+    0x2ad7_d2bb,
+    0xeb86_d391,
+];
+
+/// The MD5 digest of `data`, in hex, which Coveralls identifies source files
+/// by.
+pub fn md5_hex(data: &[u8]) -> String {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+    for chunk in message.chunks(64) {
+        let words: Vec<u32> = chunk
+            .chunks(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(MD5_K[i])
+                .wrapping_add(words[g])
+                .rotate_left(SHIFTS[(i / 16) * 4 + i % 4]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+    state
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// POST `body` to `url` with curl, sending it as `data`, like `--data-binary
+/// @-`. The URL goes in a config file rather than on the command line, where
+/// other users could see a token in it.
+pub fn curl_post(url: &str, data: &[&str], body: &[u8]) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    if url.chars().any(char::is_control) {
+        anyhow::bail!("the URL to post to has control characters in it");
+    }
+    // A quoted value in a curl config takes backslash escapes.
+    let url = url.replace('\\', "\\\\").replace('"', "\\\"");
+    let mut config = tempfile::NamedTempFile::new().context("failed to create temp file")?;
+    writeln!(config, "url = \"{}\"", url).context("failed to write the curl config")?;
+    let mut cmd = Command::new("curl");
+    cmd.arg("--silent")
+        .arg("--show-error")
+        .arg("--fail")
+        .arg("--config")
+        .arg(config.path())
+        .args(data)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null());
+    let mut child = cmd
+        .spawn()
+        .with_context(|| format!("failed to run {:?}; is curl installed?", cmd))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(body)
+        .context("failed to send the request to curl")?;
+    let status = child
+        .wait()
+        .with_context(|| format!("failed to wait on child process for command: {:?}", cmd))?;
+    if !status.success() {
+        anyhow::bail!("curl exited with {}", status);
+    }
+    Ok(())
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64, with padding.
+pub fn encode_base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, b)| bits | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(bits >> (18 - 6 * i)) as usize & 0x3f] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decode standard, padded base64, or `None` if `text` isn't that.
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.as_bytes().chunks(4) {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 {
+            return None;
+        }
+        let mut bits = 0u32;
+        for (i, c) in chunk[..4 - padding].iter().enumerate() {
+            let value = BASE64.iter().position(|b| b == c)? as u32;
+            bits |= value << (18 - 6 * i);
+        }
+        out.extend_from_slice(&bits.to_be_bytes()[1..4 - padding]);
+    }
+    // Padding only ends the text.
+    if text.trim_end_matches('=').contains('=') {
+        return None;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::md5_hex;
+
+    #[test]
+    fn md5_known_answers() {
+        // From RFC 1321's test suite.
+        assert_eq!(md5_hex(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5_hex(b"abc"), "900150983cd24fb0d6963f7d28e17f72");
+        // Long enough to take two blocks.
+        assert_eq!(
+            md5_hex("1234567890".repeat(8).as_bytes()),
+            "57edf4a22be3c955ac49da2e2107b67a"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use self::project::*;
 use assert_cmd::prelude::*;
 use predicates::prelude::*;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 fn cargo_fuzz() -> Command {
@@ -16,6 +16,15 @@ fn help() {
     cargo_fuzz().arg("help").assert().success();
 }
 
+#[test]
+fn doctor() {
+    // The toolchain may not be nightly, so only check what's reported.
+    cargo_fuzz().arg("doctor").assert().stdout(
+        predicate::str::contains("toolchain: rustc ")
+            .and(predicate::str::contains("sanitizer runtimes: ")),
+    );
+}
+
 #[test]
 fn init() {
     let project = project("init").build();
@@ -24,6 +33,7 @@ fn init() {
     assert!(project.fuzz_cargo_toml().is_file());
     assert!(project.fuzz_targets_dir().is_dir());
     assert!(project.fuzz_target_path("fuzz_target_1").is_file());
+    assert!(project.fuzz_dir().join("src").join("lib.rs").is_file());
     project
         .cargo_fuzz()
         .arg("run")
@@ -58,6 +68,99 @@ fn init_with_target() {
         .success();
 }
 
+#[test]
+fn init_with_template() {
+    for (template, expected) in &[
+        ("arbitrary", "#[derive(Arbitrary, Debug)]"),
+        (
+            "roundtrip",
+            "value changed after an encode/decode roundtrip",
+        ),
+        ("grammar", "fn expr(u: &mut Unstructured<'_>"),
+    ] {
+        let project = project(&format!("init_with_{}_template", template)).build();
+        project
+            .cargo_fuzz()
+            .arg("init")
+            .arg("--template")
+            .arg(template)
+            .assert()
+            .success();
+        let target = fs::read_to_string(project.fuzz_target_path("fuzz_target_1")).unwrap();
+        assert!(target.contains(expected));
+
+        let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+        assert_eq!(
+            cargo_toml.contains(r#"features = ["arbitrary-derive"]"#),
+            *template == "arbitrary"
+        );
+
+        project
+            .cargo_fuzz()
+            .arg("run")
+            .arg("fuzz_target_1")
+            .arg("--")
+            .arg("-runs=100")
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn init_no_std() {
+    let project = project("init_no_std")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "init_no_std"
+                version = "1.0.0"
+
+                [features]
+                default = ["device"]
+                device = []
+            "#,
+        )
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                #![no_std]
+
+                #[cfg(feature = "device")]
+                compile_error!("the `device` feature only builds for the device");
+
+                pub fn checksum(data: &[u8]) -> u8 {
+                    data.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+                }
+            "#,
+        )
+        .build();
+    project.cargo_fuzz().arg("init").assert().success();
+
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(cargo_toml.contains("default-features = false"));
+    let target = fs::read_to_string(project.fuzz_target_path("fuzz_target_1")).unwrap();
+    assert!(target.contains("`init_no_std` is `no_std`"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("second")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("second")).unwrap();
+    assert!(target.contains("`init_no_std` is `no_std`"));
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("fuzz_target_1")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+}
+
 #[test]
 fn init_twice() {
     let project = project("init_twice").build();
@@ -98,6 +201,169 @@ fn init_finds_parent_project() {
     assert!(project.fuzz_target_path("fuzz_target_1").is_file());
 }
 
+fn workspace_project(name: &str) -> Project {
+    project(name)
+        .file(
+            "Cargo.toml",
+            r#"
+                [workspace]
+                members = ["member"]
+            "#,
+        )
+        .file(
+            Path::new("member").join("Cargo.toml"),
+            r#"
+                [package]
+                name = "member"
+                version = "1.0.0"
+            "#,
+        )
+        .file(Path::new("member").join("src").join("lib.rs"), "")
+        .build()
+}
+
+#[test]
+fn init_in_workspace_member() {
+    let project = workspace_project("init_in_workspace_member");
+    let member = project.root().join("member");
+    project
+        .cargo_fuzz()
+        .current_dir(&member)
+        .arg("init")
+        .assert()
+        .stderr(predicates::str::contains(
+            "Added `member/fuzz` to `workspace.exclude`",
+        ))
+        .success();
+
+    let workspace = fs::read_to_string(project.root().join("Cargo.toml")).unwrap();
+    assert!(workspace.contains(r#"exclude = ["member/fuzz"]"#));
+    let fuzz_cargo_toml = fs::read_to_string(member.join("fuzz").join("Cargo.toml")).unwrap();
+    assert!(fuzz_cargo_toml.contains("[workspace]"));
+}
+
+#[test]
+fn init_add_to_workspace() {
+    let project = workspace_project("init_add_to_workspace");
+    let member = project.root().join("member");
+    project
+        .cargo_fuzz()
+        .current_dir(&member)
+        .arg("init")
+        .arg("--add-to-workspace")
+        .assert()
+        .success();
+
+    let workspace = fs::read_to_string(project.root().join("Cargo.toml")).unwrap();
+    assert!(workspace.contains(r#"members = ["member", "member/fuzz"]"#));
+    let fuzz_cargo_toml = fs::read_to_string(member.join("fuzz").join("Cargo.toml")).unwrap();
+    assert!(!fuzz_cargo_toml.contains("[workspace]"));
+
+    // Initializing from the workspace root has no package to fuzz.
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .assert()
+        .stderr(predicates::str::contains("is a virtual workspace manifest"))
+        .failure();
+}
+
+#[test]
+fn init_with_custom_libfuzzer() {
+    let project = project("init_with_custom_libfuzzer").build();
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--template")
+        .arg("arbitrary")
+        .arg("--libfuzzer-sys")
+        .arg("https://github.com/me/libfuzzer#patched")
+        .arg("--libfuzzer-archive")
+        .arg("../libFuzzer.a")
+        .assert()
+        .success();
+
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(cargo_toml.contains(
+        r#"libfuzzer-sys = { git = "https://github.com/me/libfuzzer", rev = "patched", features = ["arbitrary-derive"] }"#
+    ));
+    let config = fs::read_to_string(project.fuzz_dir().join("fuzz.toml")).unwrap();
+    assert_eq!(config, "libfuzzer-archive = \"../libFuzzer.a\"\n");
+
+    // The archive has to exist before anything is built.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .assert()
+        .stderr(predicate::str::contains("the libFuzzer archive"))
+        .stderr(predicate::str::contains("libFuzzer.a doesn't exist"))
+        .failure();
+}
+
+#[test]
+fn init_with_libfuzzer_sys_path() {
+    let project = project("init_with_libfuzzer_sys_path").build();
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--libfuzzer-sys")
+        .arg("../../libfuzzer")
+        .assert()
+        .success();
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(cargo_toml.contains(r#"libfuzzer-sys = { path = "../../libfuzzer" }"#));
+}
+
+#[test]
+fn init_vcs() {
+    let project = project("init_vcs").build();
+    let gitignore = project.fuzz_dir().join(".gitignore");
+    let gitattributes = project.fuzz_dir().join(".gitattributes");
+
+    project.cargo_fuzz().arg("init").assert().success();
+    let ignored = fs::read_to_string(&gitignore).unwrap();
+    assert!(ignored.lines().any(|line| line == "corpus"));
+    assert!(ignored.lines().any(|line| line == "artifacts"));
+    let attributes = fs::read_to_string(&gitattributes).unwrap();
+    assert!(attributes.contains("corpus/** binary\n"));
+    assert!(attributes.contains("artifacts/** binary\n"));
+
+    fs::remove_dir_all(project.fuzz_dir()).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--lfs-corpus")
+        .assert()
+        .stderr(predicate::str::contains("git lfs install"))
+        .success();
+    let ignored = fs::read_to_string(&gitignore).unwrap();
+    assert!(!ignored.lines().any(|line| line == "corpus"));
+    assert!(ignored.lines().any(|line| line == "artifacts"));
+    let attributes = fs::read_to_string(&gitattributes).unwrap();
+    assert!(attributes.contains("corpus/** filter=lfs diff=lfs merge=lfs -text\n"));
+
+    fs::remove_dir_all(project.fuzz_dir()).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--vcs")
+        .arg("none")
+        .arg("--lfs-corpus")
+        .assert()
+        .stderr(predicate::str::contains("`--lfs-corpus` needs `--vcs git`"))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("init")
+        .arg("--vcs")
+        .arg("none")
+        .assert()
+        .success();
+    assert!(project.fuzz_cargo_toml().is_file());
+    assert!(!gitignore.exists());
+    assert!(!gitattributes.exists());
+}
+
 #[test]
 fn add() {
     let project = project("add").with_fuzz().build();
@@ -139,488 +405,6825 @@ fn add_twice() {
         .arg("add")
         .arg("new_fuzz_target")
         .assert()
-        .stderr(
-            predicate::str::contains("could not add target")
-                .and(predicate::str::contains("File exists (os error 17)")),
-        )
+        .stderr(predicate::str::contains(
+            "a fuzz target named `new_fuzz_target` already exists",
+        ))
         .failure();
 }
 
 #[test]
-fn list() {
-    let project = project("add").with_fuzz().build();
-
-    // Create some targets.
-    project.cargo_fuzz().arg("add").arg("c").assert().success();
-    project.cargo_fuzz().arg("add").arg("b").assert().success();
-    project.cargo_fuzz().arg("add").arg("a").assert().success();
-
-    // Make sure that we can list our targets, and that they're always sorted.
-    project
-        .cargo_fuzz()
-        .arg("list")
-        .assert()
-        .stdout("a\nb\nc\n")
-        .success();
-}
-
-#[test]
-fn run_no_crash() {
-    let project = project("run_no_crash")
+fn add_arbitrary() {
+    let project = project("add_arbitrary")
         .with_fuzz()
-        .fuzz_target(
-            "no_crash",
+        .file(
+            "Cargo.toml",
             r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+                [package]
+                name = "add_arbitrary"
+                version = "1.0.0"
 
-                fuzz_target!(|data: &[u8]| {
-                    run_no_crash::pass_fuzzing(data);
-                });
+                [dependencies]
+                arbitrary = { version = "0.4", features = ["derive"], optional = true }
+            "#,
+        )
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+                #[derive(Debug)]
+                pub struct Point {
+                    pub x: u8,
+                    pub y: u8,
+                }
             "#,
         )
         .build();
-
     project
         .cargo_fuzz()
+        .arg("add")
+        .arg("point")
+        .arg("--arbitrary")
+        .arg("Point")
+        .assert()
+        .stderr(predicates::str::contains("--example point_debug"))
+        .success();
+
+    let target = fs::read_to_string(project.fuzz_target_path("point")).unwrap();
+    assert!(target.contains("fuzz_target!(|input: add_arbitrary::Point|"));
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(cargo_toml.contains("features = [\"arbitrary\"]"));
+
+    let input = project.root().join("input");
+    fs::write(&input, [1, 2]).unwrap();
+    Command::new("cargo")
         .arg("run")
-        .arg("no_crash")
+        .arg("--manifest-path")
+        .arg(project.fuzz_cargo_toml())
+        .arg("--example")
+        .arg("point_debug")
         .arg("--")
-        .arg("-runs=1000")
+        .arg(&input)
+        .env("CARGO_TARGET_DIR", target_tests().join("target"))
         .assert()
-        .stderr(predicate::str::contains("Done 1000 runs"))
+        .stdout(predicates::str::contains("x: 1").and(predicates::str::contains("y: 2")))
         .success();
 }
 
 #[test]
-fn run_with_crash() {
-    let project = project("run_with_crash")
+fn add_for_function() {
+    let project = project("add_for_function")
         .with_fuzz()
-        .fuzz_target(
-            "yes_crash",
+        .file(
+            Path::new("src").join("lib.rs"),
             r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+                pub mod header;
+            "#,
+        )
+        .file(
+            Path::new("src").join("header.rs"),
+            r#"
+                use std::collections::BTreeMap;
 
-                fuzz_target!(|data: &[u8]| {
-                    run_with_crash::fail_fuzzing(data);
-                });
+                pub fn parse(data: &[u8]) -> Option<u8> {
+                    data.first().copied()
+                }
+
+                pub fn name(name: &str) -> usize {
+                    name.len()
+                }
+
+                pub fn lookup(table: &[u32], fields: BTreeMap<u8, bool>, key: &str) -> u32 {
+                    table.len() as u32 + fields.len() as u32 + key.len() as u32
+                }
+
+                fn private(data: &[u8]) {
+                    let _ = data;
+                }
             "#,
         )
         .build();
 
     project
         .cargo_fuzz()
-        .arg("run")
-        .arg("yes_crash")
-        .arg("--")
-        .arg("-runs=1000")
-        .env("RUST_BACKTRACE", "1")
+        .arg("add")
+        .arg("--for")
+        .arg("add_for_function::header::parse")
         .assert()
-        .stderr(
-            predicate::str::contains("panicked at 'I'm afraid of number 7'")
-                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
-                .and(predicate::str::contains("run_with_crash::fail_fuzzing"))
-                .and(predicate::str::contains(
-                    "────────────────────────────────────────────────────────────────────────────────\n\
-                     \n\
-                     Failing input:\n\
-                     \n\
-                     \tfuzz/artifacts/yes_crash/crash-"
-                ))
-                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
-                .and(predicate::str::contains(
-                    "Reproduce with:\n\
-                     \n\
-                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
-                ))
-                .and(predicate::str::contains(
-                    "Minimize test case with:\n\
-                     \n\
-                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
-                )),
-        )
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("parse")).unwrap();
+    assert!(target.contains("fuzz_target!(|data: &[u8]| {"));
+    assert!(target.contains("let _ = add_for_function::header::parse(data);"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--for")
+        .arg("header::name")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("name")).unwrap();
+    assert!(target.contains("if let Ok(name) = std::str::from_utf8(data) {"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("lookup_fields")
+        .arg("--for")
+        .arg("header::lookup")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("lookup_fields")).unwrap();
+    assert!(target.contains(
+        "fuzz_target!(|input: (Vec<u32>, std::collections::BTreeMap<u8, bool>, String)| {"
+    ));
+    assert!(target.contains("let _ = add_for_function::header::lookup(&table, fields, &key);"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--for")
+        .arg("header::private")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`add_for_function::header::private` isn't public",
+        ))
         .failure();
+
+    project.cargo_fuzz().arg("build").assert().success();
 }
 
 #[test]
-fn run_without_sanitizer_with_crash() {
-    let project = project("run_without_sanitizer_with_crash")
+fn add_ffi() {
+    let project = project("add_ffi")
         .with_fuzz()
-        .fuzz_target(
-            "yes_crash",
+        .file(
+            Path::new("src").join("lib.rs"),
             r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+                use std::os::raw::c_char;
 
-                fuzz_target!(|data: &[u8]| {
-                    run_without_sanitizer_with_crash::fail_fuzzing(data);
-                });
+                mod capi;
+
+                #[no_mangle]
+                pub unsafe extern "C" fn parse(data: *const u8, len: usize) -> i32 {
+                    std::slice::from_raw_parts(data, len).len() as i32
+                }
+
+                #[no_mangle]
+                pub extern "C" fn lookup(name: *const c_char, flags: u32, out: *mut u8, out_len: usize) {
+                    let _ = (name, flags, out, out_len);
+                }
+            "#,
+        )
+        .file(
+            Path::new("src").join("capi.rs"),
+            r#"
+                #[export_name = "capi_free"]
+                pub extern "C" fn free(x: *mut u8, size: usize) {
+                    let _ = (x, size);
+                }
             "#,
         )
         .build();
 
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--ffi")
+        .arg("parse")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("parse")).unwrap();
+    assert!(target.contains("use add_ffi as _;"));
+    assert!(target.contains("fn parse(data: *const u8, len: usize) -> i32;"));
+    assert!(target.contains("parse(data.as_ptr().cast(), data.len() as _);"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--ffi")
+        .arg("lookup")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("lookup")).unwrap();
+    assert!(target.contains("let flags: u32 = match u.arbitrary() {"));
+    assert!(target.contains("name.push(0);"));
+    assert!(target
+        .contains("lookup(name.as_ptr().cast(), flags, out.as_mut_ptr().cast(), out.len() as _);"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("free_buffer")
+        .arg("--ffi")
+        .arg("capi_free")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("free_buffer")).unwrap();
+    assert!(target.contains("fn capi_free(x: *mut u8, size: usize);"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--ffi")
+        .arg("missing")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the fuzzed crate exports no `extern \"C\"` function as `missing`",
+        ))
+        .failure();
+}
+
+#[test]
+fn add_reject_invalid() {
+    let project = project("add_reject_invalid")
+        .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                pub fn parse(data: &[u8]) -> Result<u8, ()> {
+                    data.first().copied().ok_or(())
+                }
+
+                pub fn name(name: &str) -> Option<usize> {
+                    name.find('=')
+                }
+
+                pub fn count(data: &[u8]) -> usize {
+                    data.len()
+                }
+            "#,
+        )
+        .build();
+
+    // The test projects depend on libfuzzer-sys 0.3, which has no `Corpus`.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("plain")
+        .arg("--reject-invalid")
+        .assert()
+        .stderr(predicate::str::contains(
+            "needs libfuzzer-sys 0.4.4 or later, but fuzz/Cargo.toml depends on \
+             libfuzzer-sys 0.3.0",
+        ))
+        .failure();
+    assert!(!project.fuzz_target_path("plain").exists());
+
+    let manifest = project.fuzz_dir().join("Cargo.toml");
+    let toml = fs::read_to_string(&manifest).unwrap();
+    fs::write(
+        &manifest,
+        toml.replace(r#"version = "0.3.0""#, r#"version = "0.4""#),
+    )
+    .unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("plain")
+        .arg("--reject-invalid")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("plain")).unwrap();
+    assert!(target.contains("use libfuzzer_sys::{fuzz_target, Corpus};"));
+    assert!(target.contains("fuzz_target!(|data: &[u8]| -> Corpus {"));
+    assert!(target.contains("Err(_) => return Corpus::Reject,"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--for")
+        .arg("parse")
+        .arg("--reject-invalid")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("parse")).unwrap();
+    assert!(target.contains("fuzz_target!(|data: &[u8]| -> Corpus {"));
+    assert!(target.contains("match add_reject_invalid::parse(data) {"));
+    assert!(target.contains("Ok(_) => Corpus::Keep,"));
+    assert!(target.contains("Err(_) => Corpus::Reject,"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--for")
+        .arg("name")
+        .arg("--reject-invalid")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("name")).unwrap();
+    assert!(target.contains("Err(_) => return Corpus::Reject,"));
+    assert!(target.contains("Some(_) => Corpus::Keep,"));
+    assert!(target.contains("None => Corpus::Reject,"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("--for")
+        .arg("count")
+        .arg("--reject-invalid")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`add_reject_invalid::count` returns neither a `Result` nor an `Option`",
+        ))
+        .failure();
+}
+
+#[test]
+fn add_grammar() {
+    let project = project("add_grammar")
+        .with_fuzz()
+        .file(
+            "sum.ebnf",
+            r#"
+                (* Sums of numbers, like `(1 + 20) + 3`. *)
+                sum    ::= term ( " + " term )*
+                term   ::= number | "(" sum ")"
+                number ::= [1-9] [0-9]*
+            "#,
+        )
+        .file("loop.ebnf", "a = \"x\" a ;")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("sum")
+        .arg("--grammar")
+        .arg("sum.ebnf")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("sum")).unwrap();
+    assert!(target.contains("Inputs are generated from the grammar in `sum.ebnf`"));
+    assert!(target.contains("if rule_sum(&mut u, 0, &mut input).is_err() {"));
+    assert!(target.contains("// term ::= number | \"(\" sum \")\"\n"));
+    assert!(target.contains("out.push(class(u, depth, &[('1', '9')])?);"));
+
+    // Every input makes a sum, however it ends.
+    fs::write(
+        project.fuzz_target_path("sum"),
+        target.replace(
+            "    let _ = input;",
+            r#"    let total: u64 = input
+        .split(|c| "( )+".contains(c))
+        .filter(|n| !n.is_empty())
+        .map(|n| n.parse::<u64>().unwrap_or(1))
+        .sum();
+    assert!(total > 0, "{:?} isn't a sum", input);
+    assert_eq!(input.matches('(').count(), input.matches(')').count());"#,
+        ),
+    )
+    .unwrap();
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("yes_crash")
+        .arg("sum")
         .arg("--")
         .arg("-runs=1000")
-        .arg("-sanitizer=none")
-        .env("RUST_BACKTRACE", "1")
+        .assert()
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("loop")
+        .arg("--grammar")
+        .arg("loop.ebnf")
+        .assert()
+        .stderr(predicate::str::contains(
+            "rule `a` can never be finished: every way of expanding it expands it again",
+        ))
+        .failure();
+}
+
+#[test]
+fn add_differential() {
+    let project = project("add_differential")
+        .with_fuzz()
+        .file(
+            Path::new("fuzz").join("corpus").join("diff").join("x"),
+            "xy",
+        )
+        .build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("diff")
+        .arg("--differential")
+        .assert()
+        .success();
+
+    // Make the reference implementation disagree on inputs starting with `x`.
+    let target_path = project.fuzz_target_path("diff");
+    let target = fs::read_to_string(&target_path).unwrap();
+    let target = target.replace(
+        "fn reference(data: &[u8]) -> Option<Vec<u8>> {\n    Some(data.to_vec())",
+        "fn reference(data: &[u8]) -> Option<Vec<u8>> {\n    \
+         Some(data.to_vec()).filter(|data| data.first() != Some(&b'x'))",
+    );
+    fs::write(&target_path, target).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("diff")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .stderr(
+            predicate::str::contains("implementations diverged")
+                .and(predicate::str::contains("Divergence report:"))
+                .and(predicate::str::contains("divergence-")),
+        )
+        .code(2);
+    let artifacts = project.fuzz_dir().join("artifacts").join("diff");
+    let report = fs::read_dir(&artifacts)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .unwrap();
+    let report: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(report).unwrap()).unwrap();
+    assert_eq!(report["input"], "7879");
+    assert_eq!(report["ours"], "Some([120, 121])");
+    assert_eq!(report["reference"], "None");
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("diff_git")
+        .arg("--differential")
+        .arg("--reference")
+        .arg("https://example.com/add_differential.git#v1.0.0")
+        .assert()
+        .stderr(predicate::str::contains("divergence-<hash>.json"))
+        .success();
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(cargo_toml.contains(
+        "[dependencies.reference]\n\
+         package = \"add_differential\"\n\
+         git = \"https://example.com/add_differential.git\"\n\
+         rev = \"v1.0.0\"\n"
+    ));
+    let target = fs::read_to_string(project.fuzz_target_path("diff_git")).unwrap();
+    assert!(target.contains("`add_differential` and `reference`"));
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("diff_crate")
+        .arg("--differential")
+        .arg("--reference")
+        .arg("other@1.2")
+        .assert()
+        .stderr(predicate::str::contains(
+            "already has a dependency named `reference`",
+        ))
+        .failure();
+}
+
+#[test]
+fn list() {
+    let project = project("add").with_fuzz().build();
+
+    // Create some targets.
+    project.cargo_fuzz().arg("add").arg("c").assert().success();
+    project.cargo_fuzz().arg("add").arg("b").assert().success();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    // Make sure that we can list our targets, and that they're always sorted.
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("a\nb\nc\n")
+        .success();
+}
+
+#[test]
+fn list_json() {
+    let project = project("list_json").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("b").assert().success();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .arg("--message-format")
+        .arg("json")
+        .assert()
+        .stdout(
+            "{\"reason\":\"fuzz-target\",\"name\":\"a\"}\n\
+             {\"reason\":\"fuzz-target\",\"name\":\"b\"}\n",
+        )
+        .success();
+}
+
+#[test]
+fn completions() {
+    let project = project("completions")
+        .with_fuzz()
+        .file("fuzz/artifacts/a/crash-1234", "boom")
+        .file("fuzz/corpus/b/input", "")
+        .build();
+    project.cargo_fuzz().arg("add").arg("b").assert().success();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("completions")
+        .arg("bash")
+        .assert()
+        .stdout(predicates::str::contains("complete -F _cargo_fuzz"))
+        .success();
+
+    let complete = |words: &[&str]| {
+        let output = project
+            .cargo_fuzz()
+            .arg("__complete")
+            .arg("--")
+            .args(words)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+    assert_eq!(complete(&["cargo", "fuzz", "run", ""]), "a\nb\n");
+    assert_eq!(
+        complete(&["cargo", "fuzz", "run", "b", ""]),
+        "fuzz/corpus/b\n"
+    );
+    assert_eq!(
+        complete(&["cargo-fuzz", "repro", "--release", "a", ""]),
+        "fuzz/artifacts/a/crash-1234\n"
+    );
+    assert_eq!(
+        complete(&["cargo", "fuzz", "run", "--sanitizer", ""]),
+        "address\nleak\nmemory\nthread\nnone\nall\n"
+    );
+    assert!(complete(&["cargo", "fuzz", "corpus", ""]).contains("\ntrim\n"));
+    assert!(complete(&["cargo", "fuzz", "tmin", "--"]).contains("--runs\n"));
+    assert_eq!(complete(&["cargo", "fuzz", "add", ""]), "");
+}
+
+#[test]
+fn list_verbose() {
+    let project = project("list_verbose")
+        .with_fuzz()
+        .file("fuzz/corpus/healthy/one", "12345")
+        .file("fuzz/corpus/healthy/two", "67890")
+        .file("fuzz/artifacts/healthy/crash-1234", "boom")
+        .file(
+            "fuzz/stats/healthy.jsonl",
+            "{\"started\":1714564800,\"duration-secs\":60,\"execs\":60000,\"execs-per-sec\":1000,\
+             \"corpus-entries\":2,\"corpus-bytes\":10,\"coverage\":50,\"new-coverage\":5,\"crashes\":1}\n\
+             {\"started\":1714568400,\"duration-secs\":1,\"execs\":0,\"execs-per-sec\":null,\
+             \"corpus-entries\":2,\"corpus-bytes\":10,\"coverage\":50,\"new-coverage\":0,\"crashes\":0}\n",
+        )
+        .build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("healthy")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("new")
+        .assert()
+        .success();
+    // Listing treats missing directories as empty, without making them.
+    let corpus = project.fuzz_dir().join("corpus").join("new");
+    let artifacts = project.fuzz_dir().join("artifacts").join("new");
+    let _ = fs::remove_dir_all(&corpus);
+    let _ = fs::remove_dir_all(&artifacts);
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .arg("--verbose")
+        .assert()
+        .stdout(
+            "target          corpus artifacts    last run (UTC)   execs/s coverage\n\
+             healthy        2 (10b)         1  2024-05-01 13:00      1000        -\n\
+             new             0 (0b)         0             never         -        -\n",
+        )
+        .success();
+    assert!(!corpus.exists());
+    assert!(!artifacts.exists());
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .arg("--verbose")
+        .arg("--message-format")
+        .arg("json")
+        .assert()
+        .stdout(predicate::str::contains(
+            "{\"reason\":\"fuzz-target\",\"name\":\"healthy\",\"corpus-entries\":2,\
+             \"corpus-bytes\":10,\"artifacts\":1,\"last-run\":1714568400,\"execs-per-sec\":1000,\
+             \"coverage-percent\":null}\n",
+        ))
+        .success();
+}
+
+#[test]
+fn status() {
+    let project = project("status")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file("fuzz/corpus/foo/committed", "a")
+        .file("fuzz/corpus/foo/new", "b")
+        .file("fuzz/artifacts/foo/crash-committed", "c")
+        .file("fuzz/artifacts/foo/crash-new", "d")
+        .build();
+
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(project.root())
+            .args([
+                "-c",
+                "user.name=Jane Doe",
+                "-c",
+                "user.email=jane@example.com",
+            ])
+            .args(args)
+            .assert()
+            .success();
+    };
+    git(&["init", "--quiet"]);
+    git(&[
+        "add",
+        "fuzz/corpus/foo/committed",
+        "fuzz/artifacts/foo/crash-committed",
+    ]);
+    git(&["commit", "--quiet", "-m", "Initial commit"]);
+
+    project
+        .cargo_fuzz()
+        .arg("status")
+        .assert()
+        .stdout("foo: 1 of 2 corpus input(s) and 1 of 2 artifact(s) tracked\n")
+        .stderr(
+            predicate::str::contains("warning: 1 artifact(s) aren't tracked by git")
+                .and(predicate::str::contains("\tfuzz/artifacts/foo/crash-new\n"))
+                .and(predicate::str::contains("crash-committed").not()),
+        )
+        .success();
+}
+
+#[test]
+fn add_with_bypass_module() {
+    let project = project("add_with_bypass_module")
+        .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                pub fn parse(data: &[u8]) -> Option<&[u8]> {
+                    let (sum, body) = data.split_first()?;
+                    let actual = body.iter().fold(0u8, |a, b| a.wrapping_add(*b));
+                    fuzz_bypass::checksum_matches(*sum, actual).then(|| body)
+                }"#,
+        )
+        .build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parse")
+        .arg("--with-bypass-module")
+        .assert()
+        .stderr(predicates::str::contains(
+            "`crate::fuzz_bypass::checksum_matches`",
+        ))
+        .success();
+    let module = project.root().join("src").join("fuzz_bypass.rs");
+    assert!(fs::read_to_string(&module)
+        .unwrap()
+        .contains("# The `cfg(fuzzing)` contract"));
+
+    // A second target keeps the module and its declaration as they are.
+    fs::write(
+        &module,
+        "pub fn checksum_matches(_: u8, _: u8) -> bool { true }\n",
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("other")
+        .arg("--with-bypass-module")
+        .assert()
+        .stderr(predicates::str::contains("already exists"))
+        .success();
+    assert_eq!(
+        fs::read_to_string(&module).unwrap(),
+        "pub fn checksum_matches(_: u8, _: u8) -> bool { true }\n"
+    );
+    let lib = fs::read_to_string(project.root().join("src").join("lib.rs")).unwrap();
+    assert_eq!(lib.matches("mod fuzz_bypass;").count(), 1);
+
+    // The module builds without warnings, with and without `cfg(fuzzing)`.
+    fs::remove_file(&module).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("third")
+        .arg("--with-bypass-module")
+        .assert()
+        .success();
+    for cfg in [&[][..], &["--cfg", "fuzzing"][..]] {
+        Command::new("rustc")
+            .arg("--crate-type=lib")
+            .arg("--edition=2018")
+            .arg("--emit=metadata")
+            .arg("-Dwarnings")
+            .args(cfg)
+            .arg("--out-dir")
+            .arg(project.root().join("target"))
+            .arg(project.root().join("src").join("lib.rs"))
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn add_bin() {
+    let project = project("add_bin")
+        .with_fuzz()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "add_bin"
+                version = "1.0.0"
+
+                # Not a member of cargo-fuzz's own workspace.
+                [workspace]
+            "#,
+        )
+        .default_src_lib()
+        .file(
+            Path::new("src").join("bin").join("reader.rs"),
+            r#"
+                use std::io::Read;
+
+                fn main() {
+                    let mut data = Vec::new();
+                    match std::env::args_os().nth(1) {
+                        Some(path) => data = std::fs::read(path).unwrap(),
+                        None => {
+                            std::io::stdin().read_to_end(&mut data).unwrap();
+                        }
+                    }
+                    if data.is_empty() {
+                        eprintln!("no input");
+                        std::process::exit(1);
+                    }
+                    add_bin::fail_fuzzing(&data);
+                }
+            "#,
+        )
+        .file(
+            Path::new("fuzz")
+                .join("corpus")
+                .join("reader_stdin")
+                .join("0"),
+            "",
+        )
+        .file(
+            Path::new("fuzz")
+                .join("corpus")
+                .join("reader_stdin")
+                .join("1"),
+            "abc",
+        )
+        .file(
+            Path::new("fuzz")
+                .join("corpus")
+                .join("reader_file")
+                .join("0"),
+            "7777777",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("reader_stdin")
+        .arg("--bin")
+        .arg("writer")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the fuzzed crate has no binary named `writer`; it has `reader`",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("reader_stdin")
+        .arg("--bin")
+        .arg("reader")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("reader_file")
+        .arg("--bin")
+        .arg("reader")
+        .arg("--input")
+        .arg("file")
+        .assert()
+        .success();
+    let config = fs::read_to_string(project.fuzz_dir().join("fuzz.toml")).unwrap();
+    assert!(config.contains("[targets.reader_stdin]\nbin = \"reader\"\n"));
+    assert!(config.contains("[targets.reader_file]\nbin = \"reader\"\n"));
+
+    // Exiting with an error isn't a crash.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("reader_stdin")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .success();
+
+    // Panicking is.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("reader_file")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(
+            predicate::str::contains("I'm afraid of number 7")
+                .and(predicate::str::contains("`reader` crashed")),
+        )
+        .failure();
+}
+
+#[test]
+fn add_nested() {
+    let project = project("add_nested").with_fuzz().build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("top")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parser/headers")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parser/body")
+        .assert()
+        .success();
+
+    assert!(project
+        .fuzz_dir()
+        .join("fuzz_targets")
+        .join("parser")
+        .join("headers.rs")
+        .is_file());
+    assert!(project
+        .fuzz_dir()
+        .join("corpus")
+        .join("parser_headers")
+        .is_dir());
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(
+        cargo_toml.contains("name = \"parser_headers\"\npath = \"fuzz_targets/parser/headers.rs\"")
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parser/headers")
+        .assert()
+        .stderr(predicate::str::contains(
+            "a fuzz target named `parser_headers` already exists",
+        ))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("parser_headers")
+        .assert()
+        .stderr(predicate::str::contains(
+            "a fuzz target named `parser_headers` already exists",
+        ))
+        .failure();
+    assert!(!project.fuzz_target_path("parser_headers").exists());
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("parser_body\nparser_headers\ntop\n")
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .arg("parser/")
+        .assert()
+        .stdout("parser_body\nparser_headers\n")
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("parser/headers")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+}
+
+#[test]
+fn rm() {
+    let project = project("rm").with_fuzz().build();
+    for target in ["a", "b", "parser/headers"] {
+        project
+            .cargo_fuzz()
+            .arg("add")
+            .arg(target)
+            .assert()
+            .success();
+    }
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        "[targets.a]\nengine = \"honggfuzz\"\n\n[targets.b]\nengine = \"honggfuzz\"\n",
+    )
+    .unwrap();
+    fs::write(project.fuzz_dir().join("corpus").join("a").join("x"), "x").unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("rm")
+        .arg("a")
+        .assert()
+        .stderr(predicate::str::contains("Removed fuzz target `a`"))
+        .success();
+    assert!(!project.fuzz_target_path("a").exists());
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(!cargo_toml.contains("name = \"a\""));
+    assert!(cargo_toml.contains("name = \"b\""));
+    let config = fs::read_to_string(project.fuzz_dir().join("fuzz.toml")).unwrap();
+    assert_eq!(config, "[targets.b]\nengine = \"honggfuzz\"\n");
+    // The corpus and artifacts stay without `--data`.
+    assert!(project.fuzz_dir().join("corpus").join("a").is_dir());
+
+    project
+        .cargo_fuzz()
+        .arg("rm")
+        .arg("parser_headers")
+        .arg("--data")
+        .assert()
+        .success();
+    assert!(!project
+        .fuzz_dir()
+        .join("fuzz_targets")
+        .join("parser")
+        .exists());
+    assert!(!project
+        .fuzz_dir()
+        .join("corpus")
+        .join("parser_headers")
+        .exists());
+    assert!(!project
+        .fuzz_dir()
+        .join("artifacts")
+        .join("parser_headers")
+        .exists());
+
+    project
+        .cargo_fuzz()
+        .arg("rm")
+        .arg("a")
+        .assert()
+        .stderr(predicate::str::contains("no fuzz target named `a`"))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("b\n")
+        .success();
+}
+
+#[test]
+fn mv() {
+    let project = project("mv").with_fuzz().build();
+    for target in ["a", "b"] {
+        project
+            .cargo_fuzz()
+            .arg("add")
+            .arg(target)
+            .assert()
+            .success();
+    }
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        "[targets.a]\nengine = \"honggfuzz\"\n",
+    )
+    .unwrap();
+    fs::write(project.fuzz_dir().join("corpus").join("a").join("x"), "x").unwrap();
+    fs::create_dir_all(project.fuzz_dir().join("dictionaries")).unwrap();
+    fs::write(
+        project.fuzz_dir().join("dictionaries").join("a.dict"),
+        "\"x\"\n",
+    )
+    .unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("mv")
+        .arg("a")
+        .arg("b")
+        .assert()
+        .stderr(predicate::str::contains(
+            "a fuzz target named `b` already exists",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("mv")
+        .arg("a")
+        .arg("parser/headers")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Renamed fuzz target `a` to `parser_headers`",
+        ))
+        .success();
+    assert!(!project.fuzz_target_path("a").exists());
+    assert!(project
+        .fuzz_dir()
+        .join("fuzz_targets")
+        .join("parser")
+        .join("headers.rs")
+        .is_file());
+    let cargo_toml = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    assert!(
+        cargo_toml.contains("name = \"parser_headers\"\npath = \"fuzz_targets/parser/headers.rs\"")
+    );
+    assert!(!cargo_toml.contains("name = \"a\""));
+    let config = fs::read_to_string(project.fuzz_dir().join("fuzz.toml")).unwrap();
+    assert_eq!(config, "[targets.parser_headers]\nengine = \"honggfuzz\"\n");
+    assert!(project
+        .fuzz_dir()
+        .join("corpus")
+        .join("parser_headers")
+        .join("x")
+        .is_file());
+    assert!(!project.fuzz_dir().join("corpus").join("a").exists());
+    assert!(project
+        .fuzz_dir()
+        .join("dictionaries")
+        .join("parser_headers.dict")
+        .is_file());
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stdout("b\nparser_headers\n")
+        .success();
+}
+
+#[test]
+fn mv_configured_corpus() {
+    let project = project("mv_configured_corpus").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        "[targets.a]\ncorpus = \"shared\"\n",
+    )
+    .unwrap();
+    fs::create_dir_all(project.fuzz_dir().join("shared")).unwrap();
+    fs::write(project.fuzz_dir().join("shared").join("x"), "x").unwrap();
+    fs::write(
+        project
+            .fuzz_dir()
+            .join("artifacts")
+            .join("a")
+            .join("crash-1"),
+        "y",
+    )
+    .unwrap();
+    fs::create_dir_all(project.fuzz_dir().join("coverage").join("a")).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("mv")
+        .arg("a")
+        .arg("b")
+        .assert()
+        .stderr(predicate::str::contains("Renamed fuzz target `a` to `b`"))
+        .success();
+
+    // The configured corpus stays where it is, and everything else moves to
+    // where `b` keeps the same kind of data.
+    assert!(project.fuzz_dir().join("shared").join("x").is_file());
+    assert!(project
+        .fuzz_dir()
+        .join("artifacts")
+        .join("b")
+        .join("crash-1")
+        .is_file());
+    assert!(project.fuzz_dir().join("coverage").join("b").is_dir());
+    assert!(!project.fuzz_dir().join("corpus").join("b").exists());
+    let config = fs::read_to_string(project.fuzz_dir().join("fuzz.toml")).unwrap();
+    assert_eq!(config, "[targets.b]\ncorpus = \"shared\"\n");
+}
+
+#[test]
+fn migrate() {
+    let project = project("migrate")
+        .with_fuzz()
+        .file(Path::new("src").join("lib.rs"), "pub fn parse(_: &[u8]) {}")
+        .fuzz_target(
+            "old",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| migrate::parse(data));
+            "#,
+        )
+        .build();
+    let lib = project.fuzz_dir().join("src").join("lib.rs");
+
+    // A fuzz crate from before `init` added a library reports divergences
+    // itself.
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("inline")
+        .arg("--differential")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("inline")).unwrap();
+    assert!(target.contains("\nfn report_divergence("));
+
+    project
+        .cargo_fuzz()
+        .arg("migrate")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Added fuzz/src/lib.rs, a library that the fuzz targets can share as \
+             `migrate_fuzz`",
+        ))
+        .success();
+    let source = fs::read_to_string(&lib).unwrap();
+    assert!(source.contains("\npub use migrate;\n"));
+    assert!(source.contains("\npub fn report_divergence("));
+    let manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    let lib_table = manifest.find("[lib]\npath = \"src/lib.rs\"").unwrap();
+    assert!(lib_table < manifest.find("[[bin]]").unwrap());
+    assert!(project.fuzz_target_path("old").is_file());
+
+    project
+        .cargo_fuzz()
+        .arg("migrate")
+        .assert()
+        .stderr(predicate::str::contains(
+            "The fuzz targets already share a library, `migrate_fuzz`",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("shared")
+        .arg("--differential")
+        .assert()
+        .success();
+    let target = fs::read_to_string(project.fuzz_target_path("shared")).unwrap();
+    assert!(target.contains("use migrate_fuzz::report_divergence;\n"));
+    assert!(!target.contains("fn report_divergence("));
+}
+
+#[test]
+fn gen_test() {
+    let project = project("gen_test").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+    let artifact = project.root().join("crash-1234");
+    fs::write(&artifact, b"\" \\\n\x00\xff").unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("gen-test")
+        .arg("a")
+        .arg(&artifact)
+        .assert()
+        .stderr(predicates::str::contains(
+            "Added regression test `crash_1234`",
+        ))
+        .success();
+
+    let tests = fs::read_to_string(project.fuzz_dir().join("tests").join("a.rs")).unwrap();
+    assert!(tests.contains("env!(\"CARGO_BIN_EXE_a\")"));
+    assert!(tests.contains("fn crash_1234()"));
+    assert!(tests.contains(r#"b"\" \\\n\x00\xff""#));
+
+    // Generating the same test twice is an error.
+    project
+        .cargo_fuzz()
+        .arg("gen-test")
+        .arg("a")
+        .arg(&artifact)
+        .assert()
+        .stderr(predicates::str::contains("already has a regression test"))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("gen-test")
+        .arg("b")
+        .arg(&artifact)
+        .assert()
+        .stderr(predicates::str::contains("no fuzz target named `b`"))
+        .failure();
+}
+
+#[test]
+fn check() {
+    let project = project("check")
+        .with_fuzz()
+        .fuzz_target(
+            "fine",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .fuzz_target(
+            "broken",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                #[cfg(fuzzing)]
+                compile_error!("checked with the fuzzing cfg");
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("check")
+        .arg("fine")
+        .assert()
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("check")
+        .assert()
+        .stderr(
+            predicates::str::contains("checked with the fuzzing cfg")
+                .and(predicates::str::contains(
+                    "failed to check fuzz target `broken`",
+                ))
+                .and(predicates::str::contains("`fine`").not()),
+        )
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("check")
+        .arg("--all-targets")
+        .assert()
+        .stderr(predicates::str::contains(
+            "failed to check fuzz target `broken`",
+        ))
+        .failure();
+}
+
+#[test]
+fn build_print_flags() {
+    let project = project("build_print_flags")
+        .with_fuzz()
+        .fuzz_target(
+            "needs_cfg",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                #[cfg(not(my_cfg))]
+                compile_error!("my_cfg isn't set");
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--print-flags")
+        .arg("--rustflags-extra=-C target-cpu=native")
+        .env("RUSTFLAGS", "--cfg my_cfg")
+        .assert()
+        .stdout(
+            predicate::str::is_match(
+                "^# cargo fuzz\n--cfg fuzzing\n(.+\n)+\n\
+                 # RUSTFLAGS\n--cfg my_cfg\n\n\
+                 # --rustflags-extra\n-C target-cpu=native\n$",
+            )
+            .unwrap(),
+        )
+        .success();
+
+    // Encoded flags take the place of `RUSTFLAGS`, as they do with Cargo.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--print-flags")
+        .env("RUSTFLAGS", "--cfg ignored")
+        .env("CARGO_ENCODED_RUSTFLAGS", "--cfg\x1fmy_cfg")
+        .assert()
+        .stdout(
+            predicate::str::contains("# CARGO_ENCODED_RUSTFLAGS\n--cfg my_cfg\n")
+                .and(predicate::str::contains("ignored").not()),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("check")
+        .env("CARGO_ENCODED_RUSTFLAGS", "--cfg\x1fmy_cfg")
+        .assert()
+        .success();
+}
+
+#[test]
+fn build_analyze_size() {
+    let project = project("build_analyze_size")
+        .with_fuzz()
+        .fuzz_target(
+            "sized",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = std::str::from_utf8(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--analyze-size")
+        .arg("sized")
+        .assert()
+        .stdout(
+            predicate::str::contains("sized (address sanitizer): ")
+                .and(predicate::str::contains("Code by crate:"))
+                .and(predicate::str::contains("[libFuzzer]"))
+                .and(predicate::str::contains("[AddressSanitizer runtime]"))
+                .and(predicate::str::contains("  core\n")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--analyze-size")
+        .arg("--debuginfo-level=full")
+        .arg("sized")
+        .assert()
+        .stderr(predicate::str::contains(
+            "note: most of the binary is debug info",
+        ))
+        .success();
+}
+
+#[test]
+fn build_windows_flags() {
+    let project = project("build_windows_flags").with_fuzz().build();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--print-flags")
+        .arg("--target=x86_64-pc-windows-msvc")
+        .assert()
+        .stdout(
+            predicate::str::contains("\n-Zsanitizer=address\n")
+                .and(predicate::str::contains("\n-Clink-arg=/include:main\n")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--print-flags")
+        .arg("--target=x86_64-pc-windows-msvc")
+        .arg("--sanitizer=memory")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the memory sanitizer isn't supported for x86_64-pc-windows-msvc; \
+             use `--sanitizer address` or `--sanitizer none`",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--print-flags")
+        .arg("--target=x86_64-pc-windows-gnu")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the address sanitizer isn't supported for x86_64-pc-windows-gnu; \
+             use `--sanitizer none`",
+        ))
+        .failure();
+}
+
+#[test]
+fn check_replay() {
+    let project = project("check_replay")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    check_replay::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file("fuzz/corpus/yes_crash/a", "x")
+        .file("fuzz/corpus/yes_crash/b", "xx")
+        .build();
+
+    project.cargo_fuzz().arg("check").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("check")
+        .arg("--replay")
+        .assert()
+        .stderr(predicates::str::contains(
+            "Generated corpus replay tests in",
+        ))
+        .success();
+    let tests =
+        fs::read_to_string(project.fuzz_dir().join("tests").join("corpus_replay.rs")).unwrap();
+    assert!(tests.contains("fn yes_crash()"));
+    assert!(tests.contains("env!(\"CARGO_BIN_EXE_yes_crash\")"));
+
+    fs::write(
+        project
+            .fuzz_dir()
+            .join("corpus")
+            .join("yes_crash")
+            .join("c"),
+        "x7zzzzz",
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .arg("check")
+        .arg("--replay")
+        .arg("yes_crash")
+        .assert()
+        .stdout(predicates::str::contains(
+            "fuzz target `yes_crash` crashed on an input in",
+        ))
+        .stderr(predicates::str::contains("replaying the corpus failed"))
+        .failure();
+}
+
+#[test]
+#[cfg(unix)]
+fn corpus_push_pull() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = project("corpus_push_pull").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+    project.cargo_fuzz().arg("add").arg("b").assert().success();
+
+    // A fake AWS CLI that logs its arguments.
+    let bin = project.root().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let log = project.root().join("aws.log");
+    let aws = bin.join("aws");
+    fs::write(
+        &aws,
+        format!("#!/bin/sh\necho \"$@\" >> {}\n", log.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&aws, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .arg("corpus")
+        .arg("push")
+        .arg("--remote")
+        .arg("s3://bucket/prefix/")
+        .assert()
+        .success();
+
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        "[defaults]\ncorpus-remote = \"s3://bucket/other\"\n",
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .arg("corpus")
+        .arg("pull")
+        .arg("b")
+        .assert()
+        .success();
+
+    let corpus = project.fuzz_dir().join("corpus");
+    assert_eq!(
+        fs::read_to_string(&log).unwrap(),
+        format!(
+            "s3 sync --only-show-errors {0}/a s3://bucket/prefix/a\n\
+             s3 sync --only-show-errors {0}/b s3://bucket/prefix/b\n\
+             s3 sync --only-show-errors s3://bucket/other/b {0}/b\n",
+            corpus.display()
+        )
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("push")
+        .arg("--remote")
+        .arg("ftp://example.com")
+        .assert()
+        .stderr(predicates::str::contains("unsupported remote"))
+        .failure();
+}
+
+#[test]
+fn corpus_import() {
+    let project = project("corpus_import")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file("fuzz/corpus/foo/existing", "old")
+        .file("afl/main/fuzzer_stats", "")
+        .file("afl/main/queue/id:000000,orig:a", "afl")
+        .file("afl/main/queue/.state/redundant", "hidden")
+        .file("afl/secondary/fuzzer_stats", "")
+        .file("afl/secondary/queue/id:000000,orig:a", "afl")
+        .file(
+            "proptest-regressions/lib.txt",
+            "# Seeds for failure cases proptest has generated in the past.\n\
+             cc 70726f70 # shrinks to input = 1\n",
+        )
+        .file("seeds/one", "old")
+        .file("seeds/nested/two", "plain")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("import")
+        .arg("foo")
+        .arg("afl")
+        .arg("proptest-regressions/lib.txt")
+        .arg("seeds")
+        .assert()
+        .stderr(
+            predicates::str::contains("Importing 2 input(s) from AFL output directory afl")
+                .and(predicates::str::contains(
+                    "Importing 1 input(s) from proptest regression file",
+                ))
+                .and(predicates::str::contains(
+                    "Imported 3 new input(s) into fuzz/corpus/foo, skipping 2 duplicate(s)",
+                )),
+        )
+        .success();
+
+    let mut inputs: Vec<String> = fs::read_dir(project.fuzz_dir().join("corpus").join("foo"))
+        .unwrap()
+        .map(|e| fs::read_to_string(e.unwrap().path()).unwrap())
+        .collect();
+    inputs.sort();
+    assert_eq!(inputs, ["afl", "old", "plain", "prop"]);
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("import")
+        .arg("foo")
+        .arg("missing")
+        .assert()
+        .stderr(predicates::str::contains("nothing to import at missing"))
+        .failure();
+}
+
+#[test]
+fn run_warns_about_engine_args() {
+    let project = project("run_warns_about_engine_args")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(
+            "fuzz/fuzz.toml",
+            r#"
+                [targets.foo]
+                engine-args = ["-max_lne=8"]
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--")
+        .arg("--only_ascii=1")
+        .arg("-timeout")
+        .arg("5")
+        .arg("-runs=1")
+        .assert()
+        .stderr(
+            predicate::str::contains(
+                "warning: libFuzzer ignores the unknown flag `-max_lne=8`; did you mean \
+                 `-max_len`?",
+            )
+            .and(predicate::str::contains(
+                "warning: libFuzzer ignores `--only_ascii=1`, since it starts with `--`; did \
+                 you mean `-only_ascii=1`?",
+            ))
+            .and(predicate::str::contains(
+                "warning: libFuzzer ignores `-timeout 5`; write it as `-timeout=5`",
+            ))
+            .and(predicate::str::contains("-runs=1").not()),
+        )
+        .success();
+}
+
+#[test]
+fn corpus_trim() {
+    let project = project("corpus_trim")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(
+            "fuzz/fuzz.toml",
+            r#"
+                [targets.foo]
+                max-len = 4
+            "#,
+        )
+        .file("fuzz/corpus/foo/short", "abc")
+        .file("fuzz/corpus/foo/long", "abcdefgh")
+        .file("fuzz/corpus/foo/dup", "abcdxyz")
+        .file("fuzz/corpus/foo/other", "wxyz1234")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("trim")
+        .arg("foo")
+        .arg("--to-max-len")
+        .assert()
+        .stderr(predicates::str::contains(
+            "Truncated 2 and dropped 1 input(s) of fuzz/corpus/foo over 4 bytes",
+        ))
+        .success();
+
+    let corpus = project.fuzz_dir().join("corpus").join("foo");
+    let mut inputs: Vec<String> = fs::read_dir(&corpus)
+        .unwrap()
+        .map(|e| fs::read_to_string(e.unwrap().path()).unwrap())
+        .collect();
+    inputs.sort();
+    assert_eq!(inputs, ["abc", "abcd", "wxyz"]);
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("trim")
+        .arg("foo")
+        .arg("--max-len")
+        .arg("3")
+        .arg("--drop")
+        .assert()
+        .stderr(predicates::str::contains(
+            "Truncated 0 and dropped 2 input(s) of fuzz/corpus/foo over 3 bytes",
+        ))
+        .success();
+    assert_eq!(fs::read_dir(&corpus).unwrap().count(), 1);
+}
+
+#[test]
+fn corpus_export() {
+    let target = r#"
+        #![no_main]
+        use libfuzzer_sys::fuzz_target;
+
+        fuzz_target!(|data: &[u8]| {
+            let _ = data;
+        });
+    "#;
+    let project = project("corpus_export")
+        .with_fuzz()
+        .fuzz_target("foo", target)
+        .fuzz_target("bar", target)
+        .file("fuzz/artifacts/foo/crash-1234", "boom")
+        .build();
+    let corpus = project.fuzz_dir().join("corpus").join("foo");
+    fs::create_dir_all(&corpus).unwrap();
+    let inputs: [&[u8]; 4] = [b"", b"\x00\xff\r\n", b"hello\n", &[0x80; 100]];
+    for (i, input) in inputs.iter().enumerate() {
+        fs::write(corpus.join(format!("input{}", i)), input).unwrap();
+    }
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("export")
+        .arg("foo")
+        .arg("--format")
+        .arg("hex")
+        .assert()
+        .stdout("\n00ff0d0a\n68656c6c6f0a\n".to_string() + &"80".repeat(100) + "\n")
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("export")
+        .arg("foo")
+        .arg("--artifacts")
+        .assert()
+        .stdout(predicate::str::contains(r#""target": "foo""#))
+        .stdout(predicate::str::contains(r#""name": "crash-1234""#))
+        .stdout(predicate::str::contains(r#""data": "Ym9vbQ==""#))
+        .success();
+
+    // Each format round-trips every input into another target's corpus.
+    let bar = project.fuzz_dir().join("corpus").join("bar");
+    for format in &["hex", "base64", "base64-json"] {
+        let _ = fs::remove_dir_all(&bar);
+        let export = project.root().join(format!("foo.{}", format));
+        project
+            .cargo_fuzz()
+            .arg("corpus")
+            .arg("export")
+            .arg("foo")
+            .arg("--format")
+            .arg(format)
+            .arg("-o")
+            .arg(&export)
+            .assert()
+            .stderr(predicate::str::contains(format!(
+                "Exported 4 input(s) of `foo` as {}",
+                format
+            )))
+            .success();
+        project
+            .cargo_fuzz()
+            .arg("corpus")
+            .arg("import")
+            .arg("bar")
+            .arg("--format")
+            .arg(format)
+            .arg(&export)
+            .assert()
+            .stderr(predicate::str::contains(format!(
+                "Importing 4 input(s) from {} export",
+                format
+            )))
+            .success();
+        let mut imported: Vec<Vec<u8>> = fs::read_dir(&bar)
+            .unwrap()
+            .map(|e| fs::read(e.unwrap().path()).unwrap())
+            .collect();
+        imported.sort();
+        let mut expected: Vec<Vec<u8>> = inputs.iter().map(|i| i.to_vec()).collect();
+        expected.sort();
+        assert_eq!(imported, expected);
+    }
+
+    fs::write(project.root().join("mangled.txt"), "00ff\nnot hex\n").unwrap();
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("import")
+        .arg("bar")
+        .arg("--format")
+        .arg("hex")
+        .arg("mangled.txt")
+        .assert()
+        .stderr(predicate::str::contains("line 2 isn't valid hex"))
+        .failure();
+}
+
+#[test]
+fn corpus_from_tests() {
+    let lib = r#"
+        pub fn parse(data: &[u8]) -> Option<u8> {
+            data.first().copied()
+        }
+
+        #[cfg(test)]
+        mod tests {
+            #[test]
+            fn header() {
+                assert_eq!(super::parse(b"GIF89a"), Some(b'G'));
+            }
+
+            #[test]
+            fn empty() {
+                assert_eq!(super::parse(b""), None);
+            }
+        }
+    "#;
+    let project = project("corpus_from_tests")
+        .with_fuzz()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "corpus_from_tests"
+                version = "1.0.0"
+
+                # Not a member of cargo-fuzz's own workspace.
+                [workspace]
+            "#,
+        )
+        .fuzz_target(
+            "parse",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    corpus_from_tests::parse(data);
+                });
+            "#,
+        )
+        .file(Path::new("src").join("lib.rs"), lib)
+        .build();
+
+    // The first time adds the module, which nothing calls yet.
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("from-tests")
+        .arg("parse")
+        .assert()
+        .stderr(predicate::str::contains(
+            "nothing calls `crate::fuzz_record::record` yet",
+        ))
+        .failure();
+    let module = project.root().join("src").join("fuzz_record.rs");
+    assert!(module.is_file());
+    let source = fs::read_to_string(project.root().join("src").join("lib.rs")).unwrap();
+    assert!(source.contains("mod fuzz_record;"));
+
+    let source = source.replace(
+        "data.first().copied()",
+        "crate::fuzz_record::record(data);\n            data.first().copied()",
+    );
+    fs::write(project.root().join("src").join("lib.rs"), source).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("from-tests")
+        .arg("parse")
+        .assert()
+        .stderr(predicate::str::contains(
+            "The tests recorded 2 distinct input(s); added 2 new input(s) to fuzz/corpus/parse",
+        ))
+        .success();
+    let mut corpus: Vec<Vec<u8>> = fs::read_dir(project.fuzz_dir().join("corpus").join("parse"))
+        .unwrap()
+        .map(|e| fs::read(e.unwrap().path()).unwrap())
+        .collect();
+    corpus.sort();
+    assert_eq!(corpus, [b"".to_vec(), b"GIF89a".to_vec()]);
+
+    // Outside of `cargo fuzz`, `record` does nothing, and builds without
+    // warnings.
+    for cfg in [&[][..], &["--cfg", "cargo_fuzz_record"][..]] {
+        Command::new("rustc")
+            .arg("--crate-type=lib")
+            .arg("--edition=2018")
+            .arg("--emit=metadata")
+            .arg("-Dwarnings")
+            .args(cfg)
+            .arg("--out-dir")
+            .arg(project.root().join("target"))
+            .arg(project.root().join("src").join("lib.rs"))
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn corpus_cross_pollinate() {
+    let project = project("corpus_cross_pollinate")
+        .with_fuzz()
+        .fuzz_target(
+            "parse_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'A') {
+                        std::hint::black_box(data.len());
+                    }
+                });
+            "#,
+        )
+        .fuzz_target(
+            "parse_b",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'A') {
+                        std::hint::black_box(data.len());
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/parse_a/a", "A1")
+        .file("fuzz/corpus/parse_b/b", "B")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("cross-pollinate")
+        .assert()
+        .stderr(
+            predicate::str::contains("`parse_a`: added 1 input(s)")
+                .and(predicate::str::contains("`parse_b`: added 1 input(s)")),
+        )
+        .success();
+    let corpus = project.fuzz_dir().join("corpus").join("parse_b");
+    assert_eq!(fs::read_dir(&corpus).unwrap().count(), 2);
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("info")
+        .arg("parse_b")
+        .arg("--origin")
+        .arg("cross-pollinate")
+        .assert()
+        .stdout(predicate::str::contains("1 input(s): 1 cross-pollinate"))
+        .success();
+
+    // There's nothing new to share the second time.
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("cross-pollinate")
+        .arg("parse_*")
+        .assert()
+        .stderr(predicate::str::contains("`parse_b`: added 0 input(s)"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("cross-pollinate")
+        .arg("parse_a")
+        .assert()
+        .stderr(predicate::str::contains(
+            "cross-pollinating needs at least two fuzz targets, but 1 match `parse_a`",
+        ))
+        .failure();
+}
+
+#[test]
+fn corpus_info() {
+    let project = project("corpus_info")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file("fuzz/corpus/foo/existing", "old")
+        .file("seeds/new", "new")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("info")
+        .arg("foo")
+        .assert()
+        .stdout(
+            predicates::str::is_match(r"existing +untracked")
+                .unwrap()
+                .and(predicates::str::contains("1 input(s): 1 untracked")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("import")
+        .arg("foo")
+        .arg("seeds")
+        .assert()
+        .success();
+    let index: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(project.fuzz_dir().join("provenance").join("foo.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(index.as_object().unwrap().len(), 2);
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("info")
+        .arg("foo")
+        .arg("--since")
+        .arg("24h")
+        .assert()
+        .stdout(
+            predicates::str::is_match(r"existing +seed")
+                .unwrap()
+                .and(predicates::str::is_match(r"[0-9a-f]{16} +import").unwrap())
+                .and(predicates::str::contains("2 input(s): 1 import, 1 seed")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("info")
+        .arg("foo")
+        .arg("--origin")
+        .arg("import")
+        .assert()
+        .stdout(
+            predicates::str::contains("existing")
+                .not()
+                .and(predicates::str::contains("1 input(s): 1 import")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("corpus")
+        .arg("info")
+        .arg("foo")
+        .arg("--run")
+        .arg("1-2")
+        .assert()
+        .stderr(predicates::str::contains(
+            "No matching inputs in fuzz/corpus/foo",
+        ))
+        .success();
+}
+
+#[test]
+fn dict() {
+    let project = project("dict")
+        .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                // A "comment" isn't a dictionary entry.
+                pub fn parse(data: &[u8]) -> bool {
+                    if data.starts_with(b"\x7fELF") {
+                        panic!("found an {}", "ELF file");
+                    }
+                    match data.len() as u32 {
+                        0xcafe => true,
+                        _ => data == r"raw".as_bytes(),
+                    }
+                }
+            "#,
+        )
+        .build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("dict")
+        .arg("a")
+        .assert()
+        .stderr(predicates::str::contains(
+            "Wrote 5 dictionary entries from 2 source file(s)",
+        ))
+        .success();
+
+    let dict = fs::read_to_string(project.fuzz_dir().join("dictionaries").join("a.dict")).unwrap();
+    assert_eq!(
+        dict,
+        "# Generated by `cargo fuzz dict`.\n\
+         \"ELF file\"\n\
+         \"raw\"\n\
+         \"\\x7fELF\"\n\
+         \"\\xca\\xfe\"\n\
+         \"\\xfe\\xca\"\n"
+    );
+}
+
+#[test]
+fn run_dictionaries() {
+    let dicts = Path::new("fuzz").join("dictionaries");
+    let project = project("run_dictionaries")
+        .with_fuzz()
+        .fuzz_target(
+            "words",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_dictionaries::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(dicts.join("words.dict"), "\"GET\"\n\"POST\"\n")
+        .file(
+            dicts.join("words").join("more.dict"),
+            "# Methods.\nput=\"PUT\"\nget=\"GET\"\n",
+        )
+        .file("custom.dict", "\"HEAD\"\n")
+        .build();
+
+    // The target's dictionaries are found and merged, without duplicates.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("words")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains("Dictionary: 3 entries"))
+        .success();
+
+    // `--dict` replaces them.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("words")
+        .arg("--dict")
+        .arg("custom.dict")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains("Dictionary: 1 entries"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("words")
+        .arg("--dict")
+        .arg("missing.dict")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains("no dictionary at missing.dict"))
+        .failure();
+}
+
+#[test]
+fn run_seed() {
+    let corpus = Path::new("fuzz").join("corpus").join("seeded");
+    let project = project("run_seed")
+        .with_fuzz()
+        .fuzz_target(
+            "seeded",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_seed::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .file(
+            Path::new("fuzz").join("dictionaries").join("seeded.dict"),
+            "\"GET\"\n",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("seeded")
+        .arg("--seed")
+        .arg("7")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(
+            predicate::str::contains("fuzz/manifests/seeded-seed-7.json")
+                .and(predicate::str::contains("Seed: 7"))
+                .and(predicate::str::contains("Done 100 runs")),
+        )
+        .success();
+
+    // New inputs don't go to the corpus, so that a replay starts from it too.
+    assert_eq!(
+        fs::read_dir(project.root().join(&corpus)).unwrap().count(),
+        1
+    );
+    let manifest = project
+        .fuzz_dir()
+        .join("manifests")
+        .join("seeded-seed-7.json");
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&manifest).unwrap()).unwrap();
+    assert_eq!(json["seed"], 7);
+    assert_eq!(json["runs"], 100);
+    assert_eq!(json["dictionary"], "\"GET\"\n");
+    assert!(json["engine-version"]
+        .as_str()
+        .unwrap()
+        .starts_with("libfuzzer-sys "));
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--replay-manifest")
+        .arg(&manifest)
+        .assert()
+        .stderr(
+            predicate::str::contains("Replaying the run of `seeded` with seed 7 for 100 run(s)")
+                .and(predicate::str::contains("Seed: 7"))
+                .and(predicate::str::contains("since").not()),
+        )
+        .success();
+
+    fs::write(project.root().join(&corpus).join("1"), "b").unwrap();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--replay-manifest")
+        .arg(&manifest)
+        .assert()
+        .stderr(predicate::str::contains(
+            "warning: the corpus has changed since",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("seeded")
+        .arg("--seed")
+        .arg("7")
+        .arg("--")
+        .arg("-jobs=2")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`-jobs=2` can't be combined with `--seed`",
+        ))
+        .failure();
+}
+
+#[test]
+fn run_engine_features() {
+    let project = project("run_engine_features")
+        .with_fuzz()
+        .fuzz_target(
+            "features",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_engine_features::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            "[targets.features]\nentropic = false\n",
+        )
+        .build();
+
+    // The config file turns entropic scheduling off, and the command line
+    // turns value profiling on.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("features")
+        .arg("--value-profile")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(
+            predicate::str::contains("-use_value_profile=1")
+                .and(predicate::str::contains("-entropic=0"))
+                .and(predicate::str::contains("entropic power schedule").not())
+                .and(predicate::str::contains("Done 100 runs")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("features")
+        .arg("--no-entropic")
+        .arg("--entropic")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(predicate::str::contains("entropic power schedule"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("features")
+        .arg("--value-profile")
+        .arg("--")
+        .arg("-use_value_profile=0")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`-use_value_profile=0` turns value-profile on or off like `--value-profile`",
+        ))
+        .failure();
+}
+
+#[test]
+fn run_no_crash() {
+    let project = project("run_no_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_no_crash::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .stderr(predicate::str::contains("Done 1000 runs"))
+        .success();
+
+    // The run is recorded for `cargo fuzz stats`.
+    let stats =
+        fs::read_to_string(project.fuzz_dir().join("stats").join("no_crash.jsonl")).unwrap();
+    assert_eq!(stats.lines().count(), 1);
+    assert!(stats.contains("\"crashes\":0"));
+}
+
+#[test]
+fn run_with_limits() {
+    let project = project("run_with_limits")
+        .with_fuzz()
+        .fuzz_target(
+            "sleepy",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"zzz" {
+                        std::thread::sleep(std::time::Duration::from_secs(3));
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/sleepy/a", "a")
+        .file("fuzz/fuzz.toml", "[defaults]\nrss-limit = 4096\n")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("sleepy")
+        .arg("--timeout")
+        .arg("5")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(
+            predicate::str::contains("-timeout=5")
+                .and(predicate::str::contains("-rss_limit_mb=4096")),
+        )
+        .success();
+
+    // The limits the run had are recorded with its stats.
+    let stats = fs::read_to_string(project.fuzz_dir().join("stats").join("sleepy.jsonl")).unwrap();
+    assert!(stats.contains("\"timeout-secs\":5,\"rss-limit-mb\":4096"));
+
+    fs::write(
+        project.fuzz_dir().join("corpus").join("sleepy").join("b"),
+        "zzz",
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("sleepy")
+        .arg("--timeout")
+        .arg("1")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(predicate::str::contains("ERROR: libFuzzer: timeout"))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("sleepy")
+        .arg("--timeout")
+        .arg("0")
+        .assert()
+        .stderr(predicate::str::contains("must be at least 1 second"))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("sleepy")
+        .arg("--malloc-limit")
+        .arg("8192")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the malloc limit (8192 MB) is over the RSS limit (4096 MB)",
+        ))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("sleepy")
+        .arg("--")
+        .arg("-rss_limit_mb=100")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`-rss_limit_mb=100` sets the same limit as `--rss-limit`",
+        ))
+        .failure();
+}
+
+#[test]
+fn stats() {
+    let record = |started: u64, execs_per_sec: u64, coverage: u64| {
+        format!(
+            r#"{{"started":{},"duration-secs":90,"execs":1000,"execs-per-sec":{},"corpus-entries":10,"corpus-bytes":2048,"coverage":{},"new-coverage":5,"crashes":0}}"#,
+            started, execs_per_sec, coverage
+        )
+    };
+    let project = project("stats")
+        .with_fuzz()
+        .fuzz_target(
+            "stats_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .fuzz_target(
+            "stats_b",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(
+            "fuzz/stats/stats_a.jsonl",
+            &[
+                record(0, 100, 40),
+                record(86_400, 300, 60),
+                record(1_600_000_000, 100, 55),
+            ]
+            .join("\n"),
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("stats")
+        .arg("stats_a")
+        .assert()
+        .stderr(predicate::str::contains("Last 3 of 3 run(s) of `stats_a`"))
+        .stdout(
+            predicate::str::contains("1970-01-02 00:00")
+                .and(predicate::str::contains("2020-09-13 12:26"))
+                .and(predicate::str::contains("1m30s"))
+                .and(predicate::str::contains("10 (2Kb)"))
+                .and(predicate::str::contains("average of the 2 before it"))
+                .and(predicate::str::contains("-50.0% vs 200")),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("stats")
+        .arg("stats_a")
+        .arg("--last=1")
+        .assert()
+        .stderr(predicate::str::contains("Last 1 of 3 run(s)"))
+        .stdout(predicate::str::contains("average").not())
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("stats")
+        .arg("stats_b")
+        .assert()
+        .stderr(predicate::str::contains(
+            "No runs of `stats_b` recorded yet",
+        ))
+        .success();
+}
+
+#[test]
+fn session() {
+    let corpus = Path::new("fuzz").join("corpus").join("session_a");
+    let project = project("session")
+        .with_fuzz()
+        .fuzz_target(
+            "session_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .file(corpus.join("1"), "bc")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("session")
+        .arg("save")
+        .arg("nightly")
+        .arg("session_a")
+        .arg("--")
+        .arg("-max_len=64")
+        .arg("-seed=42")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Saved session `nightly` of `session_a` with 2 corpus input(s) and seed 42",
+        ))
+        .success();
+
+    let dir = project.fuzz_dir().join("sessions").join("nightly");
+    assert!(dir.join("corpus").join("1").is_file());
+    let session: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("session.json")).unwrap()).unwrap();
+    assert_eq!(session["target"], "session_a");
+    assert_eq!(session["seed"], 42);
+    assert_eq!(
+        session["settings"]["args"],
+        serde_json::json!(["-max_len=64"])
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("session")
+        .arg("save")
+        .arg("nightly")
+        .arg("session_a")
+        .assert()
+        .stderr(predicate::str::contains(
+            "there is already a session named `nightly`",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("session")
+        .arg("resume")
+        .arg("nightly")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(
+            predicate::str::contains("Resuming session `nightly` of `session_a` from 2")
+                .and(predicate::str::contains("Seed: 42"))
+                .and(predicate::str::contains("Done 100 runs")),
+        )
+        .success();
+
+    // Resuming with a different fuzz target wouldn't replay the same inputs.
+    fs::write(
+        project.fuzz_target_path("session_a"),
+        r#"
+            #![no_main]
+            use libfuzzer_sys::fuzz_target;
+
+            fuzz_target!(|data: &[u8]| {
+                if data.len() > 1000 {
+                    println!("long");
+                }
+            });
+        "#,
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .arg("session")
+        .arg("resume")
+        .arg("nightly")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the fuzz target `session_a` has changed since session `nightly` was saved",
+        ))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("session")
+        .arg("resume")
+        .arg("--force")
+        .arg("nightly")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(predicate::str::contains("Done 10 runs"))
+        .success();
+}
+
+#[test]
+fn slow_inputs_and_bench() {
+    let project = project("slow_inputs_and_bench")
+        .with_fuzz()
+        .fuzz_target(
+            "slow",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"slow" {
+                        std::thread::sleep(std::time::Duration::from_millis(1100));
+                    }
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("corpus").join("slow").join("seed"),
+            "slow",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("slow")
+        .assert()
+        .stderr(predicate::str::contains(
+            "No slow inputs of `slow` saved yet",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("slow")
+        .arg("--slow-threshold-ms")
+        .arg("1000")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(
+            predicate::str::contains("-report_slow_units=1").and(predicate::str::contains(
+                "Saved 1 input(s) slower than 1000 ms to ",
+            )),
+        )
+        .success();
+
+    // The slow unit is moved out of the artifacts, and its time recorded.
+    let slow_dir = project.fuzz_dir().join("slow").join("slow");
+    let saved: Vec<_> = fs::read_dir(&slow_dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(fs::read(&saved[0]).unwrap(), b"slow");
+    let artifacts = project.fuzz_dir().join("artifacts").join("slow");
+    assert_eq!(fs::read_dir(&artifacts).unwrap().count(), 0);
+    let timings = fs::read_to_string(project.fuzz_dir().join("slow").join("slow.json")).unwrap();
+    let timings: std::collections::BTreeMap<String, u64> = serde_json::from_str(&timings).unwrap();
+    assert_eq!(timings.len(), 1);
+    assert!(timings.values().all(|ms| *ms >= 1000), "{:?}", timings);
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("slow")
+        .arg("--runs=1")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Timing 1 slow input(s) of `slow`, 1 run(s) each",
+        ))
+        .stdout(
+            predicate::str::is_match(r"\nslow-unit-\w+ +\d+ ms +\d+ ms +[+-]\d")
+                .unwrap()
+                .and(predicate::str::contains("\ntotal")),
+        )
+        .success();
+}
+
+#[test]
+fn bench_corpus() {
+    let corpus = Path::new("fuzz").join("corpus").join("bench");
+    let project = project("bench_corpus")
+        .with_fuzz()
+        .fuzz_target(
+            "bench",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"boom" {
+                        panic!("boom");
+                    }
+                    assert!(cfg!(fuzzing));
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .file(corpus.join("1"), "abc")
+        .file(corpus.join("2"), "boom")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("bench")
+        .arg("--corpus")
+        .arg("--runs=5")
+        .arg("--update")
+        .assert()
+        .stderr(
+            predicate::str::contains("Replaying 3 corpus input(s) of `bench`, 5 run(s) each")
+                .and(predicate::str::contains("Skipped 1 input(s) that panicked")),
+        )
+        .stdout(
+            predicate::str::is_match(r"\ninputs/sec +- +\d+ +-\n")
+                .unwrap()
+                .and(predicate::str::contains("\np99 latency")),
+        )
+        .success();
+
+    let recorded = fs::read_to_string(project.fuzz_dir().join("bench").join("bench.json")).unwrap();
+    let recorded: serde_json::Value = serde_json::from_str(&recorded).unwrap();
+    assert_eq!(recorded["inputs"], 2);
+
+    project
+        .cargo_fuzz()
+        .arg("bench")
+        .arg("bench")
+        .arg("--corpus")
+        .assert()
+        .stdout(predicate::str::is_match(r"\ninputs/sec +\d+ +\d+ +[+-]\d").unwrap())
+        .success();
+}
+
+#[test]
+#[cfg(unix)]
+fn profile() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let corpus = Path::new("fuzz").join("corpus").join("profile");
+    let project = project("profile")
+        .with_fuzz()
+        .fuzz_target(
+            "profile",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("profile")
+        .arg("profile")
+        .arg("--time=0")
+        .assert()
+        .stderr(predicate::str::contains("must be at least 1"))
+        .failure();
+
+    // A fake perf that runs the command it records, logging its arguments,
+    // and prints two samples as `perf script`.
+    let bin = project.root().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let log = project.root().join("perf.log");
+    let perf = bin.join("perf");
+    fs::write(
+        &perf,
+        format!(
+            r#"#!/bin/sh
+echo "$@" >> {}
+if [ "$1" = script ]; then
+    printf 'profile 1 1.0: 1 cycles:\n\t1 profile::hot::h0123456789abcdef+0x10 (/bin)\n\t2 main+0x5 (/bin)\n\n'
+    printf 'profile 1 1.1: 1 cycles:\n\t3 main+0x8 (/bin)\n\n'
+    exit
+fi
+while [ "$1" != -- ]; do
+    if [ "$1" = -o ]; then shift; out="$1"; fi
+    shift
+done
+shift
+"$@"
+touch "$out"
+"#,
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&perf, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .arg("profile")
+        .arg("profile")
+        .assert()
+        .stderr(
+            predicate::str::contains("Replaying the corpus of `profile` under a profiler")
+                .and(predicate::str::contains("Done 2 runs"))
+                .and(predicate::str::contains(
+                    "Wrote the flamegraph to fuzz/profile/profile/flamegraph.svg",
+                )),
+        )
+        .stdout(
+            predicate::str::contains("       1   50.0%  main\n")
+                .and(predicate::str::contains("       1   50.0%  profile::hot\n")),
+        )
+        .success();
+
+    let log = fs::read_to_string(&log).unwrap();
+    assert!(log.starts_with("record --call-graph fp --quiet -F 997 -o "));
+    assert!(log.contains(" -runs=0 "));
+    let dir = project.fuzz_dir().join("profile").join("profile");
+    assert_eq!(
+        fs::read_to_string(dir.join("stacks.folded")).unwrap(),
+        "main 1\nmain;profile::hot 1\n"
+    );
+    let svg = fs::read_to_string(dir.join("flamegraph.svg")).unwrap();
+    assert!(svg.contains("<svg "));
+    assert!(svg.contains("<title>profile::hot (1 samples, 50.00%)</title>"));
+}
+
+#[test]
+fn miri_skip() {
+    let corpus = Path::new("fuzz").join("corpus").join("miri");
+    let project = project("miri_skip")
+        .with_fuzz()
+        .fuzz_target(
+            "miri",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(corpus.join("slow-1"), "a")
+        .file(corpus.join("slow-2"), "b")
+        .file(corpus.join("ffi"), "c")
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            "[targets.miri]\nmiri-skip = [\"slow-*\"]\n",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("miri")
+        .arg("miri")
+        .arg("--skip=ffi")
+        .assert()
+        .stderr(
+            predicate::str::contains("Skipping 3 input(s) of `miri` on the skip list").and(
+                predicate::str::contains("No inputs of `miri` to replay under Miri"),
+            ),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("miri")
+        .arg("miri")
+        .arg("fuzz/artifacts/miri/missing")
+        .assert()
+        .stderr(predicate::str::contains("there is no input at"))
+        .failure();
+}
+
+#[test]
+fn run_monitor() {
+    let project = project("run_monitor")
+        .with_fuzz()
+        .fuzz_target(
+            "yes",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_monitor::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes")
+        .arg("--monitor")
+        .arg("--max-total-time=7")
+        .assert()
+        .stderr(
+            predicate::str::contains("Monitored `yes` with 2 sample(s), in fuzz/monitor/yes/")
+                .and(predicate::str::contains("peak RSS: "))
+                .and(predicate::str::contains("execs/s: ")),
+        )
+        .success();
+
+    let dir = project.fuzz_dir().join("monitor").join("yes");
+    let csvs: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+    assert_eq!(csvs.len(), 1);
+    let csv = fs::read_to_string(csvs[0].as_ref().unwrap().path()).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("seconds,rss_mb,cpu_percent,execs,execs_per_sec")
+    );
+    let last: Vec<&str> = lines.last().unwrap().split(',').collect();
+    assert_eq!(last[0], "5");
+    assert!(last[1].parse::<f64>().unwrap() > 0.0);
+    assert!(last[2].parse::<f64>().unwrap() > 0.0);
+    assert!(last[3].parse::<u64>().unwrap() > 0);
+}
+
+#[test]
+fn run_fork() {
+    let project = project("run_fork")
+        .with_fuzz()
+        .fuzz_target(
+            "fork_no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_fork::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "fork_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'x') {
+                        panic!("found an x");
+                    }
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("fork_no_crash")
+        .arg("--fork=2")
+        .arg("--max-total-time=2")
+        .assert()
+        .stderr(
+            predicate::str::contains("Fork mode summary for `fork_no_crash`")
+                .and(predicate::str::contains("restarts:    0"))
+                .and(predicate::str::contains("artifacts:   0")),
+        )
+        .success();
+
+    // Crashes are saved without stopping the run, and reported at the end.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("fork_crash")
+        .arg("--fork=2")
+        .arg("--max-total-time=3")
+        .assert()
+        .stderr(
+            predicate::str::contains("Fork mode summary for `fork_crash`")
+                .and(predicate::str::contains("crash:"))
+                .and(predicate::str::contains("fork mode saved")),
+        )
+        .failure();
+    assert!(
+        fs::read_dir(project.fuzz_dir().join("artifacts").join("fork_crash"))
+            .unwrap()
+            .any(|entry| entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with("crash-"))
+    );
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("fork_no_crash")
+        .arg("--fork=2")
+        .arg("--jobs=2")
+        .assert()
+        .stderr(predicate::str::contains("cannot be used with"))
+        .failure();
+}
+
+#[test]
+fn run_tui() {
+    let project = project("run_tui")
+        .with_fuzz()
+        .fuzz_target(
+            "tui_no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .fuzz_target(
+            "tui_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'x') {
+                        panic!("found an x");
+                    }
+                });
+            "#,
+        )
+        .build();
+
+    // The fuzzer's output goes to a log per worker, and a dashboard of them
+    // to stderr.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("tui_no_crash")
+        .arg("--tui")
+        .arg("--jobs=2")
+        .arg("--max-total-time=2")
+        .assert()
+        .stderr(
+            predicate::str::contains("Fuzzing `tui_no_crash` with 2 worker(s)")
+                .and(predicate::str::is_match(r"(?m)^0 .* done$").unwrap())
+                .and(predicate::str::is_match(r"(?m)^1 .* done$").unwrap())
+                .and(predicate::str::contains("last crash: none"))
+                .and(predicate::str::contains("Done ").not()),
+        )
+        .success();
+    let logs = project.fuzz_dir().join("logs").join("tui_no_crash");
+    for worker in ["worker-0.log", "worker-1.log"] {
+        let log = fs::read_to_string(logs.join(worker)).unwrap();
+        assert!(log.contains("Done "), "{}", log);
+    }
+
+    // The first crash stops every worker.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("tui_crash")
+        .arg("--tui")
+        .arg("--jobs=2")
+        .assert()
+        .stderr(
+            predicate::str::contains("last crash: worker")
+                .and(predicate::str::contains("panic: found an x"))
+                .and(predicate::str::contains("Reproduce with:")),
+        )
+        .code(2);
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("tui_no_crash")
+        .arg("--tui")
+        .arg("--fork=2")
+        .assert()
+        .stderr(predicate::str::contains("cannot be used with"))
+        .failure();
+}
+
+#[test]
+fn run_exit_codes() {
+    let project = project("run_exit_codes")
+        .with_fuzz()
+        .fuzz_target(
+            "two_crashes",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    match data.first() {
+                        Some(b'x') => panic!("found an x"),
+                        Some(b'y') => panic!("found a y"),
+                        _ => {}
+                    }
+                });
+            "#,
+        )
+        .build();
+    let artifacts = project.fuzz_dir().join("artifacts").join("two_crashes");
+
+    // A new crash exits with 2.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("two_crashes")
+        .assert()
+        .code(2);
+    let known = project.root().join("known");
+    fs::rename(&artifacts, &known).unwrap();
+
+    // Known crashes don't fail the run, which keeps going until its time is
+    // up or it finds a new crash.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("two_crashes")
+        .arg("--ignore-known-crashes")
+        .arg(&known)
+        .arg("--max-total-time=60")
+        .assert()
+        .stderr(
+            predicate::str::contains("Loaded 1 known crash signature(s)")
+                .and(predicate::str::contains("found 1 new unique crash(es)")),
+        )
+        .code(2);
+    for artifact in fs::read_dir(&artifacts).unwrap() {
+        let artifact = artifact.unwrap();
+        fs::rename(artifact.path(), known.join(artifact.file_name())).unwrap();
+    }
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("two_crashes")
+        .arg("--ignore-known-crashes")
+        .arg(&known)
+        .arg("--max-total-time=3")
+        .assert()
+        .stderr(predicate::str::contains("known or duplicate crash(es)"))
+        .success();
+
+    // With a crash budget, fuzzing restarts after each new crash.
+    fs::remove_dir_all(&artifacts).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("two_crashes")
+        .arg("--max-crashes=2")
+        .arg("--max-total-time=60")
+        .assert()
+        .stderr(predicate::str::contains("found 2 new unique crash(es)"))
+        .code(2);
+}
+
+#[test]
+fn build_failure_exit_code() {
+    let project = project("build_failure_exit_code")
+        .with_fuzz()
+        .fuzz_target("broken", "this isn't Rust")
+        .build();
+    project.cargo_fuzz().arg("build").assert().code(3);
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("broken")
+        .assert()
+        .stderr(predicate::str::contains("failed to build fuzz script"))
+        .code(3);
+}
+
+#[test]
+fn run_until_new_coverage_stalls() {
+    let project = project("run_until_new_coverage_stalls")
+        .with_fuzz()
+        .fuzz_target(
+            "no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_until_new_coverage_stalls::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--until-new-coverage-stalls")
+        .arg("100000execs")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Stopped fuzzing `no_crash`: no new coverage for 100000 execs.",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--until-new-coverage-stalls")
+        .arg("5d")
+        .assert()
+        .stderr(predicate::str::contains("invalid stall limit: 5d"))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("no_crash")
+        .arg("--until-new-coverage-stalls")
+        .arg("999999999999999999h")
+        .assert()
+        .stderr(predicate::str::contains(
+            "stall limit too large: 999999999999999999h",
+        ))
+        .failure();
+}
+
+#[test]
+fn honggfuzz_unsupported_options() {
+    let project = project("honggfuzz_unsupported_options").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        "[targets.a]\nengine = \"honggfuzz\"\n",
+    )
+    .unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("a")
+        .arg("--until-new-coverage-stalls")
+        .arg("10s")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`--until-new-coverage-stalls` is only supported with the libFuzzer engine",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--engine")
+        .arg("honggfuzz")
+        .arg("a")
+        .arg("crash-1234")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`cargo fuzz tmin` doesn't support the honggfuzz engine",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("a")
+        .arg("--runner")
+        .arg("qemu-aarch64")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`--runner` is only supported with the libFuzzer engine",
+        ))
+        .failure();
+}
+
+#[test]
+fn libafl_unsupported_options() {
+    let project = project("libafl_unsupported_options").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--engine")
+        .arg("libafl")
+        .arg("a")
+        .arg("--until-new-coverage-stalls")
+        .arg("10s")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`--until-new-coverage-stalls` is only supported with the libFuzzer engine",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--engine")
+        .arg("libafl")
+        .arg("a")
+        .arg("crash-1234")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`cargo fuzz tmin` doesn't support the libafl engine",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--engine")
+        .arg("libafl")
+        .arg("--sanitizer")
+        .arg("memory")
+        .arg("a")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the libafl engine doesn't support the memory sanitizer",
+        ))
+        .failure();
+}
+
+#[test]
+fn run_with_crash_sarif() {
+    let project = project("run_with_crash_sarif")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_crash_sarif::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    let sarif = project.root().join("crashes.sarif");
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--sarif")
+        .arg(&sarif)
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .stderr(predicate::str::contains("Wrote 1 crash(es) as SARIF to:"))
+        .failure();
+
+    let sarif = fs::read_to_string(&sarif).unwrap();
+    assert!(sarif.contains(r#""version": "2.1.0""#));
+    assert!(sarif.contains(r#""ruleId": "panic""#));
+    assert!(sarif.contains(r#""uri": "src/lib.rs""#));
+    assert!(sarif.contains("crashHash/v1"));
+}
+
+#[test]
+fn run_stack_overflow() {
+    let project = project("run_stack_overflow")
+        .with_fuzz()
+        .fuzz_target(
+            "overflow",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fn nest(data: &[u8], depth: usize) -> usize {
+                    let frame = std::hint::black_box([depth as u8; 64]);
+                    match data.first() {
+                        Some(b'[') => nest(data, depth + 1) + frame[0] as usize,
+                        _ => depth,
+                    }
+                }
+
+                fuzz_target!(|data: &[u8]| {
+                    nest(data, 0);
+                });
+            "#,
+        )
+        .file("fuzz/corpus/overflow/nested", "[")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("overflow")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .stderr(
+            predicate::str::contains("The crash was a stack overflow (recursion through `")
+                .and(predicate::str::contains(
+                    "saved the input as fuzz/artifacts/overflow/stack-overflow-",
+                ))
+                .and(predicate::str::contains(
+                    "(ulimit -s 512; cargo fuzz repro overflow \
+                     fuzz/artifacts/overflow/stack-overflow-",
+                )),
+        )
+        .failure();
+
+    let artifacts: Vec<String> =
+        fs::read_dir(project.fuzz_dir().join("artifacts").join("overflow"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+    assert_eq!(artifacts.len(), 1);
+    assert!(artifacts[0].starts_with("stack-overflow-"));
+}
+
+#[test]
+fn run_save_hangs() {
+    let project = project("run_save_hangs")
+        .with_fuzz()
+        .fuzz_target(
+            "hang",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    while data.first() == Some(&b'h') {
+                        std::thread::sleep(std::time::Duration::from_millis(10));
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/hang/hang", "h")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("hang")
+        .arg("--save-hangs")
+        .arg("--timeout")
+        .arg("2")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .stderr(predicate::str::contains("ERROR: libFuzzer: timeout").and(
+            predicate::str::contains(
+                "Saved the input that hung as fuzz/artifacts/hang/hangs/hang-",
+            ),
+        ))
+        .failure();
+
+    let artifacts = project.fuzz_dir().join("artifacts").join("hang");
+    let mut hangs: Vec<PathBuf> = fs::read_dir(artifacts.join("hangs"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    hangs.sort();
+    assert_eq!(hangs.len(), 2);
+    assert_eq!(fs::read(&hangs[0]).unwrap(), b"h");
+    assert!(hangs[1].to_string_lossy().ends_with(".stacks.txt"));
+    assert!(fs::read_to_string(&hangs[1]).unwrap().contains("timeout"));
+    // The hang replaces libFuzzer's artifact of the timeout.
+    assert!(!fs::read_dir(&artifacts).unwrap().any(|e| e
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .starts_with("timeout-")));
+}
+
+#[test]
+fn watch() {
+    let target = |marker: &str| {
+        format!(
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {{
+                    // {}
+                }});
+            "#,
+            marker
+        )
+    };
+    let project = project("watch")
+        .with_fuzz()
+        .fuzz_target("foo", &target("before"))
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("watch")
+        .assert()
+        .stderr(predicate::str::contains("<target>"))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("watch")
+        .arg("nope")
+        .assert()
+        .stderr(predicate::str::contains("no fuzz target named `nope`"))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("watch")
+        .arg("foo")
+        .arg("--jobs")
+        .arg("0")
+        .assert()
+        .stderr(predicate::str::contains("0 jobs?"))
+        .failure();
+
+    let log_path = project.root().join("watch.log");
+    let mut watch = project
+        .cargo_fuzz()
+        .arg("watch")
+        .arg("foo")
+        .arg("--")
+        .arg("-runs=0")
+        .stderr(fs::File::create(&log_path).unwrap())
+        .spawn()
+        .unwrap();
+    let wait_for_exits = |count: usize| {
+        for _ in 0..1200 {
+            let log = fs::read_to_string(&log_path).unwrap();
+            let exits = log
+                .matches("Fuzz target exited with exit status: 0; waiting for changes...")
+                .count();
+            if exits >= count {
+                return true;
+            }
+            // A failed build waits for changes too, which won't come.
+            if log.contains("\n\nWaiting for changes...") {
+                return false;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(500));
+        }
+        false
+    };
+
+    // Changing the target's source rebuilds it, and fuzzes it again.
+    let started = wait_for_exits(1);
+    if started {
+        fs::write(project.fuzz_target_path("foo"), target("after")).unwrap();
+    }
+    let restarted = started && wait_for_exits(2);
+    let _ = watch.kill();
+    let _ = watch.wait();
+
+    let log = fs::read_to_string(&log_path).unwrap();
+    assert!(started, "{}", log);
+    assert!(restarted, "{}", log);
+    assert!(
+        log.contains("Sources changed; rebuilding `foo`..."),
+        "{}",
+        log
+    );
+}
+
+#[test]
+fn run_status_addr() {
+    use std::io::{Read, Write};
+
+    let project = project("run_status_addr")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    let get = |path: &str| {
+        let mut stream = std::net::TcpStream::connect(&addr).ok()?;
+        write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, addr).ok()?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).ok()?;
+        Some(response)
+    };
+    let mut fuzzer = project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--status-addr")
+        .arg(&addr)
+        .arg("--")
+        .arg("-max_total_time=60")
+        .spawn()
+        .unwrap();
+
+    // Wait for the build, and then for the fuzzer to report some progress.
+    let mut status = None;
+    for _ in 0..1200 {
+        if fuzzer.try_wait().unwrap().is_some() {
+            break;
+        }
+        if let Some(response) = get("/status") {
+            if !response.contains("\"execs\": 0,") {
+                status = Some(response);
+                break;
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    let metrics = get("/metrics");
+    let missing = get("/nope");
+    let _ = fuzzer.kill();
+    let _ = fuzzer.wait();
+
+    let status = status.expect("the status endpoint never reported any progress");
+    assert!(status.starts_with("HTTP/1.1 200 OK"), "{}", status);
+    assert!(status.contains("application/json"), "{}", status);
+    assert!(status.contains("\"target\": \"foo\""), "{}", status);
+    assert!(status.contains("\"crashes\": 0"), "{}", status);
+    let metrics = metrics.unwrap();
+    assert!(
+        metrics.contains("# TYPE cargo_fuzz_execs_total counter"),
+        "{}",
+        metrics
+    );
+    assert!(
+        metrics.contains("cargo_fuzz_crashes_total{target=\"foo\"} 0"),
+        "{}",
+        metrics
+    );
+    assert!(missing.unwrap().starts_with("HTTP/1.1 404 Not Found"));
+}
+
+#[test]
+fn run_with_crash() {
+    let project = project("run_with_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_crash::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .env("RUST_BACKTRACE", "1")
+        .assert()
+        .stderr(
+            predicate::str::contains("panicked at 'I'm afraid of number 7'")
+                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
+                .and(predicate::str::contains("run_with_crash::fail_fuzzing"))
+                .and(predicate::str::contains(
+                    "────────────────────────────────────────────────────────────────────────────────\n\
+                     \n\
+                     Failing input:\n\
+                     \n\
+                     \tfuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
+                .and(predicate::str::contains(
+                    "Reproduce with:\n\
+                     \n\
+                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains(
+                    "Minimize test case with:\n\
+                     \n\
+                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
+                )),
+        )
+        .failure();
+}
+
+#[test]
+fn run_auto_tmin() {
+    let input = format!("{}bug{}", "a".repeat(50), "z".repeat(47));
+    let project = project("run_auto_tmin")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.windows(3).any(|w| w == b"bug") {
+                        panic!("found the bug");
+                    }
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("corpus").join("foo").join("seed"),
+            &input,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--auto-tmin")
+        .arg("--auto-tmin-time")
+        .arg("30")
+        .assert()
+        .stderr(
+            predicate::str::contains("Minimizing fuzz/artifacts/foo/crash-")
+                .and(predicate::str::contains(
+                    "Minimized input (3 of 100 bytes):\n\n\tfuzz/artifacts/foo/minimized/crash-",
+                ))
+                .and(predicate::str::contains("\thex:     627567\n"))
+                .and(predicate::str::contains("\tescaped: b\"bug\"\n")),
+        )
+        .failure();
+
+    // The original artifact is kept next to the minimized one.
+    let artifacts = project.fuzz_dir().join("artifacts").join("foo");
+    let crash = fs::read_dir(&artifacts)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_file())
+        .unwrap();
+    assert_eq!(fs::read_to_string(&crash).unwrap(), input);
+    let minimized = artifacts.join("minimized").join(crash.file_name().unwrap());
+    assert_eq!(fs::read_to_string(minimized).unwrap(), "bug");
+}
+
+#[test]
+fn run_with_crash_json() {
+    let project = project("run_with_crash_json")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_crash_json::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--message-format")
+        .arg("json")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .stdout(
+            predicate::str::contains(
+                r#"{"reason":"build-finished","target":"yes_crash","success":true}"#,
+            )
+            .and(predicate::str::contains(
+                r#"{"reason":"new-coverage","target":"yes_crash""#,
+            ))
+            .and(predicate::str::contains(
+                r#"{"reason":"crash-found","target":"yes_crash","artifact":""#,
+            ))
+            .and(predicate::str::contains(
+                r#"{"reason":"run-finished","target":"yes_crash","success":false"#,
+            ))
+            .and(predicate::str::contains("Failing input").not()),
+        )
+        .stderr(predicate::str::contains("Failing input:"))
+        .failure();
+}
+
+#[test]
+fn run_without_sanitizer_with_crash() {
+    let project = project("run_without_sanitizer_with_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_without_sanitizer_with_crash::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("yes_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .arg("-sanitizer=none")
+        .env("RUST_BACKTRACE", "1")
+        .assert()
+        .stderr(
+            predicate::str::contains("panicked at 'I'm afraid of number 7'")
+                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
+                .and(predicate::str::contains("run_without_sanitizer_with_crash::fail_fuzzing"))
+                .and(predicate::str::contains(
+                    "────────────────────────────────────────────────────────────────────────────────\n\
+                     \n\
+                     Failing input:\n\
+                     \n\
+                     \tfuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
+                .and(predicate::str::contains(
+                    "Reproduce with:\n\
+                     \n\
+                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
+                ))
+                .and(predicate::str::contains(
+                    "Minimize test case with:\n\
+                     \n\
+                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
+                )),
+        )
+        .failure();
+}
+
+#[test]
+fn run_with_msan_no_crash() {
+    let project = project("run_with_msan_no_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "msan_no_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    // get data from fuzzer and print it
+                    // to force a memory access that cannot be optimized out
+                    if let Some(x) = data.get(0) {
+                        dbg!(x);
+                    }
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--sanitizer=memory")
+        .arg("msan_no_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .stderr(predicate::str::contains("Done 1000 runs"))
+        .success();
+}
+
+#[test]
+fn run_with_msan_with_crash() {
+    let project = project("run_with_msan_with_crash")
+        .with_fuzz()
+        .fuzz_target(
+            "msan_with_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let test_data: Vec<u8> = Vec::with_capacity(4);
+                    let uninitialized_value = unsafe {test_data.get_unchecked(0)};
+                    // prevent uninit read from being optimized out
+                    println!("{}", uninitialized_value);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--sanitizer=memory")
+        .arg("msan_with_crash")
+        .arg("--")
+        .arg("-runs=1000")
+        .assert()
+        .stderr(predicate::str::contains(
+            "MemorySanitizer: use-of-uninitialized-value",
+        ))
+        .failure();
+}
+
+#[test]
+fn run_all() {
+    let project = project("run_all")
+        .with_fuzz()
+        .fuzz_target(
+            "run_all_a",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_all::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "run_all_b",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_all::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "skipped",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_all::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--all")
+        .arg("run_all_*")
+        .arg("--")
+        .arg("-runs=100")
+        .assert()
+        .stderr(
+            predicate::str::contains("[run_all_a] Done 100 runs")
+                .and(predicate::str::contains("[run_all_b] Done 100 runs"))
+                .and(predicate::str::contains("[skipped]").not())
+                .and(predicate::str::contains(
+                    "Summary:\n\n\trun_all_a: ok\n\trun_all_b: ok\n",
+                )),
+        )
+        .success();
+}
+
+#[test]
+fn run_all_no_matching_targets() {
+    let project = project("run_all_no_matching_targets").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--all")
+        .arg("nope_*")
+        .assert()
+        .stderr(predicate::str::contains("no fuzz targets match `nope_*`"))
+        .failure();
+}
+
+#[test]
+fn ci() {
+    let project = project("ci")
+        .with_fuzz()
+        .fuzz_target(
+            "ci_pass",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    ci::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "ci_fail",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    ci::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("ci")
+        .arg("--total-time=4")
+        .arg("--junit=junit.xml")
+        .arg("--json=ci.json")
+        .assert()
+        .stderr(
+            predicate::str::contains("Fuzzing `ci_fail` for 2 seconds")
+                .and(predicate::str::contains("\tci_fail: 1 new crash(es) in "))
+                .and(predicate::str::contains("panic: I'm afraid of number 7"))
+                .and(
+                    predicate::str::is_match(r"\tci_pass: ok in \ds, \d+ corpus input\(s\)")
+                        .unwrap(),
+                )
+                .and(predicate::str::contains(
+                    "1 of 2 fuzz targets found new crashes",
+                )),
+        )
+        .code(2);
+
+    let junit = fs::read_to_string(project.root().join("junit.xml")).unwrap();
+    assert!(junit.contains(r#"<testsuite name="cargo-fuzz" tests="2" failures="1" errors="0""#));
+    assert!(junit.contains(r#"<testcase classname="fuzz" name="ci_pass""#));
+    assert!(junit.contains(r#"<failure message="panic: I'm afraid of number 7">"#));
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(project.root().join("ci.json")).unwrap()).unwrap();
+    assert_eq!(json["passed"], false);
+    assert_eq!(json["targets"][0]["target"], "ci_fail");
+    assert_eq!(
+        json["targets"][0]["new-crashes"].as_array().unwrap().len(),
+        1
+    );
+
+    // The crash is in the artifacts directory now, so finding it again
+    // doesn't fail.
+    project
+        .cargo_fuzz()
+        .arg("ci")
+        .arg("--total-time=2")
+        .arg("--no-minimize")
+        .arg("ci_fail")
+        .assert()
+        .stderr(
+            predicate::str::contains("with 1 known crash signature(s)")
+                .and(predicate::str::contains("\tci_fail: ok in "))
+                .and(predicate::str::contains(", 1 known crash(es)")),
+        )
+        .success();
+}
+
+#[test]
+fn run_with_config() {
+    let project = project("run_with_config")
+        .with_fuzz()
+        .fuzz_target(
+            "configured",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_with_config::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            r#"
+                [defaults]
+                args = ["-runs=10"]
+
+                [targets.configured]
+                sanitizer = "none"
+                args = ["-runs=20"]
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("configured")
+        .assert()
+        .stderr(
+            predicate::str::contains("Done 20 runs")
+                .and(predicate::str::contains("-Zsanitizer").not()),
+        )
+        .success();
+
+    // Arguments on the command line take precedence over the config file.
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("configured")
+        .arg("--")
+        .arg("-runs=30")
+        .assert()
+        .stderr(predicate::str::contains("Done 30 runs"))
+        .success();
+}
+
+#[test]
+#[cfg(unix)]
+fn run_with_runner() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = project("run_with_runner").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    // A runner that logs how it was called and runs the fuzz target.
+    let log = project.root().join("runner.log");
+    let runner = project.root().join("runner.sh");
+    fs::write(
+        &runner,
+        format!(
+            "#!/bin/sh\necho \"$1 $(basename $2)\" >> {}\nshift\nexec \"$@\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&runner, fs::Permissions::from_mode(0o755)).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--runner")
+        .arg(format!("{} cli", runner.display()))
+        .arg("a")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .stderr(predicate::str::contains("Done 10 runs"))
+        .success();
+
+    // Commands that run the fuzz target directly use the runner too.
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        format!("[targets.a]\nrunner = \"{} config\"\n", runner.display()),
+    )
+    .unwrap();
+    project.cargo_fuzz().arg("cmin").arg("a").assert().success();
+
+    let log = fs::read_to_string(&log).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert!(lines.len() > 1, "{}", log);
+    assert_eq!(lines[0], "cli a");
+    assert!(lines[1..].iter().all(|line| *line == "config a"), "{}", log);
+}
+
+#[test]
+fn invalid_config() {
+    let project = project("invalid_config")
+        .with_fuzz()
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            r#"
+                [defaults]
+                sanitiser = "none"
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("list")
+        .assert()
+        .stderr(
+            predicate::str::contains("could not decode the config file at")
+                .and(predicate::str::contains("unknown field `sanitiser`")),
+        )
+        .failure();
+}
+
+#[test]
+fn run_one_input() {
+    let corpus = Path::new("fuzz").join("corpus").join("run_one");
+
+    let project = project("run_one_input")
+        .with_fuzz()
+        .fuzz_target(
+            "run_one",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    assert!(data.is_empty());
+                });
+            "#,
+        )
+        .file(corpus.join("pass"), "")
+        .file(corpus.join("fail"), "not empty")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("run_one")
+        .arg(corpus.join("pass"))
+        .assert()
+        .stderr(
+            predicate::str::contains("Running 1 inputs 1 time(s) each.").and(
+                predicate::str::contains("Running: fuzz/corpus/run_one/pass"),
+            ),
+        )
+        .success();
+}
+
+#[test]
+fn run_a_few_inputs() {
+    let corpus = Path::new("fuzz").join("corpus").join("run_few");
+
+    let project = project("run_a_few_inputs")
+        .with_fuzz()
+        .fuzz_target(
+            "run_few",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    assert!(data.len() != 4);
+                });
+            "#,
+        )
+        .file(corpus.join("pass-0"), "")
+        .file(corpus.join("pass-1"), "1")
+        .file(corpus.join("pass-2"), "12")
+        .file(corpus.join("pass-3"), "123")
+        .file(corpus.join("fail"), "fail")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("run_few")
+        .arg(corpus.join("pass-0"))
+        .arg(corpus.join("pass-1"))
+        .arg(corpus.join("pass-2"))
+        .arg(corpus.join("pass-3"))
+        .assert()
+        .stderr(
+            predicate::str::contains("Running 4 inputs 1 time(s) each.").and(
+                predicate::str::contains("Running: fuzz/corpus/run_few/pass"),
+            ),
+        )
+        .success();
+}
+
+#[test]
+fn run_alt_corpus() {
+    let corpus = Path::new("fuzz").join("corpus").join("run_alt");
+    let alt_corpus = Path::new("fuzz").join("alt-corpus").join("run_alt");
+
+    let project = project("run_alt_corpus")
+        .with_fuzz()
+        .fuzz_target(
+            "run_alt",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    assert!(data.len() <= 1);
+                });
+            "#,
+        )
+        .file(corpus.join("fail"), "fail")
+        .file(alt_corpus.join("pass-0"), "0")
+        .file(alt_corpus.join("pass-1"), "1")
+        .file(alt_corpus.join("pass-2"), "2")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("run_alt")
+        .arg(&alt_corpus)
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(
+            predicate::str::contains("3 files found in fuzz/alt-corpus/run_alt")
+                .and(predicate::str::contains("fuzz/corpus/run_alt").not())
+                // libFuzzer will always test the empty input, so the number of
+                // runs performed is always one more than the number of files in
+                // the corpus.
+                .and(predicate::str::contains("Done 4 runs in")),
+        )
+        .success();
+}
+
+#[test]
+fn debug_fmt() {
+    let corpus = Path::new("fuzz").join("corpus").join("debugfmt");
+    let project = project("debugfmt")
+        .with_fuzz()
+        .fuzz_target(
+            "debugfmt",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+                use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured, Result};
+
+                #[derive(Debug)]
+                pub struct Rgb {
+                    r: u8,
+                    g: u8,
+                    b: u8,
+                }
+
+                impl Arbitrary for Rgb {
+                    fn arbitrary(raw: &mut Unstructured<'_>) -> Result<Self> {
+                        let mut buf = [0; 3];
+                        raw.fill_buffer(&mut buf)?;
+                        let r = buf[0];
+                        let g = buf[1];
+                        let b = buf[2];
+                        Ok(Rgb { r, g, b })
+                    }
+                }
+
+                fuzz_target!(|data: Rgb| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "111")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("debugfmt")
+        .arg("fuzz/corpus/debugfmt/0")
+        .assert()
+        .stderr(predicates::str::contains(
+            "
+Rgb {
+    r: 49,
+    g: 49,
+    b: 49,
+}",
+        ))
+        .success();
+}
+
+#[test]
+fn debug_fmt_decoder() {
+    let corpus = Path::new("fuzz").join("corpus");
+    let project = project("debugfmt_decoder")
+        .with_fuzz()
+        .fuzz_target(
+            "bytes",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    debugfmt_decoder::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .fuzz_target(
+            "pair",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: (u8, u16)| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("bytes").join("0"), "hi")
+        .file(corpus.join("pair").join("0"), "abc")
+        .file(corpus.join("pair").join("1"), "a")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("bytes")
+        .arg(corpus.join("bytes").join("0"))
+        .assert()
+        .stderr(predicate::str::contains("[104, 105]"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("pair")
+        .arg(corpus.join("pair").join("0"))
+        .assert()
+        .stderr(predicate::str::contains("(\n    97,\n"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("fmt")
+        .arg("pair")
+        .arg(corpus.join("pair").join("1"))
+        .assert()
+        .stderr(predicate::str::contains(
+            "the fuzz target skips inputs shorter than the 3 byte(s) `(u8, u16)` needs",
+        ));
+}
+
+#[test]
+fn cmin() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("cmin")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "a")
+        .file(corpus.join("2"), "ab")
+        .file(corpus.join("3"), "abc")
+        .file(corpus.join("4"), "abcd")
+        .build();
+
+    // The `map` fails the test on an entry that can't be read.
+    #[allow(clippy::suspicious_map)]
+    let corpus_count = || {
+        fs::read_dir(project.root().join("fuzz").join("corpus").join("foo"))
+            .unwrap()
+            .map(|e| e.unwrap())
+            .count()
+    };
+    assert_eq!(corpus_count(), 5);
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .assert()
+        .success();
+    assert_eq!(corpus_count(), 1);
+}
+
+#[test]
+fn cmin_only_len() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("cmin_only_len")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "a")
+        .file(corpus.join("2"), "ab")
+        .file(corpus.join("3"), "abc")
+        .file(corpus.join("4"), "abcd")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .arg("--only-len")
+        .arg("5..2")
+        .assert()
+        .stderr(predicate::str::contains(
+            "invalid length range: 5..2 (no length is in it)",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .arg("--only-len")
+        .arg("2..")
+        .assert()
+        .stderr(predicate::str::contains(
+            "3 of 5 input(s) in the corpus are 2.. byte(s) long",
+        ))
+        .success();
+
+    // The short inputs are kept, and the rest are minimized to one.
+    let corpus = project.root().join("fuzz").join("corpus").join("foo");
+    assert!(corpus.join("0").is_file());
+    assert!(corpus.join("1").is_file());
+    assert_eq!(fs::read_dir(&corpus).unwrap().count(), 3);
+}
+
+#[test]
+fn cmin_seeds() {
+    let project = project("cmin_seeds")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    match data.first() {
+                        Some(b'a') => println!("a"),
+                        Some(b'b') => println!("b"),
+                        _ => {}
+                    }
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            r#"
+                [targets.foo]
+                corpus = "working/foo"
+                seeds = ["seeds/foo"]
+            "#,
+        )
+        .file(Path::new("fuzz").join("seeds").join("foo").join("s"), "a")
+        .file(Path::new("fuzz").join("working").join("foo").join("x"), "a")
+        .file(Path::new("fuzz").join("working").join("foo").join("y"), "b")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(
+            predicate::str::contains("1 files found in").and(predicate::str::contains(format!(
+                "seeds{}foo",
+                std::path::MAIN_SEPARATOR
+            ))),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .assert()
+        .success();
+
+    // Only the input that adds coverage beyond the seeds is kept, and the
+    // seeds are left alone.
+    let working = project.root().join("fuzz").join("working").join("foo");
+    let inputs: Vec<_> = fs::read_dir(&working)
+        .unwrap()
+        .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    assert_eq!(inputs, vec![b"b".to_vec()]);
+    let seeds = project.root().join("fuzz").join("seeds").join("foo");
+    assert_eq!(fs::read_dir(&seeds).unwrap().count(), 1);
+    assert!(!project
+        .root()
+        .join("fuzz")
+        .join("corpus")
+        .join("foo")
+        .exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn run_env_and_hooks() {
+    let project = project("run_env_and_hooks")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {
+                    assert_eq!(std::env::var("FOO_MODE").as_deref(), Ok("strict"));
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            r#"
+                [targets.foo]
+                pre-run = "echo $FOO_MODE > pre-$CARGO_FUZZ_COMMAND"
+                post-run = "echo $CARGO_FUZZ_TARGET > post-$CARGO_FUZZ_COMMAND"
+
+                [targets.foo.env]
+                FOO_MODE = "strict"
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--")
+        .arg("-runs=10")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .assert()
+        .success();
+
+    let read = |name: &str| fs::read_to_string(project.fuzz_dir().join(name)).unwrap();
+    assert_eq!(read("pre-run"), "strict\n");
+    assert_eq!(read("post-run"), "foo\n");
+    assert_eq!(read("pre-cmin"), "strict\n");
+    assert_eq!(read("post-cmin"), "foo\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn run_failing_pre_run_hook() {
+    let project = project("run_failing_pre_run_hook")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            r#"
+                [targets.foo]
+                pre-run = "exit 3"
+                post-run = "touch ran-post"
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains("the pre-run hook of foo failed"))
+        .failure();
+    assert!(!project.fuzz_dir().join("ran-post").exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn run_on_crash_hook() {
+    let project = project("run_on_crash_hook")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"boom" {
+                        panic!("boom");
+                    }
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("corpus").join("foo").join("boom"),
+            "boom",
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            r#"
+                [targets.foo]
+                on-crash = "printf '%s\n' {target} {artifact} $CARGO_FUZZ_ENGINE $CARGO_FUZZ_CRASH_KIND $CARGO_FUZZ_STACK_HASH > on-crash.txt; exit 1"
+            "#,
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .assert()
+        .stderr(
+            predicate::str::contains("warning: the on-crash hook of foo failed on ")
+                .and(predicate::str::contains("Failing input:")),
+        )
+        .failure();
+
+    let lines: Vec<String> = fs::read_to_string(project.fuzz_dir().join("on-crash.txt"))
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect();
+    assert_eq!(lines.len(), 5, "{:?}", lines);
+    assert_eq!(lines[0], "foo");
+    let artifact = Path::new(&lines[1]);
+    assert!(artifact.is_absolute());
+    assert!(artifact.starts_with(
+        project
+            .fuzz_dir()
+            .join("artifacts")
+            .join("foo")
+            .canonicalize()
+            .unwrap()
+    ));
+    assert_eq!(fs::read_to_string(artifact).unwrap(), "boom");
+    assert_eq!(lines[2], "libfuzzer");
+    assert_eq!(lines[3], "panic");
+    assert_eq!(lines[4].len(), 16);
+}
+
+#[test]
+fn run_notify_webhook() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let project = project("run_notify_webhook")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"boom" {
+                        panic!("boom");
+                    }
+                });
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("corpus").join("foo").join("boom"),
+            "boom",
+        )
+        .build();
+
+    // A stand-in for a webhook that passes on the body of each request.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (bodies, received) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut len = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                    len = value.trim().parse().unwrap();
+                }
+                if line.trim().is_empty() {
+                    break;
+                }
+            }
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+            bodies.send(String::from_utf8(body).unwrap()).unwrap();
+            (&stream)
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+        }
+    });
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--notify-webhook")
+        .arg(format!("http://{}/hook", addr))
+        .assert()
+        .stderr(predicate::str::contains("Failing input:"))
+        .failure();
+
+    // Every event is posted before cargo-fuzz exits.
+    let events: Vec<serde_json::Value> = received
+        .try_iter()
+        .map(|body| serde_json::from_str(&body).unwrap())
+        .collect();
+    assert_eq!(events.len(), 2, "{:?}", events);
+    assert_eq!(events[0]["event"], "crash-found");
+    assert_eq!(events[0]["target"], "foo");
+    let artifacts = events[0]["artifacts"].as_array().unwrap();
+    assert_eq!(artifacts.len(), 1);
+    let artifact = Path::new(artifacts[0].as_str().unwrap());
+    assert_eq!(fs::read_to_string(artifact).unwrap(), "boom");
+    assert!(events[0]["text"]
+        .as_str()
+        .unwrap()
+        .starts_with("Fuzzing `foo` found a crash: "));
+    assert_eq!(events[1]["event"], "run-finished");
+    assert_eq!(events[1]["success"], false);
+    assert_eq!(events[1]["crashes"], 1);
+}
+
+#[test]
+fn serve_and_worker() {
+    let fuzz_target = r#"
+        #![no_main]
+        use libfuzzer_sys::fuzz_target;
+
+        fuzz_target!(|_data: &[u8]| {});
+    "#;
+    let coordinator = project("serve_and_worker_coordinator")
+        .with_fuzz()
+        .fuzz_target("foo", fuzz_target)
+        .file(
+            Path::new("fuzz").join("corpus").join("foo").join("a"),
+            "from the coordinator",
+        )
+        .build();
+    // Each test gets a single project directory, so the worker needs one of
+    // its own.
+    let worker = ProjectBuilder::new(
+        "serve_and_worker_worker",
+        next_root().with_extension("worker"),
+    )
+    .with_fuzz()
+    .fuzz_target("foo", fuzz_target)
+    .file(
+        Path::new("fuzz").join("corpus").join("foo").join("b"),
+        "from the worker",
+    )
+    .build();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{}", port);
+    let mut server = coordinator
+        .cargo_fuzz()
+        .arg("serve")
+        .arg("--bind")
+        .arg(&addr)
+        .spawn()
+        .unwrap();
+    for _ in 0..100 {
+        if std::net::TcpStream::connect(&addr).is_ok() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    let result = worker
+        .cargo_fuzz()
+        .arg("worker")
+        .arg("foo")
+        .arg("--connect")
+        .arg(&addr)
+        .arg("--max-total-time")
+        .arg("1")
+        .assert();
+    let _ = server.kill();
+    let _ = server.wait();
+    result
+        .stderr(predicate::str::contains(
+            "Synced with the coordinator: pulled 1 and pushed 1 input(s)",
+        ))
+        .success();
+
+    // Both corpora end up with both inputs.
+    for project in &[&coordinator, &worker] {
+        let corpus = project.fuzz_dir().join("corpus").join("foo");
+        let inputs: Vec<_> = fs::read_dir(&corpus)
+            .unwrap()
+            .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect();
+        assert!(inputs.contains(&"from the coordinator".to_string()));
+        assert!(inputs.contains(&"from the worker".to_string()));
+    }
+}
+
+#[test]
+fn run_asan_options() {
+    let project = project("run_asan_options")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("asan_options"),
+            "# Allocations that fail return null instead of crashing.\n\
+             allocator_may_return_null=1\n",
+        )
+        .file(
+            Path::new("fuzz").join("asan.supp"),
+            "interceptor_via_fun:foo\n",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--asan-option")
+        .arg("detect_stack_use_after_return=1")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(
+            predicate::str::contains(
+                "Sanitizer options: ASAN_OPTIONS=detect_odr_violation=0:allocator_may_return_null=1:suppressions=",
+            )
+            .and(predicate::str::contains(
+                "asan.supp:detect_stack_use_after_return=1\n",
+            )),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("foo")
+        .arg("--asan-option")
+        .arg("detect_leaks")
+        .assert()
+        .stderr(predicate::str::contains("must be a single option"))
+        .failure();
+}
+
+#[test]
+fn cmin_all() {
+    let target = r#"
+        #![no_main]
+        use libfuzzer_sys::fuzz_target;
+
+        fuzz_target!(|data: &[u8]| {
+            let _ = data;
+        });
+    "#;
+    let corpus = Path::new("fuzz").join("corpus");
+    let project = project("cmin_all")
+        .with_fuzz()
+        .fuzz_target("foo", target)
+        .fuzz_target("bar", target)
+        .file(corpus.join("foo").join("0"), "")
+        .file(corpus.join("foo").join("1"), "a")
+        .file(corpus.join("foo").join("2"), "ab")
+        .file(corpus.join("bar").join("0"), "abc")
+        .file(corpus.join("bar").join("1"), "abcd")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("--all")
+        .arg("-j2")
+        .assert()
+        .stderr(
+            predicate::str::contains("foo: 3 -> 1 inputs, 3 -> ")
+                .and(predicate::str::contains("bar: 2 -> 1 inputs, 7 -> ")),
+        )
+        .success();
+}
+
+#[test]
+fn cmin_prune_older_than() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("cmin_prune_older_than")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "a")
+        .file(corpus.join("2"), "ab")
+        .build();
+
+    // The inputs were all just written, so none of them are old enough to
+    // prune.
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .arg("--prune-older-than")
+        .arg("7d")
+        .assert()
+        .stderr(predicate::str::contains("foo: 3 -> 3 inputs"))
+        .success();
+
+    // Nothing is older than an age from before the clock's earliest time.
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .arg("--prune-older-than")
+        .arg("30000000000000w")
+        .assert()
+        .stderr(predicate::str::contains("foo: 3 -> 3 inputs"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .arg("--prune-older-than")
+        .arg("99999999999999999w")
+        .assert()
+        .stderr(predicate::str::contains(
+            "age too large: 99999999999999999w",
+        ))
+        .failure();
+}
+
+#[test]
+fn cmin_pinned() {
+    let corpus = Path::new("fuzz").join("corpus").join("foo");
+    let project = project("cmin_pinned")
+        .with_fuzz()
+        .fuzz_target(
+            "foo",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let _ = data;
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "ab")
+        .file(corpus.join("2"), "xyz")
+        .file(
+            Path::new("fuzz")
+                .join("artifacts")
+                .join("foo")
+                .join("crash-1"),
+            "ab!",
+        )
+        .file(Path::new("keep").join("k"), "q")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("foo")
+        .arg("--pin-artifacts")
+        .arg("--preserve")
+        .arg("keep")
+        .assert()
+        .stderr(predicate::str::contains(
+            "pinned input(s) of `foo` that add no coverage",
+        ))
+        .success();
+
+    let kept: Vec<Vec<u8>> = fs::read_dir(project.root().join(&corpus))
+        .unwrap()
+        .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(kept.contains(&b"ab".to_vec()));
+    assert!(kept.contains(&b"q".to_vec()));
+    assert!(!kept.contains(&b"xyz".to_vec()));
+}
+
+#[test]
+fn cmin_all_no_matching_targets() {
+    let project = project("cmin_all_no_matching_targets").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+
+    project
+        .cargo_fuzz()
+        .arg("cmin")
+        .arg("--all")
+        .arg("nope_*")
+        .assert()
+        .stderr(predicate::str::contains("no fuzz targets match `nope_*`"))
+        .failure();
+}
+
+#[test]
+fn repro() {
+    let project = project("repro")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    repro::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(Path::new("crash"), "7777777")
+        .file(Path::new("no_crash"), "7")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("yes_crash")
+        .arg("crash")
+        .assert()
+        .stderr(
+            predicate::str::contains("I'm afraid of number 7")
+                .and(predicate::str::contains("reproduced the crash")),
+        )
+        .code(2);
+
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("yes_crash")
+        .arg("no_crash")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`yes_crash` ran no_crash without crashing",
+        ))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("yes_crash")
+        .arg("crash")
+        .arg("--debugger=gdb")
+        .arg("--print-command")
+        .assert()
+        .stdout(
+            predicate::str::contains("RUST_BACKTRACE=1")
+                .and(predicate::str::is_match("ASAN_OPTIONS=[^ ]*abort_on_error=1").unwrap())
+                .and(
+                    predicate::str::is_match(
+                        r"rust-gdb --args [^ ]*/debuginfo/[^ ]*/yes_crash crash\n",
+                    )
+                    .unwrap(),
+                ),
+        )
+        .success();
+}
+
+#[test]
+#[cfg(unix)]
+fn package_repro() {
+    let project = project("package_repro")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    package_repro::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(Path::new("crash-7"), "7777777")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("package-repro")
+        .arg("--sanitizer=none")
+        .arg("yes_crash")
+        .arg("crash-7")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Reproducer for crash-7 written to:\n\n\tfuzz/repro/yes_crash-crash-7.tar.gz",
+        ))
+        .success();
+
+    // The archive reproduces the crash on its own, away from the project.
+    let unpacked = project.root().join("unpacked");
+    fs::create_dir(&unpacked).unwrap();
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(
+            project
+                .fuzz_dir()
+                .join("repro")
+                .join("yes_crash-crash-7.tar.gz"),
+        )
+        .arg("-C")
+        .arg(&unpacked)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let dir = unpacked.join("yes_crash-crash-7");
+    for file in ["yes_crash", "crash-7", "repro.sh", "README.txt"] {
+        assert!(dir.join(file).is_file(), "no {} in the archive", file);
+    }
+    let readme = fs::read_to_string(dir.join("README.txt")).unwrap();
+    assert!(readme.contains("`package_repro`"));
+    assert!(readme.contains("with no sanitizer"));
+    let output = Command::new(dir.join("repro.sh")).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("I'm afraid of number 7"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn repro_trace() {
+    let project = project("repro_trace")
+        .with_fuzz()
+        .fuzz_target(
+            "stateful",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+                use std::sync::atomic::{AtomicBool, Ordering};
+
+                static ARMED: AtomicBool = AtomicBool::new(false);
+
+                fuzz_target!(|data: &[u8]| {
+                    if data == b"arm" {
+                        ARMED.store(true, Ordering::SeqCst);
+                    }
+                    if data == b"boom" && ARMED.load(Ordering::SeqCst) {
+                        panic!("boom after arm");
+                    }
+                });
+            "#,
+        )
+        .file("fuzz/corpus/stateful/a", "arm")
+        .file("fuzz/corpus/stateful/b", "boom")
+        .file(Path::new("boom"), "boom")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("stateful")
+        .arg("--record-trace")
+        .arg("10")
+        .assert()
+        .stderr(
+            predicate::str::contains("boom after arm").and(predicate::str::contains(
+                "cargo fuzz repro stateful <artifact> --trace",
+            )),
+        )
+        .failure();
+    // libFuzzer runs an empty input before the corpus.
+    let trace = project.fuzz_dir().join("traces").join("stateful");
+    assert_eq!(fs::read_dir(&trace).unwrap().count(), 3);
+
+    // The crash only happens after the input that came before it.
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("stateful")
+        .arg("boom")
+        .assert()
+        .success();
+    project
+        .cargo_fuzz()
+        .arg("repro")
+        .arg("stateful")
+        .arg("boom")
+        .arg("--trace")
+        .assert()
+        .stderr(
+            predicate::str::contains("Running 2 input(s) from")
+                .and(predicate::str::contains("boom after arm")),
+        )
+        .code(2);
+}
+
+#[test]
+fn report() {
+    let project = project("report")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    report::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(Path::new("crash"), "x7zzzzz")
+        .file(Path::new("no_crash"), "x")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("report")
+        .arg("yes_crash")
+        .arg("crash")
+        .assert()
+        .stdout(
+            predicate::str::contains(
+                "## Fuzz target `yes_crash` crashes: panic: I'm afraid of number 7",
+            )
+            .and(predicate::str::contains("- cargo-fuzz: "))
+            .and(predicate::str::contains("- rustc: rustc "))
+            .and(predicate::str::contains("- sanitizer: address"))
+            .and(predicate::str::contains("report::fail_fuzzing(data);"))
+            .and(predicate::str::contains(
+                "7 byte(s):\n\n```rust\nb\"x7zzzzz\"\n```",
+            ))
+            .and(predicate::str::contains("panicked at"))
+            .and(predicate::str::contains(
+                "echo 'eDd6enp6eg==' | base64 -d > crash.bin\ncargo fuzz run yes_crash crash.bin",
+            )),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("report")
+        .arg("yes_crash")
+        .arg("crash")
+        .arg("--minimize")
+        .assert()
+        .stdout(predicate::str::contains("7 byte(s), minimized:"))
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("report")
+        .arg("yes_crash")
+        .arg("no_crash")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`yes_crash` ran no_crash without crashing",
+        ))
+        .failure();
+}
+
+#[test]
+#[cfg(unix)]
+fn report_github() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let project = project("report_github")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    report_github::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(Path::new("crash"), "7777777")
+        .build();
+
+    // A fake curl that logs its arguments and request, and responds like the
+    // GitHub API.
+    let bin = project.root().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let log = project.root().join("curl.log");
+    let request = project.root().join("request.json");
+    let curl = bin.join("curl");
+    fs::write(
+        &curl,
+        format!(
+            "#!/bin/sh\necho \"$@\" > {}\ncat > {}\n\
+             echo '{{\"html_url\": \"https://github.com/owner/repo/issues/1\"}}'\n",
+            log.display(),
+            request.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&curl, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", bin.display(), std::env::var("PATH").unwrap());
+
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .env_remove("GITHUB_TOKEN")
+        .arg("report")
+        .arg("yes_crash")
+        .arg("crash")
+        .arg("--github=owner/repo")
+        .assert()
+        .stderr(predicate::str::contains("needs a token in $GITHUB_TOKEN"))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .env("PATH", &path)
+        .env("GITHUB_TOKEN", "secret")
+        .env("GITHUB_API_URL", "https://github.example.com/api/v3/")
+        .arg("report")
+        .arg("yes_crash")
+        .arg("crash")
+        .arg("--github=owner/repo")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Opened https://github.com/owner/repo/issues/1",
+        ))
+        .stdout("")
+        .success();
+
+    let log = fs::read_to_string(&log).unwrap();
+    assert!(log.contains("https://github.example.com/api/v3/repos/owner/repo/issues"));
+    assert!(!log.contains("secret"));
+    let request: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&request).unwrap()).unwrap();
+    assert_eq!(
+        request["title"],
+        "Fuzz target `yes_crash` crashes: panic: I'm afraid of number 7"
+    );
+    assert!(request["body"]
+        .as_str()
+        .unwrap()
+        .contains("cargo fuzz run yes_crash crash.bin"));
+}
+
+#[test]
+fn annotate() {
+    let project = project("annotate")
+        .with_fuzz()
+        .fuzz_target(
+            "yes_crash",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    annotate::fail_fuzzing(data);
+                });
+            "#,
+        )
+        .file(Path::new("fuzz/artifacts/yes_crash/crash-7"), "7777777")
+        .build();
+    let lib = project.root().join("src").join("lib.rs");
+    let source = fs::read_to_string(&lib).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("annotate")
+        .arg("--diff")
+        .arg("yes_crash")
+        .arg("fuzz/artifacts/yes_crash/crash-7")
+        .assert()
+        .stdout(
+            predicate::str::contains("/src/lib.rs\n@@ ").and(predicate::str::is_match(
+                r"\n\+ +// FUZZ-CRASH: [0-9a-f]{16} reproduced by fuzz/artifacts/yes_crash/crash-7\n +panic!",
+            ).unwrap()),
+        )
+        .success();
+    assert_eq!(fs::read_to_string(&lib).unwrap(), source);
+
+    project
+        .cargo_fuzz()
+        .arg("annotate")
+        .arg("yes_crash")
+        .arg("fuzz/artifacts/yes_crash/crash-7")
+        .assert()
+        .stderr(predicate::str::contains("Annotated src/lib.rs:"))
+        .success();
+    let annotated = fs::read_to_string(&lib).unwrap();
+    let comment = annotated
+        .lines()
+        .position(|line| line.trim_start().starts_with("// FUZZ-CRASH: "))
+        .unwrap();
+    let lines: Vec<&str> = annotated.lines().collect();
+    assert!(lines[comment].ends_with(" reproduced by fuzz/artifacts/yes_crash/crash-7"));
+    assert!(lines[comment + 1].trim_start().starts_with("panic!"));
+
+    // Annotating the same crash again leaves the source alone.
+    project
+        .cargo_fuzz()
+        .arg("annotate")
+        .arg("yes_crash")
+        .arg("fuzz/artifacts/yes_crash/crash-7")
+        .assert()
+        .stderr(predicate::str::contains("is already annotated with crash"))
+        .success();
+    assert_eq!(fs::read_to_string(&lib).unwrap(), annotated);
+}
+
+#[test]
+fn tmin() {
+    let corpus = Path::new("fuzz").join("corpus").join("i_hate_zed");
+    let test_case = corpus.join("test-case");
+    let project = project("tmin")
+        .with_fuzz()
+        .fuzz_target(
+            "i_hate_zed",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    let s = String::from_utf8_lossy(data);
+                    if s.contains('z') {
+                        panic!("nooooooooo");
+                    }
+                });
+            "#,
+        )
+        .file(&test_case, "pack my box with five dozen liquor jugs")
+        .build();
+    let test_case = project.root().join(test_case);
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("i_hate_zed")
+        .arg(&test_case)
+        .assert()
+        .stderr(
+            predicates::str::contains("CRASH_MIN: minimizing crash input: ")
+                .and(predicate::str::contains("(1 bytes) caused a crash"))
+                .and(predicate::str::contains(
+                    "────────────────────────────────────────────────────────────────────────────────\n\
+                     \n\
+                     Minimized artifact:\n\
+                     \n\
+                     \tfuzz/artifacts/i_hate_zed/minimized-from-"))
+                .and(predicate::str::contains(
+                    "Reproduce with:\n\
+                     \n\
+                     \tcargo fuzz run i_hate_zed fuzz/artifacts/i_hate_zed/minimized-from-"
+                )),
+        )
+        .success();
+}
+
+#[test]
+fn tmin_batch() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("tmin_batch");
+    let project = project("tmin_batch")
+        .with_fuzz()
+        .fuzz_target(
+            "tmin_batch",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fn hate_z(data: &[u8]) {
+                    if data.contains(&b'z') {
+                        panic!("no z");
+                    }
+                }
+
+                fn hate_q(data: &[u8]) {
+                    if data.contains(&b'q') {
+                        panic!("no q");
+                    }
+                }
+
+                fuzz_target!(|data: &[u8]| {
+                    hate_z(data);
+                    hate_q(data);
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-1"), "the lazy dog")
+        .file(artifacts.join("crash-2"), "fizz")
+        .file(artifacts.join("crash-3"), "the quick brown fox")
+        .file(artifacts.join("crash-fixed"), "ok")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--batch")
+        .arg("-j2")
+        .arg("tmin_batch")
         .assert()
         .stderr(
-            predicate::str::contains("panicked at 'I'm afraid of number 7'")
-                .and(predicate::str::contains("ERROR: libFuzzer: deadly signal"))
-                .and(predicate::str::contains("run_without_sanitizer_with_crash::fail_fuzzing"))
+            predicate::str::contains("Minimized 3 test case(s) into 2 unique crash(es).").and(
+                predicate::str::contains(
+                    "No longer reproducing, left as they were:\n\n\t\
+                     fuzz/artifacts/tmin_batch/crash-fixed",
+                ),
+            ),
+        )
+        .success();
+
+    let mut names: Vec<String> = fs::read_dir(project.root().join(&artifacts))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names.len(), 3);
+    assert!(names.contains(&"crash-fixed".to_string()));
+    for name in names.iter().filter(|n| *n != "crash-fixed") {
+        // `crash-<16 hex digit hash>-<length>`, minimized down to one byte.
+        assert_eq!(name.len(), "crash-".len() + 16 + "-1".len());
+        assert!(name.ends_with("-1"));
+    }
+}
+
+#[test]
+fn bisect() {
+    let project = project("bisect")
+        .with_fuzz()
+        .fuzz_target(
+            "check",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    bisect::check(data);
+                });
+            "#,
+        )
+        .file(
+            Path::new("src").join("lib.rs"),
+            "pub fn check(data: &[u8]) { let _ = data; }",
+        )
+        .file("crash-x", "x")
+        .build();
+
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(project.root())
+            .args([
+                "-c",
+                "user.name=Jane Doe",
+                "-c",
+                "user.email=jane@example.com",
+            ])
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let commit = |message: &str| {
+        git(&[
+            "add",
+            "src",
+            "Cargo.toml",
+            "fuzz/Cargo.toml",
+            "fuzz/fuzz_targets",
+        ]);
+        assert!(git(&["commit", "--quiet", "-m", message]).status.success());
+    };
+    git(&["init", "--quiet"]);
+    commit("Initial commit");
+    fs::write(
+        project.root().join("src").join("lib.rs"),
+        "pub fn check(_: &[u8]) {}",
+    )
+    .unwrap();
+    commit("Rename an argument");
+    fs::write(
+        project.root().join("src").join("lib.rs"),
+        "pub fn check(data: &[u8]) { assert_ne!(data, b\"x\"); }",
+    )
+    .unwrap();
+    commit("Reject x");
+    fs::write(
+        project.root().join("src").join("lib.rs"),
+        "pub fn check(data: &[u8]) {\n    assert_ne!(data, b\"x\");\n}\n",
+    )
+    .unwrap();
+    commit("Format the code");
+
+    let first_bad = String::from_utf8(git(&["rev-parse", "HEAD~1"]).stdout).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("bisect")
+        .arg("check")
+        .arg("crash-x")
+        .arg("--good")
+        .arg("HEAD~3")
+        .assert()
+        .stdout(first_bad)
+        .stderr(predicate::str::contains("is the first bad commit"))
+        .success();
+
+    // The worktree is removed afterwards.
+    let worktrees = String::from_utf8(git(&["worktree", "list"]).stdout).unwrap();
+    assert_eq!(worktrees.lines().count(), 1);
+
+    project
+        .cargo_fuzz()
+        .arg("bisect")
+        .arg("check")
+        .arg("crash-x")
+        .arg("--good")
+        .arg("HEAD")
+        .assert()
+        .stderr(predicate::str::contains(
+            "can't bisect from `HEAD`, where the artifact crashes",
+        ))
+        .failure();
+}
+
+#[test]
+fn triage() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("triage");
+    let project = project("triage")
+        .with_fuzz()
+        .fuzz_target(
+            "triage",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fn parse_a(data: &[u8]) {
+                    panic!("bad a: {}", data.len());
+                }
+
+                fn parse_b(_data: &[u8]) {
+                    panic!("bad b");
+                }
+
+                fuzz_target!(|data: &[u8]| {
+                    match data.first() {
+                        Some(b'a') => parse_a(data),
+                        Some(b'b') => parse_b(data),
+                        _ => {}
+                    }
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-a1"), "a")
+        .file(artifacts.join("crash-a2"), "aaaa")
+        .file(artifacts.join("crash-b"), "b")
+        .file(artifacts.join("crash-fixed"), "z")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("triage")
+        .arg("triage")
+        .assert()
+        .stderr(
+            predicate::str::contains("Found 2 unique crash(es) in 4 artifact(s).")
                 .and(predicate::str::contains(
-                    "────────────────────────────────────────────────────────────────────────────────\n\
-                     \n\
-                     Failing input:\n\
-                     \n\
-                     \tfuzz/artifacts/yes_crash/crash-"
+                    "Representative input:\n\n\tfuzz/artifacts/triage/crash-a1",
                 ))
-                .and(predicate::str::contains("Output of `std::fmt::Debug`:"))
                 .and(predicate::str::contains(
-                    "Reproduce with:\n\
-                     \n\
-                     \tcargo fuzz run yes_crash fuzz/artifacts/yes_crash/crash-"
+                    "Duplicates:\n\n\tfuzz/artifacts/triage/crash-a2",
                 ))
                 .and(predicate::str::contains(
-                    "Minimize test case with:\n\
-                     \n\
-                     \tcargo fuzz tmin yes_crash fuzz/artifacts/yes_crash/crash-"
+                    "No longer reproducing:\n\n\tfuzz/artifacts/triage/crash-fixed",
                 )),
         )
-        .failure();
+        .success();
+}
+
+#[test]
+fn verify_fixes() {
+    let artifacts = Path::new("fuzz").join("artifacts").join("verify");
+    let project = project("verify_fixes")
+        .with_fuzz()
+        .fuzz_target(
+            "verify",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    if data.first() == Some(&b'x') {
+                        panic!("still an x");
+                    }
+                });
+            "#,
+        )
+        .fuzz_target(
+            "clean",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    verify_fixes::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(artifacts.join("crash-x"), "x")
+        .file(artifacts.join("crash-y"), "y")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("verify-fixes")
+        .assert()
+        .stderr(
+            predicate::str::contains("`verify`: 1 fixed, 1 still crashing")
+                .and(predicate::str::contains(
+                    "still crashes: fuzz/artifacts/verify/crash-x",
+                ))
+                .and(predicate::str::contains("still an x"))
+                .and(predicate::str::contains(
+                    "fixed: fuzz/artifacts/verify/crash-y",
+                ))
+                .and(predicate::str::contains("`clean`").not()),
+        )
+        .code(2);
+
+    project
+        .cargo_fuzz()
+        .arg("verify-fixes")
+        .arg("ver*")
+        .arg("--move-fixed")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Moved 1 fixed artifact(s) to fuzz/artifacts/verify/fixed",
+        ))
+        .code(2);
+    let artifacts = project.root().join(artifacts);
+    assert!(artifacts.join("fixed").join("crash-y").is_file());
+    assert!(!artifacts.join("crash-y").exists());
+
+    // Once the last crash is fixed too, it succeeds.
+    fs::remove_file(artifacts.join("crash-x")).unwrap();
+    project
+        .cargo_fuzz()
+        .arg("verify-fixes")
+        .assert()
+        .stderr(predicate::str::contains("No artifacts to replay."))
+        .success();
+}
+
+#[test]
+fn coverage() {
+    let corpus = Path::new("fuzz").join("corpus").join("cover");
+    let project = project("coverage")
+        .with_fuzz()
+        .fuzz_target(
+            "cover",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    coverage::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "")
+        .file(corpus.join("1"), "a")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover")
+        .arg("--format")
+        .arg("lcov")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Coverage lcov written to:\n\n\tfuzz/coverage/cover/lcov.info",
+        ))
+        .success();
+
+    let coverage_dir = project.fuzz_dir().join("coverage").join("cover");
+    assert!(coverage_dir.join("coverage.profdata").is_file());
+    let lcov = fs::read_to_string(coverage_dir.join("lcov.info")).unwrap();
+    assert!(lcov.contains("src/lib.rs"));
+    assert!(!lcov.contains("fuzz_targets"));
+}
+
+#[test]
+fn coverage_cmin_first() {
+    let corpus = Path::new("fuzz").join("corpus").join("cover_min");
+    let project = project("coverage_cmin_first")
+        .with_fuzz()
+        .fuzz_target(
+            "cover_min",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    coverage_cmin_first::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .file(corpus.join("1"), "b")
+        .file(corpus.join("2"), "c")
+        .file(corpus.join("3"), "d")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover_min")
+        .arg("--cmin-first")
+        .arg("--jobs=2")
+        .assert()
+        .stderr(
+            predicate::str::contains("Minimized the corpus from 4 to ").and(
+                predicate::str::contains(
+                    "Coverage profdata written to:\n\n\tfuzz/coverage/cover_min/coverage.profdata",
+                ),
+            ),
+        )
+        .success();
+
+    // The corpus itself is left alone.
+    assert_eq!(
+        fs::read_dir(project.root().join(&corpus)).unwrap().count(),
+        4
+    );
+    let coverage_dir = project.fuzz_dir().join("coverage").join("cover_min");
+    assert!(coverage_dir.join("coverage.profdata").is_file());
+}
+
+#[test]
+fn coverage_upload() {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let corpus = Path::new("fuzz").join("corpus").join("cover_up");
+    let project = project("coverage_upload")
+        .with_fuzz()
+        .fuzz_target(
+            "cover_up",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    coverage_upload::pass_fuzzing(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .build();
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(project.root())
+            .args([
+                "-c",
+                "user.name=Jane Doe",
+                "-c",
+                "user.email=jane@example.com",
+            ])
+            .args(args)
+            .assert()
+            .success();
+    };
+    git(&["init", "--quiet"]);
+    git(&["add", "src"]);
+    git(&["commit", "--quiet", "-m", "Initial commit"]);
+
+    // A stand-in for Codecov that records the one request it gets.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut head = String::new();
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                len = value.trim().parse().unwrap();
+            }
+            if line.trim().is_empty() {
+                break;
+            }
+            head.push_str(&line);
+        }
+        let mut body = vec![0; len];
+        reader.read_exact(&mut body).unwrap();
+        (&stream)
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        (head, String::from_utf8(body).unwrap())
+    });
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover_up")
+        .arg("--format")
+        .arg("cobertura")
+        .arg("--upload")
+        .arg("codecov")
+        .env("CODECOV_URL", format!("http://{}", addr))
+        .env("CODECOV_TOKEN", "secret")
+        .assert()
+        .stderr(
+            predicate::str::contains(
+                "Coverage cobertura written to:\n\n\tfuzz/coverage/cover_up/cobertura.xml",
+            )
+            .and(predicate::str::contains("to Codecov")),
+        )
+        .success();
+
+    let cobertura =
+        fs::read_to_string(project.fuzz_dir().join("coverage/cover_up/cobertura.xml")).unwrap();
+    assert!(cobertura.contains("<class name=\"src/lib.rs\" filename=\"src/lib.rs\""));
+    assert!(cobertura.contains("<line number="));
+
+    let (head, body) = server.join().unwrap();
+    assert!(head.starts_with("POST /upload/v2?token=secret&commit="));
+    assert!(head.contains("&flags=fuzz"));
+    assert!(body.contains("src/lib.rs"));
+    assert!(body.contains("DA:"));
+}
+
+#[test]
+fn coverage_uncovered() {
+    let corpus = Path::new("fuzz").join("corpus").join("cover_gaps");
+    let project = project("coverage_uncovered")
+        .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                pub mod codec;
+
+                pub fn parse(data: &[u8]) -> usize {
+                    data.len()
+                }
+
+                pub fn render(n: usize) -> String {
+                    let mut out = String::new();
+                    for _ in 0..n {
+                        out.push('x');
+                    }
+                    out
+                }
+
+                pub struct Parser;
+
+                impl Parser {
+                    pub fn reset(&mut self) -> bool {
+                        true
+                    }
+                }
+
+                fn private() {}
+            "#,
+        )
+        .file(
+            Path::new("src").join("codec.rs"),
+            r#"
+                pub fn encode(data: &[u8]) -> Vec<u8> {
+                    data.to_vec()
+                }
+
+                pub fn decode(data: &[u8]) -> Vec<u8> {
+                    data.to_vec()
+                }
+            "#,
+        )
+        .fuzz_target(
+            "cover_gaps",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    coverage_uncovered::parse(data);
+                });
+            "#,
+        )
+        .file(corpus.join("0"), "a")
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover_gaps")
+        .arg("--uncovered")
+        .assert()
+        .stderr(predicate::str::contains(
+            "3 public function(s) or module(s) of `coverage_uncovered` aren't covered at all",
+        ))
+        .stdout(
+            predicate::str::is_match(concat!(
+                r"(?s)coverage_uncovered::codec \(module, 2 public function\(s\)\)\n",
+                r".*coverage_uncovered::render\n.*coverage_uncovered::Parser::reset\n$",
+            ))
+            .unwrap()
+            .and(predicate::str::contains("::parse").not())
+            .and(predicate::str::contains("::private").not())
+            .and(predicate::str::contains("::encode").not()),
+        )
+        .success();
 }
 
 #[test]
-fn run_with_msan_no_crash() {
-    let project = project("run_with_msan_no_crash")
+fn coverage_diff() {
+    let corpus = Path::new("fuzz").join("corpus").join("cover_diff");
+    let project = project("coverage_diff")
         .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                pub fn classify(data: &[u8]) -> u8 {
+                    match data.first() {
+                        Some(b'a') => 1,
+                        Some(b'b') => 2,
+                        _ => 0,
+                    }
+                }
+            "#,
+        )
         .fuzz_target(
-            "msan_no_crash",
+            "cover_diff",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    // get data from fuzzer and print it
-                    // to force a memory access that cannot be optimized out
-                    if let Some(x) = data.get(0) {
-                        dbg!(x);
-                    }
+                    coverage_diff::classify(data);
                 });
             "#,
         )
+        .file(corpus.join("0"), "a")
+        .file(Path::new("more").join("1"), "b")
         .build();
 
     project
         .cargo_fuzz()
-        .arg("run")
-        .arg("--sanitizer=memory")
-        .arg("msan_no_crash")
-        .arg("--")
-        .arg("-runs=1000")
+        .arg("coverage")
+        .arg("cover_diff")
         .assert()
-        .stderr(predicate::str::contains("Done 1000 runs"))
         .success();
+    let coverage_dir = project.fuzz_dir().join("coverage").join("cover_diff");
+    let base = project.root().join("base.profdata");
+    fs::copy(coverage_dir.join("coverage.profdata"), &base).unwrap();
+
+    // Comparing with the profile that is about to be overwritten is an error.
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover_diff")
+        .arg("--diff")
+        .arg(coverage_dir.join("coverage.profdata"))
+        .assert()
+        .stderr(predicate::str::contains("is about to be overwritten"))
+        .failure();
+
+    // Adding an input that takes the other branch only gains coverage.
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover_diff")
+        .arg(&corpus)
+        .arg("more")
+        .arg("--diff")
+        .arg(&base)
+        .assert()
+        .stdout(predicate::str::contains("src/lib.rs").and(predicate::str::contains("-0")))
+        .stderr(predicate::str::contains(
+            "fuzz/coverage/cover_diff/coverage.diff",
+        ))
+        .success();
+    let diff = fs::read_to_string(coverage_dir.join("coverage.diff")).unwrap();
+    assert!(diff.contains("--- src/lib.rs (base)"));
+    assert!(diff
+        .lines()
+        .any(|line| line.starts_with('+') && line.ends_with("Some(b'b') => 2,")));
+    assert!(!diff
+        .lines()
+        .any(|line| line.starts_with('-') && line.contains(" | ")));
+
+    // Replacing the corpus with that input loses the coverage of the first.
+    project
+        .cargo_fuzz()
+        .arg("coverage")
+        .arg("cover_diff")
+        .arg("more")
+        .arg("--diff")
+        .arg(&base)
+        .assert()
+        .stderr(predicate::str::contains(
+            "region(s) lost coverage compared to",
+        ))
+        .failure();
+    let diff = fs::read_to_string(coverage_dir.join("coverage.diff")).unwrap();
+    assert!(diff
+        .lines()
+        .any(|line| line.starts_with('-') && line.ends_with("Some(b'a') => 1,")));
 }
 
 #[test]
-fn run_with_msan_with_crash() {
-    let project = project("run_with_msan_with_crash")
+fn explain() {
+    let corpus = Path::new("fuzz").join("corpus").join("explain");
+    let project = project("explain")
         .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                pub fn classify(data: &[u8]) -> u8 {
+                    match data.first() {
+                        Some(b'a') => 1,
+                        Some(b'b') => 2,
+                        _ => 0,
+                    }
+                }
+            "#,
+        )
         .fuzz_target(
-            "msan_with_crash",
+            "explain",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    let test_data: Vec<u8> = Vec::with_capacity(4);
-                    let uninitialized_value = unsafe {test_data.get_unchecked(0)};
-                    // prevent uninit read from being optimized out
-                    println!("{}", uninitialized_value);
+                    explain::classify(data);
                 });
             "#,
         )
+        .file(corpus.join("0"), "a")
+        .file(corpus.join("1"), "b")
+        .file("other", "bb")
         .build();
 
     project
         .cargo_fuzz()
-        .arg("run")
-        .arg("--sanitizer=memory")
-        .arg("msan_with_crash")
-        .arg("--")
-        .arg("-runs=1000")
+        .arg("explain")
+        .arg("explain")
+        .arg(corpus.join("1"))
         .assert()
         .stderr(predicate::str::contains(
-            "MemorySanitizer: use-of-uninitialized-value",
+            "1 of which none of the other 1 input(s) do",
         ))
-        .failure();
+        .stdout(
+            predicate::str::contains("src/lib.rs")
+                .and(predicate::str::contains("Some(b'b') => 2,"))
+                .and(predicate::str::contains("Some(b'a')").not()),
+        )
+        .success();
+
+    project
+        .cargo_fuzz()
+        .arg("explain")
+        .arg("explain")
+        .arg("other")
+        .assert()
+        .stderr(predicate::str::contains(
+            "all of which the other 2 input(s) cover too",
+        ))
+        .success();
 }
 
 #[test]
-fn run_one_input() {
-    let corpus = Path::new("fuzz").join("corpus").join("run_one");
-
-    let project = project("run_one_input")
+fn suggest_split() {
+    let corpus = Path::new("fuzz").join("corpus").join("split");
+    let project = project("suggest_split")
         .with_fuzz()
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                pub fn parse(data: &[u8]) -> usize {
+                    checksum(data) as usize
+                }
+
+                pub fn render(data: &[u8]) -> String {
+                    format!("{}", checksum(data))
+                }
+
+                pub fn checksum(data: &[u8]) -> u8 {
+                    data.iter().fold(0, |sum, b| sum.wrapping_add(*b))
+                }
+            "#,
+        )
         .fuzz_target(
-            "run_one",
+            "split",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    assert!(data.is_empty());
+                    match data.first() {
+                        Some(b'p') => {
+                            suggest_split::parse(data);
+                        }
+                        Some(b'r') => {
+                            suggest_split::render(data);
+                        }
+                        _ => {}
+                    }
                 });
             "#,
         )
-        .file(corpus.join("pass"), "")
-        .file(corpus.join("fail"), "not empty")
+        .file(corpus.join("p1"), "p1")
+        .file(corpus.join("p2"), "p2")
+        .file(corpus.join("r1"), "r1")
+        .file(corpus.join("x"), "x")
         .build();
 
     project
         .cargo_fuzz()
-        .arg("run")
-        .arg("run_one")
-        .arg(corpus.join("pass"))
+        .arg("suggest-split")
+        .arg("split")
+        .arg("--scaffold")
         .assert()
         .stderr(
-            predicate::str::contains("Running 1 inputs 1 time(s) each.").and(
-                predicate::str::contains("Running: fuzz/corpus/run_one/pass"),
-            ),
+            predicate::str::contains("1 input(s) reach none of the public functions")
+                .and(predicate::str::contains("these 2 groups"))
+                .and(predicate::str::contains(
+                    "Added fuzz/fuzz_targets/split_parse.rs",
+                )),
+        )
+        .stdout(
+            predicate::str::contains("split_parse (2 input(s))\n    suggest_split::parse\n")
+                .and(predicate::str::contains(
+                    "split_render (1 input(s))\n    suggest_split::render\n",
+                ))
+                .and(predicate::str::contains("checksum").not()),
         )
         .success();
+
+    let source = fs::read_to_string(project.fuzz_target_path("split_render")).unwrap();
+    assert!(source.starts_with("// Split from `split` by `cargo fuzz suggest-split`"));
+    assert!(source.contains("suggest_split::render(data);"));
+    let names = |target: &str| {
+        let mut names: Vec<_> = fs::read_dir(project.fuzz_dir().join("corpus").join(target))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        names
+    };
+    assert_eq!(names("split_parse"), ["p1", "p2"]);
+    assert_eq!(names("split_render"), ["r1"]);
 }
 
 #[test]
-fn run_a_few_inputs() {
-    let corpus = Path::new("fuzz").join("corpus").join("run_few");
-
-    let project = project("run_a_few_inputs")
+fn run_only_matching() {
+    let corpus = Path::new("fuzz").join("corpus").join("only_matching");
+    let project = project("run_only_matching")
         .with_fuzz()
         .fuzz_target(
-            "run_few",
+            "only_matching",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    assert!(data.len() != 4);
+                    if data.starts_with(b"boom") {
+                        panic!("boom");
+                    }
                 });
             "#,
         )
-        .file(corpus.join("pass-0"), "")
-        .file(corpus.join("pass-1"), "1")
-        .file(corpus.join("pass-2"), "12")
-        .file(corpus.join("pass-3"), "123")
-        .file(corpus.join("fail"), "fail")
+        .file(corpus.join("boom"), "boom")
+        .file(corpus.join("ok"), "ok")
         .build();
 
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("run_few")
-        .arg(corpus.join("pass-0"))
-        .arg(corpus.join("pass-1"))
-        .arg(corpus.join("pass-2"))
-        .arg(corpus.join("pass-3"))
+        .arg("only_matching")
+        .arg("--only-prefix")
+        .arg("ff")
         .assert()
-        .stderr(
-            predicate::str::contains("Running 4 inputs 1 time(s) each.").and(
-                predicate::str::contains("Running: fuzz/corpus/run_few/pass"),
-            ),
-        )
+        .stderr(predicate::str::contains(
+            "none of the 2 input(s) in the corpus start with `ff`",
+        ))
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("only_matching")
+        .arg("--only-matching")
+        .arg("^ok")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains(
+            "1 of 2 input(s) in the corpus match `^ok`",
+        ))
         .success();
 }
 
 #[test]
-fn run_alt_corpus() {
-    let corpus = Path::new("fuzz").join("corpus").join("run_alt");
-    let alt_corpus = Path::new("fuzz").join("alt-corpus").join("run_alt");
-
-    let project = project("run_alt_corpus")
+fn run_mode_leaks() {
+    let corpus = Path::new("fuzz").join("corpus").join("mode_leaks");
+    let project = project("run_mode_leaks")
         .with_fuzz()
         .fuzz_target(
-            "run_alt",
+            "mode_leaks",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    assert!(data.len() <= 1);
+                    if data == b"leak" {
+                        std::mem::forget(std::hint::black_box(data.repeat(256)));
+                    }
                 });
             "#,
         )
-        .file(corpus.join("fail"), "fail")
-        .file(alt_corpus.join("pass-0"), "0")
-        .file(alt_corpus.join("pass-1"), "1")
-        .file(alt_corpus.join("pass-2"), "2")
+        .file(corpus.join("leak"), "leak")
         .build();
 
     project
         .cargo_fuzz()
         .arg("run")
-        .arg("run_alt")
-        .arg(&alt_corpus)
+        .arg("mode_leaks")
+        .arg("--mode")
+        .arg("leaks")
         .arg("--")
         .arg("-runs=0")
         .assert()
-        .stderr(
-            predicate::str::contains("3 files found in fuzz/alt-corpus/run_alt")
-                .and(predicate::str::contains("fuzz/corpus/run_alt").not())
-                // libFuzzer will always test the empty input, so the number of
-                // runs performed is always one more than the number of files in
-                // the corpus.
-                .and(predicate::str::contains("Done 4 runs in")),
-        )
-        .success();
+        .stderr(predicate::str::contains(
+            "Saved 1 input(s) that leak memory",
+        ))
+        .failure();
+
+    let leaks = project
+        .root()
+        .join("fuzz")
+        .join("artifacts")
+        .join("mode_leaks")
+        .join("leaks");
+    let names: Vec<_> = fs::read_dir(&leaks)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(names.len(), 1);
+    assert!(names[0].starts_with("leak-"));
 }
 
 #[test]
-fn debug_fmt() {
-    let corpus = Path::new("fuzz").join("corpus").join("debugfmt");
-    let project = project("debugfmt")
+fn run_mode_quick() {
+    let project = project("run_mode_quick")
         .with_fuzz()
         .fuzz_target(
-            "debugfmt",
+            "mode_quick",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
-                use libfuzzer_sys::arbitrary::{Arbitrary, Unstructured, Result};
-
-                #[derive(Debug)]
-                pub struct Rgb {
-                    r: u8,
-                    g: u8,
-                    b: u8,
-                }
 
-                impl Arbitrary for Rgb {
-                    fn arbitrary(raw: &mut Unstructured<'_>) -> Result<Self> {
-                        let mut buf = [0; 3];
-                        raw.fill_buffer(&mut buf)?;
-                        let r = buf[0];
-                        let g = buf[1];
-                        let b = buf[2];
-                        Ok(Rgb { r, g, b })
+                fuzz_target!(|data: &[u8]| {
+                    if let [a, b, ..] = *data {
+                        let _ = std::hint::black_box(a + b);
                     }
-                }
-
-                fuzz_target!(|data: Rgb| {
-                    let _ = data;
                 });
             "#,
         )
-        .file(corpus.join("0"), "111")
         .build();
 
     project
         .cargo_fuzz()
-        .arg("fmt")
-        .arg("debugfmt")
-        .arg("fuzz/corpus/debugfmt/0")
+        .arg("run")
+        .arg("mode_quick")
+        .arg("--mode")
+        .arg("quick")
+        .arg("--sanitizer")
+        .arg("address")
         .assert()
-        .stderr(predicates::str::contains(
-            "
-Rgb {
-    r: 49,
-    g: 49,
-    b: 49,
-}",
+        .stderr(predicate::str::contains(
+            "`--mode quick` builds without a sanitizer",
         ))
-        .success();
+        .failure();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("mode_quick")
+        .arg("--mode")
+        .arg("quick")
+        .arg("--")
+        .arg("-runs=100000")
+        .assert()
+        .stderr(predicate::str::contains("attempt to add with overflow"))
+        .failure();
+
+    // It's built apart from the default build, which it doesn't overwrite.
+    let build_dir = project.fuzz_build_dir();
+    let triple = build_dir.file_name().unwrap();
+    assert!(build_dir
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("libfuzzer-none-quick")
+        .join(triple)
+        .join("release")
+        .join("mode_quick")
+        .is_file());
 }
 
 #[test]
-fn cmin() {
-    let corpus = Path::new("fuzz").join("corpus").join("foo");
-    let project = project("cmin")
+fn run_focus_changed() {
+    let corpus = Path::new("fuzz").join("corpus").join("focus_changed");
+    let lib = r#"
+        pub fn classify(data: &[u8]) -> u8 {
+            match data.first() {
+                Some(b'a') => 1,
+                Some(b'b') => 2,
+                _ => 0,
+            }
+        }
+    "#;
+    let project = project("focus_changed")
         .with_fuzz()
+        .file(Path::new("src").join("lib.rs"), lib)
         .fuzz_target(
-            "foo",
+            "focus_changed",
             r#"
                 #![no_main]
                 use libfuzzer_sys::fuzz_target;
 
                 fuzz_target!(|data: &[u8]| {
-                    let _ = data;
+                    focus_changed::classify(data);
                 });
             "#,
         )
-        .file(corpus.join("0"), "")
-        .file(corpus.join("1"), "a")
-        .file(corpus.join("2"), "ab")
-        .file(corpus.join("3"), "abc")
-        .file(corpus.join("4"), "abcd")
+        .file(corpus.join("0"), "a")
+        .file(corpus.join("1"), "b")
         .build();
 
-    let corpus_count = || {
-        fs::read_dir(project.root().join("fuzz").join("corpus").join("foo"))
-            .unwrap()
-            .map(|e| e.unwrap())
-            .count()
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .current_dir(project.root())
+            .args([
+                "-c",
+                "user.name=Jane Doe",
+                "-c",
+                "user.email=jane@example.com",
+            ])
+            .args(args)
+            .assert()
+            .success();
     };
-    assert_eq!(corpus_count(), 5);
+    git(&["init", "--quiet"]);
+    git(&["add", "src"]);
+    git(&["commit", "--quiet", "-m", "Initial commit"]);
 
     project
         .cargo_fuzz()
-        .arg("cmin")
-        .arg("foo")
+        .arg("run")
+        .arg("focus_changed")
+        .arg("--focus-changed=HEAD")
+        .arg("--")
+        .arg("-runs=0")
         .assert()
+        .stderr(predicate::str::contains(
+            "nothing in src changed since `HEAD`",
+        ))
         .success();
-    assert_eq!(corpus_count(), 1);
+
+    fs::write(
+        project.root().join("src").join("lib.rs"),
+        lib.replace("=> 2", "=> 3"),
+    )
+    .unwrap();
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("focus_changed")
+        .arg("--focus-changed=HEAD")
+        .arg("--")
+        .arg("-runs=0")
+        .assert()
+        .stderr(predicate::str::contains(
+            "1 of 2 input(s) reach the 1 code region(s) changed since `HEAD`",
+        ))
+        .success();
+    let focus = project.fuzz_dir().join("focus").join("focus_changed");
+    assert!(focus.join("1").is_file());
+    assert!(!focus.join("0").exists());
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("focus_changed")
+        .arg("--focus-changed=no-such-revision")
+        .assert()
+        .stderr(predicate::str::contains(
+            "failed to diff the sources against `no-such-revision`",
+        ))
+        .failure();
 }
 
 #[test]
-fn tmin() {
-    let corpus = Path::new("fuzz").join("corpus").join("i_hate_zed");
-    let test_case = corpus.join("test-case");
-    let project = project("tmin")
+fn oss_fuzz_init() {
+    let target = r#"
+        #![no_main]
+        use libfuzzer_sys::fuzz_target;
+
+        fuzz_target!(|_data: &[u8]| {});
+    "#;
+    let project = project("oss_fuzz_init")
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "oss_fuzz_init"
+                version = "1.0.0"
+                authors = ["Jane Doe <jane@example.com>"]
+                repository = "https://github.com/example/oss_fuzz_init"
+            "#,
+        )
         .with_fuzz()
-        .fuzz_target(
-            "i_hate_zed",
+        .fuzz_target("oss_a", target)
+        .fuzz_target("oss_b", target)
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
             r#"
-                #![no_main]
-                use libfuzzer_sys::fuzz_target;
+                [defaults]
+                args = ["-max_len=64"]
 
-                fuzz_target!(|data: &[u8]| {
-                    let s = String::from_utf8_lossy(data);
-                    if s.contains('z') {
-                        panic!("nooooooooo");
-                    }
-                });
+                [targets.oss_b]
+                features = "extra"
             "#,
         )
-        .file(&test_case, "pack my box with five dozen liquor jugs")
+        .file(
+            Path::new("fuzz").join("dictionaries").join("oss_a.dict"),
+            "\"magic\"\n",
+        )
         .build();
-    let test_case = project.root().join(test_case);
+    Command::new("git")
+        .arg("init")
+        .arg("--quiet")
+        .current_dir(project.root())
+        .assert()
+        .success();
+
     project
         .cargo_fuzz()
-        .arg("tmin")
-        .arg("i_hate_zed")
-        .arg(&test_case)
+        .arg("oss-fuzz-init")
+        .assert()
+        .stderr(predicate::str::contains(
+            "Copy them to `projects/oss-fuzz-init`",
+        ))
+        .success();
+
+    let dir = project.fuzz_dir().join("oss-fuzz").join("oss-fuzz-init");
+    let project_yaml = fs::read_to_string(dir.join("project.yaml")).unwrap();
+    assert!(project_yaml.contains("language: rust"));
+    assert!(project_yaml.contains("primary_contact: \"jane@example.com\""));
+    assert!(project_yaml.contains("main_repo: \"https://github.com/example/oss_fuzz_init\""));
+    let dockerfile = fs::read_to_string(dir.join("Dockerfile")).unwrap();
+    assert!(dockerfile.contains(
+        "RUN git clone --depth 1 https://github.com/example/oss_fuzz_init oss-fuzz-init"
+    ));
+    let build = fs::read_to_string(dir.join("build.sh")).unwrap();
+    assert!(build.contains("cd $SRC/oss-fuzz-init\n"));
+    assert!(build.contains("cargo fuzz build -O oss_a\n"));
+    assert!(build.contains("cargo fuzz build -O --features 'extra' oss_b\n"));
+    assert!(build.contains(
+        "FUZZ_TARGET_OUTPUT_DIR=$SRC/oss-fuzz-init/fuzz/target/x86_64-unknown-linux-gnu/\
+         libfuzzer-address/x86_64-unknown-linux-gnu/release"
+    ));
+    assert!(build.contains("cp $FUZZ_TARGET_OUTPUT_DIR/oss_a $OUT/"));
+    assert!(build.contains("cp fuzz/dictionaries/oss_a.dict $OUT/"));
+    assert!(!build.contains("oss_b.dict"));
+    assert!(build.contains("zip -jqr $OUT/oss_b_seed_corpus.zip fuzz/corpus/oss_b"));
+    assert!(build.contains("cat > $OUT/oss_b.options <<'EOF'\n[libfuzzer]\nmax_len = 64\nEOF"));
+
+    // Existing project files aren't overwritten by accident.
+    project
+        .cargo_fuzz()
+        .arg("oss-fuzz-init")
+        .assert()
+        .stderr(predicate::str::contains("pass `--force` to overwrite it"))
+        .failure();
+    project
+        .cargo_fuzz()
+        .arg("oss-fuzz-init")
+        .arg("--force")
+        .arg("--contact=fuzz@example.com")
         .assert()
-        .stderr(
-            predicates::str::contains("CRASH_MIN: minimizing crash input: ")
-                .and(predicate::str::contains("(1 bytes) caused a crash"))
-                .and(predicate::str::contains(
-                    "────────────────────────────────────────────────────────────────────────────────\n\
-                     \n\
-                     Minimized artifact:\n\
-                     \n\
-                     \tfuzz/artifacts/i_hate_zed/minimized-from-"))
-                .and(predicate::str::contains(
-                    "Reproduce with:\n\
-                     \n\
-                     \tcargo fuzz run i_hate_zed fuzz/artifacts/i_hate_zed/minimized-from-"
-                )),
-        )
         .success();
+    let project_yaml = fs::read_to_string(dir.join("project.yaml")).unwrap();
+    assert!(project_yaml.contains("primary_contact: \"fuzz@example.com\""));
 }
 
 #[test]
@@ -709,6 +7312,386 @@ fn build_one() {
     assert!(!b_bin.is_file());
 }
 
+#[test]
+fn build_required_features() {
+    let project = project("build_required_features")
+        .with_fuzz()
+        .fuzz_target(
+            "plain",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz_targets").join("extra.rs"),
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                #[cfg(not(feature = "extra"))]
+                compile_error!("built without the `extra` feature");
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .build();
+    let mut manifest = fs::OpenOptions::new()
+        .append(true)
+        .open(project.fuzz_cargo_toml())
+        .unwrap();
+    std::io::Write::write_all(
+        &mut manifest,
+        br#"
+[features]
+extra = []
+
+[[bin]]
+name = "extra"
+path = "fuzz_targets/extra.rs"
+required-features = ["extra"]
+"#,
+    )
+    .unwrap();
+
+    // Building every target builds the ones with required features too.
+    project.cargo_fuzz().arg("build").assert().success();
+    let build_dir = project.fuzz_build_dir().join("release");
+    assert!(build_dir.join("plain").is_file());
+    assert!(build_dir.join("extra").is_file());
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("extra")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+}
+
+#[test]
+fn build_pinned_toolchain() {
+    let project = project("build_pinned_toolchain")
+        .with_fuzz()
+        .fuzz_target(
+            "pinned",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|_data: &[u8]| {});
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("fuzz.toml"),
+            "toolchain = \"nightly-1999-01-01\"\n",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the toolchain `nightly-1999-01-01` that fuzz builds are pinned to isn't installed; \
+             run `rustup toolchain install nightly-1999-01-01`",
+        ))
+        .failure();
+
+    // `--toolchain` takes precedence over fuzz.toml.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--toolchain")
+        .arg("nightly-1999-02-02")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the toolchain `nightly-1999-02-02` that fuzz builds are pinned to",
+        ))
+        .failure();
+}
+
+#[test]
+fn build_libfuzzer_archive() {
+    let project = project("build_libfuzzer_archive").with_fuzz().build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("custom_runtime")
+        .assert()
+        .success();
+    project.cargo_fuzz().arg("build").assert().success();
+
+    // Link the archive that `libfuzzer-sys` built as if it were a patched one.
+    let build_dir = project.fuzz_build_dir();
+    let built = fs::read_dir(build_dir.join("release").join("build"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path().join("out").join("libfuzzer.a"))
+        .find(|archive| archive.is_file())
+        .unwrap();
+    fs::copy(&built, project.root().join("libFuzzer.a")).unwrap();
+    fs::write(
+        project.fuzz_dir().join("fuzz.toml"),
+        "libfuzzer-archive = \"../libFuzzer.a\"\n",
+    )
+    .unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("custom_runtime")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+    // Each archive builds into a directory of its own.
+    let configs: Vec<String> = fs::read_dir(build_dir.parent().unwrap().parent().unwrap())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert!(configs
+        .iter()
+        .any(|config| config.starts_with("libfuzzer-address-archive")));
+
+    // `--libfuzzer-archive` takes precedence over fuzz.toml.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--libfuzzer-archive")
+        .arg("missing.a")
+        .assert()
+        .stderr(predicate::str::contains(
+            "the libFuzzer archive missing.a doesn't exist",
+        ))
+        .failure();
+}
+
+#[test]
+fn build_sanitizer_dirs() {
+    let project = project("build_sanitizer_dirs").with_fuzz().build();
+    project
+        .cargo_fuzz()
+        .arg("add")
+        .arg("build_sanitizer_dirs_a")
+        .assert()
+        .success();
+
+    project.cargo_fuzz().arg("build").assert().success();
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--sanitizer=none")
+        .assert()
+        .success();
+
+    // Builds without the default address sanitizer don't overwrite it.
+    let build_dir = project.fuzz_build_dir();
+    let triple = build_dir.file_name().unwrap();
+    assert!(build_dir
+        .join("release")
+        .join("build_sanitizer_dirs_a")
+        .is_file());
+    assert!(build_dir
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("libfuzzer-none")
+        .join(triple)
+        .join("release")
+        .join("build_sanitizer_dirs_a")
+        .is_file());
+
+    // Only `build` and `run` can build with every sanitizer in turn.
+    project
+        .cargo_fuzz()
+        .arg("tmin")
+        .arg("--sanitizer=all")
+        .arg("build_sanitizer_dirs_a")
+        .arg("input")
+        .assert()
+        .stderr(predicate::str::contains(
+            "`cargo fuzz tmin` doesn't support `--sanitizer all`",
+        ))
+        .failure();
+}
+
+#[test]
+fn run_coverage_crates() {
+    let project = project("run_coverage_crates")
+        .with_fuzz()
+        .fuzz_target(
+            "cov",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    run_coverage_crates::parse(data);
+                });
+            "#,
+        )
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                #[inline(never)]
+                pub fn parse(data: &[u8]) -> usize {
+                    data.iter().filter(|&&b| b == b'{' || b == b'}').count()
+                }
+            "#,
+        )
+        .build();
+
+    // The number of coverage counters that libFuzzer finds in the binary.
+    let counters = |args: &[&str]| -> u64 {
+        let output = project
+            .cargo_fuzz()
+            .arg("run")
+            .args(args)
+            .arg("cov")
+            .arg("--")
+            .arg("-runs=1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let line = stderr
+            .lines()
+            .find(|line| line.contains(" inline 8-bit counters"))
+            .unwrap();
+        let before = &line[..line.find(" inline 8-bit counters").unwrap()];
+        before.rsplit('(').next().unwrap().trim().parse().unwrap()
+    };
+
+    let all = counters(&[]);
+    let only_lib = counters(&["--coverage-crates=run-coverage-crates"]);
+    assert!(0 < only_lib && only_lib < all, "{} of {}", only_lib, all);
+
+    // Builds that instrument different crates don't overwrite each other.
+    let build_dir = project.fuzz_build_dir();
+    let configs = build_dir.parent().unwrap().parent().unwrap();
+    assert!(configs.join("libfuzzer-address").is_dir());
+    assert!(fs::read_dir(configs).unwrap().any(|dir| dir
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .starts_with("libfuzzer-address-crates")));
+}
+
+#[test]
+fn blockers() {
+    let project = project("blockers")
+        .with_fuzz()
+        .fuzz_target(
+            "parse",
+            r#"
+                #![no_main]
+                use libfuzzer_sys::fuzz_target;
+
+                fuzz_target!(|data: &[u8]| {
+                    blockers::parse(data);
+                });
+            "#,
+        )
+        .file(
+            Path::new("src").join("lib.rs"),
+            r#"
+                #[inline(never)]
+                pub fn parse(data: &[u8]) -> bool {
+                    if data.len() < 8 {
+                        return false;
+                    }
+                    let magic = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+                    if magic != 0xdeadbeef {
+                        return false;
+                    }
+                    data[4] == b'!'
+                }
+            "#,
+        )
+        .file(
+            Path::new("fuzz").join("corpus").join("parse").join("a"),
+            "almost dead beef",
+        )
+        .file(
+            Path::new("fuzz").join("corpus").join("parse").join("b"),
+            "something else",
+        )
+        .build();
+
+    project
+        .cargo_fuzz()
+        .arg("blockers")
+        .arg("parse")
+        .assert()
+        .stdout(
+            predicate::str::contains("that the 2 input(s) of `parse` never get past:")
+                .and(predicate::str::contains("src/lib.rs:"))
+                .and(predicate::str::contains("the constant 0xdeadbeef"))
+                .and(predicate::str::contains(
+                    r#"dictionary: "\xef\xbe\xad\xde" (little-endian)"#,
+                )),
+        )
+        .success();
+}
+
+#[test]
+fn clean() {
+    let project = project("clean").with_fuzz().build();
+    // A target directory of the project's own, so that cleaning it doesn't
+    // pull builds out from under the other tests.
+    let target = project.root().join("target");
+    let triple = target.join("x86_64-unknown-linux-gnu");
+    for dir in [
+        "libfuzzer-address",
+        "libfuzzer-none",
+        "libfuzzer-none-coverage",
+        "not-a-build",
+    ] {
+        fs::create_dir_all(triple.join(dir).join("release")).unwrap();
+        fs::write(triple.join(dir).join("release").join("a"), "a").unwrap();
+    }
+
+    project
+        .cargo_fuzz()
+        .env("CARGO_TARGET_DIR", &target)
+        .arg("clean")
+        .arg("--config")
+        .arg("*-none")
+        .assert()
+        .stderr(
+            predicate::str::contains("libfuzzer-none (1 B)")
+                .and(predicate::str::contains("Removed 1 build(s), freeing 1 B")),
+        )
+        .success();
+    assert!(!triple.join("libfuzzer-none").exists());
+    assert!(triple.join("libfuzzer-none-coverage").is_dir());
+    assert!(triple.join("libfuzzer-address").is_dir());
+
+    // Without `--config`, every build goes, but nothing else does.
+    project
+        .cargo_fuzz()
+        .env("CARGO_TARGET_DIR", &target)
+        .arg("clean")
+        .assert()
+        .stderr(predicate::str::contains("Removed 2 build(s)"))
+        .success();
+    assert!(!triple.join("libfuzzer-address").exists());
+    assert!(!triple.join("libfuzzer-none-coverage").exists());
+    assert!(triple.join("not-a-build").is_dir());
+
+    project
+        .cargo_fuzz()
+        .env("CARGO_TARGET_DIR", &target)
+        .arg("clean")
+        .assert()
+        .stderr(predicate::str::contains("Nothing to clean"))
+        .success();
+}
+
 #[test]
 fn build_dev() {
     let project = project("build_dev").with_fuzz().build();
@@ -759,3 +7742,43 @@ fn build_dev() {
     assert!(a_bin.is_file());
     assert!(b_bin.is_file());
 }
+
+#[test]
+fn build_profile() {
+    let project = project("build_profile").with_fuzz().build();
+    project.cargo_fuzz().arg("add").arg("a").assert().success();
+    let mut manifest = fs::read_to_string(project.fuzz_cargo_toml()).unwrap();
+    manifest += "\n[profile.fast]\ninherits = \"release\"\nopt-level = 1\n";
+    fs::write(project.fuzz_cargo_toml(), manifest).unwrap();
+
+    project
+        .cargo_fuzz()
+        .arg("run")
+        .arg("--profile")
+        .arg("fast")
+        .arg("a")
+        .arg("--")
+        .arg("-runs=1")
+        .assert()
+        .success();
+    assert!(project.fuzz_build_dir().join("fast").join("a").is_file());
+
+    // `--profile dev` is the same as `--dev`.
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--profile")
+        .arg("dev")
+        .assert()
+        .success();
+    assert!(project.fuzz_build_dir().join("debug").join("a").is_file());
+
+    project
+        .cargo_fuzz()
+        .arg("build")
+        .arg("--profile")
+        .arg("fast")
+        .arg("--dev")
+        .assert()
+        .failure();
+}
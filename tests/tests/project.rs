@@ -21,7 +21,7 @@ pub fn next_root() -> PathBuf {
         static TEST_ID: usize = NEXT_ID.fetch_add(1, SeqCst);
     }
     let id = TEST_ID.with(|n| *n);
-    target_tests().join(&format!("t{}", id))
+    target_tests().join(format!("t{}", id))
 }
 
 pub fn project(name: &str) -> ProjectBuilder {
@@ -91,7 +91,6 @@ impl ProjectBuilder {
         let path = self.project.fuzz_target_path(name);
 
         let mut fuzz_cargo_toml = fs::OpenOptions::new()
-            .write(true)
             .append(true)
             .open(self.project.fuzz_dir().join("Cargo.toml"))
             .unwrap();
@@ -183,21 +182,23 @@ impl Project {
     ///
     /// This will panic if no fuzz targets have been built yet.
     pub fn fuzz_build_dir(&self) -> PathBuf {
-        // Because we pass an explicit `--target` to builds, its as if we were
-        // cross-compiling even when we technically aren't, and the artifacts
-        // end up in `target/<triple>/*`.
-        target_tests()
+        // cargo-fuzz builds each configuration into a target directory of its
+        // own, `target/<triple>/<engine>-<sanitizer>`, and because we pass an
+        // explicit `--target` to builds, its as if we were cross-compiling
+        // even when we technically aren't, and the artifacts end up in
+        // `<that directory>/<triple>/*`.
+        let triple = target_tests()
             .join("target")
             .read_dir()
             .expect("should get directory entries for tests' target directory")
             .map(|e| {
                 e.expect("should read an entry from the tests' target directory OK")
                     .path()
-                    .to_owned()
             })
-            .filter(|d| d.is_dir() && !d.ends_with("debug") && !d.ends_with("release"))
-            .next()
-            .unwrap()
+            .find(|d| d.join("libfuzzer-address").is_dir())
+            .unwrap();
+        let name = triple.file_name().unwrap().to_owned();
+        triple.join("libfuzzer-address").join(name)
     }
 
     pub fn fuzz_dir(&self) -> PathBuf {